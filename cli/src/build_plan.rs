@@ -0,0 +1,236 @@
+//! Ordering and running the builds `syla dev build-changed` picked.
+//!
+//! `cargo metadata` tells which services pull in another service's crates
+//! through path dependencies. Those services are rebuilt along with the
+//! one they depend on, after it, and everything else builds side by side
+//! up to the job limit.
+
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+use crate::changes::{self, ChangedService};
+use crate::commands::dev;
+use crate::config::{BuildProfile, Config, RepositoryConfig};
+use crate::runtime::Runtime;
+
+/// Log lines shown when a build running alongside others fails
+const FAILURE_TAIL: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct PlannedBuild {
+    pub service: ChangedService,
+    /// Planned builds that must finish first
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Built,
+    Failed,
+    /// A build it waits for failed
+    Skipped,
+}
+
+#[derive(Debug)]
+pub struct BuildResult {
+    pub name: String,
+    pub outcome: Outcome,
+    pub duration: Duration,
+}
+
+/// Path dependencies of each cargo service among `repos`, canonicalized
+fn path_dependencies(config: &Config, repos: &[(String, &RepositoryConfig)]) -> BTreeMap<String, (PathBuf, Vec<PathBuf>)> {
+    let mut dependencies = BTreeMap::new();
+    for (name, repo) in repos {
+        let dir = config.workspace_root.join(&repo.path);
+        if Runtime::of(repo).build_command(repo, &dir).is_some() || !dir.join("Cargo.toml").exists() {
+            continue;
+        }
+        let dir = dir.canonicalize().unwrap_or(dir);
+        let Some(metadata) = changes::cargo_metadata(config, &dir) else {
+            continue;
+        };
+        let paths: Vec<PathBuf> = metadata["packages"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|package| package["dependencies"].as_array().into_iter().flatten())
+            .filter_map(|dependency| dependency["path"].as_str().map(PathBuf::from))
+            .filter(|path| !path.starts_with(&dir))
+            .collect();
+        dependencies.insert(name.clone(), (dir, paths));
+    }
+    dependencies
+}
+
+/// Adds the services that depend on `targets` and orders everything so
+/// dependencies build first. `repos` are the services that may be added.
+pub fn plan(config: &Config, targets: Vec<ChangedService>, repos: &[(String, &RepositoryConfig)]) -> Vec<PlannedBuild> {
+    let dependencies = path_dependencies(config, repos);
+    // Service whose directory holds a path
+    let owner = |path: &Path| -> Option<&String> {
+        dependencies.iter().find(|(_, (dir, _))| path.starts_with(dir)).map(|(name, _)| name)
+    };
+    let uses: BTreeMap<&String, BTreeSet<&String>> = dependencies
+        .iter()
+        .map(|(name, (_, paths))| (name, paths.iter().filter_map(|path| owner(path)).filter(|o| *o != name).collect()))
+        .collect();
+
+    let mut planned: BTreeMap<String, ChangedService> =
+        targets.into_iter().map(|service| (service.name.clone(), service)).collect();
+    loop {
+        let mut added = false;
+        for (name, used) in &uses {
+            if planned.contains_key(*name) {
+                continue;
+            }
+            let Some(changed) = used.iter().find(|used| planned.contains_key(**used)) else {
+                continue;
+            };
+            let Some((_, repo)) = repos.iter().find(|(repo_name, _)| repo_name == *name) else {
+                continue;
+            };
+            let path = config.workspace_root.join(&repo.path);
+            let service = ChangedService {
+                name: (*name).clone(),
+                reason: format!("depends on {}", changed),
+                built: None,
+                build: Runtime::of(repo).build_command(repo, &path),
+                path,
+            };
+            planned.insert((*name).clone(), service);
+            added = true;
+        }
+        if !added {
+            break;
+        }
+    }
+
+    // Dependencies first; a cycle is left to build in name order
+    let mut order = Vec::new();
+    let mut remaining: Vec<String> = planned.keys().cloned().collect();
+    while !remaining.is_empty() {
+        let ready: Vec<String> = remaining
+            .iter()
+            .filter(|name| {
+                uses.get(name).is_none_or(|used| used.iter().all(|u| !remaining.contains(u) || *u == *name))
+            })
+            .cloned()
+            .collect();
+        let ready = if ready.is_empty() { remaining.clone() } else { ready };
+        remaining.retain(|name| !ready.contains(name));
+        order.extend(ready);
+    }
+
+    let mut placed = BTreeSet::new();
+    order
+        .into_iter()
+        .map(|name| {
+            let after = uses
+                .get(&name)
+                .into_iter()
+                .flatten()
+                .filter(|used| placed.contains(**used))
+                .map(|used| (*used).clone())
+                .collect();
+            placed.insert(name.clone());
+            let service = planned.remove(&name).expect("planned service");
+            PlannedBuild { service, after }
+        })
+        .collect()
+}
+
+/// Runs the plan with up to `jobs` builds at once. A lone build shows its
+/// output; side-by-side builds write theirs to `.logs/build-<service>.log`.
+pub async fn run(config: &Config, plan: Vec<PlannedBuild>, profile: BuildProfile, jobs: usize) -> Vec<BuildResult> {
+    let config = Arc::new(config.clone());
+    let jobs = jobs.max(1);
+    let parallel = jobs > 1 && plan.len() > 1;
+
+    let mut pending = plan;
+    let mut outcomes: HashMap<String, Outcome> = HashMap::new();
+    let mut results = Vec::new();
+    let mut running = JoinSet::new();
+    loop {
+        // Skip builds whose dependencies failed, start the ones that are ready
+        let mut index = 0;
+        while index < pending.len() {
+            let build = &pending[index];
+            if build.after.iter().any(|after| matches!(outcomes.get(after), Some(Outcome::Failed | Outcome::Skipped))) {
+                let build = pending.remove(index);
+                println!("{} {} skipped: a build it depends on failed", "[!]".yellow(), build.service.name);
+                outcomes.insert(build.service.name.clone(), Outcome::Skipped);
+                results.push(BuildResult { name: build.service.name, outcome: Outcome::Skipped, duration: Duration::ZERO });
+                continue;
+            }
+            if running.len() >= jobs || !build.after.iter().all(|after| outcomes.contains_key(after)) {
+                index += 1;
+                continue;
+            }
+
+            let build = pending.remove(index);
+            let config = config.clone();
+            let name = build.service.name.clone();
+            if parallel {
+                say!("{} Building {}...", "->".dimmed(), name);
+            } else {
+                say!("\nBuilding {}...", name);
+            }
+            running.spawn_blocking(move || {
+                let started = Instant::now();
+                let log = log_path(&config, &build.service.name);
+                let built = if parallel {
+                    dev::build_service_logged(&config, &build.service, profile, &log)
+                } else {
+                    dev::build_service(&config, &build.service, profile)
+                };
+                let outcome = match built {
+                    Ok(true) => Outcome::Built,
+                    Ok(false) => Outcome::Failed,
+                    Err(e) => {
+                        println!("{} {}: {:#}", "[X]".red(), build.service.name, e);
+                        Outcome::Failed
+                    }
+                };
+                (name, outcome, started.elapsed(), log)
+            });
+        }
+
+        let Some(finished) = running.join_next().await else {
+            break;
+        };
+        let Ok((name, outcome, duration, log)) = finished else {
+            continue;
+        };
+        if parallel {
+            match outcome {
+                Outcome::Built => say!("{} {} built in {}", "[OK]".green(), name, seconds(duration)),
+                _ => print_failure(&name, &log),
+            }
+        }
+        outcomes.insert(name.clone(), outcome);
+        results.push(BuildResult { name, outcome, duration });
+    }
+    results
+}
+
+fn log_path(config: &Config, name: &str) -> PathBuf {
+    config.workspace_root.join(format!(".logs/build-{}.log", name))
+}
+
+fn print_failure(name: &str, log: &Path) {
+    println!("{} {} failed; see {}", "[X]".red(), name, log.display());
+    let content = std::fs::read_to_string(log).unwrap_or_default();
+    let lines: Vec<&str> = content.lines().collect();
+    for line in &lines[lines.len().saturating_sub(FAILURE_TAIL)..] {
+        println!("    {}", line.dimmed());
+    }
+}
+
+pub fn seconds(duration: Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
@@ -0,0 +1,386 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::commands::dev;
+use crate::config::{BuildProfile, Config, RepositoryConfig};
+use crate::runtime::Runtime;
+
+/// A service whose sources changed since it was last built
+#[derive(Debug, Clone)]
+pub struct ChangedService {
+    pub name: String,
+    pub path: PathBuf,
+    pub reason: String,
+    /// When the existing build was made; `None` when there is none or the
+    /// comparison was against a git ref
+    pub built: Option<SystemTime>,
+    /// Shell command that builds it; `None` for cargo
+    pub build: Option<String>,
+}
+
+/// What a service's build reads and produces, from `cargo metadata` for
+/// Rust services
+struct BuildInputs {
+    /// The repository plus path dependencies that live outside it
+    dirs: Vec<PathBuf>,
+    /// Only these files count as inputs when not empty
+    files: &'static [&'static str],
+    binary: PathBuf,
+}
+
+/// Content hashes of each service's build inputs as of its last
+/// successful build, kept in `.syla/build-cache.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BuildCache {
+    /// By service, then build profile
+    #[serde(default)]
+    services: BTreeMap<String, BTreeMap<String, Baseline>>,
+}
+
+/// A service's build inputs when it was built
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    pub built_at: DateTime<Utc>,
+    /// SHA-1 of each input's contents, by path relative to the repository
+    files: BTreeMap<String, String>,
+}
+
+impl BuildCache {
+    fn path(workspace_root: &Path) -> PathBuf {
+        workspace_root.join(".syla/build-cache.json")
+    }
+
+    pub fn load(workspace_root: &Path) -> Self {
+        std::fs::read(Self::path(workspace_root))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, workspace_root: &Path) -> Result<()> {
+        let path = Self::path(workspace_root);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn baseline(&self, name: &str, profile: BuildProfile) -> Option<&Baseline> {
+        self.services.get(name)?.get(profile.name())
+    }
+
+    /// Stores the inputs `name` was just built from as its baseline, with
+    /// files the build itself created, like a new `Cargo.lock`, as they
+    /// are now
+    pub fn record(config: &Config, name: &str, profile: BuildProfile, mut baseline: Baseline) -> Result<()> {
+        let repo = &config.manifest.repositories[name];
+        if let Some(after) = snapshot(config, repo, profile) {
+            for (file, hash) in after.files {
+                baseline.files.entry(file).or_insert(hash);
+            }
+        }
+        let mut cache = Self::load(&config.workspace_root);
+        cache.services.entry(name.to_string()).or_default().insert(profile.name().to_string(), baseline);
+        cache.save(&config.workspace_root)
+    }
+}
+
+/// Hashes of a service's build inputs as they are now, taken before it
+/// builds so edits made during the build count as changes next time.
+/// `None` when there is nothing to compare its build against.
+pub fn snapshot(config: &Config, repo: &RepositoryConfig, profile: BuildProfile) -> Option<Baseline> {
+    let dir = config.workspace_root.join(&repo.path);
+    let (inputs, _) = inputs(config, repo, &dir, profile)?;
+    Some(Baseline { built_at: Utc::now(), files: hash_inputs(&inputs) })
+}
+
+/// Services that need rebuilding, sorted by name. With `since`,
+/// anything that differs from that git ref (committed or not) counts.
+/// Otherwise inputs that differ from those of the service's last `profile`
+/// build, when `build-changed` recorded it, or else files modified after
+/// its binary was built.
+pub fn detect(config: &Config, profile: BuildProfile, since: Option<&str>) -> Result<Vec<ChangedService>> {
+    let mut repos = config.get_all_repositories();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut changed = Vec::new();
+    for (name, repo) in repos {
+        changed.extend(detect_one(config, &name, repo, profile, since)?);
+    }
+    Ok(changed)
+}
+
+/// Whether one service needs rebuilding, as for [`detect`]. `None` also
+/// when there is nothing to compare its build against.
+pub fn detect_one(
+    config: &Config,
+    name: &str,
+    repo: &RepositoryConfig,
+    profile: BuildProfile,
+    since: Option<&str>,
+) -> Result<Option<ChangedService>> {
+    let dir = config.workspace_root.join(&repo.path);
+    let build = Runtime::of(repo).build_command(repo, &dir);
+    let Some((inputs, missing)) = inputs(config, repo, &dir, profile) else {
+        return Ok(None);
+    };
+    let change = match since {
+        Some(reference) => changed_since(&inputs, reference)
+            .with_context(|| format!("Failed to diff {} against {}", name, reference))?,
+        None => match BuildCache::load(&config.workspace_root).baseline(name, profile) {
+            Some(baseline) if inputs.binary.exists() => changed_from(&inputs, baseline),
+            _ => modified_after_build(&inputs, &missing),
+        },
+    };
+    let built = since.is_none().then(|| std::fs::metadata(&inputs.binary).and_then(|m| m.modified()).ok()).flatten();
+    Ok(change.map(|reason| ChangedService {
+        name: name.to_string(),
+        path: dir,
+        reason,
+        built,
+        build,
+    }))
+}
+
+impl ChangedService {
+    /// How far behind its sources the build is, e.g. `built 3 days ago, 14
+    /// commits behind (src/main.rs modified)`; just the reason when there
+    /// is no build
+    pub fn staleness(&self) -> String {
+        let Some(built) = self.built else {
+            return self.reason.clone();
+        };
+        let mut summary = format!("built {}", ago(built));
+        match commits_since(&self.path, built) {
+            Some(0) | None => {}
+            Some(1) => summary.push_str(", 1 commit behind"),
+            Some(commits) => summary.push_str(&format!(", {} commits behind", commits)),
+        }
+        format!("{} ({})", summary, self.reason)
+    }
+}
+
+/// Commits HEAD moved by since `time`, going by the reflog so pulled
+/// commits count too; `None` outside a git repository
+fn commits_since(dir: &Path, time: SystemTime) -> Option<usize> {
+    let seconds = time.duration_since(SystemTime::UNIX_EPOCH).ok()?.as_secs();
+    let range = format!("HEAD@{{@{}}}..HEAD", seconds);
+    let since = format!("--since=@{}", seconds);
+    let lines = git_lines(dir, &["rev-list", "--count", &range])
+        .or_else(|_| git_lines(dir, &["rev-list", "--count", &since, "HEAD"]))
+        .ok()?;
+    lines.first()?.parse().ok()
+}
+
+fn ago(time: SystemTime) -> String {
+    let seconds = time.elapsed().map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+    let (count, unit) = match seconds {
+        0..60 => return "just now".to_string(),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// `cargo metadata` for the workspace's own packages, `None` when cargo
+/// can't read it
+pub(crate) fn cargo_metadata(config: &Config, dir: &Path) -> Option<serde_json::Value> {
+    config
+        .cargo()
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| serde_json::from_slice(&output.stdout).ok())
+}
+
+fn build_inputs(config: &Config, dir: &Path, default_binary: PathBuf) -> BuildInputs {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let Some(metadata) = cargo_metadata(config, &dir) else {
+        return BuildInputs {
+            dirs: vec![dir],
+            files: &[],
+            binary: default_binary,
+        };
+    };
+
+    let mut dirs = vec![dir.clone()];
+    let mut binary_name = None;
+    for package in metadata["packages"].as_array().into_iter().flatten() {
+        for dependency in package["dependencies"].as_array().into_iter().flatten() {
+            if let Some(path) = dependency["path"].as_str().map(PathBuf::from) {
+                if !path.starts_with(&dir) && !dirs.contains(&path) {
+                    dirs.push(path);
+                }
+            }
+        }
+        if binary_name.is_none() {
+            binary_name = package["targets"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|target| target["kind"].as_array().is_some_and(|kinds| kinds.iter().any(|k| k == "bin")))
+                .and_then(|target| target["name"].as_str())
+                .map(String::from);
+        }
+    }
+
+    // Honours the shared target directory and any [build] target-dir
+    let binary = match (metadata["target_directory"].as_str(), binary_name) {
+        (Some(target_dir), Some(binary_name)) => {
+            let profile_dir = default_binary.parent().and_then(Path::file_name).unwrap_or_default();
+            Path::new(target_dir).join(profile_dir).join(binary_name)
+        }
+        _ => default_binary,
+    };
+    BuildInputs { dirs, files: &[], binary }
+}
+
+/// What a service's build reads and produces, and how to describe a
+/// missing build; `None` for a custom build, with nothing to compare against
+fn inputs(config: &Config, repo: &RepositoryConfig, dir: &Path, profile: BuildProfile) -> Option<(BuildInputs, String)> {
+    let runtime = Runtime::of(repo);
+    match (runtime.build_command(repo, dir), runtime.build_output(repo, dir)) {
+        (None, _) if dir.join("Cargo.toml").exists() => Some((
+            build_inputs(config, dir, dev::service_binary(config, repo, profile)),
+            format!("no {} build", profile.name()),
+        )),
+        (Some(_), Some(output)) => Some((
+            BuildInputs {
+                dirs: vec![dir.to_path_buf()],
+                files: runtime.build_inputs(),
+                binary: output,
+            },
+            "not built".to_string(),
+        )),
+        _ => None,
+    }
+}
+
+/// SHA-1 of each input file, by its path relative to the repository, or
+/// absolute for path dependencies outside it
+fn hash_inputs(inputs: &BuildInputs) -> BTreeMap<String, String> {
+    let mut hashes = BTreeMap::new();
+    for (index, dir) in inputs.dirs.iter().enumerate() {
+        let files: Vec<PathBuf> = if inputs.files.is_empty() {
+            source_files(dir)
+        } else {
+            inputs.files.iter().map(PathBuf::from).collect()
+        };
+        for file in files {
+            let Ok(content) = std::fs::read(dir.join(&file)) else {
+                continue;
+            };
+            let key = if index == 0 { file } else { dir.join(file) };
+            hashes.insert(key.display().to_string(), format!("{:x}", Sha1::digest(&content)));
+        }
+    }
+    hashes
+}
+
+/// Inputs added, removed or edited since `baseline`; files that were only
+/// touched, or changed and changed back, don't count
+fn changed_from(inputs: &BuildInputs, baseline: &Baseline) -> Option<String> {
+    let current = hash_inputs(inputs);
+    let changed: Vec<&String> = current
+        .iter()
+        .filter(|(file, hash)| baseline.files.get(*file) != Some(*hash))
+        .map(|(file, _)| file)
+        .chain(baseline.files.keys().filter(|file| !current.contains_key(*file)))
+        .collect();
+    match changed.as_slice() {
+        [] => None,
+        [file] if current.contains_key(*file) => Some(format!("{} modified", file)),
+        [file] => Some(format!("{} removed", file)),
+        files => Some(format!("{} files changed since the last build", files.len())),
+    }
+}
+
+fn changed_since(inputs: &BuildInputs, reference: &str) -> Result<Option<String>> {
+    let mut files = 0;
+    let counts = |file: &String| inputs.files.is_empty() || inputs.files.contains(&file.as_str());
+    for dir in &inputs.dirs {
+        files += git_lines(dir, &["diff", "--name-only", "--relative", reference, "--", "."])?.iter().filter(|f| counts(f)).count();
+        files += git_lines(dir, &["ls-files", "--others", "--exclude-standard"])?.iter().filter(|f| counts(f)).count();
+    }
+    if files == 0 {
+        return Ok(None);
+    }
+    let plural = if files == 1 { "" } else { "s" };
+    Ok(Some(format!("{} file{} changed since {}", files, plural, reference)))
+}
+
+fn modified_after_build(inputs: &BuildInputs, missing: &str) -> Option<String> {
+    let Ok(built) = std::fs::metadata(&inputs.binary).and_then(|m| m.modified()) else {
+        return Some(missing.to_string());
+    };
+
+    let mut newest: Option<(SystemTime, String)> = None;
+    for dir in &inputs.dirs {
+        let files: Vec<PathBuf> = if inputs.files.is_empty() {
+            source_files(dir)
+        } else {
+            inputs.files.iter().map(PathBuf::from).collect()
+        };
+        for file in files {
+            let Ok(modified) = std::fs::metadata(dir.join(&file)).and_then(|m| m.modified()) else {
+                continue;
+            };
+            if modified > built && newest.as_ref().is_none_or(|(time, _)| modified > *time) {
+                newest = Some((modified, file.display().to_string()));
+            }
+        }
+    }
+    newest.map(|(_, file)| format!("{} modified", file))
+}
+
+/// Source files under `dir` modified after `since`, relative to it
+pub(crate) fn modified_since(dir: &Path, since: SystemTime) -> Vec<PathBuf> {
+    source_files(dir)
+        .into_iter()
+        .filter(|file| std::fs::metadata(dir.join(file)).and_then(|m| m.modified()).is_ok_and(|modified| modified > since))
+        .collect()
+}
+
+/// Files under `dir` that aren't ignored, relative to it. Uses git when
+/// the directory is in a repository and walks it otherwise.
+fn source_files(dir: &Path) -> Vec<PathBuf> {
+    if let Ok(files) = git_lines(dir, &["ls-files", "--cached", "--others", "--exclude-standard"]) {
+        return files.into_iter().map(PathBuf::from).collect();
+    }
+
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0 || !(name == "target" || name.starts_with('.'))
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.path().strip_prefix(dir).ok().map(Path::to_path_buf))
+        .collect()
+}
+
+fn git_lines(dir: &Path, args: &[&str]) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to execute git")?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
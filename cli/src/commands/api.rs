@@ -0,0 +1,756 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use colored::Colorize;
+use serde_json::{Map, Value};
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::commands::dev::host_port;
+use crate::commands::proto::{Fields, Schema};
+use crate::config::Config;
+use crate::names;
+use crate::ui;
+use crate::ApiCommands;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub async fn run(command: ApiCommands, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    match command {
+        ApiCommands::Call {
+            service,
+            target,
+            data,
+            method,
+            headers,
+            port,
+        } => {
+            let (name, repo) = config.find_repository(&service)?;
+            let port = match port {
+                Some(port) => port.to_string(),
+                None => repo
+                    .ports
+                    .first()
+                    .and_then(|p| host_port(p))
+                    .map(String::from)
+                    .with_context(|| format!("{} has no ports in the manifest; pass --port", name))?,
+            };
+            let body = data.as_deref().map(read_data).transpose()?;
+            let headers = headers.iter().map(|h| parse_header(h)).collect::<Result<Vec<_>>>()?;
+            if target.starts_with('/') {
+                call_http(&name, &port, &target, method.as_deref(), body, &headers).await
+            } else {
+                if method.is_some() {
+                    anyhow::bail!("--method only applies to HTTP paths; gRPC calls are always POSTs");
+                }
+                call_grpc(&config, &name, &port, &target, body, &headers).await
+            }
+        }
+        ApiCommands::List { service } => list(&config, &service),
+    }
+}
+
+/// `--data` as given, or read from a file with `@path`, or stdin with `@-`
+fn read_data(data: &str) -> Result<String> {
+    match data.strip_prefix('@') {
+        Some("-") => {
+            let mut body = String::new();
+            std::io::stdin().read_to_string(&mut body).context("Failed to read the request from stdin")?;
+            Ok(body)
+        }
+        Some(path) => std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path)),
+        None => Ok(data.to_string()),
+    }
+}
+
+fn parse_header(header: &str) -> Result<(String, String)> {
+    let (name, value) = header
+        .split_once(':')
+        .with_context(|| format!("Invalid header '{}'; expected `name: value`", header))?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+/// Pretty-prints JSON bodies and passes anything else through
+fn print_body(body: &str) {
+    match serde_json::from_str::<Value>(body) {
+        Ok(json) => println!("{}", serde_json::to_string_pretty(&json).unwrap_or_else(|_| body.to_string())),
+        Err(_) if body.is_empty() => {}
+        Err(_) => println!("{}", body.trim_end()),
+    }
+}
+
+/// Status lines go to stderr so the response can be piped on
+fn report(ok: bool, message: &str, elapsed: Duration) {
+    if ui::is_quiet() {
+        return;
+    }
+    let mark = if ok { "[OK]".green() } else { "[X]".red() };
+    eprintln!("{} {} {}", mark, message, format!("({}ms)", elapsed.as_millis()).dimmed());
+}
+
+async fn call_http(
+    name: &str,
+    port: &str,
+    path: &str,
+    method: Option<&str>,
+    body: Option<String>,
+    headers: &[(String, String)],
+) -> Result<()> {
+    let method = match method {
+        Some(method) => reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+            .with_context(|| format!("Invalid HTTP method '{}'", method))?,
+        None if body.is_some() => reqwest::Method::POST,
+        None => reqwest::Method::GET,
+    };
+    let url = format!("http://localhost:{}{}", port, path);
+    let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+    let mut request = client.request(method.clone(), &url);
+    for (header, value) in headers {
+        request = request.header(header, value);
+    }
+    if let Some(body) = body {
+        if serde_json::from_str::<Value>(&body).is_ok() && !headers.iter().any(|(h, _)| h.eq_ignore_ascii_case("content-type")) {
+            request = request.header("content-type", "application/json");
+        }
+        request = request.body(body);
+    }
+
+    let sent = Instant::now();
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {} at {}; is it running?", name, url))?;
+    let status = response.status();
+    let text = response.text().await.context("Failed to read the response")?;
+    report(status.is_success(), &format!("{} {} -> {}", method, url, status), sent.elapsed());
+    print_body(&text);
+    if !status.is_success() {
+        anyhow::bail!("{} answered {}", name, status);
+    }
+    Ok(())
+}
+
+async fn call_grpc(
+    config: &Config,
+    name: &str,
+    port: &str,
+    method: &str,
+    body: Option<String>,
+    headers: &[(String, String)],
+) -> Result<()> {
+    let schema = Schema::load(config, name)?;
+    if schema.rpcs.is_empty() {
+        anyhow::bail!(
+            "{} defines no gRPC services in its protos; call an HTTP path such as /health instead",
+            name
+        );
+    }
+    // `names` matches on dot-separated segments, so `Service/Method` is
+    // looked up as `Service.Method`
+    let dotted: Vec<String> = schema.rpcs.keys().map(|rpc| rpc.replace('/', ".")).collect();
+    let found = names::resolve("method", method, dotted.iter().map(String::as_str))?;
+    let (service, method) = found.rsplit_once('.').unwrap_or(("", &found));
+    let rpc = format!("{}/{}", service, method);
+    let (request_type, response_type) = &schema.rpcs[&rpc];
+    if request_type.starts_with("stream ") {
+        anyhow::bail!("{} takes a client stream, which `syla api call` can't send", rpc);
+    }
+    let response_type = response_type.strip_prefix("stream ").unwrap_or(response_type);
+
+    // Request and response types are resolved from the service's package
+    let package = service.rsplit_once('.').map_or("", |(package, _)| package);
+    let codec = Codec { schema: &schema };
+    let request_message = codec.message(package, request_type)?;
+    let response_message = codec.message(package, response_type)?;
+    let input: Value = match body {
+        Some(body) => serde_json::from_str(&body).context("The request is not valid JSON")?,
+        None => Value::Object(Map::new()),
+    };
+    let mut payload = Vec::new();
+    codec.encode(&request_message, &input, &mut payload)?;
+    // Uncompressed flag and length prefix, then the message
+    let mut frame = vec![0u8];
+    frame.extend((payload.len() as u32).to_be_bytes());
+    frame.extend(payload);
+
+    let url = format!("http://localhost:{}/{}", port, rpc);
+    let client = reqwest::Client::builder().http2_prior_knowledge().timeout(REQUEST_TIMEOUT).build()?;
+    let mut request = client
+        .post(&url)
+        .header("content-type", "application/grpc")
+        .header("te", "trailers");
+    for (header, value) in headers {
+        request = request.header(header.to_lowercase(), value);
+    }
+    let sent = Instant::now();
+    let response = request
+        .body(frame)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {} at localhost:{}; is it running and serving gRPC?", name, port))?;
+    if !response.status().is_success() {
+        anyhow::bail!("{} answered HTTP {} instead of gRPC", name, response.status());
+    }
+    // Failures without a response message come back as headers; a status
+    // sent as a trailer after messages isn't visible here
+    let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok()).map(String::from);
+    let code = header("grpc-status").and_then(|code| code.parse::<u32>().ok()).unwrap_or(0);
+    let message = header("grpc-message").map(|m| percent_decode(&m)).unwrap_or_default();
+    let bytes = response.bytes().await.context("Failed to read the response")?;
+
+    if code != 0 {
+        report(false, &format!("{} -> {}", rpc, status_name(code)), sent.elapsed());
+        anyhow::bail!("{} failed with {}: {}", rpc, status_name(code), message);
+    }
+    report(true, &format!("{} -> OK", rpc), sent.elapsed());
+    let mut rest = &bytes[..];
+    while !rest.is_empty() {
+        if rest.len() < 5 {
+            anyhow::bail!("Truncated gRPC response from {}", name);
+        }
+        let compressed = rest[0] != 0;
+        let length = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        let message = rest.get(5..5 + length).with_context(|| format!("Truncated gRPC response from {}", name))?;
+        if compressed {
+            anyhow::bail!("{} sent a compressed response, which `syla api call` can't read", name);
+        }
+        let value = codec.decode(&response_message, message)?;
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        rest = &rest[5 + length..];
+    }
+    Ok(())
+}
+
+/// The gRPC methods in a service's protos, by service
+fn list(config: &Config, service: &str) -> Result<()> {
+    let (name, repo) = config.find_repository(service)?;
+    let schema = Schema::load(config, &name)?;
+    let port = repo.ports.first().and_then(|p| host_port(p)).unwrap_or("none");
+    println!("{} {}", name.bold(), format!("(port {})", port).dimmed());
+    if schema.rpcs.is_empty() {
+        println!("  No gRPC services in its protos; call it with an HTTP path such as /health");
+        return Ok(());
+    }
+    let mut current = "";
+    for (rpc, (request, response)) in &schema.rpcs {
+        let (service, method) = rpc.split_once('/').unwrap_or(("", rpc));
+        if service != current {
+            println!("  {}", service.cyan());
+            current = service;
+        }
+        println!("    {}({}) returns {}", method.bold(), request, response);
+    }
+    Ok(())
+}
+
+fn status_name(code: u32) -> &'static str {
+    match code {
+        0 => "OK",
+        1 => "CANCELLED",
+        2 => "UNKNOWN",
+        3 => "INVALID_ARGUMENT",
+        4 => "DEADLINE_EXCEEDED",
+        5 => "NOT_FOUND",
+        6 => "ALREADY_EXISTS",
+        7 => "PERMISSION_DENIED",
+        8 => "RESOURCE_EXHAUSTED",
+        9 => "FAILED_PRECONDITION",
+        10 => "ABORTED",
+        11 => "OUT_OF_RANGE",
+        12 => "UNIMPLEMENTED",
+        13 => "INTERNAL",
+        14 => "UNAVAILABLE",
+        15 => "DATA_LOSS",
+        16 => "UNAUTHENTICATED",
+        _ => "UNKNOWN",
+    }
+}
+
+/// `grpc-message` is percent-encoded
+fn percent_decode(message: &str) -> String {
+    let bytes = message.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| message.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+const SCALARS: &[&str] = &[
+    "double", "float", "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32", "fixed64", "sfixed32",
+    "sfixed64", "bool", "string", "bytes",
+];
+
+/// Well-known message with no fields, usable without its proto on hand
+const EMPTY: &str = "google.protobuf.Empty";
+
+const VARINT: u8 = 0;
+const FIXED64: u8 = 1;
+const LENGTH_DELIMITED: u8 = 2;
+const FIXED32: u8 = 5;
+
+/// What a field's type name refers to
+enum Kind<'a> {
+    Scalar(&'a str),
+    Message(String),
+    Enum(String),
+}
+
+/// How a field holds its values
+enum Label<'a> {
+    Single(&'a str),
+    Repeated(&'a str),
+    Map(&'a str, &'a str),
+}
+
+fn label(kind: &str) -> Label<'_> {
+    if let Some((key, value)) = kind
+        .strip_prefix("map<")
+        .and_then(|inner| inner.strip_suffix('>'))
+        .and_then(|inner| inner.split_once(','))
+    {
+        return Label::Map(key.trim(), value.trim());
+    }
+    if let Some(kind) = kind.strip_prefix("repeated ") {
+        return Label::Repeated(kind);
+    }
+    Label::Single(
+        kind.strip_prefix("optional ")
+            .or_else(|| kind.strip_prefix("required "))
+            .unwrap_or(kind),
+    )
+}
+
+/// A raw value off the wire
+enum Raw<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Converts between JSON and protobuf's wire format with the message
+/// definitions of a [`Schema`], so no generated code is needed. Fields are
+/// printed under their proto names; input may use those or the
+/// lowerCamelCase JSON names.
+struct Codec<'a> {
+    schema: &'a Schema,
+}
+
+impl Codec<'_> {
+    /// Resolves `name` the way protoc does: in `scope`, then each scope
+    /// enclosing it, up to the root
+    fn resolve<'n>(&self, scope: &str, name: &'n str) -> Result<Kind<'n>> {
+        if SCALARS.contains(&name) {
+            return Ok(Kind::Scalar(name));
+        }
+        let mut candidates = Vec::new();
+        if let Some(absolute) = name.strip_prefix('.') {
+            candidates.push(absolute.to_string());
+        } else {
+            let mut scope = scope;
+            loop {
+                candidates.push(if scope.is_empty() { name.to_string() } else { format!("{}.{}", scope, name) });
+                if scope.is_empty() {
+                    break;
+                }
+                scope = scope.rsplit_once('.').map_or("", |(outer, _)| outer);
+            }
+        }
+        for candidate in candidates {
+            if self.schema.messages.contains_key(&candidate) || candidate == EMPTY {
+                return Ok(Kind::Message(candidate));
+            }
+            if self.schema.enums.contains_key(&candidate) {
+                return Ok(Kind::Enum(candidate));
+            }
+        }
+        anyhow::bail!(
+            "Type {} is not defined in the service's protos or the shared includes (proto_includes)",
+            name
+        )
+    }
+
+    fn message(&self, scope: &str, name: &str) -> Result<String> {
+        match self.resolve(scope, name)? {
+            Kind::Message(message) => Ok(message),
+            _ => anyhow::bail!("{} is not a message", name),
+        }
+    }
+
+    fn fields(&self, message: &str) -> Option<&Fields> {
+        self.schema.messages.get(message)
+    }
+
+    fn encode(&self, message: &str, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+        let object = value
+            .as_object()
+            .with_context(|| format!("Expected a JSON object for {}", message))?;
+        for (key, value) in object {
+            let field = self
+                .fields(message)
+                .and_then(|fields| fields.by_number.iter().find(|(_, (name, _))| name == key || camel_case(name) == *key));
+            let Some((number, (name, kind))) = field else {
+                anyhow::bail!("{} has no field '{}'", message, key);
+            };
+            if value.is_null() {
+                continue;
+            }
+            let encoded = match label(kind) {
+                Label::Single(kind) => self.encode_field(message, *number, kind, value, out),
+                Label::Repeated(kind) => value
+                    .as_array()
+                    .context("expected a JSON array")
+                    .and_then(|items| items.iter().try_for_each(|item| self.encode_field(message, *number, kind, item, out))),
+                Label::Map(key_kind, value_kind) => value.as_object().context("expected a JSON object").and_then(|entries| {
+                    entries.iter().try_for_each(|(key, value)| {
+                        let mut entry = Vec::new();
+                        self.encode_field(message, 1, key_kind, &Value::String(key.clone()), &mut entry)?;
+                        self.encode_field(message, 2, value_kind, value, &mut entry)?;
+                        put_tag(out, *number, LENGTH_DELIMITED);
+                        put_varint(out, entry.len() as u64);
+                        out.extend(entry);
+                        Ok(())
+                    })
+                }),
+            };
+            encoded.with_context(|| format!("Invalid {}.{}", message, name))?;
+        }
+        Ok(())
+    }
+
+    fn encode_field(&self, scope: &str, number: i64, kind: &str, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+        match self.resolve(scope, kind)? {
+            Kind::Message(message) => {
+                let mut inner = Vec::new();
+                self.encode(&message, value, &mut inner)?;
+                put_tag(out, number, LENGTH_DELIMITED);
+                put_varint(out, inner.len() as u64);
+                out.extend(inner);
+            }
+            Kind::Enum(name) => {
+                let number_value = match value.as_str() {
+                    Some(symbol) => self
+                        .schema
+                        .enums
+                        .get(&name)
+                        .and_then(|values| values.by_number.iter().find(|(_, (value, _))| value == symbol))
+                        .map(|(number, _)| *number)
+                        .with_context(|| format!("{} has no value {}", name, symbol))?,
+                    None => integer(value)?,
+                };
+                put_tag(out, number, VARINT);
+                put_varint(out, number_value as u64);
+            }
+            Kind::Scalar(scalar) => encode_scalar(number, scalar, value, out)?,
+        }
+        Ok(())
+    }
+
+    fn decode(&self, message: &str, mut bytes: &[u8]) -> Result<Value> {
+        let mut object = Map::new();
+        while !bytes.is_empty() {
+            let key = read_varint(&mut bytes)?;
+            let number = (key >> 3) as i64;
+            let raw = read_raw(&mut bytes, (key & 7) as u8)?;
+            // Fields added after our copy of the protos are skipped
+            let Some((name, kind)) = self.fields(message).and_then(|fields| fields.by_number.get(&number)) else {
+                continue;
+            };
+            match label(kind) {
+                Label::Single(kind) => {
+                    object.insert(name.clone(), self.decode_value(message, kind, raw)?);
+                }
+                Label::Repeated(kind) => {
+                    let values = match (raw, packed_wire_type(&self.resolve(message, kind)?)) {
+                        (Raw::Bytes(mut packed), Some(wire_type)) => {
+                            let mut values = Vec::new();
+                            while !packed.is_empty() {
+                                values.push(self.decode_value(message, kind, read_raw(&mut packed, wire_type)?)?);
+                            }
+                            values
+                        }
+                        (raw, _) => vec![self.decode_value(message, kind, raw)?],
+                    };
+                    if let Value::Array(list) = object.entry(name.clone()).or_insert_with(|| Value::Array(Vec::new())) {
+                        list.extend(values);
+                    }
+                }
+                Label::Map(key_kind, value_kind) => {
+                    let Raw::Bytes(mut entry) = raw else {
+                        anyhow::bail!("{}.{} is not a map entry on the wire", message, name);
+                    };
+                    let (mut key, mut value) = (Value::Null, Value::Null);
+                    while !entry.is_empty() {
+                        let tag = read_varint(&mut entry)?;
+                        let raw = read_raw(&mut entry, (tag & 7) as u8)?;
+                        match tag >> 3 {
+                            1 => key = self.decode_value(message, key_kind, raw)?,
+                            2 => value = self.decode_value(message, value_kind, raw)?,
+                            _ => {}
+                        }
+                    }
+                    let key = match key {
+                        Value::String(key) => key,
+                        other => other.to_string(),
+                    };
+                    if let Value::Object(map) = object.entry(name.clone()).or_insert_with(|| Value::Object(Map::new())) {
+                        map.insert(key, value);
+                    }
+                }
+            }
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn decode_value(&self, scope: &str, kind: &str, raw: Raw) -> Result<Value> {
+        let value = match (self.resolve(scope, kind)?, raw) {
+            (Kind::Message(message), Raw::Bytes(bytes)) => self.decode(&message, bytes)?,
+            (Kind::Enum(name), Raw::Varint(n)) => {
+                let number = n as i32 as i64;
+                match self.schema.enums.get(&name).and_then(|values| values.by_number.get(&number)) {
+                    Some((symbol, _)) => Value::String(symbol.clone()),
+                    None => Value::from(number),
+                }
+            }
+            (Kind::Scalar(scalar), raw) => match (scalar, raw) {
+                ("int32", Raw::Varint(n)) => Value::from(n as i32),
+                ("int64", Raw::Varint(n)) => Value::String((n as i64).to_string()),
+                ("uint32", Raw::Varint(n)) => Value::from(n as u32),
+                ("uint64", Raw::Varint(n)) => Value::String(n.to_string()),
+                ("sint32", Raw::Varint(n)) => Value::from(zigzag_decode(n) as i32),
+                ("sint64", Raw::Varint(n)) => Value::String(zigzag_decode(n).to_string()),
+                ("bool", Raw::Varint(n)) => Value::Bool(n != 0),
+                ("fixed64", Raw::Fixed64(n)) => Value::String(n.to_string()),
+                ("sfixed64", Raw::Fixed64(n)) => Value::String((n as i64).to_string()),
+                ("double", Raw::Fixed64(n)) => float_value(f64::from_bits(n)),
+                ("fixed32", Raw::Fixed32(n)) => Value::from(n),
+                ("sfixed32", Raw::Fixed32(n)) => Value::from(n as i32),
+                ("float", Raw::Fixed32(n)) => float_value(f32::from_bits(n) as f64),
+                ("string", Raw::Bytes(bytes)) => Value::String(String::from_utf8_lossy(bytes).into_owned()),
+                ("bytes", Raw::Bytes(bytes)) => Value::String(base64::engine::general_purpose::STANDARD.encode(bytes)),
+                (scalar, _) => anyhow::bail!("Unexpected wire type for a {} field", scalar),
+            },
+            (_, _) => anyhow::bail!("Unexpected wire type for a {} field", kind),
+        };
+        Ok(value)
+    }
+}
+
+/// `created_at` -> `createdAt`, the name proto3 JSON uses
+fn camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+/// Wire type of the elements of a packed repeated field; `None` for
+/// types that can't be packed
+fn packed_wire_type(kind: &Kind) -> Option<u8> {
+    match kind {
+        Kind::Enum(_) => Some(VARINT),
+        Kind::Message(_) => None,
+        Kind::Scalar(scalar) => match *scalar {
+            "string" | "bytes" => None,
+            "double" | "fixed64" | "sfixed64" => Some(FIXED64),
+            "float" | "fixed32" | "sfixed32" => Some(FIXED32),
+            _ => Some(VARINT),
+        },
+    }
+}
+
+fn encode_scalar(number: i64, scalar: &str, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+    match scalar {
+        "int32" => {
+            let n = i32::try_from(integer(value)?).context("out of range for int32")?;
+            put_tag(out, number, VARINT);
+            put_varint(out, n as i64 as u64);
+        }
+        "int64" => {
+            put_tag(out, number, VARINT);
+            put_varint(out, integer(value)? as u64);
+        }
+        "uint32" => {
+            let n = u32::try_from(unsigned(value)?).context("out of range for uint32")?;
+            put_tag(out, number, VARINT);
+            put_varint(out, n as u64);
+        }
+        "uint64" => {
+            put_tag(out, number, VARINT);
+            put_varint(out, unsigned(value)?);
+        }
+        "sint32" => {
+            let n = i32::try_from(integer(value)?).context("out of range for sint32")?;
+            put_tag(out, number, VARINT);
+            put_varint(out, zigzag_encode(n as i64));
+        }
+        "sint64" => {
+            put_tag(out, number, VARINT);
+            put_varint(out, zigzag_encode(integer(value)?));
+        }
+        "bool" => {
+            let b = match value {
+                Value::Bool(b) => *b,
+                Value::String(s) => s.parse().with_context(|| format!("'{}' is not a bool", s))?,
+                _ => anyhow::bail!("expected true or false"),
+            };
+            put_tag(out, number, VARINT);
+            put_varint(out, b as u64);
+        }
+        "fixed64" => {
+            put_tag(out, number, FIXED64);
+            out.extend(unsigned(value)?.to_le_bytes());
+        }
+        "sfixed64" => {
+            put_tag(out, number, FIXED64);
+            out.extend(integer(value)?.to_le_bytes());
+        }
+        "double" => {
+            put_tag(out, number, FIXED64);
+            out.extend(float(value)?.to_le_bytes());
+        }
+        "fixed32" => {
+            let n = u32::try_from(unsigned(value)?).context("out of range for fixed32")?;
+            put_tag(out, number, FIXED32);
+            out.extend(n.to_le_bytes());
+        }
+        "sfixed32" => {
+            let n = i32::try_from(integer(value)?).context("out of range for sfixed32")?;
+            put_tag(out, number, FIXED32);
+            out.extend(n.to_le_bytes());
+        }
+        "float" => {
+            put_tag(out, number, FIXED32);
+            out.extend((float(value)? as f32).to_le_bytes());
+        }
+        "string" => {
+            let s = value.as_str().context("expected a string")?;
+            put_tag(out, number, LENGTH_DELIMITED);
+            put_varint(out, s.len() as u64);
+            out.extend(s.as_bytes());
+        }
+        "bytes" => {
+            let encoded = value.as_str().context("expected base64 in a string")?;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .context("expected base64 in a string")?;
+            put_tag(out, number, LENGTH_DELIMITED);
+            put_varint(out, bytes.len() as u64);
+            out.extend(bytes);
+        }
+        _ => unreachable!("{} is not a scalar type", scalar),
+    }
+    Ok(())
+}
+
+/// A JSON number, or a string holding one as proto3 JSON writes 64-bit values
+fn integer(value: &Value) -> Result<i64> {
+    match value {
+        Value::Number(n) => n.as_i64().context("expected an integer"),
+        Value::String(s) => s.parse().with_context(|| format!("'{}' is not an integer", s)),
+        _ => anyhow::bail!("expected an integer"),
+    }
+}
+
+fn unsigned(value: &Value) -> Result<u64> {
+    match value {
+        Value::Number(n) => n.as_u64().context("expected a non-negative integer"),
+        Value::String(s) => s.parse().with_context(|| format!("'{}' is not a non-negative integer", s)),
+        _ => anyhow::bail!("expected a non-negative integer"),
+    }
+}
+
+fn float(value: &Value) -> Result<f64> {
+    match value {
+        Value::Number(n) => n.as_f64().context("expected a number"),
+        Value::String(s) => s.parse().with_context(|| format!("'{}' is not a number", s)),
+        _ => anyhow::bail!("expected a number"),
+    }
+}
+
+/// NaN and infinities have no JSON number, so proto3 JSON spells them out
+fn float_value(f: f64) -> Value {
+    serde_json::Number::from_f64(f)
+        .map(Value::Number)
+        .unwrap_or_else(|| Value::String(f.to_string()))
+}
+
+fn zigzag_encode(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn zigzag_decode(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+fn put_tag(out: &mut Vec<u8>, number: i64, wire_type: u8) {
+    put_varint(out, ((number as u64) << 3) | wire_type as u64);
+}
+
+fn put_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first().context("Truncated protobuf message")?;
+        *bytes = rest;
+        n |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    anyhow::bail!("Invalid varint in protobuf message")
+}
+
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    if bytes.len() < n {
+        anyhow::bail!("Truncated protobuf message");
+    }
+    let (taken, rest) = bytes.split_at(n);
+    *bytes = rest;
+    Ok(taken)
+}
+
+fn read_raw<'a>(bytes: &mut &'a [u8], wire_type: u8) -> Result<Raw<'a>> {
+    match wire_type {
+        VARINT => read_varint(bytes).map(Raw::Varint),
+        FIXED64 => Ok(Raw::Fixed64(u64::from_le_bytes(take(bytes, 8)?.try_into()?))),
+        LENGTH_DELIMITED => {
+            let length = read_varint(bytes)? as usize;
+            Ok(Raw::Bytes(take(bytes, length)?))
+        }
+        FIXED32 => Ok(Raw::Fixed32(u32::from_le_bytes(take(bytes, 4)?.try_into()?))),
+        other => anyhow::bail!("Unsupported wire type {} in protobuf message", other),
+    }
+}
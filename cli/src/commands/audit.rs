@@ -0,0 +1,407 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use comfy_table::{Cell, Table};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::commands::ExitStatus;
+use crate::config::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    fn label(self) -> colored::ColoredString {
+        match self {
+            Severity::Low => "low".dimmed(),
+            Severity::Medium => "medium".yellow(),
+            Severity::High => "high".red(),
+            Severity::Critical => "critical".red().bold(),
+        }
+    }
+
+    fn from_score(score: f64) -> Self {
+        match score {
+            s if s >= 9.0 => Severity::Critical,
+            s if s >= 7.0 => Severity::High,
+            s if s >= 4.0 => Severity::Medium,
+            _ => Severity::Low,
+        }
+    }
+}
+
+pub struct AuditOptions {
+    /// Repository names; `*` wildcards allowed
+    pub repos: Vec<String>,
+    /// Hide findings below this severity
+    pub severity: Severity,
+    /// Exit with status 1 when a finding is at least this severe
+    pub fail_on: Option<Severity>,
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Finding {
+    repo: String,
+    tool: &'static str,
+    package: String,
+    version: String,
+    id: String,
+    title: String,
+    severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    /// How to fix it, e.g. the patched versions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Skipped {
+    repo: String,
+    reason: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    findings: Vec<Finding>,
+    skipped: Vec<Skipped>,
+}
+
+pub async fn run(options: AuditOptions, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    let mut repos = config.get_all_repositories();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    for pattern in &options.repos {
+        let pattern = glob::Pattern::new(pattern).with_context(|| format!("Invalid pattern '{}'", pattern))?;
+        if !repos.iter().any(|(name, _)| pattern.matches(name)) {
+            anyhow::bail!("No repository matches '{}'", pattern);
+        }
+    }
+
+    let mut report = Report {
+        findings: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for (name, repo) in repos {
+        let selected = options.repos.is_empty()
+            || options
+                .repos
+                .iter()
+                .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|p| p.matches(&name)));
+        let path = config.workspace_root.join(&repo.path);
+        if !selected || !path.exists() {
+            continue;
+        }
+        if !options.json {
+            println!("{} Auditing {}...", "[?]".cyan(), name);
+        }
+
+        let mut audits = Vec::new();
+        if path.join("Cargo.toml").exists() {
+            let deny = path.join("deny.toml").exists() && which::which("cargo-deny").is_ok();
+            audits.push(if deny { cargo_deny(&name, &path).await } else { cargo_audit(&name, &path).await });
+        }
+        if path.join("package.json").exists() {
+            audits.push(npm_audit(&name, &path).await);
+        }
+        for audit in audits {
+            match audit {
+                Ok(findings) => report.findings.extend(findings),
+                Err(e) => report.skipped.push(Skipped {
+                    repo: name.clone(),
+                    reason: format!("{:#}", e),
+                }),
+            }
+        }
+    }
+
+    let failing = options
+        .fail_on
+        .map_or(0, |threshold| report.findings.iter().filter(|f| f.severity >= threshold).count());
+    report.findings.retain(|f| f.severity >= options.severity);
+    report
+        .findings
+        .sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.repo.cmp(&b.repo)).then_with(|| a.id.cmp(&b.id)));
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+
+    if failing > 0 {
+        return Err(ExitStatus {
+            code: 1,
+            message: format!("{} finding(s) at or above the --fail-on severity", failing),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+fn print_report(report: &Report) {
+    println!();
+    for skipped in &report.skipped {
+        println!("{} {} not audited: {}", "[!]".yellow(), skipped.repo, skipped.reason);
+    }
+    if report.findings.is_empty() {
+        say!("{} No findings", "[OK]".green());
+        return;
+    }
+
+    let mut table = Table::new();
+    table.set_header(vec!["Severity", "Repository", "Package", "Advisory", "Fix"]);
+    for f in &report.findings {
+        table.add_row(vec![
+            Cell::new(f.severity.label()),
+            Cell::new(&f.repo),
+            Cell::new(format!("{} {}", f.package, f.version)),
+            Cell::new(format!("{}\n{}", f.id, f.title)),
+            Cell::new(f.fix.as_deref().unwrap_or("-")),
+        ]);
+    }
+    println!("{}", table);
+
+    let counts: Vec<String> = [Severity::Critical, Severity::High, Severity::Medium, Severity::Low]
+        .into_iter()
+        .filter_map(|severity| {
+            let count = report.findings.iter().filter(|f| f.severity == severity).count();
+            (count > 0).then(|| format!("{} {}", count, severity.label()))
+        })
+        .collect();
+    println!("\n{} findings: {}", report.findings.len(), counts.join(", "));
+}
+
+async fn output(dir: &Path, program: &str, args: &[&str], install_hint: &str) -> Result<std::process::Output> {
+    Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .with_context(|| format!("{} is not installed ({})", program, install_hint))
+}
+
+async fn cargo_audit(repo: &str, dir: &Path) -> Result<Vec<Finding>> {
+    if which::which("cargo-audit").is_err() {
+        anyhow::bail!("cargo-audit is not installed (cargo install cargo-audit)");
+    }
+    let output = output(dir, "cargo", &["audit", "--json"], "cargo install cargo-audit").await?;
+    let report: Value = serde_json::from_slice(&output.stdout).with_context(|| {
+        format!("cargo audit failed: {}", String::from_utf8_lossy(&output.stderr).trim())
+    })?;
+
+    let mut findings = Vec::new();
+    for vulnerability in report["vulnerabilities"]["list"].as_array().into_iter().flatten() {
+        let advisory = &vulnerability["advisory"];
+        let patched: Vec<&str> = vulnerability["versions"]["patched"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        findings.push(advisory_finding(
+            repo,
+            "cargo-audit",
+            &vulnerability["package"],
+            advisory,
+            Severity::Medium,
+            (!patched.is_empty()).then(|| format!("upgrade to {}", patched.join(" or "))),
+        ));
+    }
+    // Unmaintained, unsound and yanked crates
+    for (kind, warnings) in report["warnings"].as_object().into_iter().flatten() {
+        for warning in warnings.as_array().into_iter().flatten() {
+            let mut finding =
+                advisory_finding(repo, "cargo-audit", &warning["package"], &warning["advisory"], Severity::Low, None);
+            if warning["advisory"].is_null() {
+                finding.id = kind.clone();
+                finding.title = format!("{} crate", kind);
+            }
+            findings.push(finding);
+        }
+    }
+    Ok(findings)
+}
+
+async fn cargo_deny(repo: &str, dir: &Path) -> Result<Vec<Finding>> {
+    let output = output(dir, "cargo", &["deny", "--format", "json", "check"], "cargo install cargo-deny").await?;
+
+    let mut findings = Vec::new();
+    let lines = String::from_utf8_lossy(&output.stderr);
+    for line in lines.lines() {
+        let Ok(entry) = serde_json::from_str::<Value>(line) else { continue };
+        if entry["type"] != "diagnostic" {
+            continue;
+        }
+        let fields = &entry["fields"];
+        let fallback = match fields["severity"].as_str() {
+            Some("error") => Severity::High,
+            Some("warning") => Severity::Medium,
+            _ => Severity::Low,
+        };
+        let package = &fields["graphs"][0]["Krate"];
+        let mut finding = advisory_finding(repo, "cargo-deny", package, &fields["advisory"], fallback, None);
+        if fields["advisory"].is_null() {
+            finding.id = fields["code"].as_str().unwrap_or("deny").to_string();
+            finding.title = fields["message"].as_str().unwrap_or_default().to_string();
+        }
+        findings.push(finding);
+    }
+    if findings.is_empty() && !output.status.success() {
+        anyhow::bail!("cargo deny failed: {}", lines.trim());
+    }
+    Ok(findings)
+}
+
+async fn npm_audit(repo: &str, dir: &Path) -> Result<Vec<Finding>> {
+    let output = output(dir, "npm", &["audit", "--json"], "install Node.js").await?;
+    let report: Value = serde_json::from_slice(&output.stdout).with_context(|| {
+        format!("npm audit failed: {}", String::from_utf8_lossy(&output.stderr).trim())
+    })?;
+    if let Some(error) = report["error"]["summary"].as_str() {
+        anyhow::bail!("npm audit failed: {}", error);
+    }
+
+    let mut findings = Vec::new();
+    for (name, vulnerability) in report["vulnerabilities"].as_object().into_iter().flatten() {
+        let severity = npm_severity(vulnerability["severity"].as_str());
+        let fix = match &vulnerability["fixAvailable"] {
+            Value::Bool(true) => Some("npm audit fix".to_string()),
+            Value::Object(fix) if fix.get("isSemVerMajor") == Some(&Value::Bool(true)) => {
+                Some("npm audit fix --force (breaking)".to_string())
+            }
+            Value::Object(_) => Some("npm audit fix".to_string()),
+            _ => None,
+        };
+        let range = vulnerability["range"].as_str().unwrap_or_default().to_string();
+
+        let advisories: Vec<&Value> = vulnerability["via"].as_array().into_iter().flatten().filter(|v| v.is_object()).collect();
+        if advisories.is_empty() {
+            // Only vulnerable through its dependencies, which are listed on their own
+            let via: Vec<&str> = vulnerability["via"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+            findings.push(Finding {
+                repo: repo.to_string(),
+                tool: "npm-audit",
+                package: name.clone(),
+                version: range,
+                id: "transitive".to_string(),
+                title: format!("depends on vulnerable {}", via.join(", ")),
+                severity,
+                url: None,
+                fix,
+            });
+            continue;
+        }
+        for advisory in advisories {
+            findings.push(Finding {
+                repo: repo.to_string(),
+                tool: "npm-audit",
+                package: name.clone(),
+                version: range.clone(),
+                id: advisory["source"].as_u64().map_or_else(|| "npm".to_string(), |id| id.to_string()),
+                title: advisory["title"].as_str().unwrap_or_default().to_string(),
+                severity: npm_severity(advisory["severity"].as_str()),
+                url: advisory["url"].as_str().map(String::from),
+                fix: fix.clone(),
+            });
+        }
+    }
+    Ok(findings)
+}
+
+fn npm_severity(severity: Option<&str>) -> Severity {
+    match severity {
+        Some("critical") => Severity::Critical,
+        Some("high") => Severity::High,
+        Some("moderate") => Severity::Medium,
+        _ => Severity::Low,
+    }
+}
+
+/// A finding from a RustSec advisory, rated by its CVSS vector when it has one
+fn advisory_finding(
+    repo: &str,
+    tool: &'static str,
+    package: &Value,
+    advisory: &Value,
+    fallback: Severity,
+    fix: Option<String>,
+) -> Finding {
+    let severity = advisory["cvss"]
+        .as_str()
+        .and_then(cvss_score)
+        .map_or(fallback, Severity::from_score);
+    Finding {
+        repo: repo.to_string(),
+        tool,
+        package: package["name"]
+            .as_str()
+            .or_else(|| advisory["package"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+        version: package["version"].as_str().unwrap_or_default().to_string(),
+        id: advisory["id"].as_str().unwrap_or_default().to_string(),
+        title: advisory["title"].as_str().unwrap_or_default().to_string(),
+        severity,
+        url: advisory["url"].as_str().filter(|url| !url.is_empty()).map(String::from),
+        fix,
+    }
+}
+
+/// CVSS 3.x base score of a vector like `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`
+fn cvss_score(vector: &str) -> Option<f64> {
+    let mut parts = vector.split('/');
+    if !parts.next()?.starts_with("CVSS:3") {
+        return None;
+    }
+    let metrics: std::collections::HashMap<&str, &str> = parts.filter_map(|part| part.split_once(':')).collect();
+    let changed = *metrics.get("S")? == "C";
+    let av: f64 = match *metrics.get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        _ => 0.2,
+    };
+    let ac = if *metrics.get("AC")? == "L" { 0.77 } else { 0.44 };
+    let pr = match (*metrics.get("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        (_, false) => 0.27,
+        (_, true) => 0.5,
+    };
+    let ui = if *metrics.get("UI")? == "N" { 0.85 } else { 0.62 };
+    let impact_of = |metric: &str| -> Option<f64> {
+        match metrics.get(metric).copied() {
+            Some("H") => Some(0.56),
+            Some("L") => Some(0.22),
+            Some("N") => Some(0.0),
+            _ => None,
+        }
+    };
+    let iss: f64 = 1.0 - (1.0 - impact_of("C")?) * (1.0 - impact_of("I")?) * (1.0 - impact_of("A")?);
+    let impact = if changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02).powi(15)
+    } else {
+        6.42 * iss
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * av * ac * pr * ui;
+    let score = if changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    Some((score.min(10.0) * 10.0).ceil() / 10.0)
+}
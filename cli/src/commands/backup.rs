@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use dialoguer::Confirm;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::{Config, InfrastructureConfig};
+use crate::docker;
+use crate::names;
+use crate::ui;
+
+/// A data store `dev backup` knows how to snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StoreKind {
+    /// `pg_dump` of the configured database, restored with `psql`
+    Postgres,
+    /// Copy of the data directory after a `SAVE`, RDB and AOF alike
+    Redis,
+}
+
+/// One store in a backup; `file` is relative to the backup directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Store {
+    infrastructure: String,
+    kind: StoreKind,
+    file: String,
+}
+
+/// What `.platform/backups/<name>/backup.toml` records
+#[derive(Debug, Serialize, Deserialize)]
+struct Backup {
+    created_at: chrono::DateTime<chrono::Utc>,
+    stores: Vec<Store>,
+}
+
+impl Backup {
+    fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join("backup.toml");
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+fn backups_dir(config: &Config) -> PathBuf {
+    config.workspace_root.join(".platform/backups")
+}
+
+/// Snapshots every Postgres and Redis in `[infrastructure]` into
+/// `.platform/backups/<name>`; the name defaults to the current time
+pub async fn backup(config: &Config, name: Option<String>) -> Result<()> {
+    let stores = stores(config);
+    if stores.is_empty() {
+        anyhow::bail!("No Postgres or Redis in [infrastructure] to back up");
+    }
+    if let Some(reason) = docker::unavailable().await {
+        anyhow::bail!("Docker is not available ({}); backups are taken from the running containers", reason);
+    }
+
+    let name = name.unwrap_or_else(|| chrono::Local::now().format("%Y%m%d-%H%M%S").to_string());
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("Invalid backup name '{}'", name);
+    }
+    let dir = backups_dir(config).join(&name);
+    if dir.exists() {
+        anyhow::bail!("Backup '{}' already exists; pick another name or remove {}", name, dir.display());
+    }
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    say!("{} {}", "Backing up data stores to".bold(), dir.display());
+    let mut saved = Vec::new();
+    for (infra_name, infra, kind) in stores {
+        let result = match kind {
+            StoreKind::Postgres => dump_postgres(config, infra_name, infra, &dir),
+            StoreKind::Redis => copy_redis_data(config, infra_name, &dir),
+        };
+        match result {
+            Ok(store) => {
+                say!("{} {} -> {}", "[OK]".green(), infra_name, store.file);
+                saved.push(store);
+            }
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&dir);
+                return Err(e.context(format!("Failed to back up {}", infra_name)));
+            }
+        }
+    }
+
+    let backup = Backup { created_at: chrono::Utc::now(), stores: saved };
+    std::fs::write(dir.join("backup.toml"), toml::to_string_pretty(&backup)?)
+        .with_context(|| format!("Failed to write {}", dir.join("backup.toml").display()))?;
+    say!("\n{} Saved backup {}", "[OK]".green().bold(), name.cyan());
+    say!("Run {} to bring it back", format!("syla dev restore {}", name).bright_black());
+    Ok(())
+}
+
+/// Puts the stores of a backup back, replacing what they hold now. Without
+/// a name, asks which backup to use.
+pub async fn restore(config: &Config, name: Option<String>, yes: bool) -> Result<()> {
+    let available = list(config)?;
+    if available.is_empty() {
+        anyhow::bail!("No backups yet; take one with `syla dev backup <name>`");
+    }
+    let name = names::resolve_or_pick("backup", name.as_deref(), available.iter().map(String::as_str))?;
+    let dir = backups_dir(config).join(&name);
+    let backup = Backup::load(&dir)?;
+    if let Some(reason) = docker::unavailable().await {
+        anyhow::bail!("Docker is not available ({}); backups are restored into the running containers", reason);
+    }
+
+    if !yes {
+        println!(
+            "This replaces the data in {} with backup {} from {}",
+            backup.stores.iter().map(|store| store.infrastructure.as_str()).collect::<Vec<_>>().join(", "),
+            name.bold(),
+            backup.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+        );
+        let proceed = Confirm::new().with_prompt("Restore it?").default(false).interact()?;
+        if !proceed {
+            println!("Aborted");
+            return Ok(());
+        }
+    }
+
+    for store in &backup.stores {
+        let Some(infra) = config.manifest.infrastructure.get(&store.infrastructure) else {
+            println!(
+                "{} {} is no longer in [infrastructure], skipping",
+                "[!]".yellow(),
+                store.infrastructure
+            );
+            continue;
+        };
+        let path = dir.join(&store.file);
+        match store.kind {
+            StoreKind::Postgres => restore_postgres(config, &store.infrastructure, infra, &path),
+            StoreKind::Redis => restore_redis_data(config, &store.infrastructure, &path),
+        }
+        .with_context(|| format!("Failed to restore {}", store.infrastructure))?;
+        say!("{} Restored {}", "[OK]".green(), store.infrastructure);
+    }
+    say!("\n{} Restored backup {}", "[OK]".green().bold(), name.cyan());
+    Ok(())
+}
+
+/// Names of the backups on disk, oldest first
+fn list(config: &Config) -> Result<Vec<String>> {
+    let dir = backups_dir(config);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    let mut backups: Vec<(chrono::DateTime<chrono::Utc>, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let backup = Backup::load(&entry.path()).ok()?;
+            Some((backup.created_at, entry.file_name().to_string_lossy().into_owned()))
+        })
+        .collect();
+    backups.sort();
+    Ok(backups.into_iter().map(|(_, name)| name).collect())
+}
+
+/// Infrastructure that can be backed up, by name. Images are recognised
+/// the same way `dev up` derives `DATABASE_URL` and `REDIS_URL`.
+fn stores(config: &Config) -> Vec<(&str, &InfrastructureConfig, StoreKind)> {
+    let mut stores: Vec<_> = config
+        .manifest
+        .infrastructure
+        .iter()
+        .filter_map(|(name, infra)| {
+            let image = infra.docker_image.as_deref()?;
+            let kind = if image.starts_with("postgres") {
+                StoreKind::Postgres
+            } else if image.starts_with("redis") {
+                StoreKind::Redis
+            } else {
+                return None;
+            };
+            Some((name.as_str(), infra, kind))
+        })
+        .collect();
+    stores.sort_by(|a, b| a.0.cmp(b.0));
+    stores
+}
+
+fn env_var<'a>(infra: &'a InfrastructureConfig, key: &str) -> Option<&'a str> {
+    infra.environment.iter().find_map(|entry| entry.strip_prefix(key)?.strip_prefix('='))
+}
+
+/// `docker compose` in the workspace root; infrastructure runs as the
+/// Compose service of the same name
+fn compose(config: &Config) -> Command {
+    let mut cmd = Command::new("docker");
+    cmd.arg("compose").args(docker::compose_profile_args(config)).current_dir(&config.workspace_root);
+    cmd
+}
+
+/// `psql`/`pg_dump` arguments for the database the container was set up with
+fn postgres_exec(config: &Config, service: &str, infra: &InfrastructureConfig, program: &str) -> Command {
+    let user = env_var(infra, "POSTGRES_USER").unwrap_or("postgres");
+    let database = env_var(infra, "POSTGRES_DB").unwrap_or(user);
+    let mut cmd = compose(config);
+    cmd.args(["exec", "-T"]);
+    if let Some(password) = env_var(infra, "POSTGRES_PASSWORD") {
+        cmd.args(["-e", &format!("PGPASSWORD={}", password)]);
+    }
+    cmd.args([service, program, "-U", user, "-d", database]);
+    cmd
+}
+
+fn dump_postgres(config: &Config, name: &str, infra: &InfrastructureConfig, dir: &Path) -> Result<Store> {
+    let file = format!("{}.sql", name);
+    let out = File::create(dir.join(&file)).with_context(|| format!("Failed to create {}", file))?;
+    let mut cmd = postgres_exec(config, name, infra, "pg_dump");
+    cmd.args(["--clean", "--if-exists", "--no-owner"]).stdout(out);
+    run(&mut cmd)?;
+    Ok(Store { infrastructure: name.to_string(), kind: StoreKind::Postgres, file })
+}
+
+fn restore_postgres(config: &Config, name: &str, infra: &InfrastructureConfig, path: &Path) -> Result<()> {
+    let dump = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut cmd = postgres_exec(config, name, infra, "psql");
+    cmd.args(["-q", "-v", "ON_ERROR_STOP=1"]).stdin(dump).stdout(Stdio::null());
+    run(&mut cmd)
+}
+
+/// Flushes Redis to disk and copies its data directory, so both the RDB
+/// snapshot and any append-only files come along
+fn copy_redis_data(config: &Config, name: &str, dir: &Path) -> Result<Store> {
+    run(compose(config).args(["exec", "-T", name, "redis-cli", "SAVE"]).stdout(Stdio::null()))?;
+    run(compose(config).args(["cp", &format!("{}:/data", name)]).arg(dir.join(name)))?;
+    Ok(Store { infrastructure: name.to_string(), kind: StoreKind::Redis, file: name.to_string() })
+}
+
+/// Copies the data directory back while Redis is stopped, so it loads the
+/// restored files instead of writing its own over them on shutdown
+fn restore_redis_data(config: &Config, name: &str, path: &Path) -> Result<()> {
+    run(compose(config).args(["stop", name]))?;
+    let copied = run(compose(config).arg("cp").arg(path.join(".")).arg(format!("{}:/data", name)));
+    run(compose(config).args(["start", name]))?;
+    copied
+}
+
+fn run(cmd: &mut Command) -> Result<()> {
+    let logged = ui::log_command(cmd);
+    let output = cmd.stderr(Stdio::piped()).output().context("Failed to run docker compose")?;
+    logged.finished(&output.status);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("`docker compose` failed: {}", stderr.trim());
+    }
+    Ok(())
+}
@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::commands::dev::host_port;
+use crate::config::{Config, RepositoryConfig};
+use crate::git;
+
+pub struct BenchOptions {
+    pub service: String,
+    pub path: String,
+    /// Requests per second across all workers; 0 sends as fast as possible
+    pub rate: u32,
+    pub duration: u64,
+    pub concurrency: usize,
+    pub method: String,
+    /// Name the results are saved under; defaults to the service's branch
+    pub label: Option<String>,
+    pub compare: Option<String>,
+    pub save: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BenchResult {
+    service: String,
+    label: String,
+    url: String,
+    method: String,
+    rate: u32,
+    concurrency: usize,
+    duration_secs: f64,
+    requests: usize,
+    errors: usize,
+    throughput: f64,
+    p50_ms: f64,
+    p90_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+    recorded_at: DateTime<Utc>,
+}
+
+impl BenchResult {
+    fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.requests as f64 * 100.0
+        }
+    }
+}
+
+pub async fn run(options: BenchOptions, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    let (name, repo) = resolve(&config, &options.service)?;
+    let port = repo
+        .ports
+        .first()
+        .and_then(|p| host_port(p))
+        .with_context(|| format!("{} has no ports in the manifest", name))?;
+    let path = if options.path.starts_with('/') {
+        options.path.clone()
+    } else {
+        format!("/{}", options.path)
+    };
+    let url = format!("http://localhost:{}{}", port, path);
+    let method = reqwest::Method::from_bytes(options.method.to_uppercase().as_bytes())
+        .with_context(|| format!("Invalid HTTP method '{}'", options.method))?;
+    if options.concurrency == 0 {
+        anyhow::bail!("--concurrency must be at least 1");
+    }
+
+    // Compare against a previous run before spending time on a new one
+    let baseline = match &options.compare {
+        Some(label) => Some(load(&config, &name, label)?),
+        None => None,
+    };
+
+    let label = match &options.label {
+        Some(label) => label.clone(),
+        None => git::current_branch(&config.workspace_root.join(&repo.path))
+            .await
+            .unwrap_or_else(|_| "latest".to_string()),
+    };
+
+    println!("{} {}", "Benchmarking".bold(), name.bold());
+    let rate = if options.rate == 0 {
+        "unlimited".to_string()
+    } else {
+        format!("{}/s", options.rate)
+    };
+    println!(
+        "  {} {} for {}s, {} workers, rate {}",
+        method,
+        url,
+        options.duration,
+        options.concurrency,
+        rate
+    );
+    println!();
+
+    let (latencies, errors, elapsed) = fire(&url, method.clone(), &options).await?;
+    let result = summarize(&name, &label, &url, &options, latencies, errors, elapsed);
+    print_result(&result);
+
+    if let Some(baseline) = &baseline {
+        print_comparison(baseline, &result);
+    }
+
+    if options.save {
+        let path = save(&config, &result)?;
+        say!("\n{} Saved as {} ({})", "[OK]".green(), result.label.bold(), path.display());
+    }
+
+    Ok(())
+}
+
+fn resolve<'a>(config: &'a Config, service: &str) -> Result<(String, &'a RepositoryConfig)> {
+    config.find_repository(service)
+}
+
+/// Run the workers until the duration is up; latencies are only kept for
+/// successful requests
+async fn fire(
+    url: &str,
+    method: reqwest::Method,
+    options: &BenchOptions,
+) -> Result<(Vec<Duration>, usize, Duration)> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .pool_max_idle_per_host(options.concurrency)
+        .build()?;
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(options.duration);
+    // Each worker takes an equal share of the rate
+    let period = (options.rate > 0)
+        .then(|| Duration::from_secs_f64(options.concurrency as f64 / options.rate as f64));
+
+    let mut workers = Vec::new();
+    for _ in 0..options.concurrency {
+        let client = client.clone();
+        let method = method.clone();
+        let url = url.to_string();
+        workers.push(tokio::spawn(async move {
+            let mut latencies = Vec::new();
+            let mut errors = 0;
+            let mut ticker = period.map(|period| {
+                let mut ticker = tokio::time::interval(period);
+                ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                ticker
+            });
+            loop {
+                if let Some(ticker) = &mut ticker {
+                    ticker.tick().await;
+                }
+                if Instant::now() >= deadline {
+                    break;
+                }
+                let sent = Instant::now();
+                match client.request(method.clone(), &url).send().await {
+                    Ok(response) if response.status().is_success() => {
+                        // Read the body so the timing covers the whole response
+                        let _ = response.bytes().await;
+                        latencies.push(sent.elapsed());
+                    }
+                    _ => errors += 1,
+                }
+            }
+            (latencies, errors)
+        }));
+    }
+
+    let mut latencies = Vec::new();
+    let mut errors = 0;
+    for worker in workers {
+        let (worker_latencies, worker_errors) = worker.await?;
+        latencies.extend(worker_latencies);
+        errors += worker_errors;
+    }
+    Ok((latencies, errors, started.elapsed()))
+}
+
+fn summarize(
+    service: &str,
+    label: &str,
+    url: &str,
+    options: &BenchOptions,
+    mut latencies: Vec<Duration>,
+    errors: usize,
+    elapsed: Duration,
+) -> BenchResult {
+    latencies.sort();
+    let requests = latencies.len() + errors;
+    let percentile = |p: f64| -> f64 {
+        if latencies.is_empty() {
+            return 0.0;
+        }
+        let index = ((p * latencies.len() as f64).ceil() as usize).clamp(1, latencies.len()) - 1;
+        latencies[index].as_secs_f64() * 1000.0
+    };
+
+    BenchResult {
+        service: service.to_string(),
+        label: label.to_string(),
+        url: url.to_string(),
+        method: options.method.to_uppercase(),
+        rate: options.rate,
+        concurrency: options.concurrency,
+        duration_secs: elapsed.as_secs_f64(),
+        requests,
+        errors,
+        throughput: requests as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        p50_ms: percentile(0.50),
+        p90_ms: percentile(0.90),
+        p99_ms: percentile(0.99),
+        max_ms: percentile(1.0),
+        recorded_at: Utc::now(),
+    }
+}
+
+fn print_result(result: &BenchResult) {
+    println!("{}", "Results".bold());
+    println!("  {:<12} {}", "Requests:", result.requests);
+    println!("  {:<12} {:.1} req/s", "Throughput:", result.throughput);
+    let error_rate = format!("{:.2}% ({})", result.error_rate(), result.errors);
+    if result.errors > 0 {
+        println!("  {:<12} {}", "Errors:", error_rate.red());
+    } else {
+        println!("  {:<12} {}", "Errors:", error_rate);
+    }
+    println!("  {:<12} {:.2} ms", "p50:", result.p50_ms);
+    println!("  {:<12} {:.2} ms", "p90:", result.p90_ms);
+    println!("  {:<12} {:.2} ms", "p99:", result.p99_ms);
+    println!("  {:<12} {:.2} ms", "max:", result.max_ms);
+}
+
+fn print_comparison(baseline: &BenchResult, result: &BenchResult) {
+    println!("\n{} {}", "Compared with".bold(), baseline.label.bold());
+    // Lower is better for everything but throughput
+    let row = |name: &str, before: f64, after: f64, unit: &str, higher_is_better: bool| {
+        let change = if before == 0.0 {
+            0.0
+        } else {
+            (after - before) / before * 100.0
+        };
+        let text = format!("{:+.1}%", change);
+        let worse = if higher_is_better { change < -5.0 } else { change > 5.0 };
+        let better = if higher_is_better { change > 5.0 } else { change < -5.0 };
+        let text = if worse {
+            text.red()
+        } else if better {
+            text.green()
+        } else {
+            text.dimmed()
+        };
+        println!("  {:<12} {:>10.2} -> {:>10.2} {:<6} {}", name, before, after, unit, text);
+    };
+    row("Throughput:", baseline.throughput, result.throughput, "req/s", true);
+    row("Errors:", baseline.error_rate(), result.error_rate(), "%", false);
+    row("p50:", baseline.p50_ms, result.p50_ms, "ms", false);
+    row("p90:", baseline.p90_ms, result.p90_ms, "ms", false);
+    row("p99:", baseline.p99_ms, result.p99_ms, "ms", false);
+}
+
+fn results_path(config: &Config, service: &str, label: &str) -> PathBuf {
+    // Branch names like feature/foo become feature-foo
+    let file: String = label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '-' })
+        .collect();
+    config
+        .workspace_root
+        .join(".bench")
+        .join(service)
+        .join(format!("{}.json", file))
+}
+
+fn save(config: &Config, result: &BenchResult) -> Result<PathBuf> {
+    let path = results_path(config, &result.service, &result.label);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(result)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn load(config: &Config, service: &str, label: &str) -> Result<BenchResult> {
+    let path = results_path(config, service, label);
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("No saved results for {} labelled '{}'", service, label))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
@@ -0,0 +1,170 @@
+//! Shell completion.
+//!
+//! `syla completions <shell>` prints clap's completion script with a hook
+//! that asks `syla __complete` for the values clap can't know: service and
+//! platform names from the workspace manifest, and the IDs of executions
+//! submitted from the workspace. The hook passes the words typed so far;
+//! `__complete` works out which argument the last one fills and prints
+//! nothing when it isn't one of those, so the static completions apply.
+
+use clap::{Arg, Command};
+use clap_complete::Shell;
+use std::path::PathBuf;
+
+use crate::commands::executions;
+use crate::config::Config;
+
+/// Arguments that take a service name
+const SERVICE_ARGS: &[&str] = &["service", "services", "repos"];
+
+const BASH_HOOK: &str = r#"
+_syla_dynamic() {
+    local candidates
+    candidates=$(syla __complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null)
+    if [[ -n "$candidates" ]]; then
+        COMPREPLY=( $(compgen -W "$candidates" -- "${COMP_WORDS[COMP_CWORD]}") )
+        return 0
+    fi
+    _syla "$@"
+}
+complete -F _syla_dynamic -o bashdefault -o default syla
+"#;
+
+const ZSH_HOOK: &str = r#"
+_syla_dynamic() {
+    local -a candidates
+    candidates=(${(f)"$(syla __complete -- ${words[2,CURRENT]} 2>/dev/null)"})
+    if (( ${#candidates} )); then
+        compadd -a candidates
+    else
+        _syla "$@"
+    fi
+}
+compdef _syla_dynamic syla
+"#;
+
+const FISH_HOOK: &str = r#"
+complete -c syla -a '(syla __complete -- (commandline -opc)[2..-1] (commandline -ct) 2>/dev/null)'
+"#;
+
+/// Prints the completion script for `shell`
+pub fn run(shell: Shell, mut command: Command) {
+    clap_complete::generate(shell, &mut command, "syla", &mut std::io::stdout());
+    let hook = match shell {
+        Shell::Bash => BASH_HOOK,
+        Shell::Zsh => ZSH_HOOK,
+        Shell::Fish => FISH_HOOK,
+        _ => return,
+    };
+    print!("{}", hook);
+}
+
+/// Prints the candidates for the last of `words`, the command line after
+/// `syla` with the word being completed last
+pub fn complete(command: Command, words: &[String], workspace_root: Option<PathBuf>) {
+    for candidate in candidates(command, words, workspace_root) {
+        println!("{}", candidate);
+    }
+}
+
+/// What the word being completed can be
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Service,
+    Platform,
+    Execution,
+}
+
+fn candidates(mut command: Command, words: &[String], mut workspace_root: Option<PathBuf>) -> Vec<String> {
+    let Some((current, typed)) = words.split_last() else {
+        return Vec::new();
+    };
+    // Global options only reach subcommands once the tree is built
+    command.build();
+    let mut command = &command;
+    let mut positional = 0;
+    let mut pending: Option<&Arg> = None;
+    for word in typed {
+        if let Some(arg) = pending.take() {
+            if arg.get_id() == "workspace" {
+                workspace_root = Some(PathBuf::from(word));
+            }
+            continue;
+        }
+        if word.starts_with('-') && word.len() > 1 {
+            pending = option(command, word).filter(|arg| arg.get_action().takes_values());
+            continue;
+        }
+        match command.find_subcommand(word) {
+            Some(subcommand) => {
+                command = subcommand;
+                positional = 0;
+            }
+            None => positional += 1,
+        }
+    }
+
+    let arg = match pending {
+        Some(arg) => Some(arg),
+        None if current.starts_with('-') => None,
+        None => positional_arg(command, positional),
+    };
+    let Some(kind) = arg.and_then(|arg| kind(command, arg)) else {
+        return Vec::new();
+    };
+    let Ok(config) = Config::load(workspace_root) else {
+        return Vec::new();
+    };
+    let values: Vec<String> = match kind {
+        Kind::Service => {
+            let mut names: Vec<String> = config.manifest.repositories.keys().cloned().collect();
+            names.sort();
+            names
+        }
+        Kind::Platform => config.platforms().into_iter().map(str::to_string).collect(),
+        Kind::Execution => executions::recent(&config.workspace_root),
+    };
+    values.into_iter().filter(|value| value.starts_with(current.as_str())).collect()
+}
+
+/// The option a flag like `--platform`, `--platform=x` or `-p` names
+fn option<'a>(command: &'a Command, word: &str) -> Option<&'a Arg> {
+    if let Some(long) = word.strip_prefix("--") {
+        if long.contains('=') {
+            return None;
+        }
+        return command.get_arguments().find(|arg| arg.get_long() == Some(long));
+    }
+    let mut shorts = word[1..].chars();
+    let short = shorts.next()?;
+    // `-pcore` carries its value
+    if shorts.next().is_some() {
+        return None;
+    }
+    command.get_arguments().find(|arg| arg.get_short() == Some(short))
+}
+
+/// The positional argument the `index`th positional word fills; one
+/// taking several values fills all the rest
+fn positional_arg(command: &Command, index: usize) -> Option<&Arg> {
+    let positionals: Vec<&Arg> = command.get_positionals().collect();
+    positionals.get(index).copied().or_else(|| {
+        positionals
+            .last()
+            .copied()
+            .filter(|arg| arg.get_num_args().is_some_and(|range| range.max_values() > 1))
+    })
+}
+
+fn kind(command: &Command, arg: &Arg) -> Option<Kind> {
+    let id = arg.get_id().as_str();
+    if SERVICE_ARGS.contains(&id) {
+        Some(Kind::Service)
+    } else if id == "platform" {
+        Some(Kind::Platform)
+    } else if id == "id" && command.get_name() == "replay" {
+        Some(Kind::Execution)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,371 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::commands::{ExitStatus, OutputFormat};
+use crate::config::{self, BuildSettings, Config, DevSettings, WorkspaceSettings};
+use crate::notifications::NotificationSettings;
+use crate::{ConfigCommands, FeatureCommands};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Bool,
+    Integer,
+    String,
+    /// Comma-separated on the command line
+    List,
+}
+
+/// A setting `config get/set/unset` can address
+struct Key {
+    path: &'static str,
+    kind: Kind,
+    about: &'static str,
+}
+
+const KEYS: &[Key] = &[
+    Key { path: "dev.default_platform", kind: Kind::String, about: "Platform `dev up` starts when --platform isn't given" },
+    Key { path: "build.target_dir", kind: Kind::String, about: "Cargo target directory shared by all services, relative to the workspace" },
+    Key { path: "build.sccache", kind: Kind::Bool, about: "Compile through sccache when it's installed" },
+    Key { path: "notifications.enabled", kind: Kind::Bool, about: "Desktop notifications at all" },
+    Key { path: "notifications.crashes", kind: Kind::Bool, about: "Notify when a service crashes" },
+    Key { path: "notifications.health", kind: Kind::Bool, about: "Notify when a service turns healthy or unhealthy" },
+    Key { path: "notifications.builds", kind: Kind::Bool, about: "Notify when a long build finishes" },
+    Key { path: "notifications.build_threshold_secs", kind: Kind::Integer, about: "How long a build runs before it's worth a notification" },
+    Key { path: "compose_profiles", kind: Kind::List, about: "Docker Compose profiles enabled with the infrastructure" },
+    Key { path: "features", kind: Kind::List, about: "Enabled features; see `syla config features`" },
+];
+
+/// Free-form values under `[config]`, e.g. `config.dev_mode`
+const FREE_FORM: &str = "config.";
+
+/// Where a value comes from, lowest precedence first
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Source {
+    Default,
+    User,
+    Workspace,
+}
+
+#[derive(Debug, Serialize)]
+struct Setting {
+    value: toml::Value,
+    source: Source,
+}
+
+pub async fn run(command: ConfigCommands, output: Option<OutputFormat>, workspace_root: Option<PathBuf>) -> Result<()> {
+    match command {
+        ConfigCommands::Features { command } => {
+            let mut config = Config::load(workspace_root)?;
+            match command {
+                FeatureCommands::List => list_features(&config),
+                FeatureCommands::Enable { features } => set_features(&mut config, &features, true)?,
+                FeatureCommands::Disable { features } => set_features(&mut config, &features, false)?,
+            }
+        }
+        ConfigCommands::Show => show(workspace(workspace_root).as_deref(), output)?,
+        ConfigCommands::Get { key } => {
+            let kind = kind(&key)?;
+            let settings = effective(workspace(workspace_root).as_deref())?;
+            match settings.get(&key) {
+                Some(setting) => match output {
+                    Some(format) => format.print(setting)?,
+                    None => println!("{}", display(&setting.value, kind)),
+                },
+                None => return Err(ExitStatus { code: 1, message: format!("{} is not set", key) }.into()),
+            }
+        }
+        ConfigCommands::Set { key, value, local } => {
+            let value = parse(&key, kind(&key)?, &value)?;
+            let path = target(workspace_root, local)?;
+            write_key(&path, &key, Some(value.clone()))?;
+            say!("{} Set {} = {} in {}", "[OK]".green(), key.bold(), value.to_string().trim(), path.display());
+        }
+        ConfigCommands::Unset { key, local } => {
+            kind(&key)?;
+            let path = target(workspace_root, local)?;
+            if write_key(&path, &key, None)? {
+                say!("{} Unset {} in {}", "[OK]".green(), key.bold(), path.display());
+            } else {
+                say!("{} {} is not set in {}", "[!]".yellow(), key, path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The workspace, when there is one; the user config applies either way
+fn workspace(workspace_root: Option<PathBuf>) -> Option<PathBuf> {
+    config::resolve_workspace_root(workspace_root)
+        .ok()
+        .filter(|root| root.join(".platform").is_dir())
+}
+
+/// The file `set` and `unset` write: the user config, or with `--local`
+/// the workspace's settings
+fn target(workspace_root: Option<PathBuf>, local: bool) -> Result<PathBuf> {
+    if !local {
+        return Ok(config::user_config_path());
+    }
+    let root = config::resolve_workspace_root(workspace_root)?;
+    Ok(WorkspaceSettings::path(&root))
+}
+
+fn kind(key: &str) -> Result<Kind> {
+    if key.strip_prefix(FREE_FORM).is_some_and(|name| !name.is_empty()) {
+        return Ok(Kind::String);
+    }
+    match KEYS.iter().find(|k| k.path == key) {
+        Some(k) => Ok(k.kind),
+        None => {
+            let known: Vec<&str> = KEYS.iter().map(|k| k.path).collect();
+            anyhow::bail!("Unknown key '{}'. Known keys: {}, {}<name>", key, known.join(", "), FREE_FORM)
+        }
+    }
+}
+
+/// Checks a command-line value against the key's type
+fn parse(key: &str, kind: Kind, value: &str) -> Result<toml::Value> {
+    let invalid = |expected: &str| anyhow::anyhow!("Invalid value '{}' for {}: expected {}", value, key, expected);
+    Ok(match kind {
+        Kind::Bool => match value {
+            "true" => toml::Value::Boolean(true),
+            "false" => toml::Value::Boolean(false),
+            _ => return Err(invalid("true or false")),
+        },
+        Kind::Integer => {
+            let number: u32 = value.parse().map_err(|_| invalid("a whole number of 0 or more"))?;
+            toml::Value::Integer(number.into())
+        }
+        Kind::String => toml::Value::String(value.to_string()),
+        Kind::List => toml::Value::Array(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        ),
+    })
+}
+
+/// How `get` prints a value, in the form `set` takes it
+fn display(value: &toml::Value, kind: Kind) -> String {
+    match (value, kind) {
+        (toml::Value::String(s), _) => s.clone(),
+        (toml::Value::Array(items), Kind::List) => items
+            .iter()
+            .map(|item| item.as_str().map(str::to_string).unwrap_or_else(|| item.to_string()))
+            .collect::<Vec<_>>()
+            .join(","),
+        (value, _) => value.to_string(),
+    }
+}
+
+/// Every key with a value, and which layer it comes from
+fn effective(workspace_root: Option<&Path>) -> Result<BTreeMap<String, Setting>> {
+    let mut defaults = toml::Table::new();
+    defaults.insert("dev".to_string(), toml::Value::try_from(DevSettings::default())?);
+    defaults.insert("build".to_string(), toml::Value::try_from(BuildSettings::default())?);
+    defaults.insert("notifications".to_string(), toml::Value::try_from(NotificationSettings::default())?);
+    defaults.insert("compose_profiles".to_string(), toml::Value::Array(Vec::new()));
+    defaults.insert("features".to_string(), toml::Value::Array(Vec::new()));
+
+    let mut layers = vec![(Source::Default, defaults), (Source::User, config::read_table(&config::user_config_path())?)];
+    if let Some(root) = workspace_root {
+        // Fails on values of the wrong type, e.g. from hand edits
+        WorkspaceSettings::layered(root)?;
+        layers.push((Source::Workspace, config::read_table(&WorkspaceSettings::path(root))?));
+    }
+
+    let mut settings = BTreeMap::new();
+    for (source, table) in layers {
+        for key in KEYS {
+            if let Some(value) = lookup(&table, key.path) {
+                settings.insert(key.path.to_string(), Setting { value: value.clone(), source });
+            }
+        }
+        if let Some(toml::Value::Table(values)) = table.get("config") {
+            for (name, value) in values {
+                settings.insert(format!("{}{}", FREE_FORM, name), Setting { value: value.clone(), source });
+            }
+        }
+    }
+    Ok(settings)
+}
+
+fn segments(key: &str) -> Vec<&str> {
+    match key.strip_prefix(FREE_FORM) {
+        Some(name) => vec!["config", name],
+        None => key.split('.').collect(),
+    }
+}
+
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let segments = segments(key);
+    let (last, parents) = segments.split_last()?;
+    let mut table = table;
+    for segment in parents {
+        table = table.get(*segment)?.as_table()?;
+    }
+    table.get(*last)
+}
+
+/// Sets or, with `None`, removes a key in a settings file, keeping the rest
+/// of it as written. Returns whether anything changed.
+fn write_key(path: &Path, key: &str, value: Option<toml::Value>) -> Result<bool> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let mut doc: toml_edit::DocumentMut = content.parse().with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let segments = segments(key);
+    let (last, parents) = segments.split_last().context("Empty key")?;
+    let mut table = doc.as_table_mut();
+    for segment in parents {
+        if value.is_none() && !table.contains_key(segment) {
+            return Ok(false);
+        }
+        table = table
+            .entry(segment)
+            .or_insert_with(toml_edit::table)
+            .as_table_mut()
+            .with_context(|| format!("{} in {} is not a table", segment, path.display()))?;
+    }
+    let changed = match &value {
+        Some(value) => {
+            table.insert(last, toml_edit::value(to_edit(value)));
+            true
+        }
+        None => table.remove(last).is_some(),
+    };
+    if !changed {
+        return Ok(false);
+    }
+    // Don't leave `[dev]` and the like behind empty
+    if value.is_none() {
+        if let [parent, ..] = segments.as_slice() {
+            if doc.get(parent).and_then(|item| item.as_table()).is_some_and(|t| t.is_empty()) {
+                doc.remove(parent);
+            }
+        }
+    }
+
+    let content = doc.to_string();
+    toml::from_str::<WorkspaceSettings>(&content).with_context(|| format!("{} would no longer be valid", path.display()))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(true)
+}
+
+fn to_edit(value: &toml::Value) -> toml_edit::Value {
+    match value {
+        toml::Value::Boolean(b) => (*b).into(),
+        toml::Value::Integer(i) => (*i).into(),
+        toml::Value::Array(items) => items.iter().map(to_edit).collect::<toml_edit::Array>().into(),
+        other => other.as_str().unwrap_or_default().into(),
+    }
+}
+
+fn show(workspace_root: Option<&Path>, output: Option<OutputFormat>) -> Result<()> {
+    let settings = effective(workspace_root)?;
+    if let Some(format) = output {
+        return format.print(&settings);
+    }
+
+    println!("{}", "Configuration".bold());
+    println!("  {} {}", "user:".dimmed(), config::user_config_path().display());
+    match workspace_root {
+        Some(root) => println!("  {} {}", "workspace:".dimmed(), WorkspaceSettings::path(root).display()),
+        None => println!("  {} {}", "workspace:".dimmed(), "none found".dimmed()),
+    }
+    println!();
+    let width = KEYS.iter().map(|k| k.path.len()).chain(settings.keys().map(String::len)).max().unwrap_or(0);
+    for key in KEYS {
+        let value = match settings.get(key.path) {
+            Some(setting) => format!("{} {}", display(&setting.value, key.kind), source_label(setting.source)),
+            None => "(unset)".dimmed().to_string(),
+        };
+        println!("  {:width$}  {}", key.path, value, width = width);
+        println!("  {:width$}  {}", "", key.about.dimmed(), width = width);
+    }
+    for (key, setting) in settings.iter().filter(|(key, _)| key.starts_with(FREE_FORM)) {
+        println!("  {:width$}  {} {}", key, display(&setting.value, Kind::String), source_label(setting.source), width = width);
+    }
+    Ok(())
+}
+
+fn source_label(source: Source) -> colored::ColoredString {
+    match source {
+        Source::Default => "(default)".dimmed(),
+        Source::User => "(user)".cyan(),
+        Source::Workspace => "(workspace)".green(),
+    }
+}
+
+fn list_features(config: &Config) {
+    println!("{}", "Features".bold());
+    println!();
+
+    let features = config.features();
+    if features.is_empty() {
+        println!("  {}", "No optional repositories in the manifest".dimmed());
+        return;
+    }
+    for (feature, repos) in features {
+        let state = if config.settings.features.contains(&feature) {
+            "enabled".green()
+        } else {
+            "disabled".dimmed()
+        };
+        println!("  {} ({})", feature.bold(), state);
+        for repo in repos {
+            println!("    {} {}", "*".cyan(), repo);
+        }
+    }
+}
+
+fn set_features(config: &mut Config, features: &[String], enable: bool) -> Result<()> {
+    let known = config.features();
+    for feature in features {
+        if !known.contains_key(feature) {
+            let names: Vec<&str> = known.keys().map(String::as_str).collect();
+            if names.is_empty() {
+                anyhow::bail!("Unknown feature '{}'; the manifest has no optional repositories", feature);
+            }
+            anyhow::bail!("Unknown feature '{}'. Known features: {}", feature, names.join(", "));
+        }
+    }
+
+    let enabled = &mut config.settings.features;
+    for feature in features {
+        if enable && !enabled.contains(feature) {
+            enabled.push(feature.clone());
+        } else if !enable {
+            enabled.retain(|f| f != feature);
+        }
+    }
+    enabled.sort();
+    // Only the workspace's own settings; the user config stays where it is
+    let settings = WorkspaceSettings { features: enabled.clone(), ..WorkspaceSettings::load(&config.workspace_root)? };
+    settings.save(&config.workspace_root)?;
+
+    for feature in features {
+        if enable {
+            say!("{} Enabled {}: {}", "[OK]".green(), feature.bold(), known[feature].join(", "));
+        } else {
+            say!("{} Disabled {}", "[OK]".green(), feature.bold());
+        }
+    }
+    if enable {
+        println!("Run {} to clone the new repositories", "syla init".cyan());
+    }
+    Ok(())
+}
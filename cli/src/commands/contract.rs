@@ -0,0 +1,467 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::commands::dev::{host_port, service_process_config};
+use crate::config::{Config, ContractConfig, ContractProtocol, ExpectedResponse, RepositoryConfig};
+use crate::services::ProcessManager;
+use crate::ContractCommands;
+
+/// Side of a contract to verify
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ContractSide {
+    Consumer,
+    Provider,
+}
+
+/// A contract file: the requests a consumer makes and what it relies on in
+/// the responses. Response bodies are matched by shape, so their values are
+/// only examples (which the consumer stub serves back).
+#[derive(Debug, Deserialize)]
+struct ContractFile {
+    #[serde(default, rename = "interaction")]
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Interaction {
+    description: String,
+    #[serde(default = "default_method")]
+    method: String,
+    path: Option<String>,
+    body: Option<toml::Value>,
+    /// Full gRPC method name, `package.Service/Method`
+    rpc: Option<String>,
+    request: Option<toml::Value>,
+    #[serde(default)]
+    response: ExpectedResponse,
+}
+
+/// What the consumer stub answers a request with
+struct StubRoute {
+    method: String,
+    path: String,
+    status: u16,
+    body: Value,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+pub async fn run(command: ContractCommands, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    match command {
+        ContractCommands::Test { contracts, only, no_start } => test(&config, &contracts, only, no_start).await,
+    }
+}
+
+async fn test(config: &Config, names: &[String], only: Option<ContractSide>, no_start: bool) -> Result<()> {
+    if config.manifest.contracts.is_empty() {
+        println!("No contracts found. Add a [contracts.<name>] table to .platform/config/repos.toml");
+        return Ok(());
+    }
+    for name in names {
+        if !config.manifest.contracts.contains_key(name) {
+            anyhow::bail!("Contract '{}' not found in manifest", name);
+        }
+    }
+
+    let mut broken = Vec::new();
+    for (name, contract) in &config.manifest.contracts {
+        if !names.is_empty() && !names.contains(name) {
+            continue;
+        }
+        println!(
+            "\n{} {} ({} -> {}, {})",
+            "[>]".cyan(),
+            name.bold(),
+            contract.consumer,
+            contract.provider,
+            protocol_name(contract.protocol)
+        );
+
+        let file = load(config, contract)?;
+        let mut problems = 0;
+        if only != Some(ContractSide::Consumer) {
+            problems += verify_provider(config, contract, &file, no_start).await?;
+        }
+        if only != Some(ContractSide::Provider) {
+            problems += verify_consumer(config, contract, &file).await?;
+        }
+        if problems > 0 {
+            broken.push(name.clone());
+        }
+    }
+
+    println!();
+    if broken.is_empty() {
+        say!("{} All contracts hold", "[OK]".green());
+        Ok(())
+    } else {
+        anyhow::bail!("Contracts broken: {}", broken.join(", "))
+    }
+}
+
+fn load(config: &Config, contract: &ContractConfig) -> Result<ContractFile> {
+    let path = config.workspace_root.join(&contract.file);
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read contract {}", path.display()))?;
+    let file: ContractFile =
+        toml::from_str(&content).with_context(|| format!("Failed to parse contract {}", path.display()))?;
+    for interaction in &file.interactions {
+        let target = match contract.protocol {
+            ContractProtocol::Http => &interaction.path,
+            ContractProtocol::Grpc => &interaction.rpc,
+        };
+        if target.is_none() {
+            anyhow::bail!(
+                "Interaction '{}' in {} needs {}",
+                interaction.description,
+                contract.file,
+                if contract.protocol == ContractProtocol::Http { "a path" } else { "an rpc" }
+            );
+        }
+    }
+    Ok(file)
+}
+
+fn protocol_name(protocol: ContractProtocol) -> &'static str {
+    match protocol {
+        ContractProtocol::Http => "http",
+        ContractProtocol::Grpc => "grpc",
+    }
+}
+
+fn repository<'a>(config: &'a Config, name: &str) -> Result<&'a RepositoryConfig> {
+    config
+        .manifest
+        .repositories
+        .get(name)
+        .with_context(|| format!("Repository '{}' not found in manifest", name))
+}
+
+/// Replays every interaction against the real provider, starting it first
+/// when it isn't already up. Returns the number of broken interactions.
+async fn verify_provider(config: &Config, contract: &ContractConfig, file: &ContractFile, no_start: bool) -> Result<usize> {
+    let repo = repository(config, &contract.provider)?;
+    let port = repo
+        .ports
+        .first()
+        .and_then(|p| host_port(p))
+        .with_context(|| format!("{} has no ports in the manifest", contract.provider))?
+        .to_string();
+
+    let started = if is_ready(repo, &port).await {
+        None
+    } else if no_start {
+        println!("  {} {} is not running", "[X]".red(), contract.provider);
+        return Ok(file.interactions.len());
+    } else {
+        match start_until_ready(config, &contract.provider, repo, &port).await {
+            Ok(manager) => Some(manager),
+            Err(e) => {
+                println!("  {} Could not start {}: {:#}", "[X]".red(), contract.provider, e);
+                return Ok(file.interactions.len());
+            }
+        }
+    };
+
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?;
+    let mut broken = 0;
+    for interaction in &file.interactions {
+        let problems = match contract.protocol {
+            ContractProtocol::Http => {
+                let path = interaction.path.as_deref().unwrap_or("/");
+                check_http(&client, &port, &interaction.method, path, interaction.body.as_ref(), &interaction.response).await
+            }
+            ContractProtocol::Grpc => {
+                let rpc = interaction.rpc.as_deref().unwrap_or_default();
+                check_grpc(&port, rpc, interaction.request.as_ref(), &interaction.response).await
+            }
+        };
+        if problems.is_empty() {
+            say!("  {} provider: {}", "[OK]".green(), interaction.description);
+        } else {
+            broken += 1;
+            println!("  {} provider: {}", "[X]".red(), interaction.description);
+            for problem in problems {
+                println!("      {}", problem);
+            }
+        }
+    }
+
+    if let Some(manager) = started {
+        manager.stop_service(&contract.provider, false)?;
+    }
+    Ok(broken)
+}
+
+pub(crate) async fn is_ready(repo: &RepositoryConfig, port: &str) -> bool {
+    match repo.health_check.as_deref().filter(|url| url.starts_with("http")) {
+        Some(url) => reqwest::get(url).await.is_ok_and(|r| r.status().is_success()),
+        None => TcpStream::connect(format!("127.0.0.1:{}", port)).await.is_ok(),
+    }
+}
+
+/// Starts a service and waits for it to pass its health check, or accept
+/// connections on `port` without one
+pub(crate) async fn start_until_ready(config: &Config, name: &str, repo: &RepositoryConfig, port: &str) -> Result<ProcessManager> {
+    let profile = config.build_profile(None);
+    let process_config = service_process_config(config, name, repo, profile)?
+        .with_context(|| format!("no {} build; run `syla dev build-changed`", profile.name()))?;
+    let timeout = process_config.startup_timeout;
+
+    let manager = ProcessManager::new(config.clone());
+    manager.start_service(process_config)?;
+    let started = Instant::now();
+    while !is_ready(repo, port).await {
+        if started.elapsed() > timeout {
+            manager.stop_service(name, true)?;
+            anyhow::bail!("not ready after {}s", timeout.as_secs());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    Ok(manager)
+}
+
+/// Sends a request and lists how the response falls short of `expected`
+pub(crate) async fn check_http(
+    client: &reqwest::Client,
+    port: &str,
+    method: &str,
+    path: &str,
+    body: Option<&toml::Value>,
+    expected: &ExpectedResponse,
+) -> Vec<String> {
+    let Ok(method) = reqwest::Method::from_bytes(method.to_uppercase().as_bytes()) else {
+        return vec![format!("invalid method '{}'", method)];
+    };
+    let url = format!("http://localhost:{}{}", port, path);
+    let mut request = client.request(method, url);
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => return vec![format!("request failed: {}", e)],
+    };
+
+    let mut problems = Vec::new();
+    let status = expected.status.unwrap_or(200);
+    if response.status().as_u16() != status {
+        problems.push(format!("status {}, expected {}", response.status().as_u16(), status));
+    }
+    if let Some(expected) = &expected.body {
+        match response.json::<Value>().await {
+            Ok(actual) => compare(&json(expected), &actual, "$", &mut problems),
+            Err(_) => problems.push("response body is not JSON".to_string()),
+        }
+    }
+    problems
+}
+
+/// Calls `rpc` with grpcurl and lists how the response falls short of
+/// `expected`
+pub(crate) async fn check_grpc(port: &str, rpc: &str, request: Option<&toml::Value>, expected: &ExpectedResponse) -> Vec<String> {
+    if which::which("grpcurl").is_err() {
+        return vec!["grpcurl is required for gRPC probes: https://github.com/fullstorydev/grpcurl".to_string()];
+    }
+    let request = request.map(json).unwrap_or_else(|| Value::Object(Default::default()));
+    let output = tokio::process::Command::new("grpcurl")
+        .args(["-plaintext", "-format", "json", "-d"])
+        .arg(request.to_string())
+        .arg(format!("localhost:{}", port))
+        .arg(rpc)
+        .output()
+        .await;
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => return vec![format!("failed to run grpcurl: {}", e)],
+    };
+
+    let mut problems = Vec::new();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let code = if output.status.success() {
+        "OK".to_string()
+    } else {
+        match stderr.lines().find_map(|line| line.trim().strip_prefix("Code: ")) {
+            Some(code) => code.to_string(),
+            None => return vec![stderr.trim().to_string()],
+        }
+    };
+    let expected_code = expected.code.as_deref().unwrap_or("OK");
+    if !code.eq_ignore_ascii_case(expected_code) {
+        problems.push(format!("code {}, expected {}", code, expected_code));
+    }
+    if let (true, Some(expected)) = (output.status.success(), &expected.body) {
+        match serde_json::from_slice::<Value>(&output.stdout) {
+            Ok(actual) => compare(&json(expected), &actual, "$", &mut problems),
+            Err(_) => problems.push("response is not JSON".to_string()),
+        }
+    }
+    problems
+}
+
+fn json(value: &toml::Value) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Checks that `actual` has every field `expected` has, with the same JSON
+/// type. Array elements are checked against the first expected element.
+fn compare(expected: &Value, actual: &Value, path: &str, problems: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected) in expected {
+                let path = format!("{}.{}", path, key);
+                match actual.get(key) {
+                    Some(actual) => compare(expected, actual, &path, problems),
+                    None => problems.push(format!("{} is missing", path)),
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            if let Some(expected) = expected.first() {
+                for (i, actual) in actual.iter().enumerate() {
+                    compare(expected, actual, &format!("{}[{}]", path, i), problems);
+                }
+            }
+        }
+        _ if type_name(expected) != type_name(actual) => problems.push(format!(
+            "{} is {}, expected {}",
+            path,
+            type_name(actual),
+            type_name(expected)
+        )),
+        _ => {}
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Runs the consumer's suite against a stub that answers with the
+/// contract's example responses. Requests the contract doesn't cover are
+/// incompatibilities. Returns the number of problems found.
+async fn verify_consumer(config: &Config, contract: &ContractConfig, file: &ContractFile) -> Result<usize> {
+    let Some(command) = &contract.consumer_command else {
+        return Ok(0);
+    };
+    if contract.protocol == ContractProtocol::Grpc {
+        println!("  {} consumer: gRPC stubs aren't supported, skipping consumer suite", "[!]".yellow());
+        return Ok(0);
+    }
+    let repo = repository(config, &contract.consumer)?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    let routes = Arc::new(
+        file.interactions
+            .iter()
+            .map(|i| StubRoute {
+                method: i.method.to_uppercase(),
+                path: i.path.clone().unwrap_or_default(),
+                status: i.response.status.unwrap_or(200),
+                body: i.response.body.as_ref().map(json).unwrap_or(Value::Null),
+            })
+            .collect::<Vec<_>>(),
+    );
+    let unmatched = Arc::new(Mutex::new(Vec::new()));
+    let stub = tokio::spawn(serve_stub(listener, routes, unmatched.clone()));
+
+    let status = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(config.workspace_root.join(&repo.path))
+        .env("SYLA_CONTRACT_PROVIDER_URL", &url)
+        .status()
+        .await
+        .with_context(|| format!("Failed to run consumer suite for {}", contract.consumer))?;
+    stub.abort();
+
+    let unmatched = unmatched.lock().unwrap();
+    let mut problems = unmatched.len();
+    for request in unmatched.iter() {
+        println!("  {} consumer: {} is not in the contract", "[X]".red(), request);
+    }
+    if status.success() {
+        say!("  {} consumer: suite passed against the stub", "[OK]".green());
+    } else {
+        problems += 1;
+        println!("  {} consumer: suite failed against the stub ({})", "[X]".red(), status);
+    }
+    Ok(problems)
+}
+
+async fn serve_stub(
+    listener: TcpListener,
+    routes: Arc<Vec<StubRoute>>,
+    unmatched: Arc<Mutex<Vec<String>>>,
+) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let routes = routes.clone();
+        let unmatched = unmatched.clone();
+        tokio::spawn(async move {
+            let _ = answer(stream, &routes, &unmatched).await;
+        });
+    }
+}
+
+async fn answer(
+    stream: TcpStream,
+    routes: &[StubRoute],
+    unmatched: &Mutex<Vec<String>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default().to_string();
+
+    // Drain headers and body so the client sees a clean response
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let (status, body) = match routes.iter().find(|r| r.method == method && r.path == path) {
+        Some(route) => (route.status, route.body.to_string()),
+        None => {
+            unmatched.lock().unwrap().push(format!("{} {}", method, path));
+            (404, r#"{"error":"not in contract"}"#.to_string())
+        }
+    };
+    let response = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    reader.get_mut().write_all(response.as_bytes()).await
+}
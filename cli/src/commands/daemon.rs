@@ -0,0 +1,212 @@
+//! `syla daemon`: the supervisor of `dev supervise`, run in the background
+//! so the services it starts outlive the command that asked for them.
+//!
+//! The daemon listens on `.platform/state/control.sock` and writes its
+//! output to `.logs/daemon.log`. `dev up --detach` starts it when it isn't
+//! running; `dev down`, `dev restart` and `dev status` use it when it is.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::commands::OutputFormat;
+use crate::config::Config;
+use crate::control::{self, Client, Request, ServiceInfo, SupervisorInfo};
+use crate::ports;
+use crate::tunnels::is_alive;
+use crate::DaemonCommands;
+
+/// How long `daemon start` waits for the socket to answer
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long `daemon stop` waits; each service gets 5 seconds to exit
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(120);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// `syla daemon status` in the shape `--output` prints
+#[derive(Debug, Serialize)]
+pub struct DaemonStatus {
+    pub running: bool,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub info: Option<SupervisorInfo>,
+    /// Services the daemon started
+    pub services: Vec<ServiceInfo>,
+}
+
+pub async fn run(command: DaemonCommands, output: Option<OutputFormat>, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    match command {
+        DaemonCommands::Start => {
+            if let Some(mut client) = Client::connect(&config.workspace_root).await {
+                let info = info(&mut client).await?;
+                say!("{} The daemon is already running (pid {})", "[OK]".green(), info.pid);
+                return Ok(());
+            }
+            let mut client = start(&config).await?;
+            let info = info(&mut client).await?;
+            say!("{} Daemon started (pid {})", "[OK]".green(), info.pid);
+            say!("  Log: {}", log_path(&config).display());
+        }
+        DaemonCommands::Stop => stop(&config).await?,
+        DaemonCommands::Status => status(&config, output).await?,
+        DaemonCommands::Run => control::serve(ports::allocate(&config)?, None).await?,
+    }
+    Ok(())
+}
+
+fn log_path(config: &Config) -> PathBuf {
+    config.workspace_root.join(".logs/daemon.log")
+}
+
+async fn info(client: &mut Client) -> Result<SupervisorInfo> {
+    Ok(serde_json::from_value(client.call(&Request::Info).await?)?)
+}
+
+/// Connects to the workspace's daemon, starting one if none is running
+pub(crate) async fn connect_or_start(config: &Config) -> Result<Client> {
+    match Client::connect(&config.workspace_root).await {
+        Some(client) => Ok(client),
+        None => {
+            let client = start(config).await?;
+            say!("  Started the daemon; its log is {}", log_path(config).display());
+            Ok(client)
+        }
+    }
+}
+
+/// Spawns `syla daemon run` detached from this process and waits until it
+/// answers on its socket
+async fn start(config: &Config) -> Result<Client> {
+    let mut child = spawn(config)?;
+    let started = Instant::now();
+    loop {
+        if let Some(client) = Client::connect(&config.workspace_root).await {
+            return Ok(client);
+        }
+        if let Some(status) = child.try_wait()? {
+            let log = std::fs::read_to_string(log_path(config)).unwrap_or_default();
+            let tail: Vec<&str> = log.lines().rev().take(10).collect();
+            anyhow::bail!(
+                "The daemon exited ({}) before it was ready:\n{}",
+                status,
+                tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+            );
+        }
+        if started.elapsed() > STARTUP_TIMEOUT {
+            let _ = child.kill();
+            anyhow::bail!(
+                "The daemon didn't answer within {}s; see {}",
+                STARTUP_TIMEOUT.as_secs(),
+                log_path(config).display()
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn spawn(config: &Config) -> Result<Child> {
+    let log_path = log_path(config);
+    if let Some(dir) = log_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .with_context(|| format!("Failed to open {}", log_path.display()))?;
+
+    let exe = std::env::current_exe().context("Failed to find the syla executable")?;
+    let mut cmd = Command::new(exe);
+    cmd.args(["daemon", "run", "--non-interactive", "--workspace"])
+        .arg(&config.workspace_root)
+        // The stream belongs to the command that asked for it
+        .env_remove("SYLA_EVENTS")
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Keep running after this command returns, and out of its Ctrl+C
+        cmd.process_group(0);
+    }
+    cmd.spawn().context("Failed to start the daemon")
+}
+
+async fn stop(config: &Config) -> Result<()> {
+    let Some(mut client) = Client::connect(&config.workspace_root).await else {
+        say!("{} The daemon is not running", "[!]".yellow());
+        return Ok(());
+    };
+    let info = info(&mut client).await?;
+    say!("Stopping the daemon (pid {})...", info.pid);
+    client.call(&Request::Shutdown).await?;
+
+    let started = Instant::now();
+    while is_alive(info.pid) {
+        if started.elapsed() > SHUTDOWN_TIMEOUT {
+            anyhow::bail!("The daemon (pid {}) is still stopping services after {}s", info.pid, SHUTDOWN_TIMEOUT.as_secs());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    say!("{} Daemon stopped", "[OK]".green());
+    Ok(())
+}
+
+/// The daemon's status, or `None` when it isn't running
+pub(crate) async fn collect(config: &Config) -> Result<Option<(SupervisorInfo, Vec<ServiceInfo>)>> {
+    let Some(mut client) = Client::connect(&config.workspace_root).await else {
+        return Ok(None);
+    };
+    let info = info(&mut client).await?;
+    let services: Vec<ServiceInfo> = serde_json::from_value(client.call(&Request::List).await?)?;
+    Ok(Some((info, services.into_iter().filter(|service| service.managed).collect())))
+}
+
+async fn status(config: &Config, output: Option<OutputFormat>) -> Result<()> {
+    let status = match collect(config).await? {
+        Some((info, services)) => DaemonStatus { running: true, info: Some(info), services },
+        None => DaemonStatus { running: false, info: None, services: Vec::new() },
+    };
+    if let Some(format) = output {
+        return format.print(&status);
+    }
+
+    let Some(info) = &status.info else {
+        println!("{} The daemon is not running; start it with {}", "[!]".yellow(), "syla daemon start".cyan());
+        return Ok(());
+    };
+    println!("{}", "Daemon".bold());
+    println!();
+    println!("  Status:  {}", "running".green());
+    println!("  PID:     {}", info.pid);
+    println!("  Socket:  {}", info.socket.display());
+    println!("  Since:   {}", info.started_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"));
+    println!("  Log:     {}", log_path(config).display());
+    println!();
+    if status.services.is_empty() {
+        println!("{}", "No services supervised".dimmed());
+    } else {
+        println!("{}", "Services:".bold());
+        print_services(&status.services);
+    }
+    Ok(())
+}
+
+/// One line per supervised service with its state, pid and restarts
+pub(crate) fn print_services(services: &[ServiceInfo]) {
+    for service in services {
+        let state = match service.state.as_str() {
+            "running" => service.state.green(),
+            "failed" => service.state.red(),
+            _ => service.state.yellow(),
+        };
+        let mut detail = service.pid.map(|pid| format!("pid {}", pid)).unwrap_or_default();
+        if service.restarts > 0 {
+            detail.push_str(&format!(", restarted {}x", service.restarts));
+        }
+        println!("  {} {} {}", service.name.bold(), state, detail.dimmed());
+    }
+}
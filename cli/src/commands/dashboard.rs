@@ -0,0 +1,627 @@
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+
+use crate::commands::daemon;
+use crate::commands::dev::service_log_file;
+use crate::commands::status::check_health;
+use crate::config::Config;
+use crate::control::{self, Request, ServiceInfo};
+use crate::git;
+use crate::history;
+use crate::services::state::StartedServices;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+const LOG_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Bytes read from the end of a log file for the log pane
+const LOG_TAIL_BYTES: u64 = 64 * 1024;
+
+pub async fn run(workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+
+    let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+    let refresher = tokio::spawn(refresh_loop(config.clone(), snapshot.clone()));
+
+    let mut terminal = ratatui::init();
+    let mut app = App::new(config, snapshot);
+    let outcome = app.run(&mut terminal);
+    ratatui::restore();
+
+    refresher.abort();
+    outcome
+}
+
+#[derive(Default, Clone)]
+struct Snapshot {
+    repos: Vec<RepoRow>,
+    services: Vec<ServiceRow>,
+    infra: Vec<InfraRow>,
+    infra_error: Option<String>,
+    refreshed_at: Option<chrono::DateTime<chrono::Local>>,
+}
+
+#[derive(Clone)]
+struct RepoRow {
+    name: String,
+    branch: String,
+    status: RepoStatus,
+}
+
+#[derive(Clone)]
+enum RepoStatus {
+    NotCloned,
+    Clean,
+    Changes(usize),
+    Error,
+}
+
+#[derive(Clone)]
+struct ServiceRow {
+    name: String,
+    ports: String,
+    /// As the daemon reports it, or from `dev up`'s records without one
+    state: String,
+    healthy: Option<bool>,
+}
+
+#[derive(Clone)]
+struct InfraRow {
+    service: String,
+    state: String,
+    health: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pane {
+    Repos,
+    Services,
+    Infra,
+    Logs,
+}
+
+impl Pane {
+    fn next(self) -> Self {
+        match self {
+            Pane::Repos => Pane::Services,
+            Pane::Services => Pane::Infra,
+            Pane::Infra => Pane::Logs,
+            Pane::Logs => Pane::Repos,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Pane::Repos => Pane::Logs,
+            Pane::Services => Pane::Repos,
+            Pane::Infra => Pane::Services,
+            Pane::Logs => Pane::Infra,
+        }
+    }
+}
+
+struct App {
+    config: Config,
+    snapshot: Arc<Mutex<Snapshot>>,
+    focus: Pane,
+    repos: TableState,
+    services: TableState,
+    infra: TableState,
+    /// Service whose log is shown
+    log_service: Option<String>,
+    log_lines: Vec<String>,
+    /// Lines scrolled up from the end; 0 follows new output
+    log_scroll: usize,
+    message: Option<String>,
+    actions: (mpsc::Sender<String>, mpsc::Receiver<String>),
+}
+
+impl App {
+    fn new(config: Config, snapshot: Arc<Mutex<Snapshot>>) -> Self {
+        Self {
+            config,
+            snapshot,
+            focus: Pane::Services,
+            repos: TableState::default().with_selected(0),
+            services: TableState::default().with_selected(0),
+            infra: TableState::default().with_selected(0),
+            log_service: None,
+            log_lines: Vec::new(),
+            log_scroll: 0,
+            message: None,
+            actions: mpsc::channel(),
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let mut last_log_read = Instant::now() - LOG_REFRESH_INTERVAL;
+
+        loop {
+            if last_log_read.elapsed() >= LOG_REFRESH_INTERVAL {
+                self.read_log();
+                last_log_read = Instant::now();
+            }
+
+            // Starting the daemon prints progress; repaint over it
+            while let Ok(message) = self.actions.1.try_recv() {
+                self.message = Some(message);
+                terminal.clear()?;
+            }
+
+            let snapshot = self.snapshot.lock().unwrap().clone();
+            terminal.draw(|frame| self.draw(frame, &snapshot))?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') => return Ok(()),
+                KeyCode::Esc if self.focus == Pane::Logs => self.focus = Pane::Services,
+                KeyCode::Esc => return Ok(()),
+                KeyCode::Tab => self.focus = self.focus.next(),
+                KeyCode::BackTab => self.focus = self.focus.previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection(&snapshot, 1),
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection(&snapshot, -1),
+                KeyCode::End | KeyCode::Char('G') => self.log_scroll = 0,
+                KeyCode::Enter | KeyCode::Char('l') => self.show_logs(&snapshot),
+                KeyCode::Char('s') => self.act(&snapshot, Action::Start),
+                KeyCode::Char('x') => self.act(&snapshot, Action::Stop),
+                KeyCode::Char('r') => self.act(&snapshot, Action::Restart),
+                _ => {}
+            }
+        }
+    }
+
+    fn move_selection(&mut self, snapshot: &Snapshot, delta: isize) {
+        let (state, len) = match self.focus {
+            Pane::Repos => (&mut self.repos, snapshot.repos.len()),
+            Pane::Services => (&mut self.services, snapshot.services.len()),
+            Pane::Infra => (&mut self.infra, snapshot.infra.len()),
+            Pane::Logs => {
+                self.log_scroll = if delta > 0 {
+                    self.log_scroll.saturating_sub(1)
+                } else {
+                    (self.log_scroll + 1).min(self.log_lines.len())
+                };
+                return;
+            }
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + delta).clamp(0, len as isize - 1) as usize));
+    }
+
+    fn selected_service<'a>(&self, snapshot: &'a Snapshot) -> Option<&'a ServiceRow> {
+        snapshot.services.get(self.services.selected()?)
+    }
+
+    fn show_logs(&mut self, snapshot: &Snapshot) {
+        if self.focus != Pane::Services {
+            return;
+        }
+        if let Some(service) = self.selected_service(snapshot) {
+            self.log_service = Some(service.name.clone());
+            self.log_scroll = 0;
+            self.focus = Pane::Logs;
+            self.read_log();
+        }
+    }
+
+    fn read_log(&mut self) {
+        self.log_lines = match &self.log_service {
+            Some(name) => tail(&service_log_file(&self.config, name)),
+            None => Vec::new(),
+        };
+    }
+
+    fn act(&mut self, snapshot: &Snapshot, action: Action) {
+        match self.focus {
+            Pane::Services => {
+                let Some(service) = self.selected_service(snapshot) else {
+                    return;
+                };
+                let name = service.name.clone();
+                let config = self.config.clone();
+                self.spawn_action(format!("{} {}...", action.verb(), name), async move {
+                    service_action(&config, &name, action).await
+                });
+            }
+            Pane::Infra => {
+                let Some(row) = self.infra.selected().and_then(|i| snapshot.infra.get(i)) else {
+                    return;
+                };
+                let service = row.service.clone();
+                let root = self.config.workspace_root.clone();
+                self.spawn_action(format!("{} {}...", action.verb(), service), async move {
+                    compose_action(&root, &service, action).await
+                });
+            }
+            Pane::Repos | Pane::Logs => {}
+        }
+    }
+
+    fn spawn_action(&mut self, pending: String, action: impl Future<Output = Result<String>> + Send + 'static) {
+        self.message = Some(pending);
+        let sender = self.actions.0.clone();
+        tokio::spawn(async move {
+            let message = action.await.unwrap_or_else(|e| format!("[X] {}", e));
+            let _ = sender.send(message);
+        });
+    }
+
+    fn draw(&mut self, frame: &mut Frame, snapshot: &Snapshot) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(35),
+                Constraint::Length((snapshot.infra.len().max(1) + 3).min(10) as u16),
+                Constraint::Min(5),
+                Constraint::Length(1),
+            ])
+            .split(frame.area());
+        let top = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .split(rows[0]);
+
+        self.draw_repos(frame, top[0], snapshot);
+        self.draw_services(frame, top[1], snapshot);
+        self.draw_infra(frame, rows[1], snapshot);
+        self.draw_logs(frame, rows[2]);
+        self.draw_footer(frame, rows[3], snapshot);
+    }
+
+    fn block(&self, title: String, pane: Pane) -> Block<'static> {
+        let style = if self.focus == pane {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(style)
+            .title(title)
+    }
+
+    fn draw_repos(&mut self, frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+        let rows = snapshot.repos.iter().map(|repo| {
+            let status = match repo.status {
+                RepoStatus::NotCloned => Cell::from("Not cloned").style(Style::default().fg(Color::Red)),
+                RepoStatus::Clean => Cell::from("Clean").style(Style::default().fg(Color::Green)),
+                RepoStatus::Changes(n) => {
+                    Cell::from(format!("{} changes", n)).style(Style::default().fg(Color::Yellow))
+                }
+                RepoStatus::Error => Cell::from("Not a git repo").style(Style::default().fg(Color::Red)),
+            };
+            Row::new(vec![Cell::from(repo.name.clone()), Cell::from(repo.branch.clone()), status])
+        });
+        let table = Table::new(rows, [Constraint::Percentage(40), Constraint::Percentage(35), Constraint::Percentage(25)])
+            .header(header(["Repository", "Branch", "Status"]))
+            .block(self.block(" Repositories ".to_string(), Pane::Repos))
+            .row_highlight_style(highlight(self.focus == Pane::Repos));
+        frame.render_stateful_widget(table, area, &mut self.repos);
+    }
+
+    fn draw_services(&mut self, frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+        let rows = snapshot.services.iter().map(|service| {
+            let state = Cell::from(service.state.clone());
+            let state = match service.state.as_str() {
+                "running" => state.style(Style::default().fg(Color::Green)),
+                "failed" => state.style(Style::default().fg(Color::Red)),
+                "stopped" => state.style(Style::default().fg(Color::DarkGray)),
+                _ => state,
+            };
+            let health = match service.healthy {
+                Some(true) => Cell::from("Healthy").style(Style::default().fg(Color::Green)),
+                Some(false) => Cell::from("Unhealthy").style(Style::default().fg(Color::Red)),
+                None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+            };
+            Row::new(vec![Cell::from(service.name.clone()), state, health, Cell::from(service.ports.clone())])
+        });
+        let table = Table::new(
+            rows,
+            [Constraint::Percentage(40), Constraint::Percentage(20), Constraint::Percentage(20), Constraint::Percentage(20)],
+        )
+        .header(header(["Service", "Process", "Health", "Ports"]))
+        .block(self.block(" Services ".to_string(), Pane::Services))
+        .row_highlight_style(highlight(self.focus == Pane::Services));
+        frame.render_stateful_widget(table, area, &mut self.services);
+    }
+
+    fn draw_infra(&mut self, frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+        let block = self.block(" Infrastructure ".to_string(), Pane::Infra);
+        if let Some(error) = &snapshot.infra_error {
+            let text = Paragraph::new(error.clone()).style(Style::default().fg(Color::Yellow));
+            frame.render_widget(text.block(block), area);
+            return;
+        }
+        let rows = snapshot.infra.iter().map(|row| {
+            let color = if row.state == "running" { Color::Green } else { Color::Red };
+            Row::new(vec![
+                Cell::from(row.service.clone()),
+                Cell::from(row.state.clone()).style(Style::default().fg(color)),
+                Cell::from(row.health.clone()),
+            ])
+        });
+        let table = Table::new(rows, [Constraint::Percentage(40), Constraint::Percentage(30), Constraint::Percentage(30)])
+            .header(header(["Container", "State", "Health"]))
+            .block(block)
+            .row_highlight_style(highlight(self.focus == Pane::Infra));
+        frame.render_stateful_widget(table, area, &mut self.infra);
+    }
+
+    fn draw_logs(&mut self, frame: &mut Frame, area: Rect) {
+        let title = match &self.log_service {
+            Some(name) if self.log_scroll > 0 => format!(" Logs: {} (scrolled, End to follow) ", name),
+            Some(name) => format!(" Logs: {} ", name),
+            None => " Logs (select a service and press Enter) ".to_string(),
+        };
+        let height = area.height.saturating_sub(2) as usize;
+        let end = self.log_lines.len().saturating_sub(self.log_scroll);
+        let start = end.saturating_sub(height);
+        let lines: Vec<Line> = if self.log_service.is_some() && self.log_lines.is_empty() {
+            vec![Line::from(Span::styled("No output yet", Style::default().fg(Color::DarkGray)))]
+        } else {
+            self.log_lines[start..end].iter().map(|l| Line::from(l.as_str())).collect()
+        };
+        frame.render_widget(Paragraph::new(lines).block(self.block(title, Pane::Logs)), area);
+    }
+
+    fn draw_footer(&self, frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+        let keys = "q quit  tab pane  j/k move  enter logs  s start  x stop  r restart";
+        let status = match (&self.message, snapshot.refreshed_at) {
+            (Some(message), _) => message.clone(),
+            (None, Some(at)) => format!("updated {}", at.format("%H:%M:%S")),
+            (None, None) => "loading...".to_string(),
+        };
+        let line = Line::from(vec![
+            Span::styled(keys, Style::default().fg(Color::DarkGray)),
+            Span::raw("  "),
+            Span::styled(status, Style::default().add_modifier(Modifier::BOLD)),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl Action {
+    fn verb(self) -> &'static str {
+        match self {
+            Action::Start => "Starting",
+            Action::Stop => "Stopping",
+            Action::Restart => "Restarting",
+        }
+    }
+
+    fn done(self) -> &'static str {
+        match self {
+            Action::Start => "started",
+            Action::Stop => "stopped",
+            Action::Restart => "restarted",
+        }
+    }
+}
+
+/// Starts and stops go through the daemon, like `dev up --detach`, so the
+/// services outlive the dashboard
+async fn service_action(config: &Config, name: &str, action: Action) -> Result<String> {
+    let service = name.to_string();
+    let request = match action {
+        Action::Start => Request::Start { service, profile: None },
+        Action::Stop => Request::Stop { service },
+        Action::Restart => Request::Restart { service },
+    };
+    daemon::connect_or_start(config).await?.caller("dashboard").call(&request).await?;
+    Ok(format!("[OK] {} {}", name, action.done()))
+}
+
+async fn compose_action(workspace_root: &Path, service: &str, action: Action) -> Result<String> {
+    let verb = match action {
+        Action::Start => "start",
+        Action::Stop => "stop",
+        Action::Restart => "restart",
+    };
+    let started = Instant::now();
+    let output = tokio::process::Command::new("docker")
+        .args(["compose", verb, service])
+        .current_dir(workspace_root)
+        .output()
+        .await?;
+    let error = (!output.status.success()).then(|| {
+        format!("docker compose {} {} failed: {}", verb, service, String::from_utf8_lossy(&output.stderr).trim())
+    });
+    // Compose services don't go through the supervisor, which records the rest
+    history::record_action(workspace_root, &format!("dashboard {}", verb), vec![service.to_string()], started.elapsed(), error.clone());
+    match error {
+        None => Ok(format!("[OK] {} {}", service, action.done())),
+        Some(error) => Err(anyhow::anyhow!(error)),
+    }
+}
+
+async fn refresh_loop(config: Config, snapshot: Arc<Mutex<Snapshot>>) {
+    let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let fresh = collect(&config).await;
+        *snapshot.lock().unwrap() = fresh;
+    }
+}
+
+/// Gathers every pane's rows, running the git, health and compose checks
+/// side by side so one slow check doesn't hold up the rest
+async fn collect(config: &Config) -> Snapshot {
+    let mut repos = config.get_all_repositories();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    let services: Vec<_> = repos.iter().filter(|(_, repo)| !repo.ports.is_empty()).collect();
+
+    let repo_statuses = join_all(repos.iter().map(|(_, repo)| async {
+        let repo_path = config.workspace_root.join(&repo.path);
+        if !repo_path.exists() {
+            return ("-".to_string(), RepoStatus::NotCloned);
+        }
+        match git::status(&repo_path).await {
+            Ok(s) if s.has_changes => (s.branch, RepoStatus::Changes(s.changed_files)),
+            Ok(s) => (s.branch, RepoStatus::Clean),
+            Err(_) => ("unknown".to_string(), RepoStatus::Error),
+        }
+    }));
+    let health = join_all(services.iter().map(|(_, repo)| async {
+        let check = repo.health_check.as_ref()?;
+        Some(
+            tokio::time::timeout(Duration::from_secs(2), check_health(check))
+                .await
+                .is_ok_and(|r| r.unwrap_or(false)),
+        )
+    }));
+    let (repo_statuses, health, states, infra) =
+        tokio::join!(repo_statuses, health, service_states(config), compose_ps(&config.workspace_root));
+
+    let repo_rows = repos
+        .iter()
+        .zip(repo_statuses)
+        .map(|((name, _), (branch, status))| RepoRow { name: name.clone(), branch, status })
+        .collect();
+    let service_rows = services
+        .iter()
+        .zip(health)
+        .map(|((name, repo), healthy)| ServiceRow {
+            name: name.clone(),
+            ports: repo.ports.join(", "),
+            state: states.get(name).cloned().unwrap_or_else(|| "stopped".to_string()),
+            healthy,
+        })
+        .collect();
+    let (infra, infra_error) = match infra {
+        Ok(rows) => (rows, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+
+    Snapshot {
+        repos: repo_rows,
+        services: service_rows,
+        infra,
+        infra_error,
+        refreshed_at: Some(chrono::Local::now()),
+    }
+}
+
+/// State of each service by name, from the daemon when one is running and
+/// otherwise from what `dev up` recorded
+async fn service_states(config: &Config) -> HashMap<String, String> {
+    if let Some(mut client) = control::Client::connect(&config.workspace_root).await {
+        let listed = client.call(&Request::List).await.and_then(|v| Ok(serde_json::from_value::<Vec<ServiceInfo>>(v)?));
+        if let Ok(services) = listed {
+            return services.into_iter().map(|s| (s.name, s.state)).collect();
+        }
+    }
+    let Ok(started) = StartedServices::load(&config.workspace_root) else {
+        return HashMap::new();
+    };
+    started
+        .services
+        .iter()
+        .filter(|(_, service)| service.is_running())
+        .map(|(name, _)| (name.clone(), "running".to_string()))
+        .collect()
+}
+
+/// Containers of the workspace's compose project
+async fn compose_ps(workspace_root: &Path) -> Result<Vec<InfraRow>> {
+    if !workspace_root.join("docker-compose.yml").exists() {
+        anyhow::bail!("No docker-compose.yml in workspace");
+    }
+    let output = tokio::process::Command::new("docker")
+        .args(["compose", "ps", "--all", "--format", "json"])
+        .current_dir(workspace_root)
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!("docker compose ps failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    // Older Compose prints one JSON array, newer prints one object per line
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let containers: Vec<serde_json::Value> = match serde_json::from_str(stdout.trim()) {
+        Ok(serde_json::Value::Array(items)) => items,
+        _ => stdout.lines().filter_map(|l| serde_json::from_str(l).ok()).collect(),
+    };
+
+    let field = |c: &serde_json::Value, key: &str| c.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let mut rows: Vec<InfraRow> = containers
+        .iter()
+        .map(|c| {
+            let health = field(c, "Health");
+            InfraRow {
+                service: field(c, "Service"),
+                state: field(c, "State"),
+                health: if health.is_empty() { "-".to_string() } else { health },
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| a.service.cmp(&b.service));
+    Ok(rows)
+}
+
+/// Last lines of a log file, reading only its end
+fn tail(path: &Path) -> Vec<String> {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let start = len.saturating_sub(LOG_TAIL_BYTES);
+    if file.seek(SeekFrom::Start(start)).is_err() {
+        return Vec::new();
+    }
+    let mut bytes = Vec::new();
+    if file.read_to_end(&mut bytes).is_err() {
+        return Vec::new();
+    }
+
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    // The first line is likely cut off when reading from the middle
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+    lines
+}
+
+fn header<const N: usize>(titles: [&'static str; N]) -> Row<'static> {
+    Row::new(titles).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn highlight(focused: bool) -> Style {
+    if focused {
+        Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    }
+}
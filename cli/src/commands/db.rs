@@ -0,0 +1,415 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use dialoguer::Confirm;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tokio_postgres::{Client, NoTls};
+
+use crate::commands::dev::{service_env, workspace_database_url};
+use crate::config::{Config, MigrationTool};
+use crate::DbCommands;
+
+/// A repository's migrations and the database they run against
+struct Migrations {
+    service: String,
+    tool: MigrationTool,
+    repo_path: PathBuf,
+    dir: PathBuf,
+    database_url: String,
+}
+
+pub async fn run(command: DbCommands, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    match command {
+        DbCommands::Migrate { service } => migrate(&config, service.as_deref()).await,
+        DbCommands::Status => status(&config).await,
+        DbCommands::Reset { yes } => reset(&config, yes).await,
+    }
+}
+
+async fn migrate(config: &Config, service: Option<&str>) -> Result<()> {
+    let mut migrations = discover(config)?;
+    if let Some(service) = service {
+        let target = resolve(&migrations, service)?;
+        let required = dependencies(config, &target);
+        migrations.retain(|m| m.service == target || required.contains(&m.service));
+    }
+    if migrations.is_empty() {
+        println!("No migrations found. Add a [repositories.<name>.migrations] table to .platform/config/repos.toml");
+        return Ok(());
+    }
+
+    println!("{}", "Running database migrations...".bold());
+    apply(&migrations).await?;
+
+    say!("\n{} Migrations complete", "[OK]".green());
+    Ok(())
+}
+
+/// Runs the pending migrations of `services`, e.g. repositories that just
+/// pulled new commits. Returns how many of them have migrations.
+pub(crate) async fn migrate_repositories(config: &Config, services: &[String]) -> Result<usize> {
+    let mut migrations = discover(config)?;
+    migrations.retain(|m| services.contains(&m.service));
+    apply(&migrations).await?;
+    Ok(migrations.len())
+}
+
+async fn apply(migrations: &[Migrations]) -> Result<()> {
+    for m in migrations {
+        println!("\n{} {} {}", "[>]".cyan(), m.service.bold(), format!("({})", tool_name(m.tool)).dimmed());
+        let dir = m.dir.to_string_lossy();
+        match m.tool {
+            MigrationTool::Sqlx => run_tool(m, "sqlx", &["migrate", "run", "--source", &dir])?,
+            MigrationTool::Diesel => run_tool(m, "diesel", &["migration", "run", "--migration-dir", &dir])?,
+            MigrationTool::Sql => apply_sql(m).await?,
+        }
+    }
+    Ok(())
+}
+
+async fn status(config: &Config) -> Result<()> {
+    let migrations = discover(config)?;
+    println!("{}", "Database migrations".bold());
+    println!();
+    if migrations.is_empty() {
+        println!("  No migrations found");
+        return Ok(());
+    }
+
+    let width = migrations.iter().map(|m| m.service.len()).max().unwrap_or(0);
+    let mut clients: HashMap<String, Option<Client>> = HashMap::new();
+    for m in &migrations {
+        if !clients.contains_key(&m.database_url) {
+            let client = connect(&m.database_url).await.ok();
+            clients.insert(m.database_url.clone(), client);
+        }
+        let Some(client) = &clients[&m.database_url] else {
+            println!(
+                "  {:width$}  {:<6}  {} Cannot connect to {}",
+                m.service,
+                tool_name(m.tool),
+                "[X]".red(),
+                redact(&m.database_url),
+                width = width
+            );
+            continue;
+        };
+
+        let local = match local_migrations(m.tool, &m.dir) {
+            Ok(local) => local,
+            Err(e) => {
+                println!("  {:width$}  {:<6}  {} {:#}", m.service, tool_name(m.tool), "[!]".yellow(), e, width = width);
+                continue;
+            }
+        };
+        let applied = applied_versions(client, m).await?;
+        let version = local
+            .iter()
+            .rev()
+            .map(|(version, _)| version)
+            .find(|version| applied.contains(*version))
+            .or_else(|| applied.iter().max())
+            .map_or("none", String::as_str);
+        let pending = local.iter().filter(|(version, _)| !applied.contains(version)).count();
+        let state = if pending == 0 {
+            format!("{} up to date", "[OK]".green())
+        } else {
+            format!("{} {} pending", "[!]".yellow(), pending)
+        };
+        println!(
+            "  {:width$}  {:<6}  {:<16}  {}",
+            m.service,
+            tool_name(m.tool),
+            version,
+            state,
+            width = width
+        );
+    }
+    Ok(())
+}
+
+async fn reset(config: &Config, yes: bool) -> Result<()> {
+    let migrations = discover(config)?;
+    let mut urls: Vec<String> = migrations
+        .iter()
+        .map(|m| m.database_url.clone())
+        .chain(workspace_database_url(config))
+        .collect();
+    urls.sort();
+    urls.dedup();
+    if urls.is_empty() {
+        anyhow::bail!("No database configured; add a Postgres to [infrastructure]");
+    }
+
+    if !yes {
+        println!("This drops every table in:");
+        for url in &urls {
+            println!("  {}", redact(url));
+        }
+        let proceed = Confirm::new()
+            .with_prompt("Reset the database and re-run all migrations?")
+            .default(false)
+            .interact()?;
+        if !proceed {
+            println!("Aborted");
+            return Ok(());
+        }
+    }
+
+    for url in &urls {
+        let client = connect(url).await?;
+        client
+            .batch_execute("DROP SCHEMA public CASCADE; CREATE SCHEMA public; GRANT ALL ON SCHEMA public TO public;")
+            .await
+            .with_context(|| format!("Failed to reset {}", redact(url)))?;
+        say!("{} Reset {}", "[OK]".green(), redact(url));
+    }
+    println!();
+
+    migrate(config, None).await
+}
+
+/// Repositories with migrations, in dependency order
+fn discover(config: &Config) -> Result<Vec<Migrations>> {
+    let mut found = HashMap::new();
+    for (name, repo) in &config.manifest.repositories {
+        let repo_path = config.workspace_root.join(&repo.path);
+        let (tool, dir) = match &repo.migrations {
+            Some(migrations) => (migrations.tool, migrations.dir.clone()),
+            None if repo_path.join("diesel.toml").exists() => (MigrationTool::Diesel, "migrations".to_string()),
+            None if repo_path.join("migrations").is_dir() => (MigrationTool::Sqlx, "migrations".to_string()),
+            None => continue,
+        };
+        if !repo_path.exists() {
+            println!("{} {} is not cloned, skipping its migrations", "[!]".yellow(), name);
+            continue;
+        }
+
+        let database_url = service_env(config, repo)?
+            .remove("DATABASE_URL")
+            .or_else(|| workspace_database_url(config))
+            .with_context(|| {
+                format!(
+                    "No database for {}; add a Postgres to [infrastructure] or set its env.DATABASE_URL",
+                    name
+                )
+            })?;
+        found.insert(
+            name.clone(),
+            Migrations {
+                service: name.clone(),
+                tool,
+                dir: repo_path.join(dir),
+                repo_path,
+                database_url,
+            },
+        );
+    }
+
+    // Ordering holds through repositories without migrations too
+    let order = config.dependency_order()?;
+    Ok(order.into_iter().filter_map(|name| found.remove(&name)).collect())
+}
+
+fn resolve(migrations: &[Migrations], service: &str) -> Result<String> {
+    if let Some(m) = migrations.iter().find(|m| m.service == service) {
+        return Ok(m.service.clone());
+    }
+    let matches: Vec<&str> = migrations
+        .iter()
+        .map(|m| m.service.as_str())
+        .filter(|name| name.contains(service))
+        .collect();
+    match matches.as_slice() {
+        [name] => Ok(name.to_string()),
+        [] => anyhow::bail!("No migrations found for '{}'", service),
+        _ => anyhow::bail!("'{}' matches several services: {}", service, matches.join(", ")),
+    }
+}
+
+/// Every repository `name` depends on, directly or not
+fn dependencies(config: &Config, name: &str) -> HashSet<String> {
+    let mut found = HashSet::new();
+    let mut queue = vec![name.to_string()];
+    while let Some(current) = queue.pop() {
+        let Some(repo) = config.manifest.repositories.get(&current) else {
+            continue;
+        };
+        for dependency in &repo.depends_on {
+            if found.insert(dependency.clone()) {
+                queue.push(dependency.clone());
+            }
+        }
+    }
+    found
+}
+
+fn tool_name(tool: MigrationTool) -> &'static str {
+    match tool {
+        MigrationTool::Sqlx => "sqlx",
+        MigrationTool::Diesel => "diesel",
+        MigrationTool::Sql => "sql",
+    }
+}
+
+fn run_tool(m: &Migrations, program: &str, args: &[&str]) -> Result<()> {
+    let status = Command::new(program)
+        .args(args)
+        .current_dir(&m.repo_path)
+        .env("DATABASE_URL", &m.database_url)
+        .status()
+        .with_context(|| {
+            let package = if program == "diesel" { "diesel_cli" } else { "sqlx-cli" };
+            format!(
+                "Failed to run {}; install it with `cargo install {} --no-default-features --features postgres`",
+                program, package
+            )
+        })?;
+    if !status.success() {
+        anyhow::bail!("Migrations failed for {}", m.service);
+    }
+    Ok(())
+}
+
+/// Apply pending plain SQL migrations, each in its own transaction
+async fn apply_sql(m: &Migrations) -> Result<()> {
+    let mut client = connect(&m.database_url).await?;
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS syla_schema_migrations (
+                service TEXT NOT NULL,
+                version TEXT NOT NULL,
+                name TEXT NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                PRIMARY KEY (service, version)
+            )",
+        )
+        .await?;
+    let applied = applied_versions(&client, m).await?;
+
+    let mut count = 0;
+    for (version, path) in local_migrations(m.tool, &m.dir)? {
+        if applied.contains(&version) {
+            continue;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let sql = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let transaction = client.transaction().await?;
+        transaction
+            .batch_execute(&sql)
+            .await
+            .with_context(|| format!("Migration {} failed", name))?;
+        transaction
+            .execute(
+                "INSERT INTO syla_schema_migrations (service, version, name) VALUES ($1, $2, $3)",
+                &[&m.service, &version, &name],
+            )
+            .await?;
+        transaction.commit().await?;
+        say!("  {} {}", "[OK]".green(), name);
+        count += 1;
+    }
+    if count == 0 {
+        println!("  Already up to date");
+    }
+    Ok(())
+}
+
+/// Migrations on disk as `(version, path)`, oldest first. Versions are
+/// normalised the way each tool records them: sqlx stores numbers and
+/// diesel drops the dashes from its timestamps.
+fn local_migrations(tool: MigrationTool, dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let entries = std::fs::read_dir(dir).with_context(|| format!("No migrations directory at {}", dir.display()))?;
+
+    let mut migrations = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let is_migration = match tool {
+            MigrationTool::Diesel => path.is_dir(),
+            MigrationTool::Sqlx | MigrationTool::Sql => {
+                file_name.ends_with(".sql") && !file_name.ends_with(".down.sql")
+            }
+        };
+        if !is_migration {
+            continue;
+        }
+
+        let prefix = file_name.split('_').next().unwrap_or_default();
+        let version = match tool {
+            MigrationTool::Sqlx => match prefix.parse::<i64>() {
+                Ok(number) => number.to_string(),
+                Err(_) => continue,
+            },
+            MigrationTool::Diesel => prefix.replace('-', ""),
+            MigrationTool::Sql => file_name.trim_end_matches(".up.sql").trim_end_matches(".sql").to_string(),
+        };
+        migrations.push((version, path));
+    }
+
+    migrations.sort_by(|a, b| match (a.0.parse::<i64>(), b.0.parse::<i64>()) {
+        (Ok(x), Ok(y)) => x.cmp(&y),
+        _ => a.1.cmp(&b.1),
+    });
+    Ok(migrations)
+}
+
+/// Versions the database has recorded for a repository's migrations
+async fn applied_versions(client: &Client, m: &Migrations) -> Result<HashSet<String>> {
+    let table = match m.tool {
+        MigrationTool::Sqlx => "_sqlx_migrations",
+        MigrationTool::Diesel => "__diesel_schema_migrations",
+        MigrationTool::Sql => "syla_schema_migrations",
+    };
+    let exists: Option<String> = client
+        .query_one("SELECT to_regclass($1::text)::text", &[&table])
+        .await?
+        .get(0);
+    if exists.is_none() {
+        return Ok(HashSet::new());
+    }
+
+    let rows = match m.tool {
+        MigrationTool::Sqlx => {
+            client
+                .query("SELECT version::text FROM _sqlx_migrations WHERE success", &[])
+                .await?
+        }
+        MigrationTool::Diesel => {
+            client
+                .query("SELECT version::text FROM __diesel_schema_migrations", &[])
+                .await?
+        }
+        MigrationTool::Sql => {
+            client
+                .query("SELECT version FROM syla_schema_migrations WHERE service = $1", &[&m.service])
+                .await?
+        }
+    };
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
+async fn connect(url: &str) -> Result<Client> {
+    let (client, connection) = tokio_postgres::connect(url, NoTls)
+        .await
+        .with_context(|| format!("Failed to connect to {}", redact(url)))?;
+    tokio::spawn(async move {
+        let _ = connection.await;
+    });
+    Ok(client)
+}
+
+/// Drop the password from a connection URL before printing it
+fn redact(url: &str) -> String {
+    match (url.find("://"), url.rfind('@')) {
+        (Some(scheme), Some(at)) if at > scheme + 3 => {
+            let user = url[scheme + 3..at].split(':').next().unwrap_or_default();
+            format!("{}{}@{}", &url[..scheme + 3], user, &url[at + 1..])
+        }
+        _ => url.to_string(),
+    }
+}
@@ -1,163 +1,799 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::time::Duration;
-use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, HashMap};
 use tokio::time::interval;
 
-use crate::config::Config;
-use crate::services::{ProcessManager, ProcessConfig};
-use crate::services::process_manager::RestartPolicy;
+use crate::build_plan;
+use crate::changes::{self, BuildCache, ChangedService};
+use crate::config::{BuildProfile, Config, InfrastructureConfig, RepositoryConfig, RunIn, WatchAction};
+use crate::control;
+use crate::commands::{backup, daemon, doctor, integration, status};
+use crate::commands::validation::{CheckCategory, ValidationReport};
+use crate::commands::{ExitStatus, OutputFormat};
+use crate::docker;
+use crate::drift::{self, Drift};
+use crate::events;
+use crate::git;
+use crate::interpolation;
+use crate::lock::WorkspaceLock;
+use crate::names;
+use crate::notifications::{notify, Event};
+use crate::ports;
+use crate::runtime::{self, Runtime};
+use crate::secrets::{self, SecretStore};
+use crate::services::{container, ProcessManager, ProcessConfig};
+use crate::services::health_history::{self, ServiceReport};
+use crate::services::log_sink::LogSink;
+use crate::services::log_streamer::{LogStreamConfig, LogStreamer};
+use crate::services::process_manager::Backend;
+use crate::services::registry::{self, Registry};
+use crate::services::state::{StartedService, StartedServices};
+use crate::tunnels;
+use crate::ui;
+use crate::watch;
 use crate::DevCommands;
 
-pub async fn run(command: DevCommands, workspace_root: Option<PathBuf>) -> Result<()> {
+pub async fn run(command: DevCommands, output: Option<OutputFormat>, workspace_root: Option<PathBuf>) -> Result<()> {
     let config = Config::load(workspace_root)?;
+    let _lock = match lock_name(&command) {
+        Some(name) => Some(WorkspaceLock::acquire(&config.workspace_root, name).await?),
+        None => None,
+    };
     
     match command {
-        DevCommands::Up { platform, detach } => {
-            up(&config, platform, detach).await?;
+        DevCommands::Up { platform, detach, profile, tags } => {
+            config.check_tags(&tags)?;
+            let platform = platform.or_else(|| config.settings.dev.default_platform.clone());
+            up(&config, platform, detach, config.build_profile(profile), &tags).await?;
         }
         DevCommands::Down { volumes } => {
             down(&config, volumes).await?;
         }
-        DevCommands::Logs { service, follow, lines } => {
-            logs(&config, &service, follow, lines).await?;
+        DevCommands::Logs { service, follow, lines, grep, context, format, level } => {
+            let (service, _) = config.select_repository(service.as_deref())?;
+            let grep = grep
+                .map(|pattern| regex::Regex::new(&pattern).with_context(|| format!("Invalid --grep pattern '{}'", pattern)))
+                .transpose()?;
+            let stream_config = LogStreamConfig {
+                follow,
+                lines: Some(lines),
+                level_filter: level,
+                pattern_filter: grep,
+                context,
+                format,
+                ..Default::default()
+            };
+            logs(&config, &service, stream_config).await?;
         }
         DevCommands::Restart { service } => {
+            let (service, _) = config.select_repository(service.as_deref())?;
             restart(&config, &service).await?;
         }
         DevCommands::Status { detailed } => {
-            status(&config, detailed).await?;
+            status(&config, detailed, output).await?;
+        }
+        DevCommands::Validate { fix, integration, ci, junit } => {
+            validate(&config, fix, integration, ci, junit).await?;
+        }
+        DevCommands::Watch { services, build_only, test, test_only } => {
+            let mode = WatchMode { build: !test_only, restart: !build_only && !test_only, test: test || test_only };
+            watch(&config, services, mode).await?;
+        }
+        DevCommands::BuildChanged { all, since, dry_run, profile, tags, jobs } => {
+            config.check_tags(&tags)?;
+            build_changed(&config, all, since.as_deref(), dry_run, config.build_profile(profile), &tags, jobs).await?;
+        }
+        DevCommands::Diff { profile, apply } => {
+            diff(&config, config.build_profile(profile), apply).await?;
+        }
+        DevCommands::Doctor { service, profile } => {
+            let (service, _) = config.select_repository(service.as_deref())?;
+            doctor::service(&config, &service, config.build_profile(profile)).await?;
+        }
+        DevCommands::Envfile { service, print } => {
+            let (service, _) = config.select_repository(service.as_deref())?;
+            envfile(&config, &service, print).await?;
+        }
+        DevCommands::Expose { service, driver, port, stop } => {
+            let (service, _) = config.select_repository(service.as_deref())?;
+            if stop {
+                if tunnels::close(&config, std::slice::from_ref(&service))? == 0 {
+                    println!("{} No open tunnel for {}", "[!]".yellow(), service);
+                }
+            } else {
+                tunnels::expose(&config, &service, driver, port)?;
+            }
         }
-        DevCommands::Validate { fix, integration } => {
-            validate(&config, fix, integration).await?;
+        DevCommands::Backup { name } => {
+            backup::backup(&config, name).await?;
         }
-        DevCommands::Watch { services, build_only } => {
-            watch(&config, services, build_only).await?;
+        DevCommands::Restore { name, yes } => {
+            backup::restore(&config, name, yes).await?;
         }
-        DevCommands::BuildChanged { all } => {
-            build_changed(&config, all).await?;
+        DevCommands::Supervise { socket } => {
+            control::serve(ports::allocate(&config)?, socket).await?;
+        }
+        DevCommands::Report { since } => {
+            report(&config, &since, output)?;
         }
     }
     Ok(())
 }
 
-async fn up(config: &Config, platform: Option<String>, detach: bool) -> Result<()> {
-    println!("{}", "Starting development environment...".bold());
-    
+/// Commands that clone, build, or start and stop services, and so hold the
+/// workspace lock while they run
+fn lock_name(command: &DevCommands) -> Option<&'static str> {
+    match command {
+        DevCommands::Up { .. } => Some("dev up"),
+        DevCommands::Down { .. } => Some("dev down"),
+        DevCommands::Restart { .. } => Some("dev restart"),
+        DevCommands::BuildChanged { dry_run: false, .. } => Some("dev build-changed"),
+        DevCommands::Diff { apply: true, .. } => Some("dev diff --apply"),
+        DevCommands::Backup { .. } => Some("dev backup"),
+        DevCommands::Restore { .. } => Some("dev restore"),
+        _ => None,
+    }
+}
+
+async fn up(
+    config: &Config,
+    platform: Option<String>,
+    detach: bool,
+    profile: BuildProfile,
+    tags: &[String],
+) -> Result<()> {
+    say!("{}", "Starting development environment...".bold());
+    let config = &ports::allocate(config)?;
+
+    // Start services based on platform
+    let repos = if let Some(platform_name) = platform {
+        let platform_name = names::resolve("platform", &platform_name, config.platforms())?;
+        config.get_platform_repositories(&platform_name)
+            .ok_or_else(|| anyhow::anyhow!("Platform '{}' not found", platform_name))?
+    } else {
+        config.get_all_repositories()
+    };
+    let mut services: Vec<_> = repos
+        .into_iter()
+        .filter(|(name, repo)| repo.has_any_tag(tags) && is_service(config, name, repo))
+        .collect();
+    services.sort_by(|a, b| a.0.cmp(&b.0));
+
+    // Detached services need the daemon to keep them running
+    let mut daemon = if detach {
+        Some(daemon::connect_or_start(config).await?)
+    } else {
+        control::Client::connect(&config.workspace_root).await
+    };
+    up_services(config, services, detach, profile, daemon.as_mut()).await?;
+    Ok(())
+}
+
+/// Brings up the infrastructure, builds what's missing, then starts
+/// `services` in the order given and waits for their health checks.
+/// `config` must have its ports allocated. With a `daemon` the services are
+/// started under it; otherwise they stop when the returned manager is
+/// dropped.
+pub(crate) async fn up_services(
+    config: &Config,
+    mut services: Vec<(String, &RepositoryConfig)>,
+    detach: bool,
+    profile: BuildProfile,
+    mut daemon: Option<&mut control::Client>,
+) -> Result<ProcessManager> {
     // Check if we're in development mode
-    let dev_mode = std::env::var("SYLA_DEV_MODE").unwrap_or_else(|_| "false".to_string()) == "true";
+    let dev_mode = std::env::var("SYLA_DEV_MODE")
+        .ok()
+        .or_else(|| config.settings.config.get("dev_mode").cloned())
+        .unwrap_or_else(|| "false".to_string()) == "true";
     
+    let steps = ui::Steps::new(4);
+    let mut outcomes: Vec<Outcome> = Vec::new();
+
     // Start Docker infrastructure
+    steps.start(1, "Infrastructure");
     let docker_compose_path = config.workspace_root.join("docker-compose.yml");
-    if docker_compose_path.exists() {
-        println!("Starting Docker infrastructure...");
+    let docker_down = if docker_compose_path.exists() { docker::unavailable().await } else { None };
+    if let Some(reason) = &docker_down {
+        docker::print_skipped("infrastructure containers", reason);
+    } else if docker_compose_path.exists() {
+        let item = steps.item("docker compose", "Starting");
         
         let mut cmd = Command::new("docker");
-        cmd.args(&["compose"]);
+        cmd.args(["compose"]);
         
         // Add dev override if in dev mode
         if dev_mode {
             let dev_compose = config.workspace_root.join("docker-compose.dev.yml");
             if dev_compose.exists() {
-                cmd.args(&["-f", "docker-compose.yml", "-f", "docker-compose.dev.yml"]);
+                cmd.args(["-f", "docker-compose.yml", "-f", "docker-compose.dev.yml"]);
             }
         }
+        cmd.args(docker::compose_profile_args(config));
         
         cmd.arg("up");
         if detach {
             cmd.arg("-d");
         }
         cmd.current_dir(&config.workspace_root);
+        let logged = ui::log_command(&cmd);
         
-        let status = cmd.status()
+        let status = tracing::info_span!("docker_up")
+            .in_scope(|| steps.suspend(|| cmd.status()))
             .context("Failed to start Docker containers")?;
+        logged.finished(&status);
         
         if !status.success() {
+            item.fail("failed");
             return Err(anyhow::anyhow!("Failed to start Docker containers"));
         }
-    }
-    
-    // Start services based on platform
-    let repos = if let Some(platform_name) = platform {
-        config.get_platform_repositories(&platform_name)
-            .ok_or_else(|| anyhow::anyhow!("Platform '{}' not found", platform_name))?
+        item.ok("started");
     } else {
-        config.get_all_repositories()
-    };
-    
+        say!("  {}", "No docker-compose.yml".dimmed());
+    }
+
+
+    // Rust services are built by `syla init` and `dev build-changed`; the
+    // others only need their dependencies installed once
+    steps.start(2, "Builds");
+    let mut built = 0;
+    services.retain(|(name, repo)| {
+        if repo.run_in == RunIn::Container {
+            return true;
+        }
+        let runtime = Runtime::of(repo);
+        let service_path = config.workspace_root.join(&repo.path);
+        let (Some(output), Some(build)) = (runtime.build_output(repo, &service_path), runtime.build_command(repo, &service_path)) else {
+            return true;
+        };
+        if output.exists() {
+            return true;
+        }
+        built += 1;
+        let item = steps.item(name, "Building");
+        let _span = tracing::info_span!("build", service = %name).entered();
+        match steps.suspend(|| runtime::build(config, name, &build, &service_path)) {
+            Ok(true) => {
+                item.ok("built");
+                true
+            }
+            result => {
+                let detail = match result {
+                    Err(e) => format!("{:#}", e),
+                    _ => "build failed".to_string(),
+                };
+                outcomes.push(Outcome::new(name, UpResult::Failed, &detail, item.elapsed()));
+                item.fail(&detail);
+                false
+            }
+        }
+    });
+    if built == 0 {
+        say!("  {}", "Nothing to build".dimmed());
+    }
+
     // Initialize ProcessManager
     let process_manager = ProcessManager::new(config.clone());
     
     // Start each service using ProcessManager
-    for (name, repo) in repos {
-        if !repo.ports.is_empty() && repo.language == "rust" {
-            println!("Starting {}...", name);
-            
-            let service_path = config.workspace_root.join(&repo.path);
-            let binary_name = repo.path.split('/').last().unwrap_or("service");
-            let binary_path = service_path.join(format!("target/release/{}", binary_name));
-            
-            if !binary_path.exists() {
-                println!("{} {} not built, skipping", "[!]".yellow(), name);
+    steps.start(3, "Services");
+    let mut started = Vec::new();
+    for (name, repo) in services {
+        let item = steps.item(&name, "Starting");
+        let _span = tracing::info_span!("start_service", service = %name).entered();
+
+        match write_envfile(config, &name, repo) {
+            Ok(path) => warn_if_tracked(&path),
+            Err(e) => println!("{} Could not write .env for {}: {:#}", "[!]".yellow(), name, e),
+        }
+        
+        let skipped = |item: ui::StepItem, outcomes: &mut Vec<Outcome>, detail: &str| {
+            outcomes.push(Outcome::new(&name, UpResult::Skipped, detail, item.elapsed()));
+            item.warn(&format!("skipped: {}", detail));
+        };
+        let process_config = match service_process_config(config, &name, repo, profile) {
+            Ok(Some(process_config)) => process_config,
+            Ok(None) if Runtime::of(repo) == Runtime::Rust => {
+                skipped(item, &mut outcomes, &format!("no {} build", profile.name()));
+                continue;
+            }
+            Ok(None) => {
+                skipped(item, &mut outcomes, "not built");
                 continue;
             }
-            
-            // Create process configuration
-            let mut env = HashMap::new();
-            env.insert("RUST_LOG".to_string(), "info".to_string());
-            
-            // Extract port from the first port in the list
-            if let Some(port) = repo.ports.first() {
-                env.insert("PORT".to_string(), port.clone());
+            Err(e) => {
+                let detail = format!("{:#}", e);
+                outcomes.push(Outcome::new(&name, UpResult::Failed, &detail, item.elapsed()));
+                item.fail(&detail);
+                continue;
             }
-            
-            let process_config = ProcessConfig {
-                name: name.clone(),
-                command: binary_path.to_string_lossy().to_string(),
-                args: vec![],
-                working_dir: service_path,
-                env,
-                health_check_url: repo.health_check.clone(),
-                health_check_interval: Duration::from_secs(10),
-                startup_timeout: Duration::from_secs(30),
-                restart_policy: RestartPolicy::OnFailure,
-                log_file: Some(config.workspace_root.join(format!(".logs/{}.log", name))),
+        };
+        
+        // Start the service
+        let result = match daemon.as_deref_mut() {
+            Some(client) => {
+                let started = StartedServices::load(&config.workspace_root)?;
+                if let Some(running) = started.services.get(&name).filter(|service| service.is_running()) {
+                    skipped(item, &mut outcomes, &format!("already running (pid {})", running.pid));
+                    continue;
+                }
+                let request = control::Request::Start { service: name.clone(), profile: Some(profile) };
+                client.call(&request).await.map(|_| ())
+            }
+            None => start_service(config, &process_manager, &name, repo, process_config),
+        };
+        match result {
+            Ok(_) => {
+                outcomes.push(Outcome::new(&name, UpResult::Started, &format!("ports {}", repo.ports.join(", ")), item.elapsed()));
+                item.ok(&format!("started on ports {:?}", repo.ports));
+                if let Some(health_check) = &repo.health_check {
+                    started.push((name.clone(), health_check.clone()));
+                }
+            }
+            Err(e) => {
+                let detail = e.to_string();
+                outcomes.push(Outcome::new(&name, UpResult::Failed, &detail, item.elapsed()));
+                item.fail(&detail);
+            }
+        }
+    }
+
+    steps.start(4, "Health");
+    if started.is_empty() {
+        say!("  {}", "No health checks".dimmed());
+    }
+    let mut checks = tokio::task::JoinSet::new();
+    for (name, health_check) in started {
+        let item = steps.item(&name, "Waiting for");
+        checks.spawn(async move {
+            let deadline = Instant::now() + UP_HEALTH_TIMEOUT;
+            let healthy = loop {
+                if status::check_health(&health_check).await.unwrap_or(false) {
+                    break true;
+                }
+                if Instant::now() >= deadline {
+                    break false;
+                }
+                tokio::time::sleep(UP_HEALTH_INTERVAL).await;
             };
-            
-            // Start the service
-            match process_manager.start_service(process_config) {
-                Ok(_) => println!("{} {} started on ports {:?}", "[OK]".green(), name, repo.ports),
-                Err(e) => println!("{} Failed to start {}: {}", "[X]".red(), name, e),
+            let elapsed = item.elapsed();
+            if healthy {
+                item.ok("healthy");
+            } else {
+                item.fail(&format!("not healthy after {}s", UP_HEALTH_TIMEOUT.as_secs()));
             }
+            (name, healthy, elapsed)
+        });
+    }
+    while let Some(result) = checks.join_next().await {
+        let Ok((name, healthy, elapsed)) = result else {
+            continue;
+        };
+        if let Some(outcome) = outcomes.iter_mut().find(|outcome| outcome.name == name) {
+            outcome.result = if healthy { UpResult::Healthy } else { UpResult::Unhealthy };
+            outcome.elapsed += elapsed;
         }
     }
+
+    registry::update(config).await;
+    print_outcomes(&mut outcomes);
+    say!("\n{} Development environment is ready!", "[OK]".green().bold());
+    say!("Run {} to check status", "syla dev status".bright_black());
     
-    println!("\n{} Development environment is ready!", "[OK]".green().bold());
-    println!("Run {} to check status", "syla dev status".bright_black());
+    Ok(process_manager)
+}
+
+/// How long `dev up` waits for started services to pass their health check
+const UP_HEALTH_TIMEOUT: Duration = Duration::from_secs(30);
+const UP_HEALTH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UpResult {
+    Started,
+    Healthy,
+    Unhealthy,
+    Skipped,
+    Failed,
+}
+
+/// How `dev up` left a service, for the summary table
+struct Outcome {
+    name: String,
+    result: UpResult,
+    detail: String,
+    elapsed: Duration,
+}
+
+impl Outcome {
+    fn new(name: &str, result: UpResult, detail: &str, elapsed: Duration) -> Self {
+        Self { name: name.to_string(), result, detail: detail.to_string(), elapsed }
+    }
+}
+
+fn print_outcomes(outcomes: &mut [Outcome]) {
+    if outcomes.is_empty() || ui::is_quiet() {
+        return;
+    }
+    outcomes.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut table = comfy_table::Table::new();
+    table.set_header(vec!["Service", "Result", "Detail", "Time"]);
+    for outcome in outcomes.iter() {
+        let result = match outcome.result {
+            UpResult::Started => "Started".green(),
+            UpResult::Healthy => "Healthy".green(),
+            UpResult::Unhealthy => "Unhealthy".red(),
+            UpResult::Skipped => "Skipped".yellow(),
+            UpResult::Failed => "Failed".red(),
+        };
+        table.add_row(vec![
+            outcome.name.clone(),
+            result.to_string(),
+            outcome.detail.clone(),
+            format!("{:.1?}", outcome.elapsed),
+        ]);
+    }
+    println!("\n{}", table);
+}
+
+/// Starts a service and records how, for `dev diff` and `dev down`
+pub(crate) fn start_service(
+    config: &Config,
+    process_manager: &ProcessManager,
+    name: &str,
+    repo: &RepositoryConfig,
+    process_config: ProcessConfig,
+) -> Result<()> {
+    let (command, args) = (process_config.command.clone(), process_config.args.clone());
+    process_manager.start_service(process_config)?;
+    let Some(pid) = process_manager.pid(name) else {
+        return Ok(());
+    };
+    let container = process_manager.container(name);
+    events::emit(events::Event::ServiceStarted { service: name, pid, container: container.as_deref(), ports: &repo.ports });
+    let started = StartedService {
+        pid,
+        container,
+        command,
+        args,
+        ports: repo.ports.clone(),
+        env: unresolved_env(config, repo).into_iter().collect(),
+        started_at: chrono::Utc::now(),
+        restarts: Vec::new(),
+    };
+    StartedServices::record(&config.workspace_root, name, started)
+}
+
+/// Whether `dev up` runs the repository as a service: it has ports, is
+/// enabled, and there is a way to start it
+pub(crate) fn is_service(config: &Config, name: &str, repo: &RepositoryConfig) -> bool {
+    let runtime = Runtime::of(repo);
+    let service_path = config.workspace_root.join(&repo.path);
+    let runnable = match repo.run_in {
+        RunIn::Process => runtime == Runtime::Rust || runtime.run_command(repo, &service_path).is_some(),
+        RunIn::Container => repo.image.is_some(),
+    };
+    !repo.ports.is_empty() && runnable && config.is_enabled(name, repo)
+}
+
+/// Program and arguments a service runs with, or `None` if it hasn't been
+/// built (with `profile`, for Rust) yet
+pub(crate) fn service_command(
+    config: &Config,
+    repo: &RepositoryConfig,
+    profile: BuildProfile,
+) -> Option<(String, Vec<String>)> {
+    let service_path = config.workspace_root.join(&repo.path);
+    let runtime = Runtime::of(repo);
+    match runtime.run_command(repo, &service_path) {
+        Some(run) => {
+            if runtime.build_output(repo, &service_path).is_some_and(|output| !output.exists()) {
+                return None;
+            }
+            Some(("sh".to_string(), vec!["-c".to_string(), run]))
+        }
+        None => {
+            let binary_path = service_binary(config, repo, profile);
+            binary_path
+                .exists()
+                .then(|| (binary_path.to_string_lossy().to_string(), vec![]))
+        }
+    }
+}
+
+/// Restarts in a row the supervisor makes before giving up on a service
+const DEFAULT_MAX_RESTARTS: u32 = 5;
+
+/// How to run a service, or `None` if it hasn't been built yet
+pub(crate) fn service_process_config(
+    config: &Config,
+    name: &str,
+    repo: &RepositoryConfig,
+    profile: BuildProfile,
+) -> Result<Option<ProcessConfig>> {
+    let service_path = config.workspace_root.join(&repo.path);
+    let (command, args, backend) = match repo.run_in {
+        RunIn::Process => {
+            let Some((command, args)) = service_command(config, repo, profile) else {
+                return Ok(None);
+            };
+            (command, args, Backend::Process)
+        }
+        RunIn::Container => {
+            let image = repo
+                .image
+                .clone()
+                .with_context(|| format!("{} runs in a container but has no image", name))?;
+            let (command, args) = match &repo.run {
+                Some(run) => ("sh".to_string(), vec!["-c".to_string(), run.clone()]),
+                None => (String::new(), Vec::new()),
+            };
+            (command, args, Backend::Container { image })
+        }
+    };
     
+    Ok(Some(ProcessConfig {
+        name: name.to_string(),
+        command,
+        args,
+        working_dir: service_path,
+        env: service_env(config, repo)?,
+        health_check_url: repo.health_check.clone(),
+        health_check_interval: Duration::from_secs(10),
+        startup_timeout: Duration::from_secs(30),
+        restart_policy: repo.restart,
+        max_restarts: repo.max_restarts.unwrap_or(DEFAULT_MAX_RESTARTS),
+        log_file: Some(service_log_file(config, name)),
+        backend,
+    }))
+}
+
+/// Where a Rust service's `profile` binary is built to
+pub(crate) fn service_binary(config: &Config, repo: &RepositoryConfig, profile: BuildProfile) -> PathBuf {
+    let service_path = config.workspace_root.join(&repo.path);
+    let binary_name = repo.path.split('/').last().unwrap_or("service");
+    config.target_dir(&service_path).join(profile.name()).join(binary_name)
+}
+
+/// Environment a service is started with, secrets resolved
+pub(crate) fn service_env(config: &Config, repo: &RepositoryConfig) -> Result<HashMap<String, String>> {
+    let mut env = unresolved_env(config, repo);
+    if secrets::has_secret_refs(env.values()) {
+        SecretStore::open()?.resolve_env(&mut env)?;
+    }
+    Ok(env)
+}
+
+/// Environment a service is started with, `secret:` references left in
+pub(crate) fn unresolved_env(config: &Config, repo: &RepositoryConfig) -> HashMap<String, String> {
+    let mut env = HashMap::new();
+    let runtime = match Runtime::of(repo) {
+        // Unlabelled services are assumed to be Rust, like most of the platform
+        Runtime::Other if repo.language.is_empty() => Runtime::Rust,
+        runtime => runtime,
+    };
+    for (key, value) in runtime.env() {
+        env.insert(key.to_string(), value.to_string());
+    }
+    
+    // Extract port from the first port in the list
+    if let Some(port) = repo.ports.first() {
+        env.insert("PORT".to_string(), port.clone());
+    }
+
+    // Connection details for everything the service depends on
+    for dependency in &repo.depends_on {
+        if let Some(infra_name) = dependency.strip_prefix("infrastructure.") {
+            if let Some(infra) = config.manifest.infrastructure.get(infra_name) {
+                env.extend(infra_env(infra_name, infra));
+            }
+        } else if let Some(service) = config.manifest.repositories.get(dependency) {
+            if let Some(port) = service.ports.first().and_then(|p| host_port(p)) {
+                env.insert(url_var(dependency), format!("http://localhost:{}", port));
+                env.insert(addr_var(dependency), format!("localhost:{}", port));
+            }
+        }
+    }
+
+    // Where to look up the services it doesn't declare
+    env.insert(
+        "SYLA_REGISTRY".to_string(),
+        Registry::path(&config.workspace_root).to_string_lossy().into_owned(),
+    );
+
+    if !config.settings.compose_profiles.is_empty() {
+        env.insert("COMPOSE_PROFILES".to_string(), config.settings.compose_profiles.join(","));
+    }
+
+    env.extend(repo.env.clone());
+    env
+}
+
+/// `REDIS_URL`, `DATABASE_URL` etc. for a piece of infrastructure
+pub(crate) fn infra_env(name: &str, infra: &InfrastructureConfig) -> Vec<(String, String)> {
+    let Some(port) = infra.ports.first().and_then(|p| host_port(p)) else {
+        return Vec::new();
+    };
+    let image = infra.docker_image.as_deref().unwrap_or(name);
+
+    if image.starts_with("postgres") {
+        let var = |key: &str, default: &str| {
+            infra
+                .environment
+                .iter()
+                .find_map(|entry| entry.strip_prefix(&format!("{}=", key)))
+                .unwrap_or(default)
+                .to_string()
+        };
+        let user = var("POSTGRES_USER", "postgres");
+        let password = var("POSTGRES_PASSWORD", "");
+        let database = var("POSTGRES_DB", &user);
+        let credentials = if password.is_empty() {
+            user
+        } else {
+            format!("{}:{}", user, password)
+        };
+        vec![(
+            "DATABASE_URL".to_string(),
+            format!("postgres://{}@localhost:{}/{}", credentials, port, database),
+        )]
+    } else if image.starts_with("redis") {
+        vec![("REDIS_URL".to_string(), format!("redis://localhost:{}", port))]
+    } else {
+        vec![(url_var(name), format!("http://localhost:{}", port))]
+    }
+}
+
+/// `DATABASE_URL` of the workspace's Postgres, for services that don't
+/// declare a dependency on it
+pub(crate) fn workspace_database_url(config: &Config) -> Option<String> {
+    let mut infrastructure: Vec<_> = config.manifest.infrastructure.iter().collect();
+    infrastructure.sort_by(|a, b| a.0.cmp(b.0));
+    infrastructure
+        .into_iter()
+        .flat_map(|(name, infra)| infra_env(name, infra))
+        .find_map(|(key, value)| (key == "DATABASE_URL").then_some(value))
+}
+
+/// Host side of a port mapping such as `6380:6379`; `None` for an `auto`
+/// port that hasn't been assigned yet
+pub(crate) fn host_port(mapping: &str) -> Option<&str> {
+    mapping.split(':').next().filter(|p| p.parse::<u16>().is_ok())
+}
+
+/// `syla.core.execution-service` -> `EXECUTION_SERVICE_URL`
+fn url_var(name: &str) -> String {
+    format!("{}_URL", env_prefix(name))
+}
+
+/// `syla.core.execution-service` -> `EXECUTION_SERVICE_ADDR`, `host:port`
+/// for gRPC clients
+fn addr_var(name: &str) -> String {
+    format!("{}_ADDR", env_prefix(name))
+}
+
+fn env_prefix(name: &str) -> String {
+    let base = name.rsplit('.').next().unwrap_or(name);
+    base.to_uppercase().replace('-', "_")
+}
+
+/// Write the service's environment to `<repo>/.env` so services launched
+/// from an IDE see what `syla dev up` would give them
+pub(crate) fn write_envfile(config: &Config, name: &str, repo: &RepositoryConfig) -> Result<PathBuf> {
+    let repo_path = config.workspace_root.join(&repo.path);
+    if !repo_path.exists() {
+        anyhow::bail!("{} is not cloned; run `syla init` first", name);
+    }
+    let path = repo_path.join(".env");
+    secrets::write_private(&path, render_envfile(config, name, repo)?.as_bytes())?;
+    Ok(path)
+}
+
+fn render_envfile(config: &Config, name: &str, repo: &RepositoryConfig) -> Result<String> {
+    let mut env = service_env(config, repo)?;
+    interpolation::resolve_env(&mut env, &config.manifest)?;
+    let env: BTreeMap<String, String> = env.into_iter().collect();
+
+    let mut content = format!(
+        "# Environment for {}\n# Generated by `syla dev envfile`; rewritten by `syla dev up`\n",
+        name
+    );
+    for (key, value) in env {
+        content.push_str(&format!("{}={}\n", key, quote_env_value(&value)));
+    }
+    Ok(content)
+}
+
+fn quote_env_value(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.,:/@%+=".contains(c));
+    if plain && !value.is_empty() {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+    }
+}
+
+async fn envfile(config: &Config, service: &str, print: bool) -> Result<()> {
+    let (name, repo) = config.find_repository(service)?;
+
+    if print {
+        print!("{}", render_envfile(config, &name, repo)?);
+        return Ok(());
+    }
+
+    let path = write_envfile(config, &name, repo)?;
+    say!("{} Wrote {}", "[OK]".green(), path.display());
+    warn_if_tracked(&path);
     Ok(())
 }
 
+/// The file may hold secrets, so point it out if git would pick it up
+fn warn_if_tracked(path: &std::path::Path) {
+    let Some(dir) = path.parent() else {
+        return;
+    };
+    let ignored = Command::new("git")
+        .args(["check-ignore", "-q", ".env"])
+        .current_dir(dir)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(true);
+    if !ignored {
+        println!(
+            "{} {} is not gitignored; add .env to the repo's .gitignore",
+            "[!]".yellow(),
+            path.display()
+        );
+    }
+}
+
+/// Where a managed service's stdout and stderr are written
+pub(crate) fn service_log_file(config: &Config, name: &str) -> PathBuf {
+    config.workspace_root.join(format!(".logs/{}.log", name))
+}
+
 async fn down(config: &Config, volumes: bool) -> Result<()> {
-    println!("{}", "Stopping development environment...".bold());
+    say!("{}", "Stopping development environment...".bold());
     
+    // The daemon stops its own services, so it doesn't take them for crashed
+    if let Some(mut client) = control::Client::connect(&config.workspace_root).await {
+        say!("Stopping services supervised by the daemon...");
+        let services: Vec<control::ServiceInfo> = serde_json::from_value(client.call(&control::Request::List).await?)?;
+        for service in services.into_iter().filter(|service| service.managed && service.state != "stopped") {
+            if let Err(e) = client.call(&control::Request::Stop { service: service.name.clone() }).await {
+                println!("{} Could not stop {}: {:#}", "[!]".yellow(), service.name, e);
+            }
+        }
+    }
+
     // Initialize ProcessManager to stop services
     let process_manager = ProcessManager::new(config.clone());
     
     // Stop all services
-    println!("Stopping services...");
+    say!("Stopping services...");
     if let Err(e) = process_manager.stop_all() {
         println!("{} Error stopping services: {}", "[!]".yellow(), e);
     } else {
-        println!("{} All services stopped", "[OK]".green());
+        say!("{} All services stopped", "[OK]".green());
+    }
+
+    if let Err(e) = stop_started(config, &[]) {
+        println!("{} Error stopping services: {}", "[!]".yellow(), e);
+    }
+
+    if let Err(e) = tunnels::close(config, &[]) {
+        println!("{} Error closing tunnels: {}", "[!]".yellow(), e);
     }
     
     // Stop Docker containers
     let docker_compose_path = config.workspace_root.join("docker-compose.yml");
     if docker_compose_path.exists() {
-        println!("Stopping Docker containers...");
+        say!("Stopping Docker containers...");
         
         let mut cmd = Command::new("docker");
         cmd.args(&["compose", "down"]);
@@ -165,107 +801,300 @@ async fn down(config: &Config, volumes: bool) -> Result<()> {
             cmd.arg("-v");
         }
         cmd.current_dir(&config.workspace_root);
+        let logged = ui::log_command(&cmd);
         
         let status = cmd.status()
             .context("Failed to stop Docker containers")?;
+        logged.finished(&status);
         
         if status.success() {
-            println!("{} Docker containers stopped", "[OK]".green());
+            say!("{} Docker containers stopped", "[OK]".green());
         }
     }
     
-    println!("\n{} Development environment stopped", "[OK]".green().bold());
+    registry::update(config).await;
+    say!("\n{} Development environment stopped", "[OK]".green().bold());
     
     Ok(())
 }
 
-async fn logs(config: &Config, service: &str, _follow: bool, _lines: usize) -> Result<()> {
-    // Find the service
-    let repos = config.get_all_repositories();
-    let service_repo = repos.iter()
-        .find(|(name, _)| name.contains(service))
-        .ok_or_else(|| anyhow::anyhow!("Service '{}' not found", service))?;
-    
-    println!("Showing logs for {}...", service_repo.0);
-    
-    // TODO: Implement proper log viewing
-    println!("{} Log viewing not yet implemented", "[!]".yellow());
-    println!("Service path: {}", service_repo.1.path);
-    
+/// Stops services an earlier `dev up` left running: the given ones, or all
+pub(crate) fn stop_started(config: &Config, names: &[&str]) -> Result<()> {
+    let mut started = StartedServices::load(&config.workspace_root)?;
+    started.services.retain(|name, service| {
+        if !names.is_empty() && !names.contains(&name.as_str()) {
+            return true;
+        }
+        if !service.is_running() {
+            return false;
+        }
+        match &service.container {
+            Some(container) => match container::remove(container) {
+                Ok(()) => say!("{} Stopped {} (container {})", "[OK]".green(), name, container),
+                Err(e) => println!("{} Could not stop {}: {:#}", "[!]".yellow(), name, e),
+            },
+            None => {
+                tunnels::terminate(service.pid);
+                say!("{} Stopped {} (pid {})", "[OK]".green(), name, service.pid);
+            }
+        }
+        false
+    });
+    started.save(&config.workspace_root)
+}
+
+async fn diff(config: &Config, profile: BuildProfile, apply: bool) -> Result<()> {
+    println!("{} ({})", "Comparing workspace with the manifest...".bold(), profile.name());
+    status::print_local_overrides(config);
+    println!();
+
+    let drift = drift::detect(config, profile).await?;
+    if drift.is_empty() {
+        say!("{} No drift", "[OK]".green());
+        return Ok(());
+    }
+    for item in &drift {
+        let icon = match item {
+            Drift::Unmanaged { .. } => "-".red(),
+            Drift::NotRunning { .. } => "+".green(),
+            _ => "~".yellow(),
+        };
+        println!("  {} {}", icon, item);
+    }
+    if !apply {
+        println!("
+Run with {} to reconcile", "--apply".bright_black());
+        return Ok(());
+    }
+
+    println!();
+    let unmanaged: Vec<&str> = drift
+        .iter()
+        .filter(|item| matches!(item, Drift::Unmanaged { .. }))
+        .map(Drift::name)
+        .collect();
+    if !unmanaged.is_empty() {
+        stop_started(config, &unmanaged)?;
+    }
+
+    for item in &drift {
+        let Drift::Branch { name, expected, .. } = item else {
+            continue;
+        };
+        let path = config.workspace_root.join(&config.manifest.repositories[name].path);
+        if git::status(&path).await.is_ok_and(|status| status.has_changes) {
+            println!("{} {} has uncommitted changes; not switching to {}", "[!]".yellow(), name, expected);
+            continue;
+        }
+        match git::checkout(&path, expected).await {
+            Ok(()) => say!("{} Checked out {} in {}", "[OK]".green(), expected, name),
+            Err(e) => println!("{} Failed to check out {} in {}: {:#}", "[X]".red(), expected, name, e),
+        }
+    }
+
+    let mut restart: Vec<&str> = drift.iter().filter(|item| item.needs_restart()).map(Drift::name).collect();
+    restart.dedup();
+    restart_services(config, &restart, profile)
+}
+
+/// Stops the services if `dev up` started them, then starts them again with
+/// `profile` binaries, in the order given
+pub(crate) fn restart_services(config: &Config, names: &[&str], profile: BuildProfile) -> Result<()> {
+    if names.is_empty() {
+        return Ok(());
+    }
+    stop_started(config, names)?;
+    let process_manager = ProcessManager::new(config.clone());
+    for &name in names {
+        let repo = &config.manifest.repositories[name];
+        let process_config = match service_process_config(config, name, repo, profile) {
+            Ok(Some(process_config)) => process_config,
+            Ok(None) => {
+                println!("{} {} is not built, skipping", "[!]".yellow(), name);
+                continue;
+            }
+            Err(e) => {
+                println!("{} Failed to start {}: {:#}", "[X]".red(), name, e);
+                continue;
+            }
+        };
+        match start_service(config, &process_manager, name, repo, process_config) {
+            Ok(_) => say!("{} {} restarted", "[OK]".green(), name),
+            Err(e) => println!("{} Failed to start {}: {}", "[X]".red(), name, e),
+        }
+    }
     Ok(())
 }
 
+/// Shows the last lines of a service's log and, when following, what it
+/// writes next, forwarding what it shows to the sinks configured under
+/// `[logs]`. With a pattern, only matching entries and the context around
+/// each are shown.
+async fn logs(config: &Config, service: &str, stream_config: LogStreamConfig) -> Result<()> {
+    let (name, _) = config.find_repository(service)?;
+    let path = service_log_file(config, &name);
+    if !path.exists() {
+        anyhow::bail!("{} has no log yet; it is written once `syla dev up` starts it", name);
+    }
+
+    let mut streamer = LogStreamer::new();
+    for sink in &config.settings.logs.sinks {
+        streamer.add_sink(LogSink::open(sink, &config.workspace_root)?);
+    }
+    streamer.add_log_file(name, path, stream_config.follow)?;
+    tokio::task::spawn_blocking(move || streamer.stream(stream_config)).await?
+}
+
 async fn restart(config: &Config, service: &str) -> Result<()> {
-    println!("Restarting {}...", service);
+    let (name, _) = config.find_repository(service)?;
+    say!("Restarting {}...", name);
+
+    if let Some(mut client) = control::Client::connect(&config.workspace_root).await {
+        let result = client.call(&control::Request::Restart { service: name.clone() }).await?;
+        let pid = result.get("pid").and_then(|pid| pid.as_u64());
+        say!("{} {} restarted by the daemon (pid {})", "[OK]".green(), name, pid.unwrap_or_default());
+        return Ok(());
+    }
     
     // Initialize ProcessManager
     let process_manager = ProcessManager::new(config.clone());
     
-    // Find the matching service
-    let repos = config.get_all_repositories();
-    let service_name = repos.iter()
-        .find(|(name, _)| name.contains(service))
-        .map(|(name, _)| name.clone());
-    
-    if let Some(name) = service_name {
-        match process_manager.restart_service(&name) {
-            Ok(_) => println!("{} {} restarted successfully", "[OK]".green(), name),
-            Err(e) => println!("{} Failed to restart {}: {}", "[X]".red(), name, e),
-        }
-    } else {
-        println!("{} Service '{}' not found", "[!]".yellow(), service);
+    match process_manager.restart_service(&name) {
+        Ok(_) => say!("{} {} restarted successfully", "[OK]".green(), name),
+        Err(e) => println!("{} Failed to restart {}: {}", "[X]".red(), name, e),
     }
-    
+    registry::update(config).await;
+
     Ok(())
 }
 
-async fn status(config: &Config, detailed: bool) -> Result<()> {
+/// `syla dev status` in the shape `--output` prints
+#[derive(Debug, Serialize)]
+pub struct DevStatus {
+    /// `None` when Docker can't be reached
+    pub containers: Option<Vec<Container>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docker_unavailable: Option<String>,
+    /// Services with a health check
+    pub services: Vec<DevServiceStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daemon: Option<daemon::DaemonStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Container {
+    pub name: String,
+    pub service: String,
+    pub state: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DevServiceStatus {
+    pub name: String,
+    pub path: String,
+    pub ports: Vec<String>,
+    pub healthy: bool,
+}
+
+async fn dev_status(config: &Config) -> Result<DevStatus> {
+    let docker_unavailable = docker::unavailable().await;
+    let containers = match docker_unavailable {
+        Some(_) => None,
+        None => {
+            let output = Command::new("docker")
+                .args(["compose", "ps", "--all", "--format", "{{.Name}}\t{{.Service}}\t{{.State}}"])
+                .current_dir(&config.workspace_root)
+                .output()
+                .context("Failed to check Docker status")?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let containers = stdout
+                .lines()
+                .filter_map(|line| {
+                    let mut fields = line.split('\t');
+                    Some(Container {
+                        name: fields.next()?.to_string(),
+                        service: fields.next()?.to_string(),
+                        state: fields.next()?.to_string(),
+                    })
+                })
+                .collect();
+            Some(containers)
+        }
+    };
+
+    let mut repos = config.get_all_repositories();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut services = Vec::new();
+    for (name, repo) in repos {
+        if let Some(health_check) = &repo.health_check {
+            services.push(DevServiceStatus {
+                healthy: check_service_health(health_check).await,
+                name,
+                path: repo.path.clone(),
+                ports: repo.ports.clone(),
+            });
+        }
+    }
+    let daemon = daemon::collect(config)
+        .await?
+        .map(|(info, services)| daemon::DaemonStatus { running: true, info: Some(info), services });
+    Ok(DevStatus { containers, docker_unavailable, services, daemon })
+}
+
+async fn status(config: &Config, detailed: bool, output: Option<OutputFormat>) -> Result<()> {
+    if let Some(format) = output {
+        return format.print(&dev_status(config).await?);
+    }
+
     println!("{}", "Development Environment Status".bold());
+    status::print_local_overrides(config);
     println!();
-    
-    // Check Docker containers
-    println!("{}", "Infrastructure:".cyan());
-    let output = Command::new("docker")
-        .args(&["compose", "ps"])
-        .current_dir(&config.workspace_root)
-        .output()
-        .context("Failed to check Docker status")?;
-    
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines().skip(2) {  // Skip header lines
-            if !line.trim().is_empty() {
-                println!("  {}", line);
+
+    let health = status::HealthChecks::start(status::service_checks(config, &[])).results().await;
+    let (services, docker_unavailable) = status::services(config, &[], health).await?;
+    println!("{}", "Services:".cyan());
+    status::print_services(&services, docker_unavailable.as_deref());
+    if detailed {
+        println!("\n{}", "Paths:".cyan());
+        for service in services.iter().filter(|service| service.kind == "service") {
+            if let Some(repo) = config.manifest.repositories.get(&service.name) {
+                println!("  {}: {}", service.name, repo.path);
             }
         }
-    }
-    
-    // Check services
-    println!("\n{}", "Services:".cyan());
-    let repos = config.get_all_repositories();
-    for (name, repo) in repos {
-        if let Some(health_check) = &repo.health_check {
-            let status = check_service_health(health_check).await;
-            let status_icon = if status { "[OK]".green() } else { "[X]".red() };
-            println!("  {} {}", status_icon, name);
-            
-            if detailed {
-                println!("      Path: {}", repo.path);
-                if !repo.ports.is_empty() {
-                    println!("      Ports: {:?}", repo.ports);
+        let restarted: Vec<_> = services.iter().filter(|service| !service.restarts.is_empty()).collect();
+        if !restarted.is_empty() {
+            println!("\n{}", "Restarts:".cyan());
+            for service in restarted {
+                println!("  {}", service.name.bold());
+                for restart in &service.restarts {
+                    let at = restart.at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S");
+                    println!("    {} {}", at.to_string().dimmed(), restart.reason);
                 }
             }
         }
     }
+
+    if let Some((info, supervised)) = daemon::collect(config).await? {
+        println!("\n{} running (pid {})", "Daemon:".cyan(), info.pid);
+        daemon::print_services(&supervised);
+    }
+
+    tunnels::print_active(&config.workspace_root);
     
     Ok(())
 }
 
-async fn validate(config: &Config, fix: bool, integration: bool) -> Result<()> {
+async fn validate(
+    config: &Config,
+    fix: bool,
+    integration: bool,
+    ci: bool,
+    junit: Option<PathBuf>,
+) -> Result<()> {
     println!("{}", "Validating workspace setup...".bold());
     println!();
     
-    let mut issues = Vec::new();
+    let mut report = ValidationReport::default();
     
     // Check repositories
     println!("{} Checking repositories...", "->".dimmed());
@@ -273,79 +1102,149 @@ async fn validate(config: &Config, fix: bool, integration: bool) -> Result<()> {
     for (name, repo) in &repos {
         let repo_path = config.workspace_root.join(&repo.path);
         if !repo_path.exists() {
-            issues.push(format!("Repository {} not cloned", name));
+            report.fail(CheckCategory::Repositories, name, format!("Repository {} not cloned", name));
+            report.with_file(".platform/config/repos.toml");
             if fix {
                 println!("{} Cloning {}...", "[!]".yellow(), name);
                 // TODO: Clone repository
             }
         } else {
-            println!("{} {} exists", "[OK]".green(), name);
+            report.pass(CheckCategory::Repositories, name);
+            say!("{} {} exists", "[OK]".green(), name);
         }
     }
     
     // Check Docker
     println!("\n{} Checking Docker infrastructure...", "->".dimmed());
-    let docker_status = Command::new("docker")
-        .args(&["compose", "ps", "-q"])
-        .current_dir(&config.workspace_root)
-        .output()
-        .context("Failed to check Docker")?;
+    let docker_status = match docker::unavailable().await {
+        Some(reason) => Err(std::io::Error::other(reason)),
+        None => Command::new("docker")
+            .args(&["compose", "ps", "-q"])
+            .current_dir(&config.workspace_root)
+            .output(),
+    };
     
-    if docker_status.stdout.is_empty() {
-        issues.push("Docker containers not running".to_string());
-        if fix {
-            println!("{} Starting Docker containers...", "[!]".yellow());
-            Command::new("docker")
-                .args(&["compose", "up", "-d"])
-                .current_dir(&config.workspace_root)
-                .status()?;
+    match docker_status {
+        Err(e) => {
+            report.fail(CheckCategory::Docker, "containers", format!("Failed to check Docker: {}", e));
+            println!("{} Failed to check Docker: {}", "[X]".red(), e);
+        }
+        Ok(output) if output.stdout.is_empty() => {
+            report.fail(CheckCategory::Docker, "containers", "Docker containers not running");
+            report.with_file("docker-compose.yml");
+            if fix {
+                println!("{} Starting Docker containers...", "[!]".yellow());
+                Command::new("docker")
+                    .arg("compose")
+                    .args(docker::compose_profile_args(config))
+                    .args(["up", "-d"])
+                    .current_dir(&config.workspace_root)
+                    .status()?;
+            }
+        }
+        Ok(_) => {
+            report.pass(CheckCategory::Docker, "containers");
+            say!("{} Docker containers running", "[OK]".green());
         }
-    } else {
-        println!("{} Docker containers running", "[OK]".green());
     }
     
     // Check service builds
     println!("\n{} Checking service builds...", "->".dimmed());
+    let profile = config.build_profile(None);
     for (name, repo) in &repos {
-        if repo.language == "rust" {
-            let service_path = config.workspace_root.join(&repo.path);
-            let target_dir = service_path.join("target/release");
-            
-            if !target_dir.exists() {
-                issues.push(format!("Service {} not built", name));
-                if fix {
-                    println!("{} Building {}...", "[!]".yellow(), name);
-                    Command::new("cargo")
-                        .args(&["build", "--release"])
-                        .current_dir(&service_path)
-                        .status()?;
-                }
-            } else {
-                println!("{} {} built", "[OK]".green(), name);
+        let runtime = Runtime::of(repo);
+        let service_path = config.workspace_root.join(&repo.path);
+        let built = if repo.language == "rust" {
+            service_binary(config, repo, profile).exists()
+        } else {
+            match runtime.build_output(repo, &service_path) {
+                Some(output) => output.exists(),
+                None => continue,
             }
+        };
+        // Builds older than their sources need a rebuild as much as missing ones
+        let stale = if built {
+            changes::detect_one(config, name, repo, profile, None)
+                .ok()
+                .flatten()
+                .filter(|change| change.built.is_some())
+        } else {
+            None
+        };
+        if built && stale.is_none() {
+            report.pass(CheckCategory::Builds, name);
+            say!("{} {} built", "[OK]".green(), name);
+            continue;
+        }
+
+        match &stale {
+            Some(change) => report.fail(
+                CheckCategory::Builds,
+                name,
+                format!("Service {} is stale: {}", name, change.staleness()),
+            ),
+            None => report.fail(CheckCategory::Builds, name, format!("Service {} not built", name)),
+        }
+        if repo.language == "rust" {
+            report.with_file(format!("{}/Cargo.toml", repo.path));
+        }
+        if !fix {
+            continue;
+        }
+        let action = if stale.is_some() { "Rebuilding" } else { "Building" };
+        if repo.language == "rust" {
+            println!("{} {} {}...", "[!]".yellow(), action, name);
+            config.cargo()
+                .args(profile.cargo_args())
+                .current_dir(&service_path)
+                .status()?;
+        } else if let Some(build) = runtime.build_command(repo, &service_path) {
+            println!("{} {} {}...", "[!]".yellow(), action, name);
+            runtime::build(config, name, &build, &service_path)?;
         }
     }
     
     // Run integration tests if requested
     if integration {
         println!("\n{} Running integration tests...", "->".dimmed());
-        // TODO: Implement integration tests
-        println!("{} Integration tests not yet implemented", "[!]".yellow());
+        integration::run(config, &mut report, None).await?;
     }
     
     // Summary
     println!("\n{}", "Validation Summary".bold());
+    let issues: Vec<&str> = report.failures().filter_map(|c| c.failure.as_deref()).collect();
     if issues.is_empty() {
-        println!("{} No issues found!", "[OK]".green().bold());
+        say!("{} No issues found!", "[OK]".green().bold());
     } else {
         println!("{} Found {} issues:", "[!]".yellow().bold(), issues.len());
-        for issue in issues {
+        for issue in &issues {
             println!("  - {}", issue);
         }
-        if !fix {
+        if !fix && !ci {
             println!("\nRun with {} to fix issues", "--fix".bright_black());
         }
     }
+
+    if let Some(path) = &junit {
+        report.write_junit(path)?;
+        println!("\nJUnit report written to {}", path.display());
+    }
+
+    if ci {
+        if std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true") {
+            for annotation in report.github_annotations() {
+                println!("{}", annotation);
+            }
+        }
+        let code = report.exit_code();
+        if code != 0 {
+            return Err(ExitStatus {
+                code,
+                message: format!("Workspace validation failed with {} issues", issues.len()),
+            }
+            .into());
+        }
+    }
     
     Ok(())
 }
@@ -358,90 +1257,406 @@ async fn check_service_health(url: &str) -> bool {
     }
 }
 
-async fn watch(config: &Config, _services: Vec<String>, build_only: bool) -> Result<()> {
+#[derive(Serialize)]
+struct UptimeReport {
+    since: chrono::DateTime<chrono::Utc>,
+    services: Vec<ServiceReport>,
+}
+
+fn report(config: &Config, since: &str, output: Option<OutputFormat>) -> Result<()> {
+    let window = parse_window(since)?;
+    let since = chrono::Utc::now() - window;
+    let samples = health_history::load(&config.workspace_root, since)?;
+    let report = UptimeReport { since, services: health_history::report(&samples) };
+    if let Some(format) = output {
+        return format.print(&report);
+    }
+
+    if report.services.is_empty() {
+        println!(
+            "{}",
+            "No health history in that window; `syla dev supervise` records it while running".dimmed()
+        );
+        return Ok(());
+    }
+    println!(
+        "{} since {}",
+        "Service uptime".bold(),
+        since.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+    );
+    let mut table = comfy_table::Table::new();
+    table.set_header(vec!["Service", "Uptime", "Checks", "Incidents", "Mean recovery", "p95", "Slowest"]);
+    for service in &report.services {
+        let uptime = format!("{:.2}%", service.uptime_percent);
+        let uptime = match service.uptime_percent {
+            p if p >= 99.9 => uptime.green(),
+            p if p >= 99.0 => uptime.yellow(),
+            _ => uptime.red(),
+        };
+        let incidents = if service.down {
+            format!("{} {}", service.incidents, "(down)".red())
+        } else {
+            service.incidents.to_string()
+        };
+        let slowest: Vec<String> = service
+            .slowest
+            .iter()
+            .map(|check| {
+                format!(
+                    "{}ms at {}",
+                    check.latency_ms,
+                    check.timestamp.with_timezone(&chrono::Local).format("%m-%d %H:%M")
+                )
+            })
+            .collect();
+        table.add_row(vec![
+            service.service.clone(),
+            uptime.to_string(),
+            service.checks.to_string(),
+            incidents,
+            service.mean_recovery_seconds.map(|s| format!("{}s", s)).unwrap_or_else(|| "-".to_string()),
+            format!("{}ms", service.p95_latency_ms),
+            slowest.join("\n"),
+        ]);
+    }
+    println!("{}", table);
+    Ok(())
+}
+
+/// Parses a lookback like `90s`, `30m`, `24h` or `7d`
+fn parse_window(value: &str) -> Result<chrono::Duration> {
+    let invalid = || anyhow::anyhow!("Invalid duration '{}'; use a number and s, m, h or d, e.g. 24h", value);
+    let value = value.trim();
+    let split = value.len().checked_sub(1).filter(|&i| value.is_char_boundary(i)).ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let window = match unit {
+        "s" => chrono::Duration::try_seconds(amount),
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        _ => None,
+    };
+    window.filter(|window| *window > chrono::Duration::zero()).ok_or_else(invalid)
+}
+
+/// What `dev watch` does when a service changes
+#[derive(Debug, Clone, Copy)]
+struct WatchMode {
+    build: bool,
+    restart: bool,
+    test: bool,
+}
+
+async fn watch(config: &Config, services: Vec<String>, mode: WatchMode) -> Result<()> {
     println!("{}", "Starting file watcher...".bold());
+    
+    let profile = config.build_profile(None);
+    let selected = |name: &str| services.is_empty() || services.iter().any(|s| name.contains(s.as_str()));
+    let mut reloaders = if mode.restart {
+        start_reloaders(config, &selected)?
+    } else {
+        Vec::new()
+    };
+    let mut tests = watch::TestRunner::new();
+    let mut watcher = watch::FileWatcher::new(watched_services(config, &selected, mode)?)?;
     println!("Watching for changes (press Ctrl+C to stop)");
+    // Often enough to act on a settled change soon after its debounce
+    let tick = watcher
+        .debounce()
+        .map_or(MAX_WATCH_TICK, |debounce| debounce / 2)
+        .clamp(Duration::from_millis(50), MAX_WATCH_TICK);
+    let mut interval = interval(tick);
     
-    // Use make watch if available
-    let makefile = config.workspace_root.join("Makefile");
-    if makefile.exists() {
-        let mut cmd = Command::new("make");
-        cmd.arg("dev-watch");
-        cmd.current_dir(&config.workspace_root);
-        cmd.stdout(Stdio::inherit());
-        cmd.stderr(Stdio::inherit());
+    loop {
+        interval.tick().await;
+
+        reloaders.retain_mut(|(name, child)| match child.try_wait() {
+            Ok(None) => true,
+            Ok(Some(status)) => {
+                println!("\n{} Reloader for {} exited ({})", "[X]".red(), name, status);
+                false
+            }
+            Err(_) => false,
+        });
         
-        let status = cmd.status()
-            .context("Failed to run make dev-watch")?;
-            
-        if !status.success() {
-            return Err(anyhow::anyhow!("Watch command failed"));
+        for change in watcher.poll() {
+            println!("\n{} Detected changes in {}: {} modified", "[*]".yellow(), change.service, change.file);
+            on_watched_change(config, &change, profile, mode, &mut tests).await?;
         }
-    } else {
-        // Fallback to simple polling
-        let mut interval = interval(Duration::from_secs(2));
-        
-        loop {
-            interval.tick().await;
-            
-            // Detect changes
-            let output = Command::new(&config.workspace_root.join("scripts/detect-changes.sh"))
-                .output()
-                .context("Failed to detect changes")?;
-                
-            let changed = String::from_utf8_lossy(&output.stdout);
-            if !changed.trim().is_empty() {
-                println!("\n{} Detected changes in: {}", "[*]".yellow(), changed.trim());
-                
-                // Build changed services
-                for service in changed.split_whitespace() {
-                    println!("Building {}...", service);
-                    
-                    let status = Command::new("make")
-                        .arg(format!("{}-build", service))
-                        .current_dir(&config.workspace_root)
-                        .status()
-                        .context("Failed to build service")?;
-                        
-                    if status.success() && !build_only {
-                        // Restart service
-                        let service_path = PathBuf::from(service);
-                        let service_name = service_path
-                            .file_name()
-                            .unwrap()
-                            .to_string_lossy()
-                            .to_string();
-                        println!("Restarting {}...", service_name);
-                        restart(config, &service_name).await?;
-                    }
-                }
+    }
+}
+
+/// Longest wait between checks for settled changes
+const MAX_WATCH_TICK: Duration = Duration::from_millis(250);
+
+/// Selected repositories that have something to do on a change, minus
+/// those handed to a reloader
+fn watched_services(config: &Config, selected: &dyn Fn(&str) -> bool, mode: WatchMode) -> Result<Vec<watch::WatchedService>> {
+    let mut repos = config.get_all_repositories();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut watched = Vec::new();
+    for (name, repo) in repos {
+        if !selected(&name) || !config.is_enabled(&name, repo) || (repo.watch_command.is_some() && mode.restart) {
+            continue;
+        }
+        let dir = config.workspace_root.join(&repo.path);
+        match &repo.watch {
+            Some(watch) => watched.push(watch::WatchedService::new(&name, dir, watch)?),
+            None if dir.is_dir() && (can_build(repo, &dir) || is_service(config, &name, repo)) => {
+                watched.push(watch::WatchedService::sources(&name, dir)?)
             }
+            None => {}
         }
     }
-    
+    Ok(watched)
+}
+
+fn can_build(repo: &RepositoryConfig, dir: &Path) -> bool {
+    Runtime::of(repo).build_command(repo, dir).is_some() || dir.join("Cargo.toml").exists()
+}
+
+/// Builds and/or restarts a changed service, as its `watch` table asks,
+/// testing it in between when watching with `--test`
+async fn on_watched_change(
+    config: &Config,
+    change: &watch::Change,
+    profile: BuildProfile,
+    mode: WatchMode,
+    tests: &mut watch::TestRunner,
+) -> Result<()> {
+    let repo = &config.manifest.repositories[&change.service];
+    let path = config.workspace_root.join(&repo.path);
+
+    if mode.build && can_build(repo, &path) && matches!(change.on_change, WatchAction::Build | WatchAction::Both) {
+        say!("Building {}...", change.service);
+        let target = ChangedService {
+            name: change.service.clone(),
+            build: Runtime::of(repo).build_command(repo, &path),
+            path,
+            reason: format!("{} modified", change.file),
+            built: None,
+        };
+        let baseline = changes::snapshot(config, repo, profile);
+        let started = Instant::now();
+        let success = build_service(config, &target, profile)?;
+        notify(
+            &config.settings.notifications,
+            Event::BuildFinished { target: &change.service, success, duration: started.elapsed() },
+        );
+        if !success {
+            return Ok(());
+        }
+        if let Some(baseline) = baseline {
+            BuildCache::record(config, &change.service, profile, baseline)?;
+        }
+    }
+
+    if mode.test {
+        tests.run(config, &change.service, repo)?;
+    }
+    if mode.restart
+        && matches!(change.on_change, WatchAction::Restart | WatchAction::Both)
+        && is_service(config, &change.service, repo)
+    {
+        say!("Restarting {}...", change.service);
+        restart(config, &change.service).await?;
+    }
     Ok(())
 }
 
-async fn build_changed(config: &Config, all: bool) -> Result<()> {
-    println!("{}", "Building changed services...".bold());
+/// Hands services with a `watch_command` to their own reloader, stopping
+/// the copy `dev up` started so the two don't fight over its ports
+fn start_reloaders(config: &Config, selected: &dyn Fn(&str) -> bool) -> Result<Vec<(String, std::process::Child)>> {
+    let mut repos = config.get_all_repositories();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut reloaders = Vec::new();
+    for (name, repo) in repos {
+        let Some(watch_command) = &repo.watch_command else {
+            continue;
+        };
+        if !selected(&name) || !config.is_enabled(&name, repo) {
+            continue;
+        }
+        stop_started(config, &[name.as_str()])?;
+
+        println!("{} Running {}'s reloader: {}", "[>]".cyan(), name, watch_command.dimmed());
+        let child = Command::new("sh")
+            .args(["-c", watch_command])
+            .current_dir(config.workspace_root.join(&repo.path))
+            .envs(service_env(config, repo)?)
+            .stdin(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start the reloader for {}", name))?;
+        reloaders.push((name, child));
+    }
+    Ok(reloaders)
+}
+
+/// Builds a service with cargo or its build command, returning whether it
+/// succeeded
+pub(crate) fn build_service(config: &Config, service: &ChangedService, profile: BuildProfile) -> Result<bool> {
+    if let Some(build) = &service.build {
+        return runtime::build(config, &service.name, build, &service.path);
+    }
+    let mut cmd = config.cargo();
+    cmd.args(profile.cargo_args())
+        .current_dir(&service.path)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+    let logged = ui::log_command(&cmd);
+    events::build(&service.name, || {
+        let status = tracing::info_span!("build", service = %service.name)
+            .in_scope(|| cmd.status())
+            .with_context(|| format!("Failed to build {}", service.name))?;
+        logged.finished(&status);
+        Ok(status.success())
+    })
+}
+
+/// Like [`build_service`], with the build's output written to `log`
+/// instead of the terminal, for builds running side by side
+pub(crate) fn build_service_logged(
+    config: &Config,
+    service: &ChangedService,
+    profile: BuildProfile,
+    log: &Path,
+) -> Result<bool> {
+    let mut cmd = match &service.build {
+        Some(build) => {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", build]).env("SYLA_WORKSPACE", &config.workspace_root);
+            cmd
+        }
+        None => {
+            let mut cmd = config.cargo();
+            cmd.args(profile.cargo_args());
+            cmd
+        }
+    };
+    if let Some(parent) = log.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(log).with_context(|| format!("Failed to create {}", log.display()))?;
+    cmd.current_dir(&service.path).stdout(file.try_clone()?).stderr(file);
+    let logged = ui::log_command(&cmd);
+    events::build(&service.name, || {
+        let status = tracing::info_span!("build", service = %service.name)
+            .in_scope(|| cmd.status())
+            .with_context(|| format!("Failed to build {}", service.name))?;
+        logged.finished(&status);
+        Ok(status.success())
+    })
+}
+
+async fn build_changed(
+    config: &Config,
+    all: bool,
+    since: Option<&str>,
+    dry_run: bool,
+    profile: BuildProfile,
+    tags: &[String],
+    jobs: usize,
+) -> Result<()> {
+    say!("{} ({})", "Building changed services...".bold(), profile.name());
     
-    let mut cmd = Command::new("make");
-    if all {
-        cmd.arg("all");
+    let mut repos = config.get_all_repositories();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    repos.retain(|(_, repo)| repo.has_any_tag(tags));
+    let targets: Vec<ChangedService> = if all {
+        repos
+            .iter()
+            .filter_map(|(name, repo)| {
+                let path = config.workspace_root.join(&repo.path);
+                let build = Runtime::of(repo).build_command(repo, &path);
+                (build.is_some() || path.join("Cargo.toml").exists())
+                    .then(|| ChangedService { name: name.clone(), path, reason: "--all".to_string(), built: None, build })
+            })
+            .collect()
     } else {
-        cmd.arg("build-changed");
+        changes::detect(config, profile, since)?
+            .into_iter()
+            .filter(|service| config.manifest.repositories[&service.name].has_any_tag(tags))
+            .collect()
+    };
+    
+    if targets.is_empty() {
+        say!("{} Everything is up to date", "✓".green());
+        return Ok(());
+    }
+    let plan = build_plan::plan(config, targets, &repos);
+    // The list is what a dry run is for; otherwise it's progress
+    for build in plan.iter().filter(|_| dry_run || !ui::is_quiet()) {
+        let mut reason = build.service.reason.clone();
+        if !build.after.is_empty() {
+            reason.push_str(&format!(", after {}", build.after.join(", ")));
+        }
+        println!("  {} {} {}", "*".cyan(), build.service.name, format!("({})", reason).dimmed());
+    }
+    if dry_run {
+        return Ok(());
     }
-    cmd.current_dir(&config.workspace_root);
-    cmd.stdout(Stdio::inherit());
-    cmd.stderr(Stdio::inherit());
     
-    let status = cmd.status()
-        .context("Failed to run make build")?;
+    // Taken before building, so edits made meanwhile count next time
+    let mut baselines: HashMap<String, changes::Baseline> = plan
+        .iter()
+        .filter_map(|build| {
+            let repo = &config.manifest.repositories[&build.service.name];
+            Some((build.service.name.clone(), changes::snapshot(config, repo, profile)?))
+        })
+        .collect();
+    let started = Instant::now();
+    let results = build_plan::run(config, plan, profile, jobs).await;
+    for result in results.iter().filter(|result| result.outcome == build_plan::Outcome::Built) {
+        if let Some(baseline) = baselines.remove(&result.name) {
+            BuildCache::record(config, &result.name, profile, baseline)?;
+        }
+    }
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|result| result.outcome != build_plan::Outcome::Built)
+        .map(|result| result.name.as_str())
+        .collect();
+    if !ui::is_quiet() {
+        print_build_times(&results, started.elapsed());
+    }
+    notify(
+        &config.settings.notifications,
+        Event::BuildFinished {
+            target: if all { "Workspace" } else { "Changed services" },
+            success: failed.is_empty(),
+            duration: started.elapsed(),
+        },
+    );
         
-    if !status.success() {
-        return Err(anyhow::anyhow!("Build failed"));
+    if !failed.is_empty() {
+        return Err(anyhow::anyhow!("Build failed: {}", failed.join(", ")));
     }
     
-    println!("{} Build complete", "✓".green());
+    say!("{} Build complete", "✓".green());
     Ok(())
-}
\ No newline at end of file
+}
+
+fn print_build_times(results: &[build_plan::BuildResult], elapsed: Duration) {
+    let mut table = comfy_table::Table::new();
+    table.set_header(vec!["Service", "Result", "Time"]);
+    for result in results {
+        let outcome = match result.outcome {
+            build_plan::Outcome::Built => "built".green(),
+            build_plan::Outcome::Failed => "failed".red(),
+            build_plan::Outcome::Skipped => "skipped".yellow(),
+        };
+        let time = match result.outcome {
+            build_plan::Outcome::Skipped => "-".to_string(),
+            _ => build_plan::seconds(result.duration),
+        };
+        table.add_row(vec![result.name.clone(), outcome.to_string(), time]);
+    }
+    let total: Duration = results.iter().map(|result| result.duration).sum();
+    println!("\n{}", table);
+    println!(
+        "{}",
+        format!("{} wall clock, {} of builds", build_plan::seconds(elapsed), build_plan::seconds(total)).dimmed()
+    );
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use colored::Colorize;
+use comfy_table::{Cell, Table};
+use std::path::PathBuf;
+
+use crate::commands::OutputFormat;
+use crate::config::Config;
+use crate::names;
+use crate::services::registry::{Health, Registry};
+
+/// Where a service is listening and whether it is up, freshly checked and
+/// written back to the registry
+pub async fn run(
+    service: Option<String>,
+    url: bool,
+    output: Option<OutputFormat>,
+    workspace_root: Option<PathBuf>,
+) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    let registry = Registry::refresh(&config).await?;
+
+    let Some(service) = service else {
+        if url {
+            anyhow::bail!("--url needs a service to look up");
+        }
+        return match output {
+            Some(format) => format.print(&registry),
+            None => {
+                print_table(&registry);
+                Ok(())
+            }
+        };
+    };
+
+    let name = names::resolve("service", &service, registry.services.keys().map(String::as_str))?;
+    let endpoint = &registry.services[&name];
+    if url {
+        println!("{}", endpoint.url);
+        return Ok(());
+    }
+    if let Some(format) = output {
+        return format.print(endpoint);
+    }
+    println!("{} {}", name.bold(), health_label(endpoint.health));
+    println!("  Address: {}", endpoint.address());
+    println!("  URL:     {}", endpoint.url);
+    if endpoint.ports.len() > 1 {
+        let ports: Vec<String> = endpoint.ports.iter().map(u16::to_string).collect();
+        println!("  Ports:   {}", ports.join(", "));
+    }
+    Ok(())
+}
+
+fn health_label(health: Health) -> colored::ColoredString {
+    match health {
+        Health::Healthy => "healthy".green(),
+        Health::Up => "up".green(),
+        Health::Unhealthy => "unhealthy".yellow(),
+        Health::Down => "down".red(),
+    }
+}
+
+fn print_table(registry: &Registry) {
+    if registry.services.is_empty() {
+        println!("No services or infrastructure with ports in the manifest");
+        return;
+    }
+    let mut table = Table::new();
+    table.set_header(vec!["Name", "Address", "Health", "URL"]);
+    for (name, endpoint) in &registry.services {
+        table.add_row(vec![
+            Cell::new(name),
+            Cell::new(endpoint.address()),
+            Cell::new(health_label(endpoint.health).to_string()),
+            Cell::new(&endpoint.url),
+        ]);
+    }
+    println!("{}", table);
+}
@@ -1,117 +1,429 @@
 use anyhow::Result;
 use colored::Colorize;
+use serde::Serialize;
 use std::path::PathBuf;
+use std::time::Duration;
 use which::which;
 
-use crate::config::Config;
+use crate::changes;
+use crate::commands::status::check_health;
+use crate::commands::{dev, why, OutputFormat};
+use crate::config::{BuildProfile, Config};
 use crate::docker;
+use crate::ports;
+use crate::services::state::StartedServices;
 
-pub async fn run(fix: bool, workspace_root: Option<PathBuf>) -> Result<()> {
+/// One line of `syla doctor`
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+    /// How to fix the check when it fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), ok: true, detail: detail.into(), fix: None }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, fix: Option<&str>) -> Self {
+        Self { name: name.to_string(), ok: false, detail: detail.into(), fix: fix.map(String::from) }
+    }
+}
+
+/// `syla doctor` in the shape `--output` prints
+#[derive(Debug, Serialize)]
+pub struct DoctorReport {
+    pub ready: bool,
+    pub checks: Vec<Check>,
+}
+
+pub async fn run(fix: bool, gpu: bool, output: Option<OutputFormat>, workspace_root: Option<PathBuf>) -> Result<()> {
     let config = Config::load(workspace_root)?;
     
-    println!("{} {}", "[?]".cyan(), "Checking system health...".bold());
+    if output.is_none() {
+        println!("{} {}", "[?]".cyan(), "Checking system health...".bold());
+        println!();
+    }
+
+    let mut checks = system_checks(&config).await;
+    if gpu {
+        checks.extend(gpu_checks().await);
+    }
+    let report = DoctorReport { ready: checks.iter().all(|check| check.ok), checks };
+    if let Some(format) = output {
+        return format.print(&report);
+    }
+
+    for check in &report.checks {
+        let mark = if check.ok { "[OK]".green() } else { "[X]".red() };
+        println!("{}: {} ({})", check.name, mark, check.detail);
+        if let Some(hint) = check.fix.as_ref().filter(|_| fix) {
+            println!("  {} {}", "->".dimmed(), hint);
+        }
+    }
+
+    // Summary
     println!();
+    if report.ready {
+        println!("{} {}", "[OK]".green().bold(), "System ready!".bold());
+    } else {
+        println!("{} {}", "[X]".red().bold(), "Issues found".bold());
+        if !fix {
+            println!("\nRun {} to see fix suggestions", "syla doctor --fix".bright_black());
+        }
+    }
+
+    Ok(())
+}
 
-    let mut all_good = true;
+async fn system_checks(config: &Config) -> Vec<Check> {
+    let mut checks = Vec::new();
 
     // Check workspace
-    print!("Workspace: ");
     if config.workspace_root.exists() {
-        println!("{} ({})", "[OK]".green(), config.workspace_root.display());
+        checks.push(Check::pass("Workspace", config.workspace_root.display().to_string()));
     } else {
-        println!("{} (not found)", "[X]".red());
-        all_good = false;
+        checks.push(Check::fail("Workspace", "not found", None));
     }
 
     // Check Git
-    print!("Git: ");
-    match which("git") {
-        Ok(path) => {
-            match tokio::process::Command::new("git")
-                .arg("--version")
-                .output()
-                .await
-            {
-                Ok(output) => {
-                    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    println!("{} ({} at {})", "[OK]".green(), version, path.display());
-                }
-                Err(e) => {
-                    println!("{} (error: {})", "[X]".red(), e);
-                    all_good = false;
-                }
+    checks.push(tool("Git", "git", "git", "Install git: https://git-scm.com/downloads").await);
+
+    // Check Docker
+    checks.push(match docker::check_docker().await {
+        Ok(version) => Check::pass("Docker", version),
+        Err(e) => Check::fail("Docker", e.to_string(), Some("Install Docker: https://docs.docker.com/get-docker/")),
+    });
+
+    // Check Rust
+    checks.push(tool("Rust", "cargo", "rustc", "Install Rust: https://rustup.rs/").await);
+
+    // Check build cache
+    if config.settings.build.sccache {
+        checks.push(match which("sccache") {
+            Ok(path) => Check::pass("sccache", path.display().to_string()),
+            Err(_) => Check::fail(
+                "sccache",
+                "enabled in workspace.toml but not found",
+                Some("Install sccache: cargo install sccache"),
+            ),
+        });
+    }
+
+    // Check configuration
+    let config_path = config.workspace_root.join(".platform/config/repos.toml");
+    if config_path.exists() {
+        checks.push(Check::pass("Configuration", "repos.toml"));
+    } else {
+        checks.push(Check::fail("Configuration", "repos.toml not found", None));
+    }
+
+    checks
+}
+
+/// Image the GPU container check runs; the container toolkit mounts
+/// `nvidia-smi` into it
+const GPU_TEST_IMAGE: &str = "ubuntu:22.04";
+
+/// Time for the GPU container check, which may pull its image first
+const GPU_CONTAINER_TIMEOUT: Duration = Duration::from_secs(120);
+
+const DRIVER_INSTALL: &str = "Install the NVIDIA driver for your GPU: https://www.nvidia.com/Download/index.aspx";
+const TOOLKIT_INSTALL: &str =
+    "Install nvidia-container-toolkit: https://docs.nvidia.com/datacenter/cloud-native/container-toolkit/latest/install-guide.html";
+const TOOLKIT_CONFIGURE: &str =
+    "Register the NVIDIA runtime with Docker: sudo nvidia-ctk runtime configure --runtime=docker && sudo systemctl restart docker";
+
+/// What GPU-enabled executions need: a driver, the container toolkit, and
+/// a Docker that can hand a GPU to a container
+async fn gpu_checks() -> Vec<Check> {
+    vec![gpu_driver().await, container_toolkit().await, gpu_container().await]
+}
+
+async fn gpu_driver() -> Check {
+    const NAME: &str = "NVIDIA driver";
+    if which("nvidia-smi").is_err() {
+        return Check::fail(NAME, "nvidia-smi not found", Some(DRIVER_INSTALL));
+    }
+    let output = tokio::process::Command::new("nvidia-smi")
+        .args(["--query-gpu=name,driver_version", "--format=csv,noheader"])
+        .output()
+        .await;
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        // Also what a driver that isn't loaded looks like
+        Ok(output) => return Check::fail(NAME, last_line(&output), Some(DRIVER_INSTALL)),
+        Err(e) => return Check::fail(NAME, format!("error: {}", e), None),
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let gpus: Vec<(&str, &str)> = stdout
+        .lines()
+        .filter_map(|line| line.rsplit_once(','))
+        .map(|(name, driver)| (name.trim(), driver.trim()))
+        .collect();
+    match gpus.as_slice() {
+        [] => Check::fail(NAME, "no GPUs found", Some(DRIVER_INSTALL)),
+        [(name, driver)] => Check::pass(NAME, format!("{}, driver {}", name, driver)),
+        [(name, driver), ..] => Check::pass(NAME, format!("{} GPUs ({}), driver {}", gpus.len(), name, driver)),
+    }
+}
+
+async fn container_toolkit() -> Check {
+    const NAME: &str = "NVIDIA Container Toolkit";
+    // Older installs only have the lower-level CLI
+    let Some(program) = ["nvidia-ctk", "nvidia-container-cli"].into_iter().find(|program| which(program).is_ok()) else {
+        return Check::fail(NAME, "nvidia-ctk not found", Some(TOOLKIT_INSTALL));
+    };
+    match tokio::process::Command::new(program).arg("--version").output().await {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Check::pass(NAME, stdout.lines().next().unwrap_or(program).trim())
+        }
+        Ok(output) => Check::fail(NAME, last_line(&output), Some(TOOLKIT_INSTALL)),
+        Err(e) => Check::fail(NAME, format!("error: {}", e), None),
+    }
+}
+
+async fn gpu_container() -> Check {
+    const NAME: &str = "GPU container";
+    if let Some(reason) = docker::unavailable().await {
+        return Check::fail(NAME, format!("Docker is not available ({})", reason), None);
+    }
+    let run = tokio::process::Command::new("docker")
+        .args(["run", "--rm", "--gpus", "all", GPU_TEST_IMAGE, "nvidia-smi", "-L"])
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(GPU_CONTAINER_TIMEOUT, run).await {
+        Ok(Ok(output)) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Check::pass(NAME, stdout.lines().next().unwrap_or("GPU visible").trim())
+        }
+        Ok(Ok(output)) => Check::fail(NAME, last_line(&output), Some(TOOLKIT_CONFIGURE)),
+        Ok(Err(e)) => Check::fail(NAME, format!("error: {}", e), None),
+        Err(_) => Check::fail(
+            NAME,
+            format!("docker run did not finish within {}s", GPU_CONTAINER_TIMEOUT.as_secs()),
+            None,
+        ),
+    }
+}
+
+/// The last thing a failed program said, which is usually why
+fn last_line(output: &std::process::Output) -> String {
+    [&output.stderr, &output.stdout]
+        .into_iter()
+        .find_map(|stream| {
+            let text = String::from_utf8_lossy(stream);
+            text.lines().map(str::trim).rfind(|line| !line.is_empty()).map(str::to_string)
+        })
+        .unwrap_or_else(|| format!("exited with {}", output.status))
+}
+
+/// Finds `program` on PATH and asks `version_program` for its version
+async fn tool(name: &str, program: &str, version_program: &str, install: &str) -> Check {
+    let Ok(path) = which(program) else {
+        return Check::fail(name, "not found", Some(install));
+    };
+    match tokio::process::Command::new(version_program).arg("--version").output().await {
+        Ok(output) => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            Check::pass(name, format!("{} at {}", version, path.display()))
+        }
+        Err(e) => Check::fail(name, format!("error: {}", e), None),
+    }
+}
+
+/// Checks one service end to end, to answer "why is this service down?"
+pub async fn service(config: &Config, service: &str, profile: BuildProfile) -> Result<()> {
+    let name = why::resolve(config, service)?;
+    let Some(repo) = config.manifest.repositories.get(&name) else {
+        anyhow::bail!("{} is infrastructure, not a service; see `syla status --detailed`", name);
+    };
+
+    println!("{} {}", "[?]".cyan(), format!("Diagnosing {}...", name).bold());
+    println!();
+
+    let mut problems = Vec::new();
+
+    // Check the repository
+    print!("Repository: ");
+    let path = config.workspace_root.join(&repo.path);
+    if !path.exists() {
+        println!("{} (not cloned)", "[X]".red());
+        problems.push("not cloned; run `syla init`".to_string());
+        return print_summary(&name, &problems);
+    }
+    println!("{} ({})", "[OK]".green(), repo.path);
+
+    // Check the build is there and newer than its sources
+    print!("Build: ");
+    match dev::service_command(config, repo, profile) {
+        None => {
+            println!("{} (no {} build)", "[X]".red(), profile.name());
+            problems.push("not built; run `syla dev build-changed`".to_string());
+        }
+        Some((command, _)) => match changes::detect_one(config, &name, repo, profile, None) {
+            Ok(Some(change)) => {
+                println!("{} (stale: {})", "[!]".yellow(), change.reason);
+                problems.push(format!("build is stale ({})", change.reason));
             }
+            Ok(None) => println!("{} ({})", "[OK]".green(), command),
+            Err(e) => println!("{} (could not check freshness: {:#})", "[!]".yellow(), e),
+        },
+    }
+
+    // Check the process `dev up` started
+    print!("Process: ");
+    let started = StartedServices::load(&config.workspace_root)?
+        .services
+        .remove(&name)
+        .filter(|service| service.is_running());
+    match &started {
+        Some(service) => println!(
+            "{} (pid {}, up since {})",
+            "[OK]".green(),
+            service.pid,
+            service.started_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+        None => {
+            println!("{} (not running)", "[X]".red());
+            problems.push("not running; run `syla dev up`".to_string());
         }
-        Err(_) => {
-            println!("{} (not found)", "[X]".red());
-            all_good = false;
-            if fix {
-                println!("  {} Install git: https://git-scm.com/downloads", "->".dimmed());
+    }
+
+    // Check ports are free, or held by the service itself
+    for port in &repo.ports {
+        let Some(host) = dev::host_port(port).and_then(|p| p.parse::<u16>().ok()) else {
+            println!("Port {}: {} (assigned at `syla dev up`)", port, "-".dimmed());
+            continue;
+        };
+        print!("Port {}: ", host);
+        match (ports::is_free(host), started.is_some()) {
+            (true, false) => println!("{} (free)", "[OK]".green()),
+            (false, true) => println!("{} (in use by the service)", "[OK]".green()),
+            (true, true) => {
+                println!("{} (nothing listening)", "[!]".yellow());
+                problems.push(format!("running but not listening on port {}", host));
+            }
+            (false, false) => {
+                println!("{} (in use by another process)", "[X]".red());
+                problems.push(format!("port {} is taken by another process", host));
             }
         }
     }
 
-    // Check Docker
-    print!("Docker: ");
-    match docker::check_docker().await {
-        Ok(version) => println!("{} ({})", "[OK]".green(), version),
+    // Check the environment, secrets included, resolves
+    print!("Environment: ");
+    match dev::service_env(config, repo) {
+        Ok(env) => println!("{} ({} variables)", "[OK]".green(), env.len()),
         Err(e) => {
-            println!("{} ({})", "[X]".red(), e);
-            all_good = false;
-            if fix {
-                println!("  {} Install Docker: https://docs.docker.com/get-docker/", "->".dimmed());
-            }
+            println!("{} ({:#})", "[X]".red(), e);
+            problems.push("environment does not resolve".to_string());
         }
     }
 
-    // Check Rust
-    print!("Rust: ");
-    match which("cargo") {
-        Ok(path) => {
-            match tokio::process::Command::new("rustc")
-                .arg("--version")
-                .output()
-                .await
-            {
-                Ok(output) => {
-                    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    println!("{} ({} at {})", "[OK]".green(), version, path.display());
-                }
-                Err(e) => {
-                    println!("{} (error: {})", "[X]".red(), e);
-                    all_good = false;
-                }
+    // Check what it depends on
+    for dependency in &repo.depends_on {
+        print!("Dependency {}: ", dependency);
+        match dependency_health(config, dependency).await {
+            Some(true) => println!("{}", "[OK]".green()),
+            Some(false) => {
+                println!("{} (unreachable)", "[X]".red());
+                problems.push(format!("dependency {} is down", dependency));
             }
+            None => println!("{} (no health check or port)", "-".dimmed()),
         }
-        Err(_) => {
-            println!("{} (not found)", "[X]".red());
-            all_good = false;
-            if fix {
-                println!("  {} Install Rust: https://rustup.rs/", "->".dimmed());
+    }
+
+    // Check the health endpoint
+    print!("Health: ");
+    match &repo.health_check {
+        None => println!("{} (no health check configured)", "-".dimmed()),
+        Some(health_check) => match probe(health_check).await {
+            Ok((true, detail)) => println!("{} ({})", "[OK]".green(), detail),
+            Ok((false, detail)) | Err(detail) => {
+                println!("{} ({})", "[X]".red(), detail);
+                problems.push("health check failing".to_string());
             }
-        }
+        },
     }
 
-    // Check configuration
-    print!("Configuration: ");
-    let config_path = config.workspace_root.join(".platform/config/repos.toml");
-    if config_path.exists() {
-        println!("{} (repos.toml)", "[OK]".green());
-    } else {
-        println!("{} (repos.toml not found)", "[X]".red());
-        all_good = false;
+    // Show how the last run ended
+    let log_file = dev::service_log_file(config, &name);
+    if let Ok(log) = std::fs::read_to_string(&log_file) {
+        let lines: Vec<&str> = log.lines().collect();
+        if !lines.is_empty() {
+            println!("\n{} ({})", "Recent log:".bold(), log_file.display());
+            for line in &lines[lines.len().saturating_sub(LOG_EXCERPT_LINES)..] {
+                println!("  {}", line.dimmed());
+            }
+        }
     }
 
-    // Summary
+    print_summary(&name, &problems)
+}
+
+/// Lines of the service's log shown by `dev doctor <service>`
+const LOG_EXCERPT_LINES: usize = 10;
+
+fn print_summary(name: &str, problems: &[String]) -> Result<()> {
     println!();
-    if all_good {
-        println!("{} {}", "[OK]".green().bold(), "System ready!".bold());
+    if problems.is_empty() {
+        println!("{} {}", "[OK]".green().bold(), format!("{} looks healthy", name).bold());
     } else {
-        println!("{} {}", "[X]".red().bold(), "Issues found".bold());
-        if !fix {
-            println!("\nRun {} to see fix suggestions", "syla doctor --fix".bright_black());
+        println!("{} {}", "[X]".red().bold(), format!("{} has {} problems:", name, problems.len()).bold());
+        for problem in problems {
+            println!("  - {}", problem);
         }
     }
-
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Whether a dependency answers its health check, or accepts connections
+/// on its first port; `None` when there is neither
+async fn dependency_health(config: &Config, dependency: &str) -> Option<bool> {
+    let (health_check, port) = match dependency.strip_prefix("infrastructure.") {
+        Some(infra) => {
+            let infra = config.manifest.infrastructure.get(infra)?;
+            (infra.health_check.clone(), infra.ports.first().cloned())
+        }
+        None => {
+            let repo = config.manifest.repositories.get(dependency)?;
+            (repo.health_check.clone(), repo.ports.first().cloned())
+        }
+    };
+    if let Some(health_check) = health_check {
+        let healthy = tokio::time::timeout(Duration::from_secs(2), check_health(&health_check)).await;
+        return Some(matches!(healthy, Ok(Ok(true))));
+    }
+    let port: u16 = dev::host_port(&port?)?.parse().ok()?;
+    Some(!ports::is_free(port))
+}
+
+/// Runs a health check, describing the response
+async fn probe(health_check: &str) -> Result<(bool, String), String> {
+    if !health_check.starts_with("http://") && !health_check.starts_with("https://") {
+        let healthy = check_health(health_check).await.map_err(|e| e.to_string())?;
+        let detail = if healthy { "command succeeded" } else { "command failed" };
+        return Ok((healthy, detail.to_string()));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client.get(health_check).send().await.map_err(|e| format!("{}: {}", health_check, e))?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let body: String = body.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(80).collect();
+    let detail = if body.is_empty() {
+        format!("HTTP {}", status)
+    } else {
+        format!("HTTP {}: {}", status, body)
+    };
+    Ok((status.is_success(), detail))
+}
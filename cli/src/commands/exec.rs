@@ -0,0 +1,719 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use comfy_table::{Cell, Table};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::commands::dev::host_port;
+use crate::commands::{ExitStatus, OutputFormat};
+use crate::commands::executions;
+use crate::config::Config;
+use crate::docker;
+use crate::events;
+
+/// Where the execution service listens when the workspace doesn't say
+const DEFAULT_URL: &str = "http://localhost:8083";
+/// How long to wait for the platform to finish one submission
+const WAIT_LIMIT: Duration = Duration::from_secs(600);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Per-run limit for local runs, matching the execution service's default
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+pub struct ExecOptions {
+    pub files: Vec<PathBuf>,
+    pub language: Option<String>,
+    pub local: bool,
+    pub tests: Option<PathBuf>,
+    pub batch: bool,
+    pub jobs: usize,
+    pub report: PathBuf,
+    pub timeout: Option<u64>,
+    pub url: Option<String>,
+}
+
+/// Test cases in the execution service's format; a bare array of cases
+/// works too
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TestSpec {
+    #[serde(default)]
+    cases: Vec<TestCase>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+    #[serde(default)]
+    comparison: Comparison,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TestCase {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default)]
+    stdin: String,
+    expected_output: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Comparison {
+    Exact,
+    #[default]
+    TrimWhitespace,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TestsFile {
+    Spec(TestSpec),
+    Cases(Vec<TestCase>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Verdict {
+    Passed,
+    Failed,
+    Error,
+    Timeout,
+}
+
+impl Verdict {
+    fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Passed => "passed",
+            Verdict::Failed => "failed",
+            Verdict::Error => "error",
+            Verdict::Timeout => "timeout",
+        }
+    }
+
+    fn label(self) -> colored::ColoredString {
+        match self {
+            Verdict::Passed => "passed".green(),
+            Verdict::Failed => "failed".red(),
+            Verdict::Error => "error".red(),
+            Verdict::Timeout => "timeout".yellow(),
+        }
+    }
+}
+
+/// One test case's outcome, as the execution service reports it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CaseVerdict {
+    name: String,
+    status: Verdict,
+    #[serde(default)]
+    duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expected_output: Option<String>,
+    #[serde(default)]
+    actual_output: String,
+    #[serde(default)]
+    stderr: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RunOutput {
+    pub(crate) exit_code: i32,
+    #[serde(default)]
+    pub(crate) stdout: String,
+    #[serde(default)]
+    pub(crate) stderr: String,
+    #[serde(default)]
+    pub(crate) duration_ms: u64,
+}
+
+/// The parts of the execution service's job the CLI looks at
+#[derive(Debug, Deserialize)]
+pub(crate) struct Job {
+    pub(crate) id: String,
+    pub(crate) status: String,
+    #[serde(default)]
+    pub(crate) result: Option<RunOutput>,
+    #[serde(default)]
+    pub(crate) test_report: Option<TestReport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TestReport {
+    verdicts: Vec<CaseVerdict>,
+}
+
+impl TestReport {
+    pub(crate) fn total(&self) -> usize {
+        self.verdicts.len()
+    }
+
+    pub(crate) fn passed(&self) -> usize {
+        self.verdicts.iter().filter(|v| v.status == Verdict::Passed).count()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileResult {
+    file: PathBuf,
+    language: String,
+    verdict: Verdict,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    execution_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    stdout: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    stderr: String,
+    tests_passed: usize,
+    tests_total: usize,
+    duration_ms: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    verdicts: Vec<CaseVerdict>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchReport {
+    total: usize,
+    passed: usize,
+    failed: usize,
+    errors: usize,
+    timeouts: usize,
+    duration_ms: u64,
+    results: Vec<FileResult>,
+}
+
+/// Where submissions run
+#[derive(Clone)]
+enum Runner {
+    Platform { client: reqwest::Client, url: String },
+    Local,
+}
+
+pub async fn run(options: ExecOptions, output: Option<OutputFormat>, workspace_root: Option<PathBuf>) -> Result<()> {
+    let files = expand(&options.files)?;
+    if files.len() > 1 && !options.batch {
+        anyhow::bail!("{} files given; pass --batch to execute more than one", files.len());
+    }
+    let tests = options.tests.as_deref().map(load_tests).transpose()?;
+    let runner = if options.local {
+        if tests.as_ref().is_some_and(|spec| spec.command.is_some()) {
+            anyhow::bail!("Command tests only run on the platform; drop --local");
+        }
+        if let Some(reason) = docker::unavailable().await {
+            anyhow::bail!("--local needs Docker, which is not available ({})", reason);
+        }
+        Runner::Local
+    } else {
+        let url = options.url.clone().unwrap_or_else(|| execution_url(workspace_root.clone()));
+        Runner::Platform { client: reqwest::Client::new(), url: url.trim_end_matches('/').to_string() }
+    };
+
+    if !options.batch {
+        let file = files.into_iter().next().context("No file to execute")?;
+        let result = execute(&runner, file, options.language.clone(), tests.as_ref(), options.timeout).await;
+        executions::remember(workspace_root, result.execution_id.clone());
+        return print_single(result, output);
+    }
+
+    let started = Instant::now();
+    let limit = Arc::new(Semaphore::new(options.jobs.max(1)));
+    let tests = Arc::new(tests);
+    let mut running = JoinSet::new();
+    for file in files {
+        let language = options.language.clone();
+        let (runner, tests, limit) = (runner.clone(), tests.clone(), limit.clone());
+        let timeout = options.timeout;
+        running.spawn(async move {
+            let _permit = limit.acquire_owned().await;
+            let result = execute(&runner, file, language, tests.as_ref().as_ref(), timeout).await;
+            if output.is_none() {
+                say!("{} {} {}", marker(result.verdict), result.file.display(), result.verdict.label());
+            }
+            result
+        });
+    }
+    let mut results = Vec::new();
+    while let Some(result) = running.join_next().await {
+        results.push(result?);
+    }
+    results.sort_by(|a, b| a.file.cmp(&b.file));
+    executions::remember(workspace_root, results.iter().filter_map(|result| result.execution_id.clone()));
+
+    let count = |verdict| results.iter().filter(|r| r.verdict == verdict).count();
+    let report = BatchReport {
+        total: results.len(),
+        passed: count(Verdict::Passed),
+        failed: count(Verdict::Failed),
+        errors: count(Verdict::Error),
+        timeouts: count(Verdict::Timeout),
+        duration_ms: started.elapsed().as_millis() as u64,
+        results,
+    };
+    std::fs::write(&options.report, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("Failed to write {}", options.report.display()))?;
+
+    match output {
+        Some(format) => format.print(&report)?,
+        None => {
+            print_table(&report);
+            println!(
+                "\n{} files: {} passed, {} failed, {} errors, {} timed out in {:.1}s",
+                report.total,
+                report.passed.to_string().green(),
+                report.failed,
+                report.errors,
+                report.timeouts,
+                report.duration_ms as f64 / 1000.0
+            );
+            println!("Report written to {}", options.report.display());
+        }
+    }
+
+    let unsuccessful = report.total - report.passed;
+    if unsuccessful > 0 {
+        return Err(ExitStatus {
+            code: 1,
+            message: format!("{} of {} files did not pass", unsuccessful, report.total),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Expands patterns the shell left alone, such as quoted globs
+fn expand(patterns: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        let text = pattern.to_string_lossy();
+        if pattern.exists() || !text.contains(['*', '?', '[']) {
+            files.push(pattern.clone());
+            continue;
+        }
+        let before = files.len();
+        for path in glob::glob(&text).with_context(|| format!("Invalid pattern {}", text))? {
+            let path = path?;
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+        if files.len() == before {
+            anyhow::bail!("No files match {}", text);
+        }
+    }
+    files.dedup();
+    Ok(files)
+}
+
+fn load_tests(path: &Path) -> Result<TestSpec> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let spec = match serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))? {
+        TestsFile::Spec(spec) => spec,
+        TestsFile::Cases(cases) => TestSpec { cases, command: None, comparison: Comparison::default() },
+    };
+    if spec.cases.is_empty() && spec.command.is_none() {
+        anyhow::bail!("{} has no test cases", path.display());
+    }
+    Ok(spec)
+}
+
+fn detect_language(file: &Path) -> Result<String> {
+    let language = match file.extension().and_then(|e| e.to_str()) {
+        Some("py") => "python",
+        Some("js" | "mjs" | "cjs") => "javascript",
+        Some("go") => "go",
+        Some("rs") => "rust",
+        _ => anyhow::bail!("Can't tell the language of {}; pass --language", file.display()),
+    };
+    Ok(language.to_string())
+}
+
+/// The execution service of the workspace's manifest, if there is one
+pub(crate) fn execution_url(workspace_root: Option<PathBuf>) -> String {
+    let Ok(config) = Config::load(workspace_root) else {
+        return DEFAULT_URL.to_string();
+    };
+    config
+        .manifest
+        .repositories
+        .iter()
+        .find(|(name, _)| name.rsplit('.').next() == Some("execution-service"))
+        .and_then(|(_, repo)| host_port(repo.ports.first()?))
+        .map(|port| format!("http://localhost:{}", port))
+        .unwrap_or_else(|| DEFAULT_URL.to_string())
+}
+
+/// Runs one file, in `language` or the one its extension implies; failures
+/// to run it at all, an unknown language included, become an `error`
+/// verdict so a batch carries on
+async fn execute(
+    runner: &Runner,
+    file: PathBuf,
+    language: Option<String>,
+    tests: Option<&TestSpec>,
+    timeout: Option<u64>,
+) -> FileResult {
+    let started = Instant::now();
+    let language = language.map(Ok).unwrap_or_else(|| detect_language(&file));
+    let mut result = FileResult {
+        file,
+        language: language.as_ref().cloned().unwrap_or_default(),
+        verdict: Verdict::Error,
+        execution_id: None,
+        exit_code: None,
+        stdout: String::new(),
+        stderr: String::new(),
+        tests_passed: 0,
+        tests_total: 0,
+        duration_ms: 0,
+        verdicts: Vec::new(),
+        error: None,
+    };
+    let outcome = match (language, runner) {
+        (Err(e), _) => Err(e),
+        (Ok(_), Runner::Platform { client, url }) => execute_remote(client, url, &mut result, tests, timeout).await,
+        (Ok(_), Runner::Local) => execute_local(&mut result, tests, timeout).await,
+    };
+    if let Err(e) = outcome {
+        result.verdict = Verdict::Error;
+        result.error = Some(format!("{:#}", e));
+    }
+    if result.duration_ms == 0 {
+        result.duration_ms = started.elapsed().as_millis() as u64;
+    }
+    events::emit(events::Event::ExecutionCompleted {
+        id: result.execution_id.as_deref(),
+        file: Some(&result.file.to_string_lossy()),
+        status: result.verdict.as_str(),
+        exit_code: result.exit_code,
+    });
+    result
+}
+
+async fn execute_remote(
+    client: &reqwest::Client,
+    url: &str,
+    result: &mut FileResult,
+    tests: Option<&TestSpec>,
+    timeout: Option<u64>,
+) -> Result<()> {
+    let code = std::fs::read_to_string(&result.file).with_context(|| format!("Failed to read {}", result.file.display()))?;
+    let mut request = serde_json::json!({ "code": code, "language": result.language });
+    if let Some(timeout) = timeout {
+        request["timeout_seconds"] = timeout.into();
+    }
+    if let Some(tests) = tests {
+        request["tests"] = serde_json::to_value(tests)?;
+    }
+
+    let response = client
+        .post(format!("{}/executions", url))
+        .json(&request)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach the execution service at {}", url))?;
+    let job = checked(response).await?;
+    result.execution_id = job["id"].as_str().map(str::to_string);
+    let job: Job = serde_json::from_value(wait(client, url, job).await?)?;
+
+    if let Some(run) = job.result {
+        result.exit_code = Some(run.exit_code);
+        result.stdout = run.stdout;
+        result.stderr = run.stderr;
+        result.duration_ms = run.duration_ms;
+    }
+    result.verdict = match (job.status.as_str(), job.test_report) {
+        (_, Some(report)) => {
+            result.verdicts = report.verdicts;
+            tally(result)
+        }
+        ("timeout", None) => Verdict::Timeout,
+        ("completed", None) if result.exit_code == Some(0) => Verdict::Passed,
+        ("completed", None) => Verdict::Failed,
+        (_, None) => Verdict::Error,
+    };
+    Ok(())
+}
+
+/// Polls a submitted job until it is no longer queued or running
+pub(crate) async fn wait(client: &reqwest::Client, url: &str, mut job: serde_json::Value) -> Result<serde_json::Value> {
+    let id = job["id"].as_str().context("Unexpected response from the execution service")?.to_string();
+    let deadline = Instant::now() + WAIT_LIMIT;
+    while matches!(job["status"].as_str(), Some("queued" | "running")) {
+        if Instant::now() > deadline {
+            anyhow::bail!("Execution {} still {} after {}s", id, job["status"], WAIT_LIMIT.as_secs());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let response = client.get(format!("{}/executions/{}", url, id)).send().await?;
+        job = checked(response).await?;
+    }
+    Ok(job)
+}
+
+pub(crate) async fn checked(response: reqwest::Response) -> Result<serde_json::Value> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        match problem_message(&body) {
+            Some(message) => anyhow::bail!("{}", message),
+            None => anyhow::bail!("Execution service returned {}: {}", status, body.trim()),
+        }
+    }
+    response.json().await.context("Unexpected response from the execution service")
+}
+
+/// "Title: detail (code)" from an RFC 7807 problem document, with a hint
+/// when the request can be retried
+fn problem_message(body: &str) -> Option<String> {
+    let problem: serde_json::Value = serde_json::from_str(body).ok()?;
+    let code = problem["code"].as_str()?;
+    let title = problem["title"].as_str().unwrap_or("Execution service error");
+    let mut message = match problem["detail"].as_str() {
+        Some(detail) if !detail.is_empty() => format!("{}: {} ({})", title, detail, code),
+        _ => format!("{} ({})", title, code),
+    };
+    if problem["retryable"].as_bool() == Some(true) {
+        match problem["retry_after_seconds"].as_u64() {
+            Some(seconds) => message.push_str(&format!("; retry in {}s", seconds)),
+            None => message.push_str("; retrying may succeed"),
+        }
+    }
+    Some(message)
+}
+
+/// Counts passed cases and decides the file's verdict from them
+fn tally(result: &mut FileResult) -> Verdict {
+    result.tests_total = result.verdicts.len();
+    result.tests_passed = result.verdicts.iter().filter(|v| v.status == Verdict::Passed).count();
+    let worst = [Verdict::Error, Verdict::Timeout, Verdict::Failed];
+    worst
+        .into_iter()
+        .find(|verdict| result.verdicts.iter().any(|v| v.status == *verdict))
+        .unwrap_or(Verdict::Passed)
+}
+
+async fn execute_local(result: &mut FileResult, tests: Option<&TestSpec>, timeout: Option<u64>) -> Result<()> {
+    let timeout = Duration::from_secs(timeout.unwrap_or(DEFAULT_TIMEOUT_SECS));
+    let Some(tests) = tests else {
+        let run = run_container(&result.file, &result.language, "", timeout).await?;
+        result.verdict = match run.exit_code {
+            None => Verdict::Timeout,
+            Some(0) => Verdict::Passed,
+            Some(_) => Verdict::Failed,
+        };
+        result.exit_code = run.exit_code;
+        result.stdout = run.stdout;
+        result.stderr = run.stderr;
+        result.duration_ms = run.duration_ms;
+        return Ok(());
+    };
+
+    for (i, case) in tests.cases.iter().enumerate() {
+        let run = run_container(&result.file, &result.language, &case.stdin, timeout).await?;
+        let status = match run.exit_code {
+            None => Verdict::Timeout,
+            Some(0) if outputs_match(&run.stdout, &case.expected_output, tests.comparison) => Verdict::Passed,
+            Some(0) => Verdict::Failed,
+            Some(_) => Verdict::Error,
+        };
+        result.verdicts.push(CaseVerdict {
+            name: case.name.clone().unwrap_or_else(|| format!("case-{}", i + 1)),
+            status,
+            duration_ms: run.duration_ms,
+            expected_output: Some(case.expected_output.clone()),
+            actual_output: run.stdout,
+            stderr: run.stderr,
+        });
+    }
+    result.verdict = tally(result);
+    Ok(())
+}
+
+struct ContainerRun {
+    /// `None` when the run was killed at the time limit
+    exit_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    duration_ms: u64,
+}
+
+/// Runs a file in the language's runtime image the way the execution
+/// service does, without network access. The time limit starts once the
+/// image is present, so a first pull doesn't count against it.
+async fn run_container(file: &Path, language: &str, stdin: &str, timeout: Duration) -> Result<ContainerRun> {
+    use tokio::io::AsyncWriteExt;
+
+    let (image, source, command): (&str, &str, &[&str]) = match language {
+        "python" => ("python:3.11-slim", "main.py", &["python", "main.py"]),
+        "javascript" => ("node:20-slim", "main.js", &["node", "main.js"]),
+        "go" => ("golang:1.21-alpine", "main.go", &["go", "run", "main.go"]),
+        "rust" => ("rust:1.75-slim", "main.rs", &["sh", "-c", "rustc -o /tmp/main main.rs && /tmp/main"]),
+        other => anyhow::bail!("Local runs don't support {}; supported: python, javascript, go, rust", other),
+    };
+    let file = std::fs::canonicalize(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let name = format!("syla-exec-{}", uuid::Uuid::new_v4());
+    ensure_image(image).await?;
+
+    let started = Instant::now();
+    let mut child = tokio::process::Command::new("docker")
+        .args(["run", "--rm", "-i", "--network", "none", "--memory", "512m", "--cpus", "1", "--name", &name])
+        .arg("-v")
+        .arg(format!("{}:/workspace/{}:ro", file.display(), source))
+        .args(["-w", "/workspace", image])
+        .args(command)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .context("Failed to run docker")?;
+    // Fed alongside the run, since a program that never reads its input
+    // would otherwise block the write once the pipe fills
+    let input = child.stdin.take();
+    let stdin = stdin.to_string();
+    let writer = tokio::spawn(async move {
+        if let Some(mut input) = input {
+            let _ = input.write_all(stdin.as_bytes()).await;
+        }
+    });
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+
+    let finished = tokio::time::timeout(timeout, child.wait()).await;
+    writer.abort();
+    match finished {
+        Ok(status) => {
+            let status = status?;
+            Ok(ContainerRun {
+                exit_code: Some(status.code().unwrap_or(-1)),
+                stdout: String::from_utf8_lossy(&stdout.await?).into_owned(),
+                stderr: String::from_utf8_lossy(&stderr.await?).into_owned(),
+                duration_ms: started.elapsed().as_millis() as u64,
+            })
+        }
+        Err(_) => {
+            // The client and the container both have to go
+            let _ = child.kill().await;
+            let _ = tokio::process::Command::new("docker").args(["rm", "-f", &name]).output().await;
+            Ok(ContainerRun {
+                exit_code: None,
+                stdout: String::new(),
+                stderr: String::new(),
+                duration_ms: timeout.as_millis() as u64,
+            })
+        }
+    }
+}
+
+/// Pulls an image unless it is already present
+async fn ensure_image(image: &str) -> Result<()> {
+    let present = tokio::process::Command::new("docker")
+        .args(["image", "inspect", image])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await
+        .context("Failed to run docker")?;
+    if present.success() {
+        return Ok(());
+    }
+    let pull = tokio::process::Command::new("docker").args(["pull", "-q", image]).output().await.context("Failed to run docker")?;
+    if !pull.status.success() {
+        anyhow::bail!("Failed to pull {}: {}", image, String::from_utf8_lossy(&pull.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Reads a child's pipe to the end in the background
+fn read_all<R>(pipe: Option<R>) -> tokio::task::JoinHandle<Vec<u8>>
+where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    use tokio::io::AsyncReadExt;
+
+    tokio::spawn(async move {
+        let mut data = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut data).await;
+        }
+        data
+    })
+}
+
+fn outputs_match(actual: &str, expected: &str, comparison: Comparison) -> bool {
+    match comparison {
+        Comparison::Exact => actual == expected,
+        Comparison::TrimWhitespace => {
+            let trim = |s: &str| s.trim_end().lines().map(str::trim_end).collect::<Vec<_>>().join("\n");
+            trim(actual) == trim(expected)
+        }
+    }
+}
+
+fn marker(verdict: Verdict) -> colored::ColoredString {
+    match verdict {
+        Verdict::Passed => "[OK]".green(),
+        Verdict::Timeout => "[!]".yellow(),
+        Verdict::Failed | Verdict::Error => "[X]".red(),
+    }
+}
+
+/// Shows one run: the program's own output, or its test verdicts
+fn print_single(result: FileResult, output: Option<OutputFormat>) -> Result<()> {
+    if let Some(format) = output {
+        format.print(&result)?;
+    } else if let Some(error) = &result.error {
+        anyhow::bail!("{}", error);
+    } else if result.verdicts.is_empty() {
+        print!("{}", result.stdout);
+        eprint!("{}", result.stderr);
+    } else {
+        for verdict in &result.verdicts {
+            println!("{} {} ({}ms)", marker(verdict.status), verdict.name, verdict.duration_ms);
+            if verdict.status != Verdict::Passed {
+                if let Some(expected) = &verdict.expected_output {
+                    println!("    expected: {:?}", expected);
+                }
+                println!("    actual:   {:?}", verdict.actual_output);
+            }
+        }
+        println!("\n{}/{} tests passed", result.tests_passed, result.tests_total);
+    }
+
+    match result.verdict {
+        Verdict::Passed => Ok(()),
+        Verdict::Timeout => Err(ExitStatus { code: 124, message: format!("{} timed out", result.file.display()) }.into()),
+        // Pass the program's own exit code through
+        Verdict::Failed if result.verdicts.is_empty() => Err(ExitStatus {
+            code: result.exit_code.unwrap_or(1),
+            message: format!("{} exited with {}", result.file.display(), result.exit_code.unwrap_or(1)),
+        }
+        .into()),
+        _ => Err(ExitStatus {
+            code: 1,
+            message: format!("{} {}", result.file.display(), result.verdict.label()),
+        }
+        .into()),
+    }
+}
+
+fn print_table(report: &BatchReport) {
+    let mut table = Table::new();
+    table.set_header(vec!["File", "Language", "Verdict", "Tests", "Time"]);
+    for result in &report.results {
+        let tests = if result.tests_total > 0 {
+            format!("{}/{}", result.tests_passed, result.tests_total)
+        } else {
+            "-".to_string()
+        };
+        table.add_row(vec![
+            Cell::new(result.file.display()),
+            Cell::new(&result.language),
+            Cell::new(result.verdict.label().to_string()),
+            Cell::new(tests),
+            Cell::new(format!("{}ms", result.duration_ms)),
+        ]);
+    }
+    println!("{}", table);
+}
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use crate::commands::exec::{self, Job};
+use crate::commands::{ExitStatus, OutputFormat};
+use crate::events;
+use crate::ExecutionsCommands;
+
+pub async fn run(command: ExecutionsCommands, output: Option<OutputFormat>, workspace_root: Option<PathBuf>) -> Result<()> {
+    match command {
+        ExecutionsCommands::Replay { id, debug, no_wait, url } => {
+            let url = url.unwrap_or_else(|| exec::execution_url(workspace_root.clone()));
+            let url = url.trim_end_matches('/');
+            let client = reqwest::Client::new();
+            let response = client
+                .post(format!("{}/executions/{}/replay", url, id))
+                .json(&serde_json::json!({ "debug": debug }))
+                .send()
+                .await
+                .with_context(|| format!("Failed to reach the execution service at {}", url))?;
+            let job = exec::checked(response).await?;
+            let replay_id = job["id"].as_str().unwrap_or_default().to_string();
+            remember(workspace_root, [replay_id.clone()]);
+            if output.is_none() {
+                say!("{} Replaying {} as {}", "[OK]".green(), id, replay_id.bold());
+            }
+            let job = if no_wait { job } else { exec::wait(&client, url, job).await? };
+            if !no_wait {
+                events::emit(events::Event::ExecutionCompleted {
+                    id: Some(&replay_id),
+                    file: None,
+                    status: job["status"].as_str().unwrap_or_default(),
+                    exit_code: job["result"]["exit_code"].as_i64().map(|code| code as i32),
+                });
+            }
+
+            if let Some(format) = output {
+                return format.print(&job);
+            }
+            if no_wait {
+                return Ok(());
+            }
+            print_job(&job)
+        }
+    }
+}
+
+/// Executions submitted from the workspace that completion offers
+const RECENT_LIMIT: usize = 50;
+
+fn recent_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(".platform/state/executions.log")
+}
+
+/// Execution IDs submitted from the workspace, newest first
+pub fn recent(workspace_root: &Path) -> Vec<String> {
+    let content = std::fs::read_to_string(recent_path(workspace_root)).unwrap_or_default();
+    content.lines().rev().map(str::to_string).collect()
+}
+
+/// Adds executions to the recent ones, for completing their IDs later.
+/// Outside a workspace, or when it can't be written, nothing is kept.
+pub(crate) fn remember(workspace_root: Option<PathBuf>, ids: impl IntoIterator<Item = String>) {
+    let ids: Vec<String> = ids.into_iter().filter(|id| !id.is_empty()).collect();
+    if ids.is_empty() {
+        return;
+    }
+    let Ok(workspace_root) = crate::config::resolve_workspace_root(workspace_root) else {
+        return;
+    };
+    if !workspace_root.join(".platform").is_dir() {
+        return;
+    }
+    let mut kept: Vec<String> = recent(&workspace_root).into_iter().rev().collect();
+    kept.retain(|id| !ids.contains(id));
+    kept.extend(ids);
+    let kept = &kept[kept.len().saturating_sub(RECENT_LIMIT)..];
+    let path = recent_path(&workspace_root);
+    let written = std::fs::create_dir_all(path.parent().unwrap_or(&workspace_root))
+        .and_then(|_| std::fs::write(&path, kept.join("\n") + "\n"));
+    if let Err(e) = written {
+        tracing::debug!("Failed to record executions: {:#}", e);
+    }
+}
+
+/// Shows how the replay went, failing when the run did
+fn print_job(value: &serde_json::Value) -> Result<()> {
+    let job: Job = serde_json::from_value(value.clone())?;
+    if let Some(result) = &job.result {
+        print!("{}", result.stdout);
+        eprint!("{}", result.stderr);
+        println!("\nExit code {} in {}ms", result.exit_code, result.duration_ms);
+    }
+    if let Some(report) = &job.test_report {
+        println!("{}/{} tests passed", report.passed(), report.total());
+    }
+    if let Some(debug) = value["debug_info"].as_object() {
+        println!("\n{}", "Debug info".bold());
+        for (key, value) in debug {
+            let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+            println!("  {}: {}", key, value);
+        }
+    }
+
+    match job.status.as_str() {
+        "completed" => Ok(()),
+        status => Err(ExitStatus { code: 1, message: format!("Replay {} finished as {}", job.id, status) }.into()),
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use colored::Colorize;
+use comfy_table::{Cell, Table};
+use std::path::PathBuf;
+
+use crate::commands::OutputFormat;
+use crate::config;
+use crate::history::{self, Entry};
+
+/// Shows the latest `limit` recorded commands, oldest first, optionally
+/// only those whose command line or user contains `filter`
+pub async fn run(
+    filter: Option<String>,
+    limit: usize,
+    failed: bool,
+    output: Option<OutputFormat>,
+    workspace_root: Option<PathBuf>,
+) -> Result<()> {
+    let workspace_root = config::resolve_workspace_root(workspace_root)?;
+    let mut entries = history::load(&workspace_root)?;
+    let filter = filter.map(|filter| filter.to_lowercase());
+    entries.retain(|entry| {
+        !(failed && entry.success)
+            && filter.as_ref().is_none_or(|filter| {
+                command_line(entry).to_lowercase().contains(filter) || entry.user.to_lowercase().contains(filter)
+            })
+    });
+    let entries = &entries[entries.len().saturating_sub(limit)..];
+
+    if let Some(format) = output {
+        return format.print(&entries);
+    }
+    if entries.is_empty() {
+        println!("{}", "No matching commands recorded".dimmed());
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.set_header(vec!["Time", "User", "Command", "Result", "Took"]);
+    for entry in entries {
+        let result = match &entry.error {
+            None => "ok".green().to_string(),
+            Some(error) => format!("{} {}", "failed:".red(), error.lines().next().unwrap_or_default()),
+        };
+        table.add_row(vec![
+            Cell::new(entry.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S")),
+            Cell::new(&entry.user),
+            Cell::new(command_line(entry)),
+            Cell::new(result),
+            Cell::new(format!("{:.1}s", entry.duration_ms as f64 / 1000.0)),
+        ]);
+    }
+    println!("{}", table);
+    Ok(())
+}
+
+fn command_line(entry: &Entry) -> String {
+    std::iter::once("syla".to_string()).chain(entry.args.iter().cloned()).collect::<Vec<_>>().join(" ")
+}
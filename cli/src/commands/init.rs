@@ -1,28 +1,107 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
-use dialoguer::Confirm;
-use indicatif::{ProgressBar, ProgressStyle};
+use dialoguer::{Confirm, MultiSelect, Select};
+use indicatif::ProgressStyle;
 use std::path::PathBuf;
 use std::process::Command;
+use tracing::Instrument;
 
-use crate::config::Config;
+use super::dev;
+use crate::config::{BuildProfile, Config, RepoManifest, RepositoryConfig, WorkspaceSettings, WorkspaceTemplate};
+use crate::docker;
 use crate::git;
+use crate::lock::WorkspaceLock;
+use crate::names;
+use crate::runtime::{self, Runtime};
+use crate::ui;
 
-pub async fn run(platform: Option<String>, yes: bool, force: bool, workspace_root: Option<PathBuf>) -> Result<()> {
-    let config = Config::load(workspace_root)?;
+pub async fn run(
+    platform: Option<String>,
+    template: Option<String>,
+    yes: bool,
+    force: bool,
+    profile: Option<BuildProfile>,
+    tags: Vec<String>,
+    workspace_root: Option<PathBuf>,
+) -> Result<()> {
+    let mut config = Config::load(workspace_root)?;
+    config.check_tags(&tags)?;
+    let _lock = WorkspaceLock::acquire(&config.workspace_root, "init").await?;
+    let profile = config.build_profile(profile);
     
-    println!("{}", "Initializing Syla workspace...".bold());
-    println!("Workspace root: {}\n", config.workspace_root.display());
+    say!("{}", "Initializing Syla workspace...".bold());
+    say!("Workspace root: {}\n", config.workspace_root.display());
+
+    // Offer the manifest's templates when nothing was chosen on the command line
+    let template = match (template, &platform) {
+        (None, None) if !yes => choose_template(&config)?,
+        (template, _) => template,
+    };
+    let template = match template {
+        Some(name) => Some((resolve_template(&config, &name).await?, name)),
+        None => None,
+    };
+    if let Some((template, name)) = &template {
+        config.settings = WorkspaceSettings {
+            template: Some(name.clone()),
+            compose_profiles: template.compose_profiles.clone(),
+            config: template.config.clone(),
+            notifications: config.settings.notifications.clone(),
+            build: config.settings.build.clone(),
+            logs: config.settings.logs.clone(),
+            features: config.settings.features.clone(),
+            dev: config.settings.dev.clone(),
+        };
+    }
 
     // Get repositories to clone
-    let repos = if let Some(platform_name) = platform {
-        println!("Cloning repositories for platform: {}", platform_name.cyan());
-        config.get_platform_repositories(&platform_name)
+    let repos = if let Some((template, name)) = &template {
+        say!("Cloning repositories for template: {}", name.cyan());
+        if let Some(description) = &template.description {
+            say!("  {}", description.dimmed());
+        }
+        let mut repos: Vec<_> = config
+            .get_all_repositories()
+            .into_iter()
+            .filter(|(name, repo)| template.includes(name, repo))
+            .collect();
+        repos.sort_by(|a, b| a.0.cmp(&b.0));
+        repos
+    } else if let Some(platform_name) = &platform {
+        say!("Cloning repositories for platform: {}", platform_name.cyan());
+        config.get_platform_repositories(platform_name)
             .ok_or_else(|| anyhow::anyhow!("Platform '{}' not found", platform_name))?
     } else {
-        println!("Cloning all repositories");
+        say!("Cloning all repositories");
         config.get_all_repositories()
     };
+    let repos: Vec<_> = repos.into_iter().filter(|(_, repo)| repo.has_any_tag(&tags)).collect();
+    if !tags.is_empty() {
+        say!("Tagged: {}", tags.join(", ").cyan());
+    }
+    let (repos, disabled): (Vec<_>, Vec<_>) = repos.into_iter().partition(|(name, repo)| config.is_enabled(name, repo));
+    for (name, repo) in &disabled {
+        if let Some(feature) = repo.feature(name) {
+            println!(
+                "{} Skipping optional {} (enable with {})",
+                "[-]".dimmed(),
+                name,
+                format!("syla config features enable {}", feature).cyan()
+            );
+        }
+    }
+
+    // Pick exactly what to clone, starting from the template's or the full set
+    let repos = if platform.is_none() && !yes && names::can_prompt() {
+        let candidates: Vec<_> = config
+            .get_all_repositories()
+            .into_iter()
+            .filter(|(name, repo)| repo.has_any_tag(&tags) && config.is_enabled(name, repo))
+            .collect();
+        choose_repositories(candidates, &repos)?
+    } else {
+        repos
+    };
 
     if repos.is_empty() {
         println!("{}", "No repositories to clone".yellow());
@@ -67,7 +146,7 @@ pub async fn run(platform: Option<String>, yes: bool, force: bool, workspace_roo
     }
 
     // Clone repositories
-    let pb = ProgressBar::new(repos.len() as u64);
+    let pb = ui::progress_bar(repos.len() as u64);
     pb.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
@@ -98,7 +177,8 @@ pub async fn run(platform: Option<String>, yes: bool, force: bool, workspace_roo
         }
 
         // Clone repository
-        match git::clone(&repo.url, &repo_path, &repo.branch).await {
+        let span = tracing::info_span!("clone", repo = %name);
+        match git::clone(&repo.url, &repo_path, &repo.branch).instrument(span).await {
             Ok(_) => {
                 pb.println(format!("{} Cloned {}", "[OK]".green(), name));
             }
@@ -114,40 +194,57 @@ pub async fn run(platform: Option<String>, yes: bool, force: bool, workspace_roo
     }
 
     pb.finish_with_message("Done");
+
+    // Remember the template so later commands use the same profiles
+    // Only the workspace's own settings; the user config stays where it is
+    if template.is_some() {
+        let settings = WorkspaceSettings {
+            template: config.settings.template.clone(),
+            compose_profiles: config.settings.compose_profiles.clone(),
+            config: config.settings.config.clone(),
+            ..WorkspaceSettings::load(&config.workspace_root)?
+        };
+        settings.save(&config.workspace_root)?;
+    }
     
     // Start Docker infrastructure
-    println!("\n{}", "Setting up Docker infrastructure...".bold());
-    start_docker_infrastructure(&config)?;
+    say!("\n{}", "Setting up Docker infrastructure...".bold());
+    start_docker_infrastructure(&config).await?;
     
     // Build services
-    println!("\n{}", "Building services...".bold());
-    build_services(&config, &repos, force)?;
+    say!("\n{} ({})", "Building services...".bold(), profile.name());
+    build_services(&config, &repos, force, profile)?;
     
     // Run initial validation
-    println!("\n{}", "Validating setup...".bold());
+    say!("\n{}", "Validating setup...".bold());
     validate_setup(&config)?;
     
-    println!("\n{} Workspace initialized successfully!", "[OK]".green().bold());
+    say!("\n{} Workspace initialized successfully!", "[OK]".green().bold());
     
     // Next steps
-    println!("\n{}", "Next steps:".bold());
-    println!("  {} Check status", "*".cyan());
-    println!("    {}", "syla status".bright_black());
-    println!("  {} Start development environment", "*".cyan());
-    println!("    {}", "syla dev up".bright_black());
-    println!("  {} Validate workspace", "*".cyan());
-    println!("    {}", "syla dev validate".bright_black());
+    say!("\n{}", "Next steps:".bold());
+    say!("  {} Check status", "*".cyan());
+    say!("    {}", "syla status".bright_black());
+    say!("  {} Start development environment", "*".cyan());
+    say!("    {}", "syla dev up".bright_black());
+    say!("  {} Validate workspace", "*".cyan());
+    say!("    {}", "syla dev validate".bright_black());
 
     Ok(())
 }
 
-fn start_docker_infrastructure(config: &Config) -> Result<()> {
+async fn start_docker_infrastructure(config: &Config) -> Result<()> {
     let docker_compose_path = config.workspace_root.join("docker-compose.yml");
     
     if !docker_compose_path.exists() {
         println!("{} docker-compose.yml not found, skipping", "[!]".yellow());
         return Ok(());
     }
+    if let Some(reason) = docker::unavailable().await {
+        docker::print_skipped("infrastructure containers", &reason);
+        return Ok(());
+    }
+    let _span = tracing::info_span!("docker_up").entered();
     
     // Check if containers are already running
     let output = Command::new("docker")
@@ -157,22 +254,26 @@ fn start_docker_infrastructure(config: &Config) -> Result<()> {
         .context("Failed to check Docker containers")?;
     
     if !output.stdout.is_empty() {
-        println!("{} Docker containers already running", "[OK]".green());
+        say!("{} Docker containers already running", "[OK]".green());
         return Ok(());
     }
     
     // Start containers
-    println!("Starting Docker containers...");
-    let status = Command::new("docker")
-        .args(&["compose", "up", "-d"])
-        .current_dir(&config.workspace_root)
-        .status()
-        .context("Failed to start Docker containers")?;
+    say!("Starting Docker containers...");
+    let mut cmd = Command::new("docker");
+    cmd.arg("compose")
+        .args(docker::compose_profile_args(config))
+        .args(["up", "-d"])
+        .current_dir(&config.workspace_root);
+    let logged = ui::log_command(&cmd);
+    let status = cmd.status().context("Failed to start Docker containers")?;
+    logged.finished(&status);
     
     if status.success() {
-        println!("{} Docker infrastructure started", "[OK]".green());
+        say!("{} Docker infrastructure started", "[OK]".green());
         
         // Wait for services to be ready
+        let _span = tracing::info_span!("infrastructure_wait").entered();
         std::thread::sleep(std::time::Duration::from_secs(3));
     } else {
         println!("{} Failed to start Docker containers", "[X]".red());
@@ -181,7 +282,12 @@ fn start_docker_infrastructure(config: &Config) -> Result<()> {
     Ok(())
 }
 
-fn build_services(config: &Config, repos: &Vec<(String, &crate::config::RepositoryConfig)>, force: bool) -> Result<()> {
+fn build_services(
+    config: &Config,
+    repos: &Vec<(String, &RepositoryConfig)>,
+    force: bool,
+    profile: BuildProfile,
+) -> Result<()> {
     for (name, repo) in repos {
         if repo.language == "rust" {
             let service_path = config.workspace_root.join(&repo.path);
@@ -192,21 +298,38 @@ fn build_services(config: &Config, repos: &Vec<(String, &crate::config::Reposito
             }
             
             // Check if already built
-            let target_dir = service_path.join("target/release");
-            if target_dir.exists() && target_dir.read_dir()?.any(|_| true) && !force {
-                println!("{} {} already built", "[OK]".green(), name);
+            if dev::service_binary(config, repo, profile).exists() && !force {
+                say!("{} {} already built", "[OK]".green(), name);
                 continue;
             }
             
-            println!("Building {}...", name);
-            let status = Command::new("cargo")
-                .args(&["build", "--release"])
-                .current_dir(&service_path)
-                .status()
-                .with_context(|| format!("Failed to build {}", name))?;
+            say!("Building {}...", name);
+            let _span = tracing::info_span!("build", service = %name).entered();
+            let mut cmd = config.cargo();
+            cmd.args(profile.cargo_args()).current_dir(&service_path);
+            let logged = ui::log_command(&cmd);
+            let status = cmd.status().with_context(|| format!("Failed to build {}", name))?;
+            logged.finished(&status);
             
             if status.success() {
-                println!("{} Built {}", "[OK]".green(), name);
+                say!("{} Built {}", "[OK]".green(), name);
+            } else {
+                println!("{} Failed to build {}", "[X]".red(), name);
+            }
+        } else {
+            let runtime = Runtime::of(repo);
+            let service_path = config.workspace_root.join(&repo.path);
+            let Some(build) = runtime.build_command(repo, &service_path) else {
+                continue;
+            };
+            if runtime.build_output(repo, &service_path).is_some_and(|output| output.exists()) && !force {
+                say!("{} {} already built", "[OK]".green(), name);
+                continue;
+            }
+
+            say!("Building {}...", name);
+            if runtime::build(config, name, &build, &service_path)? {
+                say!("{} Built {}", "[OK]".green(), name);
             } else {
                 println!("{} Failed to build {}", "[X]".red(), name);
             }
@@ -224,7 +347,7 @@ fn validate_setup(_config: &Config) -> Result<()> {
         .context("Failed to check Redis")?;
     
     if redis_status.status.success() {
-        println!("{} Redis is running", "[OK]".green());
+        say!("{} Redis is running", "[OK]".green());
     } else {
         println!("{} Redis is not accessible", "[!]".yellow());
     }
@@ -243,7 +366,7 @@ fn validate_setup(_config: &Config) -> Result<()> {
     
     match pg_status {
         Ok(output) if output.status.success() => {
-            println!("{} PostgreSQL is running", "[OK]".green());
+            say!("{} PostgreSQL is running", "[OK]".green());
         }
         _ => {
             println!("{} PostgreSQL is not accessible", "[!]".yellow());
@@ -251,4 +374,88 @@ fn validate_setup(_config: &Config) -> Result<()> {
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+fn choose_template(config: &Config) -> Result<Option<String>> {
+    let templates = &config.manifest.templates;
+    if templates.is_empty() {
+        return Ok(None);
+    }
+
+    let names: Vec<&String> = templates.keys().collect();
+    let items: Vec<String> = templates
+        .iter()
+        .map(|(name, template)| match &template.description {
+            Some(description) => format!("{} - {}", name, description),
+            None => name.clone(),
+        })
+        .collect();
+    let default = names.iter().position(|name| *name == "full-platform").unwrap_or(0);
+
+    let selection = Select::new()
+        .with_prompt("Workspace template")
+        .items(&items)
+        .default(default)
+        .interact()?;
+    Ok(Some(names[selection].clone()))
+}
+
+/// Multi-select of `candidates` grouped by platform, with those in
+/// `defaults` checked
+fn choose_repositories<'a>(
+    mut candidates: Vec<(String, &'a RepositoryConfig)>,
+    defaults: &[(String, &RepositoryConfig)],
+) -> Result<Vec<(String, &'a RepositoryConfig)>> {
+    // Repositories without a platform go last
+    candidates.sort_by(|a, b| {
+        (a.1.platform.is_none(), &a.1.platform, &a.0).cmp(&(b.1.platform.is_none(), &b.1.platform, &b.0))
+    });
+    let platform = |repo: &RepositoryConfig| repo.platform.clone().unwrap_or_else(|| "other".to_string());
+    let width = candidates.iter().map(|(_, repo)| platform(repo).len()).max().unwrap_or(0);
+    let items: Vec<String> =
+        candidates.iter().map(|(name, repo)| format!("{:<width$}  {}", platform(repo), name, width = width)).collect();
+    let checked: Vec<bool> = candidates.iter().map(|(name, _)| defaults.iter().any(|(default, _)| default == name)).collect();
+
+    let selection = MultiSelect::new()
+        .with_prompt("Repositories to clone (space to toggle, enter to confirm)")
+        .items(&items)
+        .defaults(&checked)
+        .interact()?;
+    Ok(selection.into_iter().map(|index| candidates[index].clone()).collect())
+}
+
+/// Look a template up in the manifest, then in the template registry
+async fn resolve_template(config: &Config, name: &str) -> Result<WorkspaceTemplate> {
+    if let Some(template) = config.manifest.templates.get(name) {
+        return Ok(template.clone());
+    }
+
+    let registry = std::env::var("SYLA_TEMPLATE_REGISTRY")
+        .ok()
+        .or_else(|| config.manifest.template_registry.clone());
+    let mut available: Vec<String> = config.manifest.templates.keys().cloned().collect();
+
+    if let Some(url) = registry {
+        let content = reqwest::get(&url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to fetch template registry {}", url))?
+            .text()
+            .await?;
+        let remote: RepoManifest = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse template registry {}", url))?;
+        if let Some(template) = remote.templates.get(name) {
+            return Ok(template.clone());
+        }
+        available.extend(remote.templates.into_keys());
+    }
+
+    if available.is_empty() {
+        anyhow::bail!("Template '{}' not found; the manifest defines no templates", name);
+    }
+    anyhow::bail!(
+        "Template '{}' not found. Available templates: {}",
+        name,
+        available.join(", ")
+    )
+}
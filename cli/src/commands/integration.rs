@@ -0,0 +1,358 @@
+//! Cross-service smoke tests for `syla dev validate --integration`.
+//!
+//! Each `[scenarios.<name>]` in the manifest is a list of steps, each an
+//! HTTP request, a gRPC call or an execution round-trip against one
+//! service. The harness brings up the infrastructure and services the
+//! scenarios need, together with what those depend on, runs every
+//! scenario, and stops whatever it started. Anything already running is
+//! used as it is. A failing scenario shows the end of its services' logs.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::{BTreeSet, HashMap};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::commands::contract::{check_grpc, check_http, is_ready, start_until_ready};
+use crate::commands::dev::{host_port, is_service, service_log_file};
+use crate::commands::exec;
+use crate::commands::status;
+use crate::commands::validation::{CheckCategory, ValidationReport};
+use crate::config::{Config, ExecutionProbe, InfrastructureConfig, ScenarioConfig, ScenarioStep};
+use crate::docker;
+use crate::services::ProcessManager;
+use crate::ui;
+
+/// How long infrastructure gets to pass its health check once started
+const INFRA_TIMEOUT: Duration = Duration::from_secs(60);
+const INFRA_INTERVAL: Duration = Duration::from_millis(500);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Log lines shown for each service of a failed scenario
+const LOG_TAIL: usize = 20;
+
+/// Runs every scenario, or with `only` those that call or list one of
+/// those services, adding one check per scenario to `report`
+pub(crate) async fn run(config: &Config, report: &mut ValidationReport, only: Option<&BTreeSet<String>>) -> Result<()> {
+    let scenarios: Vec<(&String, &ScenarioConfig)> = config
+        .manifest
+        .scenarios
+        .iter()
+        .filter(|(_, scenario)| {
+            only.is_none_or(|only| {
+                scenario.services.iter().chain(scenario.steps.iter().map(|step| &step.service)).any(|s| only.contains(s))
+            })
+        })
+        .collect();
+    if only.is_some() && scenarios.is_empty() {
+        println!("{} No scenarios use these services", "[!]".yellow());
+        return Ok(());
+    }
+    if scenarios.is_empty() {
+        println!(
+            "{} No scenarios found. Add a [scenarios.<name>] table to .platform/config/repos.toml",
+            "[!]".yellow()
+        );
+        return Ok(());
+    }
+
+    let mut needs = HashMap::new();
+    for (name, scenario) in &scenarios {
+        needs.insert(name.as_str(), requirements(config, scenario));
+    }
+    let mut environment = Environment::default();
+    let wanted: BTreeSet<String> = needs.values().filter_map(|needs| needs.as_ref().ok()).flatten().cloned().collect();
+    environment.bring_up(config, &wanted).await?;
+
+    for (name, scenario) in scenarios {
+        let outcome = match &needs[name.as_str()] {
+            Err(e) => Err(e.clone()),
+            Ok(needed) => match needed.iter().find_map(|n| environment.unavailable.get(n).map(|reason| (n, reason))) {
+                Some((needed, reason)) => Err(format!("{} is unavailable: {}", needed, reason)),
+                None => run_scenario(config, name, scenario).await,
+            },
+        };
+        match outcome {
+            Ok(()) => {
+                report.pass(CheckCategory::Integration, name);
+                say!("{} {}", "[OK]".green(), name);
+            }
+            Err(failure) => {
+                report.fail(CheckCategory::Integration, name, format!("Scenario {} failed: {}", name, failure));
+                report.with_file(".platform/config/repos.toml");
+                println!("{} {}: {}", "[X]".red(), name, failure);
+                if let Ok(needed) = &needs[name.as_str()] {
+                    print_logs(config, needed);
+                }
+            }
+        }
+    }
+
+    environment.tear_down(config)
+}
+
+/// Repositories and `infrastructure.<name>` entries a scenario needs up,
+/// its steps' services and everything they depend on
+fn requirements(config: &Config, scenario: &ScenarioConfig) -> Result<BTreeSet<String>, String> {
+    let mut pending: Vec<String> = scenario
+        .services
+        .iter()
+        .chain(scenario.steps.iter().map(|step| &step.service))
+        .cloned()
+        .collect();
+    let mut needed = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        if needed.contains(&name) {
+            continue;
+        }
+        if let Some(infra) = name.strip_prefix("infrastructure.") {
+            if !config.manifest.infrastructure.contains_key(infra) {
+                return Err(format!("unknown infrastructure '{}'", infra));
+            }
+        } else {
+            let Some(repo) = config.manifest.repositories.get(&name) else {
+                return Err(format!("unknown service '{}'", name));
+            };
+            pending.extend(repo.depends_on.iter().cloned());
+        }
+        needed.insert(name);
+    }
+    Ok(needed)
+}
+
+/// What the harness started, and what couldn't be brought up and why
+#[derive(Default)]
+struct Environment {
+    services: Vec<(String, ProcessManager)>,
+    infrastructure: Vec<String>,
+    unavailable: HashMap<String, String>,
+}
+
+impl Environment {
+    async fn bring_up(&mut self, config: &Config, wanted: &BTreeSet<String>) -> Result<()> {
+        let infrastructure: Vec<(&str, &InfrastructureConfig)> = wanted
+            .iter()
+            .filter_map(|name| name.strip_prefix("infrastructure."))
+            .filter_map(|name| Some((name, config.manifest.infrastructure.get(name)?)))
+            .collect();
+        self.start_infrastructure(config, &infrastructure).await;
+
+        for name in config.dependency_order()? {
+            if !wanted.contains(&name) {
+                continue;
+            }
+            if let Err(reason) = self.start_service(config, &name).await {
+                println!("  {} {}: {}", "[X]".red(), name, reason);
+                self.unavailable.insert(name, reason);
+            }
+        }
+        Ok(())
+    }
+
+    async fn start_infrastructure(&mut self, config: &Config, infrastructure: &[(&str, &InfrastructureConfig)]) {
+        let mut stopped = Vec::new();
+        for (name, infra) in infrastructure {
+            if infra_ready(infra).await {
+                say!("  {} infrastructure.{} already running", "[OK]".green(), name);
+            } else {
+                stopped.push((*name, *infra));
+            }
+        }
+        if stopped.is_empty() {
+            return;
+        }
+
+        let reason = if !config.workspace_root.join("docker-compose.yml").exists() {
+            Some("no docker-compose.yml to start it from".to_string())
+        } else {
+            docker::unavailable().await
+        };
+        let names: Vec<&str> = stopped.iter().map(|(name, _)| *name).collect();
+        let started = match reason {
+            Some(reason) => Err(reason),
+            None => {
+                let mut cmd = Command::new("docker");
+                cmd.arg("compose")
+                    .args(docker::compose_profile_args(config))
+                    .args(["up", "-d"])
+                    .args(&names)
+                    .current_dir(&config.workspace_root);
+                let logged = ui::log_command(&cmd);
+                match cmd.output() {
+                    Ok(output) => {
+                        logged.finished(&output.status);
+                        if output.status.success() {
+                            Ok(())
+                        } else {
+                            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+                        }
+                    }
+                    Err(e) => Err(format!("failed to run docker compose: {}", e)),
+                }
+            }
+        };
+        if let Err(reason) = started {
+            for name in names {
+                println!("  {} infrastructure.{}: {}", "[X]".red(), name, reason);
+                self.unavailable.insert(format!("infrastructure.{}", name), reason.clone());
+            }
+            return;
+        }
+        self.infrastructure.extend(names.iter().map(|name| name.to_string()));
+
+        let deadline = Instant::now() + INFRA_TIMEOUT;
+        for (name, infra) in stopped {
+            let ready = loop {
+                if infra_ready(infra).await {
+                    break true;
+                }
+                if Instant::now() >= deadline {
+                    break false;
+                }
+                tokio::time::sleep(INFRA_INTERVAL).await;
+            };
+            if ready {
+                say!("  {} infrastructure.{} started", "[OK]".green(), name);
+            } else {
+                let reason = format!("not healthy after {}s", INFRA_TIMEOUT.as_secs());
+                println!("  {} infrastructure.{}: {}", "[X]".red(), name, reason);
+                self.unavailable.insert(format!("infrastructure.{}", name), reason);
+            }
+        }
+    }
+
+    /// Starts a service unless it's already up; its dependencies were
+    /// handled before it
+    async fn start_service(&mut self, config: &Config, name: &str) -> Result<(), String> {
+        let repo = &config.manifest.repositories[name];
+        let port = repo
+            .ports
+            .first()
+            .and_then(|p| host_port(p))
+            .ok_or("no ports in the manifest")?
+            .to_string();
+        if is_ready(repo, &port).await {
+            say!("  {} {} already running", "[OK]".green(), name);
+            return Ok(());
+        }
+        if !is_service(config, name, repo) {
+            return Err("not running, and not a service syla can start".to_string());
+        }
+        if let Some(dependency) = repo.depends_on.iter().find(|d| self.unavailable.contains_key(*d)) {
+            return Err(format!("depends on {}, which is unavailable", dependency));
+        }
+        let manager = start_until_ready(config, name, repo, &port)
+            .await
+            .map_err(|e| format!("could not start: {:#}", e))?;
+        say!("  {} {} started", "[OK]".green(), name);
+        self.services.push((name.to_string(), manager));
+        Ok(())
+    }
+
+    /// Stops what `bring_up` started, dependents first
+    fn tear_down(self, config: &Config) -> Result<()> {
+        for (name, manager) in self.services.iter().rev() {
+            manager.stop_service(name, false)?;
+        }
+        if !self.infrastructure.is_empty() {
+            let mut cmd = Command::new("docker");
+            cmd.args(["compose", "stop"]).args(&self.infrastructure).current_dir(&config.workspace_root);
+            let logged = ui::log_command(&cmd);
+            if let Ok(output) = cmd.output() {
+                logged.finished(&output.status);
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn infra_ready(infra: &InfrastructureConfig) -> bool {
+    match &infra.health_check {
+        Some(health_check) => status::check_health(health_check).await.unwrap_or(false),
+        None => match infra.ports.first().and_then(|p| host_port(p)) {
+            Some(port) => tokio::net::TcpStream::connect(format!("127.0.0.1:{}", port)).await.is_ok(),
+            None => false,
+        },
+    }
+}
+
+/// Runs the steps in order, stopping at the first that fails
+async fn run_scenario(config: &Config, name: &str, scenario: &ScenarioConfig) -> Result<(), String> {
+    println!("\n{} {}", "[>]".cyan(), name.bold());
+    if let Some(description) = &scenario.description {
+        say!("  {}", description.dimmed());
+    }
+    let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build().map_err(|e| e.to_string())?;
+    for step in &scenario.steps {
+        let problems = run_step(config, &client, step).await;
+        if problems.is_empty() {
+            say!("  {} {}", "[OK]".green(), step.describe());
+            continue;
+        }
+        println!("  {} {}", "[X]".red(), step.describe());
+        for problem in &problems {
+            println!("      {}", problem);
+        }
+        return Err(format!("{}: {}", step.describe(), problems.join("; ")));
+    }
+    Ok(())
+}
+
+async fn run_step(config: &Config, client: &reqwest::Client, step: &ScenarioStep) -> Vec<String> {
+    let Some(repo) = config.manifest.repositories.get(&step.service) else {
+        return vec![format!("{} is not a service", step.service)];
+    };
+    let Some(port) = repo.ports.first().and_then(|p| host_port(p)) else {
+        return vec![format!("{} has no ports in the manifest", step.service)];
+    };
+    match (&step.path, &step.rpc, &step.execute) {
+        (Some(path), None, None) => {
+            let method = step.method.as_deref().unwrap_or("GET");
+            check_http(client, port, method, path, step.body.as_ref(), &step.response).await
+        }
+        (None, Some(rpc), None) => check_grpc(port, rpc, step.body.as_ref(), &step.response).await,
+        (None, None, Some(probe)) => match execute(client, port, probe).await {
+            Ok(problems) => problems,
+            Err(e) => vec![format!("{:#}", e)],
+        },
+        _ => vec!["a step needs exactly one of path, rpc or execute".to_string()],
+    }
+}
+
+/// Submits the probe's code and waits for it to finish
+async fn execute(client: &reqwest::Client, port: &str, probe: &ExecutionProbe) -> Result<Vec<String>> {
+    let url = format!("http://localhost:{}", port);
+    let request = serde_json::json!({ "code": probe.code, "language": probe.language });
+    let response = client.post(format!("{}/executions", url)).json(&request).send().await?;
+    let job = exec::wait(client, &url, exec::checked(response).await?).await?;
+
+    let mut problems = Vec::new();
+    let status = job["status"].as_str().unwrap_or("unknown");
+    if status != "completed" {
+        problems.push(format!("execution {}, expected completed", status));
+    }
+    let stdout = job["result"]["stdout"].as_str().unwrap_or_default();
+    if let Some(expected) = &probe.expected_output {
+        if stdout.trim_end() != expected.trim_end() {
+            problems.push(format!("printed {:?}, expected {:?}", stdout.trim_end(), expected.trim_end()));
+        }
+    }
+    Ok(problems)
+}
+
+/// The last lines of each service's log, for working out why a scenario
+/// failed
+fn print_logs(config: &Config, services: &BTreeSet<String>) {
+    for name in services {
+        let Ok(content) = std::fs::read_to_string(service_log_file(config, name)) else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        if lines.is_empty() {
+            continue;
+        }
+        println!("  {} {}", "Logs of".dimmed(), name.bold());
+        for line in &lines[lines.len().saturating_sub(LOG_TAIL)..] {
+            println!("    {}", line.dimmed());
+        }
+    }
+}
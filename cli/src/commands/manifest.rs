@@ -0,0 +1,295 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::PathBuf;
+
+use crate::commands::dev::host_port;
+use crate::commands::{ExitStatus, OutputFormat};
+use crate::config::{Config, RepoManifest};
+use crate::names::{self, Match};
+use crate::ManifestCommands;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Stable identifier of the check, e.g. `dependency-cycle`
+    pub rule: &'static str,
+    /// Manifest entry the finding is about, e.g. `repositories."syla.core.api-gateway"`
+    pub entry: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct LintReport {
+    errors: usize,
+    warnings: usize,
+    findings: Vec<Finding>,
+}
+
+pub async fn run(command: ManifestCommands, output: Option<OutputFormat>, workspace_root: Option<PathBuf>) -> Result<()> {
+    match command {
+        ManifestCommands::Lint { strict } => {
+            let findings = match Config::load(workspace_root) {
+                Ok(config) => lint(&config.manifest),
+                Err(e) => vec![Finding {
+                    severity: Severity::Error,
+                    rule: "parse",
+                    entry: "repos.toml".to_string(),
+                    message: format!("{:#}", e),
+                }],
+            };
+            let report = LintReport {
+                errors: findings.iter().filter(|f| f.severity == Severity::Error).count(),
+                warnings: findings.iter().filter(|f| f.severity == Severity::Warning).count(),
+                findings,
+            };
+
+            match output {
+                Some(format) => format.print(&report)?,
+                None => print_report(&report),
+            }
+
+            let failing = report.errors + if strict { report.warnings } else { 0 };
+            if failing > 0 {
+                return Err(ExitStatus {
+                    code: 1,
+                    message: format!("{} manifest problem(s)", failing),
+                }
+                .into());
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Checks the manifest for mistakes parsing lets through, errors first
+pub fn lint(manifest: &RepoManifest) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    check_dependencies(manifest, &mut findings);
+    check_cycles(manifest, &mut findings);
+    check_paths(manifest, &mut findings);
+    check_ports(manifest, &mut findings);
+    check_health_checks(manifest, &mut findings);
+    check_platforms(manifest, &mut findings);
+    findings.sort_by(|a, b| a.severity.cmp(&b.severity).then_with(|| a.entry.cmp(&b.entry)).then_with(|| a.rule.cmp(b.rule)));
+    findings
+}
+
+fn repository(name: &str) -> String {
+    format!("repositories.\"{}\"", name)
+}
+
+fn infrastructure(name: &str) -> String {
+    format!("infrastructure.{}", name)
+}
+
+fn sorted<V>(entries: &std::collections::HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<_> = entries.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn check_dependencies(manifest: &RepoManifest, findings: &mut Vec<Finding>) {
+    let targets: Vec<String> = manifest
+        .repositories
+        .keys()
+        .cloned()
+        .chain(manifest.infrastructure.keys().map(|name| infrastructure(name)))
+        .collect();
+    for (name, repo) in sorted(&manifest.repositories) {
+        for dependency in &repo.depends_on {
+            if targets.contains(dependency) {
+                continue;
+            }
+            let hint = match names::find(dependency, targets.iter().map(String::as_str)) {
+                Match::One(target) => format!("; did you mean {}?", target),
+                Match::NotFound(candidates) if candidates.len() == 1 => format!("; did you mean {}?", candidates[0]),
+                Match::Ambiguous(candidates) | Match::NotFound(candidates) if !candidates.is_empty() => {
+                    format!("; did you mean one of: {}?", candidates.join(", "))
+                }
+                _ => String::new(),
+            };
+            findings.push(Finding {
+                severity: Severity::Error,
+                rule: "unknown-dependency",
+                entry: repository(name),
+                message: format!("depends on '{}', which is not a repository or infrastructure{}", dependency, hint),
+            });
+        }
+    }
+}
+
+/// Reports every dependency cycle once, from its alphabetically first
+/// member
+fn check_cycles(manifest: &RepoManifest, findings: &mut Vec<Finding>) {
+    fn visit(
+        manifest: &RepoManifest,
+        name: &str,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        cycles: &mut BTreeSet<Vec<String>>,
+    ) {
+        if let Some(start) = path.iter().position(|n| n == name) {
+            let mut cycle = path[start..].to_vec();
+            let first = cycle.iter().enumerate().min_by_key(|(_, n)| *n).map(|(i, _)| i).unwrap_or(0);
+            cycle.rotate_left(first);
+            cycles.insert(cycle);
+            return;
+        }
+        if done.contains(name) {
+            return;
+        }
+        let Some(repo) = manifest.repositories.get(name) else { return };
+        path.push(name.to_string());
+        for dependency in &repo.depends_on {
+            visit(manifest, dependency, path, done, cycles);
+        }
+        path.pop();
+        done.insert(name.to_string());
+    }
+
+    let mut cycles = BTreeSet::new();
+    let mut done = HashSet::new();
+    for (name, _) in sorted(&manifest.repositories) {
+        visit(manifest, name, &mut Vec::new(), &mut done, &mut cycles);
+    }
+    for cycle in cycles {
+        let mut chain = cycle.clone();
+        chain.push(cycle[0].clone());
+        findings.push(Finding {
+            severity: Severity::Error,
+            rule: "dependency-cycle",
+            entry: repository(&cycle[0]),
+            message: format!("dependency cycle: {}", chain.join(" -> ")),
+        });
+    }
+}
+
+fn check_paths(manifest: &RepoManifest, findings: &mut Vec<Finding>) {
+    let mut paths: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+    for (name, repo) in sorted(&manifest.repositories) {
+        let path = repo.path.trim_start_matches("./").trim_end_matches('/').to_string();
+        paths.entry(path).or_default().push(name);
+    }
+    for (path, owners) in paths {
+        if let [first, others @ ..] = owners.as_slice() {
+            for other in others {
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    rule: "duplicate-path",
+                    entry: repository(other),
+                    message: format!("path '{}' is also used by {}", path, first),
+                });
+            }
+        }
+    }
+}
+fn check_ports(manifest: &RepoManifest, findings: &mut Vec<Finding>) {
+    let mut owners: BTreeMap<u16, Vec<String>> = BTreeMap::new();
+    let entries = sorted(&manifest.repositories)
+        .into_iter()
+        .map(|(name, repo)| (repository(name), &repo.ports))
+        .chain(sorted(&manifest.infrastructure).into_iter().map(|(name, infra)| (infrastructure(name), &infra.ports)));
+    for (entry, ports) in entries {
+        for port in ports {
+            let Some(port) = host_port(port).and_then(|p| p.parse().ok()) else { continue };
+            owners.entry(port).or_default().push(entry.clone());
+        }
+    }
+    for (port, entries) in owners {
+        if let [first, others @ ..] = entries.as_slice() {
+            for other in others {
+                let message = if other == first {
+                    format!("host port {} is listed twice", port)
+                } else {
+                    format!("host port {} is also used by {}", port, first)
+                };
+                findings.push(Finding { severity: Severity::Error, rule: "duplicate-port", entry: other.clone(), message });
+            }
+        }
+    }
+}
+
+/// HTTP health checks on the local machine should hit one of the entry's
+/// own host ports
+fn check_health_checks(manifest: &RepoManifest, findings: &mut Vec<Finding>) {
+    let entries = sorted(&manifest.repositories)
+        .into_iter()
+        .map(|(name, repo)| (repository(name), &repo.health_check, &repo.ports))
+        .chain(
+            sorted(&manifest.infrastructure)
+                .into_iter()
+                .map(|(name, infra)| (infrastructure(name), &infra.health_check, &infra.ports)),
+        );
+    for (entry, health_check, ports) in entries {
+        let Some(url) = health_check.as_deref().and_then(|check| reqwest::Url::parse(check).ok()) else { continue };
+        if !matches!(url.scheme(), "http" | "https") || !matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]")) {
+            continue;
+        }
+        // Unassigned `auto` ports can't be compared yet
+        if ports.iter().any(|port| port == "auto") {
+            continue;
+        }
+        let Some(port) = url.port_or_known_default() else { continue };
+        let declared: Vec<u16> = ports.iter().filter_map(|p| host_port(p)?.parse().ok()).collect();
+        if declared.contains(&port) {
+            continue;
+        }
+        let message = if declared.is_empty() {
+            format!("health check {} uses port {}, but no ports are declared", url, port)
+        } else {
+            let declared: Vec<String> = declared.iter().map(u16::to_string).collect();
+            format!("health check {} uses port {}, which is not among its ports ({})", url, port, declared.join(", "))
+        };
+        findings.push(Finding { severity: Severity::Error, rule: "health-check-port", entry, message });
+    }
+}
+
+fn check_platforms(manifest: &RepoManifest, findings: &mut Vec<Finding>) {
+    let platforms: BTreeSet<&str> = manifest.repositories.values().filter_map(|repo| repo.platform.as_deref()).collect();
+    for (name, repo) in sorted(&manifest.repositories) {
+        if repo.platform.is_none() {
+            findings.push(Finding {
+                severity: Severity::Warning,
+                rule: "missing-platform",
+                entry: repository(name),
+                message: "has no platform, so platform commands and templates can't select it".to_string(),
+            });
+        }
+    }
+    for (name, template) in &manifest.templates {
+        for platform in &template.platforms {
+            if !platforms.contains(platform.as_str()) {
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    rule: "unknown-platform",
+                    entry: format!("templates.{}", name),
+                    message: format!("includes platform '{}', which no repository belongs to", platform),
+                });
+            }
+        }
+    }
+}
+
+fn print_report(report: &LintReport) {
+    if report.findings.is_empty() {
+        println!("{} Manifest looks good", "[OK]".green());
+        return;
+    }
+    for finding in &report.findings {
+        let marker = match finding.severity {
+            Severity::Error => "[X]".red(),
+            Severity::Warning => "[!]".yellow(),
+        };
+        println!("{} {} {} {}", marker, finding.entry.bold(), finding.message, format!("({})", finding.rule).dimmed());
+    }
+    println!("\n{} error(s), {} warning(s)", report.errors, report.warnings);
+}
@@ -1,5 +1,57 @@
+pub mod api;
+pub mod audit;
+pub mod backup;
+pub mod bench;
+pub mod completions;
+pub mod config;
+pub mod contract;
+pub mod daemon;
+pub mod dashboard;
+pub mod db;
 pub mod dev;
+pub mod discover;
 pub mod doctor;
+pub mod exec;
+pub mod executions;
+pub mod history;
 pub mod init;
+pub mod integration;
+pub mod manifest;
 pub mod platform;
-pub mod status;
\ No newline at end of file
+pub mod plugin;
+pub mod proto;
+pub mod release;
+pub mod run;
+pub mod secrets;
+pub mod serve;
+pub mod status;
+pub mod telemetry;
+pub mod upgrade;
+pub mod validation;
+pub mod why;
+/// Returned by commands whose exit status carries meaning beyond
+/// success or failure, e.g. `dev validate --ci`
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct ExitStatus {
+    pub code: i32,
+    pub message: String,
+}
+
+/// Machine-readable formats for `--output`; without the flag commands
+/// print for humans
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    pub fn print<T: serde::Serialize>(self, value: &T) -> anyhow::Result<()> {
+        match self {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+            OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+        }
+        Ok(())
+    }
+}
@@ -1,28 +1,286 @@
 use anyhow::Result;
 use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeSet;
 use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::commands::dev::{self, is_service};
+use crate::commands::integration;
+use crate::commands::status::{self, RepositoryState, RepositoryStatus, ServiceState, ServiceStatus};
+use crate::commands::validation::ValidationReport;
+use crate::commands::{ExitStatus, OutputFormat};
+use crate::config::{Config, RepositoryConfig};
+use crate::names;
+use crate::ports;
+use crate::services::registry;
+use crate::services::state::StartedServices;
+use crate::watch::TestRunner;
 use crate::PlatformCommands;
 
-pub async fn run(command: PlatformCommands, _workspace_root: Option<PathBuf>) -> Result<()> {
+/// How often `platform start` checks whether `platform stop` ran elsewhere
+const STOPPED_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A platform and the repositories that belong to it, as `--output` prints
+#[derive(Debug, Serialize)]
+pub struct PlatformEntry {
+    pub name: String,
+    pub repositories: Vec<String>,
+}
+
+/// A platform's repositories and services, with the infrastructure they
+/// depend on, as `--output` prints
+#[derive(Debug, Serialize)]
+pub struct PlatformStatus {
+    pub name: String,
+    pub repositories: Vec<RepositoryStatus>,
+    pub services: Vec<ServiceStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docker_unavailable: Option<String>,
+}
+
+pub async fn run(command: PlatformCommands, output: Option<OutputFormat>, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
     match command {
         PlatformCommands::List => {
-            println!("{}", "Platform command not yet implemented".yellow());
+            list(&config, output)?;
         }
         PlatformCommands::Status { platform } => {
-            println!("{} Platform status for '{}' not yet implemented", "->".dimmed(), platform);
+            let platform = names::resolve("platform", &platform, config.platforms())?;
+            platform_status(&config, &platform, output).await?;
         }
         PlatformCommands::Start { platform, with_deps } => {
-            println!("{} Starting platform '{}' (with_deps: {}) not yet implemented", 
-                "->".dimmed(), platform, with_deps);
+            let platform = names::resolve("platform", &platform, config.platforms())?;
+            start(&config, &platform, with_deps).await?;
         }
         PlatformCommands::Stop { platform } => {
-            println!("{} Stopping platform '{}' not yet implemented", "->".dimmed(), platform);
+            let platform = names::resolve("platform", &platform, config.platforms())?;
+            stop(&config, &platform).await?;
         }
         PlatformCommands::Test { platform, integration } => {
-            println!("{} Testing platform '{}' (integration: {}) not yet implemented", 
-                "->".dimmed(), platform, integration);
+            let platform = names::resolve("platform", &platform, config.platforms())?;
+            test(&config, &platform, integration).await?;
+        }
+    }
+    Ok(())
+}
+
+fn list(config: &Config, output: Option<OutputFormat>) -> Result<()> {
+    let platforms: Vec<PlatformEntry> = config
+        .platforms()
+        .into_iter()
+        .map(|platform| {
+            let repositories: Vec<String> = members(config, platform).into_iter().map(|(name, _)| name).collect();
+            PlatformEntry { name: platform.to_string(), repositories }
+        })
+        .collect();
+    if let Some(format) = output {
+        return format.print(&platforms);
+    }
+
+    println!("{}", "Platforms".bold());
+    println!();
+    if platforms.is_empty() {
+        println!("  {}", "No repositories declare a platform".dimmed());
+    }
+    for platform in platforms {
+        println!("  {} ({} repositories)", platform.name.bold(), platform.repositories.len());
+        for repo in platform.repositories {
+            println!("    {} {}", "*".cyan(), repo);
+        }
+    }
+    Ok(())
+}
+
+/// Repositories paired with their names
+type Members<'a> = Vec<(String, &'a RepositoryConfig)>;
+
+/// The platform's repositories, sorted by name
+fn members<'a>(config: &'a Config, platform: &str) -> Members<'a> {
+    let mut repos = config.get_platform_repositories(platform).unwrap_or_default();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    repos
+}
+
+/// The platform's services and, with `with_deps`, the services they depend
+/// on from other platforms, each after its dependencies. Dependencies left
+/// out are returned separately.
+fn services<'a>(
+    config: &'a Config,
+    platform: &str,
+    with_deps: bool,
+) -> Result<(Members<'a>, Vec<String>)> {
+    let mut wanted: BTreeSet<String> = members(config, platform).into_iter().map(|(name, _)| name).collect();
+    let mut outside = BTreeSet::new();
+    let mut pending: Vec<String> = wanted.iter().cloned().collect();
+    while let Some(name) = pending.pop() {
+        // `infrastructure.<name>` dependencies come up with docker compose
+        let Some(repo) = config.manifest.repositories.get(&name) else {
+            continue;
+        };
+        for dependency in &repo.depends_on {
+            if !config.manifest.repositories.contains_key(dependency) || wanted.contains(dependency) {
+                continue;
+            }
+            if with_deps {
+                wanted.insert(dependency.clone());
+                pending.push(dependency.clone());
+            } else {
+                outside.insert(dependency.clone());
+            }
         }
     }
+
+    let services = config
+        .dependency_order()?
+        .into_iter()
+        .filter(|name| wanted.contains(name))
+        .filter_map(|name| {
+            let repo = &config.manifest.repositories[&name];
+            is_service(config, &name, repo).then_some((name, repo))
+        })
+        .collect();
+    Ok((services, outside.into_iter().collect()))
+}
+
+async fn platform_status(config: &Config, platform: &str, output: Option<OutputFormat>) -> Result<()> {
+    let repos = members(config, platform);
+    let names: BTreeSet<&str> = repos.iter().map(|(name, _)| name.as_str()).collect();
+    let infrastructure: BTreeSet<&str> = repos.iter().flat_map(|(_, repo)| repo.depends_on.iter().map(String::as_str)).collect();
+
+    let report = status::collect(config, true, false, &[]).await?;
+    let status = PlatformStatus {
+        name: platform.to_string(),
+        repositories: report.repositories.into_iter().filter(|repo| names.contains(repo.name.as_str())).collect(),
+        services: report
+            .services
+            .into_iter()
+            .filter(|service| names.contains(service.name.as_str()) || infrastructure.contains(service.name.as_str()))
+            .collect(),
+        docker_unavailable: report.docker_unavailable,
+    };
+    if let Some(format) = output {
+        return format.print(&status);
+    }
+
+    println!("{} {}", "Platform".bold(), status.name.bold().cyan());
+    println!();
+    println!("{}", "Repositories:".bold());
+    for repo in &status.repositories {
+        let state = match repo.state {
+            RepositoryState::Clean => "clean".green(),
+            RepositoryState::Changed => format!("{} changes", repo.changed_files).yellow(),
+            RepositoryState::NotGit => "not a git repo".red(),
+            RepositoryState::NotCloned => "not cloned".red(),
+            RepositoryState::Failed => "failed".red(),
+        };
+        let branch = repo.branch.as_deref().unwrap_or("-");
+        println!("  {} {} ({})", repo.name.bold(), branch.dimmed(), state);
+    }
+
+    println!("\n{}", "Services:".bold());
+    status::print_services(&status.services, status.docker_unavailable.as_deref());
+    let own: Vec<&ServiceStatus> = status.services.iter().filter(|service| service.kind == "service").collect();
+    let running = own.iter().filter(|service| service.state == ServiceState::Running).count();
+    let healthy = own.iter().filter(|service| service.healthy == Some(true)).count();
+    println!("{}/{} services running, {} healthy", running, own.len(), healthy);
+    Ok(())
+}
+
+/// Starts the platform like `dev up`, then keeps it running until
+/// interrupted or stopped with `platform stop`
+async fn start(config: &Config, platform: &str, with_deps: bool) -> Result<()> {
+    let config = &ports::allocate(config)?;
+    let (services, outside) = services(config, platform, with_deps)?;
+    if services.is_empty() {
+        anyhow::bail!("Platform '{}' has no services to start", platform);
+    }
+    if !outside.is_empty() {
+        println!(
+            "{} Not starting dependencies from other platforms: {}; add --with-deps to start them too",
+            "[!]".yellow(),
+            outside.join(", ")
+        );
+    }
+
+    let names: Vec<String> = services.iter().map(|(name, _)| name.clone()).collect();
+    let own: Vec<String> = members(config, platform).into_iter().map(|(name, _)| name).collect();
+    let manager = dev::up_services(config, services, true, config.build_profile(None), None).await?;
+    say!("Press Ctrl+C to stop {}", platform);
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(STOPPED_POLL_INTERVAL) => {
+                let started = StartedServices::load(&config.workspace_root)?;
+                // `platform stop` only stops the platform's own services;
+                // the dependencies started for them go down with this
+                if !own.iter().any(|name| started.services.get(name).is_some_and(|service| service.is_running())) {
+                    break;
+                }
+            }
+        }
+    }
+
+    say!("\nStopping {}...", platform);
+    manager.stop_all()?;
+    dev::stop_started(config, &names.iter().map(String::as_str).collect::<Vec<_>>())?;
+    registry::update(config).await;
+    Ok(())
+}
+
+async fn stop(config: &Config, platform: &str) -> Result<()> {
+    let started = StartedServices::load(&config.workspace_root)?;
+    let repos = members(config, platform);
+    let running: Vec<&str> = repos
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| started.services.get(*name).is_some_and(|service| service.is_running()))
+        .collect();
+    // An empty list would stop every service
+    if running.is_empty() {
+        say!("{} Nothing from {} is running", "[!]".yellow(), platform);
+        return Ok(());
+    }
+    dev::stop_started(config, &running)?;
+    registry::update(config).await;
+    say!("{} Stopped {}", "[OK]".green(), platform);
+    Ok(())
+}
+
+/// Runs each repository's tests and, with `integration`, the manifest's
+/// scenarios that use the platform's services
+async fn test(config: &Config, platform: &str, integration: bool) -> Result<()> {
+    let repos = members(config, platform);
+    let mut failed = Vec::new();
+    let mut runner = TestRunner::new();
+    for (name, repo) in &repos {
+        if !config.workspace_root.join(&repo.path).exists() {
+            println!("{} {} is not cloned, skipping", "[!]".yellow(), name);
+            continue;
+        }
+        match runner.run(config, name, repo) {
+            Ok(Some(true)) => {}
+            Ok(Some(false)) => failed.push(name.clone()),
+            Ok(None) => say!("  {} {}", name, "has no tests".dimmed()),
+            Err(e) => {
+                println!("{} {}: {:#}", "[X]".red(), name, e);
+                failed.push(name.clone());
+            }
+        }
+    }
+
+    if integration {
+        say!("\n{}", "Integration scenarios".bold());
+        let config = ports::allocate(config)?;
+        let services: BTreeSet<String> = repos.iter().map(|(name, _)| name.clone()).collect();
+        let mut report = ValidationReport::default();
+        integration::run(&config, &mut report, Some(&services)).await?;
+        failed.extend(report.failures().map(|check| format!("scenario {}", check.name)));
+    }
+
+    if !failed.is_empty() {
+        return Err(ExitStatus { code: 1, message: format!("{} tests failed: {}", platform, failed.join(", ")) }.into());
+    }
+    say!("\n{} {} tests passed", "[OK]".green(), platform);
     Ok(())
-}
\ No newline at end of file
+}
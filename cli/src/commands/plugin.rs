@@ -0,0 +1,318 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::{self, Config};
+use crate::PluginCommands;
+
+/// Executables named `syla-<name>` are run as `syla <name>`
+const PLUGIN_PREFIX: &str = "syla-";
+
+/// Bumped when the environment or context handed to plugins changes shape
+const PLUGIN_API_VERSION: u32 = 1;
+
+/// Flag a plugin is called with by `syla plugin list` to describe itself
+const INFO_FLAG: &str = "--syla-plugin-info";
+
+/// Workspace context passed to plugins as JSON in `SYLA_CONTEXT`
+#[derive(Debug, Serialize)]
+struct PluginContext {
+    api_version: u32,
+    cli_version: &'static str,
+    workspace_root: Option<PathBuf>,
+    manifest_path: Option<PathBuf>,
+    repositories: Vec<RepositoryContext>,
+}
+
+#[derive(Debug, Serialize)]
+struct RepositoryContext {
+    name: String,
+    path: PathBuf,
+    language: String,
+    platform: Option<String>,
+}
+
+/// What a plugin reports about itself when called with `--syla-plugin-info`
+#[derive(Debug, Default, Deserialize)]
+struct PluginInfo {
+    version: Option<String>,
+    description: Option<String>,
+    /// Plugin API version the plugin was written against
+    api_version: Option<u32>,
+}
+
+pub async fn run(command: PluginCommands, _workspace_root: Option<PathBuf>) -> Result<()> {
+    match command {
+        PluginCommands::List => list().await,
+        PluginCommands::Install { source, force } => install(&source, force).await,
+    }
+}
+
+/// Run `syla-<name>` with the remaining arguments, exiting with its status
+pub async fn dispatch(args: Vec<OsString>, workspace_root: Option<PathBuf>) -> Result<()> {
+    let mut args = args.into_iter();
+    let name = args
+        .next()
+        .context("Missing plugin name")?
+        .to_string_lossy()
+        .into_owned();
+
+    let Some(executable) = find_plugin(&name) else {
+        anyhow::bail!(
+            "Unknown command '{}'. No {}{} plugin found in {} or on PATH",
+            name,
+            PLUGIN_PREFIX,
+            name,
+            plugin_bin_dir().display()
+        );
+    };
+
+    // Plugins may be run outside a workspace; they just get less context
+    let config = Config::load(workspace_root).ok();
+    let context = plugin_context(config.as_ref());
+
+    let mut cmd = tokio::process::Command::new(&executable);
+    cmd.args(args)
+        .env("SYLA_PLUGIN_API", PLUGIN_API_VERSION.to_string())
+        .env("SYLA_VERSION", env!("CARGO_PKG_VERSION"))
+        .env("SYLA_CONTEXT", serde_json::to_string(&context)?);
+    if let Ok(exe) = std::env::current_exe() {
+        cmd.env("SYLA_BIN", exe);
+    }
+    if let Some(config) = &config {
+        cmd.env("SYLA_WORKSPACE", &config.workspace_root)
+            .env("SYLA_MANIFEST", manifest_path(config));
+    }
+
+    let status = cmd
+        .status()
+        .await
+        .with_context(|| format!("Failed to run plugin {}", executable.display()))?;
+
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+async fn list() -> Result<()> {
+    let plugins = discover_plugins();
+
+    println!("{}", "Plugins".bold());
+    println!();
+
+    if plugins.is_empty() {
+        println!("  No plugins found");
+        println!();
+        println!(
+            "Install one with {} or put a {} executable on PATH",
+            "syla plugin install <path|crate|git-url>".cyan(),
+            format!("{}<name>", PLUGIN_PREFIX).cyan()
+        );
+        return Ok(());
+    }
+
+    for (name, path) in plugins {
+        let info = plugin_info(&path).await;
+        let version = info.version.as_deref().unwrap_or("-");
+        println!("  {} {} {}", name.bold(), version.dimmed(), path.display().to_string().dimmed());
+        if let Some(description) = &info.description {
+            println!("    {}", description);
+        }
+        if let Some(api_version) = info.api_version.filter(|v| *v != PLUGIN_API_VERSION) {
+            println!(
+                "    {} Written for plugin API v{}, this CLI provides v{}",
+                "[!]".yellow(),
+                api_version,
+                PLUGIN_API_VERSION
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Install from a local executable, a crates.io crate or a git URL into
+/// the user plugin directory
+async fn install(source: &str, force: bool) -> Result<()> {
+    let bin_dir = plugin_bin_dir();
+    std::fs::create_dir_all(&bin_dir)
+        .with_context(|| format!("Failed to create {}", bin_dir.display()))?;
+
+    let local = Path::new(source);
+    if local.is_file() {
+        let file_name = local
+            .file_name()
+            .and_then(|n| n.to_str())
+            .context("Invalid plugin path")?;
+        if !file_name.starts_with(PLUGIN_PREFIX) {
+            anyhow::bail!("Plugin executables must be named {}<name>, got '{}'", PLUGIN_PREFIX, file_name);
+        }
+
+        let target = bin_dir.join(file_name);
+        if target.exists() && !force {
+            anyhow::bail!("{} is already installed, use --force to replace it", file_name);
+        }
+        std::fs::copy(local, &target)
+            .with_context(|| format!("Failed to copy {} to {}", local.display(), target.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755))?;
+        }
+
+        say!("{} Installed {} to {}", "[OK]".green(), file_name.bold(), target.display());
+        return Ok(());
+    }
+
+    // Anything else is built with cargo, which puts binaries in <root>/bin
+    let mut cmd = tokio::process::Command::new("cargo");
+    cmd.arg("install").arg("--root").arg(plugin_home());
+    if source.contains("://") || source.starts_with("git@") {
+        cmd.arg("--git").arg(source);
+    } else {
+        cmd.arg(source);
+    }
+    if force {
+        cmd.arg("--force");
+    }
+
+    println!("{} Installing {} with cargo...", "[?]".cyan(), source.bold());
+    let status = cmd.status().await.context("Failed to run cargo install")?;
+    if !status.success() {
+        anyhow::bail!("cargo install {} failed", source);
+    }
+
+    say!("{} Installed {} to {}", "[OK]".green(), source.bold(), bin_dir.display());
+    if !discover_plugins().values().any(|path| path.starts_with(&bin_dir)) {
+        println!(
+            "{} No {}<name> executables were installed; syla will not pick this up as a plugin",
+            "[!]".yellow(),
+            PLUGIN_PREFIX
+        );
+    }
+    Ok(())
+}
+
+/// Directory plugins are installed into: `$SYLA_PLUGIN_HOME`, or `~/.syla/plugins`
+fn plugin_home() -> PathBuf {
+    std::env::var_os("SYLA_PLUGIN_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config::user_dir().join("plugins"))
+}
+
+fn plugin_bin_dir() -> PathBuf {
+    plugin_home().join("bin")
+}
+
+/// Directories searched for plugins, installed plugins first
+fn search_path() -> Vec<PathBuf> {
+    let mut dirs = vec![plugin_bin_dir()];
+    if let Some(path) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+    dirs
+}
+
+fn find_plugin(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}{}", PLUGIN_PREFIX, name, std::env::consts::EXE_SUFFIX);
+    search_path()
+        .into_iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| is_executable(path))
+}
+
+/// Every plugin name mapped to the executable that would run for it
+fn discover_plugins() -> BTreeMap<String, PathBuf> {
+    let mut plugins = BTreeMap::new();
+    for dir in search_path() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(PLUGIN_PREFIX))
+                .map(|n| n.trim_end_matches(std::env::consts::EXE_SUFFIX))
+            else {
+                continue;
+            };
+            // Earlier directories win, matching how dispatch resolves names
+            if !name.is_empty() && !plugins.contains_key(name) && is_executable(&path) {
+                plugins.insert(name.to_string(), path);
+            }
+        }
+    }
+    plugins
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Ask a plugin to describe itself. Plugins that don't understand the flag
+/// or don't answer quickly are listed without details.
+async fn plugin_info(path: &Path) -> PluginInfo {
+    let output = tokio::process::Command::new(path)
+        .arg(INFO_FLAG)
+        .env("SYLA_PLUGIN_API", PLUGIN_API_VERSION.to_string())
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+
+    match tokio::time::timeout(Duration::from_secs(2), output).await {
+        Ok(Ok(output)) if output.status.success() => {
+            serde_json::from_slice(&output.stdout).unwrap_or_default()
+        }
+        _ => PluginInfo::default(),
+    }
+}
+
+fn plugin_context(config: Option<&Config>) -> PluginContext {
+    let Some(config) = config else {
+        return PluginContext {
+            api_version: PLUGIN_API_VERSION,
+            cli_version: env!("CARGO_PKG_VERSION"),
+            workspace_root: None,
+            manifest_path: None,
+            repositories: Vec::new(),
+        };
+    };
+
+    let mut repositories: Vec<_> = config
+        .get_all_repositories()
+        .into_iter()
+        .map(|(name, repo)| RepositoryContext {
+            path: config.workspace_root.join(&repo.path),
+            language: repo.language.clone(),
+            platform: repo.platform.clone(),
+            name,
+        })
+        .collect();
+    repositories.sort_by(|a, b| a.name.cmp(&b.name));
+
+    PluginContext {
+        api_version: PLUGIN_API_VERSION,
+        cli_version: env!("CARGO_PKG_VERSION"),
+        workspace_root: Some(config.workspace_root.clone()),
+        manifest_path: Some(manifest_path(config)),
+        repositories,
+    }
+}
+
+fn manifest_path(config: &Config) -> PathBuf {
+    config.workspace_root.join(".platform/config/repos.toml")
+}
@@ -0,0 +1,742 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::ProtoCommands;
+
+/// A repository's protobuf definitions
+struct Protos {
+    service: String,
+    language: String,
+    branch: String,
+    repo_path: PathBuf,
+    /// Relative to the repository
+    dir: String,
+    out: Option<String>,
+    /// Proto files, relative to `dir`
+    files: Vec<PathBuf>,
+}
+
+/// How a repository's generated code is produced
+enum Generator {
+    /// `buf generate` with the repository's `buf.gen.yaml`
+    Buf,
+    /// `protoc` with the language's plugins, into `out`
+    Protoc { plugins: &'static [&'static str], out: String },
+    /// Generated by the build itself, e.g. tonic-build in `build.rs`
+    Build,
+}
+
+impl Protos {
+    fn proto_dir(&self) -> PathBuf {
+        self.repo_path.join(&self.dir)
+    }
+
+    fn generator(&self) -> Result<Generator> {
+        if self.repo_path.join("buf.gen.yaml").exists() {
+            return Ok(Generator::Buf);
+        }
+        let Some(out) = self.out.clone() else {
+            return Ok(Generator::Build);
+        };
+        let plugins = protoc_plugins(&self.language).with_context(|| {
+            format!(
+                "No protoc plugins known for {} ({}); add a buf.gen.yaml instead",
+                self.service, self.language
+            )
+        })?;
+        Ok(Generator::Protoc { plugins, out })
+    }
+}
+
+fn protoc_plugins(language: &str) -> Option<&'static [&'static str]> {
+    match language {
+        "rust" => Some(&["--prost_out", "--tonic_out"]),
+        "go" => Some(&["--go_out", "--go-grpc_out"]),
+        "python" => Some(&["--python_out", "--pyi_out"]),
+        "typescript" => Some(&["--ts_out"]),
+        _ => None,
+    }
+}
+
+pub async fn run(command: ProtoCommands, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    match command {
+        ProtoCommands::List => list(&config),
+        ProtoCommands::Generate { service } => generate(&config, service.as_deref()),
+        ProtoCommands::Check { service } => check(&config, service.as_deref()),
+        ProtoCommands::Breaking { service, against } => breaking(&config, service.as_deref(), against.as_deref()),
+    }
+}
+
+fn list(config: &Config) -> Result<()> {
+    let protos = discover(config, None)?;
+    println!("{}", "Protobuf definitions".bold());
+    println!();
+    if protos.is_empty() {
+        println!("  No protos found. Add a proto/ directory or [repositories.<name>.protos] to .platform/config/repos.toml");
+        return Ok(());
+    }
+
+    for p in &protos {
+        let generator = match p.generator() {
+            Ok(Generator::Buf) => "buf generate".to_string(),
+            Ok(Generator::Protoc { out, .. }) => format!("protoc -> {}", out),
+            Ok(Generator::Build) => "generated at build time".to_string(),
+            Err(e) => format!("{:#}", e),
+        };
+        println!("{} {}", p.service.bold(), format!("({}, {})", p.dir, generator).dimmed());
+        let mut packages = BTreeMap::new();
+        for file in &p.files {
+            let source = std::fs::read_to_string(p.proto_dir().join(file)).unwrap_or_default();
+            let package = Schema::parse(&source).package.unwrap_or_else(|| "(no package)".to_string());
+            packages.entry(package).or_insert_with(Vec::new).push(file.display().to_string());
+        }
+        for (package, files) in packages {
+            println!("  {} {}", package.cyan(), files.join(", "));
+        }
+    }
+
+    let includes = include_dirs(config);
+    if !includes.is_empty() {
+        println!("\n{}", "Shared includes".bold());
+        for dir in includes {
+            println!("  {}", dir.display());
+        }
+    }
+    Ok(())
+}
+
+fn generate(config: &Config, service: Option<&str>) -> Result<()> {
+    let protos = discover(config, service)?;
+    if protos.is_empty() {
+        println!("No protos found");
+        return Ok(());
+    }
+
+    println!("{}", "Generating code from protos...".bold());
+    for p in &protos {
+        match p.generator()? {
+            Generator::Build => {
+                say!("{} {} generates its code at build time", "[OK]".green(), p.service);
+            }
+            generator => {
+                let out = match &generator {
+                    Generator::Protoc { out, .. } => p.repo_path.join(out),
+                    _ => p.repo_path.clone(),
+                };
+                std::fs::create_dir_all(&out)?;
+                run_generator(config, p, &generator, &out)?;
+                say!("{} Generated {}", "[OK]".green(), p.service);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs buf or protoc for `p`, writing into `out` (the output base for
+/// buf, the plugins' output directory for protoc)
+fn run_generator(config: &Config, p: &Protos, generator: &Generator, out: &Path) -> Result<()> {
+    let mut cmd = match generator {
+        Generator::Buf => {
+            let mut cmd = Command::new("buf");
+            cmd.arg("generate").arg(&p.dir).arg("--output").arg(out);
+            cmd
+        }
+        Generator::Protoc { plugins, .. } => {
+            let mut cmd = Command::new("protoc");
+            cmd.arg(format!("--proto_path={}", p.proto_dir().display()));
+            for include in include_dirs(config) {
+                cmd.arg(format!("--proto_path={}", include.display()));
+            }
+            for plugin in *plugins {
+                cmd.arg(format!("{}={}", plugin, out.display()));
+            }
+            cmd.args(&p.files);
+            cmd
+        }
+        Generator::Build => return Ok(()),
+    };
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let output = cmd
+        .current_dir(&p.repo_path)
+        .output()
+        .with_context(|| format!("Failed to run {}; is it installed?", program))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} failed for {}:\n{}",
+            program,
+            p.service,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Fails when checked-in generated code is stale or copies of a shared
+/// proto have drifted apart
+fn check(config: &Config, service: Option<&str>) -> Result<()> {
+    let protos = discover(config, service)?;
+    println!("{}", "Checking generated code...".bold());
+    let mut problems = 0;
+
+    for p in &protos {
+        let generator = p.generator()?;
+        let base = match &generator {
+            Generator::Build => continue,
+            Generator::Protoc { out, .. } => p.repo_path.join(out),
+            Generator::Buf => p.repo_path.clone(),
+        };
+        let scratch = std::env::temp_dir().join(format!("syla-proto-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&scratch)?;
+        let generated = run_generator(config, p, &generator, &scratch).map(|_| files_under(&scratch));
+        let stale = generated.map(|generated| {
+            let mut stale: Vec<String> = generated
+                .iter()
+                .filter(|file| std::fs::read(scratch.join(file)).ok() != std::fs::read(base.join(file)).ok())
+                .map(|file| file.display().to_string())
+                .collect();
+            // buf writes wherever buf.gen.yaml says, so only protoc's output
+            // directory can be checked for leftovers
+            if matches!(generator, Generator::Protoc { .. }) {
+                stale.extend(
+                    files_under(&base)
+                        .into_iter()
+                        .filter(|file| !generated.contains(file))
+                        .map(|file| format!("{} (no longer generated)", file.display())),
+                );
+            }
+            stale
+        });
+        let _ = std::fs::remove_dir_all(&scratch);
+
+        match stale? {
+            stale if stale.is_empty() => say!("{} {} is up to date", "[OK]".green(), p.service),
+            stale => {
+                problems += 1;
+                println!("{} {} has stale generated code:", "[X]".red(), p.service);
+                for file in stale {
+                    println!("    {}", file);
+                }
+            }
+        }
+    }
+
+    // The same import path in two proto roots should be the same file
+    let mut copies: BTreeMap<PathBuf, Vec<(PathBuf, Vec<u8>)>> = BTreeMap::new();
+    let roots = include_dirs(config).into_iter().chain(protos.iter().map(Protos::proto_dir));
+    for root in roots {
+        for file in files_under(&root).into_iter().filter(|f| is_proto(f)) {
+            if let Ok(content) = std::fs::read(root.join(&file)) {
+                copies.entry(file).or_default().push((root.clone(), content));
+            }
+        }
+    }
+    for (file, copies) in copies {
+        if copies.windows(2).any(|pair| pair[0].1 != pair[1].1) {
+            problems += 1;
+            let roots: Vec<String> = copies
+                .iter()
+                .map(|(root, _)| display_path(config, root))
+                .collect();
+            println!("{} {} differs between {}", "[X]".red(), file.display(), roots.join(", "));
+        }
+    }
+
+    if problems > 0 {
+        anyhow::bail!("{} proto problem(s); run `syla proto generate` and sync shared protos", problems);
+    }
+    say!("{} Protos are in sync", "[OK]".green());
+    Ok(())
+}
+
+/// Compares each repository's protos with another branch, with
+/// `buf breaking` when the repository has a buf.yaml and a built-in check
+/// of removed or changed messages, fields, enum values and RPCs otherwise
+fn breaking(config: &Config, service: Option<&str>, against: Option<&str>) -> Result<()> {
+    let protos = discover(config, service)?;
+    let mut broken = Vec::new();
+
+    for p in &protos {
+        let reference = against.unwrap_or(&p.branch);
+        println!("{} {} against {}", "[>]".cyan(), p.service.bold(), reference);
+
+        if p.repo_path.join("buf.yaml").exists() && which::which("buf").is_ok() {
+            let status = Command::new("buf")
+                .arg("breaking")
+                .arg(&p.dir)
+                .arg("--against")
+                .arg(format!(".git#ref={},subdir={}", reference, p.dir))
+                .current_dir(&p.repo_path)
+                .status()
+                .context("Failed to run buf")?;
+            if !status.success() {
+                broken.push(p.service.clone());
+            }
+            continue;
+        }
+
+        let old = Schema::at_ref(p, reference)?;
+        let mut new = Schema::default();
+        for file in &p.files {
+            new.merge(Schema::parse(&std::fs::read_to_string(p.proto_dir().join(file))?));
+        }
+        let changes = old.breaking_changes(&new);
+        if changes.is_empty() {
+            say!("  {} No breaking changes", "[OK]".green());
+        } else {
+            broken.push(p.service.clone());
+            for change in changes {
+                println!("  {} {}", "[X]".red(), change);
+            }
+        }
+    }
+
+    if !broken.is_empty() {
+        anyhow::bail!("Breaking proto changes in {}", broken.join(", "));
+    }
+    Ok(())
+}
+
+fn discover(config: &Config, service: Option<&str>) -> Result<Vec<Protos>> {
+    if let Some(service) = service {
+        if !config.manifest.repositories.contains_key(service) {
+            anyhow::bail!("Repository '{}' not found in manifest", service);
+        }
+    }
+
+    let mut found = Vec::new();
+    for (name, repo) in &config.manifest.repositories {
+        if service.is_some_and(|s| s != name) {
+            continue;
+        }
+        let repo_path = config.workspace_root.join(&repo.path);
+        let (dir, out) = match &repo.protos {
+            Some(protos) => (protos.dir.clone(), protos.out.clone()),
+            None if repo_path.join("proto").is_dir() => ("proto".to_string(), None),
+            None => continue,
+        };
+        if !repo_path.exists() {
+            println!("{} {} is not cloned, skipping its protos", "[!]".yellow(), name);
+            continue;
+        }
+
+        let files: Vec<PathBuf> = files_under(&repo_path.join(&dir))
+            .into_iter()
+            .filter(|f| is_proto(f))
+            .collect();
+        found.push(Protos {
+            service: name.clone(),
+            language: repo.language.clone(),
+            branch: repo.branch.clone(),
+            repo_path,
+            dir,
+            out,
+            files,
+        });
+    }
+    found.sort_by(|a, b| a.service.cmp(&b.service));
+    Ok(found)
+}
+
+fn include_dirs(config: &Config) -> Vec<PathBuf> {
+    config
+        .manifest
+        .proto_includes
+        .iter()
+        .map(|dir| config.workspace_root.join(dir))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+fn is_proto(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "proto")
+}
+
+/// Files under `dir`, relative to it and sorted
+fn files_under(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.path().strip_prefix(dir).ok().map(Path::to_path_buf))
+        .collect();
+    files.sort();
+    files
+}
+
+fn display_path(config: &Config, path: &Path) -> String {
+    path.strip_prefix(&config.workspace_root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// The parts of a set of proto files that clients depend on, keyed by
+/// fully qualified name
+#[derive(Debug, Default)]
+pub(crate) struct Schema {
+    pub(crate) package: Option<String>,
+    pub(crate) messages: BTreeMap<String, Fields>,
+    pub(crate) enums: BTreeMap<String, Fields>,
+    /// `package.Service/Method` to `(request, response)`
+    pub(crate) rpcs: BTreeMap<String, (String, String)>,
+}
+
+/// Fields of a message or values of an enum, by number
+#[derive(Debug, Default)]
+pub(crate) struct Fields {
+    /// Number to `(name, type)`; enum values have no type
+    pub(crate) by_number: BTreeMap<i64, (String, String)>,
+    reserved: Vec<(i64, i64)>,
+}
+
+impl Fields {
+    fn is_reserved(&self, number: i64) -> bool {
+        self.reserved.iter().any(|(from, to)| (*from..=*to).contains(&number))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scope {
+    Message,
+    Enum,
+    Service,
+    Oneof,
+    Other,
+}
+
+impl Schema {
+    /// Everything a repository's checked-out protos and the shared includes
+    /// define, for talking to the service without generated code
+    pub(crate) fn load(config: &Config, service: &str) -> Result<Self> {
+        let mut schema = Schema::default();
+        for p in discover(config, Some(service))? {
+            for file in &p.files {
+                let path = p.proto_dir().join(file);
+                let source =
+                    std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+                schema.merge(Schema::parse(&source));
+            }
+        }
+        for dir in include_dirs(config) {
+            for file in files_under(&dir).into_iter().filter(|f| is_proto(f)) {
+                schema.merge(Schema::parse(&std::fs::read_to_string(dir.join(&file)).unwrap_or_default()));
+            }
+        }
+        Ok(schema)
+    }
+
+    fn at_ref(p: &Protos, reference: &str) -> Result<Self> {
+        let git = |args: &[&str]| -> Result<String> {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(&p.repo_path)
+                .output()
+                .context("Failed to execute git")?;
+            if !output.status.success() {
+                anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        };
+
+        let listing = git(&["ls-tree", "-r", "--name-only", reference, "--", &p.dir])
+            .with_context(|| format!("Failed to list {} protos at {}", p.service, reference))?;
+        let mut schema = Schema::default();
+        for file in listing.lines().filter(|f| f.ends_with(".proto")) {
+            let source = git(&["show", &format!("{}:./{}", reference, file)])?;
+            schema.merge(Schema::parse(&source));
+        }
+        Ok(schema)
+    }
+
+    fn merge(&mut self, other: Schema) {
+        self.package = self.package.take().or(other.package);
+        self.messages.extend(other.messages);
+        self.enums.extend(other.enums);
+        self.rpcs.extend(other.rpcs);
+    }
+
+    fn parse(source: &str) -> Self {
+        let tokens = tokenize(source);
+        let mut schema = Schema::default();
+        let mut package = String::new();
+        let mut scopes: Vec<(Scope, String)> = Vec::new();
+
+        let qualified = |scopes: &[(Scope, String)], package: &str, name: &str| {
+            let mut parts: Vec<&str> = Vec::new();
+            if !package.is_empty() {
+                parts.push(package);
+            }
+            parts.extend(scopes.iter().filter(|(s, _)| *s == Scope::Message).map(|(_, n)| n.as_str()));
+            parts.push(name);
+            parts.join(".")
+        };
+        let statement_end = |from: usize| tokens[from..].iter().position(|t| t == ";").map_or(tokens.len(), |p| from + p);
+
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = tokens[i].as_str();
+            let opens_block = tokens.get(i + 2).is_some_and(|t| t == "{");
+            match token {
+                "}" => {
+                    scopes.pop();
+                    i += 1;
+                }
+                ";" => i += 1,
+                "package" => {
+                    package = tokens.get(i + 1).cloned().unwrap_or_default();
+                    schema.package = Some(package.clone());
+                    i = statement_end(i) + 1;
+                }
+                "syntax" | "edition" | "import" | "option" | "extensions" => i = statement_end(i) + 1,
+                "message" | "enum" | "service" | "oneof" | "extend" if opens_block => {
+                    let name = tokens[i + 1].clone();
+                    let scope = match token {
+                        "message" => Scope::Message,
+                        "enum" => Scope::Enum,
+                        "service" => Scope::Service,
+                        "oneof" => Scope::Oneof,
+                        _ => Scope::Other,
+                    };
+                    match scope {
+                        Scope::Message => {
+                            schema.messages.entry(qualified(&scopes, &package, &name)).or_default();
+                        }
+                        Scope::Enum => {
+                            schema.enums.entry(qualified(&scopes, &package, &name)).or_default();
+                        }
+                        _ => {}
+                    }
+                    scopes.push((scope, name));
+                    i += 3;
+                }
+                "rpc" => {
+                    // rpc Name ( [stream] Request ) returns ( [stream] Response ) ; or { options }
+                    let end = tokens[i..]
+                        .iter()
+                        .position(|t| t == ";" || t == "{")
+                        .map_or(tokens.len(), |p| i + p);
+                    let parts = &tokens[i..end];
+                    let groups: Vec<String> = parts
+                        .split(|t| t == "(" || t == ")")
+                        .skip(1)
+                        .step_by(2)
+                        .map(|group| group.join(" "))
+                        .collect();
+                    if let (Some(name), Some(service), [request, response, ..]) =
+                        (parts.get(1), scopes.last(), groups.as_slice())
+                    {
+                        let key = format!("{}/{}", qualified(&[], &package, &service.1), name);
+                        schema.rpcs.insert(key, (request.clone(), response.clone()));
+                    }
+                    i = if tokens.get(end).is_some_and(|t| t == "{") {
+                        skip_block(&tokens, end)
+                    } else {
+                        end + 1
+                    };
+                }
+                _ => {
+                    let end = statement_end(i);
+                    let statement = &tokens[i..end];
+                    i = end + 1;
+                    let Some((scope, _)) = scopes.last() else { continue };
+                    let owner = match scope {
+                        // Oneof fields belong to the enclosing message
+                        Scope::Message | Scope::Oneof => {
+                            let Some(at) = scopes.iter().rposition(|(s, _)| *s == Scope::Message) else { continue };
+                            schema.messages.entry(qualified(&scopes[..at], &package, &scopes[at].1)).or_default()
+                        }
+                        Scope::Enum => {
+                            let at = scopes.len() - 1;
+                            schema.enums.entry(qualified(&scopes[..at], &package, &scopes[at].1)).or_default()
+                        }
+                        _ => continue,
+                    };
+                    if statement.first().is_some_and(|t| t == "reserved") {
+                        owner.reserved.extend(reserved_ranges(&statement[1..]));
+                        continue;
+                    }
+                    let Some(eq) = statement.iter().position(|t| t == "=") else { continue };
+                    let Some(number) = statement.get(eq + 1).and_then(|n| parse_number(n)) else { continue };
+                    if eq == 0 {
+                        continue;
+                    }
+                    let name = statement[eq - 1].clone();
+                    let kind = if *scope == Scope::Enum {
+                        String::new()
+                    } else {
+                        statement[..eq - 1]
+                            .join(" ")
+                            .replace(" < ", "<")
+                            .replace(" , ", ", ")
+                            .replace(" >", ">")
+                    };
+                    owner.by_number.insert(number, (name, kind));
+                }
+            }
+        }
+        schema
+    }
+
+    /// Changes from `self` to `new` that break existing clients
+    fn breaking_changes(&self, new: &Schema) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.package.is_some() && self.package != new.package {
+            changes.push(format!(
+                "package changed from {} to {}",
+                self.package.as_deref().unwrap_or_default(),
+                new.package.as_deref().unwrap_or("(none)")
+            ));
+        }
+        for (kind, old_types, new_types) in [("message", &self.messages, &new.messages), ("enum", &self.enums, &new.enums)] {
+            for (name, old) in old_types {
+                let Some(current) = new_types.get(name) else {
+                    changes.push(format!("{} {} was removed", kind, name));
+                    continue;
+                };
+                let member = if kind == "message" { "field" } else { "value" };
+                for (number, (old_name, old_type)) in &old.by_number {
+                    match current.by_number.get(number) {
+                        None if current.is_reserved(*number) => {}
+                        None => changes.push(format!(
+                            "{} {} {} ({}) was removed without reserving it",
+                            name, member, number, old_name
+                        )),
+                        Some((new_name, new_type)) => {
+                            if new_type != old_type {
+                                changes.push(format!(
+                                    "{}.{} ({}) changed type from {} to {}",
+                                    name, old_name, number, old_type, new_type
+                                ));
+                            }
+                            if new_name != old_name {
+                                changes.push(format!(
+                                    "{} {} {} was renamed from {} to {}",
+                                    name, member, number, old_name, new_name
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for (rpc, (old_request, old_response)) in &self.rpcs {
+            match new.rpcs.get(rpc) {
+                None => changes.push(format!("rpc {} was removed", rpc)),
+                Some((request, response)) => {
+                    if request != old_request {
+                        changes.push(format!("rpc {} request changed from {} to {}", rpc, old_request, request));
+                    }
+                    if response != old_response {
+                        changes.push(format!("rpc {} response changed from {} to {}", rpc, old_response, response));
+                    }
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// Splits proto source into identifiers, numbers, strings and punctuation,
+/// dropping comments
+fn tokenize(source: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' => {
+                let mut literal = String::from(c);
+                for next in chars.by_ref() {
+                    literal.push(next);
+                    if next == c {
+                        break;
+                    }
+                }
+                tokens.push(literal);
+            }
+            c if c.is_whitespace() => {}
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '+' => {
+                let mut word = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_alphanumeric() || next == '_' || next == '.' {
+                        word.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(word);
+            }
+            c => tokens.push(c.to_string()),
+        }
+    }
+    tokens
+}
+
+/// Index just past the block opened at `start`
+fn skip_block(tokens: &[String], start: usize) -> usize {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token.as_str() {
+            "{" => depth += 1,
+            "}" => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+fn parse_number(token: &str) -> Option<i64> {
+    match token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => token.parse().ok(),
+    }
+}
+
+/// Numbers and ranges from a `reserved` statement; reserved names are ignored
+fn reserved_ranges(tokens: &[String]) -> Vec<(i64, i64)> {
+    let mut ranges = Vec::new();
+    for part in tokens.split(|t| t == ",") {
+        match part {
+            [from] => {
+                if let Some(n) = parse_number(from) {
+                    ranges.push((n, n));
+                }
+            }
+            [from, to, end] if to == "to" => {
+                let end = if end == "max" { Some(i64::MAX) } else { parse_number(end) };
+                if let (Some(from), Some(end)) = (parse_number(from), end) {
+                    ranges.push((from, end));
+                }
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
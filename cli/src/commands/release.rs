@@ -0,0 +1,324 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use semver::Version;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use toml_edit::{DocumentMut, Item};
+
+use crate::config::Config;
+
+pub struct ReleaseOptions {
+    /// `major`, `minor`, `patch` or an exact version
+    pub bump: String,
+    /// Repository names; `*` wildcards allowed
+    pub repos: Vec<String>,
+    pub platform: Option<String>,
+    pub dry_run: bool,
+    pub tag: bool,
+    pub push: bool,
+    /// Also write the combined release notes to this file
+    pub notes: Option<PathBuf>,
+}
+
+/// A repository being released
+struct Release {
+    repo: String,
+    path: PathBuf,
+    url: String,
+    crate_name: String,
+    from: Version,
+    to: Version,
+    notes: Vec<String>,
+}
+
+impl Release {
+    fn tag(&self) -> String {
+        format!("v{}", self.to)
+    }
+}
+
+pub async fn run(options: ReleaseOptions, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    let releases = plan(&config, &options)?;
+    if releases.is_empty() {
+        anyhow::bail!("No Rust repositories matched");
+    }
+
+    if !options.dry_run {
+        let dirty: Vec<&str> = releases
+            .iter()
+            .filter(|r| git(&r.path, &["status", "--porcelain"]).is_ok_and(|s| !s.trim().is_empty()))
+            .map(|r| r.repo.as_str())
+            .collect();
+        if !dirty.is_empty() {
+            anyhow::bail!("Uncommitted changes in {}; commit or stash them first", dirty.join(", "));
+        }
+    }
+
+    println!("{}", "Release plan".bold());
+    for r in &releases {
+        println!("  {} {} {} -> {}", "*".cyan(), r.repo.bold(), r.from, r.to.to_string().green());
+    }
+
+    // Point every repository's dependencies on released crates at the new versions
+    let mut updates: BTreeMap<PathBuf, (String, Vec<String>)> = BTreeMap::new();
+    for (name, repo) in config.get_all_repositories() {
+        let path = config.workspace_root.join(&repo.path);
+        let manifest = path.join("Cargo.toml");
+        let Ok(content) = std::fs::read_to_string(&manifest) else {
+            continue;
+        };
+        let mut doc: DocumentMut = content
+            .parse()
+            .with_context(|| format!("Failed to parse {}", manifest.display()))?;
+        let mut changes = Vec::new();
+        if let Some(release) = releases.iter().find(|r| r.path == path) {
+            doc["package"]["version"] = toml_edit::value(release.to.to_string());
+        }
+        update_references(&mut doc, &releases, &mut changes);
+        if !changes.is_empty() {
+            println!("  {} {}: {}", "->".dimmed(), name, changes.join(", "));
+        }
+        if !options.dry_run && doc.to_string() != content {
+            std::fs::write(&manifest, doc.to_string())
+                .with_context(|| format!("Failed to write {}", manifest.display()))?;
+            updates.insert(path, (name, changes));
+        }
+    }
+
+    let notes = release_notes(&releases);
+    println!("\n{}", notes);
+    if let Some(file) = &options.notes {
+        std::fs::write(file, &notes).with_context(|| format!("Failed to write {}", file.display()))?;
+    }
+    if options.dry_run {
+        return Ok(());
+    }
+
+    // Commit everything first so a failure leaves no half-tagged release
+    for (path, (name, changes)) in &updates {
+        if path.join("Cargo.lock").exists() {
+            let updated = config
+                .cargo()
+                .args(["update", "--workspace", "--offline", "--quiet"])
+                .current_dir(path)
+                .status();
+            if !updated.is_ok_and(|s| s.success()) {
+                println!("{} Could not refresh Cargo.lock in {}", "[!]".yellow(), name);
+            }
+        }
+        let message = match releases.iter().find(|r| &r.path == path) {
+            Some(release) => format!("Release {}", release.tag()),
+            None => format!("Update {}", changes.join(", ")),
+        };
+        git(path, &["add", "--", "Cargo.toml", "Cargo.lock"])
+            .or_else(|_| git(path, &["add", "--", "Cargo.toml"]))
+            .with_context(|| format!("Failed to stage {}", name))?;
+        git(path, &["commit", "-q", "-m", &message]).with_context(|| format!("Failed to commit {}", name))?;
+        say!("{} Committed {}: {}", "[OK]".green(), name, message);
+    }
+
+    for r in &releases {
+        if options.tag {
+            let message = format!("{} {}\n\n{}", r.crate_name, r.tag(), r.notes.join("\n"));
+            git(&r.path, &["tag", "-a", &r.tag(), "-m", &message])
+                .with_context(|| format!("Failed to tag {}", r.repo))?;
+            say!("{} Tagged {} {}", "[OK]".green(), r.repo, r.tag());
+        }
+    }
+    if options.push {
+        for (path, (name, _)) in &updates {
+            git(path, &["push", "--follow-tags", "origin", "HEAD"]).with_context(|| format!("Failed to push {}", name))?;
+            say!("{} Pushed {}", "[OK]".green(), name);
+        }
+    }
+
+    say!("\n{} Released {} repositories", "[OK]".green().bold(), releases.len());
+    Ok(())
+}
+
+fn plan(config: &Config, options: &ReleaseOptions) -> Result<Vec<Release>> {
+    let mut repos = match &options.platform {
+        Some(platform) => config
+            .get_platform_repositories(platform)
+            .ok_or_else(|| anyhow::anyhow!("Platform '{}' not found", platform))?,
+        None => config.get_all_repositories(),
+    };
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    for pattern in &options.repos {
+        let pattern = glob::Pattern::new(pattern).with_context(|| format!("Invalid pattern '{}'", pattern))?;
+        if !repos.iter().any(|(name, _)| pattern.matches(name)) {
+            anyhow::bail!("No repository matches '{}'", pattern);
+        }
+    }
+
+    let mut releases = Vec::new();
+    for (name, repo) in repos {
+        let selected = options.repos.is_empty()
+            || options
+                .repos
+                .iter()
+                .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|p| p.matches(&name)));
+        let path = config.workspace_root.join(&repo.path);
+        let manifest = path.join("Cargo.toml");
+        if !selected || !manifest.exists() {
+            continue;
+        }
+
+        let doc: DocumentMut = std::fs::read_to_string(&manifest)?
+            .parse()
+            .with_context(|| format!("Failed to parse {}", manifest.display()))?;
+        let crate_name = doc["package"]["name"]
+            .as_str()
+            .with_context(|| format!("{} has no package name", name))?
+            .to_string();
+        let from = doc["package"]["version"]
+            .as_str()
+            .and_then(|v| Version::parse(v).ok())
+            .with_context(|| format!("{} has no valid package version", name))?;
+        let to = bump(&from, &options.bump)?;
+        let notes = commits_since_release(&path)?;
+        releases.push(Release {
+            repo: name,
+            path,
+            url: repo.url.clone(),
+            crate_name,
+            from,
+            to,
+            notes,
+        });
+    }
+    Ok(releases)
+}
+
+fn bump(version: &Version, bump: &str) -> Result<Version> {
+    let next = match bump {
+        "major" => Version::new(version.major + 1, 0, 0),
+        "minor" => Version::new(version.major, version.minor + 1, 0),
+        "patch" => Version::new(version.major, version.minor, version.patch + 1),
+        exact => Version::parse(exact.trim_start_matches('v'))
+            .with_context(|| format!("'{}' is not major, minor, patch or a version", exact))?,
+    };
+    if next <= *version {
+        anyhow::bail!("{} is not newer than {}", next, version);
+    }
+    Ok(next)
+}
+
+/// Commit subjects since the latest `v*` tag, or all of them for a first release
+fn commits_since_release(path: &Path) -> Result<Vec<String>> {
+    let range = match git(path, &["describe", "--tags", "--abbrev=0", "--match", "v*"]) {
+        Ok(tag) => format!("{}..HEAD", tag.trim()),
+        Err(_) => "HEAD".to_string(),
+    };
+    let log = git(path, &["log", "--no-merges", "--pretty=format:%s", &range])
+        .with_context(|| format!("Failed to read history of {}", path.display()))?;
+    Ok(log
+        .lines()
+        .filter(|subject| !subject.starts_with("Release v"))
+        .map(String::from)
+        .collect())
+}
+
+/// Rewrites dependencies on released crates, in every dependency table
+fn update_references(doc: &mut DocumentMut, releases: &[Release], changes: &mut Vec<String>) {
+    let mut tables: Vec<&mut Item> = Vec::new();
+    let root = doc.as_table_mut();
+    let (targets, rest): (Vec<_>, Vec<_>) = root.iter_mut().partition(|(key, _)| key.get() == "target");
+    for (key, item) in rest {
+        match key.get() {
+            "dependencies" | "dev-dependencies" | "build-dependencies" => tables.push(item),
+            "workspace" => {
+                if let Some(deps) = item.get_mut("dependencies") {
+                    tables.push(deps);
+                }
+            }
+            _ => {}
+        }
+    }
+    for (_, target) in targets {
+        for (_, platform) in target.as_table_like_mut().into_iter().flat_map(|t| t.iter_mut()) {
+            for (key, item) in platform.as_table_like_mut().into_iter().flat_map(|t| t.iter_mut()) {
+                if key.get().ends_with("dependencies") {
+                    tables.push(item);
+                }
+            }
+        }
+    }
+
+    for table in tables {
+        let Some(table) = table.as_table_like_mut() else { continue };
+        for (key, dependency) in table.iter_mut() {
+            let crate_name = dependency
+                .get("package")
+                .and_then(Item::as_str)
+                .unwrap_or(key.get())
+                .to_string();
+            let Some(release) = releases.iter().find(|r| r.crate_name == crate_name) else {
+                continue;
+            };
+            let version = release.to.to_string();
+            if dependency.is_str() {
+                *dependency = toml_edit::value(version.clone());
+                changes.push(format!("{} {}", crate_name, version));
+                continue;
+            }
+            let Some(fields) = dependency.as_table_like_mut() else { continue };
+            let from_release_repo = fields
+                .get("git")
+                .and_then(Item::as_str)
+                .is_some_and(|url| url.trim_end_matches(".git") == release.url.trim_end_matches(".git"));
+            let mut changed = false;
+            if from_release_repo && fields.contains_key("tag") {
+                fields.insert("tag", toml_edit::value(release.tag()));
+                changed = true;
+            }
+            if fields.contains_key("version") {
+                fields.insert("version", toml_edit::value(version.clone()));
+                changed = true;
+            }
+            if changed {
+                changes.push(format!("{} {}", crate_name, version));
+            }
+        }
+    }
+}
+
+fn release_notes(releases: &[Release]) -> String {
+    let mut notes = String::from("# Release notes\n");
+    for r in releases {
+        notes.push_str(&format!("\n## {} {}\n", r.repo, r.tag()));
+        let mut sections: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for subject in &r.notes {
+            let (section, text) = match subject.split_once(':') {
+                Some((kind, text)) if kind.trim_end_matches('!').starts_with("feat") => ("Features", text.trim()),
+                Some((kind, text)) if kind.trim_end_matches('!').starts_with("fix") => ("Fixes", text.trim()),
+                _ => ("Other changes", subject.as_str()),
+            };
+            sections.entry(section).or_default().push(text);
+        }
+        if sections.is_empty() {
+            notes.push_str("\nNo changes since the last release.\n");
+        }
+        for (section, entries) in sections {
+            notes.push_str(&format!("\n### {}\n\n", section));
+            for entry in entries {
+                notes.push_str(&format!("- {}\n", entry));
+            }
+        }
+    }
+    notes
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to execute git")?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::config::{Config, TaskConfig};
+use crate::secrets::{self, SecretStore};
+
+pub async fn run(
+    task: Option<String>,
+    args: Vec<String>,
+    dry_run: bool,
+    workspace_root: Option<PathBuf>,
+) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+
+    let Some(task) = task else {
+        list(&config);
+        return Ok(());
+    };
+
+    let order = plan(&config, &task)?;
+    for name in &order {
+        let task_config = &config.manifest.tasks[name];
+        // Extra arguments only go to the task that was asked for
+        let extra_args = if *name == task { args.as_slice() } else { &[] };
+        run_task(&config, name, task_config, extra_args, dry_run)?;
+    }
+
+    if !dry_run {
+        say!("\n{} {} complete", "[OK]".green(), task.bold());
+    }
+    Ok(())
+}
+
+fn list(config: &Config) {
+    println!("{}", "Tasks".bold());
+    println!();
+
+    if config.manifest.tasks.is_empty() {
+        println!("  No tasks defined. Add a [tasks.<name>] table to .platform/config/repos.toml");
+        return;
+    }
+
+    let width = config.manifest.tasks.keys().map(String::len).max().unwrap_or(0);
+    for (name, task) in &config.manifest.tasks {
+        let description = task.description.as_deref().unwrap_or(&task.command);
+        println!("  {:width$}  {}", name.cyan(), description, width = width);
+        if !task.depends_on.is_empty() {
+            println!("  {:width$}  {}", "", format!("after: {}", task.depends_on.join(", ")).dimmed(), width = width);
+        }
+    }
+}
+
+/// Tasks to run for `target`, dependencies first, each at most once
+fn plan(config: &Config, target: &str) -> Result<Vec<String>> {
+    fn visit(
+        tasks: &std::collections::BTreeMap<String, TaskConfig>,
+        name: &str,
+        required_by: Option<&str>,
+        visiting: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        order: &mut Vec<String>,
+    ) -> Result<()> {
+        if done.contains(name) {
+            return Ok(());
+        }
+        if let Some(start) = visiting.iter().position(|n| n == name) {
+            let mut cycle = visiting[start..].to_vec();
+            cycle.push(name.to_string());
+            anyhow::bail!("Task dependency cycle: {}", cycle.join(" -> "));
+        }
+        let Some(task) = tasks.get(name) else {
+            return match required_by {
+                Some(parent) => Err(anyhow::anyhow!("Task '{}' depends on unknown task '{}'", parent, name)),
+                None => Err(anyhow::anyhow!(
+                    "Task '{}' not found. Available tasks: {}",
+                    name,
+                    tasks.keys().cloned().collect::<Vec<_>>().join(", ")
+                )),
+            };
+        };
+
+        visiting.push(name.to_string());
+        for dependency in &task.depends_on {
+            visit(tasks, dependency, Some(name), visiting, done, order)?;
+        }
+        visiting.pop();
+
+        done.insert(name.to_string());
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    visit(
+        &config.manifest.tasks,
+        target,
+        None,
+        &mut Vec::new(),
+        &mut HashSet::new(),
+        &mut order,
+    )?;
+    Ok(order)
+}
+
+fn run_task(config: &Config, name: &str, task: &TaskConfig, extra_args: &[String], dry_run: bool) -> Result<()> {
+    let mut command = task.command.clone();
+    for arg in extra_args {
+        command.push(' ');
+        command.push_str(&shell_quote(arg));
+    }
+
+    let mut env: HashMap<String, String> = task.env.clone().into_iter().collect();
+    if !dry_run && secrets::has_secret_refs(env.values()) {
+        SecretStore::open()?.resolve_env(&mut env)?;
+    }
+    env.insert("SYLA_WORKSPACE".to_string(), config.workspace_root.display().to_string());
+    env.insert("SYLA_TASK".to_string(), name.to_string());
+
+    if task.repos.is_empty() {
+        let dir = match &task.dir {
+            Some(dir) => config.workspace_root.join(dir),
+            None => config.workspace_root.clone(),
+        };
+        println!("{} {}", "[>]".cyan(), name.bold());
+        return execute(name, None, &command, &dir, &env, dry_run);
+    }
+
+    let mut repos: Vec<_> = config
+        .get_all_repositories()
+        .into_iter()
+        .filter(|(repo_name, _)| {
+            task.repos
+                .iter()
+                .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|p| p.matches(repo_name)))
+        })
+        .collect();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+
+    if repos.is_empty() {
+        println!("{} {}: no repositories match {}", "[!]".yellow(), name, task.repos.join(", "));
+        return Ok(());
+    }
+
+    for (repo_name, repo) in repos {
+        let dir = config.workspace_root.join(&repo.path);
+        if !dir.exists() {
+            println!("{} {} ({}): not cloned, skipping", "[!]".yellow(), name, repo_name);
+            continue;
+        }
+
+        println!("{} {} {}", "[>]".cyan(), name.bold(), format!("({})", repo_name).dimmed());
+        let mut repo_env = env.clone();
+        repo_env.insert("SYLA_REPO".to_string(), repo_name.clone());
+        repo_env.insert("SYLA_REPO_PATH".to_string(), dir.display().to_string());
+        execute(name, Some(&repo_name), &command, &dir, &repo_env, dry_run)?;
+    }
+    Ok(())
+}
+
+fn execute(
+    name: &str,
+    repo: Option<&str>,
+    command: &str,
+    dir: &PathBuf,
+    env: &HashMap<String, String>,
+    dry_run: bool,
+) -> Result<()> {
+    if dry_run {
+        println!("    {} {}", format!("[{}]", dir.display()).dimmed(), command);
+        return Ok(());
+    }
+
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    let status = cmd
+        .current_dir(dir)
+        .envs(env)
+        .status()
+        .with_context(|| format!("Failed to run task '{}'", name))?;
+
+    if !status.success() {
+        let code = status
+            .code()
+            .map_or_else(|| "a signal".to_string(), |c| format!("exit code {}", c));
+        match repo {
+            Some(repo) => anyhow::bail!("Task '{}' failed in {} with {}", name, repo, code),
+            None => anyhow::bail!("Task '{}' failed with {}", name, code),
+        }
+    }
+    Ok(())
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
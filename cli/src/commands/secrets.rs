@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use dialoguer::Password;
+use std::io::{IsTerminal, Read};
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::secrets::{SecretStore, SECRET_REF_PREFIX};
+use crate::SecretsCommands;
+
+pub async fn run(command: SecretsCommands, workspace_root: Option<PathBuf>) -> Result<()> {
+    match command {
+        SecretsCommands::Set { name, value } => {
+            let value = match value {
+                Some(value) => value,
+                None => read_value(&name)?,
+            };
+            let mut store = SecretStore::open()?;
+            store.set(&name, value)?;
+            say!("{} Secret {} saved", "[OK]".green(), name.bold());
+            println!(
+                "Reference it from repos.toml as {}",
+                format!("env.VAR = \"{}{}\"", SECRET_REF_PREFIX, name).cyan()
+            );
+        }
+        SecretsCommands::Get { name } => {
+            let store = SecretStore::open()?;
+            let value = store
+                .get(&name)
+                .with_context(|| format!("Secret '{}' is not set", name))?;
+            println!("{}", value);
+        }
+        SecretsCommands::List => list(workspace_root)?,
+    }
+    Ok(())
+}
+
+fn list(workspace_root: Option<PathBuf>) -> Result<()> {
+    let store = SecretStore::open()?;
+    // References are only shown from inside a workspace
+    let config = Config::load(workspace_root).ok();
+    let references = |name: &str| -> Vec<String> {
+        let Some(config) = &config else {
+            return Vec::new();
+        };
+        let reference = format!("{}{}", SECRET_REF_PREFIX, name);
+        let mut users: Vec<String> = config
+            .manifest
+            .repositories
+            .iter()
+            .flat_map(|(repo, repo_config)| {
+                repo_config
+                    .env
+                    .iter()
+                    .filter(|(_, value)| **value == reference)
+                    .map(move |(key, _)| format!("{}.{}", repo, key))
+            })
+            .collect();
+        users.sort();
+        users
+    };
+
+    println!("{}", "Secrets".bold());
+    println!();
+
+    let mut names = store.names().peekable();
+    if names.peek().is_none() {
+        println!("  No secrets set. Add one with {}", "syla secrets set <name>".cyan());
+        return Ok(());
+    }
+    for name in names {
+        let users = references(name);
+        if users.is_empty() {
+            println!("  {}", name);
+        } else {
+            println!("  {} {}", name, format!("(used by {})", users.join(", ")).dimmed());
+        }
+    }
+
+    // Flag references that would fail at startup
+    if let Some(config) = &config {
+        let mut missing: Vec<String> = config
+            .manifest
+            .repositories
+            .iter()
+            .flat_map(|(repo, repo_config)| {
+                repo_config.env.iter().filter_map(move |(key, value)| {
+                    value
+                        .strip_prefix(SECRET_REF_PREFIX)
+                        .map(|name| (format!("{}.{}", repo, key), name))
+                })
+            })
+            .filter(|(_, name)| store.get(name).is_none())
+            .map(|(user, name)| format!("{} (used by {})", name, user))
+            .collect();
+        missing.sort();
+        if !missing.is_empty() {
+            println!();
+            println!("{} Referenced but not set:", "[!]".yellow());
+            for entry in missing {
+                println!("  {}", entry);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prompt without echo, or read piped input so values stay out of shell history
+fn read_value(name: &str) -> Result<String> {
+    if std::io::stdin().is_terminal() {
+        return Ok(Password::new()
+            .with_prompt(format!("Value for {}", name))
+            .interact()?);
+    }
+
+    let mut value = String::new();
+    std::io::stdin().read_to_string(&mut value)?;
+    let value = value.trim_end_matches(['\r', '\n']).to_string();
+    if value.is_empty() {
+        anyhow::bail!("No value given for secret '{}'", name);
+    }
+    Ok(value)
+}
@@ -0,0 +1,451 @@
+//! `syla serve`: the CLI as a JSON-RPC 2.0 server, for editor extensions
+//! and dashboards.
+//!
+//! Clients connect to `ws://127.0.0.1:7777/` and exchange one JSON-RPC
+//! message (or batch) per text frame, or `POST` a single message to
+//! `http://127.0.0.1:7777/` for a one-off call. Services started through
+//! the server are supervised like under `dev supervise` and stop with it.
+//!
+//! ```text
+//! {"jsonrpc": "2.0", "id": 1, "method": "services.start", "params": {"service": "execution-service"}}
+//! {"jsonrpc": "2.0", "id": 1, "result": {"service": "syla.core.execution-service", "pid": 4242}}
+//! ```
+//!
+//! `logs.follow` only works over a WebSocket: new lines arrive as
+//! `logs.line` notifications until `logs.unfollow` or the connection closes.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::JoinHandle;
+
+use crate::commands::{exec, status};
+use crate::config::Config;
+use crate::control::{self, Supervisor};
+use crate::events;
+use crate::ports;
+use crate::websocket::{self, Message};
+
+/// Methods the server answers, for `server.info`
+const METHODS: &[&str] = &[
+    "server.info",
+    "workspace.status",
+    "services.list",
+    "services.start",
+    "services.stop",
+    "services.health",
+    "logs.tail",
+    "logs.follow",
+    "logs.unfollow",
+    "exec.submit",
+];
+
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The call was understood but failed, e.g. an unknown service
+const CALL_FAILED: i64 = -32000;
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError { code, message: message.into() }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(e: anyhow::Error) -> Self {
+        RpcError::new(CALL_FAILED, format!("{:#}", e))
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    /// Absent for notifications, which get no response
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct StatusParams {
+    tags: Vec<String>,
+    github: bool,
+}
+
+#[derive(Deserialize)]
+struct FollowParams {
+    service: String,
+}
+
+#[derive(Deserialize)]
+struct UnfollowParams {
+    subscription: u64,
+}
+
+#[derive(Deserialize)]
+struct SubmitParams {
+    language: String,
+    code: String,
+    timeout_seconds: Option<u64>,
+    tests: Option<Value>,
+    /// Return once the execution finished rather than when it was queued
+    #[serde(default = "default_wait")]
+    wait: bool,
+}
+
+fn default_wait() -> bool {
+    true
+}
+
+struct Server {
+    supervisor: Arc<Supervisor>,
+    /// Browser origins accepted besides localhost ones
+    allowed_origins: Vec<String>,
+}
+
+impl Server {
+    /// Requests without an `Origin` come from tools rather than browsers;
+    /// pages are only let in from localhost or `--allow-origin`, so a site
+    /// open in a browser can't drive the workspace
+    fn origin_allowed(&self, origin: Option<&str>) -> bool {
+        let Some(origin) = origin else {
+            return true;
+        };
+        if self.allowed_origins.iter().any(|allowed| allowed == origin) {
+            return true;
+        }
+        let Some(authority) = origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://")) else {
+            return false;
+        };
+        let host = match authority.rsplit_once(':') {
+            Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+            _ => authority,
+        };
+        matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+    }
+}
+
+/// One client: a WebSocket, whose notifications go to `notify`, or a
+/// single HTTP request, which can't take any
+struct Session {
+    server: Arc<Server>,
+    notify: Option<UnboundedSender<(u8, Vec<u8>)>>,
+    follows: Mutex<HashMap<u64, JoinHandle<()>>>,
+    next_subscription: AtomicU64,
+}
+
+impl Session {
+    fn new(server: Arc<Server>, notify: Option<UnboundedSender<(u8, Vec<u8>)>>) -> Arc<Self> {
+        Arc::new(Session { server, notify, follows: Mutex::new(HashMap::new()), next_subscription: AtomicU64::new(1) })
+    }
+
+    fn config(&self) -> &Config {
+        &self.server.supervisor.config
+    }
+
+    /// Answers a message or batch; `None` when it held only notifications
+    async fn handle(self: &Arc<Self>, text: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => return Some(response(Value::Null, Err(RpcError::new(PARSE_ERROR, format!("Parse error: {}", e))))),
+        };
+        match message {
+            Value::Array(batch) if batch.is_empty() => {
+                Some(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, "Empty batch"))))
+            }
+            Value::Array(batch) => {
+                let mut responses = Vec::new();
+                for message in batch {
+                    responses.extend(self.handle_one(message).await);
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            message => self.handle_one(message).await,
+        }
+    }
+
+    async fn handle_one(self: &Arc<Self>, message: Value) -> Option<Value> {
+        let request = match serde_json::from_value::<RpcRequest>(message) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            Ok(_) => return Some(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, "Only JSON-RPC 2.0 is supported")))),
+            Err(e) => return Some(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, format!("Invalid request: {}", e))))),
+        };
+        let outcome = self.call(&request.method, request.params).await;
+        if let Err(e) = &outcome {
+            tracing::debug!("{} failed: {}", request.method, e.message);
+        }
+        Some(response(request.id?, outcome))
+    }
+
+    async fn call(self: &Arc<Self>, method: &str, params: Value) -> Result<Value, RpcError> {
+        let control_command = match method {
+            "services.list" => Some("list"),
+            "services.start" => Some("start"),
+            "services.stop" => Some("stop"),
+            "services.health" => Some("health"),
+            "logs.tail" => Some("logs"),
+            _ => None,
+        };
+        if let Some(command) = control_command {
+            let mut params = match params {
+                Value::Null => json!({}),
+                Value::Object(params) => Value::Object(params),
+                _ => return Err(RpcError::new(INVALID_PARAMS, "Params must be an object")),
+            };
+            params["command"] = command.into();
+            let request: control::Request = parse(params)?;
+            return Ok(self.server.supervisor.dispatch(request, "serve").await?);
+        }
+
+        match method {
+            "server.info" => Ok(json!({
+                "name": "syla",
+                "version": env!("CARGO_PKG_VERSION"),
+                "workspace_root": self.config().workspace_root,
+                "methods": METHODS,
+            })),
+            "workspace.status" => {
+                let params: StatusParams = if params.is_null() { StatusParams::default() } else { parse(params)? };
+                let config = self.config();
+                config.check_tags(&params.tags)?;
+                let report = status::collect(config, false, params.github, &params.tags).await?;
+                Ok(serde_json::to_value(report).map_err(anyhow::Error::from)?)
+            }
+            "logs.follow" => self.follow(parse(params)?),
+            "logs.unfollow" => {
+                let UnfollowParams { subscription } = parse(params)?;
+                let follow = self.follows.lock().unwrap().remove(&subscription);
+                match follow {
+                    Some(follow) => {
+                        follow.abort();
+                        Ok(json!({ "subscription": subscription }))
+                    }
+                    None => Err(RpcError::new(CALL_FAILED, format!("No subscription {}", subscription))),
+                }
+            }
+            "exec.submit" => self.submit(parse(params)?).await,
+            method => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+
+    /// Sends lines appended to a service's log as `logs.line`
+    /// notifications, starting from its current end
+    fn follow(self: &Arc<Self>, params: FollowParams) -> Result<Value, RpcError> {
+        let Some(notify) = self.notify.clone() else {
+            return Err(RpcError::new(CALL_FAILED, "logs.follow needs a WebSocket connection"));
+        };
+        let (name, _) = self.config().find_repository(&params.service)?;
+        let path = crate::commands::dev::service_log_file(self.config(), &name);
+        let subscription = self.next_subscription.fetch_add(1, Ordering::Relaxed);
+
+        let mut offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let service = name.clone();
+        let follow = tokio::spawn(async move {
+            let mut partial = String::new();
+            loop {
+                tokio::time::sleep(LOG_POLL_INTERVAL).await;
+                let Ok(mut file) = std::fs::File::open(&path) else { continue };
+                let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+                if len < offset {
+                    // Truncated, e.g. by a restart
+                    offset = 0;
+                    partial.clear();
+                }
+                if len == offset || file.seek(SeekFrom::Start(offset)).is_err() {
+                    continue;
+                }
+                let mut appended = Vec::new();
+                if file.read_to_end(&mut appended).is_err() {
+                    continue;
+                }
+                offset += appended.len() as u64;
+                partial.push_str(&String::from_utf8_lossy(&appended));
+                while let Some(end) = partial.find('\n') {
+                    let line: String = partial.drain(..=end).collect();
+                    let notification = json!({
+                        "jsonrpc": "2.0",
+                        "method": "logs.line",
+                        "params": { "subscription": subscription, "service": service, "line": line.trim_end_matches(['\r', '\n']) },
+                    });
+                    if notify.send((websocket::OPCODE_TEXT, notification.to_string().into_bytes())).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        self.follows.lock().unwrap().insert(subscription, follow);
+        Ok(json!({ "subscription": subscription, "service": name }))
+    }
+
+    async fn submit(&self, params: SubmitParams) -> Result<Value, RpcError> {
+        let url = exec::execution_url(Some(self.config().workspace_root.clone()));
+        let url = url.trim_end_matches('/');
+        let mut request = json!({ "code": params.code, "language": params.language });
+        if let Some(timeout) = params.timeout_seconds {
+            request["timeout_seconds"] = timeout.into();
+        }
+        if let Some(tests) = params.tests {
+            request["tests"] = tests;
+        }
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("{}/executions", url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| RpcError::new(CALL_FAILED, format!("Failed to reach the execution service at {}: {}", url, e)))?;
+        let job = exec::checked(response).await?;
+        if !params.wait {
+            return Ok(job);
+        }
+        let job = exec::wait(&client, url, job).await?;
+        events::emit(events::Event::ExecutionCompleted {
+            id: job["id"].as_str(),
+            file: None,
+            status: job["status"].as_str().unwrap_or_default(),
+            exit_code: job["result"]["exit_code"].as_i64().map(|code| code as i32),
+        });
+        Ok(job)
+    }
+
+    fn close(&self) {
+        for (_, follow) in self.follows.lock().unwrap().drain() {
+            follow.abort();
+        }
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid params: {}", e)))
+}
+
+fn response(id: Value, outcome: Result<Value, RpcError>) -> Value {
+    match outcome {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": e.code, "message": e.message } }),
+    }
+}
+
+/// Serves until interrupted, then stops the services started through it
+pub async fn run(workspace_root: Option<PathBuf>, host: String, port: u16, allowed_origins: Vec<String>) -> Result<()> {
+    let config = ports::allocate(&Config::load(workspace_root)?)?;
+    let listener = TcpListener::bind((host.as_str(), port))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to listen on {}:{}: {}", host, port, e))?;
+    let address = listener.local_addr()?;
+    // Nobody is at the terminal to answer a picker for a client's request
+    crate::names::set_non_interactive(true);
+
+    let server = Arc::new(Server { supervisor: Supervisor::new(config), allowed_origins });
+    say!("{} JSON-RPC listening on ws://{}/ (or POST to http://{}/)", "[OK]".green(), address, address);
+    if !address.ip().is_loopback() {
+        say!("{} Anyone who can reach {} can start services and run code", "[!]".yellow(), address);
+    }
+    say!("Press Ctrl+C to stop; services started through it stop too");
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => accepted?.0,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let server = server.clone();
+        tokio::spawn(async move {
+            if let Err(e) = connection(server, stream).await {
+                tracing::debug!("Connection failed: {:#}", e);
+            }
+        });
+    }
+
+    say!("\nStopping services started through the server...");
+    server.supervisor.clone().shutdown().await
+}
+
+async fn connection(server: Arc<Server>, stream: TcpStream) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let Some(request) = websocket::read_request(&mut reader).await? else {
+        return Ok(());
+    };
+    if !server.origin_allowed(request.header("origin")) {
+        return websocket::write_response(&mut writer, 403, "Forbidden", "text/plain", b"Origin not allowed; see --allow-origin\n").await;
+    }
+    if request.path != "/" {
+        return websocket::write_response(&mut writer, 404, "Not Found", "text/plain", b"JSON-RPC is served at /\n").await;
+    }
+
+    if let Some(key) = request.websocket_key() {
+        websocket::accept(&mut writer, key).await?;
+        let (notify, mut outgoing) = tokio::sync::mpsc::unbounded_channel::<(u8, Vec<u8>)>();
+        let sender = tokio::spawn(async move {
+            while let Some((opcode, payload)) = outgoing.recv().await {
+                if websocket::write_frame(&mut writer, opcode, &payload).await.is_err() || opcode == websocket::OPCODE_CLOSE {
+                    break;
+                }
+            }
+        });
+
+        let session = Session::new(server, Some(notify.clone()));
+        let received = loop {
+            match websocket::read_message(&mut reader).await {
+                Ok(Some(Message::Text(text))) => {
+                    // Calls run side by side; a slow execution doesn't hold up the rest
+                    let (session, notify) = (session.clone(), notify.clone());
+                    tokio::spawn(async move {
+                        if let Some(reply) = session.handle(&text).await {
+                            let _ = notify.send((websocket::OPCODE_TEXT, reply.to_string().into_bytes()));
+                        }
+                    });
+                }
+                Ok(Some(Message::Ping(payload))) => {
+                    let _ = notify.send((websocket::OPCODE_PONG, payload));
+                }
+                Ok(Some(Message::Close)) => {
+                    let _ = notify.send((websocket::OPCODE_CLOSE, Vec::new()));
+                    break Ok(true);
+                }
+                Ok(None) => break Ok(false),
+                Err(e) => break Err(e),
+            }
+        };
+        session.close();
+        // Answering a close frame ends the sender; otherwise nobody is listening
+        if !matches!(received, Ok(true)) {
+            sender.abort();
+        }
+        return received.map(|_| ());
+    }
+
+    if request.method != "POST" {
+        return websocket::write_response(&mut writer, 405, "Method Not Allowed", "text/plain", b"POST a JSON-RPC message or upgrade to a WebSocket\n").await;
+    }
+    let session = Session::new(server, None);
+    let body = String::from_utf8_lossy(&request.body);
+    match session.handle(&body).await {
+        Some(reply) => websocket::write_response(&mut writer, 200, "OK", "application/json", reply.to_string().as_bytes()).await,
+        None => websocket::write_response(&mut writer, 204, "No Content", "application/json", b"").await,
+    }
+}
@@ -1,140 +1,743 @@
 use anyhow::Result;
 use colored::Colorize;
 use comfy_table::{Cell, Table};
-use std::path::PathBuf;
+use indicatif::ProgressStyle;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use serde::Serialize;
+use tracing::Instrument;
 
-use crate::config::Config;
+use crate::commands::OutputFormat;
+use crate::changes;
+use crate::config::{BuildProfile, Config, InfrastructureConfig, RepositoryConfig, RunIn, LOCAL_MANIFEST};
+use crate::docker::{self, ContainerSummary};
 use crate::git;
-use crate::docker;
+use crate::github::{self, BranchStatus, CiState, GitHub};
+use crate::services::state::{Restart, StartedService, StartedServices};
+use crate::tunnels;
+use crate::ui;
 
-pub async fn run(detailed: bool, workspace_root: Option<PathBuf>) -> Result<()> {
+/// Everything `syla status` shows, in the shape `--output` prints
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub workspace_root: PathBuf,
+    pub local_overrides: Vec<String>,
+    #[serde(skip)]
+    detailed: bool,
+    pub repositories: Vec<RepositoryStatus>,
+    /// The manifest's services and infrastructure, one entry each
+    pub services: Vec<ServiceStatus>,
+    /// Why containers show as unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docker_unavailable: Option<String>,
+    /// Why some repositories have no GitHub status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_unavailable: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepositoryStatus {
+    pub name: String,
+    pub path: String,
+    pub state: RepositoryState,
+    pub branch: Option<String>,
+    pub changed_files: usize,
+    /// Why the repository couldn't be checked, when `state` is `failed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Only with `--github`, for repositories hosted there
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github: Option<BranchStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepositoryState {
+    Clean,
+    Changed,
+    NotGit,
+    NotCloned,
+    /// Checking it failed, e.g. because the task panicked
+    Failed,
+}
+
+/// A service or piece of infrastructure, with what is actually running it:
+/// a process or container `dev up` started, a compose container, or
+/// whatever answers its health check
+#[derive(Debug, Serialize)]
+pub struct ServiceStatus {
+    pub name: String,
+    /// `service` for repositories, otherwise the infrastructure type
+    pub kind: String,
+    pub state: ServiceState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runs_as: Option<RunsAs>,
+    pub running: bool,
+    pub ports: Vec<String>,
+    /// `None` without a health check or when it couldn't be run
+    pub healthy: Option<bool>,
+    /// Set when the build is older than the service's sources
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale_build: Option<String>,
+    /// The supervisor's latest restarts of it, oldest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub restarts: Vec<Restart>,
+    #[serde(skip)]
+    has_check: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceState {
+    Running,
+    /// Was started, but its process or container has since exited
+    Exited,
+    Stopped,
+    /// A system dependency
+    Available,
+    /// Docker couldn't be asked
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RunsAs {
+    /// A process `dev up` started
+    Process { pid: u32 },
+    /// A container `dev up` started, for `run_in = "container"`
+    Container { name: String },
+    /// A container from the workspace's compose file
+    Compose { name: String },
+    /// Outside the workspace; only its health check says whether it's up
+    External,
+    /// Something syla didn't start answers the health check
+    Unmanaged,
+}
+
+impl fmt::Display for RunsAs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunsAs::Process { pid } => write!(f, "process {}", pid),
+            RunsAs::Container { name } => write!(f, "container {}", name),
+            RunsAs::Compose { name } => write!(f, "compose {}", name),
+            RunsAs::External => write!(f, "external"),
+            RunsAs::Unmanaged => write!(f, "not started by syla"),
+        }
+    }
+}
+
+pub async fn run(
+    detailed: bool,
+    tags: Vec<String>,
+    github: bool,
+    output: Option<OutputFormat>,
+    workspace_root: Option<PathBuf>,
+) -> Result<()> {
     let config = Config::load(workspace_root)?;
-    
-    println!("{}", "Workspace Status".bold());
-    println!("Root: {}\n", config.workspace_root.display());
+    config.check_tags(&tags)?;
+
+    if let Some(format) = output {
+        return format.print(&collect(&config, detailed, github, &tags).await?);
+    }
+
+    print_header(&config);
+    // The GitHub column needs every row before the table can be drawn
+    if github {
+        let report = collect(&config, detailed, github, &tags).await?;
+        print_repositories(&report, github);
+        print_rest(&config, &report);
+        return Ok(());
+    }
 
-    // Repository status
     println!("{}", "Repositories:".bold());
-    let mut table = Table::new();
-    table.set_header(vec!["Repository", "Path", "Branch", "Status"]);
+    let width = config.get_all_repositories().iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let report = collect_with(&config, detailed, github, &tags, &mut |repo| {
+        if let Some(line) = repository_line(repo, detailed, width) {
+            println!("{}", line);
+        }
+    })
+    .await?;
+    print_rest(&config, &report);
+    Ok(())
+}
 
-    let repos = config.get_all_repositories();
-    for (name, repo) in repos {
-        let repo_path = config.workspace_root.join(&repo.path);
-        
-        let (exists, branch, status) = if repo_path.exists() {
-            match git::status(&repo_path).await {
-                Ok(git_status) => {
-                    let status = if git_status.has_changes {
-                        format!("{} changes", git_status.changed_files).yellow().to_string()
-                    } else {
-                        "Clean".green().to_string()
-                    };
-                    (true, git_status.branch, status)
-                }
-                Err(_) => (true, "unknown".to_string(), "Not a git repo".red().to_string()),
-            }
-        } else {
-            (false, "-".to_string(), "Not cloned".red().to_string())
+/// Queries git, health checks and containers
+pub(crate) async fn collect(config: &Config, detailed: bool, github: bool, tags: &[String]) -> Result<StatusReport> {
+    collect_with(config, detailed, github, tags, &mut |_| {}).await
+}
+
+/// Like `collect`, handing each repository to `on_repo` as soon as git has
+/// answered for it
+async fn collect_with(
+    config: &Config,
+    detailed: bool,
+    github: bool,
+    tags: &[String],
+    on_repo: &mut (dyn FnMut(&RepositoryStatus) + Send),
+) -> Result<StatusReport> {
+    // Health checks run in the background while git is queried
+    let health = HealthChecks::start(service_checks(config, tags));
+
+    let mut repos: Vec<_> = config
+        .get_all_repositories()
+        .into_iter()
+        .filter(|(_, repo)| repo.has_any_tag(tags))
+        .collect();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut repositories = repository_statuses(config, &repos, on_repo).await;
+    let github_unavailable = if github {
+        github_statuses(config, &mut repositories).await
+    } else {
+        None
+    };
+
+    let health = health.results().await;
+    let (services, docker_unavailable) = services(config, tags, health).await?;
+
+    Ok(StatusReport {
+        workspace_root: config.workspace_root.clone(),
+        local_overrides: config.local_overrides.clone(),
+        detailed,
+        repositories,
+        services,
+        docker_unavailable,
+        github_unavailable,
+    })
+}
+
+/// Services shown with `tags`, and the infrastructure they depend on,
+/// joined with the processes and containers running them. Also returns
+/// why Docker couldn't be asked about containers, if it couldn't.
+pub(crate) async fn services(
+    config: &Config,
+    tags: &[String],
+    health: HashMap<String, Option<bool>>,
+) -> Result<(Vec<ServiceStatus>, Option<String>)> {
+    let started = StartedServices::load(&config.workspace_root)?;
+    let (containers, docker_unavailable) = workspace_containers(config, &started).await;
+    let compose = |names: &[&str]| {
+        containers.iter().find(|container| {
+            container.compose_service.as_deref().is_some_and(|service| names.contains(&service))
+                || names.contains(&container.name.as_str())
+        })
+    };
+    let profile = config.build_profile(None);
+
+    let mut repos: Vec<_> = config
+        .get_all_repositories()
+        .into_iter()
+        .filter(|(_, repo)| repo.has_any_tag(tags) && !repo.ports.is_empty())
+        .collect();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut services = Vec::new();
+    for (name, repo) in &repos {
+        let healthy = health.get(name).copied().flatten();
+        let directory = Path::new(&repo.path).file_name().and_then(|dir| dir.to_str()).unwrap_or(name);
+        let (state, runs_as) = match started.services.get(name) {
+            Some(service) => started_state(service, &containers, docker_unavailable.is_some()),
+            None => match compose(&[name, directory]) {
+                Some(container) => compose_state(container),
+                None if healthy == Some(true) => (ServiceState::Running, Some(RunsAs::Unmanaged)),
+                None => (ServiceState::Stopped, None),
+            },
         };
+        services.push(ServiceStatus {
+            name: name.clone(),
+            kind: "service".to_string(),
+            running: state == ServiceState::Running,
+            state,
+            runs_as,
+            ports: repo.ports.clone(),
+            healthy,
+            stale_build: stale_build(config, name, repo, profile),
+            restarts: started.services.get(name).map(|service| service.restarts.clone()).unwrap_or_default(),
+            has_check: health.contains_key(name),
+        });
+    }
+
+    // Only what the shown services use, when narrowed by tags
+    let mut infrastructure: Vec<(&String, &InfrastructureConfig)> = config
+        .manifest
+        .infrastructure
+        .iter()
+        .filter(|(name, _)| {
+            let dependency = format!("infrastructure.{}", name);
+            tags.is_empty() || repos.iter().any(|(_, repo)| repo.depends_on.contains(&dependency))
+        })
+        .collect();
+    infrastructure.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, infra) in infrastructure {
+        let key = format!("infrastructure.{}", name);
+        let healthy = health.get(&key).copied().flatten();
+        let (state, runs_as) = match (compose(&[name]), &infra.infra_type[..]) {
+            (Some(container), _) => compose_state(container),
+            (None, "docker") if docker_unavailable.is_some() => (ServiceState::Unknown, None),
+            (None, "docker") => (ServiceState::Stopped, None),
+            (None, "external") => match healthy {
+                Some(true) => (ServiceState::Running, Some(RunsAs::External)),
+                Some(false) => (ServiceState::Stopped, Some(RunsAs::External)),
+                None => (ServiceState::Unknown, Some(RunsAs::External)),
+            },
+            // TODO: Check system dependencies
+            (None, "system") => (ServiceState::Available, None),
+            (None, _) => (ServiceState::Unknown, None),
+        };
+        services.push(ServiceStatus {
+            name: name.clone(),
+            kind: infra.infra_type.clone(),
+            running: state == ServiceState::Running,
+            state,
+            runs_as,
+            ports: infra.ports.clone(),
+            healthy,
+            stale_build: None,
+            restarts: Vec::new(),
+            has_check: health.contains_key(&key),
+        });
+    }
+    Ok((services, docker_unavailable))
+}
 
-        if exists || detailed {
-            table.add_row(vec![
-                Cell::new(name),
-                Cell::new(&repo.path),
-                Cell::new(branch),
-                Cell::new(status),
-            ]);
+/// State of a service an earlier `dev up` started
+fn started_state(
+    service: &StartedService,
+    containers: &[ContainerSummary],
+    docker_unavailable: bool,
+) -> (ServiceState, Option<RunsAs>) {
+    let Some(name) = &service.container else {
+        let state = if service.is_running() { ServiceState::Running } else { ServiceState::Exited };
+        return (state, Some(RunsAs::Process { pid: service.pid }));
+    };
+    let state = match containers.iter().find(|container| container.name == *name) {
+        Some(container) if container.running => ServiceState::Running,
+        Some(_) => ServiceState::Exited,
+        None if docker_unavailable => ServiceState::Unknown,
+        None => ServiceState::Stopped,
+    };
+    (state, Some(RunsAs::Container { name: name.clone() }))
+}
+
+fn compose_state(container: &ContainerSummary) -> (ServiceState, Option<RunsAs>) {
+    let state = if container.running { ServiceState::Running } else { ServiceState::Exited };
+    (state, Some(RunsAs::Compose { name: container.name.clone() }))
+}
+
+/// Containers that may run part of the workspace: the ones from its
+/// compose file and any other on the daemon. Docker is only asked when
+/// something could be in a container. Also returns why it couldn't be
+/// asked, if it couldn't.
+async fn workspace_containers(config: &Config, started: &StartedServices) -> (Vec<ContainerSummary>, Option<String>) {
+    let uses_docker = config.workspace_root.join("docker-compose.yml").exists()
+        || config.manifest.infrastructure.values().any(|infra| infra.infra_type == "docker")
+        || config.manifest.repositories.values().any(|repo| repo.run_in == RunIn::Container)
+        || started.services.values().any(|service| service.container.is_some());
+    if !uses_docker {
+        return (Vec::new(), None);
+    }
+    if let Some(reason) = docker::unavailable().await {
+        return (Vec::new(), Some(reason));
+    }
+    let root = config.workspace_root.canonicalize().unwrap_or_else(|_| config.workspace_root.clone());
+    match docker::containers().await {
+        Ok(mut containers) => {
+            // Another project's compose services may share names with ours
+            containers.retain(|container| {
+                container.compose_dir.as_ref().is_none_or(|dir| dir.canonicalize().is_ok_and(|dir| dir == root))
+            });
+            (containers, None)
         }
+        Err(e) => (Vec::new(), Some(format!("{:#}", e))),
+    }
+}
+
+fn print_header(config: &Config) {
+    println!("{}", "Workspace Status".bold());
+    println!("Root: {}", config.workspace_root.display());
+    print_local_overrides(config);
+    println!();
+}
+
+fn print_repositories(report: &StatusReport, github: bool) {
+    println!("{}", "Repositories:".bold());
+    let mut table = Table::new();
+    let mut header = vec!["Repository", "Path", "Branch", "Status"];
+    if github {
+        header.push("Pull Request");
+    }
+    table.set_header(header);
+    for repo in &report.repositories {
+        let Some(status) = repo.status_text(report.detailed) else {
+            continue;
+        };
+        let mut row = vec![
+            Cell::new(&repo.name),
+            Cell::new(&repo.path),
+            Cell::new(repo.branch_text()),
+            Cell::new(status),
+        ];
+        if github {
+            row.push(Cell::new(github_cell(repo.github.as_ref())));
+        }
+        table.add_row(row);
     }
-    
     println!("{}", table);
+    if let Some(reason) = &report.github_unavailable {
+        println!("{} GitHub: {}", "[!]".yellow(), reason);
+    }
+}
 
-    // Service status
+/// Everything below the repositories
+fn print_rest(config: &Config, report: &StatusReport) {
     println!("\n{}", "Services:".bold());
-    let mut service_table = Table::new();
-    service_table.set_header(vec!["Service", "Status", "Port", "Health"]);
-
-    // Check Docker first
-    match docker::check_docker().await {
-        Ok(_) => {
-            // Check each service
-            for (name, repo) in config.get_all_repositories() {
-                if !repo.ports.is_empty() {
-                    let health = if let Some(health_check) = &repo.health_check {
-                        match check_health(health_check).await {
-                            Ok(true) => "Healthy".green().to_string(),
-                            Ok(false) => "Unhealthy".red().to_string(),
-                            Err(_) => "Unknown".yellow().to_string(),
-                        }
-                    } else {
-                        "-".dimmed().to_string()
-                    };
+    print_services(&report.services, report.docker_unavailable.as_deref());
+    tunnels::print_active(&config.workspace_root);
+}
 
-                    service_table.add_row(vec![
-                        Cell::new(name),
-                        Cell::new("Running"), // TODO: Actually check if running
-                        Cell::new(repo.ports.join(", ")),
-                        Cell::new(health),
-                    ]);
+/// The merged services table, with notes on stale builds
+pub(crate) fn print_services(services: &[ServiceStatus], docker_unavailable: Option<&str>) {
+    if services.is_empty() {
+        println!("{}", "No services configured".dimmed());
+        return;
+    }
+    if let Some(reason) = docker_unavailable {
+        docker::print_skipped("container status", reason);
+    }
+    // Only worth a column once something has been restarted
+    let restarted = services.iter().any(|service| !service.restarts.is_empty());
+    let mut table = Table::new();
+    let mut header = vec!["Service", "Status", "Runs As", "Port", "Health"];
+    if restarted {
+        header.push("Restarts");
+    }
+    table.set_header(header);
+    for service in services {
+        let state = match service.state {
+            ServiceState::Running => "Running".green(),
+            ServiceState::Exited => "Exited".red(),
+            ServiceState::Stopped => "Stopped".dimmed(),
+            ServiceState::Available => "Available".green(),
+            ServiceState::Unknown => "Unknown".yellow(),
+        };
+        let runs_as = match (&service.runs_as, service.kind.as_str()) {
+            (Some(runs_as), _) => runs_as.to_string(),
+            (None, "service") => "-".dimmed().to_string(),
+            (None, kind) => kind.dimmed().to_string(),
+        };
+        let health = match (service.has_check, service.healthy) {
+            (true, Some(true)) => "Healthy".green().to_string(),
+            (true, Some(false)) => "Unhealthy".red().to_string(),
+            (true, None) => "Unknown".yellow().to_string(),
+            (false, _) => "-".dimmed().to_string(),
+        };
+        let mut row = vec![
+            Cell::new(&service.name),
+            Cell::new(state.to_string()),
+            Cell::new(runs_as),
+            Cell::new(service.ports.join(", ")),
+            Cell::new(health),
+        ];
+        if restarted {
+            row.push(Cell::new(match service.restarts.last() {
+                Some(last) => format!(
+                    "{} (last {})",
+                    service.restarts.len(),
+                    last.at.with_timezone(&chrono::Local).format("%H:%M:%S")
+                ),
+                None => "-".dimmed().to_string(),
+            }));
+        }
+        table.add_row(row);
+    }
+    println!("{}", table);
+    for service in services {
+        if let Some(staleness) = &service.stale_build {
+            println!("{} {} is stale: {}", "[!]".yellow(), service.name, staleness);
+        }
+    }
+    if services.iter().any(|service| service.stale_build.is_some()) {
+        println!("{}", "Rebuild with `syla dev build-changed`".dimmed());
+    }
+}
+
+/// Only builds that exist and are older than their sources; missing ones
+/// are `dev validate`'s concern
+fn stale_build(config: &Config, name: &str, repo: &RepositoryConfig, profile: BuildProfile) -> Option<String> {
+    if !config.workspace_root.join(&repo.path).exists() {
+        return None;
+    }
+    let change = changes::detect_one(config, name, repo, profile, None).ok()??;
+    change.built.is_some().then(|| change.staleness())
+}
+
+/// `#12 passing`, or the branch's CI state when it has no open PR
+fn github_cell(status: Option<&BranchStatus>) -> String {
+    let Some(status) = status else {
+        return "-".dimmed().to_string();
+    };
+    let ci = match status.ci {
+        CiState::Passing => "passing".green(),
+        CiState::Failing => "failing".red(),
+        CiState::Pending => "pending".yellow(),
+        CiState::None => "no checks".dimmed(),
+    };
+    match &status.pull_request {
+        Some(pull) if pull.draft => format!("#{} draft, {}", pull.number, ci),
+        Some(pull) => format!("#{} {}", pull.number, ci),
+        None => format!("{}, {}", "no PR".dimmed(), ci),
+    }
+}
+
+/// Looks up the open PR and CI state of each repository's branch on
+/// GitHub, concurrently. Returns why some couldn't be looked up, if any.
+async fn github_statuses(config: &Config, repositories: &mut [RepositoryStatus]) -> Option<String> {
+    let client = match GitHub::new() {
+        Ok(client) => Arc::new(client),
+        Err(e) => return Some(format!("{:#}", e)),
+    };
+    let mut tasks = JoinSet::new();
+    for (index, repo) in repositories.iter().enumerate() {
+        let Some(remote) = github::repository(&config.manifest.repositories[&repo.name].url) else {
+            continue;
+        };
+        // `main...origin/main [ahead 1]` -> `main`; nothing for a detached HEAD
+        let Some(branch) = repo.branch.as_deref().and_then(|branch| branch.split("...").next()) else {
+            continue;
+        };
+        if branch.starts_with("HEAD") || branch.contains(' ') {
+            continue;
+        }
+        let (client, branch) = (client.clone(), branch.to_string());
+        tasks.spawn(async move { (index, client.branch_status(&remote, &branch).await) });
+    }
+
+    let mut unavailable = None;
+    while let Some(result) = tasks.join_next().await {
+        let Ok((index, status)) = result else {
+            continue;
+        };
+        match status {
+            Ok(status) => repositories[index].github = Some(status),
+            Err(e) => {
+                unavailable.get_or_insert_with(|| format!("{:#}", e));
+            }
+        }
+    }
+    if let Err(e) = client.save().await {
+        tracing::debug!("Failed to save the GitHub cache: {:#}", e);
+    }
+    if let Some(until) = client.rate_limited_until().await {
+        let until = until.with_timezone(&chrono::Local).format("%H:%M");
+        return Some(format!("rate limit reached until {}; showing cached results", until));
+    }
+    unavailable
+}
+
+/// `git status` calls in flight at once
+const MAX_CONCURRENT_GIT: usize = 8;
+
+/// `git status` of every repository, queried concurrently with a counter
+/// of how many have answered. Each row goes to `on_repo` as it arrives; the
+/// result is in the order of `repos`.
+async fn repository_statuses(
+    config: &Config,
+    repos: &[(String, &RepositoryConfig)],
+    on_repo: &mut (dyn FnMut(&RepositoryStatus) + Send),
+) -> Vec<RepositoryStatus> {
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_GIT));
+    let mut tasks = JoinSet::new();
+    let mut names = HashMap::new();
+    let mut statuses = HashMap::new();
+    for (name, repo) in repos {
+        let path = config.workspace_root.join(&repo.path);
+        if !path.exists() {
+            statuses.insert(name.clone(), RepositoryStatus::new(name, repo, RepositoryState::NotCloned));
+            continue;
+        }
+        let permits = permits.clone();
+        let span = tracing::info_span!("git_status", repo = %name);
+        let task = tasks.spawn(
+            async move {
+                let _permit = permits.acquire_owned().await;
+                git::status(&path).await
+            }
+            .instrument(span),
+        );
+        names.insert(task.id(), (name.clone(), *repo));
+    }
+    for status in statuses.values() {
+        on_repo(status);
+    }
+
+    let progress = ui::progress_bar(tasks.len() as u64);
+    progress.set_style(ProgressStyle::default_spinner().template("{spinner} Checking repositories {pos}/{len}").unwrap());
+    while let Some(result) = tasks.join_next_with_id().await {
+        let id = match &result {
+            Ok((id, _)) => *id,
+            Err(e) => e.id(),
+        };
+        let Some((name, repo)) = names.remove(&id) else {
+            continue;
+        };
+        let status = match result {
+            Ok((_, Ok(git_status))) => {
+                let state = if git_status.has_changes { RepositoryState::Changed } else { RepositoryState::Clean };
+                RepositoryStatus {
+                    branch: Some(git_status.branch),
+                    changed_files: git_status.changed_files,
+                    ..RepositoryStatus::new(&name, repo, state)
                 }
             }
+            Ok((_, Err(_))) => RepositoryStatus::new(&name, repo, RepositoryState::NotGit),
+            Err(e) => RepositoryStatus {
+                error: Some(e.to_string()),
+                ..RepositoryStatus::new(&name, repo, RepositoryState::Failed)
+            },
+        };
+        progress.suspend(|| on_repo(&status));
+        statuses.insert(name, status);
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+    repos.iter().filter_map(|(name, _)| statuses.remove(name)).collect()
+}
+
+impl RepositoryStatus {
+    fn new(name: &str, repo: &RepositoryConfig, state: RepositoryState) -> Self {
+        RepositoryStatus {
+            name: name.to_string(),
+            path: repo.path.clone(),
+            state,
+            branch: None,
+            changed_files: 0,
+            error: None,
+            github: None,
         }
-        Err(e) => {
-            println!("{} Docker not available: {}", "Warning:".yellow(), e);
+    }
+
+    /// Colored status cell; `None` for rows only shown with `--detailed`
+    fn status_text(&self, detailed: bool) -> Option<String> {
+        Some(match self.state {
+            RepositoryState::Clean => "Clean".green().to_string(),
+            RepositoryState::Changed => format!("{} changes", self.changed_files).yellow().to_string(),
+            RepositoryState::NotGit => "Not a git repo".red().to_string(),
+            RepositoryState::NotCloned if detailed => "Not cloned".red().to_string(),
+            RepositoryState::NotCloned => return None,
+            RepositoryState::Failed => {
+                format!("Failed: {}", self.error.as_deref().unwrap_or("unknown error")).red().to_string()
+            }
+        })
+    }
+
+    fn branch_text(&self) -> String {
+        match (&self.branch, self.state) {
+            (Some(branch), _) => branch.clone(),
+            (None, RepositoryState::NotGit | RepositoryState::Failed) => "unknown".to_string(),
+            (None, _) => "-".to_string(),
         }
     }
+}
+
+/// One streamed row, with names padded to `width`
+fn repository_line(repo: &RepositoryStatus, detailed: bool, width: usize) -> Option<String> {
+    let status = repo.status_text(detailed)?;
+    Some(format!(
+        "  {} {} {} ({})",
+        format!("{:<width$}", repo.name, width = width).bold(),
+        repo.path.dimmed(),
+        repo.branch_text(),
+        status
+    ))
+}
+
+/// How long one health check may take before the service counts as unhealthy
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
 
-    let has_services = config.get_all_repositories()
+/// Health checks in flight at once
+const MAX_CONCURRENT_CHECKS: usize = 16;
+
+/// Health checks of the services shown with `tags`, and of external
+/// infrastructure as `infrastructure.<name>`
+pub(crate) fn service_checks(config: &Config, tags: &[String]) -> Vec<(String, String)> {
+    let services = config
+        .get_all_repositories()
+        .into_iter()
+        .filter(|(_, repo)| repo.has_any_tag(tags) && !repo.ports.is_empty())
+        .filter_map(|(name, repo)| Some((name, repo.health_check.clone()?)));
+    let infrastructure = config
+        .manifest
+        .infrastructure
         .iter()
-        .any(|(_, repo)| !repo.ports.is_empty());
-        
-    if has_services {
-        println!("{}", service_table);
-    } else {
-        println!("{}", "No services configured".dimmed());
-    }
+        .filter(|(_, infra)| infra.infra_type == "external")
+        .filter_map(|(name, infra)| Some((format!("infrastructure.{}", name), infra.health_check.clone()?)));
+    services.chain(infrastructure).collect()
+}
 
-    // Infrastructure status
-    if detailed {
-        println!("\n{}", "Infrastructure:".bold());
-        let mut infra_table = Table::new();
-        infra_table.set_header(vec!["Component", "Type", "Status"]);
-
-        for (name, infra) in &config.manifest.infrastructure {
-            let status = match &infra.infra_type[..] {
-                "external" => {
-                    if let Some(health_check) = &infra.health_check {
-                        match check_health(health_check).await {
-                            Ok(true) => "Running".green().to_string(),
-                            Ok(false) => "Stopped".red().to_string(),
-                            Err(_) => "Unknown".yellow().to_string(),
-                        }
-                    } else {
-                        "Unknown".yellow().to_string()
-                    }
-                }
-                "system" => {
-                    // TODO: Check system dependencies
-                    "Available".green().to_string()
+/// Health checks running concurrently, so one slow service doesn't hold up
+/// the rest
+pub(crate) struct HealthChecks {
+    tasks: JoinSet<(String, Option<bool>, Duration)>,
+}
+
+impl HealthChecks {
+    pub(crate) fn start(checks: impl IntoIterator<Item = (String, String)>) -> Self {
+        let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_CHECKS));
+        let mut tasks = JoinSet::new();
+        for (name, health_check) in checks {
+            let permits = permits.clone();
+            let span = tracing::info_span!("health_check", service = %name);
+            tasks.spawn(
+                async move {
+                    let _permit = permits.acquire_owned().await;
+                    let started = std::time::Instant::now();
+                    let healthy = match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, check_health(&health_check)).await {
+                        Ok(Ok(healthy)) => Some(healthy),
+                        Ok(Err(_)) => None,
+                        Err(_) => Some(false),
+                    };
+                    (name, healthy, started.elapsed())
                 }
-                _ => "Unknown".yellow().to_string(),
-            };
-
-            infra_table.add_row(vec![
-                Cell::new(name),
-                Cell::new(&infra.infra_type),
-                Cell::new(status),
-            ]);
+                .instrument(span),
+            );
         }
+        Self { tasks }
+    }
 
-        println!("{}", infra_table);
+    /// Whether each check passed; `None` when it couldn't be run
+    pub(crate) async fn results(self) -> HashMap<String, Option<bool>> {
+        self.timed().await.into_iter().map(|(name, healthy, _)| (name, healthy)).collect()
     }
 
-    Ok(())
+    /// Like `results`, with how long each check took
+    pub(crate) async fn timed(mut self) -> Vec<(String, Option<bool>, Duration)> {
+        let mut results = Vec::new();
+        while let Some(result) = self.tasks.join_next().await {
+            if let Ok(result) = result {
+                results.push(result);
+            }
+        }
+        results
+    }
+}
+
+/// Flags `repos.local.toml` being in effect, so nobody is surprised their
+/// workspace behaves differently from everyone else's
+pub(crate) fn print_local_overrides(config: &Config) {
+    if !config.local_overrides.is_empty() {
+        println!(
+            "{} Local overrides active ({}): {}",
+            "[!]".yellow(),
+            LOCAL_MANIFEST,
+            config.local_overrides.join(", ")
+        );
+    }
 }
 
-async fn check_health(health_check: &str) -> Result<bool> {
+pub(crate) async fn check_health(health_check: &str) -> Result<bool> {
     if health_check.starts_with("http://") || health_check.starts_with("https://") {
         // HTTP health check
         match reqwest::get(health_check).await {
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::telemetry::{self, Settings};
+use crate::TelemetryCommands;
+
+pub async fn run(command: TelemetryCommands) -> Result<()> {
+    match command {
+        TelemetryCommands::Status => status(),
+        TelemetryCommands::Enable { endpoint } => {
+            let settings = telemetry::enable(endpoint)?;
+            say!("{} Anonymous usage metrics enabled", "[OK]".green());
+            println!();
+            println!("Recorded per command: name (no arguments), duration, success, failure category,");
+            println!("CLI version and OS. Disable at any time with {}.", "syla telemetry disable".cyan());
+            if settings.upload_endpoint().is_none() {
+                println!();
+                println!(
+                    "{} No upload endpoint configured; events are only kept locally",
+                    "[!]".yellow()
+                );
+            }
+            Ok(())
+        }
+        TelemetryCommands::Disable => {
+            telemetry::disable()?;
+            say!("{} Usage metrics disabled and pending events deleted", "[OK]".green());
+            Ok(())
+        }
+    }
+}
+
+fn status() -> Result<()> {
+    let settings = Settings::load();
+    let enabled = telemetry::is_enabled(&settings);
+
+    println!("{}", "Telemetry".bold());
+    println!();
+    let state = if enabled {
+        "enabled".green()
+    } else if settings.enabled {
+        "disabled by environment".yellow()
+    } else {
+        "disabled".dimmed()
+    };
+    println!("  Status:         {}", state);
+
+    if settings.enabled {
+        if let Some(id) = settings.install_id {
+            println!("  Install ID:     {}", id);
+        }
+        println!(
+            "  Endpoint:       {}",
+            settings.upload_endpoint().unwrap_or_else(|| "(none, local only)".to_string())
+        );
+        println!("  Pending events: {}", telemetry::pending_events().len());
+        match settings.last_upload {
+            Some(at) => println!("  Last upload:    {}", at.format("%Y-%m-%d %H:%M UTC")),
+            None => println!("  Last upload:    never"),
+        }
+    } else {
+        println!();
+        println!("Help improve syla by running {}", "syla telemetry enable".cyan());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::PathBuf;
+
+use crate::changes;
+use crate::commands::{db, dev};
+use crate::config::{BuildProfile, Config};
+use crate::git;
+use crate::lock::WorkspaceLock;
+use crate::services::state::StartedServices;
+use crate::ui;
+
+/// Catches the workspace up with upstream: pulls every repository, rebuilds
+/// the services that changed, runs their pending migrations and restarts
+/// the running ones that were affected, dependencies first
+pub async fn run(
+    profile: Option<BuildProfile>,
+    tags: Vec<String>,
+    strategy: SyncStrategy,
+    workspace_root: Option<PathBuf>,
+) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    config.check_tags(&tags)?;
+    let profile = config.build_profile(profile);
+    let _lock = WorkspaceLock::acquire(&config.workspace_root, "upgrade").await?;
+
+    let steps = ui::Steps::new(4);
+    steps.start(1, "Pull");
+    let (updated, notes) = pull(&config, &steps, &tags, strategy).await?;
+
+    steps.start(2, "Build");
+    let mut changed: Vec<_> = changes::detect(&config, profile, None)?
+        .into_iter()
+        .filter(|service| config.manifest.repositories[&service.name].has_any_tag(&tags))
+        .collect();
+    if changed.is_empty() {
+        say!("  {}", "Nothing to build".dimmed());
+    }
+    let mut failed = Vec::new();
+    changed.retain(|service| {
+        let item = steps.item(&service.name, "Building");
+        match steps.suspend(|| dev::build_service(&config, service, profile)) {
+            Ok(true) => {
+                item.ok("built");
+                true
+            }
+            Ok(false) => {
+                item.fail("build failed");
+                failed.push(service.name.clone());
+                false
+            }
+            Err(e) => {
+                item.fail(&format!("{:#}", e));
+                failed.push(service.name.clone());
+                false
+            }
+        }
+    });
+
+    steps.start(3, "Migrate");
+    let migrated = if updated.is_empty() {
+        0
+    } else {
+        db::migrate_repositories(&config, &updated).await?
+    };
+    if migrated == 0 {
+        say!("  {}", "No migrations to run".dimmed());
+    }
+
+    // Services whose code changed, whether through a rebuild or, for
+    // interpreted ones, the pull itself; a failed build keeps the old binary
+    // running
+    steps.start(4, "Restart");
+    let started = StartedServices::load(&config.workspace_root)?;
+    let order = config.dependency_order()?;
+    let restart: Vec<&str> = order
+        .iter()
+        .filter(|name| started.services.get(*name).is_some_and(|service| service.is_running()))
+        .filter(|name| !failed.contains(name))
+        .filter(|name| updated.contains(name) || changed.iter().any(|service| &service.name == *name))
+        .map(String::as_str)
+        .collect();
+    if restart.is_empty() {
+        say!("  {}", "No running service needs a restart".dimmed());
+    }
+    dev::restart_services(&config, &restart, profile)?;
+
+    print_notes(&notes);
+    if !failed.is_empty() {
+        anyhow::bail!("Build failed: {}", failed.join(", "));
+    }
+    say!(
+        "\n{} Workspace is up to date ({} pulled, {} rebuilt, {} restarted)",
+        "[OK]".green().bold(),
+        updated.len(),
+        changed.len(),
+        restart.len()
+    );
+    Ok(())
+}
+
+/// What `upgrade` does with a repository that has local changes or has
+/// diverged from upstream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SyncStrategy {
+    /// Stash local changes, rebase onto upstream, then re-apply them
+    StashRebase,
+    /// Rebase local commits onto upstream; skip repositories with local changes
+    Rebase,
+    /// Only fast-forward; leave anything else alone
+    Skip,
+    /// Stop at the first repository that can't be fast-forwarded
+    Fail,
+}
+
+/// A repository that wasn't simply fast-forwarded, for the report at the end
+struct SyncNote {
+    name: String,
+    note: String,
+}
+
+/// Brings every cloned repository up to date with its upstream using
+/// `strategy`, returning the ones that moved and notes on the ones that
+/// needed more than a fast-forward
+async fn pull(
+    config: &Config,
+    steps: &ui::Steps,
+    tags: &[String],
+    strategy: SyncStrategy,
+) -> Result<(Vec<String>, Vec<SyncNote>)> {
+    let mut repos = config.get_all_repositories();
+    repos.retain(|(name, repo)| config.is_enabled(name, repo) && repo.has_any_tag(tags));
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut updated = Vec::new();
+    let mut notes = Vec::new();
+    for (name, repo) in repos {
+        let path = config.workspace_root.join(&repo.path);
+        if !path.join(".git").exists() {
+            continue;
+        }
+        let item = steps.item(&name, "Pulling");
+        let mut note = |note: String| notes.push(SyncNote { name: name.clone(), note });
+
+        let dirty = git::status(&path).await.is_ok_and(|status| status.has_changes);
+        let stashed = match (dirty, strategy) {
+            (false, _) => false,
+            (true, SyncStrategy::Fail) => {
+                item.fail("uncommitted changes");
+                anyhow::bail!("{} has uncommitted changes; commit or stash them, or pick another --strategy", name);
+            }
+            (true, SyncStrategy::Skip | SyncStrategy::Rebase) => {
+                item.warn("skipped: uncommitted changes");
+                note("skipped: uncommitted changes".to_string());
+                continue;
+            }
+            (true, SyncStrategy::StashRebase) => {
+                if let Err(e) = git::stash_push(&path, "syla upgrade").await {
+                    item.warn(&format!("skipped: {}", first_line(&e)));
+                    note(format!("skipped: could not stash ({})", first_line(&e)));
+                    continue;
+                }
+                true
+            }
+        };
+
+        let before = git::head(&path).await.ok();
+        let pulled = match strategy {
+            SyncStrategy::StashRebase | SyncStrategy::Rebase => git::pull_rebase(&path).await,
+            SyncStrategy::Skip | SyncStrategy::Fail => git::pull(&path).await,
+        };
+        let restored = if stashed { Some(git::stash_pop(&path).await) } else { None };
+        match restored {
+            Some(Ok(())) => note("local changes stashed and re-applied".to_string()),
+            Some(Err(_)) => note("local changes conflict with upstream; kept in `git stash list`".to_string()),
+            None => {}
+        }
+
+        if let Err(e) = pulled {
+            let reason = if e.to_string().contains("fast-forward") {
+                "diverged from upstream".to_string()
+            } else {
+                first_line(&e)
+            };
+            if strategy == SyncStrategy::Fail {
+                item.fail(&reason);
+                return Err(e.context(format!("Failed to pull {}", name)));
+            }
+            item.warn(&format!("skipped: {}", reason));
+            note(format!("skipped: {}", reason));
+            continue;
+        }
+        let after = git::head(&path).await.ok();
+        if before == after {
+            item.ok("up to date");
+        } else {
+            item.ok("updated");
+            updated.push(name);
+        }
+    }
+    Ok((updated, notes))
+}
+
+/// The gist of a git error: its first line after git's own prefix
+fn first_line(error: &anyhow::Error) -> String {
+    let message = error.to_string();
+    let line = message.lines().find(|line| !line.trim().is_empty()).unwrap_or_default();
+    line.split_once(": ").map_or(line, |(_, rest)| rest).trim().to_string()
+}
+
+fn print_notes(notes: &[SyncNote]) {
+    if notes.is_empty() || ui::is_quiet() {
+        return;
+    }
+    println!("\n{}", "Sync report".bold());
+    let width = notes.iter().map(|note| note.name.len()).max().unwrap_or(0);
+    for note in notes {
+        let mark = if note.note.starts_with("skipped") || note.note.contains("conflict") {
+            "[!]".yellow()
+        } else {
+            "[OK]".green()
+        };
+        println!("  {} {:width$}  {}", mark, note.name, note.note, width = width);
+    }
+}
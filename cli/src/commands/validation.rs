@@ -0,0 +1,169 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Area a `dev validate` check belongs to. Each maps to one bit of the
+/// `--ci` exit code so pipelines can tell failures apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckCategory {
+    Repositories,
+    Docker,
+    Builds,
+    Integration,
+}
+
+impl CheckCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            CheckCategory::Repositories => "repositories",
+            CheckCategory::Docker => "docker",
+            CheckCategory::Builds => "builds",
+            CheckCategory::Integration => "integration",
+        }
+    }
+
+    /// Exit code bit; 1 stays reserved for errors running validation itself
+    pub fn exit_bit(self) -> i32 {
+        match self {
+            CheckCategory::Repositories => 2,
+            CheckCategory::Docker => 4,
+            CheckCategory::Builds => 8,
+            CheckCategory::Integration => 16,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub category: CheckCategory,
+    pub name: String,
+    /// Why the check failed; `None` when it passed
+    pub failure: Option<String>,
+    /// File the failure is about, for annotations
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub checks: Vec<Check>,
+}
+
+impl ValidationReport {
+    pub fn pass(&mut self, category: CheckCategory, name: impl Into<String>) {
+        self.checks.push(Check {
+            category,
+            name: name.into(),
+            failure: None,
+            file: None,
+        });
+    }
+
+    pub fn fail(&mut self, category: CheckCategory, name: impl Into<String>, failure: impl Into<String>) {
+        self.checks.push(Check {
+            category,
+            name: name.into(),
+            failure: Some(failure.into()),
+            file: None,
+        });
+    }
+
+    /// Attach a file to the most recent check
+    pub fn with_file(&mut self, file: impl Into<String>) {
+        if let Some(check) = self.checks.last_mut() {
+            check.file = Some(file.into());
+        }
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|c| c.failure.is_some())
+    }
+
+    /// OR of the exit bits of every category with a failure; 0 when clean
+    pub fn exit_code(&self) -> i32 {
+        self.failures().fold(0, |code, check| code | check.category.exit_bit())
+    }
+
+    /// `::error` workflow commands, shown inline by GitHub Actions
+    pub fn github_annotations(&self) -> Vec<String> {
+        self.failures()
+            .map(|check| {
+                let file = check
+                    .file
+                    .as_deref()
+                    .map(|f| format!("file={},", escape_annotation_property(f)))
+                    .unwrap_or_default();
+                format!(
+                    "::error {}title={}::{}",
+                    file,
+                    escape_annotation_property(&format!("{}: {}", check.category.name(), check.name)),
+                    escape_annotation_data(check.failure.as_deref().unwrap_or_default())
+                )
+            })
+            .collect()
+    }
+
+    pub fn to_junit(&self) -> String {
+        let mut categories: Vec<CheckCategory> = self.checks.iter().map(|c| c.category).collect();
+        categories.sort();
+        categories.dedup();
+
+        let total_failures = self.failures().count();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"syla dev validate\" tests=\"{}\" failures=\"{}\">\n",
+            self.checks.len(),
+            total_failures
+        ));
+        for category in categories {
+            let checks: Vec<&Check> = self.checks.iter().filter(|c| c.category == category).collect();
+            let failures = checks.iter().filter(|c| c.failure.is_some()).count();
+            xml.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+                category.name(),
+                checks.len(),
+                failures
+            ));
+            for check in checks {
+                let case = format!(
+                    "    <testcase classname=\"syla.validate.{}\" name=\"{}\"",
+                    category.name(),
+                    escape_xml(&check.name)
+                );
+                match &check.failure {
+                    Some(failure) => xml.push_str(&format!(
+                        "{}>\n      <failure message=\"{}\"/>\n    </testcase>\n",
+                        case,
+                        escape_xml(failure)
+                    )),
+                    None => xml.push_str(&format!("{}/>\n", case)),
+                }
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    pub fn write_junit(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_junit())
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn escape_annotation_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_annotation_property(text: &str) -> String {
+    escape_annotation_data(text).replace(':', "%3A").replace(',', "%2C")
+}
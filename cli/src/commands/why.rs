@@ -0,0 +1,190 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::{BTreeSet, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::commands::status::check_health;
+use crate::config::Config;
+
+const INFRA_PREFIX: &str = "infrastructure.";
+
+pub async fn run(service: String, workspace_root: Option<PathBuf>) -> Result<()> {
+    let config = Config::load(workspace_root)?;
+    let node = resolve(&config, &service)?;
+
+    println!("{}", node.bold());
+    if let Some(repo) = config.manifest.repositories.get(&node) {
+        if let Some(description) = &repo.description {
+            println!("  {}", description);
+        }
+        println!("  {} {}", "Path:".dimmed(), repo.path);
+        println!("  {} {}", "Platform:".dimmed(), repo.platform.as_deref().unwrap_or("-"));
+        if !repo.ports.is_empty() {
+            println!("  {} {}", "Ports:".dimmed(), repo.ports.join(", "));
+        }
+    } else if let Some(infra) = infra_name(&node).and_then(|n| config.manifest.infrastructure.get(n)) {
+        let image = infra.docker_image.as_deref().unwrap_or("-");
+        println!("  {} {} ({})", "Infrastructure:".dimmed(), infra.infra_type, image);
+    }
+
+    // What it needs
+    println!("\n{}", "Depends on:".bold());
+    let dependencies = dependencies(&config, &node);
+    if dependencies.is_empty() {
+        println!("  {}", "nothing".dimmed());
+    } else {
+        let mut seen = HashSet::new();
+        for dependency in dependencies {
+            print_dependency_tree(&config, &dependency, 1, &mut seen);
+        }
+    }
+
+    // What needs it, directly or through other services
+    println!("\n{}", "Required by:".bold());
+    let dependents = transitive_dependents(&config, &node);
+    let mut running = Vec::new();
+    if dependents.is_empty() {
+        println!("  {}", "nothing".dimmed());
+    } else {
+        for (dependent, direct) in &dependents {
+            let state = match liveness(&config, dependent).await {
+                Some(true) => {
+                    running.push(dependent.clone());
+                    "running".green().to_string()
+                }
+                Some(false) => "not running".dimmed().to_string(),
+                None => "unknown".yellow().to_string(),
+            };
+            let via = if *direct { "" } else { " (indirectly)" };
+            println!("  {} {}{} [{}]", "*".cyan(), dependent, via.dimmed(), state);
+        }
+    }
+
+    // Templates and the compose profiles they turn on
+    if let Some(repo) = config.manifest.repositories.get(&node) {
+        let templates: Vec<_> = config
+            .manifest
+            .templates
+            .iter()
+            .filter(|(_, template)| template.includes(&node, repo))
+            .collect();
+        if !templates.is_empty() {
+            println!("\n{}", "Included in templates:".bold());
+            for (name, template) in templates {
+                let current = if config.settings.template.as_deref() == Some(name.as_str()) {
+                    " (this workspace)".green().to_string()
+                } else {
+                    String::new()
+                };
+                let profiles = if template.compose_profiles.is_empty() {
+                    String::new()
+                } else {
+                    format!(" profiles: {}", template.compose_profiles.join(", "))
+                        .dimmed()
+                        .to_string()
+                };
+                println!("  {} {}{}{}", "*".cyan(), name, profiles, current);
+            }
+        }
+    }
+
+    println!();
+    if running.is_empty() {
+        println!("{} Nothing running depends on {}", "[OK]".green(), node);
+    } else {
+        println!(
+            "{} Stopping {} would affect running services: {}",
+            "[!]".yellow(),
+            node,
+            running.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Find the repository or infrastructure a name refers to
+pub(crate) fn resolve(config: &Config, service: &str) -> Result<String> {
+    if config.manifest.repositories.contains_key(service) {
+        return Ok(service.to_string());
+    }
+    let infra = service.strip_prefix(INFRA_PREFIX).unwrap_or(service);
+    if config.manifest.infrastructure.contains_key(infra) {
+        return Ok(format!("{}{}", INFRA_PREFIX, infra));
+    }
+    Ok(config.find_repository(service)?.0)
+}
+
+fn infra_name(node: &str) -> Option<&str> {
+    node.strip_prefix(INFRA_PREFIX)
+}
+
+fn dependencies(config: &Config, node: &str) -> Vec<String> {
+    config
+        .manifest
+        .repositories
+        .get(node)
+        .map(|repo| repo.depends_on.clone())
+        .unwrap_or_default()
+}
+
+fn print_dependency_tree(config: &Config, node: &str, depth: usize, seen: &mut HashSet<String>) {
+    let indent = "  ".repeat(depth);
+    let known = config.manifest.repositories.contains_key(node)
+        || infra_name(node).is_some_and(|n| config.manifest.infrastructure.contains_key(n));
+    let label = if known {
+        node.to_string()
+    } else {
+        format!("{} {}", node, "(not in manifest)".red())
+    };
+
+    if !seen.insert(node.to_string()) {
+        println!("{}{} {} {}", indent, "-".dimmed(), label, "(see above)".dimmed());
+        return;
+    }
+    println!("{}{} {}", indent, "-".dimmed(), label);
+    for dependency in dependencies(config, node) {
+        print_dependency_tree(config, &dependency, depth + 1, seen);
+    }
+}
+
+/// Everything that needs `node`, sorted, flagged with whether it depends
+/// on it directly
+fn transitive_dependents(config: &Config, node: &str) -> Vec<(String, bool)> {
+    let direct: BTreeSet<String> = direct_dependents(config, node).into_iter().collect();
+    let mut all = BTreeSet::new();
+    let mut queue: Vec<String> = direct.iter().cloned().collect();
+    while let Some(current) = queue.pop() {
+        if current == node || !all.insert(current.clone()) {
+            continue;
+        }
+        queue.extend(direct_dependents(config, &current));
+    }
+
+    all.into_iter()
+        .map(|name| {
+            let is_direct = direct.contains(&name);
+            (name, is_direct)
+        })
+        .collect()
+}
+
+fn direct_dependents(config: &Config, node: &str) -> Vec<String> {
+    config
+        .manifest
+        .repositories
+        .iter()
+        .filter(|(_, repo)| repo.depends_on.iter().any(|d| d == node))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Whether a service answers its health check; `None` when it has none
+async fn liveness(config: &Config, name: &str) -> Option<bool> {
+    let url = config.manifest.repositories.get(name)?.health_check.as_ref()?;
+    match tokio::time::timeout(Duration::from_secs(2), check_health(url)).await {
+        Ok(Ok(healthy)) => Some(healthy),
+        _ => Some(false),
+    }
+}
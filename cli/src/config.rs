@@ -1,14 +1,478 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use crate::interpolation;
+use crate::names;
+use crate::notifications::NotificationSettings;
+use crate::ports;
+use crate::services::log_sink::LogSettings;
+use crate::services::process_manager::RestartPolicy;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepoManifest {
+    /// More manifest files merged into this one, relative to it; glob
+    /// patterns allowed. An entry may only be defined in one file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// URL of a TOML document with more `[templates.*]`, consulted for
+    /// templates not defined here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_registry: Option<String>,
+    /// Import paths shared by every repository's protos, relative to the
+    /// workspace root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proto_includes: Vec<String>,
+    /// Profile services are built with when `--profile` isn't given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_profile: Option<BuildProfile>,
     #[serde(default)]
     pub repositories: HashMap<String, RepositoryConfig>,
     #[serde(default)]
     pub infrastructure: HashMap<String, InfrastructureConfig>,
+    #[serde(default)]
+    pub templates: BTreeMap<String, WorkspaceTemplate>,
+    #[serde(default)]
+    pub tasks: BTreeMap<String, TaskConfig>,
+    #[serde(default)]
+    pub contracts: BTreeMap<String, ContractConfig>,
+    #[serde(default)]
+    pub scenarios: BTreeMap<String, ScenarioConfig>,
+}
+
+impl RepoManifest {
+    /// Reads a manifest and everything it includes, merged into one
+    pub fn load(path: &Path) -> Result<Self> {
+        let base = path.parent().unwrap_or(Path::new("."));
+        let mut includes = Includes {
+            base,
+            stack: Vec::new(),
+            loaded: Vec::new(),
+            origins: HashMap::new(),
+        };
+        includes.load(path)
+    }
+
+    /// Names of everything this file defines, for conflict detection
+    fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::new();
+        if self.template_registry.is_some() {
+            keys.push("template_registry".to_string());
+        }
+        if self.build_profile.is_some() {
+            keys.push("build_profile".to_string());
+        }
+        keys.extend(self.repositories.keys().map(|name| format!("repository '{}'", name)));
+        keys.extend(self.infrastructure.keys().map(|name| format!("infrastructure '{}'", name)));
+        keys.extend(self.templates.keys().map(|name| format!("template '{}'", name)));
+        keys.extend(self.tasks.keys().map(|name| format!("task '{}'", name)));
+        keys.extend(self.contracts.keys().map(|name| format!("contract '{}'", name)));
+        keys.extend(self.scenarios.keys().map(|name| format!("scenario '{}'", name)));
+        keys
+    }
+
+    /// Takes over an included manifest's entries; conflicts were ruled out
+    /// while loading
+    fn merge(&mut self, other: RepoManifest) {
+        self.template_registry = self.template_registry.take().or(other.template_registry);
+        self.build_profile = self.build_profile.or(other.build_profile);
+        for include in other.proto_includes {
+            if !self.proto_includes.contains(&include) {
+                self.proto_includes.push(include);
+            }
+        }
+        self.repositories.extend(other.repositories);
+        self.infrastructure.extend(other.infrastructure);
+        self.templates.extend(other.templates);
+        self.tasks.extend(other.tasks);
+        self.contracts.extend(other.contracts);
+        self.scenarios.extend(other.scenarios);
+    }
+}
+
+/// Personal overlay of repos.toml, kept out of git
+pub const LOCAL_MANIFEST: &str = ".platform/config/repos.local.toml";
+
+impl RepoManifest {
+    /// Applies `repos.local.toml` on top: tables merge key by key, any other
+    /// value replaces the manifest's. Returns the entries it touched.
+    fn overlay(&mut self, path: &Path) -> Result<Vec<String>> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let local: toml::Table = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if local.contains_key("include") {
+            anyhow::bail!("{} cannot include other files", path.display());
+        }
+
+        let mut overrides = Vec::new();
+        for (key, value) in &local {
+            match value {
+                toml::Value::Table(entries) => {
+                    overrides.extend(entries.keys().map(|name| format!("{}.{}", key, name)))
+                }
+                _ => overrides.push(key.clone()),
+            }
+        }
+        let mut merged = toml::Value::try_from(&*self).context("Failed to read back the manifest")?;
+        merge_values(&mut merged, toml::Value::Table(local));
+        *self = merged
+            .try_into()
+            .with_context(|| format!("Invalid manifest after applying {}", path.display()))?;
+        Ok(overrides)
+    }
+}
+
+fn merge_values(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// State of loading a manifest and its includes
+struct Includes<'a> {
+    /// Directory of the top-level manifest, which paths in errors are relative to
+    base: &'a Path,
+    /// Files being loaded, to catch include cycles
+    stack: Vec<PathBuf>,
+    /// Files already merged; including one twice is harmless
+    loaded: Vec<PathBuf>,
+    /// File each entry was defined in
+    origins: HashMap<String, PathBuf>,
+}
+
+impl Includes<'_> {
+    fn load(&mut self, path: &Path) -> Result<RepoManifest> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest at {}", path.display()))?;
+        let mut manifest: RepoManifest = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", self.display(path)))?;
+        for key in manifest.keys() {
+            if let Some(other) = self.origins.insert(key.clone(), path.to_path_buf()) {
+                anyhow::bail!("{} is defined in both {} and {}", key, self.display(&other), self.display(path));
+            }
+        }
+
+        let dir = path.parent().unwrap_or(Path::new("."));
+        self.stack.push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+        for pattern in std::mem::take(&mut manifest.include) {
+            let full = dir.join(&pattern);
+            let mut files: Vec<PathBuf> = glob::glob(&full.to_string_lossy())
+                .with_context(|| format!("Invalid include '{}' in {}", pattern, self.display(path)))?
+                .filter_map(Result::ok)
+                .collect();
+            files.sort();
+            if files.is_empty() && !pattern.contains(['*', '?', '[']) {
+                anyhow::bail!("{} includes {}, which does not exist", self.display(path), pattern);
+            }
+            for file in files {
+                let canonical = file.canonicalize().unwrap_or_else(|_| file.clone());
+                if self.stack.contains(&canonical) {
+                    anyhow::bail!("{} includes itself through {}", self.display(&file), self.display(path));
+                }
+                if self.loaded.contains(&canonical) {
+                    continue;
+                }
+                let included = self.load(&file)?;
+                self.loaded.push(canonical);
+                manifest.merge(included);
+            }
+        }
+        self.stack.pop();
+        Ok(manifest)
+    }
+
+    fn display(&self, path: &Path) -> String {
+        path.strip_prefix(self.base).unwrap_or(path).display().to_string()
+    }
+}
+
+/// Cargo profile services are built with and run from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildProfile {
+    Debug,
+    #[default]
+    Release,
+}
+
+impl BuildProfile {
+    pub fn name(self) -> &'static str {
+        match self {
+            BuildProfile::Debug => "debug",
+            BuildProfile::Release => "release",
+        }
+    }
+
+    pub fn cargo_args(self) -> &'static [&'static str] {
+        match self {
+            BuildProfile::Debug => &["build"],
+            BuildProfile::Release => &["build", "--release"],
+        }
+    }
+}
+
+/// Named command for `syla run`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Shell command to run
+    pub command: String,
+    /// Run once in each matching repository (`*` wildcards allowed);
+    /// workspace-level when empty
+    #[serde(default)]
+    pub repos: Vec<String>,
+    /// Working directory of workspace-level tasks, relative to the root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    /// Tasks to run first
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// `secret:<name>` values are read from `syla secrets`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Consumer/provider contract checked by `syla contract test`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractConfig {
+    pub consumer: String,
+    pub provider: String,
+    #[serde(default)]
+    pub protocol: ContractProtocol,
+    /// TOML file of `[[interaction]]`s, relative to the workspace root
+    pub file: String,
+    /// The consumer's contract suite, run in its repository against a stub
+    /// of the provider at `SYLA_CONTRACT_PROVIDER_URL`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumer_command: Option<String>,
+}
+
+/// What a request is expected to get back
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExpectedResponse {
+    /// HTTP status, 200 when not given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// gRPC status code name, `OK` when not given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Matched by shape, so values are only examples
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<toml::Value>,
+}
+
+/// Cross-service smoke test run by `syla dev validate --integration`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScenarioConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Services to bring up besides the ones its steps call
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<String>,
+    /// Run in order; the first failure fails the scenario
+    #[serde(default, rename = "step")]
+    pub steps: Vec<ScenarioStep>,
+}
+
+/// One probe of a scenario: an HTTP request (`path`), a gRPC call (`rpc`)
+/// or an execution round-trip (`execute`) against `service`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioStep {
+    pub service: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// HTTP method, GET when not given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Full gRPC method name, `package.Service/Method`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc: Option<String>,
+    /// HTTP body or gRPC request message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<toml::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execute: Option<ExecutionProbe>,
+    #[serde(default)]
+    pub response: ExpectedResponse,
+}
+
+impl ScenarioStep {
+    /// What the step does, for reports
+    pub fn describe(&self) -> String {
+        if let Some(description) = &self.description {
+            return description.clone();
+        }
+        match (&self.path, &self.rpc, &self.execute) {
+            (Some(path), _, _) => format!("{} {} {}", self.method.as_deref().unwrap_or("GET"), self.service, path),
+            (_, Some(rpc), _) => format!("{} {}", self.service, rpc),
+            (_, _, Some(execute)) => format!("{} runs {}", self.service, execute.language),
+            _ => self.service.clone(),
+        }
+    }
+}
+
+/// Code submitted to a service's `/executions` API, which must complete
+/// and, when given, print `expected_output`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionProbe {
+    pub language: String,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_output: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractProtocol {
+    #[default]
+    Http,
+    /// Verified with `grpcurl`, which needs server reflection
+    Grpc,
+}
+
+/// Named workspace layout for `syla init --template`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceTemplate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Include every repository of these platforms
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    /// Include repositories by name; `*` matches any part of a name
+    #[serde(default)]
+    pub repositories: Vec<String>,
+    /// Docker Compose profiles enabled when starting infrastructure
+    #[serde(default)]
+    pub compose_profiles: Vec<String>,
+    /// Default workspace configuration values
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+}
+
+impl WorkspaceTemplate {
+    /// Whether a repository belongs to the template. Templates that list no
+    /// platforms or repositories include everything.
+    pub fn includes(&self, name: &str, repo: &RepositoryConfig) -> bool {
+        if self.platforms.is_empty() && self.repositories.is_empty() {
+            return true;
+        }
+        repo.platform.as_ref().is_some_and(|p| self.platforms.contains(p))
+            || self.repositories.iter().any(|pattern| {
+                glob::Pattern::new(pattern).is_ok_and(|p| p.matches(name))
+            })
+    }
+}
+
+/// Per-workspace choices made at init, kept in `.platform/config/workspace.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub compose_profiles: Vec<String>,
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "NotificationSettings::is_default")]
+    pub notifications: NotificationSettings,
+    #[serde(default, skip_serializing_if = "BuildSettings::is_default")]
+    pub build: BuildSettings,
+    #[serde(default, skip_serializing_if = "LogSettings::is_default")]
+    pub logs: LogSettings,
+    /// Enabled features, bringing in the optional repositories gated on them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    #[serde(default, skip_serializing_if = "DevSettings::is_default")]
+    pub dev: DevSettings,
+}
+
+/// Defaults for `syla dev`, under `[dev]`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DevSettings {
+    /// Platform `dev up` starts when `--platform` isn't given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_platform: Option<String>,
+}
+
+impl DevSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Build caching shared by every service, under `[build]` in
+/// `.platform/config/workspace.toml`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildSettings {
+    /// One cargo target directory for all services, relative to the
+    /// workspace root, so shared dependencies are compiled once. Service
+    /// binaries need distinct names.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_dir: Option<String>,
+    /// Compile through `sccache` when it's installed
+    pub sccache: bool,
+}
+
+impl BuildSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl WorkspaceSettings {
+    pub fn path(workspace_root: &Path) -> PathBuf {
+        workspace_root.join(".platform/config/workspace.toml")
+    }
+
+    /// The workspace's own settings, without the user config
+    pub fn load(workspace_root: &Path) -> Result<Self> {
+        let path = Self::path(workspace_root);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// The user config with the workspace's settings on top: tables merge
+    /// key by key, any other value replaces the user's
+    pub fn layered(workspace_root: &Path) -> Result<Self> {
+        let mut merged = toml::Value::Table(read_table(&user_config_path())?);
+        merge_values(&mut merged, toml::Value::Table(read_table(&Self::path(workspace_root))?));
+        merged.try_into().with_context(|| {
+            format!("Invalid settings in {} or {}", user_config_path().display(), Self::path(workspace_root).display())
+        })
+    }
+
+    pub fn save(&self, workspace_root: &Path) -> Result<()> {
+        let path = Self::path(workspace_root);
+        let content = format!(
+            "# Generated by `syla init`; safe to edit\n{}",
+            toml::to_string_pretty(self)?
+        );
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,10 +483,50 @@ pub struct RepositoryConfig {
     pub branch: String,
     #[serde(default)]
     pub language: String,
+    /// Shell command that builds the service, instead of the language's
+    /// default (cargo, `npm ci`, a Python venv, `go build`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
+    /// Shell command that starts the service, instead of the language's
+    /// default (the Rust binary, `npm start`, `main.py`, the Go binary)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run: Option<String>,
+    /// Whether the service runs as a process or in a container from `image`
+    #[serde(default, skip_serializing_if = "RunIn::is_process")]
+    pub run_in: RunIn,
+    /// Image a `run_in = "container"` service runs from; `run`, when set,
+    /// replaces the image's command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Shell command that runs the service's tests, instead of the
+    /// language's default (cargo test, `npm test`, pytest, `go test`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test: Option<String>,
+    /// Shell command that rebuilds and restarts the service on its own when
+    /// sources change (cargo watch, nodemon, air); `dev watch` runs it
+    /// instead of rebuilding the service itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watch_command: Option<String>,
+    /// What `dev watch` looks at and does for the service; without it,
+    /// sources newer than the build trigger a rebuild and restart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watch: Option<WatchConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub health_check: Option<String>,
+    /// When the supervisor restarts the service after it exits or turns
+    /// unhealthy: `on-failure` (default), `always`, `unless-stopped` or `never`
+    #[serde(default, skip_serializing_if = "RestartPolicy::is_default")]
+    pub restart: RestartPolicy,
+    /// Restarts in a row before the supervisor gives up on the service
+    /// (default 5); the count resets once it stays up for a minute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_restarts: Option<u32>,
+    /// Host ports; `auto` ones are assigned at `syla dev up`
     #[serde(default)]
     pub ports: Vec<String>,
+    /// Indices of the `auto` entries in `ports`
+    #[serde(skip)]
+    pub auto_ports: Vec<usize>,
     #[serde(default)]
     pub depends_on: Vec<String>,
     #[serde(rename = "type")]
@@ -31,6 +535,137 @@ pub struct RepositoryConfig {
     pub platform: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Labels such as `core` or `backend` that `--tags` selects by
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Left out of `syla init` and `dev up` until its feature is enabled
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// Feature that enables the repository; implies `optional`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requires_feature: Option<String>,
+    /// Extra environment for the service; `secret:<name>` values are read
+    /// from `syla secrets`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Database migrations for `syla db`; detected from `diesel.toml` or a
+    /// `migrations/` directory when not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub migrations: Option<MigrationsConfig>,
+    /// Protobuf definitions for `syla proto`; a `proto/` directory is
+    /// picked up when not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protos: Option<ProtoConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtoConfig {
+    /// Relative to the repository
+    #[serde(default = "default_proto_dir")]
+    pub dir: String,
+    /// Where `protoc` writes generated code, relative to the repository.
+    /// Unset when a `buf.gen.yaml` or the build (e.g. tonic-build) generates it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out: Option<String>,
+}
+
+impl RepositoryConfig {
+    /// Whether the repository carries any of `tags`; everything matches
+    /// when no tags are given
+    pub fn has_any_tag(&self, tags: &[String]) -> bool {
+        tags.is_empty() || self.tags.iter().any(|tag| tags.contains(tag))
+    }
+
+    /// Feature gating the repository: `requires_feature`, else the
+    /// repository's own name when it is `optional`
+    pub fn feature<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        match &self.requires_feature {
+            Some(feature) => Some(feature),
+            None => self.optional.then_some(name),
+        }
+    }
+}
+
+fn default_proto_dir() -> String {
+    "proto".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationsConfig {
+    pub tool: MigrationTool,
+    /// Relative to the repository
+    #[serde(default = "default_migrations_dir")]
+    pub dir: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationTool {
+    Sqlx,
+    Diesel,
+    /// Plain `.sql` files applied in name order, tracked by syla
+    Sql,
+}
+
+fn default_migrations_dir() -> String {
+    "migrations".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Files and directories to watch, relative to the repository; all of
+    /// it when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Globs for files that never trigger, on top of `target/`,
+    /// `node_modules/` and `.git/`; a trailing `/` matches directories
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// Quiet time after the last change before acting, so a burst of
+    /// writes is handled once
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+    #[serde(default)]
+    pub on_change: WatchAction,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            ignore: Vec::new(),
+            debounce_ms: default_debounce_ms(),
+            on_change: WatchAction::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchAction {
+    Build,
+    Restart,
+    /// Build, then restart if the build succeeded
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunIn {
+    #[default]
+    Process,
+    Container,
+}
+
+impl RunIn {
+    fn is_process(&self) -> bool {
+        *self == RunIn::Process
+    }
+}
+
+fn default_debounce_ms() -> u64 {
+    500
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,31 +692,91 @@ fn default_branch() -> String {
 pub struct Config {
     pub workspace_root: PathBuf,
     pub manifest: RepoManifest,
+    pub settings: WorkspaceSettings,
+    /// Manifest entries changed by `repos.local.toml`
+    pub local_overrides: Vec<String>,
 }
 
 impl Config {
     pub fn load(workspace_root: Option<PathBuf>) -> Result<Self> {
-        let workspace_root = if let Some(path) = workspace_root {
-            path
-        } else {
-            // Try to find workspace root by looking for .platform directory
-            let current_dir = std::env::current_dir()?;
-            find_workspace_root(&current_dir)?
-        };
+        let workspace_root = resolve_workspace_root(workspace_root)?;
 
         let manifest_path = workspace_root.join(".platform/config/repos.toml");
-        let manifest_content = std::fs::read_to_string(&manifest_path)
-            .with_context(|| format!("Failed to read manifest at {}", manifest_path.display()))?;
-        
-        let manifest: RepoManifest = toml::from_str(&manifest_content)
-            .context("Failed to parse repository manifest")?;
+        let mut manifest = RepoManifest::load(&manifest_path)?;
+        let local_overrides = manifest.overlay(&workspace_root.join(LOCAL_MANIFEST))?;
+        let allocations = ports::PortAllocations::load(&workspace_root)?;
+        interpolation::resolve(&mut manifest, &workspace_root, &allocations)?;
+        ports::resolve(&mut manifest, &allocations);
+        let settings = WorkspaceSettings::layered(&workspace_root)?;
 
         Ok(Self {
             workspace_root,
             manifest,
+            settings,
+            local_overrides,
         })
     }
 
+    /// The requested profile, else the manifest's default, else release
+    pub fn build_profile(&self, requested: Option<BuildProfile>) -> BuildProfile {
+        requested.or(self.manifest.build_profile).unwrap_or_default()
+    }
+
+    /// Where cargo writes a service's build output: the shared target
+    /// directory when configured, else the repository's own
+    pub fn target_dir(&self, service_path: &Path) -> PathBuf {
+        match &self.settings.build.target_dir {
+            Some(dir) => self.workspace_root.join(dir),
+            None => service_path.join("target"),
+        }
+    }
+
+    /// A `cargo` invocation that uses the workspace's build cache
+    pub fn cargo(&self) -> std::process::Command {
+        let mut cmd = std::process::Command::new("cargo");
+        if let Some(dir) = &self.settings.build.target_dir {
+            cmd.env("CARGO_TARGET_DIR", self.workspace_root.join(dir));
+        }
+        if self.settings.build.sccache {
+            match which::which("sccache") {
+                Ok(path) => {
+                    cmd.env("RUSTC_WRAPPER", path);
+                }
+                Err(_) => tracing::warn!("sccache is enabled but not installed; building without it"),
+            }
+        }
+        cmd
+    }
+
+    /// Finds the repository a service argument refers to, e.g.
+    /// `api-gateway` for `syla.core.api-gateway`; see [`names::find`]
+    pub fn find_repository(&self, service: &str) -> Result<(String, &RepositoryConfig)> {
+        let name = names::resolve("service", service, self.manifest.repositories.keys().map(String::as_str))?;
+        let repo = &self.manifest.repositories[&name];
+        Ok((name, repo))
+    }
+
+    /// Like [`Config::find_repository`], but asks which repository to use
+    /// when `service` is omitted
+    pub fn select_repository(&self, service: Option<&str>) -> Result<(String, &RepositoryConfig)> {
+        let name = names::resolve_or_pick("service", service, self.manifest.repositories.keys().map(String::as_str))?;
+        let repo = &self.manifest.repositories[&name];
+        Ok((name, repo))
+    }
+
+    /// Platforms repositories belong to
+    pub fn platforms(&self) -> Vec<&str> {
+        let mut platforms: Vec<&str> = self
+            .manifest
+            .repositories
+            .values()
+            .filter_map(|repo| repo.platform.as_deref())
+            .collect();
+        platforms.sort_unstable();
+        platforms.dedup();
+        platforms
+    }
+
     pub fn get_all_repositories(&self) -> Vec<(String, &RepositoryConfig)> {
         self.manifest.repositories
             .iter()
@@ -89,6 +784,87 @@ impl Config {
             .collect()
     }
 
+    /// Whether a repository is part of the workspace: always, unless it is
+    /// optional and its feature isn't enabled
+    pub fn is_enabled(&self, name: &str, repo: &RepositoryConfig) -> bool {
+        repo.feature(name)
+            .is_none_or(|feature| self.settings.features.iter().any(|enabled| enabled == feature))
+    }
+
+    /// Every feature the manifest gates repositories on, with those
+    /// repositories
+    pub fn features(&self) -> BTreeMap<String, Vec<String>> {
+        let mut features: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, repo) in &self.manifest.repositories {
+            if let Some(feature) = repo.feature(name) {
+                features.entry(feature.to_string()).or_default().push(name.clone());
+            }
+        }
+        for repos in features.values_mut() {
+            repos.sort();
+        }
+        features
+    }
+
+    /// Every repository name, each after the repositories it depends on;
+    /// fails on a dependency cycle
+    pub fn dependency_order(&self) -> Result<Vec<String>> {
+        fn visit(
+            config: &Config,
+            name: &str,
+            visiting: &mut Vec<String>,
+            done: &mut HashSet<String>,
+            order: &mut Vec<String>,
+        ) -> Result<()> {
+            if done.contains(name) {
+                return Ok(());
+            }
+            if let Some(start) = visiting.iter().position(|n| n == name) {
+                let mut cycle = visiting[start..].to_vec();
+                cycle.push(name.to_string());
+                anyhow::bail!("Dependency cycle: {}", cycle.join(" -> "));
+            }
+            visiting.push(name.to_string());
+            if let Some(repo) = config.manifest.repositories.get(name) {
+                for dependency in &repo.depends_on {
+                    if config.manifest.repositories.contains_key(dependency) {
+                        visit(config, dependency, visiting, done, order)?;
+                    }
+                }
+            }
+            visiting.pop();
+            done.insert(name.to_string());
+            order.push(name.to_string());
+            Ok(())
+        }
+
+        let mut names: Vec<&String> = self.manifest.repositories.keys().collect();
+        names.sort();
+        let mut order = Vec::new();
+        let mut done = HashSet::new();
+        for name in names {
+            visit(self, name, &mut Vec::new(), &mut done, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Fails on tags no repository carries, which are most likely typos
+    pub fn check_tags(&self, tags: &[String]) -> Result<()> {
+        let mut known: Vec<&String> = self.manifest.repositories.values().flat_map(|repo| &repo.tags).collect();
+        known.sort();
+        known.dedup();
+        for tag in tags {
+            if !known.contains(&tag) {
+                if known.is_empty() {
+                    anyhow::bail!("Unknown tag '{}'; no repository has tags", tag);
+                }
+                let known: Vec<&str> = known.iter().map(|tag| tag.as_str()).collect();
+                anyhow::bail!("Unknown tag '{}'. Known tags: {}", tag, known.join(", "));
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_platform_repositories(&self, platform: &str) -> Option<Vec<(String, &RepositoryConfig)>> {
         let repos: Vec<_> = self.manifest.repositories
             .iter()
@@ -106,6 +882,39 @@ impl Config {
     }
 }
 
+/// Per-user state shared across workspaces: `$SYLA_HOME`, or `~/.syla`
+pub fn user_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("SYLA_HOME") {
+        return PathBuf::from(dir);
+    }
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    home.join(".syla")
+}
+
+/// Settings shared by every workspace, in the shape of
+/// `.platform/config/workspace.toml`, whose values win over these
+pub fn user_config_path() -> PathBuf {
+    user_dir().join("config.toml")
+}
+
+/// A TOML file's top-level table; empty when the file doesn't exist
+pub fn read_table(path: &Path) -> Result<toml::Table> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(toml::Table::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// The given workspace root, else the nearest directory up from the current
+/// one that has a `.platform` directory
+pub fn resolve_workspace_root(workspace_root: Option<PathBuf>) -> Result<PathBuf> {
+    match workspace_root {
+        Some(path) => Ok(path),
+        None => find_workspace_root(&std::env::current_dir()?),
+    }
+}
+
 fn find_workspace_root(start: &Path) -> Result<PathBuf> {
     let mut current = start.to_path_buf();
     
@@ -0,0 +1,524 @@
+//! Control API of the supervisor.
+//!
+//! `syla dev supervise` keeps the services it starts running and answers
+//! requests on a unix socket, `.platform/state/control.sock` by default, so
+//! editors, scripts and the dashboard can drive the environment without
+//! spawning the CLI for every action. Each request is one line of JSON and
+//! gets one line back:
+//!
+//! ```text
+//! {"command": "start", "service": "execution-service"}
+//! {"ok": true, "result": {"service": "syla.core.execution-service", "pid": 4242}}
+//! ```
+//!
+//! Commands are `list`, `start` (optionally with a build `profile`),
+//! `stop`, `restart`, `health` (one `service`, or all), `logs` (a `service`
+//! and optionally `lines`, default 100), `info` and `shutdown`. A request
+//! may name its `caller`; starts, stops, restarts and shutdowns go into
+//! `syla history` as e.g. `dashboard restart`, or `control restart` when
+//! it doesn't.
+//!
+//! `syla daemon` runs the same supervisor in the background; `dev up
+//! --detach`, `dev down`, `dev restart` and `dev status` go through it
+//! while it's running. Meanwhile it records the health of running services
+//! for `syla dev report`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::commands::dev;
+use crate::commands::status::HealthChecks;
+use crate::config::{BuildProfile, Config};
+use crate::history;
+use crate::services::health_history::{self, Sample};
+use crate::services::process_manager::ProcessState;
+use crate::services::registry;
+use crate::services::state::StartedServices;
+use crate::services::ProcessManager;
+
+/// How often the supervisor records the health of running services
+const HEALTH_SAMPLE_INTERVAL: Duration = Duration::from_secs(30);
+
+pub fn socket_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(".platform/state/control.sock")
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    List,
+    Start {
+        service: String,
+        /// Run the binary built with this profile (default: the manifest's)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        profile: Option<BuildProfile>,
+    },
+    Stop {
+        service: String,
+    },
+    Restart {
+        service: String,
+    },
+    Health {
+        service: Option<String>,
+    },
+    Logs {
+        service: String,
+        #[serde(default = "default_lines")]
+        lines: usize,
+    },
+    /// The supervisor's pid, socket and uptime
+    Info,
+    /// Stop the supervised services and exit
+    Shutdown,
+}
+
+impl Request {
+    /// What a state-changing request does, and its arguments, for the history
+    fn action(&self) -> Option<(&'static str, Vec<String>)> {
+        match self {
+            Request::Start { service, .. } => Some(("start", vec![service.clone()])),
+            Request::Stop { service } => Some(("stop", vec![service.clone()])),
+            Request::Restart { service } => Some(("restart", vec![service.clone()])),
+            Request::Shutdown => Some(("shutdown", Vec::new())),
+            _ => None,
+        }
+    }
+}
+
+fn default_lines() -> usize {
+    100
+}
+
+/// Caller the CLI sends for its own commands, which it records itself
+pub const CLI_CALLER: &str = "cli";
+
+/// A request as it travels over the socket
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope<R> {
+    #[serde(flatten)]
+    request: R,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    caller: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<Result<Value>> for Response {
+    fn from(result: Result<Value>) -> Self {
+        match result {
+            Ok(result) => Response { ok: true, result: Some(result), error: None },
+            Err(e) => Response { ok: false, result: None, error: Some(format!("{:#}", e)) },
+        }
+    }
+}
+
+/// A service as `list` reports it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceInfo {
+    pub name: String,
+    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    pub ports: Vec<String>,
+    /// Started by this supervisor, rather than by `dev up`
+    pub managed: bool,
+    /// Times this supervisor restarted it
+    #[serde(default)]
+    pub restarts: u32,
+}
+
+/// What `info` reports about the supervisor
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupervisorInfo {
+    pub pid: u32,
+    pub socket: PathBuf,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Services it started that haven't been stopped
+    pub services: usize,
+}
+
+/// Owns the services started through the socket; they stop with it.
+/// `syla serve` drives one too.
+pub(crate) struct Supervisor {
+    pub(crate) config: Config,
+    manager: ProcessManager,
+    socket: PathBuf,
+    started_at: chrono::DateTime<chrono::Utc>,
+    /// Profile each service was last started with, to restart it the same
+    profiles: std::sync::Mutex<HashMap<String, BuildProfile>>,
+    /// Signalled by a `shutdown` request
+    shutdown: tokio::sync::Notify,
+}
+
+impl Supervisor {
+    pub(crate) fn new(config: Config) -> std::sync::Arc<Self> {
+        let socket = socket_path(&config.workspace_root);
+        Self::listening_on(config, socket)
+    }
+
+    fn listening_on(config: Config, socket: PathBuf) -> std::sync::Arc<Self> {
+        std::sync::Arc::new(Supervisor {
+            manager: ProcessManager::new(config.clone()),
+            config,
+            socket,
+            started_at: chrono::Utc::now(),
+            profiles: Default::default(),
+            shutdown: tokio::sync::Notify::new(),
+        })
+    }
+
+    /// Answers a request from `caller`, recording state changes in the
+    /// history unless the CLI does so itself
+    pub(crate) async fn dispatch(self: &std::sync::Arc<Self>, request: Request, caller: &str) -> Result<Value> {
+        let action = if caller == CLI_CALLER { None } else { request.action() };
+        let started = Instant::now();
+        let result = self.handle(request).await;
+        if let Some((action, args)) = action {
+            let error = result.as_ref().err().map(|e| format!("{:#}", e));
+            history::record_action(&self.config.workspace_root, &format!("{} {}", caller, action), args, started.elapsed(), error);
+        }
+        result
+    }
+
+    async fn handle(self: &std::sync::Arc<Self>, request: Request) -> Result<Value> {
+        match request {
+            Request::List => self.blocking(|supervisor| supervisor.list()).await,
+            Request::Start { service, profile } => {
+                let result = self.blocking(move |supervisor| supervisor.start(&service, profile)).await;
+                registry::update(&self.config).await;
+                result
+            }
+            Request::Stop { service } => {
+                let result = self.blocking(move |supervisor| supervisor.stop(&service)).await;
+                registry::update(&self.config).await;
+                result
+            }
+            Request::Restart { service } => {
+                let result = self.blocking(move |supervisor| supervisor.restart(&service)).await;
+                registry::update(&self.config).await;
+                result
+            }
+            Request::Health { service } => self.health(service.as_deref()).await,
+            Request::Logs { service, lines } => self.logs(&service, lines),
+            Request::Info => Ok(serde_json::to_value(self.info())?),
+            Request::Shutdown => {
+                self.shutdown.notify_one();
+                Ok(json!({ "pid": std::process::id() }))
+            }
+        }
+    }
+
+    fn info(&self) -> SupervisorInfo {
+        let services = self
+            .manager
+            .list_services()
+            .into_iter()
+            .filter(|(_, state, _)| !matches!(state, ProcessState::Stopped))
+            .count();
+        SupervisorInfo { pid: std::process::id(), socket: self.socket.clone(), started_at: self.started_at, services }
+    }
+
+    /// Runs `f` off the async threads; starting and stopping processes waits
+    async fn blocking<F>(self: &std::sync::Arc<Self>, f: F) -> Result<Value>
+    where
+        F: FnOnce(&Supervisor) -> Result<Value> + Send + 'static,
+    {
+        let supervisor = self.clone();
+        tokio::task::spawn_blocking(move || f(&supervisor)).await?
+    }
+
+    fn list(&self) -> Result<Value> {
+        let started = StartedServices::load(&self.config.workspace_root)?;
+        let mut repos = self.config.get_all_repositories();
+        repos.retain(|(name, repo)| dev::is_service(&self.config, name, repo));
+        repos.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let services: Vec<ServiceInfo> = repos
+            .into_iter()
+            .map(|(name, repo)| {
+                let (state, pid) = match self.manager.get_service_status(&name) {
+                    Some((state, _)) => (state_name(&state), self.manager.pid(&name)),
+                    None => match started.services.get(&name).filter(|service| service.is_running()) {
+                        Some(service) => ("running", Some(service.pid)),
+                        None => ("stopped", None),
+                    },
+                };
+                let managed = self.manager.get_service_status(&name).is_some();
+                let restarts = self.manager.restart_count(&name);
+                ServiceInfo { name, state: state.to_string(), pid, ports: repo.ports.clone(), managed, restarts }
+            })
+            .collect();
+        Ok(serde_json::to_value(services)?)
+    }
+
+    fn start(&self, service: &str, profile: Option<BuildProfile>) -> Result<Value> {
+        let (name, repo) = self.config.find_repository(service)?;
+        if !dev::is_service(&self.config, &name, repo) {
+            anyhow::bail!("{} is not a service: it has no ports, is disabled or has no way to run", name);
+        }
+        let started = StartedServices::load(&self.config.workspace_root)?;
+        if let Some(running) = started.services.get(&name).filter(|service| service.is_running()) {
+            anyhow::bail!("{} is already running (pid {})", name, running.pid);
+        }
+        let profile = self.config.build_profile(profile);
+        let process_config = dev::service_process_config(&self.config, &name, repo, profile)?
+            .with_context(|| format!("{} is not built yet; run `syla dev build-changed`", name))?;
+        dev::start_service(&self.config, &self.manager, &name, repo, process_config)?;
+        self.profiles.lock().unwrap().insert(name.clone(), profile);
+        Ok(json!({ "service": name, "pid": self.manager.pid(&name) }))
+    }
+
+    fn stop(&self, service: &str) -> Result<Value> {
+        let (name, _) = self.config.find_repository(service)?;
+        if matches!(self.manager.get_service_status(&name), Some((ProcessState::Running, _))) {
+            self.manager.stop_service(&name, false)?;
+        }
+        dev::stop_started(&self.config, &[&name])?;
+        Ok(json!({ "service": name }))
+    }
+
+    /// Stops the service, whoever started it, and starts it under this
+    /// supervisor with the profile it was started with
+    fn restart(&self, service: &str) -> Result<Value> {
+        let (name, _) = self.config.find_repository(service)?;
+        let profile = self.profiles.lock().unwrap().get(&name).copied();
+        self.stop(&name)?;
+        self.start(&name, profile)
+    }
+
+    async fn health(&self, service: Option<&str>) -> Result<Value> {
+        let checks: Vec<(String, String)> = match service {
+            Some(service) => {
+                let (name, repo) = self.config.find_repository(service)?;
+                let check = repo.health_check.clone().with_context(|| format!("{} has no health check", name))?;
+                vec![(name, check)]
+            }
+            None => self
+                .config
+                .get_all_repositories()
+                .into_iter()
+                .filter(|(name, repo)| dev::is_service(&self.config, name, repo))
+                .filter_map(|(name, repo)| Some((name, repo.health_check.clone()?)))
+                .collect(),
+        };
+        let results: BTreeMap<String, Option<bool>> = HealthChecks::start(checks).results().await.into_iter().collect();
+        Ok(serde_json::to_value(results)?)
+    }
+
+    /// Checks the services that are running, or that failed under this
+    /// supervisor, and appends the outcome to the health history
+    pub(crate) async fn sample_health(&self) -> Result<()> {
+        let started = StartedServices::load(&self.config.workspace_root)?;
+        let checks: Vec<(String, String)> = self
+            .config
+            .get_all_repositories()
+            .into_iter()
+            .filter(|(name, repo)| dev::is_service(&self.config, name, repo))
+            .filter(|(name, _)| match self.manager.get_service_status(name) {
+                Some((state, _)) => !matches!(state, ProcessState::Stopped),
+                None => started.services.get(name).is_some_and(|service| service.is_running()),
+            })
+            .filter_map(|(name, repo)| Some((name, repo.health_check.clone()?)))
+            .collect();
+        let timestamp = chrono::Utc::now();
+        let samples: Vec<Sample> = HealthChecks::start(checks)
+            .timed()
+            .await
+            .into_iter()
+            .filter_map(|(service, healthy, latency)| {
+                Some(Sample { timestamp, service, healthy: healthy?, latency_ms: latency.as_millis() as u64 })
+            })
+            .collect();
+        health_history::record(&self.config.workspace_root, &samples)
+    }
+
+    /// Stops the services started through this supervisor
+    pub(crate) async fn shutdown(self: std::sync::Arc<Self>) -> Result<()> {
+        tokio::task::spawn_blocking(move || {
+            let managed: Vec<String> = self.manager.list_services().into_iter().map(|(name, ..)| name).collect();
+            self.manager.stop_all()?;
+            dev::stop_started(&self.config, &managed.iter().map(String::as_str).collect::<Vec<_>>())
+        })
+        .await?
+    }
+
+    fn logs(&self, service: &str, lines: usize) -> Result<Value> {
+        let (name, _) = self.config.find_repository(service)?;
+        let path = dev::service_log_file(&self.config, &name);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let all: Vec<&str> = content.lines().collect();
+        let tail = &all[all.len().saturating_sub(lines)..];
+        Ok(json!({ "service": name, "lines": tail }))
+    }
+}
+
+fn state_name(state: &ProcessState) -> &'static str {
+    match state {
+        ProcessState::Starting => "starting",
+        ProcessState::Running => "running",
+        ProcessState::Stopping => "stopping",
+        ProcessState::Stopped => "stopped",
+        ProcessState::Failed(_) => "failed",
+        ProcessState::Restarting => "restarting",
+    }
+}
+
+/// Serves the control API until interrupted, terminated or asked to shut
+/// down, then stops the services it started
+#[cfg(unix)]
+pub async fn serve(config: Config, socket: Option<PathBuf>) -> Result<()> {
+    use colored::Colorize;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let path = socket.unwrap_or_else(|| socket_path(&config.workspace_root));
+    if path.exists() {
+        if UnixStream::connect(&path).await.is_ok() {
+            anyhow::bail!("A supervisor is already listening on {}", path.display());
+        }
+        // Left behind by one that didn't shut down cleanly
+        std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(&path).with_context(|| format!("Failed to listen on {}", path.display()))?;
+    // Nobody is at the terminal to answer a picker for a socket request
+    crate::names::set_non_interactive(true);
+
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    let supervisor = Supervisor::listening_on(config, path.clone());
+    say!("{} Control API listening on {}", "[OK]".green(), path.display());
+    say!("Press Ctrl+C to stop; services started through it stop too");
+
+    let sampler = {
+        let supervisor = supervisor.clone();
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(HEALTH_SAMPLE_INTERVAL);
+            loop {
+                ticks.tick().await;
+                if let Err(e) = supervisor.sample_health().await {
+                    tracing::debug!("Failed to record service health: {:#}", e);
+                }
+            }
+        })
+    };
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => accepted?.0,
+            _ = tokio::signal::ctrl_c() => break,
+            _ = terminate.recv() => break,
+            _ = supervisor.shutdown.notified() => break,
+        };
+        let supervisor = supervisor.clone();
+        tokio::spawn(async move {
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let response = match serde_json::from_str::<Envelope<Request>>(&line) {
+                    Ok(Envelope { request, caller }) => {
+                        Response::from(supervisor.dispatch(request, caller.as_deref().unwrap_or("control")).await)
+                    }
+                    Err(e) => Response::from(Err(anyhow::anyhow!("Invalid request: {}", e))),
+                };
+                let Ok(mut reply) = serde_json::to_vec(&response) else { break };
+                reply.push(b'\n');
+                if writer.write_all(&reply).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    sampler.abort();
+    let _ = std::fs::remove_file(&path);
+    say!("\nStopping supervised services...");
+    supervisor.shutdown().await
+}
+
+#[cfg(not(unix))]
+pub async fn serve(_config: Config, _socket: Option<PathBuf>) -> Result<()> {
+    anyhow::bail!("The control API needs unix sockets, which this platform doesn't have")
+}
+
+/// A connection to a running supervisor
+#[cfg(unix)]
+pub struct Client {
+    stream: tokio::io::BufReader<tokio::net::UnixStream>,
+    caller: &'static str,
+}
+
+#[cfg(unix)]
+impl Client {
+    /// Connects to the workspace's supervisor, or `None` if none is running
+    pub async fn connect(workspace_root: &Path) -> Option<Client> {
+        let stream = tokio::net::UnixStream::connect(socket_path(workspace_root)).await.ok()?;
+        Some(Client { stream: tokio::io::BufReader::new(stream), caller: CLI_CALLER })
+    }
+
+    /// Sends requests on behalf of `caller`, such as the dashboard, so the
+    /// supervisor records what they change
+    pub fn caller(mut self, caller: &'static str) -> Self {
+        self.caller = caller;
+        self
+    }
+
+    /// Sends one request and waits for its result
+    pub async fn call(&mut self, request: &Request) -> Result<Value> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+        let mut line = serde_json::to_vec(&Envelope { request, caller: Some(self.caller.to_string()) })?;
+        line.push(b'\n');
+        self.stream.get_mut().write_all(&line).await.context("Failed to reach the supervisor")?;
+        let mut reply = String::new();
+        if self.stream.read_line(&mut reply).await? == 0 {
+            anyhow::bail!("The supervisor closed the connection");
+        }
+        let response: Response = serde_json::from_str(&reply).context("Invalid reply from the supervisor")?;
+        match response {
+            Response { ok: true, result, .. } => Ok(result.unwrap_or(Value::Null)),
+            Response { error, .. } => Err(anyhow::anyhow!(error.unwrap_or_else(|| "Request failed".to_string()))),
+        }
+    }
+}
+
+/// Unix sockets are needed to reach a supervisor
+#[cfg(not(unix))]
+pub struct Client;
+
+#[cfg(not(unix))]
+impl Client {
+    pub async fn connect(_workspace_root: &Path) -> Option<Client> {
+        None
+    }
+
+    pub fn caller(self, _caller: &'static str) -> Self {
+        self
+    }
+
+    pub async fn call(&mut self, _request: &Request) -> Result<Value> {
+        anyhow::bail!("The control API needs unix sockets, which this platform doesn't have")
+    }
+}
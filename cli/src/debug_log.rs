@@ -0,0 +1,206 @@
+//! The CLI's own record of what it did, for "syla did something weird"
+//! reports.
+//!
+//! Every tracing event at debug level and above is appended to
+//! `.platform/logs/syla.log` as one JSON object per line, whatever the
+//! console verbosity: the command lines `ui::log_command` reports and how
+//! they exited, span timings, warnings and errors, each with the spans it
+//! happened in. The file rotates at 5 MiB, keeping three older copies as
+//! `syla.log.1` to `syla.log.3`. `SYLA_DEBUG_LOG=off` turns it off.
+
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+const MAX_BYTES: u64 = 5 * 1024 * 1024;
+const KEEP: usize = 3;
+
+pub fn path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(".platform/logs/syla.log")
+}
+
+pub struct DebugLog {
+    file: Mutex<LogFile>,
+}
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl DebugLog {
+    /// The log for the workspace the CLI runs in; `None` outside one, when
+    /// turned off, or when the file can't be opened
+    pub fn open(workspace_root: Option<PathBuf>) -> Option<Self> {
+        if std::env::var("SYLA_DEBUG_LOG").is_ok_and(|v| matches!(v.as_str(), "off" | "0" | "false")) {
+            return None;
+        }
+        let workspace_root = crate::config::resolve_workspace_root(workspace_root).ok()?;
+        if !workspace_root.join(".platform").is_dir() {
+            return None;
+        }
+        let path = path(&workspace_root);
+        std::fs::create_dir_all(path.parent()?).ok()?;
+        let file = LogFile::open(path).ok()?;
+        Some(Self { file: Mutex::new(file) })
+    }
+
+    fn write(&self, entry: Value) {
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+        let mut line = entry.to_string();
+        line.push('\n');
+        // Losing a debug line must never fail the command being logged
+        if file.file.write_all(line.as_bytes()).is_ok() {
+            file.size += line.len() as u64;
+        }
+        if file.size >= MAX_BYTES {
+            let _ = file.rotate();
+        }
+    }
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        let mut log = Self::append(path)?;
+        if log.size >= MAX_BYTES {
+            log.rotate()?;
+        }
+        Ok(log)
+    }
+
+    fn append(path: PathBuf) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, file, size })
+    }
+
+    /// Shifts `syla.log.N` up by one, dropping the oldest, and starts afresh
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let numbered = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+        for n in (1..KEEP).rev() {
+            let _ = std::fs::rename(numbered(n), numbered(n + 1));
+        }
+        std::fs::rename(&self.path, numbered(1))?;
+        *self = Self::append(self.path.clone())?;
+        Ok(())
+    }
+}
+
+/// A span's fields, and when it was created
+struct SpanData {
+    fields: Map<String, Value>,
+    created: Instant,
+}
+
+impl<S> Layer<S> for DebugLog
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = JsonFields::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanData { fields: fields.0, created: Instant::now() });
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<SpanData>() {
+            let mut fields = JsonFields(std::mem::take(&mut data.fields));
+            values.record(&mut fields);
+            data.fields = fields.0;
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let message = fields.0.remove("message").unwrap_or(Value::Null);
+        let spans: Vec<Value> = ctx
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+            .map(|span| {
+                let fields = span.extensions().get::<SpanData>().map(|data| data.fields.clone()).unwrap_or_default();
+                json!({ "name": span.name(), "fields": fields })
+            })
+            .collect();
+
+        let metadata = event.metadata();
+        self.write(json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "message": message,
+            "fields": fields.0,
+            "spans": spans,
+            "pid": std::process::id(),
+        }));
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(data) = extensions.get::<SpanData>() else {
+            return;
+        };
+        self.write(json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "level": span.metadata().level().as_str(),
+            "target": span.metadata().target(),
+            "message": "span closed",
+            "span": span.name(),
+            "fields": data.fields,
+            "duration_ms": data.created.elapsed().as_millis() as u64,
+            "pid": std::process::id(),
+        }));
+    }
+}
+
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), Value::String(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+}
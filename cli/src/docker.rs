@@ -1,25 +1,87 @@
 use anyhow::{Context, Result};
+use bollard::container::ListContainersOptions;
 use bollard::Docker;
+use colored::Colorize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+use crate::config::Config;
+
+/// How long the daemon gets to answer before it counts as unavailable
+const DAEMON_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of the first daemon check, shared by the rest of the command
+static DAEMON: OnceCell<Result<String, String>> = OnceCell::const_new();
 
 pub async fn check_docker() -> Result<String> {
     let docker = Docker::connect_with_local_defaults()
         .context("Failed to connect to Docker")?;
     
-    let version = docker.version().await
+    let version = tokio::time::timeout(DAEMON_TIMEOUT, docker.version())
+        .await
+        .context("Docker daemon did not respond")?
         .context("Failed to get Docker version")?;
     
     Ok(format!("Docker {}", version.version.unwrap_or_else(|| "unknown".to_string())))
 }
 
-pub async fn is_container_running(name: &str) -> Result<bool> {
-    let docker = Docker::connect_with_local_defaults()?;
-    
-    match docker.inspect_container(name, None).await {
-        Ok(info) => {
-            Ok(info.state
-                .and_then(|s| s.running)
-                .unwrap_or(false))
-        }
-        Err(_) => Ok(false),
-    }
-}
\ No newline at end of file
+/// Why the Docker daemon can't be reached, or `None` when it can. The
+/// daemon is only asked once per command.
+pub async fn unavailable() -> Option<String> {
+    let daemon = DAEMON
+        .get_or_init(|| async { check_docker().await.map_err(|e| format!("{:#}", e)) })
+        .await;
+    daemon.as_ref().err().cloned()
+}
+
+/// Notes that a container-dependent step was skipped
+pub fn print_skipped(what: &str, reason: &str) {
+    println!("{} Docker is not available ({}), skipping {}", "[!]".yellow(), reason, what);
+}
+
+/// A container Docker knows about, running or not
+#[derive(Debug, Clone)]
+pub struct ContainerSummary {
+    pub name: String,
+    /// Service it was created for, for containers from a compose file
+    pub compose_service: Option<String>,
+    /// Directory of the compose project it belongs to
+    pub compose_dir: Option<PathBuf>,
+    pub running: bool,
+}
+
+/// Every container on the daemon, stopped ones included
+pub async fn containers() -> Result<Vec<ContainerSummary>> {
+    let docker = Docker::connect_with_local_defaults()
+        .context("Failed to connect to Docker")?;
+    let options = ListContainersOptions::<String> { all: true, ..Default::default() };
+    let containers = tokio::time::timeout(DAEMON_TIMEOUT, docker.list_containers(Some(options)))
+        .await
+        .context("Docker daemon did not respond")?
+        .context("Failed to list containers")?;
+
+    Ok(containers
+        .into_iter()
+        .map(|container| {
+            let mut labels = container.labels.unwrap_or_default();
+            let name = container.names.unwrap_or_default().into_iter().next().unwrap_or_default();
+            ContainerSummary {
+                name: name.trim_start_matches('/').to_string(),
+                compose_service: labels.remove("com.docker.compose.service"),
+                compose_dir: labels.remove("com.docker.compose.project.working_dir").map(PathBuf::from),
+                running: container.state.as_deref() == Some("running"),
+            }
+        })
+        .collect())
+}
+
+/// `--profile` arguments for the workspace's Docker Compose profiles
+pub fn compose_profile_args(config: &Config) -> Vec<String> {
+    config
+        .settings
+        .compose_profiles
+        .iter()
+        .flat_map(|profile| ["--profile".to_string(), profile.clone()])
+        .collect()
+}
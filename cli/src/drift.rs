@@ -0,0 +1,132 @@
+use anyhow::Result;
+use std::fmt;
+
+use crate::commands::dev;
+use crate::config::{BuildProfile, Config};
+use crate::git;
+use crate::services::state::StartedServices;
+
+/// One way the workspace differs from what the manifest asks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Drift {
+    /// Started by `dev up` but no longer a service in the manifest
+    Unmanaged { name: String, pid: u32 },
+    NotRunning { name: String },
+    Ports { name: String, expected: Vec<String>, actual: Vec<String> },
+    /// Runs another binary or command, e.g. one built with another profile
+    Command { name: String, expected: String, actual: String },
+    Env { name: String, key: String, expected: Option<String>, actual: Option<String> },
+    Branch { name: String, expected: String, actual: String },
+}
+
+impl Drift {
+    pub fn name(&self) -> &str {
+        match self {
+            Drift::Unmanaged { name, .. }
+            | Drift::NotRunning { name }
+            | Drift::Ports { name, .. }
+            | Drift::Command { name, .. }
+            | Drift::Env { name, .. }
+            | Drift::Branch { name, .. } => name,
+        }
+    }
+
+    /// Whether reconciling means (re)starting the service
+    pub fn needs_restart(&self) -> bool {
+        matches!(
+            self,
+            Drift::NotRunning { .. } | Drift::Ports { .. } | Drift::Command { .. } | Drift::Env { .. }
+        )
+    }
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unset = |value: &Option<String>| value.clone().unwrap_or_else(|| "(unset)".to_string());
+        match self {
+            Drift::Unmanaged { name, pid } => write!(f, "{} is running (pid {}) but not in the manifest", name, pid),
+            Drift::NotRunning { name } => write!(f, "{} is not running", name),
+            Drift::Ports { name, expected, actual } => write!(
+                f,
+                "{} runs on ports [{}], manifest has [{}]",
+                name,
+                actual.join(", "),
+                expected.join(", ")
+            ),
+            Drift::Command { name, expected, actual } => write!(f, "{} runs `{}`, expected `{}`", name, actual, expected),
+            Drift::Env { name, key, expected, actual } => {
+                write!(f, "{} has {}={}, manifest gives {}", name, key, unset(actual), unset(expected))
+            }
+            Drift::Branch { name, expected, actual } => {
+                write!(f, "{} is on branch {}, manifest has {}", name, actual, expected)
+            }
+        }
+    }
+}
+
+/// Compares the manifest, built for `profile`, with the services `dev up`
+/// started and the branches repositories are on
+pub async fn detect(config: &Config, profile: BuildProfile) -> Result<Vec<Drift>> {
+    let started = StartedServices::load(&config.workspace_root)?;
+    let mut repos = config.get_all_repositories();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut drift = Vec::new();
+    for (name, repo) in &repos {
+        let path = config.workspace_root.join(&repo.path);
+        if !path.exists() {
+            continue;
+        }
+        if let Ok(branch) = git::current_branch(&path).await {
+            if branch != repo.branch {
+                drift.push(Drift::Branch { name: name.clone(), expected: repo.branch.clone(), actual: branch });
+            }
+        }
+
+        if !dev::is_service(config, name, repo) {
+            continue;
+        }
+        let Some(running) = started.services.get(name).filter(|service| service.is_running()) else {
+            drift.push(Drift::NotRunning { name: name.clone() });
+            continue;
+        };
+        if running.ports != repo.ports {
+            drift.push(Drift::Ports { name: name.clone(), expected: repo.ports.clone(), actual: running.ports.clone() });
+        }
+        if let Some((command, args)) = dev::service_command(config, repo, profile) {
+            let expected = std::iter::once(command).chain(args).collect::<Vec<_>>().join(" ");
+            let actual = std::iter::once(running.command.clone()).chain(running.args.clone()).collect::<Vec<_>>().join(" ");
+            if expected != actual {
+                drift.push(Drift::Command { name: name.clone(), expected, actual });
+            }
+        }
+
+        let expected = dev::unresolved_env(config, repo);
+        let mut keys: Vec<&String> = expected.keys().chain(running.env.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let (expected, actual) = (expected.get(key), running.env.get(key));
+            if expected != actual {
+                drift.push(Drift::Env {
+                    name: name.clone(),
+                    key: key.clone(),
+                    expected: expected.cloned(),
+                    actual: actual.cloned(),
+                });
+            }
+        }
+    }
+
+    for (name, service) in &started.services {
+        let managed = config
+            .manifest
+            .repositories
+            .get(name)
+            .is_some_and(|repo| dev::is_service(config, name, repo));
+        if !managed && service.is_running() {
+            drift.push(Drift::Unmanaged { name: name.clone(), pid: service.pid });
+        }
+    }
+    Ok(drift)
+}
@@ -0,0 +1,108 @@
+//! Machine-readable lifecycle events for editors and wrapper tools.
+//!
+//! With `--events <path|fd>`, builds, service starts and exits, health
+//! changes and finished executions are written as they happen, one JSON
+//! object per line, each with an `event` name and a `timestamp`. A number
+//! names an inherited file descriptor, so a wrapper can pass a pipe;
+//! anything else is a file to append to. Without the option nothing is
+//! written.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+static SINK: OnceLock<Mutex<File>> = OnceLock::new();
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    BuildStarted {
+        service: &'a str,
+    },
+    BuildFinished {
+        service: &'a str,
+        success: bool,
+        duration_ms: u64,
+    },
+    ServiceStarted {
+        service: &'a str,
+        pid: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        container: Option<&'a str>,
+        ports: &'a [String],
+    },
+    ServiceExited {
+        service: &'a str,
+        exit: &'a str,
+    },
+    HealthChanged {
+        service: &'a str,
+        healthy: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        detail: Option<&'a str>,
+    },
+    ExecutionCompleted {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<&'a str>,
+        /// Submitted file, for `syla exec`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file: Option<&'a str>,
+        status: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+    },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+    timestamp: String,
+}
+
+/// Starts writing events to `target`, a path or a file descriptor number
+pub fn open(target: &str) -> Result<()> {
+    let path = match target.parse::<u32>() {
+        Ok(fd) => format!("/dev/fd/{}", fd),
+        Err(_) => target.to_string(),
+    };
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {} for --events", target))?;
+    let _ = SINK.set(Mutex::new(file));
+    Ok(())
+}
+
+pub fn emit(event: Event) {
+    let Some(sink) = SINK.get() else {
+        return;
+    };
+    let line = Line { event: &event, timestamp: Utc::now().to_rfc3339() };
+    let Ok(mut json) = serde_json::to_string(&line) else {
+        return;
+    };
+    json.push('\n');
+    // A reader that went away must never fail the command
+    if let Ok(mut file) = sink.lock() {
+        let _ = file.write_all(json.as_bytes());
+    }
+}
+
+/// Runs a build between `build_started` and `build_finished` events
+pub fn build(service: &str, build: impl FnOnce() -> Result<bool>) -> Result<bool> {
+    emit(Event::BuildStarted { service });
+    let started = Instant::now();
+    let result = build();
+    emit(Event::BuildFinished {
+        service,
+        success: matches!(result, Ok(true)),
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
+    result
+}
@@ -1,11 +1,25 @@
 use anyhow::{Context, Result};
 use std::path::Path;
+use std::process::Output;
 use tokio::process::Command;
 
+use crate::ui;
+
+/// Runs git, logging the command line for `--verbose`
+async fn git(dir: Option<&Path>, args: &[&str]) -> std::io::Result<Output> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    cmd.args(args);
+    let logged = ui::log_command(cmd.as_std());
+    let output = cmd.output().await?;
+    logged.finished(&output.status);
+    Ok(output)
+}
+
 pub async fn clone(url: &str, path: &Path, branch: &str) -> Result<()> {
-    let output = Command::new("git")
-        .args(&["clone", "-b", branch, url, path.to_str().unwrap()])
-        .output()
+    let output = git(None, &["clone", "-b", branch, url, path.to_str().unwrap()])
         .await
         .context("Failed to execute git clone")?;
 
@@ -18,10 +32,7 @@ pub async fn clone(url: &str, path: &Path, branch: &str) -> Result<()> {
 }
 
 pub async fn status(repo_path: &Path) -> Result<GitStatus> {
-    let output = Command::new("git")
-        .current_dir(repo_path)
-        .args(&["status", "--porcelain", "-b"])
-        .output()
+    let output = git(Some(repo_path), &["status", "--porcelain", "-b"])
         .await
         .context("Failed to execute git status")?;
 
@@ -47,11 +58,35 @@ pub async fn status(repo_path: &Path) -> Result<GitStatus> {
     })
 }
 
+pub async fn current_branch(repo_path: &Path) -> Result<String> {
+    let output = git(Some(repo_path), &["rev-parse", "--abbrev-ref", "HEAD"])
+        .await
+        .context("Failed to execute git rev-parse")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Git rev-parse failed: {}", stderr);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Commit `HEAD` points at
+pub async fn head(repo_path: &Path) -> Result<String> {
+    let output = git(Some(repo_path), &["rev-parse", "HEAD"])
+        .await
+        .context("Failed to execute git rev-parse")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Git rev-parse failed: {}", stderr);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 pub async fn pull(repo_path: &Path) -> Result<()> {
-    let output = Command::new("git")
-        .current_dir(repo_path)
-        .args(&["pull", "--ff-only"])
-        .output()
+    let output = git(Some(repo_path), &["pull", "--ff-only"])
         .await
         .context("Failed to execute git pull")?;
 
@@ -63,6 +98,63 @@ pub async fn pull(repo_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Pulls, replaying local commits on top of upstream. A rebase that stops
+/// on conflicts is aborted, leaving the branch as it was.
+pub async fn pull_rebase(repo_path: &Path) -> Result<()> {
+    let output = git(Some(repo_path), &["pull", "--rebase"])
+        .await
+        .context("Failed to execute git pull")?;
+
+    if !output.status.success() {
+        let _ = git(Some(repo_path), &["rebase", "--abort"]).await;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Git pull --rebase failed: {}", stderr);
+    }
+
+    Ok(())
+}
+
+/// Stashes local changes, untracked files included
+pub async fn stash_push(repo_path: &Path, message: &str) -> Result<()> {
+    let output = git(Some(repo_path), &["stash", "push", "--include-untracked", "-m", message])
+        .await
+        .context("Failed to execute git stash")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Git stash failed: {}", stderr);
+    }
+
+    Ok(())
+}
+
+/// Re-applies the latest stash; on conflicts the stash is kept
+pub async fn stash_pop(repo_path: &Path) -> Result<()> {
+    let output = git(Some(repo_path), &["stash", "pop"])
+        .await
+        .context("Failed to execute git stash pop")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Git stash pop failed: {}", stderr);
+    }
+
+    Ok(())
+}
+
+pub async fn checkout(repo_path: &Path, branch: &str) -> Result<()> {
+    let output = git(Some(repo_path), &["checkout", branch])
+        .await
+        .context("Failed to execute git checkout")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Git checkout failed: {}", stderr);
+    }
+
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct GitStatus {
     pub branch: String,
@@ -0,0 +1,254 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::config;
+use crate::secrets;
+
+/// Answers younger than this are used without asking GitHub again
+const CACHE_TTL: chrono::Duration = chrono::Duration::seconds(60);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Secret holding a token when neither `GITHUB_TOKEN` nor `GH_TOKEN` is set
+pub const TOKEN_SECRET: &str = "github_token";
+
+/// The open pull request for a branch and how its checks are doing
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchStatus {
+    pub pull_request: Option<PullRequest>,
+    pub ci: CiState,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub draft: bool,
+}
+
+/// Checks and commit statuses of a branch's head, combined
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CiState {
+    Passing,
+    Failing,
+    Pending,
+    /// Nothing reported for the commit
+    None,
+}
+
+/// `owner/repo` of a GitHub remote URL, in SSH or HTTPS form
+pub fn repository(url: &str) -> Option<String> {
+    let path = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.strip_prefix("ssh://git@github.com/"))
+        .or_else(|| url.strip_prefix("https://github.com/"))
+        .or_else(|| url.strip_prefix("http://github.com/"))?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    let (owner, name) = path.split_once('/')?;
+    (!owner.is_empty() && !name.is_empty() && !name.contains('/')).then(|| path.to_string())
+}
+
+/// A response as last seen, with the ETag to revalidate it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    etag: Option<String>,
+    fetched_at: chrono::DateTime<chrono::Utc>,
+    body: serde_json::Value,
+}
+
+/// GitHub answers kept in `~/.syla/github-cache.json`, so repeated
+/// `status --github` calls stay well inside the rate limit
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cache {
+    #[serde(default)]
+    entries: BTreeMap<String, CacheEntry>,
+    /// Set when GitHub reports the rate limit exhausted; until then only
+    /// cached answers are used
+    rate_limited_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Cache {
+    fn path() -> PathBuf {
+        config::user_dir().join("github-cache.json")
+    }
+
+    fn load() -> Self {
+        std::fs::read(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_vec(self)?).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// A GitHub API client that answers from its cache when it can
+pub struct GitHub {
+    client: reqwest::Client,
+    api: String,
+    token: Option<String>,
+    cache: Mutex<Cache>,
+}
+
+impl GitHub {
+    /// Uses `GITHUB_TOKEN`, `GH_TOKEN` or the `github_token` secret when
+    /// set; without one only public repositories can be queried. The API
+    /// base can be changed with `SYLA_GITHUB_API`, e.g. for GitHub Enterprise.
+    pub fn new() -> Result<Self> {
+        let token = std::env::var("GITHUB_TOKEN")
+            .or_else(|_| std::env::var("GH_TOKEN"))
+            .ok()
+            .filter(|token| !token.is_empty())
+            .or_else(|| secrets::lookup(TOKEN_SECRET));
+        let api = std::env::var("SYLA_GITHUB_API").unwrap_or_else(|_| "https://api.github.com".to_string());
+        Ok(Self {
+            client: reqwest::Client::builder().timeout(REQUEST_TIMEOUT).user_agent("syla-cli").build()?,
+            api: api.trim_end_matches('/').to_string(),
+            token,
+            cache: Mutex::new(Cache::load()),
+        })
+    }
+
+    /// When the rate limit resets, if it ran out
+    pub async fn rate_limited_until(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.cache.lock().await.rate_limited_until.filter(|until| *until > chrono::Utc::now())
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        self.cache.lock().await.save()
+    }
+
+    /// The open pull request for `branch` of `repository` (`owner/repo`)
+    /// and the CI state of the branch
+    pub async fn branch_status(&self, repository: &str, branch: &str) -> Result<BranchStatus> {
+        let owner = repository.split('/').next().unwrap_or_default();
+        let pulls = self
+            .get(&format!("/repos/{}/pulls", repository), &[("head", &format!("{}:{}", owner, branch)), ("state", "open")])
+            .await?;
+        let pull_request = pulls.as_array().and_then(|pulls| pulls.first()).map(|pull| PullRequest {
+            number: pull["number"].as_u64().unwrap_or_default(),
+            title: pull["title"].as_str().unwrap_or_default().to_string(),
+            url: pull["html_url"].as_str().unwrap_or_default().to_string(),
+            draft: pull["draft"].as_bool().unwrap_or(false),
+        });
+
+        let reference = branch.replace('%', "%25").replace('/', "%2F");
+        let check_runs = self.get(&format!("/repos/{}/commits/{}/check-runs", repository, reference), &[]).await?;
+        let statuses = self.get(&format!("/repos/{}/commits/{}/status", repository, reference), &[]).await?;
+        Ok(BranchStatus { pull_request, ci: ci_state(&check_runs, &statuses) })
+    }
+
+    async fn get(&self, path: &str, query: &[(&str, &str)]) -> Result<serde_json::Value> {
+        let url = reqwest::Url::parse_with_params(&format!("{}{}", self.api, path), query)
+            .with_context(|| format!("Invalid GitHub URL for {}", path))?;
+        let key = url.to_string();
+        let now = chrono::Utc::now();
+
+        let cached = {
+            let cache = self.cache.lock().await;
+            let cached = cache.entries.get(&key).cloned();
+            if let Some(entry) = cached.as_ref().filter(|entry| now - entry.fetched_at < CACHE_TTL) {
+                return Ok(entry.body.clone());
+            }
+            if let Some(until) = cache.rate_limited_until.filter(|until| *until > now) {
+                return match cached {
+                    Some(entry) => Ok(entry.body),
+                    None => anyhow::bail!("GitHub rate limit reached until {}", until.with_timezone(&chrono::Local).format("%H:%M")),
+                };
+            }
+            cached
+        };
+
+        let mut request = self.client.get(url).header("Accept", "application/vnd.github+json");
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        // A 304 for a revalidated answer doesn't count against the limit
+        if let Some(etag) = cached.as_ref().and_then(|entry| entry.etag.as_deref()) {
+            request = request.header("If-None-Match", etag);
+        }
+        tracing::debug!("GET {}", key);
+        let response = request.send().await.context("Failed to reach GitHub")?;
+
+        let status = response.status();
+        let header = |name: &str| response.headers().get(name).and_then(|value| value.to_str().ok()).map(String::from);
+        let etag = header("etag");
+        let remaining = header("x-ratelimit-remaining");
+        let reset = header("x-ratelimit-reset").and_then(|reset| reset.parse::<i64>().ok());
+
+        let mut cache = self.cache.lock().await;
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(mut entry) = cached {
+                entry.fetched_at = now;
+                let body = entry.body.clone();
+                cache.entries.insert(key, entry);
+                return Ok(body);
+            }
+        }
+        let limited = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || (status == reqwest::StatusCode::FORBIDDEN && remaining.as_deref() == Some("0"));
+        if limited {
+            let until = reset
+                .and_then(|reset| chrono::DateTime::from_timestamp(reset, 0))
+                .unwrap_or(now + chrono::Duration::minutes(1));
+            cache.rate_limited_until = Some(until);
+            return match cached {
+                Some(entry) => Ok(entry.body),
+                None => anyhow::bail!("GitHub rate limit reached until {}", until.with_timezone(&chrono::Local).format("%H:%M")),
+            };
+        }
+        if !status.is_success() {
+            anyhow::bail!("GitHub answered {} for {}", status, path);
+        }
+        drop(cache);
+
+        let body: serde_json::Value = response.json().await.context("Invalid response from GitHub")?;
+        self.cache
+            .lock()
+            .await
+            .entries
+            .insert(key, CacheEntry { etag, fetched_at: now, body: body.clone() });
+        Ok(body)
+    }
+}
+
+/// Failing if anything failed, pending while anything runs, passing once
+/// everything reported succeeded
+fn ci_state(check_runs: &serde_json::Value, statuses: &serde_json::Value) -> CiState {
+    let mut states = Vec::new();
+    for run in check_runs["check_runs"].as_array().into_iter().flatten() {
+        states.push(match (run["status"].as_str(), run["conclusion"].as_str()) {
+            (Some("completed"), Some("success" | "neutral" | "skipped")) => CiState::Passing,
+            (Some("completed"), _) => CiState::Failing,
+            _ => CiState::Pending,
+        });
+    }
+    if statuses["total_count"].as_u64().unwrap_or(0) > 0 {
+        states.push(match statuses["state"].as_str() {
+            Some("success") => CiState::Passing,
+            Some("pending") => CiState::Pending,
+            _ => CiState::Failing,
+        });
+    }
+    if states.is_empty() {
+        CiState::None
+    } else if states.contains(&CiState::Failing) {
+        CiState::Failing
+    } else if states.contains(&CiState::Pending) {
+        CiState::Pending
+    } else {
+        CiState::Passing
+    }
+}
@@ -0,0 +1,86 @@
+//! Local record of state-changing commands.
+//!
+//! Each `init`, `dev up`, `dev restart`, `config set` and the like appends
+//! one JSON line to `.platform/state/history.log` with who ran it, the full
+//! command line and how it ended, for `syla history` to search. Services
+//! started, stopped or restarted through the supervisor on behalf of the
+//! dashboard, `syla serve` or a control socket client are recorded too.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub timestamp: DateTime<Utc>,
+    pub user: String,
+    /// Subcommand path, e.g. `dev restart`
+    pub command: String,
+    /// Arguments as given, after the program name
+    pub args: Vec<String>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+fn path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(".platform/state/history.log")
+}
+
+/// Appends a finished command. Never fails the command it records; a
+/// workspace that can't be found or written to is skipped.
+pub fn record(workspace_root: Option<PathBuf>, command: &str, duration: Duration, result: &Result<()>) {
+    let Ok(workspace_root) = crate::config::resolve_workspace_root(workspace_root) else {
+        return;
+    };
+    if !workspace_root.join(".platform").is_dir() {
+        return;
+    }
+    let error = result.as_ref().err().map(|e| format!("{:#}", e));
+    record_action(&workspace_root, command, std::env::args().skip(1).collect(), duration, error);
+}
+
+/// Appends an action that didn't come from this process's command line,
+/// e.g. `dashboard restart` with the service as its argument
+pub fn record_action(workspace_root: &Path, command: &str, args: Vec<String>, duration: Duration, error: Option<String>) {
+    let entry = Entry {
+        timestamp: Utc::now(),
+        user: std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_else(|_| "unknown".to_string()),
+        command: command.to_string(),
+        args,
+        success: error.is_none(),
+        error,
+        duration_ms: duration.as_millis() as u64,
+    };
+    if let Err(e) = append(workspace_root, &entry) {
+        tracing::debug!("Failed to record history: {:#}", e);
+    }
+}
+
+fn append(workspace_root: &Path, entry: &Entry) -> Result<()> {
+    let path = path(workspace_root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Every recorded command, oldest first; unreadable lines are skipped
+pub fn load(workspace_root: &Path) -> Result<Vec<Entry>> {
+    let path = path(workspace_root);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
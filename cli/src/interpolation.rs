@@ -0,0 +1,201 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::commands::dev::{host_port, infra_env};
+use crate::config::RepoManifest;
+use crate::ports::{PortAllocations, AUTO};
+
+/// What `${...}` references in manifest values resolve against
+struct Scope<'a> {
+    workspace_root: &'a Path,
+    /// First host port of each repository and infrastructure component;
+    /// `None` while an `auto` port is unassigned
+    ports: HashMap<&'a str, Option<String>>,
+}
+
+/// Expands `${workspace_root}`, `${env:VAR}` (or `${env:VAR:-default}`) and
+/// `${ports.<name>}` in every manifest value. Endpoint references are left
+/// for `resolve_env`, as are other `${...}` such as shell variables in task
+/// commands, and `$${` is a literal `${`.
+pub fn resolve(manifest: &mut RepoManifest, workspace_root: &Path, allocations: &PortAllocations) -> Result<()> {
+    let mut ports = HashMap::new();
+    for (name, repo) in &manifest.repositories {
+        let port = match repo.ports.first().map(String::as_str) {
+            Some(AUTO) => allocations.get(name).and_then(|ports| ports.first()).map(u16::to_string),
+            Some(port) => host_port(port).map(String::from),
+            None => continue,
+        };
+        ports.insert(name.as_str(), port);
+    }
+    for (name, infra) in &manifest.infrastructure {
+        if let Some(port) = infra.ports.first().and_then(|p| host_port(p)) {
+            ports.insert(name.as_str(), Some(port.to_string()));
+        }
+    }
+    let scope = Scope { workspace_root, ports };
+
+    let mut value = toml::Value::try_from(&*manifest).context("Failed to read back the manifest")?;
+    if expand(&mut value, &mut Vec::new(), &scope)? {
+        *manifest = value.try_into().context("Manifest is invalid after interpolation")?;
+    }
+    Ok(())
+}
+
+/// Expands the strings in a value, returning whether any changed
+fn expand(value: &mut toml::Value, path: &mut Vec<String>, scope: &Scope) -> Result<bool> {
+    let mut changed = false;
+    match value {
+        toml::Value::String(s) => {
+            if let Some(expanded) = expand_str(s, |reference| lookup(reference, scope), is_endpoint).with_context(|| format!("In manifest value {}", path.join(".")))? {
+                *s = expanded;
+                changed = true;
+            }
+        }
+        toml::Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                path.push(format!("[{}]", i));
+                changed |= expand(item, path, scope)?;
+                path.pop();
+            }
+        }
+        toml::Value::Table(table) => {
+            for (key, item) in table.iter_mut() {
+                path.push(if key.contains('.') { format!("\"{}\"", key) } else { key.clone() });
+                changed |= expand(item, path, scope)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(changed)
+}
+
+/// The string with its references expanded, or `None` when it has none.
+/// Escapes in front of `deferred` references are kept for a later pass.
+fn expand_str(
+    s: &str,
+    lookup: impl Fn(&str) -> Result<Option<String>>,
+    deferred: impl Fn(&str) -> bool,
+) -> Result<Option<String>> {
+    if !s.contains("${") {
+        return Ok(None);
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    let mut changed = false;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            let escaped = if deferred(&rest[start + 2..]) { "$${" } else { "${" };
+            out.push_str(&rest[..start - 1]);
+            out.push_str(escaped);
+            rest = &rest[start + 2..];
+            changed = true;
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            anyhow::bail!("Unterminated reference in '{}'", s);
+        };
+        let reference = &rest[start + 2..start + len];
+        match lookup(reference)? {
+            Some(resolved) => {
+                out.push_str(&resolved);
+                changed = true;
+            }
+            None => out.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(changed.then_some(out))
+}
+
+/// The value of a reference, or `None` for references that aren't ours or
+/// can't be resolved yet
+fn lookup(reference: &str, scope: &Scope) -> Result<Option<String>> {
+    if reference == "workspace_root" {
+        return Ok(Some(scope.workspace_root.display().to_string()));
+    }
+    if let Some(var) = reference.strip_prefix("env:") {
+        let (var, default) = match var.split_once(":-") {
+            Some((var, default)) => (var, Some(default)),
+            None => (var, None),
+        };
+        return match (std::env::var(var), default) {
+            (Ok(value), _) => Ok(Some(value)),
+            (Err(_), Some(default)) => Ok(Some(default.to_string())),
+            (Err(_), None) => anyhow::bail!("${{env:{}}} is not set; export it or use ${{env:{}:-default}}", var, var),
+        };
+    }
+    if let Some(name) = reference.strip_prefix("ports.") {
+        let name = name.strip_prefix("infrastructure.").unwrap_or(name);
+        return match scope.ports.get(name) {
+            Some(port) => Ok(port.clone()),
+            None => anyhow::bail!("${{ports.{}}} does not name a repository or infrastructure component with ports", name),
+        };
+    }
+    Ok(None)
+}
+
+/// Whether a reference (or text starting with one) is `service.` or
+/// `infra.`, which only `resolve_env` expands
+fn is_endpoint(reference: &str) -> bool {
+    reference.starts_with("service.") || reference.starts_with("infra.")
+}
+
+/// Expands `${service.<name>.<field>}` and `${infra.<name>.<field>}` in
+/// service environment values, where the field is `host`, `port` or `url`.
+/// Runs when a service is spawned, after `auto` ports are assigned, so
+/// values follow wherever the other side actually listens.
+pub fn resolve_env(env: &mut HashMap<String, String>, manifest: &RepoManifest) -> Result<()> {
+    for (key, value) in env.iter_mut() {
+        let lookup = |reference: &str| match is_endpoint(reference) {
+            true => endpoint(reference, manifest).map(Some),
+            false => Ok(None),
+        };
+        if let Some(expanded) = expand_str(value, lookup, |_| false).with_context(|| format!("In environment variable {}", key))? {
+            *value = expanded;
+        }
+    }
+    Ok(())
+}
+
+/// Resolves one `service.<name>.<field>` or `infra.<name>.<field>`
+fn endpoint(reference: &str, manifest: &RepoManifest) -> Result<String> {
+    let Some((target, field)) = reference.rsplit_once('.') else {
+        anyhow::bail!("${{{}}} needs a field: host, port or url", reference);
+    };
+    let (ports, url) = match target.split_once('.') {
+        Some(("service", name)) => {
+            let repo = manifest
+                .repositories
+                .get(name)
+                .with_context(|| format!("${{{}}}: there is no repository named {}", reference, name))?;
+            (&repo.ports, None)
+        }
+        Some(("infra", name)) => {
+            let infra = manifest
+                .infrastructure
+                .get(name)
+                .with_context(|| format!("${{{}}}: there is no infrastructure named {}", reference, name))?;
+            (&infra.ports, infra_env(name, infra).into_iter().next().map(|(_, url)| url))
+        }
+        _ => anyhow::bail!("${{{}}} names no service or infrastructure", reference),
+    };
+    let port = || match ports.first() {
+        Some(port) => host_port(port)
+            .map(str::to_string)
+            .with_context(|| format!("${{{}}}: port {} has not been assigned yet", reference, port)),
+        None => anyhow::bail!("${{{}}}: {} declares no ports", reference, target),
+    };
+    match field {
+        "host" => Ok("localhost".to_string()),
+        "port" => port(),
+        "url" => match url {
+            Some(url) => Ok(url),
+            None => Ok(format!("http://localhost:{}", port()?)),
+        },
+        _ => anyhow::bail!("${{{}}}: unknown field '{}'; use host, port or url", reference, field),
+    }
+}
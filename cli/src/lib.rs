@@ -1,9 +1,32 @@
+#[macro_use]
+pub mod ui;
+
+pub mod build_plan;
+pub mod changes;
 pub mod commands;
 pub mod config;
+pub mod control;
+pub mod debug_log;
 pub mod docker;
+pub mod drift;
+pub mod events;
 pub mod git;
+pub mod github;
+pub mod history;
+pub mod interpolation;
+pub mod lock;
+pub mod names;
+pub mod notifications;
+pub mod otel;
 pub mod platform;
+pub mod ports;
+pub mod runtime;
+pub mod secrets;
 pub mod services;
+pub mod telemetry;
+pub mod tunnels;
+pub mod watch;
+pub mod websocket;
 
 // Re-export commonly used types
 pub use config::Config;
@@ -23,33 +46,57 @@ pub enum DevCommands {
         /// Detached mode
         #[clap(short, long)]
         detach: bool,
+
+        /// Run binaries built with this profile (default: manifest's build_profile, else release)
+        #[clap(long, value_enum)]
+        profile: Option<crate::config::BuildProfile>,
+
+        /// Only repositories with any of these tags (comma-separated)
+        #[clap(long, value_delimiter = ',')]
+        tags: Vec<String>,
     },
 
     /// Stop development environment
     Down {
         /// Remove volumes
-        #[clap(short, long)]
+        #[clap(long)]
         volumes: bool,
     },
 
     /// Show service logs
     Logs {
-        /// Service path (e.g., syla/core/api-gateway)
-        service: String,
+        /// Service path (e.g., syla/core/api-gateway); asks when omitted
+        service: Option<String>,
 
         /// Follow log output
         #[clap(short, long)]
         follow: bool,
 
-        /// Number of lines to show
+        /// Number of lines to show; matches, with --grep
         #[arg(short = 'n', long, default_value = "100")]
         lines: usize,
+
+        /// Only show entries whose message matches this regex, highlighting the match
+        #[arg(long, value_name = "REGEX")]
+        grep: Option<String>,
+
+        /// Entries to show before and after each --grep match
+        #[arg(short = 'C', long, value_name = "N", default_value = "0", requires = "grep")]
+        context: usize,
+
+        /// How to print entries: pretty, json (one entry per line) or the raw line
+        #[arg(long, value_enum, default_value = "pretty")]
+        format: crate::services::log_streamer::LogFormat,
+
+        /// Only show entries at this level or above
+        #[arg(long, value_enum, value_name = "LEVEL")]
+        level: Option<crate::services::log_streamer::LogLevel>,
     },
 
     /// Restart a service
     Restart {
-        /// Service path
-        service: String,
+        /// Service path; asks when omitted
+        service: Option<String>,
     },
 
     /// Show development environment status
@@ -68,6 +115,15 @@ pub enum DevCommands {
         /// Run integration tests
         #[clap(long)]
         integration: bool,
+
+        /// Non-interactive run for pipelines: GitHub annotations and an
+        /// exit code per failure category
+        #[clap(long)]
+        ci: bool,
+
+        /// Write results as JUnit XML to this file
+        #[clap(long, value_name = "PATH")]
+        junit: Option<std::path::PathBuf>,
     },
 
     /// Watch for changes and auto-rebuild/restart
@@ -79,6 +135,14 @@ pub enum DevCommands {
         /// Build only, don't restart
         #[clap(long)]
         build_only: bool,
+
+        /// Run the changed service's tests after building
+        #[clap(long)]
+        test: bool,
+
+        /// Run tests instead of building and restarting
+        #[clap(long, conflicts_with = "build_only")]
+        test_only: bool,
     },
 
     /// Build changed services
@@ -86,6 +150,106 @@ pub enum DevCommands {
         /// Force rebuild all
         #[clap(long)]
         all: bool,
+
+        /// Count changes relative to this git ref instead of the last build
+        #[clap(long, value_name = "REF", conflicts_with = "all")]
+        since: Option<String>,
+
+        /// List what would be built without building it
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Cargo profile to build with (default: manifest's build_profile, else release)
+        #[clap(long, value_enum)]
+        profile: Option<crate::config::BuildProfile>,
+
+        /// Only repositories with any of these tags (comma-separated)
+        #[clap(long, value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Services built at once; dependencies still build first
+        #[clap(short, long, default_value = "2")]
+        jobs: usize,
+    },
+
+    /// Compare running services and checked-out branches with the manifest
+    Diff {
+        /// Profile services should run with (default: manifest's build_profile, else release)
+        #[clap(long, value_enum)]
+        profile: Option<crate::config::BuildProfile>,
+
+        /// Stop, restart and check out whatever differs
+        #[clap(long)]
+        apply: bool,
+    },
+
+    /// Diagnose why a service is down: build, process, ports, env, dependencies, health
+    Doctor {
+        /// Service name (e.g., syla.core.api-gateway); asks when omitted
+        service: Option<String>,
+
+        /// Profile the service should be built with (default: manifest's build_profile, else release)
+        #[clap(long, value_enum)]
+        profile: Option<crate::config::BuildProfile>,
+    },
+
+    /// Write a service's environment to <repo>/.env for IDE launches
+    Envfile {
+        /// Service name (e.g., syla.core.execution-service); asks when omitted
+        service: Option<String>,
+
+        /// Print to stdout instead of writing the file
+        #[clap(long)]
+        print: bool,
+    },
+
+    /// Expose a service at a public URL, e.g. for webhooks or demos
+    Expose {
+        /// Service name (e.g., syla.core.api-gateway); asks when omitted
+        service: Option<String>,
+
+        /// Tunnel program (default: the first of cloudflared, ngrok, ssh found)
+        #[clap(long, value_enum)]
+        driver: Option<crate::tunnels::TunnelDriver>,
+
+        /// Local port to expose instead of the service's first port
+        #[clap(long)]
+        port: Option<u16>,
+
+        /// Close the service's tunnel instead
+        #[clap(long, conflicts_with_all = ["driver", "port"])]
+        stop: bool,
+    },
+    /// Snapshot the workspace's Postgres databases and Redis data
+    Backup {
+        /// Backup name (default: the current date and time)
+        name: Option<String>,
+    },
+
+    /// Restore a backup taken with `dev backup`, replacing the current data
+    Restore {
+        /// Backup name; asks when omitted
+        name: Option<String>,
+
+        /// Skip the confirmation prompt
+        #[clap(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Keep services running and take start/stop/health/log requests on a
+    /// unix socket until interrupted
+    Supervise {
+        /// Socket to listen on (default: .platform/state/control.sock)
+        #[clap(long)]
+        socket: Option<std::path::PathBuf>,
+    },
+
+    /// Uptime, incidents, recovery time and slow health checks per service,
+    /// from the history `dev supervise` records
+    Report {
+        /// How far back to look, e.g. 90m, 24h or 7d
+        #[clap(long, default_value = "24h")]
+        since: String,
     },
 }
 
@@ -105,7 +269,7 @@ pub enum PlatformCommands {
         /// Platform name
         platform: String,
 
-        /// Start with dependencies
+        /// Also start services from other platforms that this one depends on
         #[clap(long)]
         with_deps: bool,
     },
@@ -125,4 +289,264 @@ pub enum PlatformCommands {
         #[clap(long)]
         integration: bool,
     },
-}
\ No newline at end of file
+}
+#[derive(Subcommand)]
+pub enum DaemonCommands {
+    /// Start the daemon in the background, if it isn't running
+    Start,
+
+    /// Stop the daemon and the services it supervises
+    Stop,
+
+    /// Show whether the daemon is running and what it supervises
+    Status,
+
+    /// Run the daemon in the foreground
+    Run,
+}
+
+#[derive(Subcommand)]
+pub enum PluginCommands {
+    /// List plugins found in the plugin directory and on PATH
+    List,
+
+    /// Install a plugin from a local executable, crate name or git URL
+    Install {
+        /// Path to a syla-<name> executable, a crate name or a git URL
+        source: String,
+
+        /// Replace an existing installation
+        #[clap(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TelemetryCommands {
+    /// Show whether usage metrics are recorded and what is pending
+    Status,
+
+    /// Opt in to anonymous usage metrics
+    Enable {
+        /// URL pending events are uploaded to
+        #[clap(long)]
+        endpoint: Option<String>,
+    },
+
+    /// Opt out and delete pending events
+    Disable,
+}
+
+#[derive(Subcommand)]
+pub enum SecretsCommands {
+    /// Store a secret; prompts for the value when it is not given
+    Set {
+        /// Secret name
+        name: String,
+
+        /// Secret value (read from stdin or a prompt if omitted)
+        value: Option<String>,
+    },
+
+    /// Print a secret's value
+    Get {
+        /// Secret name
+        name: String,
+    },
+
+    /// List secret names and which services use them
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ManifestCommands {
+    /// Check repos.toml for unknown dependencies, cycles, clashing paths
+    /// and ports, and other mistakes parsing lets through
+    Lint {
+        /// Fail on warnings too
+        #[clap(long)]
+        strict: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExecutionsCommands {
+    /// Re-run a stored execution with the same code, inputs and limits
+    Replay {
+        /// Execution ID
+        id: String,
+
+        /// Relax the time limit and output cap and record where it ran
+        #[clap(long)]
+        debug: bool,
+
+        /// Print the new execution's ID without waiting for it
+        #[clap(long)]
+        no_wait: bool,
+
+        /// Execution service to ask (default: the workspace's)
+        #[clap(long, env = "SYLA_EXECUTION_URL")]
+        url: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ApiCommands {
+    /// Send a request to an HTTP path or gRPC method of a service
+    Call {
+        /// Service name
+        service: String,
+
+        /// HTTP path such as `/health`, or gRPC method such as
+        /// `CreateExecution` or `ExecutionService/CreateExecution`
+        target: String,
+
+        /// Request body as JSON; `@file` reads it from a file, `@-` from stdin
+        #[clap(short, long)]
+        data: Option<String>,
+
+        /// HTTP method (default: GET, or POST with --data)
+        #[clap(short = 'X', long)]
+        method: Option<String>,
+
+        /// Extra header or gRPC metadata as `name: value`
+        #[clap(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// Port to call instead of the service's first one
+        #[clap(long)]
+        port: Option<u16>,
+    },
+
+    /// List the gRPC methods in a service's protos
+    List {
+        /// Service name
+        service: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ContractCommands {
+    /// Verify consumer/provider contracts declared in the manifest
+    Test {
+        /// Contracts to check (all if not specified)
+        contracts: Vec<String>,
+
+        /// Only verify one side
+        #[clap(long, value_enum)]
+        only: Option<crate::commands::contract::ContractSide>,
+
+        /// Fail instead of starting providers that aren't running
+        #[clap(long)]
+        no_start: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProtoCommands {
+    /// List each repository's proto files and how its code is generated
+    List,
+
+    /// Generate code with buf or protoc
+    Generate {
+        /// Only this repository
+        service: Option<String>,
+    },
+
+    /// Fail if generated code is stale or shared protos have drifted apart
+    Check {
+        /// Only this repository
+        service: Option<String>,
+    },
+
+    /// Detect changes that break clients built against another branch
+    Breaking {
+        /// Only this repository
+        service: Option<String>,
+
+        /// Branch or ref to compare with (default: the repository's branch)
+        #[clap(long)]
+        against: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DbCommands {
+    /// Run pending migrations, dependencies first
+    Migrate {
+        /// Only this service and the services it depends on
+        service: Option<String>,
+    },
+
+    /// Show each service's schema version and pending migrations
+    Status,
+
+    /// Drop the workspace database schema and migrate from scratch
+    Reset {
+        /// Skip the confirmation prompt
+        #[clap(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Show every setting, its value and whether it comes from the
+    /// defaults, the user config (~/.syla/config.toml) or the workspace
+    Show,
+
+    /// Set a configuration value in the user config, e.g. `dev.default_platform syla`
+    Set {
+        /// Configuration key, as a dot path
+        key: String,
+
+        /// Configuration value; lists are comma-separated
+        value: String,
+
+        /// Write to the workspace's settings instead of the user config
+        #[clap(long)]
+        local: bool,
+    },
+
+    /// Get a configuration value, with the workspace's settings over the user config
+    Get {
+        /// Configuration key, as a dot path
+        key: String,
+    },
+
+    /// Remove a configuration value from the user config
+    Unset {
+        /// Configuration key, as a dot path
+        key: String,
+
+        /// Remove it from the workspace's settings instead
+        #[clap(long)]
+        local: bool,
+    },
+
+    /// Turn on optional repositories gated by feature flags
+    Features {
+        #[clap(subcommand)]
+        command: FeatureCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum FeatureCommands {
+    /// List features and the repositories each one brings in
+    List,
+
+    /// Enable features so init clones and dev up starts their repositories
+    Enable {
+        /// Feature names
+        #[clap(required = true)]
+        features: Vec<String>,
+    },
+
+    /// Disable features again
+    Disable {
+        /// Feature names
+        #[clap(required = true)]
+        features: Vec<String>,
+    },
+}
@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::tunnels::is_alive;
+
+/// How often `--wait` checks whether the lock was released
+const WAIT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// After this long an unreadable lock file is taken to be left over
+const UNREADABLE_GRACE: Duration = Duration::from_secs(5);
+
+/// Set by `--wait`
+static WAIT: AtomicBool = AtomicBool::new(false);
+
+/// Wait for a held lock instead of failing
+pub fn set_wait(wait: bool) {
+    WAIT.store(wait, Ordering::Relaxed);
+}
+
+/// Who holds the workspace lock, as written to the lock file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Holder {
+    pid: u32,
+    command: String,
+    since: chrono::DateTime<chrono::Utc>,
+}
+
+/// Advisory lock on `.platform/state/workspace.lock`, held by commands that
+/// clone, build, or start and stop services so two of them don't race.
+/// Released on drop; a lock left by a process that died is taken over.
+pub struct WorkspaceLock {
+    path: PathBuf,
+}
+
+impl WorkspaceLock {
+    fn path(workspace_root: &Path) -> PathBuf {
+        workspace_root.join(".platform/state/workspace.lock")
+    }
+
+    /// Takes the lock for `command` (e.g. "dev up"), waiting for the holder
+    /// to finish with `--wait`
+    pub async fn acquire(workspace_root: &Path, command: &str) -> Result<Self> {
+        let path = Self::path(workspace_root);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let holder = Holder { pid: std::process::id(), command: command.to_string(), since: chrono::Utc::now() };
+        let content = toml::to_string(&holder)?;
+
+        let mut announced = false;
+        loop {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(content.as_bytes())
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    tracing::debug!("Took workspace lock for {}", command);
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e).with_context(|| format!("Failed to create {}", path.display())),
+            }
+
+            let Some(current) = read_holder(&path) else {
+                // Either being written right now, or left half-written by a
+                // process that died in between
+                let age = std::fs::metadata(&path).and_then(|m| m.modified()).ok().and_then(|m| m.elapsed().ok());
+                if age.is_some_and(|age| age > UNREADABLE_GRACE) {
+                    let _ = std::fs::remove_file(&path);
+                } else {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                continue;
+            };
+            if !is_alive(current.pid) {
+                tracing::debug!("Removing stale workspace lock of pid {}", current.pid);
+                let _ = std::fs::remove_file(&path);
+                continue;
+            }
+            let held = format!(
+                "Workspace is locked by `syla {}` (held by pid {} since {})",
+                current.command,
+                current.pid,
+                current.since.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S")
+            );
+            if !WAIT.load(Ordering::Relaxed) {
+                anyhow::bail!("{}; pass --wait to wait for it", held);
+            }
+            if !announced {
+                eprintln!("{}; waiting...", held);
+                announced = true;
+            }
+            tokio::time::sleep(WAIT_INTERVAL).await;
+        }
+    }
+}
+
+impl Drop for WorkspaceLock {
+    fn drop(&mut self) {
+        // Only remove the lock if it is still ours
+        if read_holder(&self.path).is_some_and(|holder| holder.pid == std::process::id()) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn read_holder(path: &Path) -> Option<Holder> {
+    let content = std::fs::read_to_string(path).ok()?;
+    toml::from_str(&content).ok()
+}
@@ -1,16 +1,45 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use colored::Colorize;
+use std::ffi::OsString;
 use std::path::PathBuf;
+use std::time::Instant;
+use tracing::Instrument;
 
+#[macro_use]
+mod ui;
+
+mod build_plan;
+mod changes;
 mod commands;
 mod config;
+mod control;
+mod debug_log;
 mod docker;
+mod drift;
+mod events;
 mod git;
+mod github;
+mod history;
+mod interpolation;
+mod lock;
+mod names;
+mod notifications;
+mod otel;
 mod platform;
+mod ports;
+mod runtime;
+mod secrets;
 mod services;
+mod telemetry;
+mod tunnels;
+mod watch;
+mod websocket;
 
-use commands::{dev, doctor, init, platform as platform_cmd, status};
+use commands::{
+    api, audit, bench, completions, config as config_cmd, contract, daemon, dashboard, db, dev, discover, doctor, exec, executions, history as history_cmd, init, manifest, platform as platform_cmd, plugin, proto, release, run as run_cmd,
+    secrets as secrets_cmd, serve, status, telemetry as telemetry_cmd, upgrade, why, OutputFormat,
+};
 
 #[derive(Parser)]
 #[command(name = "syla")]
@@ -25,9 +54,29 @@ struct Cli {
     #[arg(short, long, global = true)]
     workspace: Option<PathBuf>,
 
-    /// Verbose output
+    /// Verbose output: commands being run and how long each step took
     #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// Only print errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print status, doctor, dev status and platform list as JSON or YAML
+    #[arg(short, long, global = true, value_enum)]
+    output: Option<OutputFormat>,
+
+    /// Never prompt; fail when a name is missing or matches several entries
+    #[arg(long, global = true, env = "SYLA_NON_INTERACTIVE")]
+    non_interactive: bool,
+
+    /// Wait for another syla command holding the workspace lock to finish
+    #[arg(long, global = true)]
+    wait: bool,
+
+    /// Write lifecycle events as NDJSON to a file or inherited descriptor
+    #[arg(long, global = true, value_name = "PATH|FD", env = "SYLA_EVENTS")]
+    events: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -38,6 +87,10 @@ enum Commands {
         #[arg(short, long)]
         platform: Option<String>,
 
+        /// Workspace template selecting repos, compose profiles and defaults
+        #[arg(short, long, conflicts_with = "platform")]
+        template: Option<String>,
+
         /// Skip confirmation prompts
         #[arg(short = 'y', long)]
         yes: bool,
@@ -45,6 +98,14 @@ enum Commands {
         /// Force re-initialization (re-clone repos, rebuild services)
         #[arg(short, long)]
         force: bool,
+
+        /// Cargo profile to build services with (default: manifest's build_profile, else release)
+        #[arg(long, value_enum)]
+        profile: Option<config::BuildProfile>,
+
+        /// Only repositories with any of these tags (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
     },
 
     /// Show status of all repositories and services
@@ -52,6 +113,30 @@ enum Commands {
         /// Show detailed status
         #[arg(short, long)]
         detailed: bool,
+
+        /// Only repositories with any of these tags (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Add each branch's open pull request and CI state from GitHub
+        #[arg(long)]
+        github: bool,
+    },
+
+    /// Pull every repository, rebuild what changed, run pending migrations
+    /// and restart the affected services
+    Upgrade {
+        /// What to do with repositories that have local changes or diverged from upstream
+        #[arg(long, value_enum, default_value = "skip")]
+        strategy: upgrade::SyncStrategy,
+
+        /// Cargo profile to build services with (default: manifest's build_profile, else release)
+        #[arg(long, value_enum)]
+        profile: Option<config::BuildProfile>,
+
+        /// Only repositories with any of these tags (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
     },
 
     /// Platform-specific operations
@@ -66,11 +151,48 @@ enum Commands {
         command: DevCommands,
     },
 
+    /// Background supervisor that keeps services running after `dev up
+    /// --detach` returns
+    Daemon {
+        #[command(subcommand)]
+        command: DaemonCommands,
+    },
+
+    /// Interactive dashboard of repos, services, infrastructure and logs
+    Dashboard,
+
+    /// Serve workspace status, service control, logs and executions as
+    /// JSON-RPC over a WebSocket or HTTP, for editors and dashboards
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Port to listen on
+        #[arg(long, default_value_t = 7777)]
+        port: u16,
+
+        /// Browser origin to accept besides localhost, e.g. a VS Code webview
+        #[arg(long = "allow-origin", value_name = "ORIGIN")]
+        allow_origins: Vec<String>,
+    },
+
+    /// Explain a service's dependencies, dependents and templates
+    Why {
+        /// Service or infrastructure name
+        service: String,
+    },
+
     /// Check system health and dependencies
     Doctor {
         /// Fix issues if possible
         #[arg(long)]
         fix: bool,
+
+        /// Also check the NVIDIA driver, container toolkit and Docker GPU
+        /// access that GPU-enabled executions need
+        #[arg(long)]
+        gpu: bool,
     },
 
     /// Manage workspace configuration
@@ -81,8 +203,9 @@ enum Commands {
 
     /// Execute code using Syla platform
     Exec {
-        /// File to execute
-        file: PathBuf,
+        /// File to execute; with --batch, any number of files or globs
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
 
         /// Language (auto-detected if not specified)
         #[arg(short, long)]
@@ -91,7 +214,245 @@ enum Commands {
         /// Use local Docker instead of platform
         #[arg(long)]
         local: bool,
+
+        /// Evaluate against test cases from a JSON file
+        #[arg(long)]
+        tests: Option<PathBuf>,
+
+        /// Execute every file given and summarize their verdicts
+        #[arg(long)]
+        batch: bool,
+
+        /// Files executed at once with --batch
+        #[arg(short, long, default_value = "4")]
+        jobs: usize,
+
+        /// Where --batch writes its JSON report
+        #[arg(long, default_value = "exec-report.json")]
+        report: PathBuf,
+
+        /// Time limit per run, in seconds
+        #[arg(long)]
+        timeout: Option<u64>,
+
+        /// Execution service to submit to (default: the workspace's)
+        #[arg(long, env = "SYLA_EXECUTION_URL")]
+        url: Option<String>,
+    },
+
+    /// Check the workspace manifest
+    Manifest {
+        #[command(subcommand)]
+        command: ManifestCommands,
+    },
+
+    /// Inspect and re-run executions on the platform
+    Executions {
+        #[command(subcommand)]
+        command: ExecutionsCommands,
     },
+
+    /// Run a task from the manifest's [tasks], after its dependencies
+    Run {
+        /// Task name (lists tasks if omitted)
+        task: Option<String>,
+
+        /// Extra arguments appended to the task's command
+        #[arg(last = true)]
+        args: Vec<String>,
+
+        /// Show what would run without running it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Run and inspect database migrations
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+
+    /// Check every repository's dependencies for known vulnerabilities
+    Audit {
+        /// Repositories to audit (all if not specified)
+        repos: Vec<String>,
+
+        /// Hide findings below this severity
+        #[arg(long, value_enum, default_value = "low")]
+        severity: audit::Severity,
+
+        /// Exit with status 1 if a finding is at least this severe
+        #[arg(long, value_enum)]
+        fail_on: Option<audit::Severity>,
+
+        /// Print the merged report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Bump, tag and write release notes for several repositories at once
+    Release {
+        /// major, minor, patch or an exact version
+        bump: String,
+
+        /// Repositories to release (all Rust repositories if not specified)
+        repos: Vec<String>,
+
+        /// Only repositories of this platform
+        #[arg(short, long)]
+        platform: Option<String>,
+
+        /// Show the plan and notes without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Commit without tagging
+        #[arg(long)]
+        no_tag: bool,
+
+        /// Push commits and tags to origin
+        #[arg(long)]
+        push: bool,
+
+        /// Also write the release notes to this file
+        #[arg(long)]
+        notes: Option<PathBuf>,
+    },
+
+    /// Manage protobuf definitions across repositories
+    Proto {
+        #[command(subcommand)]
+        command: ProtoCommands,
+    },
+
+    /// Check that services still honour each other's API contracts
+    Contract {
+        #[command(subcommand)]
+        command: ContractCommands,
+    },
+
+    /// Fire a request load at a service and report latency percentiles
+    Bench {
+        /// Service name
+        service: String,
+
+        /// Request path
+        #[arg(long, default_value = "/")]
+        path: String,
+
+        /// Requests per second across all workers (0 = as fast as possible)
+        #[arg(long, default_value_t = 0)]
+        rate: u32,
+
+        /// How long to run, in seconds
+        #[arg(long, default_value_t = 10)]
+        duration: u64,
+
+        /// Concurrent workers
+        #[arg(short, long, default_value_t = 10)]
+        concurrency: usize,
+
+        /// HTTP method
+        #[arg(long, default_value = "GET")]
+        method: String,
+
+        /// Name to save the results under (defaults to the service's branch)
+        #[arg(long)]
+        label: Option<String>,
+
+        /// Compare with results saved under this label
+        #[arg(long)]
+        compare: Option<String>,
+
+        /// Don't save the results
+        #[arg(long)]
+        no_save: bool,
+    },
+
+    /// Show where services are listening and whether they are up
+    Discover {
+        /// Service to look up (all if not specified)
+        service: Option<String>,
+
+        /// Print only the service's URL, e.g. for `$(syla discover api --url)`
+        #[arg(long)]
+        url: bool,
+    },
+
+    /// Call a service's HTTP or gRPC API by name
+    Api {
+        #[command(subcommand)]
+        command: ApiCommands,
+    },
+
+    /// Manage external plugins
+    Plugin {
+        #[command(subcommand)]
+        command: PluginCommands,
+    },
+
+    /// Show state-changing commands run in this workspace, latest last
+    History {
+        /// Only commands whose command line or user contains this
+        filter: Option<String>,
+
+        /// Number of entries to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
+        /// Only commands that failed
+        #[arg(long)]
+        failed: bool,
+    },
+
+    /// Manage anonymous usage metrics (opt-in)
+    Telemetry {
+        #[command(subcommand)]
+        command: TelemetryCommands,
+    },
+
+    /// Manage encrypted dev secrets referenced from the manifest
+    Secrets {
+        #[command(subcommand)]
+        command: SecretsCommands,
+    },
+
+    /// Print a shell completion script, e.g. `source <(syla completions bash)`
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    /// Completion candidates for the last of the given words; called by
+    /// the completion scripts
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        words: Vec<String>,
+    },
+
+    /// Run a `syla-<name>` plugin
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
+}
+
+impl Commands {
+    /// Whether the command changes the workspace, so `syla history` records it
+    fn changes_state(&self) -> bool {
+        match self {
+            Commands::Init { .. } | Commands::Upgrade { .. } => true,
+            Commands::Run { task, dry_run, .. } => task.is_some() && !dry_run,
+            Commands::Release { dry_run, .. } => !dry_run,
+            Commands::Platform { command } => command.changes_state(),
+            Commands::Dev { command } => command.changes_state(),
+            Commands::Daemon { command } => command.changes_state(),
+            Commands::Config { command } => command.changes_state(),
+            Commands::Db { command } => command.changes_state(),
+            Commands::Secrets { command } => command.changes_state(),
+            Commands::Plugin { command } => command.changes_state(),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -110,7 +471,7 @@ enum PlatformCommands {
         /// Platform name
         platform: String,
 
-        /// Start with dependencies
+        /// Also start services from other platforms that this one depends on
         #[arg(long)]
         with_deps: bool,
     },
@@ -132,6 +493,12 @@ enum PlatformCommands {
     },
 }
 
+impl PlatformCommands {
+    fn changes_state(&self) -> bool {
+        matches!(self, PlatformCommands::Start { .. } | PlatformCommands::Stop { .. })
+    }
+}
+
 #[derive(Subcommand)]
 enum DevCommands {
     /// Start development environment
@@ -143,33 +510,57 @@ enum DevCommands {
         /// Detached mode
         #[arg(short, long)]
         detach: bool,
+
+        /// Run binaries built with this profile (default: manifest's build_profile, else release)
+        #[arg(long, value_enum)]
+        profile: Option<config::BuildProfile>,
+
+        /// Only repositories with any of these tags (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
     },
 
     /// Stop development environment
     Down {
         /// Remove volumes
-        #[arg(short, long)]
+        #[arg(long)]
         volumes: bool,
     },
 
     /// Show service logs
     Logs {
-        /// Service path (e.g., syla/core/api-gateway)
-        service: String,
+        /// Service path (e.g., syla/core/api-gateway); asks when omitted
+        service: Option<String>,
 
         /// Follow log output
         #[arg(short, long)]
         follow: bool,
 
-        /// Number of lines to show
+        /// Number of lines to show; matches, with --grep
         #[arg(short = 'n', long, default_value = "100")]
         lines: usize,
+
+        /// Only show entries whose message matches this regex, highlighting the match
+        #[arg(long, value_name = "REGEX")]
+        grep: Option<String>,
+
+        /// Entries to show before and after each --grep match
+        #[arg(short = 'C', long, value_name = "N", default_value = "0", requires = "grep")]
+        context: usize,
+
+        /// How to print entries: pretty, json (one entry per line) or the raw line
+        #[arg(long, value_enum, default_value = "pretty")]
+        format: services::log_streamer::LogFormat,
+
+        /// Only show entries at this level or above
+        #[arg(long, value_enum, value_name = "LEVEL")]
+        level: Option<services::log_streamer::LogLevel>,
     },
 
     /// Restart a service
     Restart {
-        /// Service path
-        service: String,
+        /// Service path; asks when omitted
+        service: Option<String>,
     },
 
     /// Show development environment status
@@ -188,6 +579,15 @@ enum DevCommands {
         /// Run integration tests
         #[arg(long)]
         integration: bool,
+
+        /// Non-interactive run for pipelines: GitHub annotations and an
+        /// exit code per failure category
+        #[arg(long)]
+        ci: bool,
+
+        /// Write results as JUnit XML to this file
+        #[arg(long, value_name = "PATH")]
+        junit: Option<PathBuf>,
     },
 
     /// Watch for changes and auto-rebuild/restart
@@ -199,6 +599,14 @@ enum DevCommands {
         /// Build only, don't restart
         #[arg(long)]
         build_only: bool,
+
+        /// Run the changed service's tests after building
+        #[arg(long)]
+        test: bool,
+
+        /// Run tests instead of building and restarting
+        #[arg(long, conflicts_with = "build_only")]
+        test_only: bool,
     },
 
     /// Build changed services
@@ -206,82 +614,683 @@ enum DevCommands {
         /// Force rebuild all
         #[arg(long)]
         all: bool,
+
+        /// Count changes relative to this git ref instead of the last build
+        #[arg(long, value_name = "REF", conflicts_with = "all")]
+        since: Option<String>,
+
+        /// List what would be built without building it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Cargo profile to build with (default: manifest's build_profile, else release)
+        #[arg(long, value_enum)]
+        profile: Option<config::BuildProfile>,
+
+        /// Only repositories with any of these tags (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Services built at once; dependencies still build first
+        #[arg(short, long, default_value = "2")]
+        jobs: usize,
+    },
+
+    /// Compare running services and checked-out branches with the manifest
+    Diff {
+        /// Profile services should run with (default: manifest's build_profile, else release)
+        #[arg(long, value_enum)]
+        profile: Option<config::BuildProfile>,
+
+        /// Stop, restart and check out whatever differs
+        #[arg(long)]
+        apply: bool,
+    },
+
+    /// Diagnose why a service is down: build, process, ports, env, dependencies, health
+    Doctor {
+        /// Service name (e.g., syla.core.api-gateway); asks when omitted
+        service: Option<String>,
+
+        /// Profile the service should be built with (default: manifest's build_profile, else release)
+        #[arg(long, value_enum)]
+        profile: Option<config::BuildProfile>,
+    },
+
+    /// Write a service's environment to <repo>/.env for IDE launches
+    Envfile {
+        /// Service name (e.g., syla.core.execution-service); asks when omitted
+        service: Option<String>,
+
+        /// Print to stdout instead of writing the file
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Expose a service at a public URL, e.g. for webhooks or demos
+    Expose {
+        /// Service name (e.g., syla.core.api-gateway); asks when omitted
+        service: Option<String>,
+
+        /// Tunnel program (default: the first of cloudflared, ngrok, ssh found)
+        #[arg(long, value_enum)]
+        driver: Option<tunnels::TunnelDriver>,
+
+        /// Local port to expose instead of the service's first port
+        #[arg(long)]
+        port: Option<u16>,
+
+        /// Close the service's tunnel instead
+        #[arg(long, conflicts_with_all = ["driver", "port"])]
+        stop: bool,
+    },
+    /// Snapshot the workspace's Postgres databases and Redis data
+    Backup {
+        /// Backup name (default: the current date and time)
+        name: Option<String>,
+    },
+
+    /// Restore a backup taken with `dev backup`, replacing the current data
+    Restore {
+        /// Backup name; asks when omitted
+        name: Option<String>,
+
+        /// Skip the confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Keep services running and take start/stop/health/log requests on a
+    /// unix socket until interrupted
+    Supervise {
+        /// Socket to listen on (default: .platform/state/control.sock)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+
+    /// Uptime, incidents, recovery time and slow health checks per service,
+    /// from the history `dev supervise` records
+    Report {
+        /// How far back to look, e.g. 90m, 24h or 7d
+        #[arg(long, default_value = "24h")]
+        since: String,
+    },
+}
+
+impl DevCommands {
+    fn changes_state(&self) -> bool {
+        matches!(
+            self,
+            DevCommands::Up { .. }
+                | DevCommands::Down { .. }
+                | DevCommands::Restart { .. }
+                | DevCommands::BuildChanged { dry_run: false, .. }
+                | DevCommands::Diff { apply: true, .. }
+                | DevCommands::Envfile { print: false, .. }
+                | DevCommands::Expose { .. }
+                | DevCommands::Backup { .. }
+                | DevCommands::Restore { .. }
+        )
+    }
+}
+
+#[derive(Subcommand)]
+enum DaemonCommands {
+    /// Start the daemon in the background, if it isn't running
+    Start,
+
+    /// Stop the daemon and the services it supervises
+    Stop,
+
+    /// Show whether the daemon is running and what it supervises
+    Status,
+
+    /// Run the daemon in the foreground
+    Run,
+}
+
+impl DaemonCommands {
+    fn changes_state(&self) -> bool {
+        matches!(self, DaemonCommands::Start | DaemonCommands::Stop)
+    }
+}
+
+#[derive(Subcommand)]
+enum PluginCommands {
+    /// List plugins found in the plugin directory and on PATH
+    List,
+
+    /// Install a plugin from a local executable, crate name or git URL
+    Install {
+        /// Path to a syla-<name> executable, a crate name or a git URL
+        source: String,
+
+        /// Replace an existing installation
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+impl PluginCommands {
+    fn changes_state(&self) -> bool {
+        matches!(self, PluginCommands::Install { .. })
+    }
+}
+
+#[derive(Subcommand)]
+enum TelemetryCommands {
+    /// Show whether usage metrics are recorded and what is pending
+    Status,
+
+    /// Opt in to anonymous usage metrics
+    Enable {
+        /// URL pending events are uploaded to
+        #[arg(long)]
+        endpoint: Option<String>,
+    },
+
+    /// Opt out and delete pending events
+    Disable,
+}
+
+#[derive(Subcommand)]
+enum SecretsCommands {
+    /// Store a secret; prompts for the value when it is not given
+    Set {
+        /// Secret name
+        name: String,
+
+        /// Secret value (read from stdin or a prompt if omitted)
+        value: Option<String>,
+    },
+
+    /// Print a secret's value
+    Get {
+        /// Secret name
+        name: String,
+    },
+
+    /// List secret names and which services use them
+    List,
+}
+
+impl SecretsCommands {
+    fn changes_state(&self) -> bool {
+        matches!(self, SecretsCommands::Set { .. })
+    }
+}
+
+#[derive(Subcommand)]
+enum ApiCommands {
+    /// Send a request to an HTTP path or gRPC method of a service
+    Call {
+        /// Service name
+        service: String,
+
+        /// HTTP path such as `/health`, or gRPC method such as
+        /// `CreateExecution` or `ExecutionService/CreateExecution`
+        target: String,
+
+        /// Request body as JSON; `@file` reads it from a file, `@-` from stdin
+        #[arg(short, long)]
+        data: Option<String>,
+
+        /// HTTP method (default: GET, or POST with --data)
+        #[arg(short = 'X', long)]
+        method: Option<String>,
+
+        /// Extra header or gRPC metadata as `name: value`
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// Port to call instead of the service's first one
+        #[arg(long)]
+        port: Option<u16>,
+    },
+
+    /// List the gRPC methods in a service's protos
+    List {
+        /// Service name
+        service: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ManifestCommands {
+    /// Check repos.toml for unknown dependencies, cycles, clashing paths
+    /// and ports, and other mistakes parsing lets through
+    Lint {
+        /// Fail on warnings too
+        #[arg(long)]
+        strict: bool,
     },
 }
 
+#[derive(Subcommand)]
+enum ExecutionsCommands {
+    /// Re-run a stored execution with the same code, inputs and limits
+    Replay {
+        /// Execution ID
+        id: String,
+
+        /// Relax the time limit and output cap and record where it ran
+        #[arg(long)]
+        debug: bool,
+
+        /// Print the new execution's ID without waiting for it
+        #[arg(long)]
+        no_wait: bool,
+
+        /// Execution service to ask (default: the workspace's)
+        #[arg(long, env = "SYLA_EXECUTION_URL")]
+        url: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ContractCommands {
+    /// Verify consumer/provider contracts declared in the manifest
+    Test {
+        /// Contracts to check (all if not specified)
+        contracts: Vec<String>,
+
+        /// Only verify one side
+        #[arg(long, value_enum)]
+        only: Option<crate::commands::contract::ContractSide>,
+
+        /// Fail instead of starting providers that aren't running
+        #[arg(long)]
+        no_start: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProtoCommands {
+    /// List each repository's proto files and how its code is generated
+    List,
+
+    /// Generate code with buf or protoc
+    Generate {
+        /// Only this repository
+        service: Option<String>,
+    },
+
+    /// Fail if generated code is stale or shared protos have drifted apart
+    Check {
+        /// Only this repository
+        service: Option<String>,
+    },
+
+    /// Detect changes that break clients built against another branch
+    Breaking {
+        /// Only this repository
+        service: Option<String>,
+
+        /// Branch or ref to compare with (default: the repository's branch)
+        #[arg(long)]
+        against: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbCommands {
+    /// Run pending migrations, dependencies first
+    Migrate {
+        /// Only this service and the services it depends on
+        service: Option<String>,
+    },
+
+    /// Show each service's schema version and pending migrations
+    Status,
+
+    /// Drop the workspace database schema and migrate from scratch
+    Reset {
+        /// Skip the confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+impl DbCommands {
+    fn changes_state(&self) -> bool {
+        matches!(self, DbCommands::Migrate { .. } | DbCommands::Reset { .. })
+    }
+}
+
 #[derive(Subcommand)]
 enum ConfigCommands {
-    /// Show current configuration
+    /// Show every setting, its value and whether it comes from the
+    /// defaults, the user config (~/.syla/config.toml) or the workspace
     Show,
 
-    /// Set a configuration value
+    /// Set a configuration value in the user config, e.g. `dev.default_platform syla`
     Set {
-        /// Configuration key
+        /// Configuration key, as a dot path
         key: String,
 
-        /// Configuration value
+        /// Configuration value; lists are comma-separated
         value: String,
+
+        /// Write to the workspace's settings instead of the user config
+        #[arg(long)]
+        local: bool,
     },
 
-    /// Get a configuration value
+    /// Get a configuration value, with the workspace's settings over the user config
     Get {
-        /// Configuration key
+        /// Configuration key, as a dot path
         key: String,
     },
+
+    /// Remove a configuration value from the user config
+    Unset {
+        /// Configuration key, as a dot path
+        key: String,
+
+        /// Remove it from the workspace's settings instead
+        #[arg(long)]
+        local: bool,
+    },
+
+    /// Turn on optional repositories gated by feature flags
+    Features {
+        #[command(subcommand)]
+        command: FeatureCommands,
+    },
+}
+
+impl ConfigCommands {
+    fn changes_state(&self) -> bool {
+        match self {
+            ConfigCommands::Set { .. } | ConfigCommands::Unset { .. } => true,
+            ConfigCommands::Features { command } => {
+                matches!(command, FeatureCommands::Enable { .. } | FeatureCommands::Disable { .. })
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum FeatureCommands {
+    /// List features and the repositories each one brings in
+    List,
+
+    /// Enable features so init clones and dev up starts their repositories
+    Enable {
+        /// Feature names
+        #[arg(required = true)]
+        features: Vec<String>,
+    },
+
+    /// Disable features again
+    Disable {
+        /// Feature names
+        #[arg(required = true)]
+        features: Vec<String>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // Runs on every tab press, so it skips logging, history and telemetry
+    if let Commands::Complete { words } = &cli.command {
+        completions::complete(Cli::command(), words, cli.workspace);
+        return Ok(());
+    }
 
     // Initialize logging
-    let filter = if cli.verbose { "debug" } else { "info" };
-
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_target(false)
-        .init();
-
-    // Print header
-    println!(
-        "\n{} {}\n",
-        "Syla".cyan().bold(),
-        "Meta-Platform CLI".dimmed()
+    let verbosity = match (cli.quiet, cli.verbose) {
+        (true, _) => ui::Verbosity::Quiet,
+        (_, true) => ui::Verbosity::Verbose,
+        _ => ui::Verbosity::Normal,
+    };
+    ui::set_verbosity(verbosity);
+
+    let tracer_provider = otel::init(verbosity, cli.workspace.clone());
+    names::set_non_interactive(cli.non_interactive);
+    lock::set_wait(cli.wait);
+    if let Some(target) = &cli.events {
+        events::open(target)?;
+    }
+
+    // Plugins own their output, so they run without the header
+    if let Commands::External(args) = cli.command {
+        return plugin::dispatch(args, cli.workspace).await;
+    }
+
+    // Print header, except where stdout is meant to be consumed as-is
+    if cli.output.is_none() && !cli.quiet && !is_machine_output(&cli.command) {
+        println!(
+            "\n{} {}\n",
+            "Syla".cyan().bold(),
+            "Meta-Platform CLI".dimmed()
+        );
+    }
+
+    // Execute command, timing it for opt-in usage metrics
+    let command_name = command_path(&matches);
+    let recorded = cli.command.changes_state().then(|| cli.workspace.clone());
+    let started = Instant::now();
+    let span = tracing::info_span!(
+        "command",
+        name = %command_name,
+        otel.status_code = tracing::field::Empty,
     );
+    let result = run(cli.command, cli.output, cli.workspace).instrument(span.clone()).await;
+    if result.is_err() {
+        span.record("otel.status_code", "ERROR");
+    }
+    drop(span);
+    if let Some(workspace) = recorded {
+        history::record(workspace, &command_name, started.elapsed(), &result);
+    }
+    if !command_name.starts_with("telemetry") {
+        telemetry::record(&command_name, started.elapsed(), &result).await;
+    }
+    otel::shutdown(tracer_provider);
+    if let Some(status) = result.as_ref().err().and_then(|e| e.downcast_ref::<commands::ExitStatus>()) {
+        eprintln!("{} {}", "[X]".red(), status.message);
+        std::process::exit(status.code);
+    }
+    result
+}
 
-    // Execute command
-    match cli.command {
+async fn run(command: Commands, output: Option<OutputFormat>, workspace: Option<PathBuf>) -> Result<()> {
+    match command {
         Commands::Init {
             platform,
+            template,
             yes,
             force,
+            profile,
+            tags,
         } => {
-            init::run(platform, yes, force, cli.workspace).await?;
+            init::run(platform, template, yes, force, profile, tags, workspace).await?;
+        }
+        Commands::Status { detailed, tags, github } => {
+            status::run(detailed, tags, github, output, workspace).await?;
         }
-        Commands::Status { detailed } => {
-            status::run(detailed, cli.workspace).await?;
+        Commands::Upgrade { strategy, profile, tags } => {
+            upgrade::run(profile, tags, strategy, workspace).await?;
         }
         Commands::Platform { command } => {
-            platform_cmd::run(command, cli.workspace).await?;
+            platform_cmd::run(command, output, workspace).await?;
         }
         Commands::Dev { command } => {
-            dev::run(command, cli.workspace).await?;
+            dev::run(command, output, workspace).await?;
+        }
+        Commands::Daemon { command } => {
+            daemon::run(command, output, workspace).await?;
+        }
+        Commands::Dashboard => {
+            dashboard::run(workspace).await?;
+        }
+        Commands::Serve { host, port, allow_origins } => {
+            serve::run(workspace, host, port, allow_origins).await?;
         }
-        Commands::Doctor { fix } => {
-            doctor::run(fix, cli.workspace).await?;
+        Commands::Why { service } => {
+            why::run(service, workspace).await?;
         }
-        Commands::Config { command: _ } => {
-            println!("Config command not yet implemented");
+        Commands::Doctor { fix, gpu } => {
+            doctor::run(fix, gpu, output, workspace).await?;
+        }
+        Commands::Config { command } => {
+            config_cmd::run(command, output, workspace).await?;
         }
         Commands::Exec {
-            file: _,
-            language: _,
-            local: _,
+            files,
+            language,
+            local,
+            tests,
+            batch,
+            jobs,
+            report,
+            timeout,
+            url,
         } => {
-            println!("Exec command not yet implemented");
+            let options = exec::ExecOptions {
+                files,
+                language,
+                local,
+                tests,
+                batch,
+                jobs,
+                report,
+                timeout,
+                url,
+            };
+            exec::run(options, output, workspace).await?;
+        }
+        Commands::Manifest { command } => {
+            manifest::run(command, output, workspace).await?;
+        }
+        Commands::Executions { command } => {
+            executions::run(command, output, workspace).await?;
+        }
+        Commands::Run { task, args, dry_run } => {
+            run_cmd::run(task, args, dry_run, workspace).await?;
+        }
+        Commands::Db { command } => {
+            db::run(command, workspace).await?;
         }
+        Commands::Audit {
+            repos,
+            severity,
+            fail_on,
+            json,
+        } => {
+            let options = audit::AuditOptions {
+                repos,
+                severity,
+                fail_on,
+                json,
+            };
+            audit::run(options, workspace).await?;
+        }
+        Commands::Release {
+            bump,
+            repos,
+            platform,
+            dry_run,
+            no_tag,
+            push,
+            notes,
+        } => {
+            let options = release::ReleaseOptions {
+                bump,
+                repos,
+                platform,
+                dry_run,
+                tag: !no_tag,
+                push,
+                notes,
+            };
+            release::run(options, workspace).await?;
+        }
+        Commands::Proto { command } => {
+            proto::run(command, workspace).await?;
+        }
+        Commands::Contract { command } => {
+            contract::run(command, workspace).await?;
+        }
+        Commands::Bench {
+            service,
+            path,
+            rate,
+            duration,
+            concurrency,
+            method,
+            label,
+            compare,
+            no_save,
+        } => {
+            let options = bench::BenchOptions {
+                service,
+                path,
+                rate,
+                duration,
+                concurrency,
+                method,
+                label,
+                compare,
+                save: !no_save,
+            };
+            bench::run(options, workspace).await?;
+        }
+        Commands::Discover { service, url } => {
+            discover::run(service, url, output, workspace).await?;
+        }
+        Commands::Api { command } => {
+            api::run(command, workspace).await?;
+        }
+        Commands::Plugin { command } => {
+            plugin::run(command, workspace).await?;
+        }
+        Commands::History { filter, limit, failed } => {
+            history_cmd::run(filter, limit, failed, output, workspace).await?;
+        }
+        Commands::Telemetry { command } => {
+            telemetry_cmd::run(command).await?;
+        }
+        Commands::Secrets { command } => {
+            secrets_cmd::run(command, workspace).await?;
+        }
+        Commands::Completions { shell } => {
+            completions::run(shell, Cli::command());
+        }
+        Commands::Complete { .. } => unreachable!("completions are answered before logging starts"),
+        Commands::External(_) => unreachable!("plugins are dispatched before the header"),
     }
 
     Ok(())
 }
 
+/// Commands whose stdout is piped into files or other tools
+fn is_machine_output(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Secrets { command: SecretsCommands::Get { .. } }
+            | Commands::Dev { command: DevCommands::Envfile { print: true, .. } }
+            | Commands::Dev { command: DevCommands::Logs { format: services::log_streamer::LogFormat::Json | services::log_streamer::LogFormat::Raw, .. } }
+            | Commands::Audit { json: true, .. }
+            | Commands::Discover { url: true, .. }
+            | Commands::Exec { batch: false, .. }
+            | Commands::Completions { .. }
+    )
+}
+
+/// Subcommand names without arguments, e.g. `dev up`
+fn command_path(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
+}
+
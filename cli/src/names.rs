@@ -0,0 +1,155 @@
+use anyhow::Result;
+use dialoguer::Select;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// What a service argument resolved to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Match {
+    One(String),
+    /// Several names match equally well, sorted
+    Ambiguous(Vec<String>),
+    /// Nothing matches; the closest names by edit distance, best first
+    NotFound(Vec<String>),
+}
+
+/// Suggestions offered when nothing matches
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Set by `--non-interactive`
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Never prompt for a choice; ambiguous or missing names become errors
+pub fn set_non_interactive(non_interactive: bool) {
+    NON_INTERACTIVE.store(non_interactive, Ordering::Relaxed);
+}
+
+/// Whether a picker can be shown: allowed, and someone is at the terminal
+pub(crate) fn can_prompt() -> bool {
+    !NON_INTERACTIVE.load(Ordering::Relaxed) && std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// Matches `query` against `names`, trying each rule in turn and stopping
+/// at the first that matches anything:
+///
+/// 1. the exact name
+/// 2. the last segment, e.g. `api-gateway` for `syla.core.api-gateway`
+/// 3. a trailing run of segments, e.g. `core.api-gateway`
+/// 4. a leading run of segments, e.g. `syla.core`
+/// 5. any substring
+///
+/// `/` is accepted in place of `.`, so repository paths work too.
+pub fn find<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> Match {
+    let mut names: Vec<&str> = names.into_iter().collect();
+    names.sort_unstable();
+    names.dedup();
+    let query = query.replace('/', ".");
+    let query = query.as_str();
+
+    let rules: [&dyn Fn(&str) -> bool; 5] = [
+        &|name| name == query,
+        &|name| name.rsplit('.').next() == Some(query),
+        &|name| name.ends_with(&format!(".{}", query)),
+        &|name| name.starts_with(&format!("{}.", query)),
+        &|name| name.contains(query),
+    ];
+    for rule in rules {
+        let matches: Vec<&str> = names.iter().copied().filter(|name| rule(name)).collect();
+        match matches.as_slice() {
+            [] => continue,
+            [name] => return Match::One(name.to_string()),
+            _ => return Match::Ambiguous(matches.into_iter().map(String::from).collect()),
+        }
+    }
+    Match::NotFound(suggestions(query, &names))
+}
+
+/// Like [`find`], but an ambiguous name is offered in a picker, or is an
+/// error listing the candidates when nobody can answer one. An unknown
+/// name is an error with suggestions.
+pub fn resolve<'a>(kind: &str, query: &str, names: impl IntoIterator<Item = &'a str>) -> Result<String> {
+    match find(query, names) {
+        Match::One(name) => Ok(name),
+        Match::Ambiguous(names) if can_prompt() => {
+            select(&format!("'{}' matches several {}s", query, kind), names)
+        }
+        Match::Ambiguous(names) => {
+            anyhow::bail!("'{}' matches several {}s: {}", query, kind, names.join(", "))
+        }
+        Match::NotFound(suggestions) => match suggestions.as_slice() {
+            [] => anyhow::bail!("{} '{}' not found", capitalize(kind), query),
+            [name] => anyhow::bail!("{} '{}' not found. Did you mean {}?", capitalize(kind), query, name),
+            _ => anyhow::bail!(
+                "{} '{}' not found. Did you mean one of: {}?",
+                capitalize(kind),
+                query,
+                suggestions.join(", ")
+            ),
+        },
+    }
+}
+
+/// Resolves `query` when given, otherwise asks which of `names` to use
+pub fn resolve_or_pick<'a>(
+    kind: &str,
+    query: Option<&str>,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<String> {
+    if let Some(query) = query {
+        return resolve(kind, query, names);
+    }
+    let mut names: Vec<String> = names.into_iter().map(String::from).collect();
+    names.sort();
+    names.dedup();
+    match names.as_slice() {
+        [] => anyhow::bail!("No {}s in the manifest", kind),
+        [name] => Ok(name.clone()),
+        _ if can_prompt() => select(&format!("Which {}?", kind), names),
+        _ => anyhow::bail!("Name a {}; one of: {}", kind, names.join(", ")),
+    }
+}
+
+fn select(prompt: &str, names: Vec<String>) -> Result<String> {
+    let selection = Select::new().with_prompt(prompt).items(&names).default(0).interact()?;
+    Ok(names[selection].clone())
+}
+
+/// Names within a typo or two of `query`, either as a whole or by their
+/// last segment
+fn suggestions(query: &str, names: &[&str]) -> Vec<String> {
+    let limit = (query.chars().count() / 3).max(2);
+    let mut scored: Vec<(usize, &str)> = names
+        .iter()
+        .map(|name| {
+            let last = name.rsplit('.').next().unwrap_or(name);
+            (edit_distance(query, name).min(edit_distance(query, last)), *name)
+        })
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    scored.sort();
+    scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, name)| name.to_string()).collect()
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
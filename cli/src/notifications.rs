@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Which environment events raise a desktop notification. Lives under
+/// `[notifications]` in `.platform/config/workspace.toml`; `SYLA_NOTIFICATIONS=0`
+/// turns them all off, e.g. on headless machines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// A service process exited while it was supposed to be running
+    pub crashes: bool,
+    /// A service went from healthy to unhealthy or back
+    pub health: bool,
+    /// A build finished after running longer than `build_threshold_secs`
+    pub builds: bool,
+    pub build_threshold_secs: u64,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            crashes: true,
+            health: true,
+            builds: true,
+            build_threshold_secs: 30,
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn allows(&self, event: &Event) -> bool {
+        if !self.enabled || std::env::var("SYLA_NOTIFICATIONS").is_ok_and(|v| v == "0") {
+            return false;
+        }
+        match event {
+            Event::ServiceCrashed { .. } => self.crashes,
+            Event::HealthChanged { .. } => self.health,
+            Event::BuildFinished { duration, .. } => {
+                self.builds && *duration >= Duration::from_secs(self.build_threshold_secs)
+            }
+        }
+    }
+}
+
+pub enum Event<'a> {
+    ServiceCrashed { service: &'a str, exit: String },
+    HealthChanged { service: &'a str, healthy: bool, detail: Option<String> },
+    BuildFinished { target: &'a str, success: bool, duration: Duration },
+}
+
+impl Event<'_> {
+    fn summary(&self) -> String {
+        match self {
+            Event::ServiceCrashed { service, .. } => format!("{} crashed", service),
+            Event::HealthChanged { service, healthy: true, .. } => format!("{} is healthy again", service),
+            Event::HealthChanged { service, healthy: false, .. } => format!("{} is unhealthy", service),
+            Event::BuildFinished { target, success: true, .. } => format!("{} built", target),
+            Event::BuildFinished { target, success: false, .. } => format!("{} build failed", target),
+        }
+    }
+
+    fn body(&self) -> String {
+        match self {
+            Event::ServiceCrashed { exit, .. } => format!("Process {}", exit),
+            Event::HealthChanged { detail, .. } => detail.clone().unwrap_or_default(),
+            Event::BuildFinished { duration, .. } => format!("Took {}s", duration.as_secs()),
+        }
+    }
+}
+
+/// Show a desktop notification if the settings allow it. Delivery happens on
+/// a background thread and failures (no notification daemon, no display) are
+/// ignored: a missed notification must never break the command.
+pub fn notify(settings: &NotificationSettings, event: Event) {
+    if !settings.allows(&event) {
+        return;
+    }
+
+    let summary = event.summary();
+    let body = event.body();
+    std::thread::spawn(move || {
+        let _ = notify_rust::Notification::new()
+            .appname("syla")
+            .summary(&summary)
+            .body(&body)
+            .show();
+    });
+}
@@ -0,0 +1,72 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+use std::path::PathBuf;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::debug_log::DebugLog;
+use crate::ui::Verbosity;
+
+const SERVICE_NAME: &str = "syla-cli";
+
+/// Install the tracing subscriber. Command spans (clones, builds, health
+/// checks) are exported over OTLP when `OTEL_EXPORTER_OTLP_ENDPOINT` is set;
+/// otherwise only logs are written, with span timings when verbose. Inside
+/// a workspace, debug events also go to its debug log regardless of
+/// verbosity. The returned provider must be shut down before exiting to
+/// flush pending spans.
+pub fn init(verbosity: Verbosity, workspace_root: Option<PathBuf>) -> Option<TracerProvider> {
+    let provider = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok().and_then(|_| {
+        // A broken exporter must not stop the CLI from working
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .build()
+            .map_err(|e| eprintln!("Tracing disabled: {}", e))
+            .ok()?;
+        let provider = TracerProvider::builder()
+            .with_batch_exporter(exporter, runtime::Tokio)
+            .with_resource(Resource::new_with_defaults([
+                KeyValue::new("service.name", SERVICE_NAME),
+                KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+            ]))
+            .build();
+        global::set_tracer_provider(provider.clone());
+        Some(provider)
+    });
+
+    let (filter, span_events) = match verbosity {
+        Verbosity::Quiet => ("error", FmtSpan::NONE),
+        Verbosity::Normal => ("info", FmtSpan::NONE),
+        Verbosity::Verbose => ("info,syla=debug", FmtSpan::CLOSE),
+    };
+    // Each layer filters on its own, so the debug log sees more than the
+    // console shows
+    let otel_layer = provider.as_ref().map(|p| {
+        tracing_opentelemetry::layer()
+            .with_tracer(p.tracer(SERVICE_NAME))
+            .with_filter(EnvFilter::new(filter))
+    });
+    let debug_log = DebugLog::open(workspace_root).map(|log| log.with_filter(EnvFilter::new("info,syla=debug")));
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .with_span_events(span_events)
+                .with_filter(EnvFilter::new(filter)),
+        )
+        .with(otel_layer)
+        .with(debug_log)
+        .init();
+
+    provider
+}
+
+pub fn shutdown(provider: Option<TracerProvider>) {
+    if let Some(provider) = provider {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("Failed to flush traces: {}", e);
+        }
+    }
+}
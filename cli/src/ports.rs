@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+use crate::commands::dev::host_port;
+use crate::config::{Config, RepoManifest};
+use crate::services::state::StartedServices;
+
+/// Manifest port entry asking for a port to be assigned at `dev up`
+pub const AUTO: &str = "auto";
+
+/// Range automatically assigned ports come from
+const RANGE: std::ops::RangeInclusive<u16> = 20000..=29999;
+
+/// Ports assigned to `auto` entries, kept in `.platform/state/ports.toml` so
+/// services keep their ports across restarts
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PortAllocations {
+    #[serde(flatten)]
+    services: BTreeMap<String, Vec<u16>>,
+}
+
+impl PortAllocations {
+    fn path(workspace_root: &Path) -> PathBuf {
+        workspace_root.join(".platform/state/ports.toml")
+    }
+
+    pub fn load(workspace_root: &Path) -> Result<Self> {
+        let path = Self::path(workspace_root);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Ports assigned to a service's `auto` entries
+    pub fn get(&self, service: &str) -> Option<&[u16]> {
+        self.services.get(service).map(Vec::as_slice)
+    }
+
+    fn save(&self, workspace_root: &Path) -> Result<()> {
+        let path = Self::path(workspace_root);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = format!(
+            "# Ports assigned by `syla dev up` to services with ports = [\"auto\"]\n{}",
+            toml::to_string_pretty(self)?
+        );
+        std::fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Replaces `auto` port entries with their assigned ports, and `{port}` in
+/// health checks with the service's first port. Entries without an
+/// assignment yet stay `auto`.
+pub fn resolve(manifest: &mut RepoManifest, allocations: &PortAllocations) {
+    for (name, repo) in manifest.repositories.iter_mut() {
+        let assigned = allocations.services.get(name).map(Vec::as_slice).unwrap_or_default();
+        repo.auto_ports = repo
+            .ports
+            .iter()
+            .enumerate()
+            .filter(|(_, port)| *port == AUTO)
+            .map(|(i, _)| i)
+            .collect();
+        for (&i, port) in repo.auto_ports.iter().zip(assigned) {
+            repo.ports[i] = port.to_string();
+        }
+        if let (Some(health_check), Some(port)) = (&repo.health_check, repo.ports.first().and_then(|p| host_port(p))) {
+            repo.health_check = Some(health_check.replace("{port}", port));
+        }
+    }
+}
+
+/// Assigns ports to `auto` entries that don't have one, or whose port is
+/// now taken by something other than the service itself, and returns the
+/// configuration with them resolved
+pub fn allocate(config: &Config) -> Result<Config> {
+    let mut allocations = PortAllocations::load(&config.workspace_root)?;
+
+    let mut services: Vec<_> = config
+        .manifest
+        .repositories
+        .iter()
+        .filter(|(_, repo)| !repo.auto_ports.is_empty())
+        .collect();
+    if services.is_empty() {
+        return Ok(config.clone());
+    }
+    services.sort_by(|a, b| a.0.cmp(b.0));
+
+    // Fixed ports in the manifest are off limits
+    let mut taken: HashSet<u16> = config
+        .manifest
+        .repositories
+        .values()
+        .flat_map(|repo| {
+            repo.ports
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !repo.auto_ports.contains(i))
+                .map(|(_, port)| port)
+        })
+        .chain(config.manifest.infrastructure.values().flat_map(|infra| &infra.ports))
+        .filter_map(|port| host_port(port)?.parse().ok())
+        .collect();
+    // A service that is still running holds its own port
+    let started = StartedServices::load(&config.workspace_root)?;
+    let held_by = |name: &str, port: u16| {
+        started.services.get(name).is_some_and(|service| {
+            service.ports.iter().any(|p| host_port(p).and_then(|p| p.parse().ok()) == Some(port)) && service.is_running()
+        })
+    };
+    let before = allocations.services.len();
+    allocations.services.retain(|name, _| config.manifest.repositories.contains_key(name));
+
+    let mut changed = allocations.services.len() != before;
+    for (name, repo) in services {
+        let previous = allocations.services.get(name).cloned().unwrap_or_default();
+        let mut ports = Vec::new();
+        for i in 0..repo.auto_ports.len() {
+            let port = match previous.get(i) {
+                Some(&port) if !taken.contains(&port) && (is_free(port) || held_by(name, port)) => port,
+                previous => {
+                    let port = RANGE
+                        .clone()
+                        .find(|port| !taken.contains(port) && is_free(*port))
+                        .context("No free port left to assign")?;
+                    match previous {
+                        Some(old) => println!("{} Port {} of {} is in use, moved to {}", "[!]".yellow(), old, name, port),
+                        None => say!("{} Assigned port {} to {}", "[OK]".green(), port, name),
+                    }
+                    changed = true;
+                    port
+                }
+            };
+            taken.insert(port);
+            ports.push(port);
+        }
+        allocations.services.insert(name.clone(), ports);
+    }
+
+    if changed {
+        allocations.save(&config.workspace_root)?;
+    }
+    Config::load(Some(config.workspace_root.clone()))
+}
+
+/// Whether nothing is listening on a local port
+pub(crate) fn is_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::{Config, RepositoryConfig};
+use crate::events;
+use crate::ui;
+
+/// What a service runs on, deciding how it is built and started when the
+/// manifest has no `build` / `run` commands for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Runtime {
+    Rust,
+    Node,
+    Python,
+    Go,
+    Other,
+}
+
+impl Runtime {
+    pub fn of(repo: &RepositoryConfig) -> Self {
+        match repo.language.to_ascii_lowercase().as_str() {
+            "rust" => Runtime::Rust,
+            "node" | "nodejs" | "javascript" | "js" | "typescript" | "ts" => Runtime::Node,
+            "python" | "py" => Runtime::Python,
+            "go" | "golang" => Runtime::Go,
+            _ => Runtime::Other,
+        }
+    }
+
+    /// Shell command that installs dependencies or compiles the service, run
+    /// in its repository. `None` for Rust, which is built with cargo.
+    pub fn build_command(self, repo: &RepositoryConfig, dir: &Path) -> Option<String> {
+        if let Some(build) = &repo.build {
+            return Some(build.clone());
+        }
+        match self {
+            Runtime::Node if dir.join("package.json").exists() => {
+                let install = match node_package_manager(dir) {
+                    "npm" if dir.join("package-lock.json").exists() => "npm ci",
+                    "npm" => "npm install",
+                    "pnpm" => "pnpm install",
+                    _ => "yarn install",
+                };
+                Some(install.to_string())
+            }
+            Runtime::Python => {
+                let install = if dir.join("requirements.txt").exists() {
+                    "-r requirements.txt"
+                } else if dir.join("pyproject.toml").exists() {
+                    "-e ."
+                } else {
+                    return None;
+                };
+                Some(format!("python3 -m venv .venv && .venv/bin/pip install -q {}", install))
+            }
+            Runtime::Go if dir.join("go.mod").exists() => {
+                Some(format!("go build -o bin/{} .", binary_name(repo)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Shell command that starts the service in its repository. `None` for
+    /// Rust, which runs its built binary, and when nothing sensible applies.
+    pub fn run_command(self, repo: &RepositoryConfig, dir: &Path) -> Option<String> {
+        if let Some(run) = &repo.run {
+            return Some(run.clone());
+        }
+        match self {
+            Runtime::Node if dir.join("package.json").exists() => {
+                Some(format!("{} start", node_package_manager(dir)))
+            }
+            Runtime::Python => {
+                let python = if dir.join(".venv/bin/python").exists() { ".venv/bin/python" } else { "python3" };
+                if dir.join("manage.py").exists() {
+                    return Some(format!("{} manage.py runserver 0.0.0.0:${{PORT:-8000}}", python));
+                }
+                ["main.py", "app.py", "server.py"]
+                    .into_iter()
+                    .find(|entry| dir.join(entry).exists())
+                    .map(|entry| format!("{} {}", python, entry))
+            }
+            Runtime::Go => Some(format!("bin/{}", binary_name(repo))),
+            _ => None,
+        }
+    }
+
+    /// Shell command that runs the service's tests in its repository. `None`
+    /// for Rust, which is tested with cargo, and when there are no tests to
+    /// run.
+    pub fn test_command(self, repo: &RepositoryConfig, dir: &Path) -> Option<String> {
+        if let Some(test) = &repo.test {
+            return Some(test.clone());
+        }
+        match self {
+            Runtime::Node if dir.join("package.json").exists() => {
+                Some(format!("{} test", node_package_manager(dir)))
+            }
+            Runtime::Python if ["tests", "pytest.ini", "pyproject.toml"].iter().any(|f| dir.join(f).exists()) => {
+                let python = if dir.join(".venv/bin/python").exists() { ".venv/bin/python" } else { "python3" };
+                Some(format!("{} -m pytest", python))
+            }
+            Runtime::Go if dir.join("go.mod").exists() => Some("go test ./...".to_string()),
+            _ => None,
+        }
+    }
+
+    /// Environment every service on this runtime gets, before the manifest's
+    pub fn env(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Runtime::Rust => &[("RUST_LOG", "info")],
+            Runtime::Node => &[("NODE_ENV", "development")],
+            Runtime::Python => &[("PYTHONUNBUFFERED", "1")],
+            Runtime::Go | Runtime::Other => &[],
+        }
+    }
+
+    /// What the default build produces, for telling whether it ran.
+    /// `None` when the manifest's own `build` is used or there is none.
+    pub fn build_output(self, repo: &RepositoryConfig, dir: &Path) -> Option<PathBuf> {
+        if repo.build.is_some() {
+            return None;
+        }
+        self.build_command(repo, dir)?;
+        match self {
+            Runtime::Node => Some(dir.join("node_modules")),
+            Runtime::Python => Some(dir.join(".venv")),
+            Runtime::Go => Some(dir.join("bin").join(binary_name(repo))),
+            Runtime::Rust | Runtime::Other => None,
+        }
+    }
+
+    /// Files the default build depends on; empty when any source file does
+    pub fn build_inputs(self) -> &'static [&'static str] {
+        match self {
+            Runtime::Node => &["package.json", "package-lock.json", "pnpm-lock.yaml", "yarn.lock"],
+            Runtime::Python => &["requirements.txt", "pyproject.toml"],
+            Runtime::Rust | Runtime::Go | Runtime::Other => &[],
+        }
+    }
+}
+
+/// Runs a service's build command with its output shown, returning whether
+/// it succeeded
+pub fn build(config: &Config, name: &str, command: &str, dir: &Path) -> Result<bool> {
+    let _span = tracing::info_span!("build", service = %name).entered();
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command])
+        .current_dir(dir)
+        .env("SYLA_WORKSPACE", &config.workspace_root)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+    let logged = ui::log_command(&cmd);
+    events::build(name, || {
+        let status = cmd.status().with_context(|| format!("Failed to build {}", name))?;
+        logged.finished(&status);
+        Ok(status.success())
+    })
+}
+
+fn node_package_manager(dir: &Path) -> &'static str {
+    if dir.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if dir.join("yarn.lock").exists() {
+        "yarn"
+    } else {
+        "npm"
+    }
+}
+
+fn binary_name(repo: &RepositoryConfig) -> &str {
+    repo.path.rsplit('/').next().unwrap_or("service")
+}
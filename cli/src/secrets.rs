@@ -0,0 +1,164 @@
+use age::secrecy::ExposeSecret;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::config;
+
+/// Manifest values of the form `secret:<name>` are replaced with the
+/// named secret when a service's environment is built
+pub const SECRET_REF_PREFIX: &str = "secret:";
+
+/// Dev secrets encrypted at rest with an age key.
+///
+/// The store lives in `~/.syla/secrets/store.age` and is encrypted to the
+/// identity in `~/.syla/secrets/identity.txt` (or `$SYLA_SECRETS_IDENTITY`),
+/// which is generated on first use and readable only by the user.
+pub struct SecretStore {
+    identity: age::x25519::Identity,
+    path: PathBuf,
+    secrets: BTreeMap<String, String>,
+}
+
+fn secrets_dir() -> PathBuf {
+    config::user_dir().join("secrets")
+}
+
+fn identity_path() -> PathBuf {
+    std::env::var_os("SYLA_SECRETS_IDENTITY")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| secrets_dir().join("identity.txt"))
+}
+
+impl SecretStore {
+    /// Decrypt the store, creating the identity if this is the first use
+    pub fn open() -> Result<Self> {
+        let identity = load_or_create_identity(&identity_path())?;
+        let path = secrets_dir().join("store.age");
+
+        let secrets = match std::fs::read(&path) {
+            Ok(ciphertext) => {
+                let plaintext = age::decrypt(&identity, &ciphertext)
+                    .with_context(|| format!("Failed to decrypt {}", path.display()))?;
+                serde_json::from_slice(&plaintext).context("Secret store is corrupt")?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+
+        Ok(Self {
+            identity,
+            path,
+            secrets,
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.secrets.get(name).map(String::as_str)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.secrets.keys().map(String::as_str)
+    }
+
+    pub fn set(&mut self, name: &str, value: String) -> Result<()> {
+        validate_name(name)?;
+        self.secrets.insert(name.to_string(), value);
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let plaintext = serde_json::to_vec(&self.secrets)?;
+        let ciphertext = age::encrypt(&self.identity.to_public(), &plaintext)
+            .context("Failed to encrypt secrets")?;
+        write_private(&self.path, &ciphertext)
+    }
+
+    /// Replace `secret:<name>` references in an environment with their values
+    pub fn resolve_env(&self, env: &mut HashMap<String, String>) -> Result<()> {
+        for (key, value) in env.iter_mut() {
+            if let Some(name) = value.strip_prefix(SECRET_REF_PREFIX) {
+                *value = self
+                    .get(name)
+                    .with_context(|| {
+                        format!("Secret '{}' used by {} is not set; run `syla secrets set {}`", name, key, name)
+                    })?
+                    .to_string();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A secret's value, without creating the store or its key when there is
+/// none yet
+pub fn lookup(name: &str) -> Option<String> {
+    if !secrets_dir().join("store.age").exists() {
+        return None;
+    }
+    SecretStore::open().ok()?.get(name).map(String::from)
+}
+
+/// Whether any value refers to a secret, so the store is only decrypted
+/// when it is needed
+pub fn has_secret_refs<'a>(values: impl IntoIterator<Item = &'a String>) -> bool {
+    values.into_iter().any(|v| v.starts_with(SECRET_REF_PREFIX))
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        anyhow::bail!("Invalid secret name '{}': use letters, digits, '_', '-' or '.'", name);
+    }
+    Ok(())
+}
+
+fn load_or_create_identity(path: &Path) -> Result<age::x25519::Identity> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => {
+            let key = content
+                .lines()
+                .map(str::trim)
+                .find(|line| line.starts_with("AGE-SECRET-KEY-"))
+                .with_context(|| format!("No age secret key in {}", path.display()))?;
+            age::x25519::Identity::from_str(key)
+                .map_err(|e| anyhow::anyhow!("Invalid age key in {}: {}", path.display(), e))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let identity = age::x25519::Identity::generate();
+            let content = format!(
+                "# Key for syla dev secrets; losing it makes them unreadable\n# public key: {}\n{}\n",
+                identity.to_public(),
+                identity.to_string().expose_secret()
+            );
+            write_private(path, content.as_bytes())?;
+            Ok(identity)
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Write a file only the current user can read
+pub(crate) fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    use std::io::Write;
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(data))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
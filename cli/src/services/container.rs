@@ -0,0 +1,208 @@
+//! Services that run in a Docker container rather than as a process.
+//!
+//! A repository with `run_in = "container"` is started from its `image` as
+//! `syla-<service>`, on the host network so its ports and the `localhost`
+//! addresses in its environment mean the same as for a process. Its output
+//! is appended to the service's log file like a process's.
+//!
+//! The process manager works from plain threads, so each call to the daemon
+//! runs to completion on a runtime of its own.
+
+use anyhow::{Context, Result};
+use bollard::container::{
+    Config as ContainerConfig, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions,
+    StopContainerOptions,
+};
+use bollard::image::CreateImageOptions;
+use bollard::models::HostConfig;
+use bollard::Docker;
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::services::process_manager::Exit;
+
+/// Seconds a container gets to exit after SIGTERM before it is killed
+const STOP_TIMEOUT: i64 = 5;
+
+/// Name of the container a service runs in
+pub fn container_name(service: &str) -> String {
+    format!("syla-{}", service)
+}
+
+/// A service's running container
+pub struct Container {
+    docker: Docker,
+    name: String,
+}
+
+impl Container {
+    /// Creates and starts the service's container, pulling `image` first if
+    /// it isn't there. A container left over from an earlier run is
+    /// replaced. `command` replaces the image's own when not empty.
+    pub fn start(
+        service: &str,
+        image: &str,
+        command: &[String],
+        env: &HashMap<String, String>,
+        log_file: Option<PathBuf>,
+    ) -> Result<Self> {
+        let docker = Docker::connect_with_local_defaults().context("Failed to connect to Docker")?;
+        let name = container_name(service);
+        let config = ContainerConfig {
+            image: Some(image.to_string()),
+            cmd: (!command.is_empty()).then(|| command.to_vec()),
+            env: Some(env.iter().map(|(key, value)| format!("{}={}", key, value)).collect()),
+            labels: Some(HashMap::from([("syla.service".to_string(), service.to_string())])),
+            host_config: Some(HostConfig { network_mode: Some("host".to_string()), ..Default::default() }),
+            ..Default::default()
+        };
+        tracing::debug!("$ docker run --name {} --network host {} {}", name, image, command.join(" "));
+
+        block_on(async {
+            let _ = docker
+                .remove_container(&name, Some(RemoveContainerOptions { force: true, ..Default::default() }))
+                .await;
+            if docker.inspect_image(image).await.is_err() {
+                let options = CreateImageOptions { from_image: image, ..Default::default() };
+                let mut pull = docker.create_image(Some(options), None, None);
+                while let Some(progress) = pull.next().await {
+                    progress.with_context(|| format!("Failed to pull {}", image))?;
+                }
+            }
+            docker
+                .create_container(Some(CreateContainerOptions { name: name.as_str(), platform: None }), config)
+                .await
+                .with_context(|| format!("Failed to create container {}", name))?;
+            docker
+                .start_container::<String>(&name, None)
+                .await
+                .with_context(|| format!("Failed to start container {}", name))
+        })?;
+
+        let container = Self { docker, name };
+        if let Some(log_file) = log_file {
+            container.follow_logs(log_file)?;
+        }
+        Ok(container)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Host process ID of the container's main process, while it runs
+    pub fn pid(&self) -> Option<u32> {
+        let info = block_on(async { Ok(self.docker.inspect_container(&self.name, None).await?) }).ok()?;
+        let pid = info.state?.pid?;
+        u32::try_from(pid).ok().filter(|pid| *pid > 0)
+    }
+
+    /// How the container exited, or `None` while it's still running
+    pub fn exited(&self) -> Option<Exit> {
+        let info = match block_on(async { Ok(self.docker.inspect_container(&self.name, None).await?) }) {
+            Ok(info) => info,
+            Err(_) => return Some(Exit::Stopped("container was removed".to_string())),
+        };
+        let state = info.state?;
+        if state.running.unwrap_or(false) {
+            return None;
+        }
+        Some(match state.exit_code {
+            Some(137) if state.oom_killed.unwrap_or(false) => {
+                Exit::Failed("was killed for running out of memory".to_string())
+            }
+            Some(0) => Exit::Succeeded,
+            // SIGINT, SIGKILL and SIGTERM, e.g. from `docker stop`
+            Some(code @ (130 | 137 | 143)) => Exit::Stopped(format!("exited with code {}", code)),
+            Some(code) => Exit::Failed(format!("exited with code {}", code)),
+            None => Exit::Stopped("stopped".to_string()),
+        })
+    }
+
+    /// Stops the container, SIGTERM first unless `force`, and removes it.
+    /// Returns whether it exited on its own.
+    pub fn stop(&self, force: bool) -> Result<bool> {
+        block_on(async {
+            if force {
+                self.docker.kill_container::<String>(&self.name, None).await.ok();
+            } else {
+                self.docker
+                    .stop_container(&self.name, Some(StopContainerOptions { t: STOP_TIMEOUT }))
+                    .await
+                    .with_context(|| format!("Failed to stop container {}", self.name))?;
+            }
+            let graceful = match self.docker.inspect_container(&self.name, None).await {
+                // 137 is SIGKILL, which `docker stop` sends once the timeout is up
+                Ok(info) => !force && info.state.and_then(|state| state.exit_code) != Some(137),
+                Err(_) => !force,
+            };
+            self.docker
+                .remove_container(&self.name, Some(RemoveContainerOptions { force: true, ..Default::default() }))
+                .await
+                .with_context(|| format!("Failed to remove container {}", self.name))?;
+            Ok(graceful)
+        })
+    }
+
+    /// Appends the container's output to `log_file` until it exits
+    fn follow_logs(&self, log_file: PathBuf) -> Result<()> {
+        if let Some(parent) = log_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&log_file)?;
+        let docker = self.docker.clone();
+        let name = self.name.clone();
+        std::thread::spawn(move || {
+            let _ = block_on(async {
+                let options = LogsOptions::<String> { follow: true, stdout: true, stderr: true, ..Default::default() };
+                let mut logs = docker.logs(&name, Some(options));
+                while let Some(Ok(output)) = logs.next().await {
+                    let bytes = match output {
+                        LogOutput::StdOut { message } | LogOutput::StdErr { message } | LogOutput::Console { message } => message,
+                        LogOutput::StdIn { .. } => continue,
+                    };
+                    file.write_all(&bytes)?;
+                }
+                Ok(())
+            });
+        });
+        Ok(())
+    }
+}
+
+/// Whether a service's container is running, for one started by an
+/// earlier command
+pub fn is_running(name: &str) -> bool {
+    block_on(async {
+        let docker = Docker::connect_with_local_defaults()?;
+        let info = docker.inspect_container(name, None).await?;
+        Ok(info.state.and_then(|state| state.running).unwrap_or(false))
+    })
+    .unwrap_or(false)
+}
+
+/// Stops and removes a container started by an earlier command
+pub fn remove(name: &str) -> Result<()> {
+    block_on(async {
+        let docker = Docker::connect_with_local_defaults().context("Failed to connect to Docker")?;
+        let _ = docker.stop_container(name, Some(StopContainerOptions { t: STOP_TIMEOUT })).await;
+        docker
+            .remove_container(name, Some(RemoveContainerOptions { force: true, ..Default::default() }))
+            .await
+            .with_context(|| format!("Failed to remove container {}", name))
+    })
+}
+
+/// Runs `future` on a runtime of its own, on a thread of its own so it
+/// works from inside another runtime too
+fn block_on<T: Send>(future: impl Future<Output = Result<T>> + Send) -> Result<T> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| tokio::runtime::Builder::new_current_thread().enable_all().build()?.block_on(future))
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
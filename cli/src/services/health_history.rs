@@ -0,0 +1,154 @@
+//! Health checks over time, for `syla dev report`.
+//!
+//! While `syla dev supervise` runs it checks every running service every
+//! 30 seconds and appends one JSON line per check to
+//! `.platform/state/health.log`. Once the log outgrows 16 MiB its older half
+//! is dropped, so a busy workspace keeps a few weeks of history.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const MAX_LOG_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Slow checks listed per service
+const SLOWEST: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub timestamp: DateTime<Utc>,
+    pub service: String,
+    pub healthy: bool,
+    pub latency_ms: u64,
+}
+
+fn path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(".platform/state/health.log")
+}
+
+pub fn record(workspace_root: &Path, samples: &[Sample]) -> Result<()> {
+    if samples.is_empty() {
+        return Ok(());
+    }
+    let path = path(workspace_root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::metadata(&path).is_ok_and(|metadata| metadata.len() > MAX_LOG_BYTES) {
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let lines: Vec<&str> = content.lines().collect();
+        let kept = lines[lines.len() / 2..].join("\n") + "\n";
+        std::fs::write(&path, kept).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut lines = String::new();
+    for sample in samples {
+        lines.push_str(&serde_json::to_string(sample)?);
+        lines.push('\n');
+    }
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Samples taken at or after `since`, oldest first; unreadable lines are
+/// skipped
+pub fn load(workspace_root: &Path, since: DateTime<Utc>) -> Result<Vec<Sample>> {
+    let path = path(workspace_root);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let mut samples: Vec<Sample> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Sample>(line).ok())
+        .filter(|sample| sample.timestamp >= since)
+        .collect();
+    samples.sort_by_key(|sample| sample.timestamp);
+    Ok(samples)
+}
+
+#[derive(Debug, Serialize)]
+pub struct SlowCheck {
+    pub timestamp: DateTime<Utc>,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceReport {
+    pub service: String,
+    pub checks: usize,
+    /// Share of checks that passed
+    pub uptime_percent: f64,
+    /// Runs of failed checks
+    pub incidents: usize,
+    /// Whether the latest check failed
+    pub down: bool,
+    /// From the first failed check of an incident to the next passing one,
+    /// over the incidents that ended
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_recovery_seconds: Option<u64>,
+    pub p95_latency_ms: u64,
+    pub slowest: Vec<SlowCheck>,
+}
+
+/// Summarises samples per service, sorted by name
+pub fn report(samples: &[Sample]) -> Vec<ServiceReport> {
+    let mut by_service: BTreeMap<&str, Vec<&Sample>> = BTreeMap::new();
+    for sample in samples {
+        by_service.entry(&sample.service).or_default().push(sample);
+    }
+    by_service.into_iter().map(|(service, samples)| service_report(service, &samples)).collect()
+}
+
+fn service_report(service: &str, samples: &[&Sample]) -> ServiceReport {
+    let healthy = samples.iter().filter(|sample| sample.healthy).count();
+    let mut incidents = 0;
+    let mut recoveries = Vec::new();
+    let mut failing_since = None;
+    for sample in samples {
+        match (sample.healthy, failing_since) {
+            (false, None) => {
+                incidents += 1;
+                failing_since = Some(sample.timestamp);
+            }
+            (true, Some(since)) => {
+                recoveries.push((sample.timestamp - since).num_seconds().max(0) as u64);
+                failing_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    let mut latencies: Vec<u64> = samples.iter().map(|sample| sample.latency_ms).collect();
+    latencies.sort_unstable();
+    let p95 = latencies
+        .get((latencies.len() * 95).div_ceil(100).saturating_sub(1))
+        .copied()
+        .unwrap_or_default();
+    let mut slowest: Vec<&Sample> = samples.to_vec();
+    slowest.sort_by(|a, b| b.latency_ms.cmp(&a.latency_ms).then_with(|| a.timestamp.cmp(&b.timestamp)));
+    slowest.truncate(SLOWEST);
+
+    ServiceReport {
+        service: service.to_string(),
+        checks: samples.len(),
+        uptime_percent: healthy as f64 * 100.0 / samples.len().max(1) as f64,
+        incidents,
+        down: failing_since.is_some(),
+        mean_recovery_seconds: (!recoveries.is_empty())
+            .then(|| recoveries.iter().sum::<u64>() / recoveries.len() as u64),
+        p95_latency_ms: p95,
+        slowest: slowest
+            .into_iter()
+            .map(|sample| SlowCheck { timestamp: sample.timestamp, latency_ms: sample.latency_ms })
+            .collect(),
+    }
+}
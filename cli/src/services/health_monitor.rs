@@ -4,12 +4,43 @@ use serde::{Deserialize, Serialize};
 
 use anyhow::Result;
 
+/// Wait before the first retry within a check; doubled for each one after
+const RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheck {
     pub endpoint: String,
     pub interval: Duration,
     pub timeout: Duration,
+    /// Further attempts within one check before it counts as failed
     pub retries: u32,
+    /// Failed checks in a row before a service turns unhealthy
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Passing checks in a row before an unhealthy service recovers
+    #[serde(default = "default_success_threshold")]
+    pub success_threshold: u32,
+}
+
+impl HealthCheck {
+    pub fn new(endpoint: String, interval: Duration) -> Self {
+        Self {
+            endpoint,
+            interval,
+            timeout: Duration::from_secs(5),
+            retries: 2,
+            failure_threshold: default_failure_threshold(),
+            success_threshold: default_success_threshold(),
+        }
+    }
+}
+
+fn default_failure_threshold() -> u32 {
+    3
+}
+
+fn default_success_threshold() -> u32 {
+    2
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -22,10 +53,10 @@ pub enum HealthStatus {
 
 #[derive(Debug, Clone)]
 pub struct ServiceHealth {
-    pub name: String,
     pub status: HealthStatus,
     pub last_check: Option<Instant>,
     pub consecutive_failures: u32,
+    pub consecutive_successes: u32,
     pub uptime: Option<Duration>,
     pub response_time: Option<Duration>,
 }
@@ -45,66 +76,69 @@ impl HealthMonitor {
 
     pub fn add_check(&mut self, name: String, check: HealthCheck) {
         self.checks.insert(name.clone(), check);
-        self.results.insert(name.clone(), ServiceHealth {
-            name,
+        self.results.insert(name, ServiceHealth {
             status: HealthStatus::Unknown,
             last_check: None,
             consecutive_failures: 0,
+            consecutive_successes: 0,
             uptime: None,
             response_time: None,
         });
     }
 
+    /// Runs a check, retrying a failed attempt with backoff, and returns
+    /// the service's status. It only changes after `failure_threshold`
+    /// failed checks in a row, or `success_threshold` passing ones, so a
+    /// blip doesn't flip it; a passing check settles an unknown status.
     pub fn perform_check(&mut self, name: &str) -> Result<HealthStatus> {
         let check = self.checks.get(name)
             .ok_or_else(|| anyhow::anyhow!("Health check {} not found", name))?
             .clone();
         
         let start = Instant::now();
-        let status = self.check_endpoint(&check)?;
+        let mut observed = self.check_endpoint(&check)?;
+        let mut backoff = RETRY_BACKOFF;
+        for _ in 0..check.retries {
+            if observed == HealthStatus::Healthy {
+                break;
+            }
+            std::thread::sleep(backoff);
+            backoff *= 2;
+            observed = self.check_endpoint(&check)?;
+        }
         let response_time = start.elapsed();
         
-        if let Some(health) = self.results.get_mut(name) {
-            health.status = status.clone();
-            health.last_check = Some(Instant::now());
-            health.response_time = Some(response_time);
-            
-            match &status {
-                HealthStatus::Healthy => {
-                    health.consecutive_failures = 0;
-                    if health.uptime.is_none() {
-                        health.uptime = Some(Duration::from_secs(0));
-                    }
+        let Some(health) = self.results.get_mut(name) else {
+            return Ok(observed);
+        };
+        health.last_check = Some(Instant::now());
+        health.response_time = Some(response_time);
+        
+        match &observed {
+            HealthStatus::Healthy => {
+                health.consecutive_failures = 0;
+                health.consecutive_successes += 1;
+                let recovered = health.consecutive_successes >= check.success_threshold;
+                if recovered || health.status == HealthStatus::Unknown {
+                    health.status = HealthStatus::Healthy;
                 }
-                HealthStatus::Unhealthy(_) | HealthStatus::Degraded(_) => {
-                    health.consecutive_failures += 1;
+                if health.uptime.is_none() {
+                    health.uptime = Some(Duration::from_secs(0));
                 }
-                _ => {}
             }
-        }
-        
-        Ok(status)
-    }
-
-    pub fn check_all(&mut self) -> HashMap<String, HealthStatus> {
-        let names: Vec<String> = self.checks.keys().cloned().collect();
-        let mut results = HashMap::new();
-        
-        for name in names {
-            if let Ok(status) = self.perform_check(&name) {
-                results.insert(name, status);
+            HealthStatus::Unhealthy(_) | HealthStatus::Degraded(_) => {
+                health.consecutive_successes = 0;
+                health.consecutive_failures += 1;
+                let failed = health.consecutive_failures >= check.failure_threshold;
+                // Once unhealthy, the latest reason is the one worth showing
+                if failed || !matches!(health.status, HealthStatus::Healthy | HealthStatus::Unknown) {
+                    health.status = observed;
+                }
             }
+            HealthStatus::Unknown => {}
         }
         
-        results
-    }
-
-    pub fn get_status(&self, name: &str) -> Option<&ServiceHealth> {
-        self.results.get(name)
-    }
-
-    pub fn get_all_status(&self) -> &HashMap<String, ServiceHealth> {
-        &self.results
+        Ok(health.status.clone())
     }
 
     fn check_endpoint(&self, check: &HealthCheck) -> Result<HealthStatus> {
@@ -133,17 +167,4 @@ impl HealthMonitor {
             Err(e) => Ok(HealthStatus::Unhealthy(format!("Connection error: {}", e))),
         }
     }
-
-    pub fn is_healthy(&self, name: &str) -> bool {
-        self.results.get(name)
-            .map(|h| matches!(h.status, HealthStatus::Healthy))
-            .unwrap_or(false)
-    }
-
-    pub fn get_unhealthy_services(&self) -> Vec<String> {
-        self.results.iter()
-            .filter(|(_, health)| !matches!(health.status, HealthStatus::Healthy | HealthStatus::Unknown))
-            .map(|(name, _)| name.clone())
-            .collect()
-    }
 }
\ No newline at end of file
@@ -0,0 +1,239 @@
+//! Where `dev logs` forwards parsed entries besides the terminal.
+//!
+//! Sinks are configured under `[logs]` in `.platform/config/workspace.toml`:
+//!
+//! ```toml
+//! [logs]
+//! sinks = [
+//!     { type = "file", path = ".logs/all.ndjson" },
+//!     { type = "journald" },
+//!     { type = "syslog", address = "127.0.0.1:514" },
+//! ]
+//! ```
+//!
+//! A file gets one JSON entry per line unless it asks for another `format`;
+//! syslog takes RFC 5424 messages on `/dev/log` unless given a socket path
+//! or a UDP `host:port`. Only entries that pass the `dev logs` filters are
+//! forwarded, so `syla dev logs <service> --follow` keeps a sink fed.
+
+use anyhow::{Context, Result};
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::UdpSocket;
+use std::path::{Path, PathBuf};
+
+use super::log_streamer::{LogEntry, LogFormat, LogLevel};
+
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+const SYSLOG_SOCKET: &str = "/dev/log";
+/// Syslog's `user` facility, for messages from ordinary programs
+const FACILITY_USER: u8 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl LogSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// Appends to a file, relative to the workspace root
+    File {
+        path: PathBuf,
+        #[serde(default = "default_file_format")]
+        format: LogFormat,
+    },
+    /// The systemd journal's native protocol
+    Journald {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        socket: Option<PathBuf>,
+    },
+    Syslog {
+        /// A unix socket path or a UDP `host:port`; `/dev/log` when unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        address: Option<String>,
+    },
+}
+
+fn default_file_format() -> LogFormat {
+    LogFormat::Json
+}
+
+pub enum LogSink {
+    File { file: File, format: LogFormat },
+    Journald(Datagram),
+    Syslog { socket: Datagram, hostname: String },
+}
+
+pub enum Datagram {
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixDatagram),
+    Udp(UdpSocket),
+}
+
+impl Datagram {
+    fn connect(address: &str) -> Result<Self> {
+        #[cfg(unix)]
+        if address.starts_with('/') {
+            let socket = std::os::unix::net::UnixDatagram::unbound()?;
+            socket.connect(address)?;
+            return Ok(Datagram::Unix(socket));
+        }
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.connect(address)?;
+        Ok(Datagram::Udp(socket))
+    }
+
+    fn send(&self, payload: &[u8]) -> std::io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Datagram::Unix(socket) => socket.send(payload).map(drop),
+            Datagram::Udp(socket) => socket.send(payload).map(drop),
+        }
+    }
+}
+
+impl LogSink {
+    pub fn open(config: &SinkConfig, workspace_root: &Path) -> Result<Self> {
+        match config {
+            SinkConfig::File { path, format } => {
+                let path = workspace_root.join(path);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .with_context(|| format!("Failed to open log sink {}", path.display()))?;
+                Ok(LogSink::File { file, format: *format })
+            }
+            SinkConfig::Journald { socket } => {
+                let socket = socket.as_deref().unwrap_or(Path::new(JOURNALD_SOCKET));
+                let datagram = Datagram::connect(&socket.to_string_lossy())
+                    .with_context(|| format!("Failed to connect to journald at {}", socket.display()))?;
+                Ok(LogSink::Journald(datagram))
+            }
+            SinkConfig::Syslog { address } => {
+                let address = address.as_deref().unwrap_or(SYSLOG_SOCKET);
+                let socket = Datagram::connect(address)
+                    .with_context(|| format!("Failed to connect to syslog at {}", address))?;
+                Ok(LogSink::Syslog { socket, hostname: hostname() })
+            }
+        }
+    }
+
+    /// Forwards one entry; a sink that went away doesn't stop the terminal
+    /// output, so failures are only logged
+    pub fn send(&self, entry: &LogEntry) {
+        let result = match self {
+            LogSink::File { file, format } => {
+                let mut line = render(entry, *format);
+                line.push('\n');
+                // Unbuffered so a `tail -f` on the file keeps up
+                (&*file).write_all(line.as_bytes())
+            }
+            LogSink::Journald(socket) => socket.send(&journald_message(entry)),
+            LogSink::Syslog { socket, hostname } => socket.send(syslog_message(entry, hostname).as_bytes()),
+        };
+        if let Err(e) = result {
+            tracing::debug!("Failed to forward a log entry: {}", e);
+        }
+    }
+}
+
+fn render(entry: &LogEntry, format: LogFormat) -> String {
+    match format {
+        LogFormat::Json => serde_json::to_string(entry).unwrap_or_default(),
+        LogFormat::Raw => entry.raw.clone(),
+        LogFormat::Pretty => format!(
+            "{} {:5} {} {}",
+            entry.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            format!("{:?}", entry.level).to_uppercase(),
+            entry.service,
+            entry.message
+        ),
+    }
+}
+
+/// Syslog severity, which journald's `PRIORITY` shares
+fn severity(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Error => 3,
+        LogLevel::Warn => 4,
+        LogLevel::Info => 6,
+        LogLevel::Debug | LogLevel::Trace => 7,
+    }
+}
+
+fn syslog_message(entry: &LogEntry, hostname: &str) -> String {
+    // APP-NAME is at most 48 printable characters
+    let app: String = entry.service.chars().filter(|c| c.is_ascii_graphic()).take(48).collect();
+    format!(
+        "<{}>1 {} {} {} - - - {}",
+        FACILITY_USER * 8 + severity(entry.level),
+        entry.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+        hostname,
+        app,
+        entry.message
+    )
+}
+
+fn journald_message(entry: &LogEntry) -> Vec<u8> {
+    let mut message = Vec::new();
+    journald_field(&mut message, "MESSAGE", &entry.message);
+    journald_field(&mut message, "PRIORITY", &severity(entry.level).to_string());
+    journald_field(&mut message, "SYSLOG_IDENTIFIER", &entry.service);
+    journald_field(&mut message, "SYLA_SERVICE", &entry.service);
+    for (key, value) in &entry.fields {
+        let Some(name) = journald_field_name(key) else { continue };
+        match value {
+            serde_json::Value::String(value) => journald_field(&mut message, &name, value),
+            value => journald_field(&mut message, &name, &value.to_string()),
+        }
+    }
+    message
+}
+
+/// Appends `NAME=value`, or the length-prefixed form journald wants for
+/// values spanning lines
+fn journald_field(message: &mut Vec<u8>, name: &str, value: &str) {
+    message.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        message.push(b'\n');
+        message.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        message.push(b'=');
+    }
+    message.extend_from_slice(value.as_bytes());
+    message.push(b'\n');
+}
+
+/// Journal field names are uppercase letters, digits and underscores, and
+/// may not start with an underscore or a digit
+fn journald_field_name(key: &str) -> Option<String> {
+    let name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    let first = name.chars().next()?;
+    (first.is_ascii_uppercase() && name.len() <= 64).then_some(name)
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "-".to_string())
+}
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek};
+use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local, Utc};
@@ -13,6 +13,8 @@ use serde::{Deserialize, Serialize};
 use colored::*;
 use regex::Regex;
 
+use super::log_sink::LogSink;
+
 /// Log entry with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
@@ -24,11 +26,12 @@ pub struct LogEntry {
     pub raw: String,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
 pub enum LogLevel {
     Trace,
     Debug,
     Info,
+    #[value(alias = "warning")]
     Warn,
     Error,
 }
@@ -64,11 +67,13 @@ pub struct LogStreamConfig {
     pub level_filter: Option<LogLevel>,
     pub service_filter: Option<String>,
     pub pattern_filter: Option<Regex>,
+    /// Entries shown before and after each `pattern_filter` match, per service
+    pub context: usize,
     pub format: LogFormat,
-    pub buffer_size: usize,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
 pub enum LogFormat {
     Pretty,
     Json,
@@ -83,23 +88,30 @@ impl Default for LogStreamConfig {
             level_filter: None,
             service_filter: None,
             pattern_filter: None,
+            context: 0,
             format: LogFormat::Pretty,
-            buffer_size: 8192,
         }
     }
 }
 
+/// What a watcher sends to the streamer
+enum Event {
+    Entry(LogEntry),
+    /// Everything already in the file has been sent
+    CaughtUp,
+}
+
 /// Log file watcher that monitors changes and sends new entries
 struct LogWatcher {
     path: PathBuf,
     service: String,
-    sender: Sender<LogEntry>,
+    sender: Sender<Event>,
     position: u64,
     parser: LogParser,
 }
 
 impl LogWatcher {
-    fn new(path: PathBuf, service: String, sender: Sender<LogEntry>) -> Self {
+    fn new(path: PathBuf, service: String, sender: Sender<Event>) -> Self {
         Self {
             path,
             service,
@@ -114,22 +126,22 @@ impl LogWatcher {
             .with_context(|| format!("Failed to open log file: {}", self.path.display()))?;
         
         let mut reader = BufReader::new(file);
-        
-        // Seek to end if following
-        if follow {
-            self.position = reader.seek(SeekFrom::End(0))?;
-        }
+        let mut caught_up = false;
         
         loop {
             // Read new lines
             let mut line = String::new();
             while reader.read_line(&mut line)? > 0 {
                 if let Some(entry) = self.parser.parse_line(&line, &self.service) {
-                    let _ = self.sender.send(entry);
+                    let _ = self.sender.send(Event::Entry(entry));
                 }
                 line.clear();
                 self.position = reader.stream_position()?;
             }
+            if !caught_up {
+                caught_up = true;
+                let _ = self.sender.send(Event::CaughtUp);
+            }
             
             if !follow {
                 break;
@@ -156,7 +168,6 @@ impl LogWatcher {
 
 /// Log parser that extracts structured data from log lines
 struct LogParser {
-    json_regex: Regex,
     level_regex: Regex,
     timestamp_regex: Regex,
 }
@@ -164,7 +175,6 @@ struct LogParser {
 impl LogParser {
     fn new() -> Self {
         Self {
-            json_regex: Regex::new(r"^\{.*\}$").unwrap(),
             level_regex: Regex::new(r"(?i)\b(TRACE|DEBUG|INFO|WARN|WARNING|ERROR)\b").unwrap(),
             timestamp_regex: Regex::new(r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}").unwrap(),
         }
@@ -177,7 +187,7 @@ impl LogParser {
         }
 
         // Try to parse as JSON first
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(line) {
+        if let Ok(json @ serde_json::Value::Object(_)) = serde_json::from_str::<serde_json::Value>(line) {
             return self.parse_json_log(json, service, line);
         }
 
@@ -192,25 +202,22 @@ impl LogParser {
         let timestamp = obj.remove("timestamp")
             .or_else(|| obj.remove("time"))
             .or_else(|| obj.remove("ts"))
-            .and_then(|v| v.as_str())
-            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .and_then(|v| DateTime::parse_from_rfc3339(v.as_str()?).ok())
             .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(|| Utc::now());
+            .unwrap_or_else(Utc::now);
         
         let level = obj.remove("level")
             .or_else(|| obj.remove("severity"))
-            .and_then(|v| v.as_str())
-            .map(LogLevel::from_str)
+            .and_then(|v| v.as_str().map(LogLevel::from_str))
             .unwrap_or(LogLevel::Info);
         
         let message = obj.remove("message")
             .or_else(|| obj.remove("msg"))
-            .and_then(|v| v.as_str())
-            .unwrap_or(raw)
-            .to_string();
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| raw.to_string());
         
         // Remaining fields become metadata
-        let fields: HashMap<String, serde_json::Value> = obj.clone();
+        let fields: HashMap<String, serde_json::Value> = std::mem::take(obj).into_iter().collect();
         
         Some(LogEntry {
             timestamp,
@@ -227,7 +234,7 @@ impl LogParser {
         let timestamp = self.timestamp_regex.find(line)
             .and_then(|m| DateTime::parse_from_str(m.as_str(), "%Y-%m-%d %H:%M:%S").ok())
             .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(|| Utc::now());
+            .unwrap_or_else(Utc::now);
         
         // Extract log level
         let level = self.level_regex.find(line)
@@ -245,11 +252,88 @@ impl LogParser {
     }
 }
 
+/// Whether an entry is shown for matching the pattern or as context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line {
+    Match,
+    Context,
+}
+
+struct Shown {
+    entry: LogEntry,
+    line: Line,
+    /// Entries of the same service were left out just before this one
+    gap: bool,
+    /// The match this entry belongs to, counting from 1
+    group: usize,
+}
+
+/// Picks the entries a pattern shows: the matches and, like `grep -C`, the
+/// entries around them. Context is kept per service, as their logs interleave.
+struct ContextFilter {
+    context: usize,
+    matches: usize,
+    services: HashMap<String, ServiceContext>,
+}
+
+#[derive(Default)]
+struct ServiceContext {
+    /// Recent entries, shown if a match follows
+    before: VecDeque<LogEntry>,
+    /// Entries still to show after the last match
+    after: usize,
+    /// Something was left out since the last shown entry
+    skipped: bool,
+    shown_any: bool,
+}
+
+impl ContextFilter {
+    fn new(context: usize) -> Self {
+        Self { context, matches: 0, services: HashMap::new() }
+    }
+
+    fn push(&mut self, entry: LogEntry, pattern: &Regex) -> Vec<Shown> {
+        let state = self.services.entry(entry.service.clone()).or_default();
+        let mut shown = Vec::new();
+        let mut show = |state: &mut ServiceContext, entry: LogEntry, line: Line, group: usize| {
+            shown.push(Shown { entry, line, gap: state.shown_any && state.skipped, group });
+            state.skipped = false;
+            state.shown_any = true;
+        };
+
+        if pattern.is_match(&entry.message) {
+            self.matches += 1;
+            for before in std::mem::take(&mut state.before) {
+                show(state, before, Line::Context, self.matches);
+            }
+            show(state, entry, Line::Match, self.matches);
+            state.after = self.context;
+        } else if state.after > 0 {
+            state.after -= 1;
+            show(state, entry, Line::Context, self.matches);
+        } else {
+            state.before.push_back(entry);
+            if state.before.len() > self.context {
+                state.before.pop_front();
+                state.skipped = true;
+            }
+        }
+        shown
+    }
+}
+
 /// Main log streaming service
 pub struct LogStreamer {
     watchers: Arc<Mutex<HashMap<String, thread::JoinHandle<()>>>>,
-    receiver: Arc<Mutex<Receiver<LogEntry>>>,
-    sender: Sender<LogEntry>,
+    receiver: Arc<Mutex<Receiver<Event>>>,
+    sender: Sender<Event>,
+    sinks: Vec<LogSink>,
+}
+
+impl Default for LogStreamer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl LogStreamer {
@@ -260,17 +344,26 @@ impl LogStreamer {
             watchers: Arc::new(Mutex::new(HashMap::new())),
             receiver: Arc::new(Mutex::new(receiver)),
             sender,
+            sinks: Vec::new(),
         }
     }
 
+    /// Forward displayed entries to `sink` too
+    pub fn add_sink(&mut self, sink: LogSink) {
+        self.sinks.push(sink);
+    }
+
     /// Add a log file to watch
     pub fn add_log_file(&self, service: String, path: PathBuf, follow: bool) -> Result<()> {
         let sender = self.sender.clone();
+        let name = service.clone();
         
         let handle = thread::spawn(move || {
-            let mut watcher = LogWatcher::new(path, service.clone(), sender);
+            let mut watcher = LogWatcher::new(path, name.clone(), sender.clone());
             if let Err(e) = watcher.watch(follow) {
-                eprintln!("Error watching log file for {}: {}", service, e);
+                eprintln!("Error watching log file for {}: {}", name, e);
+                // Don't keep the streamer waiting for a file it can't read
+                let _ = sender.send(Event::CaughtUp);
             }
         });
         
@@ -282,47 +375,61 @@ impl LogStreamer {
     pub fn stream(&self, config: LogStreamConfig) -> Result<()> {
         let receiver = self.receiver.lock().unwrap();
         let mut buffer = Vec::new();
-        let mut count = 0;
+        let mut context = ContextFilter::new(config.context);
         
-        // Collect logs first if not following
+        // Collect what the files already hold, then show the last N lines
         if !config.follow {
-            while let Ok(entry) = receiver.recv_timeout(Duration::from_millis(100)) {
-                if self.should_display(&entry, &config) {
-                    buffer.push(entry);
-                }
+            // Every file has been read once its watcher is done
+            let watchers: Vec<_> = self.watchers.lock().unwrap().drain().map(|(_, handle)| handle).collect();
+            for handle in watchers {
+                let _ = handle.join();
             }
-            
-            // Display last N lines
-            let start = buffer.len().saturating_sub(config.lines.unwrap_or(buffer.len()));
-            for entry in &buffer[start..] {
-                self.display_entry(entry, &config);
+            while let Ok(event) = receiver.recv_timeout(Duration::from_millis(100)) {
+                if let Event::Entry(entry) = event {
+                    if self.should_display(&entry, &config) {
+                        buffer.push(entry);
+                    }
+                }
             }
-            
+            self.display_backlog(buffer, &config, &mut context);
             return Ok(());
         }
+
+        let mut pending = self.watchers.lock().unwrap().len();
+        while pending > 0 {
+            match receiver.recv() {
+                Ok(Event::Entry(entry)) => {
+                    if self.should_display(&entry, &config) {
+                        buffer.push(entry);
+                    }
+                }
+                Ok(Event::CaughtUp) => pending -= 1,
+                Err(_) => break,
+            }
+        }
+        self.display_backlog(buffer, &config, &mut context);
         
         // Stream logs in real-time
-        println!("{}", "Streaming logs (press Ctrl-C to stop)...".dimmed());
+        if config.format == LogFormat::Pretty {
+            println!("{}", "Streaming logs (press Ctrl-C to stop)...".dimmed());
+        }
         
         loop {
             match receiver.recv_timeout(Duration::from_millis(100)) {
-                Ok(entry) => {
+                Ok(Event::CaughtUp) => {}
+                Ok(Event::Entry(entry)) => {
                     if self.should_display(&entry, &config) {
-                        self.display_entry(&entry, &config);
-                        count += 1;
-                        
-                        if let Some(limit) = config.lines {
-                            if count >= limit && !config.follow {
-                                break;
+                        match &config.pattern_filter {
+                            Some(pattern) => {
+                                for shown in context.push(entry, pattern) {
+                                    self.display_shown(&shown, shown.gap, &config);
+                                }
                             }
+                            None => self.display_entry(&entry, &config, Line::Match),
                         }
                     }
                 }
-                Err(mpsc::RecvTimeoutError::Timeout) => {
-                    if !config.follow {
-                        break;
-                    }
-                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
                 Err(mpsc::RecvTimeoutError::Disconnected) => break,
             }
         }
@@ -330,6 +437,24 @@ impl LogStreamer {
         Ok(())
     }
 
+    /// Shows the last N of `buffer`'s entries, or with a pattern the last N
+    /// matches with their context
+    fn display_backlog(&self, buffer: Vec<LogEntry>, config: &LogStreamConfig, context: &mut ContextFilter) {
+        if let Some(pattern) = &config.pattern_filter {
+            let shown: Vec<Shown> = buffer.into_iter().flat_map(|entry| context.push(entry, pattern)).collect();
+            let cutoff = context.matches.saturating_sub(config.lines.unwrap_or(context.matches));
+            for (index, shown) in shown.into_iter().filter(|shown| shown.group > cutoff).enumerate() {
+                self.display_shown(&shown, index > 0 && shown.gap, config);
+            }
+            return;
+        }
+
+        let start = buffer.len().saturating_sub(config.lines.unwrap_or(buffer.len()));
+        for entry in &buffer[start..] {
+            self.display_entry(entry, config, Line::Match);
+        }
+    }
+
     fn should_display(&self, entry: &LogEntry, config: &LogStreamConfig) -> bool {
         // Check level filter
         if let Some(min_level) = config.level_filter {
@@ -345,35 +470,45 @@ impl LogStreamer {
             }
         }
         
-        // Check pattern filter
-        if let Some(ref pattern) = config.pattern_filter {
-            if !pattern.is_match(&entry.message) {
-                return false;
-            }
-        }
-        
+        // The pattern is applied afterwards, so context can be kept
         true
     }
 
-    fn display_entry(&self, entry: &LogEntry, config: &LogStreamConfig) {
+    fn display_shown(&self, shown: &Shown, separate: bool, config: &LogStreamConfig) {
+        if separate && config.format != LogFormat::Json {
+            println!("{}", "--".dimmed());
+        }
+        self.display_entry(&shown.entry, config, shown.line);
+    }
+
+    fn display_entry(&self, entry: &LogEntry, config: &LogStreamConfig, line: Line) {
+        let highlight = config.pattern_filter.as_ref().filter(|_| line == Line::Match);
         match config.format {
-            LogFormat::Pretty => self.display_pretty(entry),
+            LogFormat::Pretty => self.display_pretty(entry, highlight, line),
             LogFormat::Json => self.display_json(entry),
-            LogFormat::Raw => println!("{}", entry.raw),
+            LogFormat::Raw => println!("{}", highlighted(&entry.raw, highlight)),
+        }
+        for sink in &self.sinks {
+            sink.send(entry);
         }
     }
 
-    fn display_pretty(&self, entry: &LogEntry) {
+    fn display_pretty(&self, entry: &LogEntry, highlight: Option<&Regex>, line: Line) {
         let timestamp = entry.timestamp.with_timezone(&Local).format("%H:%M:%S%.3f");
         let level = format!("{:5}", format!("{:?}", entry.level).to_uppercase())
             .color(entry.level.color());
         let service = entry.service.bright_black();
         
+        let message = match line {
+            Line::Match => highlighted(&entry.message, highlight),
+            Line::Context => entry.message.dimmed().to_string(),
+        };
+        
         println!("{} {} {} {}", 
             timestamp.to_string().dimmed(),
             level,
             service,
-            entry.message
+            message
         );
         
         // Display additional fields if present
@@ -391,28 +526,6 @@ impl LogStreamer {
         }
     }
 
-    /// Create a log file for a service
-    pub fn create_log_file(&self, service: &str, log_dir: &Path) -> Result<PathBuf> {
-        std::fs::create_dir_all(log_dir)?;
-        
-        let log_path = log_dir.join(format!("{}.log", service));
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_path)?;
-        
-        // Write initial log entry
-        let mut writer = BufWriter::new(file);
-        writeln!(writer, "{} {} Service '{}' started", 
-            Utc::now().format("%Y-%m-%d %H:%M:%S"),
-            "[INFO]".green(),
-            service
-        )?;
-        writer.flush()?;
-        
-        Ok(log_path)
-    }
-
     /// Stop all watchers
     pub fn stop(&self) {
         let mut watchers = self.watchers.lock().unwrap();
@@ -420,6 +533,16 @@ impl LogStreamer {
     }
 }
 
+/// `text` with what `pattern` matches in bold red, like `grep --color`
+fn highlighted(text: &str, pattern: Option<&Regex>) -> String {
+    match pattern {
+        Some(pattern) => pattern
+            .replace_all(text, |caps: &regex::Captures| caps[0].red().bold().to_string())
+            .into_owned(),
+        None => text.to_string(),
+    }
+}
+
 impl Drop for LogStreamer {
     fn drop(&mut self) {
         self.stop();
@@ -1,4 +1,10 @@
+pub mod container;
 pub mod process_manager;
+pub mod health_history;
 pub mod health_monitor;
+pub mod log_sink;
+pub mod log_streamer;
+pub mod registry;
+pub mod state;
 
 pub use process_manager::{ProcessManager, ProcessConfig};
\ No newline at end of file
@@ -1,14 +1,32 @@
 use std::collections::HashMap;
-use std::process::{Child, Command, Stdio};
+use std::fmt;
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::path::PathBuf;
 use std::thread;
 
 use colored::*;
+use serde::{Deserialize, Serialize};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crate::config::Config;
+use crate::events;
+use crate::interpolation;
+use crate::notifications::{notify, Event};
+use crate::services::container::Container;
+use crate::services::health_monitor::{HealthCheck, HealthMonitor, HealthStatus as Checked};
+use crate::services::state::{Restart, StartedServices};
+use crate::ui;
+
+/// How often a running service is checked for having exited
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Delay before the first restart in a row; it doubles with each one after
+const RESTART_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+/// A service that stays up this long starts over with a short backoff and
+/// its full number of restarts
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Clone)]
 pub struct ProcessConfig {
@@ -21,17 +39,160 @@ pub struct ProcessConfig {
     pub health_check_interval: Duration,
     pub startup_timeout: Duration,
     pub restart_policy: RestartPolicy,
+    /// Restarts in a row before the service is left failed
+    pub max_restarts: u32,
     pub log_file: Option<PathBuf>,
+    pub backend: Backend,
+}
+
+/// What a service runs as
+#[derive(Debug, Clone, PartialEq)]
+pub enum Backend {
+    /// `command` spawned in `working_dir`
+    Process,
+    /// A container from `image`; `command` and `args`, when set, replace
+    /// the image's command
+    Container { image: String },
+}
+
+/// A started service: a child process or its container
+pub enum Running {
+    Process(Child),
+    Container(Container),
+}
+
+impl Running {
+    /// Process ID on the host, for a container that of its main process
+    pub fn id(&self) -> Option<u32> {
+        match self {
+            Running::Process(child) => Some(child.id()),
+            Running::Container(container) => container.pid(),
+        }
+    }
+
+    /// How the service exited, or `None` while it's still running
+    fn exited(&mut self) -> Option<Exit> {
+        match self {
+            Running::Process(child) => {
+                let status = child.try_wait().ok().flatten()?;
+                Some(match status.code() {
+                    Some(0) => Exit::Succeeded,
+                    Some(code) => Exit::Failed(format!("exited with code {}", code)),
+                    None => signal_exit(status),
+                })
+            }
+            Running::Container(container) => container.exited(),
+        }
+    }
+
+    /// Stops the service, giving it 5 seconds to exit on SIGTERM unless
+    /// `force`. Returns whether it exited on its own.
+    fn stop(&mut self, force: bool) -> Result<bool> {
+        let process = match self {
+            Running::Process(process) => process,
+            Running::Container(container) => return container.stop(force),
+        };
+        if force {
+            process.kill()?;
+            return Ok(false);
+        }
+        #[cfg(unix)]
+        {
+            use nix::sys::signal::{self, Signal};
+            use nix::unistd::Pid;
+
+            if let Ok(pid) = process.id().try_into() {
+                let _ = signal::kill(Pid::from_raw(pid), Signal::SIGTERM);
+            }
+        }
+
+        // Wait for graceful shutdown
+        thread::sleep(Duration::from_secs(5));
+
+        match process.try_wait()? {
+            Some(_) => Ok(true),
+            None => {
+                process.kill()?;
+                Ok(false)
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn signal_exit(status: ExitStatus) -> Exit {
+    use nix::sys::signal::Signal;
+    use std::os::unix::process::ExitStatusExt;
+
+    match status.signal().map(Signal::try_from) {
+        Some(Ok(signal @ (Signal::SIGINT | Signal::SIGKILL | Signal::SIGTERM))) => {
+            Exit::Stopped(format!("was stopped by {}", signal))
+        }
+        Some(Ok(signal)) => Exit::Failed(format!("was killed by {}", signal)),
+        _ => Exit::Failed("was killed by a signal".to_string()),
+    }
+}
+
+#[cfg(not(unix))]
+fn signal_exit(_status: ExitStatus) -> Exit {
+    Exit::Failed("was killed by a signal".to_string())
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// How a service ended, which decides whether its policy restarts it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Exit {
+    /// Exited with code 0
+    Succeeded,
+    /// Exited with another code, crashed or stopped passing its health check
+    Failed(String),
+    /// Ended by SIGINT, SIGTERM or SIGKILL from outside the supervisor,
+    /// e.g. `kill` or `platform stop`
+    Stopped(String),
+}
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exit::Succeeded => write!(f, "exited with code 0"),
+            Exit::Failed(how) | Exit::Stopped(how) => write!(f, "{}", how),
+        }
+    }
+}
+
+/// When the supervisor restarts a service that ends or turns unhealthy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum RestartPolicy {
     Never,
+    /// After it fails: a non-zero exit, a crash or a failing health check
+    #[default]
     OnFailure,
+    /// Whenever it ends, even when stopped from outside the supervisor
     Always,
+    /// Whenever it ends, unless it was stopped from outside the supervisor
     UnlessStopped,
 }
 
+impl RestartPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == RestartPolicy::default()
+    }
+
+    fn restarts_after(self, exit: &Exit) -> bool {
+        match self {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => matches!(exit, Exit::Failed(_)),
+            RestartPolicy::Always => true,
+            RestartPolicy::UnlessStopped => !matches!(exit, Exit::Stopped(_)),
+        }
+    }
+}
+
+/// Wait before the `attempt`th restart in a row, counting from 1
+fn backoff(attempt: u32) -> Duration {
+    RESTART_BACKOFF.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1))).min(MAX_RESTART_BACKOFF)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ProcessState {
     Starting,
@@ -45,9 +206,11 @@ pub enum ProcessState {
 pub struct ServiceProcess {
     pub config: ProcessConfig,
     pub state: ProcessState,
-    pub process: Option<Child>,
+    pub process: Option<Running>,
     pub started_at: Option<Instant>,
     pub restart_count: u32,
+    /// Restarts in a row, without the service staying up in between
+    pub attempts: u32,
     pub last_health_check: Option<Instant>,
     pub health_status: HealthStatus,
 }
@@ -93,13 +256,14 @@ impl ProcessManager {
             process: None,
             started_at: None,
             restart_count: 0,
+            attempts: 0,
             last_health_check: None,
             health_status: HealthStatus::Unknown,
         };
 
-        match self.spawn_process(&process_config) {
-            Ok(child) => {
-                service.process = Some(child);
+        match launch(&self.config, &process_config) {
+            Ok(running) => {
+                service.process = Some(running);
                 service.state = ProcessState::Running;
                 service.started_at = Some(Instant::now());
                 
@@ -113,7 +277,7 @@ impl ProcessManager {
                 services.insert(name.clone(), service);
                 
                 // Start health monitoring
-                self.start_health_monitoring(name);
+                self.start_health_monitoring(name, &process_config);
                 
                 Ok(())
             }
@@ -139,37 +303,14 @@ impl ProcessManager {
             service.state = ProcessState::Stopping;
             
             if let Some(mut process) = service.process.take() {
-                if force {
-                    process.kill()?;
-                    println!("{} {} killed", "✓".yellow(), name);
-                } else {
-                    // Try graceful shutdown first
-                    #[cfg(unix)]
-                    {
-                        use nix::sys::signal::{self, Signal};
-                        use nix::unistd::Pid;
-                        
-                        if let Ok(pid) = process.id().try_into() {
-                            let _ = signal::kill(Pid::from_raw(pid), Signal::SIGTERM);
-                        }
-                    }
-                    
-                    // Wait for graceful shutdown
-                    thread::sleep(Duration::from_secs(5));
-                    
-                    match process.try_wait()? {
-                        Some(_) => {
-                            println!("{} {} stopped gracefully", "✓".green(), name);
-                        }
-                        None => {
-                            process.kill()?;
-                            println!("{} {} force killed", "✓".yellow(), name);
-                        }
-                    }
+                match process.stop(force)? {
+                    _ if force => println!("{} {} killed", "✓".yellow(), name),
+                    true => println!("{} {} stopped gracefully", "✓".green(), name),
+                    false => println!("{} {} force killed", "✓".yellow(), name),
                 }
-                
-                service.state = ProcessState::Stopped;
             }
+            // Also ends a restart the supervisor is waiting to make
+            service.state = ProcessState::Stopped;
             
             Ok(())
         } else {
@@ -207,6 +348,21 @@ impl ProcessManager {
         services.get(name).map(|s| (s.state.clone(), s.health_status.clone()))
     }
 
+    /// Process ID of a running service
+    pub fn pid(&self, name: &str) -> Option<u32> {
+        let services = self.services.lock().unwrap();
+        services.get(name)?.process.as_ref()?.id()
+    }
+
+    /// Name of the container a running service is in
+    pub fn container(&self, name: &str) -> Option<String> {
+        let services = self.services.lock().unwrap();
+        match services.get(name)?.process.as_ref()? {
+            Running::Container(container) => Some(container.name().to_string()),
+            Running::Process(_) => None,
+        }
+    }
+
     pub fn list_services(&self) -> Vec<(String, ProcessState, HealthStatus)> {
         let services = self.services.lock().unwrap();
         services.iter()
@@ -216,94 +372,137 @@ impl ProcessManager {
             .collect()
     }
 
-    fn spawn_process(&self, config: &ProcessConfig) -> Result<Child> {
-        let mut cmd = Command::new(&config.command);
-        
-        cmd.args(&config.args)
-            .current_dir(&config.working_dir)
-            .envs(&config.env)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-        
-        #[cfg(unix)]
-        {
-            use std::os::unix::process::CommandExt;
-            // Create new process group
-            cmd.process_group(0);
-        }
-        
-        Ok(cmd.spawn()?)
+    /// Times a service has been restarted since it was first started
+    pub fn restart_count(&self, name: &str) -> u32 {
+        let services = self.services.lock().unwrap();
+        services.get(name).map_or(0, |s| s.restart_count)
     }
 
-    fn start_health_monitoring(&self, name: String) {
+    fn start_health_monitoring(&self, name: String, config: &ProcessConfig) {
         let services = self.services.clone();
+        let manager_config = self.config.clone();
+        let notifications = self.config.settings.notifications.clone();
+        let interval = config.health_check_interval;
+        let startup_timeout = config.startup_timeout;
+        let mut monitor = HealthMonitor::new();
+        if let Some(url) = &config.health_check_url {
+            monitor.add_check(name.clone(), HealthCheck::new(url.clone(), interval));
+        }
         
         thread::spawn(move || {
+            let mut next_check = Instant::now() + interval;
             loop {
-                thread::sleep(Duration::from_secs(10));
+                thread::sleep(EXIT_POLL_INTERVAL);
                 
-                let should_check = {
-                    let services = services.lock().unwrap();
-                    if let Some(service) = services.get(&name) {
-                        matches!(service.state, ProcessState::Running)
-                            && service.config.health_check_url.is_some()
-                    } else {
-                        false
+                let exited = {
+                    let mut services = services.lock().unwrap();
+                    let Some(service) = services.get_mut(&name) else {
+                        break;
+                    };
+                    if !matches!(service.state, ProcessState::Running) {
+                        break;
+                    }
+                    
+                    // A process that exits while it should be running has crashed
+                    let exited = service.process.as_mut().and_then(Running::exited);
+                    if let Some(exit) = &exited {
+                        let exit = exit.to_string();
+                        println!("{} {} {}", "✗".red(), name.bold(), exit);
+                        service.process = None;
+                        events::emit(events::Event::ServiceExited { service: &name, exit: &exit });
+                        notify(&notifications, Event::ServiceCrashed { service: &name, exit });
                     }
+                    exited
                 };
-                
-                if !should_check {
-                    break;
+                if let Some(exit) = exited {
+                    match supervise(&services, &manager_config, &name, exit) {
+                        true => continue,
+                        false => break,
+                    }
                 }
+                if Instant::now() < next_check {
+                    continue;
+                }
+                next_check = Instant::now() + interval;
                 
-                // Perform health check
-                let health_status = {
-                    let services = services.lock().unwrap();
-                    if let Some(service) = services.get(&name) {
-                        if let Some(url) = &service.config.health_check_url {
-                            match Self::check_health(url) {
-                                Ok(()) => HealthStatus::Healthy,
-                                Err(e) => HealthStatus::Unhealthy(e.to_string()),
-                            }
-                        } else {
-                            HealthStatus::Unknown
-                        }
-                    } else {
-                        break;
-                    }
+                // Retried, and only flips after several checks in a row
+                let health_status = match monitor.perform_check(&name) {
+                    Ok(Checked::Healthy) => HealthStatus::Healthy,
+                    Ok(Checked::Unhealthy(reason) | Checked::Degraded(reason)) => HealthStatus::Unhealthy(reason),
+                    Ok(Checked::Unknown) => HealthStatus::Unknown,
+                    // No health check configured
+                    Err(_) => continue,
                 };
                 
                 // Update health status
-                let mut services = services.lock().unwrap();
-                if let Some(service) = services.get_mut(&name) {
+                let unhealthy = {
+                    let mut services = services.lock().unwrap();
+                    let Some(service) = services.get_mut(&name) else {
+                        break;
+                    };
+                    // Only transitions are worth interrupting someone for
+                    match (&service.health_status, &health_status) {
+                        (HealthStatus::Healthy, HealthStatus::Unhealthy(reason)) => notify(
+                            &notifications,
+                            Event::HealthChanged { service: &name, healthy: false, detail: Some(reason.clone()) },
+                        ),
+                        (HealthStatus::Unhealthy(_), HealthStatus::Healthy) => notify(
+                            &notifications,
+                            Event::HealthChanged { service: &name, healthy: true, detail: None },
+                        ),
+                        _ => {}
+                    }
+                    // Scripts also want the first result, not only flips
+                    let was_healthy = match &service.health_status {
+                        HealthStatus::Healthy => Some(true),
+                        HealthStatus::Unhealthy(_) => Some(false),
+                        _ => None,
+                    };
+                    match &health_status {
+                        HealthStatus::Healthy if was_healthy != Some(true) => {
+                            events::emit(events::Event::HealthChanged { service: &name, healthy: true, detail: None })
+                        }
+                        HealthStatus::Unhealthy(reason) if was_healthy != Some(false) => {
+                            events::emit(events::Event::HealthChanged { service: &name, healthy: false, detail: Some(reason) })
+                        }
+                        _ => {}
+                    }
+                    
                     service.health_status = health_status;
                     service.last_health_check = Some(Instant::now());
                     
-                    // Handle restart policy
-                    if let HealthStatus::Unhealthy(_) = &service.health_status {
-                        if matches!(service.config.restart_policy, RestartPolicy::OnFailure | RestartPolicy::Always) {
-                            service.state = ProcessState::Restarting;
-                            // Restart will be handled by another thread
+                    // A service still starting up gets its startup timeout
+                    // before failing checks count against it
+                    let settled = service.started_at.is_some_and(|at| at.elapsed() >= startup_timeout);
+                    match &service.health_status {
+                        HealthStatus::Unhealthy(reason)
+                            if settled && service.state == ProcessState::Running =>
+                        {
+                            let exit = Exit::Failed(format!("turned unhealthy: {}", reason));
+                            if service.config.restart_policy.restarts_after(&exit) {
+                                service.state = ProcessState::Restarting;
+                                service.process.take().map(|process| (process, exit))
+                            } else {
+                                None
+                            }
                         }
+                        _ => None,
+                    }
+                };
+                
+                // Stopped outside the lock so `dev status` isn't held up
+                if let Some((mut process, exit)) = unhealthy {
+                    println!("{} {} {}", "✗".red(), name.bold(), exit);
+                    let _ = process.stop(false);
+                    match supervise(&services, &manager_config, &name, exit) {
+                        true => continue,
+                        false => break,
                     }
                 }
             }
         });
     }
 
-    fn check_health(url: &str) -> Result<()> {
-        let response = ureq::get(url)
-            .timeout(Duration::from_secs(5))
-            .call();
-        
-        match response {
-            Ok(resp) if resp.status() >= 200 && resp.status() < 300 => Ok(()),
-            Ok(resp) => Err(anyhow::anyhow!("Health check failed with status: {}", resp.status())),
-            Err(e) => Err(anyhow::anyhow!("Health check failed: {}", e)),
-        }
-    }
-
     fn start_log_streaming(&self, _name: &str) {
         // TODO: Implement log streaming
         // This will be implemented in the next step
@@ -327,4 +526,146 @@ impl Drop for ProcessManager {
     fn drop(&mut self) {
         let _ = self.stop_all();
     }
-}
\ No newline at end of file
+}
+
+/// Restarts a service that ended, under its restart policy and with a
+/// backoff that doubles with each restart in a row. Returns whether it is
+/// running again.
+fn supervise(services: &Mutex<HashMap<String, ServiceProcess>>, config: &Config, name: &str, mut exit: Exit) -> bool {
+    loop {
+        let (process_config, attempt, delay) = {
+            let mut services = services.lock().unwrap();
+            let Some(service) = services.get_mut(name) else {
+                return false;
+            };
+            if service.started_at.is_some_and(|at| at.elapsed() >= STABLE_UPTIME) {
+                service.attempts = 0;
+            }
+            if !service.config.restart_policy.restarts_after(&exit) {
+                service.state = match exit {
+                    Exit::Succeeded => ProcessState::Stopped,
+                    _ => ProcessState::Failed(exit.to_string()),
+                };
+                return false;
+            }
+            if service.attempts >= service.config.max_restarts {
+                let reason = format!("gave up after {} restarts: {}", service.attempts, exit);
+                println!("{} {} {}", "✗".red(), name.bold(), reason);
+                service.state = ProcessState::Failed(reason);
+                return false;
+            }
+            service.attempts += 1;
+            service.state = ProcessState::Restarting;
+            (service.config.clone(), service.attempts, backoff(service.attempts))
+        };
+        println!(
+            "{} Restarting {} in {}s ({}/{})",
+            "↻".yellow(),
+            name.bold(),
+            delay.as_secs(),
+            attempt,
+            process_config.max_restarts
+        );
+        thread::sleep(delay);
+
+        let mut services = services.lock().unwrap();
+        let Some(service) = services.get_mut(name) else {
+            return false;
+        };
+        // Stopped while waiting
+        if service.state != ProcessState::Restarting {
+            return false;
+        }
+        match launch(config, &process_config) {
+            Ok(running) => {
+                let pid = running.id();
+                let container = match &running {
+                    Running::Container(container) => Some(container.name().to_string()),
+                    Running::Process(_) => None,
+                };
+                service.process = Some(running);
+                service.state = ProcessState::Running;
+                service.started_at = Some(Instant::now());
+                service.restart_count += 1;
+                service.health_status = HealthStatus::Unknown;
+                println!("{} {} restarted", "✓".green(), name.bold());
+
+                if let Some(pid) = pid {
+                    let restart = Restart { at: chrono::Utc::now(), reason: exit.to_string() };
+                    if let Err(e) = StartedServices::record_restart(&config.workspace_root, name, pid, container.clone(), restart) {
+                        println!("{} Failed to record the restart of {}: {}", "⚠".yellow(), name, e);
+                    }
+                    let ports = config.find_repository(name).map(|(_, repo)| repo.ports.clone()).unwrap_or_default();
+                    events::emit(events::Event::ServiceStarted { service: name, pid, container: container.as_deref(), ports: &ports });
+                }
+                return true;
+            }
+            Err(e) => {
+                println!("{} Failed to restart {}: {}", "✗".red(), name.bold(), e);
+                service.started_at = None;
+                exit = Exit::Failed(e.to_string());
+            }
+        }
+    }
+}
+
+/// Starts a service the way its config says
+fn launch(config: &Config, process_config: &ProcessConfig) -> Result<Running> {
+    match &process_config.backend {
+        Backend::Process => spawn_process(config, process_config).map(Running::Process),
+        Backend::Container { image } => start_container(config, process_config, image).map(Running::Container),
+    }
+}
+
+/// Environment with references to other services resolved against the
+/// ports in use now
+fn resolved_env(manager_config: &Config, config: &ProcessConfig) -> Result<HashMap<String, String>> {
+    let mut env = config.env.clone();
+    interpolation::resolve_env(&mut env, &manager_config.manifest)
+        .with_context(|| format!("Failed to resolve the environment of {}", config.name))?;
+    Ok(env)
+}
+
+fn start_container(manager_config: &Config, config: &ProcessConfig, image: &str) -> Result<Container> {
+    let env = resolved_env(manager_config, config)?;
+    let command: Vec<String> = if config.command.is_empty() {
+        Vec::new()
+    } else {
+        std::iter::once(config.command.clone()).chain(config.args.iter().cloned()).collect()
+    };
+    Container::start(&config.name, image, &command, &env, config.log_file.clone())
+}
+
+fn spawn_process(manager_config: &Config, config: &ProcessConfig) -> Result<Child> {
+    let env = resolved_env(manager_config, config)?;
+    let mut cmd = Command::new(&config.command);
+    
+    cmd.args(&config.args)
+        .current_dir(&config.working_dir)
+        .envs(&env)
+        .stdin(Stdio::null());
+
+    // Append output to the log file so it can be tailed; otherwise pipe it
+    if let Some(log_file) = &config.log_file {
+        if let Some(parent) = log_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_file)?;
+        cmd.stdout(file.try_clone()?).stderr(file);
+    } else {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Create new process group
+        cmd.process_group(0);
+    }
+    
+    ui::log_command(&cmd);
+    Ok(cmd.spawn()?)
+}
@@ -0,0 +1,150 @@
+//! Where each service of the workspace is listening and how it is doing.
+//!
+//! `dev up`, `dev restart`, `dev down` and `syla discover` rewrite
+//! `.platform/state/registry.json`; services find its path in
+//! `SYLA_REGISTRY` and can read it instead of hardcoding each other's ports.
+//! It is JSON so services in any language can read it.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use crate::commands::dev::host_port;
+use crate::commands::status::HealthChecks;
+use crate::config::Config;
+use crate::services::state::StartedServices;
+
+/// How long to wait for a port without a health check to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+pub const HOST: &str = "localhost";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Health {
+    /// Its health check passes
+    Healthy,
+    /// Running, but its health check fails
+    Unhealthy,
+    /// Accepting connections; there is no health check to ask
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Endpoint {
+    pub host: String,
+    /// The first host port, which the service's clients talk to
+    pub port: u16,
+    /// `http://host:port`, or the connection URL of infrastructure
+    pub url: String,
+    /// Every host port, for services listening on more than one
+    pub ports: Vec<u16>,
+    pub health: Health,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub infrastructure: bool,
+}
+
+impl Endpoint {
+    /// `host:port`, the way gRPC clients want it
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Registry {
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub services: BTreeMap<String, Endpoint>,
+}
+
+impl Registry {
+    pub fn path(workspace_root: &Path) -> PathBuf {
+        workspace_root.join(".platform/state/registry.json")
+    }
+
+    fn save(&self, workspace_root: &Path) -> Result<()> {
+        let path = Self::path(workspace_root);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Checks every service and piece of infrastructure with a port and
+    /// writes what it found to the registry file
+    pub async fn refresh(config: &Config) -> Result<Self> {
+        let started = StartedServices::load(&config.workspace_root)?;
+        let mut services = BTreeMap::new();
+        let mut checks = Vec::new();
+        for (name, repo) in &config.manifest.repositories {
+            if !config.is_enabled(name, repo) {
+                continue;
+            }
+            let Some(endpoint) = endpoint(&repo.ports, None, false) else { continue };
+            if let Some(health_check) = &repo.health_check {
+                checks.push((name.clone(), health_check.clone()));
+            }
+            services.insert(name.clone(), endpoint);
+        }
+        for (name, infra) in &config.manifest.infrastructure {
+            let url = crate::commands::dev::infra_env(name, infra).into_iter().next().map(|(_, url)| url);
+            let Some(endpoint) = endpoint(&infra.ports, url, true) else { continue };
+            services.insert(name.clone(), endpoint);
+        }
+
+        let health = HealthChecks::start(checks).results().await;
+        let mut listening = JoinSet::new();
+        for (name, endpoint) in &services {
+            let (name, port) = (name.clone(), endpoint.port);
+            listening.spawn(async move {
+                let connected = tokio::time::timeout(CONNECT_TIMEOUT, tokio::net::TcpStream::connect((HOST, port))).await;
+                (name, matches!(connected, Ok(Ok(_))))
+            });
+        }
+        while let Some(result) = listening.join_next().await {
+            let Ok((name, accepting)) = result else { continue };
+            let running = accepting || started.services.get(&name).is_some_and(|service| service.is_running());
+            let endpoint = services.get_mut(&name).expect("checked services are registered");
+            endpoint.health = match health.get(&name) {
+                Some(Some(true)) => Health::Healthy,
+                _ if !running => Health::Down,
+                Some(_) => Health::Unhealthy,
+                None => Health::Up,
+            };
+        }
+
+        let registry = Registry { updated_at: Some(Utc::now()), services };
+        registry.save(&config.workspace_root)?;
+        Ok(registry)
+    }
+}
+
+/// Registers the first host port of `ports`; `None` while an `auto` port
+/// has yet to be assigned
+fn endpoint(ports: &[String], url: Option<String>, infrastructure: bool) -> Option<Endpoint> {
+    let ports: Vec<u16> = ports.iter().filter_map(|p| host_port(p)?.parse().ok()).collect();
+    let port = *ports.first()?;
+    Some(Endpoint {
+        host: HOST.to_string(),
+        port,
+        url: url.unwrap_or_else(|| format!("http://{}:{}", HOST, port)),
+        ports,
+        health: Health::Down,
+        infrastructure,
+    })
+}
+
+/// Refreshes the registry after services were started or stopped; a
+/// failure only means it is stale until the next refresh
+pub async fn update(config: &Config) {
+    if let Err(e) = Registry::refresh(config).await {
+        tracing::debug!("Failed to update the service registry: {:#}", e);
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::secrets;
+use crate::services::container;
+use crate::tunnels::is_alive;
+
+/// How `syla dev up` started a service. `env` is recorded before secrets
+/// are resolved, so only references end up on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartedService {
+    pub pid: u32,
+    /// Container the service runs in, for `run_in = "container"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub ports: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Restarts the supervisor made under the service's restart policy,
+    /// latest last
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub restarts: Vec<Restart>,
+}
+
+/// A restart the supervisor made after the service ended
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Restart {
+    pub at: chrono::DateTime<chrono::Utc>,
+    /// Why, e.g. `exited with code 1`
+    pub reason: String,
+}
+
+/// Restarts kept per service
+const RESTART_HISTORY: usize = 10;
+
+impl StartedService {
+    pub fn is_running(&self) -> bool {
+        match &self.container {
+            Some(name) => container::is_running(name),
+            None => is_alive(self.pid),
+        }
+    }
+}
+
+/// Services started by `syla dev up`, kept in `.platform/state/services.toml`
+/// so `dev diff` and `dev down` can find them after the command exits
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StartedServices {
+    #[serde(flatten)]
+    pub services: BTreeMap<String, StartedService>,
+}
+
+impl StartedServices {
+    fn path(workspace_root: &Path) -> PathBuf {
+        workspace_root.join(".platform/state/services.toml")
+    }
+
+    pub fn load(workspace_root: &Path) -> Result<Self> {
+        let path = Self::path(workspace_root);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, workspace_root: &Path) -> Result<()> {
+        let path = Self::path(workspace_root);
+        if self.services.is_empty() {
+            return match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+                }
+                _ => Ok(()),
+            };
+        }
+        let content = format!("# Services started by `syla dev up`\n{}", toml::to_string_pretty(self)?);
+        secrets::write_private(&path, content.as_bytes())
+    }
+
+    /// Records one service, keeping the others
+    pub fn record(workspace_root: &Path, name: &str, service: StartedService) -> Result<()> {
+        let mut started = Self::load(workspace_root)?;
+        started.services.insert(name.to_string(), service);
+        started.save(workspace_root)
+    }
+
+    /// Notes that the supervisor restarted a recorded service, now running
+    /// as `pid` (in `container`)
+    pub fn record_restart(
+        workspace_root: &Path,
+        name: &str,
+        pid: u32,
+        container: Option<String>,
+        restart: Restart,
+    ) -> Result<()> {
+        let mut started = Self::load(workspace_root)?;
+        let Some(service) = started.services.get_mut(name) else {
+            return Ok(());
+        };
+        service.pid = pid;
+        service.container = container;
+        service.restarts.push(restart);
+        let excess = service.restarts.len().saturating_sub(RESTART_HISTORY);
+        service.restarts.drain(..excess);
+        started.save(workspace_root)
+    }
+}
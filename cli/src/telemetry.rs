@@ -0,0 +1,239 @@
+//! Opt-in anonymous usage metrics.
+//!
+//! Nothing is recorded until the user runs `syla telemetry enable`. Events
+//! hold only the command path (e.g. `dev up`, never arguments), how long it
+//! took and a coarse failure category. They are appended to a local file and
+//! uploaded in batches at most once a day when an endpoint is configured.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config;
+
+/// Minimum time between uploads
+const UPLOAD_INTERVAL: chrono::Duration = chrono::Duration::hours(24);
+
+/// Upload requests are abandoned after this long so commands never hang on them
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Pending events beyond this are dropped, oldest first
+const MAX_PENDING_EVENTS: usize = 10_000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Settings {
+    pub enabled: bool,
+    /// Random identifier so uploads from one machine can be grouped
+    pub install_id: Option<Uuid>,
+    pub endpoint: Option<String>,
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub last_upload: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub command: String,
+    pub duration_ms: u64,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_category: Option<String>,
+    pub cli_version: String,
+    pub os: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct UploadBatch<'a> {
+    install_id: Option<Uuid>,
+    events: &'a [Event],
+}
+
+fn settings_path() -> PathBuf {
+    config::user_dir().join("telemetry.json")
+}
+
+fn events_path() -> PathBuf {
+    config::user_dir().join("telemetry").join("events.jsonl")
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        std::fs::read_to_string(settings_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = settings_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Endpoint uploads go to; `SYLA_TELEMETRY_ENDPOINT` wins over settings
+    pub fn upload_endpoint(&self) -> Option<String> {
+        std::env::var("SYLA_TELEMETRY_ENDPOINT")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .or_else(|| self.endpoint.clone())
+    }
+}
+
+/// Whether events are recorded. `DO_NOT_TRACK=1` or `SYLA_TELEMETRY=0`
+/// override an earlier opt-in.
+pub fn is_enabled(settings: &Settings) -> bool {
+    let opted_out = std::env::var("DO_NOT_TRACK").is_ok_and(|v| v == "1" || v == "true")
+        || std::env::var("SYLA_TELEMETRY").is_ok_and(|v| v == "0" || v == "false");
+    settings.enabled && !opted_out
+}
+
+pub fn enable(endpoint: Option<String>) -> Result<Settings> {
+    let mut settings = Settings::load();
+    settings.enabled = true;
+    settings.install_id.get_or_insert_with(Uuid::new_v4);
+    settings.enabled_at = Some(Utc::now());
+    if endpoint.is_some() {
+        settings.endpoint = endpoint;
+    }
+    settings.save()?;
+    Ok(settings)
+}
+
+/// Turn recording off and discard anything not yet uploaded
+pub fn disable() -> Result<()> {
+    let mut settings = Settings::load();
+    settings.enabled = false;
+    settings.install_id = None;
+    settings.save()?;
+    match std::fs::remove_file(events_path()) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+pub fn pending_events() -> Vec<Event> {
+    std::fs::read_to_string(events_path())
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Record a finished command when telemetry is enabled, uploading pending
+/// events if it is time to. Never fails the command it measures.
+pub async fn record(command: &str, duration: Duration, result: &Result<()>) {
+    let settings = Settings::load();
+    if !is_enabled(&settings) {
+        return;
+    }
+
+    let event = Event {
+        command: command.to_string(),
+        duration_ms: duration.as_millis() as u64,
+        success: result.is_ok(),
+        failure_category: result.as_ref().err().map(|e| failure_category(e).to_string()),
+        cli_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        timestamp: Utc::now(),
+    };
+    if let Err(e) = append_event(&event) {
+        tracing::debug!("Failed to record telemetry: {:#}", e);
+        return;
+    }
+
+    let due = settings
+        .last_upload
+        .is_none_or(|last| Utc::now() - last >= UPLOAD_INTERVAL);
+    if due {
+        if let Err(e) = upload(settings).await {
+            tracing::debug!("Failed to upload telemetry: {:#}", e);
+        }
+    }
+}
+
+fn append_event(event: &Event) -> Result<()> {
+    let path = events_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut events = pending_events();
+    if events.len() >= MAX_PENDING_EVENTS {
+        events.drain(..=events.len() - MAX_PENDING_EVENTS);
+        events.push(event.clone());
+        return write_events(&events);
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+fn write_events(events: &[Event]) -> Result<()> {
+    let mut content = String::new();
+    for event in events {
+        content.push_str(&serde_json::to_string(event)?);
+        content.push('\n');
+    }
+    std::fs::write(events_path(), content)?;
+    Ok(())
+}
+
+/// Send pending events and clear them once the endpoint accepts the batch
+pub async fn upload(mut settings: Settings) -> Result<usize> {
+    let Some(endpoint) = settings.upload_endpoint() else {
+        return Ok(0);
+    };
+    let events = pending_events();
+    if events.is_empty() {
+        return Ok(0);
+    }
+
+    let client = reqwest::Client::builder().timeout(UPLOAD_TIMEOUT).build()?;
+    client
+        .post(&endpoint)
+        .json(&UploadBatch {
+            install_id: settings.install_id,
+            events: &events,
+        })
+        .send()
+        .await?
+        .error_for_status()?;
+
+    write_events(&[])?;
+    settings.last_upload = Some(Utc::now());
+    settings.save()?;
+    Ok(events.len())
+}
+
+/// Coarse, message-free classification of why a command failed
+fn failure_category(error: &anyhow::Error) -> &'static str {
+    if error.chain().any(|e| e.is::<reqwest::Error>()) {
+        return "network";
+    }
+
+    let message = format!("{:#}", error).to_lowercase();
+    if message.contains("manifest") || message.contains("workspace root") {
+        "workspace"
+    } else if message.contains("docker") || message.contains("compose") {
+        "docker"
+    } else if message.contains("git") {
+        "git"
+    } else if message.contains("cargo") || message.contains("build") {
+        "build"
+    } else if error.chain().any(|e| e.is::<std::io::Error>()) {
+        "io"
+    } else {
+        "other"
+    }
+}
@@ -0,0 +1,297 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::commands::dev::host_port;
+use crate::config::Config;
+
+/// How long a driver gets to report its public URL
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Program that exposes a local port at a public URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelDriver {
+    /// Cloudflare quick tunnel (`cloudflared tunnel --url`)
+    Cloudflared,
+    /// ngrok agent (`ngrok http`)
+    Ngrok,
+    /// localhost.run over SSH, nothing to install
+    Ssh,
+}
+
+impl TunnelDriver {
+    fn program(self) -> &'static str {
+        match self {
+            TunnelDriver::Cloudflared => "cloudflared",
+            TunnelDriver::Ngrok => "ngrok",
+            TunnelDriver::Ssh => "ssh",
+        }
+    }
+
+    fn command(self, port: u16) -> Command {
+        let mut cmd = Command::new(self.program());
+        match self {
+            TunnelDriver::Cloudflared => {
+                cmd.args(["tunnel", "--no-autoupdate", "--url"]).arg(format!("http://localhost:{}", port));
+            }
+            TunnelDriver::Ngrok => {
+                cmd.args(["http", &port.to_string(), "--log", "stdout", "--log-format", "logfmt"]);
+            }
+            TunnelDriver::Ssh => {
+                cmd.args(["-o", "StrictHostKeyChecking=accept-new", "-o", "ServerAliveInterval=30", "-R"])
+                    .arg(format!("80:localhost:{}", port))
+                    .arg("nokey@localhost.run");
+            }
+        }
+        cmd
+    }
+
+    /// Hosts the driver hands out public URLs on
+    fn domains(self) -> &'static [&'static str] {
+        match self {
+            TunnelDriver::Cloudflared => &[".trycloudflare.com"],
+            TunnelDriver::Ngrok => &[".ngrok-free.app", ".ngrok-free.dev", ".ngrok.app", ".ngrok.dev", ".ngrok.io"],
+            TunnelDriver::Ssh => &[".lhr.life", ".localhost.run"],
+        }
+    }
+
+    /// The public URL in the driver's output, skipping banners and doc links
+    fn find_url(self, output: &str) -> Option<String> {
+        output
+            .split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '|' | '<' | '>' | '=' | ','))
+            .filter(|token| token.starts_with("https://"))
+            .map(|token| token.trim_end_matches(['.', ')', ';', '/']))
+            .find(|url| {
+                let host = url["https://".len()..].split(['/', ':']).next().unwrap_or_default();
+                self.domains().iter().any(|domain| host.ends_with(domain))
+            })
+            .map(String::from)
+    }
+
+    /// First driver found on PATH
+    fn detect() -> Result<Self> {
+        [TunnelDriver::Cloudflared, TunnelDriver::Ngrok, TunnelDriver::Ssh]
+            .into_iter()
+            .find(|driver| which::which(driver.program()).is_ok())
+            .context("No tunnel driver found; install cloudflared or ngrok, or make ssh available")
+    }
+}
+
+impl std::fmt::Display for TunnelDriver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.program())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tunnel {
+    pub driver: TunnelDriver,
+    pub pid: u32,
+    pub port: u16,
+    pub url: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Tunnels opened by `syla dev expose`, kept in `.platform/state/tunnels.toml`
+/// so `status` and `dev down` can find them after the command exits
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Tunnels {
+    #[serde(flatten)]
+    services: BTreeMap<String, Tunnel>,
+}
+
+impl Tunnels {
+    fn path(workspace_root: &Path) -> PathBuf {
+        workspace_root.join(".platform/state/tunnels.toml")
+    }
+
+    fn load(workspace_root: &Path) -> Result<Self> {
+        let path = Self::path(workspace_root);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    fn save(&self, workspace_root: &Path) -> Result<()> {
+        let path = Self::path(workspace_root);
+        if self.services.is_empty() {
+            return match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+                }
+                _ => Ok(()),
+            };
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = format!("# Tunnels opened by `syla dev expose`\n{}", toml::to_string_pretty(self)?);
+        std::fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Tunnels whose driver process is still running
+pub fn active(workspace_root: &Path) -> Vec<(String, Tunnel)> {
+    Tunnels::load(workspace_root)
+        .map(|tunnels| tunnels.services.into_iter().filter(|(_, t)| is_alive(t.pid)).collect())
+        .unwrap_or_default()
+}
+
+/// Opens a tunnel to a service's first port, or to `port`, and prints its URL
+pub fn expose(config: &Config, service: &str, driver: Option<TunnelDriver>, port: Option<u16>) -> Result<()> {
+    let (name, repo) = config.find_repository(service)?;
+    let port = match port {
+        Some(port) => port,
+        None => repo
+            .ports
+            .first()
+            .and_then(|p| host_port(p))
+            .and_then(|p| p.parse().ok())
+            .with_context(|| format!("{} has no port to expose; pass --port", name))?,
+    };
+
+    let mut tunnels = Tunnels::load(&config.workspace_root)?;
+    if let Some(tunnel) = tunnels.services.get(&name) {
+        if is_alive(tunnel.pid) {
+            println!("{} {} is already exposed at {}", "[OK]".green(), name, tunnel.url.bold());
+            return Ok(());
+        }
+    }
+
+    let driver = match driver {
+        Some(driver) => driver,
+        None => TunnelDriver::detect()?,
+    };
+    if std::net::TcpStream::connect(("127.0.0.1", port)).is_err() {
+        println!("{} Nothing is listening on port {} yet", "[!]".yellow(), port);
+    }
+
+    println!("{} Opening a {} tunnel to {} (port {})...", "[>]".cyan(), driver, name, port);
+    let log_path = config.workspace_root.join(".logs").join(format!("tunnel-{}.log", name));
+    if let Some(dir) = log_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let log = File::create(&log_path).with_context(|| format!("Failed to create {}", log_path.display()))?;
+    let mut cmd = driver.command(port);
+    cmd.stdin(Stdio::null()).stdout(log.try_clone()?).stderr(log);
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Keep the tunnel up after this command returns
+        cmd.process_group(0);
+    }
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to start {}", driver.program()))?;
+
+    let started = Instant::now();
+    let url = loop {
+        let output = std::fs::read_to_string(&log_path).unwrap_or_default();
+        if let Some(url) = driver.find_url(&output) {
+            break url;
+        }
+        if let Some(status) = child.try_wait()? {
+            anyhow::bail!("{} exited ({}) before reporting a URL:\n{}", driver, status, tail(&output));
+        }
+        if started.elapsed() > STARTUP_TIMEOUT {
+            let _ = child.kill();
+            anyhow::bail!("{} reported no URL within {}s:\n{}", driver, STARTUP_TIMEOUT.as_secs(), tail(&output));
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    };
+
+    tunnels.services.insert(
+        name.clone(),
+        Tunnel {
+            driver,
+            pid: child.id(),
+            port,
+            url: url.clone(),
+            started_at: chrono::Utc::now(),
+        },
+    );
+    tunnels.save(&config.workspace_root)?;
+
+    println!("{} {} is exposed at {}", "[OK]".green(), name, url.bold());
+    println!("  Driver log: {}", log_path.display());
+    println!("  Close it with `syla dev expose {} --stop` or `syla dev down`", name);
+    Ok(())
+}
+
+/// Closes the tunnels of the given services, or all of them, returning how
+/// many were running
+pub fn close(config: &Config, services: &[String]) -> Result<usize> {
+    let mut tunnels = Tunnels::load(&config.workspace_root)?;
+    let names: Vec<String> = tunnels
+        .services
+        .keys()
+        .filter(|name| services.is_empty() || services.iter().any(|s| name.contains(s.as_str())))
+        .cloned()
+        .collect();
+    let mut closed = 0;
+    for name in names {
+        let Some(tunnel) = tunnels.services.remove(&name) else { continue };
+        if is_alive(tunnel.pid) {
+            terminate(tunnel.pid);
+            say!("{} Closed tunnel {} ({})", "[OK]".green(), name, tunnel.url);
+            closed += 1;
+        }
+    }
+    tunnels.save(&config.workspace_root)?;
+    Ok(closed)
+}
+
+/// Prints the running tunnels under a heading, if there are any
+pub fn print_active(workspace_root: &Path) {
+    let tunnels = active(workspace_root);
+    if tunnels.is_empty() {
+        return;
+    }
+    println!("\n{}", "Tunnels:".bold());
+    for (name, tunnel) in tunnels {
+        println!("  {} {} -> {} ({}, port {})", "[OK]".green(), name, tunnel.url, tunnel.driver, tunnel.port);
+    }
+}
+
+fn tail(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    lines[lines.len().saturating_sub(10)..].join("\n")
+}
+
+#[cfg(unix)]
+pub(crate) fn is_alive(pid: u32) -> bool {
+    use nix::sys::signal;
+    use nix::unistd::Pid;
+
+    i32::try_from(pid).is_ok_and(|pid| signal::kill(Pid::from_raw(pid), None).is_ok())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_alive(pid: u32) -> bool {
+    sysinfo::System::new_all().process(sysinfo::Pid::from_u32(pid)).is_some()
+}
+
+#[cfg(unix)]
+pub(crate) fn terminate(pid: u32) {
+    use nix::sys::signal::{self, Signal};
+    use nix::unistd::Pid;
+
+    if let Ok(pid) = i32::try_from(pid) {
+        let _ = signal::kill(Pid::from_raw(pid), Signal::SIGTERM);
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn terminate(pid: u32) {
+    if let Some(process) = sysinfo::System::new_all().process(sysinfo::Pid::from_u32(pid)) {
+        process.kill();
+    }
+}
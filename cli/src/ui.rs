@@ -0,0 +1,177 @@
+use colored::Colorize;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+
+/// Progress chatter and `[OK]` lines; nothing with `--quiet`
+#[macro_export]
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::ui::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// How much commands print, from `--quiet` and `--verbose`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Errors only
+    Quiet,
+    Normal,
+    /// Also the command lines being run and how long each step took
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+pub fn is_quiet() -> bool {
+    verbosity() == Verbosity::Quiet
+}
+
+/// A progress bar that stays hidden with `--quiet`
+pub fn progress_bar(len: u64) -> ProgressBar {
+    if is_quiet() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(len)
+    }
+}
+
+/// Logs the command line about to run; shown with `--verbose`. Call
+/// `finished` on the result to log how long it ran and how it exited.
+pub fn log_command(cmd: &std::process::Command) -> LoggedCommand {
+    let mut line = cmd.get_program().to_string_lossy().into_owned();
+    for arg in cmd.get_args() {
+        let arg = arg.to_string_lossy();
+        if arg.is_empty() || arg.contains(char::is_whitespace) {
+            line.push_str(&format!(" '{}'", arg.replace('\'', r"'\''")));
+        } else {
+            line.push(' ');
+            line.push_str(&arg);
+        }
+    }
+    match cmd.get_current_dir() {
+        Some(dir) => tracing::debug!("$ {} (in {})", line, dir.display()),
+        None => tracing::debug!("$ {}", line),
+    }
+    LoggedCommand { program: cmd.get_program().to_string_lossy().into_owned(), started: Instant::now() }
+}
+
+/// A command `log_command` reported starting
+pub struct LoggedCommand {
+    program: String,
+    started: Instant,
+}
+
+impl LoggedCommand {
+    pub fn finished(self, status: &std::process::ExitStatus) {
+        tracing::debug!(
+            duration_ms = self.started.elapsed().as_millis() as u64,
+            exit_code = status.code(),
+            "{} exited with {}",
+            self.program,
+            status
+        );
+    }
+}
+
+/// Numbered steps with a spinner per item. On a terminal the spinners
+/// update in place; elsewhere each item prints a line as it starts and
+/// finishes, so logs stay readable.
+pub struct Steps {
+    multi: MultiProgress,
+    total: usize,
+    live: bool,
+}
+
+impl Steps {
+    pub fn new(total: usize) -> Self {
+        let live = !is_quiet() && std::io::stderr().is_terminal();
+        Self { multi: MultiProgress::new(), total, live }
+    }
+
+    pub fn start(&self, step: usize, title: &str) {
+        let header = format!("{} {}", format!("[{}/{}]", step, self.total).dimmed(), title.bold());
+        if self.live {
+            let _ = self.multi.println(format!("\n{}", header));
+        } else {
+            say!("\n{}", header);
+        }
+    }
+
+    /// A spinner for `name`, showing `action` (e.g. "Building") until it
+    /// finishes
+    pub fn item(&self, name: &str, action: &str) -> StepItem {
+        let bar = if self.live {
+            let bar = self.multi.add(ProgressBar::new_spinner());
+            bar.set_style(ProgressStyle::with_template("  {spinner} {prefix:.bold} {msg} {elapsed:.dim}").unwrap());
+            bar.set_prefix(name.to_string());
+            bar.set_message(action.to_lowercase());
+            bar.enable_steady_tick(Duration::from_millis(100));
+            bar
+        } else {
+            say!("{} {}...", action, name);
+            ProgressBar::hidden()
+        };
+        StepItem { bar, name: name.to_string(), started: Instant::now(), live: self.live }
+    }
+
+    /// Runs `f` with the spinners cleared, for commands that print their own
+    /// output
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.multi.suspend(f)
+    }
+}
+
+/// One item of a [`Steps`] display
+pub struct StepItem {
+    bar: ProgressBar,
+    name: String,
+    started: Instant,
+    live: bool,
+}
+
+impl StepItem {
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn ok(self, message: &str) {
+        self.finish("[OK]".green().to_string(), message, false);
+    }
+
+    /// Skipped or degraded, but not an error
+    pub fn warn(self, message: &str) {
+        self.finish("[!]".yellow().to_string(), message, false);
+    }
+
+    /// Shown even with `--quiet`
+    pub fn fail(self, message: &str) {
+        self.finish("[X]".red().to_string(), message, true);
+    }
+
+    fn finish(self, mark: String, message: &str, always: bool) {
+        let elapsed = format!("({:.1?})", self.started.elapsed()).dimmed();
+        if self.live {
+            self.bar.set_style(ProgressStyle::with_template("  {prefix} {msg}").unwrap());
+            self.bar.set_prefix(format!("{} {}", mark, self.name.bold()));
+            self.bar.finish_with_message(format!("{} {}", message, elapsed));
+        } else if always || !is_quiet() {
+            println!("{} {} {} {}", mark, self.name, message, elapsed);
+        }
+    }
+}
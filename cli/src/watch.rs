@@ -0,0 +1,362 @@
+//! File watching for `dev watch`.
+//!
+//! One native watcher (inotify, FSEvents or ReadDirectoryChangesW) covers
+//! every watched service: the paths of its `watch` table, or its `src/`
+//! directory without one. Each event goes to the service whose repository
+//! holds the file, unless it's ignored. A change is reported once nothing
+//! else has changed for the debounce window, so a build writing many files
+//! or an editor saving several buffers triggers one rebuild.
+//!
+//! With `--test`, the service's tests run after each change, narrowed to
+//! the cargo packages or Go packages whose files changed.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::changes;
+use crate::config::{Config, RepositoryConfig, WatchAction, WatchConfig};
+use crate::runtime::Runtime;
+use crate::ui;
+
+/// Never worth reacting to, whatever the manifest says
+const DEFAULT_IGNORE: &[&str] = &["target/", "node_modules/", ".git/"];
+
+pub struct WatchedService {
+    pub name: String,
+    pub on_change: WatchAction,
+    dir: PathBuf,
+    paths: Vec<PathBuf>,
+    /// Patterns for directories, from entries ending in `/`
+    ignored_dirs: Vec<glob::Pattern>,
+    ignored_files: Vec<glob::Pattern>,
+    debounce: Duration,
+    /// When the latest unhandled change was noticed, and the file
+    pending: Option<(Instant, String)>,
+}
+
+impl WatchedService {
+    pub fn new(name: &str, dir: PathBuf, config: &WatchConfig) -> Result<Self> {
+        let mut ignored_dirs = Vec::new();
+        let mut ignored_files = Vec::new();
+        for pattern in DEFAULT_IGNORE.iter().copied().chain(config.ignore.iter().map(String::as_str)) {
+            let (list, glob) = match pattern.strip_suffix('/') {
+                Some(dir) => (&mut ignored_dirs, dir),
+                None => (&mut ignored_files, pattern),
+            };
+            list.push(
+                glob::Pattern::new(glob)
+                    .with_context(|| format!("Invalid watch ignore pattern '{}' for {}", pattern, name))?,
+            );
+        }
+        // Events name files the way the OS resolves them, e.g. through
+        // macOS's /var -> /private/var
+        let dir = dir.canonicalize().unwrap_or(dir);
+        let paths = if config.paths.is_empty() {
+            vec![dir.clone()]
+        } else {
+            config.paths.iter().map(|path| dir.join(path)).collect()
+        };
+        Ok(Self {
+            name: name.to_string(),
+            on_change: config.on_change,
+            dir,
+            paths,
+            ignored_dirs,
+            ignored_files,
+            debounce: Duration::from_millis(config.debounce_ms),
+            pending: None,
+        })
+    }
+
+    /// A service without a `watch` table: its `src/` directory, or all of
+    /// it when it has none, built and then restarted
+    pub fn sources(name: &str, dir: PathBuf) -> Result<Self> {
+        let paths = if dir.join("src").is_dir() { vec!["src".to_string()] } else { Vec::new() };
+        Self::new(name, dir, &WatchConfig { paths, ..WatchConfig::default() })
+    }
+
+    /// Notes a change to `path` unless it's outside the watched paths or
+    /// ignored
+    fn record(&mut self, path: &Path) {
+        if !self.paths.iter().any(|watched| path.starts_with(watched)) {
+            return;
+        }
+        let Ok(relative) = path.strip_prefix(&self.dir) else {
+            return;
+        };
+        if relative.as_os_str().is_empty() || self.is_ignored(path, relative) {
+            return;
+        }
+        self.pending = Some((Instant::now(), relative.display().to_string()));
+    }
+
+    /// The changed file, once changes have settled
+    fn settled(&mut self) -> Option<String> {
+        match &self.pending {
+            Some((noticed, _)) if noticed.elapsed() >= self.debounce => self.pending.take().map(|(_, file)| file),
+            _ => None,
+        }
+    }
+
+    /// Patterns match the path relative to the repository or just its
+    /// name; directory patterns also match any directory the file is in
+    fn is_ignored(&self, path: &Path, relative: &Path) -> bool {
+        let matches = |patterns: &[glob::Pattern], prefix: &Path| {
+            let name = prefix.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+            patterns.iter().any(|pattern| pattern.matches_path(prefix) || pattern.matches(&name))
+        };
+        let mut prefix = PathBuf::new();
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            prefix.push(component);
+            let is_dir = components.peek().is_some() || path.is_dir();
+            if is_dir && matches(&self.ignored_dirs, &prefix) {
+                return true;
+            }
+        }
+        !path.is_dir() && matches(&self.ignored_files, relative)
+    }
+}
+
+/// A settled change to a watched service
+pub struct Change {
+    pub service: String,
+    pub on_change: WatchAction,
+    /// The file changed last, relative to the repository
+    pub file: String,
+}
+
+/// Watches the paths of several services and reports which of them changed
+pub struct FileWatcher {
+    services: Vec<WatchedService>,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    // Watching stops when it's dropped
+    _watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    pub fn new(services: Vec<WatchedService>) -> Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).context("Failed to start the file watcher")?;
+        for service in &services {
+            // Created later, they'd go unnoticed; most are build outputs
+            for path in service.paths.iter().filter(|path| path.exists()) {
+                watcher
+                    .watch(path, RecursiveMode::Recursive)
+                    .with_context(|| format!("Failed to watch {} for {}", path.display(), service.name))?;
+            }
+        }
+        Ok(Self { services, events, _watcher: watcher })
+    }
+
+    /// Quiet time the most impatient service needs, for choosing how often
+    /// to check for settled changes
+    pub fn debounce(&self) -> Option<Duration> {
+        self.services.iter().map(|service| service.debounce).min()
+    }
+
+    /// Services whose changes have settled since the last call
+    pub fn poll(&mut self) -> Vec<Change> {
+        while let Ok(event) = self.events.try_recv() {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    println!("{} File watcher: {}", "[!]".yellow(), e);
+                    continue;
+                }
+            };
+            if event.kind.is_access() {
+                continue;
+            }
+            for path in &event.paths {
+                // The innermost repository, for one checked out in another
+                let owner = self
+                    .services
+                    .iter_mut()
+                    .filter(|service| path.starts_with(&service.dir))
+                    .max_by_key(|service| service.dir.components().count());
+                if let Some(service) = owner {
+                    service.record(path);
+                }
+            }
+        }
+        self.services
+            .iter_mut()
+            .filter_map(|service| {
+                let file = service.settled()?;
+                Some(Change { service: service.name.clone(), on_change: service.on_change, file })
+            })
+            .collect()
+    }
+}
+
+/// Runs a service's tests after changes, scoped to what changed since its
+/// last run where the toolchain allows
+pub struct TestRunner {
+    started: SystemTime,
+    last_run: HashMap<String, SystemTime>,
+}
+
+impl Default for TestRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestRunner {
+    pub fn new() -> Self {
+        Self { started: SystemTime::now(), last_run: HashMap::new() }
+    }
+
+    /// Streams the tests' output, returning whether they passed; `None`
+    /// when the service has no tests to run
+    pub fn run(&mut self, config: &Config, name: &str, repo: &RepositoryConfig) -> Result<Option<bool>> {
+        let dir = config.workspace_root.join(&repo.path);
+        let since = self.last_run.get(name).copied().unwrap_or(self.started);
+        let changed = changes::modified_since(&dir, since);
+        let Some((mut cmd, scope)) = test_command(config, repo, &dir, &changed) else {
+            return Ok(None);
+        };
+        self.last_run.insert(name.to_string(), SystemTime::now());
+
+        match &scope {
+            Some(scope) => say!("Testing {} ({})...", name, scope),
+            None => say!("Testing {}...", name),
+        }
+        cmd.current_dir(&dir)
+            .env("SYLA_WORKSPACE", &config.workspace_root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let logged = ui::log_command(&cmd);
+        let started = Instant::now();
+        let mut child = cmd.spawn().with_context(|| format!("Failed to run the tests of {}", name))?;
+        let stderr = child.stderr.take().map(|stderr| std::thread::spawn(move || stream(stderr)));
+        if let Some(stdout) = child.stdout.take() {
+            stream(stdout);
+        }
+        if let Some(stderr) = stderr {
+            let _ = stderr.join();
+        }
+        let status = child.wait()?;
+        logged.finished(&status);
+        let passed = status.success();
+
+        let elapsed = format!("({:.1}s)", started.elapsed().as_secs_f64());
+        if passed {
+            println!("{} {} tests passed {}", "[OK]".green(), name, elapsed.dimmed());
+        } else {
+            println!("{} {} tests failed {}", "[X]".red(), name, elapsed.dimmed());
+        }
+        Ok(Some(passed))
+    }
+}
+
+/// The test command for a service and, when narrowed to what changed, a
+/// description of the scope
+fn test_command(
+    config: &Config,
+    repo: &RepositoryConfig,
+    dir: &Path,
+    changed: &[PathBuf],
+) -> Option<(Command, Option<String>)> {
+    let runtime = Runtime::of(repo);
+    if runtime == Runtime::Rust && repo.test.is_none() && dir.join("Cargo.toml").exists() {
+        let mut cmd = config.cargo();
+        cmd.arg("test");
+        let packages = changed_packages(config, dir, changed);
+        if !packages.is_empty() {
+            for package in &packages {
+                cmd.args(["-p", package]);
+            }
+            return Some((cmd, Some(packages.join(", "))));
+        }
+        return Some((cmd, None));
+    }
+
+    let test = runtime.test_command(repo, dir)?;
+    let mut scope = None;
+    let test = match go_packages(changed) {
+        Some(packages) if runtime == Runtime::Go && repo.test.is_none() => {
+            scope = Some(packages.join(", "));
+            format!("go test {}", packages.join(" "))
+        }
+        _ => test,
+    };
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", &test]);
+    Some((cmd, scope))
+}
+
+/// Cargo packages holding the changed files; empty when any file is
+/// outside every package, e.g. a workspace-level `Cargo.lock`
+fn changed_packages(config: &Config, dir: &Path, changed: &[PathBuf]) -> Vec<String> {
+    if changed.is_empty() {
+        return Vec::new();
+    }
+    let Some(metadata) = changes::cargo_metadata(config, dir) else {
+        return Vec::new();
+    };
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let packages: Vec<(PathBuf, String)> = metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|package| {
+            let root = Path::new(package["manifest_path"].as_str()?).parent()?.to_path_buf();
+            Some((root, package["name"].as_str()?.to_string()))
+        })
+        .collect();
+
+    let mut names = BTreeSet::new();
+    for file in changed {
+        let file = dir.join(file);
+        // The innermost package, for crates nested in another's directory
+        let owner = packages
+            .iter()
+            .filter(|(root, _)| file.starts_with(root) && !file.ends_with("Cargo.lock"))
+            .max_by_key(|(root, _)| root.components().count());
+        match owner {
+            Some((_, name)) => names.insert(name.clone()),
+            None => return Vec::new(),
+        };
+    }
+    names.into_iter().collect()
+}
+
+/// `./dir` for each directory with a changed `.go` file; `None` when
+/// something else changed, like `go.mod`, that can affect every package
+fn go_packages(changed: &[PathBuf]) -> Option<Vec<String>> {
+    if changed.is_empty() || !changed.iter().all(|file| file.extension().is_some_and(|ext| ext == "go")) {
+        return None;
+    }
+    let packages: BTreeSet<String> = changed
+        .iter()
+        .map(|file| match file.parent().map(Path::to_string_lossy) {
+            Some(parent) if !parent.is_empty() => format!("./{}", parent),
+            _ => ".".to_string(),
+        })
+        .collect();
+    Some(packages.into_iter().collect())
+}
+
+/// Echoes test output as it arrives, failures in red
+fn stream(output: impl std::io::Read) {
+    for line in BufReader::new(output).lines().map_while(|line| line.ok()) {
+        if FAILURE_MARKERS.iter().any(|marker| line.contains(marker)) {
+            println!("{}", line.red());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Lines that report a failing test in the usual runners' output
+const FAILURE_MARKERS: &[&str] = &["FAILED", "--- FAIL", "FAIL ", "panicked at", "AssertionError", "error[E", "✕"];
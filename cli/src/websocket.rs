@@ -0,0 +1,192 @@
+//! Just enough HTTP/1.1 and WebSocket (RFC 6455) for `syla serve`: one
+//! request per connection, optionally upgraded to a WebSocket carrying
+//! text messages.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use sha1::{Digest, Sha1};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Largest request body or message accepted
+const MAX_PAYLOAD: u64 = 16 * 1024 * 1024;
+
+pub(crate) const OPCODE_CONTINUATION: u8 = 0x0;
+pub(crate) const OPCODE_TEXT: u8 = 0x1;
+pub(crate) const OPCODE_CLOSE: u8 = 0x8;
+pub(crate) const OPCODE_PING: u8 = 0x9;
+pub(crate) const OPCODE_PONG: u8 = 0xA;
+
+#[derive(Debug)]
+pub(crate) struct HttpRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    /// Names lowercased
+    headers: Vec<(String, String)>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// The key to answer when this asks for a WebSocket upgrade
+    pub(crate) fn websocket_key(&self) -> Option<&str> {
+        let upgrade = self.header("upgrade")?;
+        if !upgrade.eq_ignore_ascii_case("websocket") {
+            return None;
+        }
+        self.header("sec-websocket-key")
+    }
+}
+
+/// Reads the request line, headers and body; `None` when the client
+/// closed the connection without sending anything
+pub(crate) async fn read_request<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<HttpRequest>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("Malformed request line: {}", line.trim());
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            anyhow::bail!("Connection closed in the middle of the headers");
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header.split_once(':').with_context(|| format!("Malformed header: {}", header))?;
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+
+    let mut request = HttpRequest { method, path, headers, body: Vec::new() };
+    if let Some(length) = request.header("content-length") {
+        let length: u64 = length.parse().context("Malformed Content-Length")?;
+        if length > MAX_PAYLOAD {
+            anyhow::bail!("Request body of {} bytes is too large", length);
+        }
+        let mut body = vec![0; length as usize];
+        reader.read_exact(&mut body).await?;
+        request.body = body;
+    }
+    Ok(Some(request))
+}
+
+pub(crate) async fn write_response<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    status: u16,
+    reason: &str,
+    content_type: &str,
+    body: &[u8],
+) -> Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        content_type,
+        body.len()
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Completes the upgrade handshake for a request with `key`
+pub(crate) async fn accept<W: AsyncWrite + Unpin>(writer: &mut W, key: &str) -> Result<()> {
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(ACCEPT_GUID.as_bytes());
+    let accept = base64::engine::general_purpose::STANDARD.encode(hasher.finalize());
+    let head = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) enum Message {
+    Text(String),
+    Ping(Vec<u8>),
+    Close,
+}
+
+/// Reads the next message, joining fragments; `None` when the connection
+/// closed without a close frame. Pongs are skipped.
+pub(crate) async fn read_message<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Message>> {
+    let mut text = Vec::new();
+    loop {
+        let mut head = [0u8; 2];
+        match reader.read_exact(&mut head).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let fin = head[0] & 0x80 != 0;
+        let opcode = head[0] & 0x0F;
+        if head[1] & 0x80 == 0 {
+            anyhow::bail!("Client frames must be masked");
+        }
+        let length = match head[1] & 0x7F {
+            126 => reader.read_u16().await? as u64,
+            127 => reader.read_u64().await?,
+            length => length as u64,
+        };
+        if text.len() as u64 + length > MAX_PAYLOAD {
+            anyhow::bail!("Message of {} bytes is too large", text.len() as u64 + length);
+        }
+        let mut mask = [0u8; 4];
+        reader.read_exact(&mut mask).await?;
+        let mut payload = vec![0; length as usize];
+        reader.read_exact(&mut payload).await?;
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+
+        match opcode {
+            OPCODE_TEXT | OPCODE_CONTINUATION => {
+                text.extend_from_slice(&payload);
+                if fin {
+                    return Ok(Some(Message::Text(String::from_utf8(text).context("Text message is not UTF-8")?)));
+                }
+            }
+            OPCODE_PING => return Ok(Some(Message::Ping(payload))),
+            OPCODE_PONG => {}
+            OPCODE_CLOSE => return Ok(Some(Message::Close)),
+            opcode => anyhow::bail!("Unsupported frame opcode {:#x}", opcode),
+        }
+    }
+}
+
+/// Writes one unfragmented, unmasked frame
+pub(crate) async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, opcode: u8, payload: &[u8]) -> Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame).await?;
+    writer.flush().await?;
+    Ok(())
+}
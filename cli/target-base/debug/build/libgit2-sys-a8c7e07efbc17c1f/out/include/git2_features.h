@@ -0,0 +1,16 @@
+#ifndef INCLUDE_features_h
+#define INCLUDE_features_h
+#define GIT_THREADS 1
+#define GIT_TRACE 1
+#define GIT_USE_NSEC 1
+#define GIT_IO_POLL 1
+#define GIT_IO_SELECT 1
+#define GIT_USE_STAT_MTIM 1
+#define GIT_ARCH_64 1
+#define GIT_SSH 1
+#define GIT_SSH_MEMORY_CREDENTIALS 1
+#define GIT_HTTPS 1
+#define GIT_OPENSSL 1
+#define GIT_SHA1_COLLISIONDETECT 1
+#define GIT_SHA256_OPENSSL 1
+#endif
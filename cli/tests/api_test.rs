@@ -0,0 +1,711 @@
+mod common;
+
+use std::process::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+mod api_tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    const GREETER: &str = r#"
+syntax = "proto3";
+package test.v1;
+
+service Greeter {
+  rpc SayHello (HelloRequest) returns (HelloReply);
+}
+
+enum Mood {
+  MOOD_UNSPECIFIED = 0;
+  HAPPY = 1;
+}
+
+message HelloRequest {
+  string name = 1;
+  int32 times = 2;
+  Mood mood = 3;
+  repeated string tags = 4;
+}
+
+message HelloReply {
+  string message = 1;
+  int64 count = 2;
+  Mood mood = 3;
+  repeated int32 scores = 4;
+  map<string, string> labels = 5;
+}
+"#;
+
+    fn create_workspace(port: u16) -> TempDir {
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.greeter"]
+url = "https://github.com/test/greeter.git"
+path = "greeter"
+ports = ["{}"]
+"#,
+            port
+        ));
+        let proto_dir = workspace.path().join("greeter/proto");
+        fs::create_dir_all(&proto_dir).unwrap();
+        fs::write(proto_dir.join("greeter.proto"), GREETER).unwrap();
+        workspace
+    }
+
+    /// Answers one HTTP request with a JSON body and hands back what it got
+    fn serve_http() -> (u16, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            if let Some(mut stream) = listener.incoming().flatten().next() {
+                let mut buf = [0u8; 4096];
+                let read = stream.read(&mut buf).unwrap_or(0);
+                let _ = sender.send(String::from_utf8_lossy(&buf[..read]).into_owned());
+                let body = r#"{"ok":true}"#;
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 201 Created\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        (port, receiver)
+    }
+
+    /// Answers gRPC calls over HTTP/2 with `reply`, handing back the path
+    /// and message of each request
+    fn serve_grpc(reply: Vec<u8>) -> (u16, mpsc::Receiver<(String, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        listener.set_nonblocking(true).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                let (socket, _) = listener.accept().await.unwrap();
+                let mut connection = h2::server::handshake(socket).await.unwrap();
+                while let Some(Ok((request, mut respond))) = connection.accept().await {
+                    let path = request.uri().path().to_string();
+                    let mut body = request.into_body();
+                    let mut received = Vec::new();
+                    while let Some(Ok(chunk)) = body.data().await {
+                        let _ = body.flow_control().release_capacity(chunk.len());
+                        received.extend_from_slice(&chunk);
+                    }
+                    let _ = sender.send((path, received[5..].to_vec()));
+
+                    let response = http::Response::builder()
+                        .status(200)
+                        .header("content-type", "application/grpc")
+                        .body(())
+                        .unwrap();
+                    let mut stream = respond.send_response(response, false).unwrap();
+                    let mut frame = vec![0u8];
+                    frame.extend((reply.len() as u32).to_be_bytes());
+                    frame.extend(&reply);
+                    stream.send_data(bytes::Bytes::from(frame), false).unwrap();
+                    let mut trailers = http::HeaderMap::new();
+                    trailers.insert("grpc-status", "0".parse().unwrap());
+                    stream.send_trailers(trailers).unwrap();
+                }
+            });
+        });
+        (port, receiver)
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|window| window == needle)
+    }
+
+    #[test]
+    fn test_api_call_http_path() {
+        let (port, requests) = serve_http();
+        let workspace = create_workspace(port);
+
+        let mut cmd = common::syla();
+        cmd.args(["api", "call", "greeter", "/things", "--data", r#"{"name":"bob"}"#, "-H", "X-Trace: 42", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\"ok\": true"))
+            .stderr(predicate::str::contains("POST"))
+            .stderr(predicate::str::contains("201 Created"));
+
+        let request = requests.recv_timeout(std::time::Duration::from_secs(5)).unwrap().to_lowercase();
+        assert!(request.starts_with("post /things http/1.1"), "{}", request);
+        assert!(request.contains("content-type: application/json"), "{}", request);
+        assert!(request.contains("x-trace: 42"), "{}", request);
+        assert!(request.ends_with(r#"{"name":"bob"}"#), "{}", request);
+    }
+
+    #[test]
+    fn test_api_call_grpc_method() {
+        let reply = [
+            &[0x0a, 0x06][..],
+            b"hi bob",
+            &[0x10, 0x07],
+            &[0x18, 0x01],
+            // Packed scores [1, 2]
+            &[0x22, 0x02, 0x01, 0x02],
+            // labels {"k": "v"}
+            &[0x2a, 0x06, 0x0a, 0x01, b'k', 0x12, 0x01, b'v'],
+        ]
+        .concat();
+        let (port, requests) = serve_grpc(reply);
+        let workspace = create_workspace(port);
+        let request = workspace.path().join("req.json");
+        fs::write(&request, r#"{"name": "bob", "times": 3, "mood": "HAPPY", "tags": ["x"]}"#).unwrap();
+
+        let mut cmd = common::syla();
+        cmd.args(["api", "call", "greeter", "SayHello", "--data"])
+            .arg(format!("@{}", request.display()))
+            .arg("--workspace")
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\"message\": \"hi bob\""))
+            .stdout(predicate::str::contains("\"count\": \"7\""))
+            .stdout(predicate::str::contains("\"mood\": \"HAPPY\""))
+            .stdout(predicate::str::contains("\"k\": \"v\""))
+            .stderr(predicate::str::contains("test.v1.Greeter/SayHello -> OK"));
+
+        let (path, message) = requests.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(path, "/test.v1.Greeter/SayHello");
+        assert!(contains(&message, &[0x0a, 0x03, b'b', b'o', b'b']), "{:?}", message);
+        assert!(contains(&message, &[0x10, 0x03]), "{:?}", message);
+        assert!(contains(&message, &[0x18, 0x01]), "{:?}", message);
+        assert!(contains(&message, &[0x22, 0x01, b'x']), "{:?}", message);
+    }
+
+    #[test]
+    fn test_api_rejects_unknown_methods_and_fields() {
+        let workspace = create_workspace(1);
+
+        let mut cmd = common::syla();
+        cmd.args(["api", "call", "greeter", "SayHelo", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Method 'SayHelo' not found. Did you mean test.v1.Greeter.SayHello?"));
+
+        let mut cmd = common::syla();
+        cmd.args(["api", "call", "greeter", "SayHello", "--data", r#"{"nmae": "bob"}"#, "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("test.v1.HelloRequest has no field 'nmae'"));
+    }
+
+    #[test]
+    fn test_api_list_shows_methods() {
+        let workspace = create_workspace(50051);
+
+        let mut cmd = common::syla();
+        cmd.env("NO_COLOR", "1")
+            .args(["api", "list", "greeter", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.greeter (port 50051)"))
+            .stdout(predicate::str::contains("test.v1.Greeter"))
+            .stdout(predicate::str::contains("SayHello(HelloRequest) returns HelloReply"));
+    }
+}
+
+mod discover_tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Answers every connection with a 200, for health checks
+    fn serve() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+            }
+        });
+        port
+    }
+
+    /// A port nothing listens on
+        fn create_workspace(api_port: u16, worker_port: u16) -> TempDir {
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+ports = ["{api}"]
+health_check = "http://localhost:{api}/health"
+
+[repositories."test.worker"]
+url = "https://github.com/test/worker.git"
+path = "worker"
+language = "python"
+ports = ["{worker}"]
+depends_on = ["test.api"]
+
+[infrastructure.cache]
+type = "cache"
+docker_image = "redis:7"
+ports = ["{cache}:6379"]
+"#,
+            api = api_port,
+            worker = worker_port,
+            cache = common::free_port()
+        ));
+        fs::create_dir_all(workspace.path().join("worker")).unwrap();
+        workspace
+    }
+
+    fn syla(workspace: &TempDir, args: &[&str]) -> assert_cmd::assert::Assert {
+        let mut cmd = common::syla();
+        cmd.env("NO_COLOR", "1").args(args).arg("--workspace").arg(workspace.path()).assert()
+    }
+
+    #[test]
+    fn test_discover_lists_services_and_writes_the_registry() {
+        let api_port = serve();
+        let worker_port = common::free_port();
+        let workspace = create_workspace(api_port, worker_port);
+
+        syla(&workspace, &["discover"])
+            .success()
+            .stdout(predicate::str::contains("test.api"))
+            .stdout(predicate::str::contains(format!("localhost:{}", api_port)))
+            .stdout(predicate::str::contains("healthy"))
+            .stdout(predicate::str::contains("down"))
+            .stdout(predicate::str::contains("redis://localhost:"));
+
+        let registry: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(workspace.path().join(".platform/state/registry.json")).unwrap())
+                .unwrap();
+        assert_eq!(registry["services"]["test.api"]["port"], api_port);
+        assert_eq!(registry["services"]["test.api"]["health"], "healthy");
+        assert_eq!(registry["services"]["test.worker"]["health"], "down");
+        assert_eq!(registry["services"]["cache"]["infrastructure"], true);
+    }
+
+    #[test]
+    fn test_discover_prints_a_service_url() {
+        let api_port = serve();
+        let workspace = create_workspace(api_port, common::free_port());
+
+        syla(&workspace, &["discover", "api", "--url"])
+            .success()
+            .stdout(format!("http://localhost:{}\n", api_port));
+
+        let output = syla(&workspace, &["discover", "worker", "-o", "json"]).success().get_output().stdout.clone();
+        let endpoint: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(endpoint["health"], "down");
+
+        syla(&workspace, &["discover", "missing"])
+            .failure()
+            .stderr(predicate::str::contains("Service 'missing' not found"));
+    }
+
+    #[test]
+    fn test_services_are_given_the_registry_and_dependency_addresses() {
+        let api_port = serve();
+        let workspace = create_workspace(api_port, common::free_port());
+
+        syla(&workspace, &["dev", "envfile", "worker", "--print"])
+            .success()
+            .stdout(predicate::str::contains(format!("API_ADDR=localhost:{}", api_port)))
+            .stdout(predicate::str::contains(format!("API_URL=http://localhost:{}", api_port)))
+            .stdout(predicate::str::contains("SYLA_REGISTRY="))
+            .stdout(predicate::str::contains(".platform/state/registry.json"));
+    }
+}
+
+#[cfg(unix)]
+mod serve_tests {
+    use super::*;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::time::Duration;
+
+        fn post(port: u16, body: &str, origin: Option<&str>) -> (u16, String) {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let origin = origin.map(|origin| format!("Origin: {}\r\n", origin)).unwrap_or_default();
+        write!(stream, "POST / HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: {}\r\n\r\n{}", origin, body.len(), body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let body = response.split_once("\r\n\r\n").unwrap().1.to_string();
+        (status, body)
+    }
+
+    fn send(stream: &mut TcpStream, text: &str) {
+        let mask = [1u8, 2, 3, 4];
+        let mut frame = vec![0x81];
+        match text.len() {
+            length if length < 126 => frame.push(0x80 | length as u8),
+            length => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(length as u16).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(&mask);
+        frame.extend(text.bytes().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+        stream.write_all(&frame).unwrap();
+    }
+
+    fn receive(stream: &mut TcpStream) -> serde_json::Value {
+        let mut head = [0u8; 2];
+        stream.read_exact(&mut head).unwrap();
+        assert_eq!(head[0], 0x81, "expected a text frame");
+        let length = match head[1] {
+            126 => {
+                let mut length = [0u8; 2];
+                stream.read_exact(&mut length).unwrap();
+                u16::from_be_bytes(length) as usize
+            }
+            127 => {
+                let mut length = [0u8; 8];
+                stream.read_exact(&mut length).unwrap();
+                u64::from_be_bytes(length) as usize
+            }
+            length => length as usize,
+        };
+        let mut payload = vec![0; length];
+        stream.read_exact(&mut payload).unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    #[test]
+    fn test_serve_answers_json_rpc_over_http_and_websocket() {
+        let (port, service_port) = (common::free_port(), common::free_port());
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.echo"]
+url = "https://github.com/test/echo.git"
+path = "echo"
+language = "shell"
+run = "echo started-$PORT; sleep 60"
+ports = ["{}"]
+"#,
+            service_port
+        ));
+        fs::create_dir_all(workspace.path().join("echo")).unwrap();
+
+        let mut server = Command::new(env!("CARGO_BIN_EXE_syla"))
+            .args(["serve", "--port", &port.to_string(), "--workspace"])
+            .arg(workspace.path())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        (0..100)
+            .find(|_| {
+                std::thread::sleep(Duration::from_millis(100));
+                TcpStream::connect(("127.0.0.1", port)).is_ok()
+            })
+            .expect("server never listened");
+
+        let (status, body) = post(port, r#"{"jsonrpc": "2.0", "id": 1, "method": "services.list"}"#, None);
+        assert_eq!(status, 200, "{}", body);
+        let list: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(list["id"], 1);
+        assert_eq!(list["result"][0]["name"], "test.echo");
+        assert_eq!(list["result"][0]["state"], "stopped");
+
+        let (_, body) = post(port, r#"[{"jsonrpc": "2.0", "id": "a", "method": "nope"}, {"jsonrpc": "2.0", "method": "server.info"}]"#, None);
+        let batch: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(batch.as_array().unwrap().len(), 1, "notifications get no response: {}", batch);
+        assert_eq!(batch[0]["error"]["code"], -32601);
+
+        let (status, _) = post(port, r#"{"jsonrpc": "2.0", "id": 1, "method": "services.list"}"#, Some("https://example.com"));
+        assert_eq!(status, 403);
+        let (status, _) = post(port, r#"{"jsonrpc": "2.0", "id": 1, "method": "services.list"}"#, Some("http://localhost:3000"));
+        assert_eq!(status, 200);
+
+        let mut ws = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        ws.set_read_timeout(Some(Duration::from_secs(20))).unwrap();
+        write!(
+            ws,
+            "GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n"
+        )
+        .unwrap();
+        let mut handshake = BufReader::new(ws.try_clone().unwrap());
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            handshake.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            lines.push(line.trim().to_string());
+        }
+        assert!(lines[0].contains("101"), "{:?}", lines);
+        assert!(lines.contains(&"Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=".to_string()), "{:?}", lines);
+
+        send(&mut ws, r#"{"jsonrpc": "2.0", "id": 1, "method": "logs.follow", "params": {"service": "echo"}}"#);
+        let followed = receive(&mut ws);
+        assert_eq!(followed["result"]["service"], "test.echo", "{}", followed);
+        let subscription = followed["result"]["subscription"].clone();
+
+        send(&mut ws, r#"{"jsonrpc": "2.0", "id": 2, "method": "services.start", "params": {"service": "echo"}}"#);
+        let expected = format!("started-{}", service_port);
+        let (mut started, mut logged) = (false, false);
+        while !(started && logged) {
+            let message = receive(&mut ws);
+            if message["id"] == 2 {
+                assert!(message["result"]["pid"].is_u64(), "{}", message);
+                started = true;
+            } else if message["method"] == "logs.line" && message["params"]["line"] == expected.as_str() {
+                assert_eq!(message["params"]["subscription"], subscription);
+                logged = true;
+            }
+        }
+
+        send(&mut ws, r#"{"jsonrpc": "2.0", "id": 3, "method": "services.start", "params": {}}"#);
+        let invalid = receive(&mut ws);
+        assert_eq!(invalid["error"]["code"], -32602, "{}", invalid);
+
+        Command::new("kill").args(["-INT", &server.id().to_string()]).status().unwrap();
+        assert!(server.wait().unwrap().success());
+        assert!(TcpStream::connect(("127.0.0.1", port)).is_err(), "server still listening");
+    }
+}
+
+mod contract_tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Answers every connection with `body` as JSON
+    fn serve(body: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                std::thread::spawn(move || {
+                    let mut stream = stream;
+                    let mut buf = [0u8; 1024];
+                    let _ = stream.read(&mut buf);
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes());
+                });
+            }
+        });
+        port
+    }
+
+    fn create_workspace(port: u16, consumer_command: &str) -> TempDir {
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.gateway"]
+url = "https://github.com/test/gateway.git"
+path = "gateway"
+
+[repositories."test.executor"]
+url = "https://github.com/test/executor.git"
+path = "executor"
+ports = ["{}"]
+
+[contracts.gateway-executor]
+consumer = "test.gateway"
+provider = "test.executor"
+file = "contracts/gateway-executor.toml"
+consumer_command = "{}"
+"#,
+            port, consumer_command
+        ));
+        fs::create_dir_all(workspace.path().join("gateway")).unwrap();
+        fs::create_dir_all(workspace.path().join("contracts")).unwrap();
+        fs::write(
+            workspace.path().join("contracts/gateway-executor.toml"),
+            r#"
+[[interaction]]
+description = "get a job"
+path = "/jobs/1"
+
+[interaction.response]
+body = { id = "1", status = "queued", logs = [{ line = "started" }] }
+"#,
+        )
+        .unwrap();
+        workspace
+    }
+
+    #[test]
+    fn test_contract_holds() {
+        let port = serve(r#"{"id":"1","status":"running","logs":[{"line":"a"},{"line":"b"}],"extra":true}"#);
+        let workspace = create_workspace(port, "curl -sf $SYLA_CONTRACT_PROVIDER_URL/jobs/1");
+
+        let mut cmd = common::syla();
+        cmd.args(["contract", "test", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[OK] provider: get a job"))
+            .stdout(predicate::str::contains("[OK] consumer: suite passed against the stub"))
+            .stdout(predicate::str::contains("All contracts hold"));
+    }
+
+    #[test]
+    fn test_contract_reports_drift() {
+        let port = serve(r#"{"id":1,"logs":[{"line":"a"},{}]}"#);
+        let workspace = create_workspace(port, "curl -s $SYLA_CONTRACT_PROVIDER_URL/jobs/1/cancel");
+
+        let mut cmd = common::syla();
+        cmd.args(["contract", "test", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("$.id is a number, expected a string"))
+            .stdout(predicate::str::contains("$.status is missing"))
+            .stdout(predicate::str::contains("$.logs[1].line is missing"))
+            .stdout(predicate::str::contains("GET /jobs/1/cancel is not in the contract"))
+            .stderr(predicate::str::contains("Contracts broken: gateway-executor"));
+    }
+}
+
+mod proto_tests {
+    use super::*;
+    use std::fs;
+
+    const JOBS_PROTO: &str = r#"
+syntax = "proto3";
+package syla.jobs.v1;
+
+// Submitted work
+message Job {
+    string id = 1;
+    repeated string tags = 2;
+    map<string, string> labels = 3;
+    oneof source {
+        string code = 4;
+        string url = 5;
+    }
+    enum State {
+        STATE_UNSPECIFIED = 0;
+        STATE_QUEUED = 1;
+    }
+    State state = 6;
+}
+
+service Jobs {
+    rpc Get(GetRequest) returns (Job);
+    rpc Watch(GetRequest) returns (stream Job) {
+        option deprecated = true;
+    }
+}
+
+message GetRequest {
+    string id = 1;
+}
+"#;
+
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+proto_includes = ["proto-common"]
+
+[repositories."test.jobs"]
+url = "https://github.com/test/jobs.git"
+path = "jobs"
+language = "rust"
+"#);
+        let repo = workspace.path().join("jobs");
+        fs::create_dir_all(repo.join("proto/syla")).unwrap();
+        fs::write(repo.join("proto/jobs.proto"), JOBS_PROTO).unwrap();
+        workspace
+    }
+
+    fn syla(workspace: &TempDir, args: &[&str]) -> assert_cmd::assert::Assert {
+        let mut cmd = common::syla();
+        cmd.arg("proto").args(args).arg("--workspace").arg(workspace.path()).assert()
+    }
+
+    #[test]
+    fn test_proto_breaking_changes() {
+        let workspace = create_workspace();
+        let repo = workspace.path().join("jobs");
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(&repo)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+
+        syla(&workspace, &["list"])
+            .success()
+            .stdout(predicate::str::contains("syla.jobs.v1 jobs.proto"));
+        syla(&workspace, &["breaking"])
+            .success()
+            .stdout(predicate::str::contains("No breaking changes"));
+
+        // Compatible: a new field and a removed-but-reserved one
+        let compatible = JOBS_PROTO
+            .replace("string url = 5;", "")
+            .replace("State state = 6;", "State state = 6;\n    reserved 5;\n    int64 priority = 7;");
+        fs::write(repo.join("proto/jobs.proto"), compatible).unwrap();
+        syla(&workspace, &["breaking"])
+            .success()
+            .stdout(predicate::str::contains("No breaking changes"));
+
+        let breaking = JOBS_PROTO
+            .replace("repeated string tags = 2;", "string tags = 2;")
+            .replace("string code = 4;", "string source_code = 4;")
+            .replace("STATE_QUEUED = 1;", "")
+            .replace("returns (stream Job)", "returns (Job)");
+        fs::write(repo.join("proto/jobs.proto"), breaking).unwrap();
+        syla(&workspace, &["breaking", "--against", "main"])
+            .failure()
+            .stdout(predicate::str::contains(
+                "syla.jobs.v1.Job.tags (2) changed type from repeated string to string",
+            ))
+            .stdout(predicate::str::contains("syla.jobs.v1.Job field 4 was renamed from code to source_code"))
+            .stdout(predicate::str::contains(
+                "syla.jobs.v1.Job.State value 1 (STATE_QUEUED) was removed without reserving it",
+            ))
+            .stdout(predicate::str::contains(
+                "rpc syla.jobs.v1.Jobs/Watch response changed from stream Job to Job",
+            ))
+            .stderr(predicate::str::contains("Breaking proto changes in test.jobs"));
+    }
+
+    #[test]
+    fn test_proto_check_finds_drifted_copies() {
+        let workspace = create_workspace();
+        fs::create_dir_all(workspace.path().join("proto-common/syla")).unwrap();
+        fs::write(workspace.path().join("proto-common/syla/common.proto"), "syntax = \"proto3\";\n").unwrap();
+        fs::write(workspace.path().join("jobs/proto/syla/common.proto"), "syntax = \"proto3\";\n").unwrap();
+        syla(&workspace, &["check"])
+            .success()
+            .stdout(predicate::str::contains("Protos are in sync"));
+
+        fs::write(
+            workspace.path().join("jobs/proto/syla/common.proto"),
+            "syntax = \"proto3\";\nmessage Old {}\n",
+        )
+        .unwrap();
+        syla(&workspace, &["check"])
+            .failure()
+            .stdout(predicate::str::contains("syla/common.proto differs between proto-common, jobs/proto"));
+    }
+}
@@ -0,0 +1,666 @@
+mod common;
+
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+#[test]
+fn test_syla_version() {
+    let mut cmd = common::syla();
+    cmd.arg("--version")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("syla"));
+}
+
+#[test]
+fn test_syla_help() {
+    let mut cmd = common::syla();
+    cmd.arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Meta-platform CLI"));
+}
+
+#[test]
+fn test_syla_doctor() {
+    let mut cmd = common::syla();
+    cmd.arg("doctor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checking system requirements"));
+}
+
+#[test]
+fn test_syla_status_without_workspace() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cmd = common::syla();
+    cmd.arg("status")
+        .arg("--workspace")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to read manifest"));
+}
+
+#[test]
+fn test_dashboard_without_workspace() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cmd = common::syla();
+    cmd.arg("dashboard")
+        .arg("--workspace")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to read manifest"));
+}
+
+#[test]
+fn test_dev_validate_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cmd = common::syla();
+    cmd.arg("dev")
+        .arg("validate")
+        .arg("--workspace")
+        .arg(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[cfg(unix)]
+mod plugin_tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn install_script(dir: &std::path::Path, name: &str, body: &str) {
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_external_plugin_dispatch() {
+        let plugin_home = TempDir::new().unwrap();
+        let bin_dir = plugin_home.path().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        install_script(&bin_dir, "syla-hello", "echo \"api=$SYLA_PLUGIN_API args=$*\"\nexit 3");
+
+        let mut cmd = common::syla();
+        cmd.arg("hello")
+            .arg("world")
+            .env("SYLA_PLUGIN_HOME", plugin_home.path())
+            .assert()
+            .code(3)
+            .stdout(predicate::str::contains("api=1 args=world"));
+    }
+
+    #[test]
+    fn test_unknown_plugin() {
+        let plugin_home = TempDir::new().unwrap();
+        let mut cmd = common::syla();
+        cmd.arg("definitely-not-a-plugin")
+            .env("SYLA_PLUGIN_HOME", plugin_home.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Unknown command 'definitely-not-a-plugin'"));
+    }
+
+    #[test]
+    fn test_plugin_install_and_list() {
+        let plugin_home = TempDir::new().unwrap();
+        let source = TempDir::new().unwrap();
+        install_script(
+            source.path(),
+            "syla-lint",
+            r#"echo '{"version":"0.2.0","description":"Workspace linter"}'"#,
+        );
+
+        let mut cmd = common::syla();
+        cmd.arg("plugin")
+            .arg("install")
+            .arg(source.path().join("syla-lint"))
+            .env("SYLA_PLUGIN_HOME", plugin_home.path())
+            .assert()
+            .success();
+
+        let mut cmd = common::syla();
+        cmd.arg("plugin")
+            .arg("list")
+            .env("SYLA_PLUGIN_HOME", plugin_home.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("lint"))
+            .stdout(predicate::str::contains("Workspace linter"));
+    }
+}
+
+mod telemetry_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_telemetry_disabled_by_default() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+
+        let mut cmd = common::syla();
+        cmd.arg("status")
+            .arg("--workspace")
+            .arg(workspace.path())
+            .env("SYLA_HOME", home.path())
+            .assert()
+            .failure();
+
+        assert!(!home.path().join("telemetry").exists());
+    }
+
+    #[test]
+    fn test_telemetry_records_after_opt_in() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+
+        let mut cmd = common::syla();
+        cmd.arg("telemetry")
+            .arg("enable")
+            .env("SYLA_HOME", home.path())
+            .env_remove("DO_NOT_TRACK")
+            .assert()
+            .success();
+
+        let mut cmd = common::syla();
+        cmd.arg("status")
+            .arg("--workspace")
+            .arg(workspace.path())
+            .env("SYLA_HOME", home.path())
+            .env_remove("DO_NOT_TRACK")
+            .assert()
+            .failure();
+
+        let events = fs::read_to_string(home.path().join("telemetry/events.jsonl")).unwrap();
+        assert!(events.contains("\"command\":\"status\""));
+        assert!(events.contains("\"failure_category\":\"workspace\""));
+        assert!(!events.contains(&workspace.path().display().to_string()));
+
+        let mut cmd = common::syla();
+        cmd.arg("telemetry")
+            .arg("disable")
+            .env("SYLA_HOME", home.path())
+            .assert()
+            .success();
+        assert!(!home.path().join("telemetry/events.jsonl").exists());
+    }
+}
+
+mod secrets_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_secrets_roundtrip_encrypted() {
+        let home = TempDir::new().unwrap();
+
+        let mut cmd = common::syla();
+        cmd.args(["secrets", "set", "openai_api_key", "sk-test-value"])
+            .env("SYLA_HOME", home.path())
+            .assert()
+            .success();
+
+        let store = fs::read(home.path().join("secrets/store.age")).unwrap();
+        assert!(!String::from_utf8_lossy(&store).contains("sk-test-value"));
+
+        let mut cmd = common::syla();
+        cmd.args(["secrets", "get", "openai_api_key"])
+            .env("SYLA_HOME", home.path())
+            .assert()
+            .success()
+            .stdout("sk-test-value\n");
+
+        let mut cmd = common::syla();
+        cmd.args(["secrets", "list"])
+            .env("SYLA_HOME", home.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("openai_api_key"))
+            .stdout(predicate::str::contains("sk-test-value").not());
+    }
+
+    #[test]
+    fn test_secrets_get_missing() {
+        let home = TempDir::new().unwrap();
+        let mut cmd = common::syla();
+        cmd.args(["secrets", "get", "missing"])
+            .env("SYLA_HOME", home.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Secret 'missing' is not set"));
+    }
+}
+
+mod user_config_tests {
+    use super::*;
+    use std::fs;
+
+    fn syla(home: &TempDir, workspace: &TempDir, args: &[&str]) -> assert_cmd::assert::Assert {
+        common::syla()
+            .env("SYLA_HOME", home.path())
+            .env("NO_COLOR", "1")
+            .args(["-q", "--workspace"])
+            .arg(workspace.path())
+            .args(args)
+            .assert()
+    }
+
+    #[test]
+    fn test_config_set_get_unset_layers_user_and_workspace() {
+        let home = TempDir::new().unwrap();
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+"#);
+        let config_dir = workspace.path().join(".platform/config");
+        fs::write(config_dir.join("workspace.toml"), "# kept\n[notifications]\nhealth = false\n").unwrap();
+
+        syla(&home, &workspace, &["config", "set", "dev.default_platform", "syla"]).success();
+        syla(&home, &workspace, &["config", "set", "notifications.health", "true"]).success();
+        syla(&home, &workspace, &["config", "set", "compose_profiles", "gpu, db"]).success();
+        let user = fs::read_to_string(home.path().join("config.toml")).unwrap();
+        assert!(user.contains("default_platform = \"syla\""), "{}", user);
+
+        // The workspace's own value wins over the user's
+        syla(&home, &workspace, &["config", "get", "notifications.health"]).success().stdout("false\n");
+        syla(&home, &workspace, &["config", "get", "dev.default_platform"]).success().stdout("syla\n");
+        syla(&home, &workspace, &["config", "get", "compose_profiles"]).success().stdout("gpu,db\n");
+        syla(&home, &workspace, &["config", "get", "build.sccache"]).success().stdout("false\n");
+
+        syla(&home, &workspace, &["config", "set", "build.sccache", "true", "--local"]).success();
+        let local = fs::read_to_string(config_dir.join("workspace.toml")).unwrap();
+        assert!(local.starts_with("# kept\n"), "{}", local);
+        assert!(local.contains("sccache = true"), "{}", local);
+
+        syla(&home, &workspace, &["config", "show"])
+            .success()
+            .stdout(predicate::str::is_match(r"dev\.default_platform +syla \(user\)").unwrap())
+            .stdout(predicate::str::is_match(r"notifications\.health +false \(workspace\)").unwrap())
+            .stdout(predicate::str::is_match(r"notifications\.crashes +true \(default\)").unwrap());
+
+        syla(&home, &workspace, &["config", "set", "notifications.build_threshold_secs", "soon"])
+            .failure()
+            .stderr(predicate::str::contains("expected a whole number"));
+        syla(&home, &workspace, &["config", "set", "dev.nothing", "x"])
+            .failure()
+            .stderr(predicate::str::contains("Unknown key 'dev.nothing'"));
+
+        syla(&home, &workspace, &["config", "unset", "dev.default_platform"]).success();
+        let user = fs::read_to_string(home.path().join("config.toml")).unwrap();
+        assert!(!user.contains("[dev]"), "{}", user);
+        syla(&home, &workspace, &["config", "get", "dev.default_platform"])
+            .failure()
+            .stderr(predicate::str::contains("dev.default_platform is not set"));
+    }
+}
+
+mod feature_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_enable_feature() {
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+
+[repositories."test.trainer"]
+url = "https://github.com/test/trainer.git"
+path = "trainer"
+requires_feature = "ml"
+
+[repositories."test.labs"]
+url = "https://github.com/test/labs.git"
+path = "labs"
+optional = true
+"#);
+        let config_dir = workspace.path().join(".platform/config");
+
+        let mut cmd = common::syla();
+        cmd.args(["config", "features", "list", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("ml (disabled)"))
+            .stdout(predicate::str::contains("test.labs (disabled)"))
+            .stdout(predicate::str::contains("test.api").not());
+
+        let mut cmd = common::syla();
+        cmd.args(["config", "features", "enable", "ml", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Enabled ml: test.trainer"));
+        let settings = fs::read_to_string(config_dir.join("workspace.toml")).unwrap();
+        assert!(settings.contains("features = [\"ml\"]"));
+
+        let mut cmd = common::syla();
+        cmd.args(["config", "features", "list", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("ml (enabled)"));
+
+        let mut cmd = common::syla();
+        cmd.args(["config", "features", "enable", "gpu", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Unknown feature 'gpu'. Known features: ml, test.labs"));
+    }
+}
+
+mod verbosity_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.tool"]
+url = "https://github.com/test/tool.git"
+path = "tool"
+build = "echo built > out.txt"
+run = "true"
+"#);
+        fs::create_dir_all(workspace.path().join("tool")).unwrap();
+        workspace
+    }
+
+    #[test]
+    fn test_quiet_prints_nothing_on_success() {
+        let workspace = create_workspace();
+        let mut cmd = common::syla();
+        cmd.args(["dev", "build-changed", "--all", "--quiet", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout("");
+        assert!(workspace.path().join("tool/out.txt").exists());
+    }
+
+    #[test]
+    fn test_quiet_still_reports_errors() {
+        let workspace = create_workspace();
+        let mut cmd = common::syla();
+        cmd.args(["dev", "restart", "nothing-like-it", "-q", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Service 'nothing-like-it' not found"));
+    }
+
+    #[test]
+    fn test_verbose_shows_commands_and_timings() {
+        let workspace = create_workspace();
+        let mut cmd = common::syla();
+        cmd.env("NO_COLOR", "1")
+            .args(["dev", "build-changed", "--all", "--verbose", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("$ sh -c 'echo built > out.txt'"))
+            .stdout(predicate::str::contains("time.busy"));
+    }
+
+    #[test]
+    fn test_quiet_conflicts_with_verbose() {
+        let mut cmd = common::syla();
+        cmd.args(["status", "-q", "-v"]).assert().failure();
+    }
+}
+
+mod completion_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace() -> TempDir {
+        common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+platform = "core"
+
+[repositories."test.web"]
+url = "https://github.com/test/web.git"
+path = "web"
+platform = "frontend"
+
+[repositories."tools.cli"]
+url = "https://github.com/test/cli.git"
+path = "cli"
+platform = "tools"
+"#)
+    }
+
+    fn complete(workspace: &TempDir, words: &[&str]) -> Vec<String> {
+        let output = common::syla()
+            .args(["--workspace"])
+            .arg(workspace.path())
+            .args(["__complete", "--"])
+            .args(words)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_complete_service_platform_and_execution() {
+        let workspace = create_workspace();
+        assert_eq!(complete(&workspace, &["dev", "restart", "test."]), ["test.api", "test.web"]);
+        assert_eq!(complete(&workspace, &["why", ""]), ["test.api", "test.web", "tools.cli"]);
+        assert_eq!(complete(&workspace, &["platform", "start", ""]), ["core", "frontend", "tools"]);
+        assert_eq!(complete(&workspace, &["dev", "up", "--platform", "f"]), ["frontend"]);
+        // Other arguments are left to the static completions
+        assert!(complete(&workspace, &["dev", "logs", "--lines", ""]).is_empty());
+        assert!(complete(&workspace, &["dev", ""]).is_empty());
+
+        let state = workspace.path().join(".platform/state");
+        fs::create_dir_all(&state).unwrap();
+        fs::write(state.join("executions.log"), "exec-1\nexec-2\n").unwrap();
+        assert_eq!(complete(&workspace, &["executions", "replay", "exec"]), ["exec-2", "exec-1"]);
+    }
+
+    #[test]
+    fn test_completions_script_calls_hook() {
+        for shell in ["bash", "zsh", "fish"] {
+            common::syla()
+                .args(["completions", shell])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("syla __complete --"))
+                .stdout(predicate::str::contains("Meta-Platform CLI").not());
+        }
+    }
+}
+
+mod debug_log_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_commands_are_logged_as_json() {
+        let workspace = common::workspace(r#"
+[repositories."test.tool"]
+url = "https://github.com/test/tool.git"
+path = "tool"
+language = "shell"
+build = "echo built"
+"#);
+        fs::create_dir_all(workspace.path().join("tool")).unwrap();
+
+        common::syla()
+            .env_remove("SYLA_DEBUG_LOG")
+            .args(["dev", "build-changed", "--all", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success();
+
+        let log = fs::read_to_string(workspace.path().join(".platform/logs/syla.log")).unwrap();
+        let entries: Vec<serde_json::Value> = log.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let started = entries
+            .iter()
+            .find(|entry| entry["message"].as_str().is_some_and(|m| m.starts_with("$ sh -c 'echo built'")))
+            .expect("command line logged");
+        assert_eq!(started["level"], "DEBUG");
+        let finished = entries
+            .iter()
+            .find(|entry| entry["message"].as_str().is_some_and(|m| m.starts_with("sh exited")))
+            .expect("command exit logged");
+        assert_eq!(finished["fields"]["exit_code"], 0);
+        assert!(finished["fields"]["duration_ms"].is_u64());
+        assert!(finished["spans"].as_array().unwrap().iter().any(|span| span["fields"]["service"] == "test.tool"));
+    }
+}
+
+#[cfg(unix)]
+mod gpu_doctor_tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn create_workspace() -> TempDir {
+        common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+"#)
+    }
+
+    fn fake_program(dir: &std::path::Path, name: &str, script: &str) {
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    fn doctor(workspace: &TempDir, bin: &std::path::Path, args: &[&str]) -> assert_cmd::assert::Assert {
+        // Only the fakes, so a real GPU setup on the machine doesn't leak in
+        common::syla()
+            .env("PATH", bin)
+            .env("DOCKER_HOST", format!("unix://{}", workspace.path().join("no-docker.sock").display()))
+            .env("NO_COLOR", "1")
+            .arg("doctor")
+            .args(args)
+            .arg("--workspace")
+            .arg(workspace.path())
+            .assert()
+    }
+
+    #[test]
+    fn test_doctor_gpu_finds_driver_and_toolkit() {
+        let workspace = create_workspace();
+        let bin = TempDir::new().unwrap();
+        fake_program(bin.path(), "nvidia-smi", "echo 'NVIDIA A100-SXM4-40GB, 535.104.05'\necho 'NVIDIA A100-SXM4-40GB, 535.104.05'");
+        fake_program(bin.path(), "nvidia-ctk", "echo 'NVIDIA Container Toolkit CLI version 1.14.3'");
+
+        let output = doctor(&workspace, bin.path(), &["--gpu", "-o", "json"]).success().get_output().stdout.clone();
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let check = |name: &str| report["checks"].as_array().unwrap().iter().find(|c| c["name"] == name).unwrap().clone();
+
+        let driver = check("NVIDIA driver");
+        assert_eq!(driver["ok"], true);
+        assert_eq!(driver["detail"], "2 GPUs (NVIDIA A100-SXM4-40GB), driver 535.104.05");
+        let toolkit = check("NVIDIA Container Toolkit");
+        assert_eq!(toolkit["ok"], true);
+        assert_eq!(toolkit["detail"], "NVIDIA Container Toolkit CLI version 1.14.3");
+        let container = check("GPU container");
+        assert_eq!(container["ok"], false);
+        assert!(container["detail"].as_str().unwrap().starts_with("Docker is not available"), "{}", container);
+    }
+
+    #[test]
+    fn test_doctor_gpu_suggests_fixes() {
+        let workspace = create_workspace();
+        let bin = TempDir::new().unwrap();
+        fake_program(
+            bin.path(),
+            "nvidia-smi",
+            "echo \"NVIDIA-SMI has failed because it couldn't communicate with the NVIDIA driver.\"; exit 9",
+        );
+
+        doctor(&workspace, bin.path(), &["--gpu", "--fix"])
+            .success()
+            .stdout(predicate::str::contains("NVIDIA driver: [X] (NVIDIA-SMI has failed"))
+            .stdout(predicate::str::contains("Install the NVIDIA driver"))
+            .stdout(predicate::str::contains("NVIDIA Container Toolkit: [X] (nvidia-ctk not found)"))
+            .stdout(predicate::str::contains("Install nvidia-container-toolkit"));
+
+        doctor(&workspace, bin.path(), &[]).success().stdout(predicate::str::contains("NVIDIA").not());
+    }
+}
+
+mod history_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.tool"]
+url = "https://github.com/test/tool.git"
+path = "tool"
+build = "echo built > out.txt"
+
+[tasks.hello]
+command = "echo hello"
+"#);
+        fs::create_dir_all(workspace.path().join("tool")).unwrap();
+        workspace
+    }
+
+    fn syla(workspace: &TempDir, args: &[&str]) -> assert_cmd::assert::Assert {
+        let mut cmd = common::syla();
+        cmd.env("USER", "alice").env("NO_COLOR", "1").args(args).arg("--workspace").arg(workspace.path()).assert()
+    }
+
+    #[test]
+    fn test_history_records_state_changing_commands() {
+        let workspace = create_workspace();
+        syla(&workspace, &["dev", "build-changed", "--all"]).success();
+        syla(&workspace, &["dev", "restart", "gateway"]).failure();
+        syla(&workspace, &["status"]);
+        syla(&workspace, &["dev", "envfile", "--print"]).success();
+        syla(&workspace, &["dev", "envfile"]).success();
+
+        syla(&workspace, &["history"])
+            .success()
+            .stdout(predicate::str::contains("syla dev build-changed --all"))
+            .stdout(predicate::str::contains("syla dev restart gateway"))
+            .stdout(predicate::str::contains("failed: Service 'gateway' not found"))
+            .stdout(predicate::str::contains("alice"))
+            .stdout(predicate::str::contains("syla dev envfile").count(1))
+            .stdout(predicate::str::contains("syla status").not());
+
+        syla(&workspace, &["history", "gateway"])
+            .success()
+            .stdout(predicate::str::contains("dev restart gateway"))
+            .stdout(predicate::str::contains("build-changed").not());
+
+        let output = syla(&workspace, &["history", "--failed", "-o", "json"]).success().get_output().stdout.clone();
+        let entries: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let entries = entries.as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["command"], "dev restart");
+        assert_eq!(entries[0]["user"], "alice");
+        assert_eq!(entries[0]["success"], false);
+    }
+
+    #[test]
+    fn test_history_records_tasks_but_not_dry_runs() {
+        let workspace = create_workspace();
+        syla(&workspace, &["run", "hello"]).success();
+        syla(&workspace, &["run", "hello", "--dry-run"]).success();
+        syla(&workspace, &["run"]).success();
+
+        let output = syla(&workspace, &["history", "-o", "json"]).success().get_output().stdout.clone();
+        let entries: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let commands: Vec<&str> = entries.as_array().unwrap().iter().map(|e| e["command"].as_str().unwrap()).collect();
+        assert_eq!(commands, ["run"]);
+    }
+}
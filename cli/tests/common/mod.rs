@@ -0,0 +1,30 @@
+//! Helpers shared by the integration test binaries.
+//!
+//! No binary uses all of them, hence the `dead_code` allow.
+#![allow(dead_code)]
+
+use assert_cmd::Command as TestCommand;
+use std::fs;
+use tempfile::TempDir;
+
+/// The `syla` binary with its debug log off, so commands that aren't
+/// pointed at a temp workspace don't append to this repository's log
+pub fn syla() -> TestCommand {
+    let mut cmd = TestCommand::cargo_bin("syla").unwrap();
+    cmd.env("SYLA_DEBUG_LOG", "off");
+    cmd
+}
+
+/// A workspace in a temp dir with `manifest` as its repos.toml
+pub fn workspace(manifest: &str) -> TempDir {
+    let workspace = TempDir::new().unwrap();
+    let config_dir = workspace.path().join(".platform/config");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(config_dir.join("repos.toml"), manifest).unwrap();
+    workspace
+}
+
+/// A port nothing is listening on
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
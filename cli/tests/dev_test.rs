@@ -0,0 +1,1674 @@
+mod common;
+
+use std::process::Command;
+use predicates::prelude::*;
+use assert_cmd::Command as TestCommand;
+use tempfile::TempDir;
+
+mod validate_ci_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_dev_validate_ci_junit() {
+        let workspace = common::workspace(r#"
+[repositories."test.web"]
+url = "https://github.com/test/web.git"
+path = "web"
+language = "typescript"
+"#);
+        let junit = workspace.path().join("reports/validate.xml");
+
+        // Docker may or may not be available here, but the missing
+        // repository always sets the repositories bit
+        let mut cmd = common::syla();
+        cmd.args(["dev", "validate", "--ci", "--junit"])
+            .arg(&junit)
+            .arg("--workspace")
+            .arg(workspace.path())
+            .env("GITHUB_ACTIONS", "true")
+            .assert()
+            .code(predicate::function(|code: &i32| code & 2 != 0 && code & 8 == 0))
+            .stdout(predicate::str::contains(
+                "::error file=.platform/config/repos.toml,title=repositories%3A test.web::Repository test.web not cloned",
+            ));
+
+        let report = fs::read_to_string(&junit).unwrap();
+        assert!(report.contains("<testsuite name=\"repositories\" tests=\"1\" failures=\"1\">"));
+        assert!(report.contains("<failure message=\"Repository test.web not cloned\"/>"));
+    }
+
+    /// A running service with `/health` and an `/executions` API whose jobs
+    /// complete at once, printing 3; anything else is a 404
+    fn serve() -> u16 {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; 4096];
+                let read = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..read]).into_owned();
+                let (status, body) = if request.starts_with("GET /health") {
+                    ("200 OK", r#"{"status": "ok"}"#)
+                } else if request.starts_with("POST /executions") {
+                    ("200 OK", r#"{"id": "job-1", "status": "queued"}"#)
+                } else if request.starts_with("GET /executions/job-1") {
+                    ("200 OK", r#"{"id": "job-1", "status": "completed", "result": {"exit_code": 0, "stdout": "3\n"}}"#)
+                } else {
+                    ("404 Not Found", r#"{"error": "not found"}"#)
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn test_dev_validate_integration_scenarios() {
+        let port = serve();
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+ports = ["{port}"]
+health_check = "http://localhost:{port}/health"
+
+[scenarios.round-trip]
+description = "Submit code and read back its output"
+
+[[scenarios.round-trip.step]]
+service = "test.api"
+path = "/health"
+response = {{ body = {{ status = "ok" }} }}
+
+[[scenarios.round-trip.step]]
+service = "test.api"
+execute = {{ language = "python", code = "print(1 + 2)", expected_output = "3" }}
+
+[[scenarios.broken.step]]
+service = "test.api"
+path = "/missing"
+"#,
+            port = port
+        ));
+        fs::create_dir_all(workspace.path().join("api")).unwrap();
+        let junit = workspace.path().join("validate.xml");
+
+        let mut cmd = common::syla();
+        cmd.args(["dev", "validate", "--integration", "--ci", "--junit"])
+            .arg(&junit)
+            .arg("--workspace")
+            .arg(workspace.path())
+            .assert()
+            .code(predicate::function(|code: &i32| code & 16 != 0))
+            .stdout(predicate::str::contains("test.api already running"))
+            .stdout(predicate::str::contains("[OK] test.api runs python"))
+            .stdout(predicate::str::contains("[OK] round-trip"))
+            .stdout(predicate::str::contains("[X] GET test.api /missing"))
+            .stdout(predicate::str::contains("status 404, expected 200"));
+
+        let report = fs::read_to_string(&junit).unwrap();
+        assert!(report.contains("<testsuite name=\"integration\" tests=\"2\" failures=\"1\">"), "{}", report);
+    }
+}
+
+mod changes_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.svc"]
+url = "https://github.com/test/svc.git"
+path = "svc"
+language = "rust"
+"#);
+        let repo = workspace.path().join("svc");
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::write(
+            repo.join("Cargo.toml"),
+            "[package]\nname = \"svc\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        fs::write(repo.join("src/main.rs"), "fn main() {}\n").unwrap();
+        workspace
+    }
+
+    fn build_changed(workspace: &TempDir, args: &[&str]) -> assert_cmd::assert::Assert {
+        let mut cmd = common::syla();
+        cmd.args(["dev", "build-changed", "--dry-run"])
+            .args(args)
+            .arg("--workspace")
+            .arg(workspace.path())
+            .assert()
+    }
+
+    #[test]
+    fn test_build_changed_since_last_build() {
+        let workspace = create_workspace();
+        build_changed(&workspace, &[])
+            .success()
+            .stdout(predicate::str::contains("test.svc (no release build)"));
+        build_changed(&workspace, &["--profile", "debug"])
+            .success()
+            .stdout(predicate::str::contains("test.svc (no debug build)"));
+
+        let release = workspace.path().join("svc/target/release");
+        fs::create_dir_all(&release).unwrap();
+        fs::write(release.join("svc"), "").unwrap();
+        build_changed(&workspace, &[])
+            .success()
+            .stdout(predicate::str::contains("Everything is up to date"));
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        fs::write(workspace.path().join("svc/src/main.rs"), "fn main() { }\n").unwrap();
+        build_changed(&workspace, &[])
+            .success()
+            .stdout(predicate::str::contains("test.svc (src/main.rs modified)"));
+    }
+
+    #[test]
+    fn test_build_changed_compares_contents_with_the_last_build() {
+        let workspace = create_workspace();
+        common::syla()
+            .args(["dev", "build-changed", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success();
+        let cache = fs::read_to_string(workspace.path().join(".syla/build-cache.json")).unwrap();
+        assert!(cache.contains("src/main.rs"));
+
+        // Saved again without changes, it's newer than the build but the same
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        fs::write(workspace.path().join("svc/src/main.rs"), "fn main() {}\n").unwrap();
+        build_changed(&workspace, &[])
+            .success()
+            .stdout(predicate::str::contains("Everything is up to date"));
+
+        fs::write(workspace.path().join("svc/src/main.rs"), "fn main() { }\n").unwrap();
+        build_changed(&workspace, &[])
+            .success()
+            .stdout(predicate::str::contains("test.svc (src/main.rs modified)"));
+        build_changed(&workspace, &["--all"])
+            .success()
+            .stdout(predicate::str::contains("test.svc (--all)"));
+    }
+
+    #[test]
+    fn test_build_changed_since_ref() {
+        let workspace = create_workspace();
+        let repo = workspace.path().join("svc");
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(&repo)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+
+        build_changed(&workspace, &["--since", "HEAD"])
+            .success()
+            .stdout(predicate::str::contains("Everything is up to date"));
+
+        fs::write(repo.join("src/lib.rs"), "").unwrap();
+        build_changed(&workspace, &["--since", "HEAD"])
+            .success()
+            .stdout(predicate::str::contains("test.svc (1 file changed since HEAD)"));
+
+        build_changed(&workspace, &["--since", "no-such-ref"])
+            .failure()
+            .stderr(predicate::str::contains("Failed to diff test.svc against no-such-ref"));
+    }
+}
+
+mod build_cache_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_shared_target_dir() {
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+language = "rust"
+"#);
+        let config_dir = workspace.path().join(".platform/config");
+        fs::write(config_dir.join("workspace.toml"), "[build]\ntarget_dir = \"target\"\n").unwrap();
+        let repo = workspace.path().join("api");
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::write(
+            repo.join("Cargo.toml"),
+            "[package]\nname = \"api\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        fs::write(repo.join("src/main.rs"), "fn main() {}\n").unwrap();
+
+        // A binary in the repository's own target dir doesn't count
+        fs::create_dir_all(repo.join("target/release")).unwrap();
+        fs::write(repo.join("target/release/api"), "").unwrap();
+        let mut cmd = common::syla();
+        cmd.args(["dev", "build-changed", "--dry-run", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.api (no release build)"));
+
+        fs::create_dir_all(workspace.path().join("target/release")).unwrap();
+        fs::write(workspace.path().join("target/release/api"), "").unwrap();
+        let mut cmd = common::syla();
+        cmd.args(["dev", "build-changed", "--dry-run", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Everything is up to date"));
+        let mut cmd = common::syla();
+        cmd.args(["dev", "validate", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .stdout(predicate::str::contains("[OK] test.api built"));
+    }
+}
+
+mod build_plan_tests {
+    use super::*;
+    use std::fs;
+
+    fn write_crate(dir: &std::path::Path, name: &str, dependencies: &str) {
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n{}\n", name, dependencies),
+        )
+        .unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+    }
+
+    /// `test.app` uses a crate from `test.core` through a path dependency
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.app"]
+url = "https://github.com/test/app.git"
+path = "app"
+language = "rust"
+
+[repositories."test.core"]
+url = "https://github.com/test/core.git"
+path = "core"
+language = "rust"
+
+[repositories."test.other"]
+url = "https://github.com/test/other.git"
+path = "other"
+language = "rust"
+"#);
+        write_crate(&workspace.path().join("core"), "core-lib", "");
+        write_crate(&workspace.path().join("app"), "app", "core-lib = { path = \"../core\" }");
+        write_crate(&workspace.path().join("other"), "other", "");
+        workspace
+    }
+
+    #[test]
+    fn test_dependencies_build_first() {
+        let workspace = create_workspace();
+        let output = common::syla()
+            .args(["dev", "build-changed", "--all", "--dry-run", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        let core = output.find("test.core").unwrap();
+        let app = output.find("test.app").unwrap();
+        assert!(core < app, "{}", output);
+        assert!(output.contains("after test.core"), "{}", output);
+        assert!(!output.lines().any(|line| line.contains("test.other") && line.contains("after")), "{}", output);
+    }
+
+    #[test]
+    fn test_builds_side_by_side_with_timings() {
+        let workspace = create_workspace();
+        common::syla()
+            .args(["dev", "build-changed", "--all", "--profile", "debug", "--jobs", "2", "--workspace"])
+            .arg(workspace.path())
+            .env("CARGO_TARGET_DIR", workspace.path().join("target"))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("wall clock"))
+            .stdout(predicate::str::is_match(r"test\.app\s+.*built").unwrap());
+        assert!(workspace.path().join(".logs/build-test.app.log").exists());
+    }
+
+    #[test]
+    fn test_dependents_of_a_tagged_selection_are_not_added_outside_it() {
+        let workspace = create_workspace();
+        let manifest = workspace.path().join(".platform/config/repos.toml");
+        let content = fs::read_to_string(&manifest).unwrap().replace(
+            "path = \"core\"\nlanguage = \"rust\"",
+            "path = \"core\"\nlanguage = \"rust\"\ntags = [\"core\"]",
+        );
+        fs::write(&manifest, content).unwrap();
+        common::syla()
+            .args(["dev", "build-changed", "--all", "--dry-run", "--tags", "core", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.core"))
+            .stdout(predicate::str::contains("test.app").not());
+    }
+}
+
+mod profile_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_validate_uses_manifest_build_profile() {
+        let workspace = common::workspace(r#"
+build_profile = "debug"
+
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+language = "rust"
+"#);
+        fs::create_dir_all(workspace.path().join("api/target/debug")).unwrap();
+        fs::write(workspace.path().join("api/target/debug/api"), "").unwrap();
+
+        let mut cmd = common::syla();
+        cmd.args(["dev", "validate", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .stdout(predicate::str::contains("[OK] test.api built"));
+    }
+
+    #[test]
+    fn test_dev_up_rejects_unknown_profile() {
+        let mut cmd = common::syla();
+        cmd.args(["dev", "up", "--profile", "fast"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("possible values: debug, release"));
+    }
+}
+
+mod drift_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_dev_diff_and_apply() {
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+run = "sleep 60"
+ports = ["3200"]
+env = { MODE = "dev" }
+"#);
+        fs::create_dir_all(workspace.path().join("api")).unwrap();
+
+        // Services an earlier `dev up` left behind
+        let mut api = Command::new("sleep").arg("60").spawn().unwrap();
+        let mut old = Command::new("sleep").arg("60").spawn().unwrap();
+        let state_dir = workspace.path().join(".platform/state");
+        fs::create_dir_all(&state_dir).unwrap();
+        fs::write(
+            state_dir.join("services.toml"),
+            format!(
+                r#"
+["test.api"]
+pid = {}
+command = "sh"
+args = ["-c", "sleep 60"]
+ports = ["3200"]
+env = {{ MODE = "old", PORT = "3200", RUST_LOG = "info" }}
+started_at = "2026-01-01T00:00:00Z"
+
+["test.old"]
+pid = {}
+command = "sleep"
+args = ["60"]
+started_at = "2026-01-01T00:00:00Z"
+"#,
+                api.id(),
+                old.id()
+            ),
+        )
+        .unwrap();
+
+        let mut cmd = common::syla();
+        cmd.args(["dev", "diff", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.api has MODE=old, manifest gives dev"))
+            .stdout(predicate::str::contains(format!("test.old is running (pid {}) but not in the manifest", old.id())))
+            .stdout(predicate::str::contains("--apply"));
+
+        let mut cmd = common::syla();
+        cmd.args(["dev", "diff", "--apply", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Stopped test.old"))
+            .stdout(predicate::str::contains("test.api restarted"));
+        assert!(!old.wait().unwrap().success());
+        assert!(!api.wait().unwrap().success());
+    }
+}
+
+mod service_doctor_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_dev_doctor_reports_problems() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+run = "sleep 60"
+ports = ["{}"]
+depends_on = ["infrastructure.redis"]
+env = {{ TOKEN = "secret:missing-token" }}
+
+[infrastructure.redis]
+type = "docker"
+ports = ["{}:6379"]
+"#,
+            port, closed
+        ));
+        fs::create_dir_all(workspace.path().join("api")).unwrap();
+        fs::create_dir_all(workspace.path().join(".logs")).unwrap();
+        fs::write(workspace.path().join(".logs/test.api.log"), "listening\npanicked at 'boom'\n").unwrap();
+
+        let mut cmd = common::syla();
+        cmd.env("SYLA_HOME", workspace.path().join("home"))
+            .args(["dev", "doctor", "api", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Diagnosing test.api"))
+            .stdout(predicate::str::contains("Process: [X] (not running)"))
+            .stdout(predicate::str::contains(format!("Port {}: [X] (in use by another process)", port)))
+            .stdout(predicate::str::contains("Environment: [X]"))
+            .stdout(predicate::str::contains("Dependency infrastructure.redis: [X] (unreachable)"))
+            .stdout(predicate::str::contains("panicked at 'boom'"))
+            .stdout(predicate::str::contains("test.api has 4 problems"));
+        drop(taken);
+    }
+}
+
+mod dev_up_steps_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_dev_up_shows_steps_and_summary() {
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+language = "rust"
+ports = ["8100"]
+"#);
+        fs::create_dir_all(workspace.path().join("api")).unwrap();
+
+        let mut cmd = common::syla();
+        cmd.env("NO_COLOR", "1")
+            .args(["dev", "up", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[1/4] Infrastructure"))
+            .stdout(predicate::str::contains("[3/4] Services"))
+            .stdout(predicate::str::contains("[4/4] Health"))
+            .stdout(predicate::str::contains("[!] test.api skipped: no release build"))
+            .stdout(predicate::str::contains(".env is not gitignored"))
+            .stdout(predicate::str::is_match(r"test\.api\s+\S\s+Skipped\s+\S\s+no release build").unwrap());
+    }
+}
+
+mod workspace_lock_tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.tool"]
+url = "https://github.com/test/tool.git"
+path = "tool"
+build = "echo built > out.txt"
+"#);
+        fs::create_dir_all(workspace.path().join("tool")).unwrap();
+        fs::create_dir_all(workspace.path().join(".platform/state")).unwrap();
+        workspace
+    }
+
+    fn write_lock(workspace: &TempDir, pid: u32) {
+        fs::write(
+            workspace.path().join(".platform/state/workspace.lock"),
+            format!("pid = {}\ncommand = \"dev up\"\nsince = \"2026-01-02T03:04:05Z\"\n", pid),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_held_lock_names_the_holder() {
+        let workspace = create_workspace();
+        let mut holder = Command::new("sleep").arg("30").spawn().unwrap();
+        write_lock(&workspace, holder.id());
+
+        let mut cmd = common::syla();
+        cmd.args(["dev", "build-changed", "--all", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "locked by `syla dev up` (held by pid {} since",
+                holder.id()
+            )))
+            .stderr(predicate::str::contains("--wait"));
+        assert!(!workspace.path().join("tool/out.txt").exists());
+
+        // Read-only commands don't take the lock
+        let mut cmd = common::syla();
+        cmd.args(["dev", "build-changed", "--all", "--dry-run", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success();
+        holder.kill().unwrap();
+        holder.wait().unwrap();
+    }
+
+    #[test]
+    fn test_wait_runs_once_the_lock_is_released() {
+        let workspace = create_workspace();
+        let mut holder = Command::new("sleep").arg("2").spawn().unwrap();
+        write_lock(&workspace, holder.id());
+
+        let lock = workspace.path().join(".platform/state/workspace.lock");
+        let releaser = std::thread::spawn(move || {
+            holder.wait().unwrap();
+            let _ = fs::remove_file(lock);
+        });
+        let mut cmd = common::syla();
+        cmd.args(["dev", "build-changed", "--all", "--wait", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("waiting"));
+        releaser.join().unwrap();
+        assert!(workspace.path().join("tool/out.txt").exists());
+        assert!(!workspace.path().join(".platform/state/workspace.lock").exists());
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let workspace = create_workspace();
+        let mut dead = Command::new("true").spawn().unwrap();
+        dead.wait().unwrap();
+        write_lock(&workspace, dead.id());
+
+        let mut cmd = common::syla();
+        cmd.args(["dev", "build-changed", "--all", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success();
+        assert!(workspace.path().join("tool/out.txt").exists());
+        assert!(!workspace.path().join(".platform/state/workspace.lock").exists());
+    }
+}
+
+mod reloader_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_dev_watch_runs_watch_command() {
+        let workspace = common::workspace(r#"
+[repositories."test.web"]
+url = "https://github.com/test/web.git"
+path = "web"
+language = "node"
+ports = ["3300"]
+watch_command = "echo \"$NODE_ENV $PORT\" > reloaded"
+"#);
+        let web = workspace.path().join("web");
+        fs::create_dir_all(&web).unwrap();
+
+        let mut watch = Command::new(env!("CARGO_BIN_EXE_syla"))
+            .args(["dev", "watch", "--workspace"])
+            .arg(workspace.path())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let reloaded = web.join("reloaded");
+        for _ in 0..100 {
+            if fs::read_to_string(&reloaded).is_ok_and(|content| content.ends_with('\n')) {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        watch.kill().unwrap();
+        watch.wait().unwrap();
+        assert_eq!(fs::read_to_string(&reloaded).unwrap().trim(), "development 3300");
+    }
+}
+
+mod watch_config_tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+
+    fn wait_for(mut done: impl FnMut() -> bool) -> bool {
+        for _ in 0..100 {
+            if done() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        false
+    }
+
+    #[test]
+    fn test_watch_respects_paths_ignores_and_debounce() {
+        let workspace = common::workspace(r#"
+[repositories."test.tool"]
+url = "https://github.com/test/tool.git"
+path = "tool"
+language = "shell"
+build = "echo built >> builds.txt"
+watch = { paths = ["src"], ignore = ["*.gen", "cache/"], debounce_ms = 300, on_change = "build" }
+"#);
+        let tool = workspace.path().join("tool");
+        fs::create_dir_all(tool.join("src/cache")).unwrap();
+        fs::write(tool.join("src/main.sh"), "echo hi\n").unwrap();
+        fs::write(tool.join("notes.txt"), "").unwrap();
+
+        let mut watch = Command::new(env!("CARGO_BIN_EXE_syla"))
+            .args(["dev", "watch", "--workspace"])
+            .arg(workspace.path())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let builds = tool.join("builds.txt");
+        let count = || fs::read_to_string(&builds).map(|content| content.lines().count()).unwrap_or(0);
+        std::thread::sleep(Duration::from_millis(500));
+
+        // Outside the watched paths, or ignored
+        fs::write(tool.join("notes.txt"), "later\n").unwrap();
+        fs::write(tool.join("src/out.gen"), "generated\n").unwrap();
+        fs::write(tool.join("src/cache/entry"), "cached\n").unwrap();
+        std::thread::sleep(Duration::from_millis(1000));
+        let ignored = count();
+
+        // A burst of saves builds once
+        for i in 0..3 {
+            fs::write(tool.join("src/main.sh"), format!("echo {}\n", i)).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let built = wait_for(|| count() == 1);
+        std::thread::sleep(Duration::from_millis(800));
+        let total = count();
+        watch.kill().unwrap();
+        watch.wait().unwrap();
+
+        assert_eq!(ignored, 0);
+        assert!(built);
+        assert_eq!(total, 1);
+    }
+
+    #[test]
+    fn test_watch_rebuilds_only_the_service_whose_sources_changed() {
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+language = "shell"
+build = "echo built >> builds.txt"
+
+[repositories."test.worker"]
+url = "https://github.com/test/worker.git"
+path = "worker"
+language = "shell"
+build = "echo built >> builds.txt"
+"#);
+        for repo in ["api", "worker"] {
+            fs::create_dir_all(workspace.path().join(repo).join("src")).unwrap();
+            fs::write(workspace.path().join(repo).join("src/main.sh"), "echo hi\n").unwrap();
+        }
+
+        let mut watch = Command::new(env!("CARGO_BIN_EXE_syla"))
+            .args(["dev", "watch", "--workspace"])
+            .arg(workspace.path())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let count = |repo: &str| {
+            fs::read_to_string(workspace.path().join(repo).join("builds.txt"))
+                .map(|content| content.lines().count())
+                .unwrap_or(0)
+        };
+        std::thread::sleep(Duration::from_millis(500));
+
+        // Only `src/` is watched without a `watch` table
+        fs::write(workspace.path().join("api/README.md"), "docs\n").unwrap();
+        std::thread::sleep(Duration::from_millis(1000));
+        let outside = count("api");
+
+        fs::write(workspace.path().join("api/src/main.sh"), "echo changed\n").unwrap();
+        let built = wait_for(|| count("api") == 1);
+        std::thread::sleep(Duration::from_millis(800));
+        watch.kill().unwrap();
+        watch.wait().unwrap();
+
+        assert_eq!(outside, 0);
+        assert!(built);
+        assert_eq!(count("worker"), 0);
+    }
+}
+
+mod watch_test_tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn test_watch_test_only_runs_tests_without_building() {
+        let workspace = common::workspace(r#"
+[repositories."test.tool"]
+url = "https://github.com/test/tool.git"
+path = "tool"
+language = "shell"
+build = "echo built >> builds.txt"
+test = "echo ran >> tests.txt; echo 'test result: FAILED'; exit 1"
+watch = { paths = ["src"], debounce_ms = 200 }
+"#);
+        let tool = workspace.path().join("tool");
+        fs::create_dir_all(tool.join("src")).unwrap();
+        fs::write(tool.join("src/main.sh"), "echo hi\n").unwrap();
+
+        let mut watch = Command::new(env!("CARGO_BIN_EXE_syla"))
+            .args(["dev", "watch", "--test-only", "--workspace"])
+            .arg(workspace.path())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(500));
+        fs::write(tool.join("src/main.sh"), "echo changed\n").unwrap();
+
+        let tests = tool.join("tests.txt");
+        let mut ran = false;
+        for _ in 0..60 {
+            if tests.exists() {
+                ran = true;
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        std::thread::sleep(Duration::from_millis(300));
+        watch.kill().unwrap();
+        let output = watch.wait_with_output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(ran);
+        assert!(!tool.join("builds.txt").exists());
+        assert!(stdout.contains("test result: FAILED"));
+        assert!(stdout.contains("test.tool tests failed"));
+    }
+}
+
+mod health_monitor_tests {
+    use std::collections::VecDeque;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use syla::services::health_monitor::{HealthCheck, HealthMonitor, HealthStatus};
+
+    /// Answers with the queued status codes in turn, then 200
+    fn serve(statuses: Arc<Mutex<VecDeque<u16>>>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let status = statuses.lock().unwrap().pop_front().unwrap_or(200);
+                let response = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn test_health_monitor_retries_and_thresholds() {
+        let statuses = Arc::new(Mutex::new(VecDeque::new()));
+        let port = serve(statuses.clone());
+        let mut check = HealthCheck::new(format!("http://127.0.0.1:{}/health", port), Duration::from_secs(1));
+        check.retries = 1;
+        check.failure_threshold = 2;
+        check.success_threshold = 2;
+        let mut monitor = HealthMonitor::new();
+        monitor.add_check("api".to_string(), check);
+        let mut respond = |codes: &[u16]| {
+            statuses.lock().unwrap().extend(codes);
+            monitor.perform_check("api").unwrap()
+        };
+
+        // A failed attempt the retry gets past doesn't count
+        assert_eq!(respond(&[500, 200]), HealthStatus::Healthy);
+        // One failed check is a blip; the second in a row flips the status
+        assert_eq!(respond(&[500, 500]), HealthStatus::Healthy);
+        assert!(matches!(respond(&[503, 503]), HealthStatus::Unhealthy(reason) if reason.contains("503")));
+        // Recovering takes two passing checks
+        assert!(matches!(respond(&[200]), HealthStatus::Unhealthy(_)));
+        assert_eq!(respond(&[200]), HealthStatus::Healthy);
+        assert!(statuses.lock().unwrap().is_empty());
+    }
+}
+
+#[cfg(unix)]
+mod control_tests {
+    use super::*;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    fn request(stream: &mut UnixStream, line: &str) -> serde_json::Value {
+        writeln!(stream, "{}", line).unwrap();
+        let mut response = String::new();
+        BufReader::new(stream.try_clone().unwrap()).read_line(&mut response).unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[test]
+    fn test_supervisor_takes_requests_on_its_socket() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.echo"]
+url = "https://github.com/test/echo.git"
+path = "echo"
+language = "shell"
+run = "echo started-$PORT; sleep 60"
+ports = ["{}"]
+"#,
+            port
+        ));
+        fs::create_dir_all(workspace.path().join("echo")).unwrap();
+
+        let mut supervisor = Command::new(env!("CARGO_BIN_EXE_syla"))
+            .args(["dev", "supervise", "--workspace"])
+            .arg(workspace.path())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let socket = workspace.path().join(".platform/state/control.sock");
+        let mut stream = (0..100)
+            .find_map(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                UnixStream::connect(&socket).ok()
+            })
+            .expect("supervisor never listened");
+
+        let list = request(&mut stream, r#"{"command": "list"}"#);
+        assert_eq!(list["ok"], true);
+        assert_eq!(list["result"][0]["name"], "test.echo");
+        assert_eq!(list["result"][0]["state"], "stopped");
+
+        let started = request(&mut stream, r#"{"command": "start", "service": "echo"}"#);
+        assert_eq!(started["ok"], true, "{}", started);
+        assert!(started["result"]["pid"].is_u64());
+        let list = request(&mut stream, r#"{"command": "list"}"#);
+        assert_eq!(list["result"][0]["state"], "running");
+        assert_eq!(list["result"][0]["managed"], true);
+
+        let again = request(&mut stream, r#"{"command": "start", "service": "echo"}"#);
+        assert_eq!(again["ok"], false);
+        assert!(again["error"].as_str().unwrap().contains("already running"), "{}", again);
+
+        let expected = format!("started-{}", port);
+        let logged = (0..50).any(|_| {
+            let logs = request(&mut stream, r#"{"command": "logs", "service": "echo", "lines": 5}"#);
+            if logs["result"]["lines"].as_array().unwrap().iter().any(|line| line == &expected) {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            false
+        });
+        assert!(logged, "service output never reached its log");
+
+        let invalid = request(&mut stream, r#"{"command": "explode"}"#);
+        assert_eq!(invalid["ok"], false);
+        assert!(invalid["error"].as_str().unwrap().starts_with("Invalid request"), "{}", invalid);
+
+        let stopped = request(&mut stream, r#"{"command": "stop", "service": "echo"}"#);
+        assert_eq!(stopped["ok"], true, "{}", stopped);
+        let list = request(&mut stream, r#"{"command": "list"}"#);
+        assert_eq!(list["result"][0]["state"], "stopped");
+
+        let cli = request(&mut stream, r#"{"command": "stop", "service": "echo", "caller": "cli"}"#);
+        assert_eq!(cli["ok"], true, "{}", cli);
+
+        Command::new("kill").args(["-INT", &supervisor.id().to_string()]).status().unwrap();
+        assert!(supervisor.wait().unwrap().success());
+        assert!(!socket.exists());
+
+        let output = common::syla()
+            .args(["history", "-o", "json", "--workspace"])
+            .arg(workspace.path())
+            .output()
+            .unwrap();
+        let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let actions: Vec<_> = entries
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| (e["command"].as_str().unwrap(), e["success"].as_bool().unwrap()))
+            .collect();
+        assert_eq!(actions, [("control start", true), ("control start", false), ("control stop", true)]);
+    }
+}
+
+#[cfg(unix)]
+mod daemon_tests {
+    use super::*;
+    use std::fs;
+
+    fn syla(workspace: &TempDir, args: &[&str]) -> assert_cmd::assert::Assert {
+        let mut cmd = common::syla();
+        cmd.args(args).arg("--workspace").arg(workspace.path()).assert()
+    }
+
+    fn daemon_status(workspace: &TempDir) -> serde_json::Value {
+        let output = syla(workspace, &["daemon", "status", "--output", "json"]).success().get_output().stdout.clone();
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[test]
+    fn test_detached_services_run_under_the_daemon() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.echo"]
+url = "https://github.com/test/echo.git"
+path = "echo"
+language = "shell"
+run = "echo started-$PORT; sleep 60"
+ports = ["{}"]
+"#,
+            port
+        ));
+        fs::create_dir_all(workspace.path().join("echo")).unwrap();
+
+        assert_eq!(daemon_status(&workspace)["running"], false);
+        syla(&workspace, &["dev", "up", "--detach"]).success().stdout(predicate::str::contains("Started the daemon"));
+
+        // `dev up` has exited, but the service keeps running under the daemon
+        let status = daemon_status(&workspace);
+        assert_eq!(status["running"], true, "{}", status);
+        let daemon_pid = status["pid"].as_u64().unwrap();
+        assert_eq!(status["services"][0]["name"], "test.echo");
+        assert_eq!(status["services"][0]["state"], "running");
+        let pid = status["services"][0]["pid"].as_u64().unwrap();
+
+        syla(&workspace, &["daemon", "start"]).success().stdout(predicate::str::contains("already running"));
+        syla(&workspace, &["dev", "restart", "echo"]).success().stdout(predicate::str::contains("restarted by the daemon"));
+        let status = daemon_status(&workspace);
+        assert_ne!(status["services"][0]["pid"].as_u64().unwrap(), pid);
+
+        syla(&workspace, &["dev", "down"]).success();
+        assert_eq!(daemon_status(&workspace)["services"][0]["state"], "stopped");
+
+        syla(&workspace, &["daemon", "stop"]).success().stdout(predicate::str::contains("Daemon stopped"));
+        assert_eq!(daemon_status(&workspace)["running"], false);
+        let alive = Command::new("kill").args(["-0", &daemon_pid.to_string()]).stderr(std::process::Stdio::null()).status().unwrap();
+        assert!(!alive.success());
+    }
+}
+
+#[cfg(unix)]
+mod log_sink_tests {
+    use super::*;
+    use std::fs;
+    use std::net::UdpSocket;
+    use std::os::unix::net::UnixDatagram;
+    use std::time::Duration;
+
+    #[test]
+    fn test_dev_logs_forwards_to_sinks() {
+        let workspace = common::workspace(r#"
+[repositories."test.echo"]
+url = "https://github.com/test/echo.git"
+path = "echo"
+language = "shell"
+run = "echo started"
+ports = ["8080"]
+"#);
+        let config_dir = workspace.path().join(".platform/config");
+
+        let syslog = UdpSocket::bind("127.0.0.1:0").unwrap();
+        syslog.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let journal_path = workspace.path().join("journal.sock");
+        let journal = UnixDatagram::bind(&journal_path).unwrap();
+        journal.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        fs::write(
+            config_dir.join("workspace.toml"),
+            format!(
+                r#"
+[logs]
+sinks = [
+    {{ type = "file", path = ".logs/all.ndjson" }},
+    {{ type = "syslog", address = "{}" }},
+    {{ type = "journald", socket = "{}" }},
+]
+"#,
+                syslog.local_addr().unwrap(),
+                journal_path.display()
+            ),
+        )
+        .unwrap();
+        fs::create_dir_all(workspace.path().join(".logs")).unwrap();
+        fs::write(
+            workspace.path().join(".logs/test.echo.log"),
+            "INFO booting\n\
+             {\"level\": \"error\", \"message\": \"disk full\", \"request_id\": \"abc\"}\n",
+        )
+        .unwrap();
+
+        common::syla()
+            .args(["dev", "logs", "echo", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("disk full"));
+
+        let file = fs::read_to_string(workspace.path().join(".logs/all.ndjson")).unwrap();
+        let entries: Vec<serde_json::Value> = file.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["service"], "test.echo");
+        assert_eq!(entries[1]["level"], "Error");
+        assert_eq!(entries[1]["fields"]["request_id"], "abc");
+
+        let mut buf = [0u8; 2048];
+        let n = syslog.recv(&mut buf).unwrap();
+        let message = String::from_utf8_lossy(&buf[..n]);
+        assert!(message.starts_with("<14>1 "), "{}", message);
+        assert!(message.ends_with(" test.echo - - - INFO booting"), "{}", message);
+        let n = syslog.recv(&mut buf).unwrap();
+        assert!(String::from_utf8_lossy(&buf[..n]).starts_with("<11>1 "));
+
+        let n = journal.recv(&mut buf).unwrap();
+        let fields = String::from_utf8_lossy(&buf[..n]).to_string();
+        assert!(fields.contains("MESSAGE=INFO booting\n"), "{}", fields);
+        assert!(fields.contains("PRIORITY=6\n"));
+        let n = journal.recv(&mut buf).unwrap();
+        let fields = String::from_utf8_lossy(&buf[..n]).to_string();
+        assert!(fields.contains("PRIORITY=3\n"));
+        assert!(fields.contains("REQUEST_ID=abc\n"), "{}", fields);
+    }
+
+    #[test]
+    fn test_dev_logs_grep_with_context() {
+        let workspace = common::workspace(r#"
+[repositories."test.echo"]
+url = "https://github.com/test/echo.git"
+path = "echo"
+language = "shell"
+ports = ["8080"]
+"#);
+        fs::create_dir_all(workspace.path().join(".logs")).unwrap();
+        fs::write(
+            workspace.path().join(".logs/test.echo.log"),
+            "INFO one\nINFO two\nERROR boom at three\nINFO four\nINFO five\nINFO six\nINFO seven\nERROR boom at eight\nINFO nine\n",
+        )
+        .unwrap();
+
+        let output = common::syla()
+            .args(["dev", "logs", "echo", "--grep", "bo+m", "-C", "1", "--workspace"])
+            .arg(workspace.path())
+            .env("CLICOLOR_FORCE", "1")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        for shown in ["two", "three", "four", "seven", "eight", "nine"] {
+            assert!(output.contains(shown), "{}", output);
+        }
+        for hidden in ["one", "five", "six"] {
+            assert!(!output.contains(hidden), "{}", output);
+        }
+        assert_eq!(output.matches("--").count(), 1, "{}", output);
+        assert!(output.contains("\u{1b}[1;31mboom\u{1b}[0m"), "{}", output);
+
+        // -n counts matches, with their context
+        let output = common::syla()
+            .args(["dev", "logs", "echo", "--grep", "boom", "-C", "1", "-n", "1", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("seven") && output.contains("nine"), "{}", output);
+        assert!(!output.contains("three"), "{}", output);
+    }
+
+    #[test]
+    fn test_dev_logs_format_and_level() {
+        let workspace = common::workspace(r#"
+[repositories."test.echo"]
+url = "https://github.com/test/echo.git"
+path = "echo"
+language = "shell"
+ports = ["8080"]
+"#);
+        fs::create_dir_all(workspace.path().join(".logs")).unwrap();
+        fs::write(
+            workspace.path().join(".logs/test.echo.log"),
+            "DEBUG cache miss\nINFO booting\nWARNING slow disk\nERROR disk full\n",
+        )
+        .unwrap();
+
+        let output = common::syla()
+            .args(["dev", "logs", "echo", "--format", "json", "--level", "warn", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let entries: Vec<serde_json::Value> =
+            String::from_utf8(output).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let levels: Vec<&str> = entries.iter().map(|entry| entry["level"].as_str().unwrap()).collect();
+        assert_eq!(levels, ["Warn", "Error"]);
+
+        common::syla()
+            .args(["dev", "logs", "echo", "--format", "raw", "-n", "2", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout("WARNING slow disk\nERROR disk full\n");
+    }
+
+    #[test]
+    fn test_dev_logs_follow_shows_last_lines_first() {
+        let workspace = common::workspace(r#"
+[repositories."test.echo"]
+url = "https://github.com/test/echo.git"
+path = "echo"
+language = "shell"
+ports = ["8080"]
+"#);
+        let log = workspace.path().join(".logs/test.echo.log");
+        fs::create_dir_all(log.parent().unwrap()).unwrap();
+        fs::write(&log, "INFO one\nINFO two\nINFO three\n").unwrap();
+
+        let mut follow = Command::new(env!("CARGO_BIN_EXE_syla"))
+            .args(["dev", "logs", "echo", "--follow", "-n", "2", "--format", "raw", "--workspace"])
+            .arg(workspace.path())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut lines = std::io::BufRead::lines(std::io::BufReader::new(follow.stdout.take().unwrap()));
+        assert_eq!(lines.next().unwrap().unwrap(), "INFO two");
+        assert_eq!(lines.next().unwrap().unwrap(), "INFO three");
+
+        let mut file = fs::OpenOptions::new().append(true).open(&log).unwrap();
+        std::io::Write::write_all(&mut file, b"INFO four\n").unwrap();
+        assert_eq!(lines.next().unwrap().unwrap(), "INFO four");
+        follow.kill().unwrap();
+        follow.wait().unwrap();
+    }
+
+    #[test]
+    fn test_dev_logs_without_a_log() {
+        let workspace = common::workspace(r#"
+[repositories."test.echo"]
+url = "https://github.com/test/echo.git"
+path = "echo"
+language = "shell"
+ports = ["8080"]
+"#);
+
+        common::syla()
+            .args(["dev", "logs", "echo", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("has no log yet"));
+    }
+}
+
+mod events_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.ok"]
+url = "https://github.com/test/ok.git"
+path = "ok"
+build = "true"
+run = "true"
+
+[repositories."test.broken"]
+url = "https://github.com/test/broken.git"
+path = "broken"
+build = "false"
+run = "true"
+"#);
+        fs::create_dir_all(workspace.path().join("ok")).unwrap();
+        fs::create_dir_all(workspace.path().join("broken")).unwrap();
+        workspace
+    }
+
+    fn read_events(path: &std::path::Path) -> Vec<serde_json::Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_events_written_as_ndjson() {
+        let workspace = create_workspace();
+        let events = workspace.path().join("events.ndjson");
+        common::syla()
+            .args(["dev", "build-changed", "--all", "--events"])
+            .arg(&events)
+            .arg("--workspace")
+            .arg(workspace.path())
+            .assert()
+            .failure();
+
+        let events = read_events(&events);
+        assert!(events.iter().all(|e| e["timestamp"].is_string()), "{:?}", events);
+        let finished = |service: &str| {
+            events
+                .iter()
+                .find(|e| e["event"] == "build_finished" && e["service"] == service)
+                .unwrap_or_else(|| panic!("no build_finished for {} in {:?}", service, events))
+                .clone()
+        };
+        assert!(events.iter().any(|e| e["event"] == "build_started" && e["service"] == "test.ok"));
+        assert_eq!(finished("test.ok")["success"], true);
+        assert_eq!(finished("test.broken")["success"], false);
+        assert!(finished("test.broken")["duration_ms"].is_u64());
+    }
+
+    #[test]
+    fn test_events_appended_and_off_by_default() {
+        let workspace = create_workspace();
+        let events = workspace.path().join("events.ndjson");
+        fs::write(&events, "").unwrap();
+        for _ in 0..2 {
+            common::syla()
+                .env("SYLA_EVENTS", &events)
+                .args(["dev", "build-changed", "--all", "--workspace"])
+                .arg(workspace.path())
+                .assert()
+                .failure();
+        }
+        let started = read_events(&events).iter().filter(|e| e["event"] == "build_started").count();
+        assert_eq!(started, 4);
+
+        common::syla()
+            .args(["dev", "build-changed", "--all", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure();
+        assert_eq!(read_events(&events).len(), 8);
+    }
+}
+
+mod tunnel_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_dev_expose_records_tunnel_until_dev_down() {
+        use std::os::unix::fs::PermissionsExt;
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+ports = ["8084:8080"]
+"#);
+
+        // A cloudflared that prints its banner and a quick tunnel URL, then stays up
+        let bin = workspace.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let script = bin.join("cloudflared");
+        fs::write(
+            &script,
+            "#!/bin/sh\n\
+             echo \"INF Terms https://www.cloudflare.com/website-terms/\" >&2\n\
+             echo \"INF |  https://quiet-fox-test.trycloudflare.com  |\" >&2\n\
+             echo \"$@\" > \"$(dirname \"$0\")/args\"\n\
+             exec sleep 60\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap_or_default());
+
+        let mut cmd = common::syla();
+        cmd.env("PATH", &path)
+            .args(["dev", "expose", "test.api", "--driver", "cloudflared", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("https://quiet-fox-test.trycloudflare.com"))
+            .stdout(predicate::str::contains("website-terms").not());
+        let args = fs::read_to_string(bin.join("args")).unwrap();
+        assert!(args.contains("--url http://localhost:8084"), "{}", args);
+        let state = workspace.path().join(".platform/state/tunnels.toml");
+        assert!(state.exists());
+
+        let mut cmd = common::syla();
+        cmd.args(["dev", "status", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .stdout(predicate::str::contains("test.api -> https://quiet-fox-test.trycloudflare.com"));
+
+        let mut cmd = common::syla();
+        cmd.args(["dev", "down", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Closed tunnel test.api"));
+        assert!(!state.exists());
+    }
+
+    #[test]
+    fn test_dev_expose_without_port_fails() {
+        let workspace = common::workspace(r#"
+[repositories."test.worker"]
+url = "https://github.com/test/worker.git"
+path = "worker"
+"#);
+
+        let mut cmd = common::syla();
+        cmd.args(["dev", "expose", "test.worker", "--driver", "ssh", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("no port to expose"));
+    }
+}
+
+mod uptime_report_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace(samples: &[(&str, i64, bool, u64)]) -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+"#);
+        let state_dir = workspace.path().join(".platform/state");
+        fs::create_dir_all(&state_dir).unwrap();
+        let now = chrono::Utc::now();
+        let log: String = samples
+            .iter()
+            .map(|(service, minutes_ago, healthy, latency_ms)| {
+                let sample = serde_json::json!({
+                    "timestamp": now - chrono::Duration::minutes(*minutes_ago),
+                    "service": service,
+                    "healthy": healthy,
+                    "latency_ms": latency_ms,
+                });
+                format!("{}\n", sample)
+            })
+            .collect();
+        fs::write(state_dir.join("health.log"), log + "not json\n").unwrap();
+        workspace
+    }
+
+    #[test]
+    fn test_dev_report_summarises_health_history() {
+        let workspace = create_workspace(&[
+            ("test.api", 3 * 24 * 60, false, 5),
+            ("test.api", 120, true, 10),
+            ("test.api", 90, false, 2000),
+            ("test.api", 89, false, 1500),
+            ("test.api", 88, true, 50),
+            ("test.api", 60, true, 30),
+            ("test.worker", 30, true, 20),
+            ("test.worker", 29, false, 40),
+        ]);
+
+        let output = common::syla()
+            .args(["dev", "report", "--since", "24h", "-o", "json", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let api = &report["services"][0];
+        assert_eq!(api["service"], "test.api");
+        assert_eq!(api["checks"], 5);
+        assert_eq!(api["uptime_percent"], 60.0);
+        assert_eq!(api["incidents"], 1);
+        assert_eq!(api["down"], false);
+        assert_eq!(api["mean_recovery_seconds"], 120);
+        assert_eq!(api["p95_latency_ms"], 2000);
+        assert_eq!(api["slowest"][0]["latency_ms"], 2000);
+        assert_eq!(api["slowest"][1]["latency_ms"], 1500);
+
+        let worker = &report["services"][1];
+        assert_eq!(worker["service"], "test.worker");
+        assert_eq!(worker["incidents"], 1);
+        assert_eq!(worker["down"], true);
+        assert!(worker.get("mean_recovery_seconds").is_none());
+
+        common::syla()
+            .args(["dev", "report", "--workspace"])
+            .arg(workspace.path())
+            .env("NO_COLOR", "1")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("60.00%"))
+            .stdout(predicate::str::contains("120s"))
+            .stdout(predicate::str::contains("(down)"));
+    }
+
+    #[test]
+    fn test_dev_report_rejects_invalid_window() {
+        let workspace = create_workspace(&[]);
+        common::syla()
+            .args(["dev", "report", "--since", "yesterday", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid duration 'yesterday'"));
+        common::syla()
+            .args(["dev", "report", "--since", "7d", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No health history"));
+    }
+}
+
+mod backup_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace(infrastructure: &str) -> TempDir {
+        common::workspace(&format!(
+            r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+{}"#,
+            infrastructure
+        ))
+    }
+
+    fn syla(workspace: &TempDir) -> TestCommand {
+        let mut cmd = common::syla();
+        cmd.env("DOCKER_HOST", format!("unix://{}", workspace.path().join("no-docker.sock").display()))
+            .arg("--workspace")
+            .arg(workspace.path());
+        cmd
+    }
+
+    const STORES: &str = r#"
+[infrastructure.postgres]
+type = "external"
+docker_image = "postgres:15"
+ports = ["5434:5432"]
+
+[infrastructure.redis]
+type = "external"
+docker_image = "redis:7-alpine"
+ports = ["6380:6379"]
+"#;
+
+    #[test]
+    fn test_backup_needs_a_data_store() {
+        let workspace = create_workspace("");
+        syla(&workspace)
+            .args(["dev", "backup", "before-demo"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No Postgres or Redis in [infrastructure]"));
+    }
+
+    #[test]
+    fn test_backup_needs_docker() {
+        let workspace = create_workspace(STORES);
+        syla(&workspace)
+            .args(["dev", "backup", "before-demo"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Docker is not available"));
+        assert!(!workspace.path().join(".platform/backups/before-demo").exists());
+    }
+
+    #[test]
+    fn test_restore_without_backups() {
+        let workspace = create_workspace(STORES);
+        syla(&workspace)
+            .args(["dev", "restore", "before-demo", "--yes"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No backups yet"));
+    }
+
+    #[test]
+    fn test_restore_suggests_backup_names() {
+        let workspace = create_workspace(STORES);
+        let backup = workspace.path().join(".platform/backups/before-demo");
+        fs::create_dir_all(&backup).unwrap();
+        fs::write(
+            backup.join("backup.toml"),
+            "created_at = \"2026-01-02T03:04:05Z\"\n\n[[stores]]\ninfrastructure = \"postgres\"\nkind = \"postgres\"\nfile = \"postgres.sql\"\n",
+        )
+        .unwrap();
+        syla(&workspace)
+            .args(["dev", "restore", "before-dmeo", "--yes"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Backup 'before-dmeo' not found. Did you mean before-demo?"));
+    }
+}
+
+mod db_tests {
+    use super::*;
+    use std::fs;
+    use std::net::TcpListener;
+
+    fn create_workspace(postgres_port: u16) -> TempDir {
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+depends_on = ["test.accounts"]
+
+[repositories."test.accounts"]
+url = "https://github.com/test/accounts.git"
+path = "accounts"
+migrations = {{ tool = "sql", dir = "db" }}
+
+[infrastructure.postgres]
+type = "external"
+docker_image = "postgres:15"
+ports = ["{}:5432"]
+environment = ["POSTGRES_USER=syla", "POSTGRES_PASSWORD=hunter2"]
+"#,
+            postgres_port
+        ));
+        fs::create_dir_all(workspace.path().join("api/migrations")).unwrap();
+        fs::write(workspace.path().join("api/migrations/0001_init.sql"), "SELECT 1;").unwrap();
+        fs::create_dir_all(workspace.path().join("accounts/db")).unwrap();
+        workspace
+    }
+
+    /// A port nothing listens on
+    fn closed_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    }
+
+    #[test]
+    fn test_db_status_unreachable() {
+        let workspace = create_workspace(closed_port());
+
+        let mut cmd = common::syla();
+        cmd.args(["db", "status", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.api"))
+            .stdout(predicate::str::contains("sqlx"))
+            .stdout(predicate::str::contains("Cannot connect to postgres://syla@localhost:"))
+            .stdout(predicate::str::contains("hunter2").not());
+    }
+
+    #[test]
+    fn test_db_migrate_unknown_service() {
+        let workspace = create_workspace(closed_port());
+
+        let mut cmd = common::syla();
+        cmd.args(["db", "migrate", "billing", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No migrations found for 'billing'"));
+    }
+}
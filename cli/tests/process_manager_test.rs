@@ -1,11 +1,10 @@
 #[cfg(test)]
 mod process_manager_tests {
     use syla::services::{ProcessManager, ProcessConfig};
-    use syla::services::process_manager::RestartPolicy;
+    use syla::services::process_manager::{Backend, ProcessState, RestartPolicy};
     use syla::config::Config;
     use std::time::Duration;
     use std::collections::HashMap;
-    use std::path::PathBuf;
     use std::fs;
     use tempfile::TempDir;
 
@@ -52,7 +51,9 @@ language = "rust"
             health_check_interval: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(30),
             restart_policy: RestartPolicy::Never,
+            max_restarts: 0,
             log_file: None,
+            backend: Backend::Process,
         };
         
         let result = pm.start_service(process_config);
@@ -96,7 +97,9 @@ language = "rust"
             health_check_interval: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(30),
             restart_policy: RestartPolicy::Never,
+            max_restarts: 0,
             log_file: None,
+            backend: Backend::Process,
         };
         
         let result = pm.start_service(process_config);
@@ -109,4 +112,101 @@ language = "rust"
         let stop_result = pm.stop_service("test-echo", false);
         assert!(stop_result.is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_container_service_without_docker() {
+        let (config, temp_dir) = create_test_config();
+        let pm = ProcessManager::new(config);
+        std::env::set_var("DOCKER_HOST", format!("unix://{}", temp_dir.path().join("docker.sock").display()));
+
+        let process_config = ProcessConfig {
+            name: "test-container".to_string(),
+            command: String::new(),
+            args: vec![],
+            working_dir: temp_dir.path().to_path_buf(),
+            env: HashMap::new(),
+            health_check_url: None,
+            health_check_interval: Duration::from_secs(10),
+            startup_timeout: Duration::from_secs(30),
+            restart_policy: RestartPolicy::Never,
+            max_restarts: 0,
+            log_file: None,
+            backend: Backend::Container { image: "alpine:3.20".to_string() },
+        };
+
+        // The container can't be created, and the service is left failed
+        assert!(pm.start_service(process_config).is_err());
+        assert!(matches!(pm.get_service_status("test-container"), Some((ProcessState::Failed(_), _))));
+        assert_eq!(pm.pid("test-container"), None);
+    }
+
+    fn shell_service(name: &str, script: &str, restart_policy: RestartPolicy, max_restarts: u32, dir: &TempDir) -> ProcessConfig {
+        ProcessConfig {
+            name: name.to_string(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            working_dir: dir.path().to_path_buf(),
+            env: HashMap::new(),
+            health_check_url: None,
+            health_check_interval: Duration::from_secs(10),
+            startup_timeout: Duration::from_secs(30),
+            restart_policy,
+            max_restarts,
+            log_file: None,
+            backend: Backend::Process,
+        }
+    }
+
+    fn wait_for_state(pm: &ProcessManager, name: &str, done: impl Fn(&ProcessState) -> bool) -> ProcessState {
+        let deadline = std::time::Instant::now() + Duration::from_secs(15);
+        loop {
+            let (state, _) = pm.get_service_status(name).unwrap();
+            if done(&state) || std::time::Instant::now() > deadline {
+                return state;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn test_failing_service_is_restarted_until_it_gives_up() {
+        let (config, temp_dir) = create_test_config();
+        let pm = ProcessManager::new(config);
+
+        pm.start_service(shell_service("crashes", "exit 3", RestartPolicy::OnFailure, 2, &temp_dir)).unwrap();
+
+        let state = wait_for_state(&pm, "crashes", |state| matches!(state, ProcessState::Failed(_)));
+        match state {
+            ProcessState::Failed(reason) => assert_eq!(reason, "gave up after 2 restarts: exited with code 3"),
+            other => panic!("expected the service to fail, got {:?}", other),
+        }
+        assert_eq!(pm.restart_count("crashes"), 2);
+    }
+
+    #[test]
+    fn test_clean_exit_is_not_restarted_on_failure_policy() {
+        let (config, temp_dir) = create_test_config();
+        let pm = ProcessManager::new(config);
+
+        pm.start_service(shell_service("finishes", "exit 0", RestartPolicy::OnFailure, 5, &temp_dir)).unwrap();
+
+        let state = wait_for_state(&pm, "finishes", |state| *state != ProcessState::Running);
+        assert_eq!(state, ProcessState::Stopped);
+        assert_eq!(pm.restart_count("finishes"), 0);
+    }
+
+    #[test]
+    fn test_stopping_during_backoff_cancels_the_restart() {
+        let (config, temp_dir) = create_test_config();
+        let pm = ProcessManager::new(config);
+
+        pm.start_service(shell_service("flaps", "exit 0", RestartPolicy::Always, 5, &temp_dir)).unwrap();
+        wait_for_state(&pm, "flaps", |state| *state == ProcessState::Restarting);
+        pm.stop_service("flaps", false).unwrap();
+
+        // Longer than the first backoff
+        std::thread::sleep(Duration::from_millis(1500));
+        assert_eq!(pm.get_service_status("flaps").unwrap().0, ProcessState::Stopped);
+        assert_eq!(pm.restart_count("flaps"), 0);
+    }
+}
@@ -0,0 +1,392 @@
+mod common;
+
+use std::process::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+mod release_tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    fn create_repo(dir: &Path, cargo_toml: &str, commits: &[&str]) {
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("Cargo.toml"), cargo_toml).unwrap();
+        fs::write(dir.join("src/lib.rs"), "").unwrap();
+        git(dir, &["init", "-q", "-b", "main"]);
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-q", "-m", "Initial commit"]);
+        for (i, subject) in commits.iter().enumerate() {
+            fs::write(dir.join("src/lib.rs"), format!("// {}\n", i)).unwrap();
+            git(dir, &["commit", "-q", "-am", subject]);
+        }
+    }
+
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.common"]
+url = "https://github.com/test/common.git"
+path = "common"
+language = "rust"
+
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+language = "rust"
+"#);
+        create_repo(
+            &workspace.path().join("common"),
+            "[package]\nname = \"syla-common\"\nversion = \"0.3.1\"\nedition = \"2021\"\n",
+            &["feat: add retry helper", "fix: handle empty ids", "Tidy docs"],
+        );
+        create_repo(
+            &workspace.path().join("api"),
+            "[package]\nname = \"api\"\nversion = \"1.0.0\"\nedition = \"2021\"\n\n[dependencies]\n# Shared types\nsyla-common = { git = \"https://github.com/test/common\", tag = \"v0.3.1\" }\n",
+            &[],
+        );
+        workspace
+    }
+
+    #[test]
+    fn test_release_dry_run() {
+        let workspace = create_workspace();
+        let mut cmd = common::syla();
+        cmd.args(["release", "minor", "test.common", "--dry-run", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.common 0.3.1 -> 0.4.0"))
+            .stdout(predicate::str::contains("test.api: syla-common 0.4.0"))
+            .stdout(predicate::str::contains("### Features\n\n- add retry helper"))
+            .stdout(predicate::str::contains("### Fixes\n\n- handle empty ids"))
+            .stdout(predicate::str::contains("### Other changes\n\n- Tidy docs\n- Initial commit"));
+        assert!(git(&workspace.path().join("common"), &["tag"]).is_empty());
+    }
+
+    #[test]
+    fn test_release_bumps_tags_and_updates_dependents() {
+        let workspace = create_workspace();
+        let mut cmd = common::syla();
+        cmd.args(["release", "minor", "test.common", "--workspace"])
+            .arg(workspace.path())
+            .env("GIT_AUTHOR_NAME", "test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Tagged test.common v0.4.0"));
+
+        let common = workspace.path().join("common");
+        assert!(fs::read_to_string(common.join("Cargo.toml")).unwrap().contains("version = \"0.4.0\""));
+        assert_eq!(git(&common, &["tag"]).trim(), "v0.4.0");
+        assert_eq!(git(&common, &["log", "-1", "--pretty=%s"]).trim(), "Release v0.4.0");
+
+        let api = workspace.path().join("api");
+        let manifest = fs::read_to_string(api.join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("# Shared types\nsyla-common = { git = \"https://github.com/test/common\", tag = \"v0.4.0\" }"));
+        assert_eq!(git(&api, &["log", "-1", "--pretty=%s"]).trim(), "Update syla-common 0.4.0");
+        assert!(git(&api, &["tag"]).is_empty());
+
+        // Only commits since the last release make the next notes
+        let mut cmd = common::syla();
+        cmd.args(["release", "patch", "test.common", "--dry-run", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No changes since the last release"));
+    }
+}
+
+mod audit_tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    const CARGO_AUDIT_REPORT: &str = r#"{
+  "vulnerabilities": {"found": true, "count": 2, "list": [
+    {"advisory": {"id": "RUSTSEC-2024-0001", "package": "hyper", "title": "Request smuggling",
+      "url": "https://rustsec.org/advisories/RUSTSEC-2024-0001",
+      "cvss": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"},
+     "versions": {"patched": [">=1.1.0"]}, "package": {"name": "hyper", "version": "1.0.0"}},
+    {"advisory": {"id": "RUSTSEC-2024-0002", "package": "h2", "title": "Resource exhaustion",
+      "cvss": "CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:N/A:H"},
+     "versions": {"patched": []}, "package": {"name": "h2", "version": "0.3.0"}}
+  ]},
+  "warnings": {"unmaintained": [
+    {"kind": "unmaintained", "package": {"name": "instant", "version": "0.1.12"},
+     "advisory": {"id": "RUSTSEC-2024-0003", "title": "instant is unmaintained", "cvss": null}}
+  ]}
+}"#;
+
+    const NPM_AUDIT_REPORT: &str = r#"{
+  "vulnerabilities": {
+    "lodash": {"name": "lodash", "severity": "high", "range": "<4.17.21", "fixAvailable": true,
+      "via": [{"source": 1106913, "title": "Command Injection in lodash", "severity": "high",
+        "url": "https://github.com/advisories/GHSA-35jh-r3h4-6jhm"}]}
+  }
+}"#;
+
+    /// Puts scripts that print canned reports first on PATH
+    fn fake_tools(dir: &Path) -> String {
+        use std::os::unix::fs::PermissionsExt;
+        let bin = dir.join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(dir.join("cargo-audit.json"), CARGO_AUDIT_REPORT).unwrap();
+        fs::write(dir.join("npm-audit.json"), NPM_AUDIT_REPORT).unwrap();
+        for (tool, report) in [("cargo-audit", "cargo-audit.json"), ("npm", "npm-audit.json")] {
+            let script = bin.join(tool);
+            fs::write(&script, format!("#!/bin/sh\ncat {}\n", dir.join(report).display())).unwrap();
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        format!("{}:{}", bin.display(), std::env::var("PATH").unwrap_or_default())
+    }
+
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+language = "rust"
+
+[repositories."test.web"]
+url = "https://github.com/test/web.git"
+path = "web"
+language = "typescript"
+"#);
+        fs::create_dir_all(workspace.path().join("api")).unwrap();
+        fs::write(workspace.path().join("api/Cargo.toml"), "[package]\nname = \"api\"\n").unwrap();
+        fs::create_dir_all(workspace.path().join("web")).unwrap();
+        fs::write(workspace.path().join("web/package.json"), "{}").unwrap();
+        workspace
+    }
+
+    #[test]
+    fn test_audit_merges_reports() {
+        let workspace = create_workspace();
+        let path = fake_tools(workspace.path());
+
+        let mut cmd = common::syla();
+        let output = cmd
+            .args(["audit", "--json", "--workspace"])
+            .arg(workspace.path())
+            .env("PATH", &path)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let findings: Vec<(&str, &str, &str)> = report["findings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| (f["severity"].as_str().unwrap(), f["repo"].as_str().unwrap(), f["package"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                ("critical", "test.api", "hyper"),
+                ("high", "test.web", "lodash"),
+                ("medium", "test.api", "h2"),
+                ("low", "test.api", "instant"),
+            ]
+        );
+        assert_eq!(report["findings"][0]["fix"], "upgrade to >=1.1.0");
+    }
+
+    #[test]
+    fn test_audit_severity_filter_and_fail_on() {
+        let workspace = create_workspace();
+        let path = fake_tools(workspace.path());
+
+        let mut cmd = common::syla();
+        cmd.args(["audit", "--severity", "high", "--fail-on", "critical", "--workspace"])
+            .arg(workspace.path())
+            .env("PATH", &path)
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains("RUSTSEC-2024-0001"))
+            .stdout(predicate::str::contains("Command Injection in lodash"))
+            .stdout(predicate::str::contains("RUSTSEC-2024-0002").not())
+            .stdout(predicate::str::contains("2 findings: 1 critical, 1 high"))
+            .stderr(predicate::str::contains("1 finding(s) at or above the --fail-on severity"));
+    }
+}
+
+mod upgrade_tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// A bare upstream with one commit, cloned into the workspace at `path`
+    fn clone_upstream(root: &Path, name: &str, path: &str) -> std::path::PathBuf {
+        let upstream = root.join(format!("upstream/{}.git", name));
+        let seed = root.join(format!("seed/{}", name));
+        fs::create_dir_all(&upstream).unwrap();
+        fs::create_dir_all(&seed).unwrap();
+        git(&upstream, &["init", "-q", "--bare", "-b", "main"]);
+        git(&seed, &["init", "-q", "-b", "main"]);
+        fs::write(seed.join("package.json"), "{\"name\": \"web\"}").unwrap();
+        git(&seed, &["add", "."]);
+        git(&seed, &["commit", "-q", "-m", "initial"]);
+        git(&seed, &["remote", "add", "origin", upstream.to_str().unwrap()]);
+        git(&seed, &["push", "-q", "-u", "origin", "main"]);
+        git(root, &["clone", "-q", upstream.to_str().unwrap(), path]);
+        seed
+    }
+
+    #[test]
+    fn test_upgrade_pulls_and_rebuilds_changed_services() {
+        let workspace = common::workspace(r#"
+[repositories."test.web"]
+url = "https://github.com/test/web.git"
+path = "web"
+language = "typescript"
+ports = ["3100"]
+
+[repositories."test.docs"]
+url = "https://github.com/test/docs.git"
+path = "docs"
+language = "typescript"
+"#);
+        let root = workspace.path();
+        let web_seed = clone_upstream(root, "web", "web");
+        clone_upstream(root, "docs", "docs");
+        fs::create_dir_all(root.join("web/node_modules")).unwrap();
+        fs::create_dir_all(root.join("docs/node_modules")).unwrap();
+
+        // Upstream moves on for web; docs has local work in progress
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        fs::write(web_seed.join("package.json"), "{\"name\": \"web\", \"version\": \"2.0.0\"}").unwrap();
+        git(&web_seed, &["commit", "-q", "-am", "bump"]);
+        git(&web_seed, &["push", "-q"]);
+        fs::write(root.join("docs/notes.md"), "draft").unwrap();
+
+        // An npm that installs by recording it did
+        let bin = root.join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let npm = bin.join("npm");
+        fs::write(&npm, "#!/bin/sh\ntouch installed\n").unwrap();
+        fs::set_permissions(&npm, fs::Permissions::from_mode(0o755)).unwrap();
+        let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap_or_default());
+
+        let mut cmd = common::syla();
+        cmd.env("PATH", &path)
+            .env("NO_COLOR", "1")
+            .args(["upgrade", "--workspace"])
+            .arg(root)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.web updated"))
+            .stdout(predicate::str::contains("test.docs skipped: uncommitted changes"))
+            .stdout(predicate::str::contains("test.web built"))
+            .stdout(predicate::str::contains("No migrations to run"))
+            .stdout(predicate::str::contains("1 pulled, 1 rebuilt, 0 restarted"));
+        assert!(fs::read_to_string(root.join("web/package.json")).unwrap().contains("2.0.0"));
+        assert!(root.join("web/installed").exists());
+        assert!(!root.join("docs/installed").exists());
+    }
+
+    /// A workspace with test.docs cloned, whose upstream then gained a commit
+    fn diverging_workspace() -> (TempDir, std::path::PathBuf) {
+        let workspace = common::workspace(r#"
+[repositories."test.docs"]
+url = "https://github.com/test/docs.git"
+path = "docs"
+"#);
+        let root = workspace.path();
+        let seed = clone_upstream(root, "docs", "docs");
+        fs::write(seed.join("upstream.md"), "from upstream").unwrap();
+        git(&seed, &["add", "."]);
+        git(&seed, &["commit", "-q", "-m", "upstream"]);
+        git(&seed, &["push", "-q"]);
+        (workspace, seed)
+    }
+
+    #[test]
+    fn test_stash_rebase_keeps_local_changes() {
+        let (workspace, _) = diverging_workspace();
+        let docs = workspace.path().join("docs");
+        fs::write(docs.join("notes.md"), "draft").unwrap();
+
+        let mut cmd = common::syla();
+        cmd.env("NO_COLOR", "1")
+            .args(["upgrade", "--strategy", "stash-rebase", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.docs updated"))
+            .stdout(predicate::str::contains("Sync report"))
+            .stdout(predicate::str::contains("local changes stashed and re-applied"));
+        assert!(docs.join("upstream.md").exists());
+        assert_eq!(fs::read_to_string(docs.join("notes.md")).unwrap(), "draft");
+    }
+
+    #[test]
+    fn test_rebase_replays_local_commits() {
+        let (workspace, _) = diverging_workspace();
+        let docs = workspace.path().join("docs");
+        fs::write(docs.join("local.md"), "mine").unwrap();
+        git(&docs, &["add", "."]);
+        git(&docs, &["commit", "-q", "-m", "local"]);
+
+        let mut cmd = common::syla();
+        cmd.env("NO_COLOR", "1")
+            .args(["upgrade", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.docs skipped: diverged from upstream"));
+        assert!(!docs.join("upstream.md").exists());
+
+        let mut cmd = common::syla();
+        cmd.env("NO_COLOR", "1")
+            .env("GIT_COMMITTER_NAME", "test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .args(["upgrade", "--strategy", "rebase", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("test.docs updated"));
+        assert!(docs.join("upstream.md").exists());
+        assert!(docs.join("local.md").exists());
+    }
+
+    #[test]
+    fn test_fail_strategy_stops_on_local_changes() {
+        let (workspace, _) = diverging_workspace();
+        fs::write(workspace.path().join("docs/notes.md"), "draft").unwrap();
+
+        let mut cmd = common::syla();
+        cmd.args(["upgrade", "--strategy", "fail", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("test.docs has uncommitted changes"));
+    }
+}
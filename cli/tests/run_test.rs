@@ -0,0 +1,389 @@
+mod common;
+
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+#[cfg(unix)]
+mod run_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace(tasks: &str) -> TempDir {
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.a"]
+url = "https://github.com/test/a.git"
+path = "services/a"
+
+[repositories."test.b"]
+url = "https://github.com/test/b.git"
+path = "services/b"
+
+{}
+"#,
+            tasks
+        ));
+        fs::create_dir_all(workspace.path().join("services/a")).unwrap();
+        fs::create_dir_all(workspace.path().join("services/b")).unwrap();
+        workspace
+    }
+
+    #[test]
+    fn test_run_task_with_dependencies() {
+        let workspace = create_workspace(
+            r#"
+[tasks.setup]
+command = "echo setup >> order.txt"
+
+[tasks.build]
+command = "echo $SYLA_REPO >> \"$SYLA_WORKSPACE/order.txt\""
+repos = ["test.*"]
+depends_on = ["setup"]
+"#,
+        );
+
+        let mut cmd = common::syla();
+        cmd.args(["run", "build", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success();
+
+        let order = fs::read_to_string(workspace.path().join("order.txt")).unwrap();
+        let lines: Vec<&str> = order.lines().collect();
+        assert_eq!(lines, vec!["setup", "test.a", "test.b"]);
+    }
+
+    #[test]
+    fn test_run_task_cycle() {
+        let workspace = create_workspace(
+            r#"
+[tasks.a]
+command = "true"
+depends_on = ["b"]
+
+[tasks.b]
+command = "true"
+depends_on = ["a"]
+"#,
+        );
+
+        let mut cmd = common::syla();
+        cmd.args(["run", "a", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Task dependency cycle: a -> b -> a"));
+    }
+
+    #[test]
+    fn test_run_task_failure() {
+        let workspace = create_workspace(
+            r#"
+[tasks.broken]
+command = "exit 4"
+"#,
+        );
+
+        let mut cmd = common::syla();
+        cmd.args(["run", "broken", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Task 'broken' failed with exit code 4"));
+    }
+}
+
+mod otel_tests {
+    use super::*;
+
+    #[test]
+    fn test_unreachable_collector_does_not_fail_commands() {
+        let workspace = common::workspace("[tasks.hello]\ncommand = \"echo hi\"\n");
+
+        let mut cmd = common::syla();
+        cmd.args(["run", "hello", "--workspace"])
+            .arg(workspace.path())
+            .env("OTEL_EXPORTER_OTLP_ENDPOINT", "http://127.0.0.1:1")
+            .timeout(std::time::Duration::from_secs(30))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("hello complete"));
+    }
+}
+
+mod bench_tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Answers every connection with a small 200 response
+    fn serve() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                std::thread::spawn(move || {
+                    let mut stream = stream;
+                    let mut buf = [0u8; 1024];
+                    let _ = stream.read(&mut buf);
+                    let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+                });
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn test_bench_saves_and_compares() {
+        let port = serve();
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+ports = ["{}"]
+"#,
+            port
+        ));
+
+        let mut cmd = common::syla();
+        cmd.args(["bench", "api", "--duration", "1", "--rate", "50", "-c", "2", "--label", "main", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("p99:"))
+            .stdout(predicate::str::contains("Errors:      0.00% (0)"));
+        assert!(workspace.path().join(".bench/test.api/main.json").exists());
+
+        let mut cmd = common::syla();
+        cmd.args(["bench", "api", "--duration", "1", "--compare", "main", "--no-save", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Compared with main"));
+
+        let mut cmd = common::syla();
+        cmd.args(["bench", "api", "--compare", "missing", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No saved results for test.api labelled 'missing'"));
+    }
+}
+
+mod exec_tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Reads a whole HTTP request, body included
+    fn read_request(stream: &mut std::net::TcpStream) -> String {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let read = stream.read(&mut buf).unwrap_or(0);
+            request.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&request).into_owned();
+            let Some(end) = text.find("\r\n\r\n") else {
+                if read == 0 { return String::new() } else { continue }
+            };
+            let length = text[..end]
+                .lines()
+                .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+                .unwrap_or(0usize);
+            if request.len() >= end + 4 + length || read == 0 {
+                return text;
+            }
+        }
+    }
+
+    fn respond(stream: &mut std::net::TcpStream, body: &str) {
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+    }
+
+    /// A stand-in execution service: submissions whose code says "wrong"
+    /// fail their second test case, everything else passes
+    fn serve_execution_service() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let body = read_request(&mut stream);
+                let response = if body.starts_with("POST /executions") {
+                    assert!(body.contains("expected_output"), "tests were not sent: {}", body);
+                    let id = if body.contains("wrong") { "fail" } else { "pass" };
+                    format!(r#"{{"id": "{}", "status": "queued"}}"#, id)
+                } else if body.starts_with("GET /executions/pass") {
+                    r#"{"id": "pass", "status": "completed", "test_report": {"verdicts": [
+                        {"name": "small", "status": "passed", "duration_ms": 3, "actual_output": "3"},
+                        {"name": "negative", "status": "passed", "duration_ms": 4, "actual_output": "-2"}]}}"#
+                        .to_string()
+                } else {
+                    r#"{"id": "fail", "status": "completed", "test_report": {"verdicts": [
+                        {"name": "small", "status": "passed", "duration_ms": 3, "actual_output": "3"},
+                        {"name": "negative", "status": "failed", "duration_ms": 4, "expected_output": "-2", "actual_output": "8"}]}}"#
+                        .to_string()
+                };
+                respond(&mut stream, &response);
+            }
+        });
+        port
+    }
+
+    fn create_solutions() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("solutions")).unwrap();
+        fs::write(dir.path().join("solutions/a.py"), "a, b = map(int, input().split())\nprint(a + b)\n").unwrap();
+        fs::write(dir.path().join("solutions/b.py"), "a, b = map(int, input().split())\nprint(a - b)  # wrong\n").unwrap();
+        fs::write(
+            dir.path().join("tests.json"),
+            r#"{"cases": [
+                {"name": "small", "stdin": "1 2\n", "expected_output": "3"},
+                {"name": "negative", "stdin": "-5 3\n", "expected_output": "-2"}
+            ]}"#,
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_exec_batch_summarizes_verdicts() {
+        let port = serve_execution_service();
+        let dir = create_solutions();
+
+        common::syla()
+            .current_dir(dir.path())
+            .args(["exec", "--batch", "solutions/*.py", "--tests", "tests.json", "--url"])
+            .arg(format!("http://127.0.0.1:{}", port))
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains("solutions/a.py"))
+            .stdout(predicate::str::contains("2/2"))
+            .stdout(predicate::str::contains("1/2"))
+            .stdout(predicate::str::contains("2 files: 1 passed, 1 failed"))
+            .stderr(predicate::str::contains("1 of 2 files did not pass"));
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("exec-report.json")).unwrap()).unwrap();
+        assert_eq!(report["total"], 2);
+        assert_eq!(report["passed"], 1);
+        assert_eq!(report["results"][0]["verdict"], "passed");
+        assert_eq!(report["results"][1]["verdict"], "failed");
+        assert_eq!(report["results"][1]["execution_id"], "fail");
+        assert_eq!(report["results"][1]["verdicts"][1]["actual_output"], "8");
+    }
+
+    #[test]
+    fn test_exec_batch_carries_on_past_unknown_languages() {
+        let port = serve_execution_service();
+        let dir = create_solutions();
+        fs::write(dir.path().join("solutions/notes.txt"), "not code").unwrap();
+
+        common::syla()
+            .current_dir(dir.path())
+            .args(["exec", "--batch", "solutions/a.py", "solutions/notes.txt", "--tests", "tests.json", "--url"])
+            .arg(format!("http://127.0.0.1:{}", port))
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains("2 files: 1 passed, 0 failed, 1 errors"));
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("exec-report.json")).unwrap()).unwrap();
+        assert_eq!(report["results"][0]["verdict"], "passed");
+        assert_eq!(report["results"][1]["verdict"], "error");
+        assert!(report["results"][1]["error"].as_str().unwrap().contains("Can't tell the language"));
+    }
+
+    #[test]
+    fn test_exec_unreachable_service_is_an_error_verdict() {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let dir = create_solutions();
+
+        common::syla()
+            .current_dir(dir.path())
+            .args(["exec", "--batch", "solutions/a.py", "solutions/b.py", "--tests", "tests.json", "--output", "json", "--report", "out.json", "--url"])
+            .arg(format!("http://127.0.0.1:{}", port))
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains(r#""errors": 2"#));
+        assert!(dir.path().join("out.json").exists());
+    }
+
+    #[test]
+    fn test_exec_needs_batch_for_many_files() {
+        let dir = create_solutions();
+
+        common::syla()
+            .current_dir(dir.path())
+            .args(["exec", "solutions/a.py", "solutions/b.py"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("pass --batch"));
+    }
+
+    #[test]
+    fn test_executions_replay_in_debug_mode() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let request = read_request(&mut stream);
+                let _ = sender.send(request.clone());
+                if request.starts_with("POST /executions/original/replay") {
+                    respond(&mut stream, r#"{"id": "replayed", "status": "queued", "replay_of": "original"}"#);
+                } else {
+                    respond(
+                        &mut stream,
+                        r#"{"id": "replayed", "status": "completed", "replay_of": "original",
+                            "result": {"exit_code": 0, "stdout": "3\n", "stderr": "", "duration_ms": 12},
+                            "debug_info": {"worker_id": "worker-1", "timeout_seconds": 120}}"#,
+                    );
+                }
+            }
+        });
+
+        common::syla()
+            .args(["executions", "replay", "original", "--debug", "--url"])
+            .arg(format!("http://127.0.0.1:{}", port))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Replaying original as replayed"))
+            .stdout(predicate::str::contains("Exit code 0"))
+            .stdout(predicate::str::contains("worker_id: worker-1"));
+        let submitted = receiver.recv().unwrap();
+        assert!(submitted.ends_with(r#"{"debug":true}"#), "{}", submitted);
+    }
+
+    #[test]
+    fn test_problem_responses_are_rendered() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                read_request(&mut stream);
+                let body = r#"{"type": "urn:syla:error:overloaded", "title": "Service overloaded", "status": 429,
+                    "detail": "Queue is full", "code": "overloaded", "retryable": true, "retry_after_seconds": 7}"#;
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 429 Too Many Requests\r\nContent-Type: application/problem+json\r\nRetry-After: 7\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+
+        common::syla()
+            .args(["executions", "replay", "original", "--url"])
+            .arg(format!("http://127.0.0.1:{}", port))
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Service overloaded: Queue is full (overloaded); retry in 7s"));
+    }
+}
@@ -0,0 +1,609 @@
+mod common;
+
+use std::process::Command;
+use predicates::prelude::*;
+use assert_cmd::Command as TestCommand;
+use tempfile::TempDir;
+
+mod workspace_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_test_workspace() -> TempDir {
+        // A minimal repos.toml
+        let temp_dir = common::workspace(r#"
+[repositories."test.service"]
+url = "https://github.com/test/service.git"
+path = "test/service"
+branch = "main"
+language = "rust"
+"#);
+        
+        // Create docker-compose.yml
+        let docker_compose = r#"
+version: '3.8'
+services:
+  redis:
+    image: redis:7-alpine
+    ports:
+      - "6379:6379"
+"#;
+        fs::write(temp_dir.path().join("docker-compose.yml"), docker_compose).unwrap();
+        
+        temp_dir
+    }
+
+    #[test]
+    fn test_syla_status_with_workspace() {
+        let workspace = create_test_workspace();
+        let mut cmd = common::syla();
+        cmd.arg("status")
+            .arg("--workspace")
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Repository Status"));
+    }
+
+    #[test]
+    fn test_syla_init_unknown_template() {
+        let workspace = create_test_workspace();
+        let manifest = workspace.path().join(".platform/config/repos.toml");
+        let mut content = fs::read_to_string(&manifest).unwrap();
+        content.push_str(
+            r#"
+[templates.backend]
+description = "Backend services"
+repositories = ["test.*"]
+compose_profiles = ["backend"]
+"#,
+        );
+        fs::write(&manifest, content).unwrap();
+
+        let mut cmd = common::syla();
+        cmd.arg("init")
+            .arg("--workspace")
+            .arg(workspace.path())
+            .arg("--template")
+            .arg("frontend")
+            .arg("--yes")
+            .env_remove("SYLA_TEMPLATE_REGISTRY")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Template 'frontend' not found. Available templates: backend"));
+
+        assert!(!workspace.path().join(".platform/config/workspace.toml").exists());
+    }
+
+    #[test]
+    fn test_syla_init_template_conflicts_with_platform() {
+        let workspace = create_test_workspace();
+        let mut cmd = common::syla();
+        cmd.arg("init")
+            .arg("--workspace")
+            .arg(workspace.path())
+            .arg("--template")
+            .arg("backend")
+            .arg("--platform")
+            .arg("test")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+
+    #[test]
+    fn test_syla_init_dry_run() {
+        let workspace = create_test_workspace();
+        let mut cmd = common::syla();
+        cmd.arg("init")
+            .arg("--workspace")
+            .arg(workspace.path())
+            .arg("--yes")
+            .env("DRY_RUN", "1")
+            .assert()
+            .success();
+    }
+}
+
+mod status_health_tests {
+    use super::*;
+
+    #[test]
+    fn test_status_checks_run_concurrently_with_timeout() {
+        let workspace = common::workspace(r#"
+[infrastructure.slow-a]
+type = "external"
+health_check = "sleep 5"
+
+[infrastructure.slow-b]
+type = "external"
+health_check = "sleep 5"
+
+[infrastructure.up]
+type = "external"
+health_check = "true"
+"#);
+
+        let started = std::time::Instant::now();
+        let mut cmd = common::syla();
+        let output = cmd
+            .args(["status", "--detailed", "--workspace"])
+            .arg(workspace.path())
+            .output()
+            .unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let status = |name: &str| stdout.lines().find(|line| line.contains(name)).unwrap().to_string();
+        assert!(status("slow-a").contains("Stopped"));
+        assert!(status("slow-b").contains("Stopped"));
+        assert!(status("| up").contains("Running"));
+    }
+}
+
+mod status_git_tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    #[test]
+    fn test_status_reports_every_repository() {
+        let mut manifest = String::new();
+        for i in 0..12 {
+            manifest.push_str(&format!(
+                "[repositories.repo-{i:02}]\nurl = \"https://example.com/repo-{i:02}.git\"\npath = \"repo-{i:02}\"\n\n"
+            ));
+        }
+        manifest.push_str("[repositories.missing]\nurl = \"https://example.com/missing.git\"\npath = \"missing\"\n");
+        let workspace = common::workspace(&manifest);
+        for i in 0..12 {
+            let name = format!("repo-{:02}", i);
+            let path = workspace.path().join(&name);
+            fs::create_dir_all(&path).unwrap();
+            Command::new("git").args(["init", "-q", "-b", "main"]).current_dir(&path).status().unwrap();
+            if i % 3 == 0 {
+                fs::write(path.join("new.txt"), "x").unwrap();
+            }
+        }
+
+        let mut cmd = common::syla();
+        let output = cmd
+            .args(["status", "--detailed", "--workspace"])
+            .arg(workspace.path())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let rows: Vec<&str> = stdout.lines().filter(|line| line.contains("repo-")).collect();
+        assert_eq!(rows.len(), 12, "{}", stdout);
+        // Rows are printed as git answers, so only their content is fixed
+        for i in 0..12 {
+            let name = format!("repo-{:02}", i);
+            let row = rows.iter().find(|row| row.contains(&name)).unwrap_or_else(|| panic!("no row for {}", name));
+            assert!(row.contains(if i % 3 == 0 { "1 changes" } else { "Clean" }), "{}", row);
+        }
+        assert!(stdout.lines().any(|line| line.contains("missing") && line.contains("Not cloned")));
+    }
+}
+
+mod github_status_tests {
+    use super::*;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::process::Command;
+    use std::sync::{Arc, Mutex};
+
+    /// Serves canned GitHub answers, recording the request lines
+    fn serve(requests: Arc<Mutex<Vec<String>>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                    line.clear();
+                }
+                requests.lock().unwrap().push(request_line.clone());
+                let body = if request_line.contains("/pulls") {
+                    r#"[{"number": 7, "title": "Add thing", "html_url": "https://github.com/test/web/pull/7", "draft": false}]"#
+                } else if request_line.contains("/check-runs") {
+                    r#"{"total_count": 1, "check_runs": [{"status": "completed", "conclusion": "failure"}]}"#
+                } else {
+                    r#"{"state": "pending", "total_count": 0, "statuses": []}"#
+                };
+                let mut stream = reader.into_inner();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        address
+    }
+
+    #[test]
+    fn test_status_shows_pull_request_and_ci() {
+        let workspace = common::workspace(r#"
+[repositories."test.web"]
+url = "git@github.com:test/web.git"
+path = "web"
+"#);
+        let root = workspace.path();
+        let web = root.join("web");
+        fs::create_dir_all(&web).unwrap();
+        for args in [&["init", "-q", "-b", "feature/thing"][..], &["commit", "-q", "--allow-empty", "-m", "initial"]] {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(&web)
+                .env("GIT_AUTHOR_NAME", "test")
+                .env("GIT_AUTHOR_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_NAME", "test")
+                .env("GIT_COMMITTER_EMAIL", "test@example.com")
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let api = serve(requests.clone());
+        let home = TempDir::new().unwrap();
+        for _ in 0..2 {
+            let mut cmd = common::syla();
+            cmd.env("SYLA_GITHUB_API", &api)
+                .env("SYLA_HOME", home.path())
+                .env("GITHUB_TOKEN", "test-token")
+                .env("NO_COLOR", "1")
+                .args(["status", "--github", "--workspace"])
+                .arg(root)
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("Pull Request"))
+                .stdout(predicate::str::contains("#7 failing"));
+        }
+
+        // The second run is answered from the cache
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3, "{:?}", requests);
+        assert!(requests.iter().any(|r| r.contains("head=test%3Afeature%2Fthing")));
+        assert!(requests.iter().any(|r| r.contains("/commits/feature%2Fthing/check-runs")));
+    }
+}
+
+mod docker_unavailable_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+run = "true"
+ports = ["3200"]
+
+[infrastructure.redis]
+type = "docker"
+ports = ["6379:6379"]
+"#);
+        fs::create_dir_all(workspace.path().join("api")).unwrap();
+        fs::write(workspace.path().join("docker-compose.yml"), "services: {}\n").unwrap();
+        workspace
+    }
+
+    fn syla(workspace: &TempDir) -> TestCommand {
+        let mut cmd = common::syla();
+        cmd.env("DOCKER_HOST", format!("unix://{}", workspace.path().join("no-docker.sock").display()));
+        cmd
+    }
+
+    #[test]
+    fn test_status_without_docker() {
+        let workspace = create_workspace();
+        syla(&workspace)
+            .args(["status", "--detailed", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Docker is not available"))
+            .stdout(predicate::str::contains("test.api"))
+            .stdout(predicate::str::contains("Stopped"));
+    }
+
+    #[test]
+    fn test_dev_up_without_docker_still_starts_services() {
+        let workspace = create_workspace();
+        syla(&workspace)
+            .args(["dev", "up", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("skipping infrastructure containers"))
+            .stdout(predicate::str::contains("test.api"));
+    }
+}
+
+mod output_format_tests {
+    use super::*;
+    use std::fs;
+
+    fn create_workspace() -> TempDir {
+        common::workspace(r#"
+[repositories."test.api"]
+url = "https://github.com/test/api.git"
+path = "api"
+platform = "test"
+ports = ["3300"]
+
+[repositories."test.web"]
+url = "https://github.com/test/web.git"
+path = "web"
+platform = "test"
+"#)
+    }
+
+    fn json(args: &[&str], workspace: &TempDir) -> serde_json::Value {
+        let mut cmd = common::syla();
+        let output = cmd.args(args).arg("--workspace").arg(workspace.path()).output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice(&output.stdout).unwrap()
+    }
+
+    #[test]
+    fn test_status_json() {
+        let workspace = create_workspace();
+        let status = json(&["status", "--output", "json"], &workspace);
+        assert_eq!(status["repositories"][0]["name"], "test.api");
+        assert_eq!(status["repositories"][0]["state"], "not_cloned");
+        assert_eq!(status["services"][0]["running"], false);
+        assert_eq!(status["services"].as_array().unwrap().len(), 1);
+        assert!(status.get("infrastructure").is_none());
+    }
+
+    #[test]
+    fn test_status_merges_services_and_infrastructure() {
+        let workspace = create_workspace();
+        let config_dir = workspace.path().join(".platform/config");
+        let mut manifest = fs::read_to_string(config_dir.join("repos.toml")).unwrap();
+        manifest.push_str(
+            r#"
+[repositories."test.worker"]
+url = "https://github.com/test/worker.git"
+path = "worker"
+ports = ["3301"]
+health_check = "true"
+
+[infrastructure.cache]
+type = "external"
+ports = ["6379"]
+health_check = "true"
+
+[infrastructure.docker]
+type = "system"
+"#,
+        );
+        fs::write(config_dir.join("repos.toml"), manifest).unwrap();
+        let state = workspace.path().join(".platform/state");
+        fs::create_dir_all(&state).unwrap();
+        fs::write(
+            state.join("services.toml"),
+            "[\"test.api\"]\npid = 999999999\ncommand = \"api\"\nstarted_at = \"2026-01-01T00:00:00Z\"\n",
+        )
+        .unwrap();
+
+        let status = json(&["status", "--output", "json"], &workspace);
+        let services = status["services"].as_array().unwrap();
+        let names: Vec<&str> = services.iter().map(|service| service["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["test.api", "test.worker", "cache", "docker"]);
+
+        // Started, but its process is gone
+        assert_eq!(services[0]["state"], "exited");
+        assert_eq!(services[0]["runs_as"]["kind"], "process");
+        assert_eq!(services[0]["runs_as"]["pid"], 999999999);
+        // Never started, yet something answers its health check
+        assert_eq!(services[1]["state"], "running");
+        assert_eq!(services[1]["runs_as"]["kind"], "unmanaged");
+        assert_eq!(services[2]["kind"], "external");
+        assert_eq!(services[2]["state"], "running");
+        assert_eq!(services[2]["healthy"], true);
+        assert_eq!(services[3]["state"], "available");
+
+        common::syla()
+            .args(["status", "--workspace"])
+            .arg(workspace.path())
+            .env("NO_COLOR", "1")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Runs As"))
+            .stdout(predicate::str::contains("process 999999999"))
+            .stdout(predicate::str::contains("not started by syla"))
+            .stdout(predicate::str::contains("Infrastructure:").not());
+    }
+
+    #[test]
+    fn test_doctor_json() {
+        let workspace = create_workspace();
+        let doctor = json(&["doctor", "-o", "json"], &workspace);
+        let checks = doctor["checks"].as_array().unwrap();
+        assert!(checks.iter().any(|check| check["name"] == "Configuration" && check["ok"] == true));
+    }
+
+    #[test]
+    fn test_platform_list_yaml() {
+        let workspace = create_workspace();
+        let mut cmd = common::syla();
+        cmd.args(["platform", "list", "--output", "yaml", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .stdout("- name: test\n  repositories:\n  - test.api\n  - test.web\n");
+    }
+}
+
+mod stale_build_tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+
+    /// A Node service whose `node_modules` predates its `package.json`
+    fn create_workspace() -> TempDir {
+        let workspace = common::workspace(r#"
+[repositories."test.web"]
+url = "https://github.com/test/web.git"
+path = "web"
+language = "node"
+ports = ["3000"]
+"#);
+        let web = workspace.path().join("web");
+        fs::create_dir_all(web.join("node_modules")).unwrap();
+        fs::write(web.join("package.json"), "{}").unwrap();
+        let built = SystemTime::now() - Duration::from_secs(3 * 24 * 3600);
+        fs::File::open(web.join("node_modules")).unwrap().set_modified(built).unwrap();
+        workspace
+    }
+
+    #[test]
+    fn test_status_reports_stale_build() {
+        let workspace = create_workspace();
+        let output = common::syla()
+            .args(["status", "-o", "json", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let status: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let stale = status["services"][0]["stale_build"].as_str().unwrap();
+        assert!(stale.starts_with("built 3 days ago"), "{}", stale);
+        assert!(stale.contains("package.json modified"), "{}", stale);
+    }
+
+    #[test]
+    fn test_fresh_build_is_not_stale() {
+        let workspace = create_workspace();
+        fs::File::open(workspace.path().join("web/node_modules"))
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        let output = common::syla()
+            .args(["status", "-o", "json", "--workspace"])
+            .arg(workspace.path())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let status: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert!(status["services"][0].get("stale_build").is_none());
+    }
+
+    #[test]
+    fn test_validate_flags_stale_build() {
+        let workspace = create_workspace();
+        common::syla()
+            .args(["dev", "validate", "--workspace"])
+            .arg(workspace.path())
+            .env("DOCKER_HOST", format!("unix://{}", workspace.path().join("no-docker.sock").display()))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Service test.web is stale: built 3 days ago"));
+    }
+}
+
+#[cfg(unix)]
+mod platform_tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+
+        fn create_workspace() -> TempDir {
+        let workspace = common::workspace(&format!(
+            r#"
+[repositories."test.gateway"]
+url = "https://github.com/test/gateway.git"
+path = "gateway"
+language = "shell"
+platform = "api"
+run = "echo started-$PORT; sleep 60"
+test = "true"
+ports = ["{}"]
+depends_on = ["test.store"]
+
+[repositories."test.store"]
+url = "https://github.com/test/store.git"
+path = "store"
+language = "shell"
+platform = "data"
+run = "echo started-$PORT; sleep 60"
+test = "echo 'test result: FAILED'; exit 1"
+ports = ["{}"]
+"#,
+            common::free_port(),
+            common::free_port()
+        ));
+        fs::create_dir_all(workspace.path().join("gateway")).unwrap();
+        fs::create_dir_all(workspace.path().join("store")).unwrap();
+        workspace
+    }
+
+    fn syla(workspace: &TempDir, args: &[&str]) -> assert_cmd::assert::Assert {
+        let mut cmd = common::syla();
+        cmd.args(args).arg("--workspace").arg(workspace.path()).assert()
+    }
+
+    fn service_state(workspace: &TempDir, name: &str) -> String {
+        let output = syla(workspace, &["platform", "status", "api", "--output", "json"]).success().get_output().stdout.clone();
+        let status: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        status["services"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|service| service["name"] == name)
+            .map(|service| service["state"].as_str().unwrap().to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_platform_test_exit_status() {
+        let workspace = create_workspace();
+        syla(&workspace, &["platform", "test", "api"]).success().stdout(predicate::str::contains("api tests passed"));
+        syla(&workspace, &["platform", "test", "data"])
+            .code(1)
+            .stderr(predicate::str::contains("data tests failed: test.store"));
+    }
+
+    #[test]
+    fn test_platform_start_with_deps_and_stop() {
+        let workspace = create_workspace();
+        syla(&workspace, &["platform", "stop", "api"]).success().stdout(predicate::str::contains("Nothing from api is running"));
+
+        let mut start = Command::new(env!("CARGO_BIN_EXE_syla"))
+            .args(["platform", "start", "api", "--with-deps", "--workspace"])
+            .arg(workspace.path())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let running = (0..100).any(|_| {
+            std::thread::sleep(Duration::from_millis(200));
+            service_state(&workspace, "test.gateway") == "running"
+        });
+        assert!(running, "platform never started");
+        assert_eq!(service_state(&workspace, "test.store"), "running");
+
+        syla(&workspace, &["platform", "stop", "api"]).success().stdout(predicate::str::contains("Stopped api"));
+        // Each service gets a few seconds to shut down gracefully
+        let exited = (0..300).find_map(|_| {
+            std::thread::sleep(Duration::from_millis(100));
+            start.try_wait().unwrap()
+        });
+        if exited.is_none() {
+            start.kill().unwrap();
+        }
+        assert!(exited.expect("platform start kept running").success());
+        assert_ne!(service_state(&workspace, "test.store"), "running");
+    }
+}
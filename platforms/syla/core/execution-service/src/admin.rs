@@ -0,0 +1,189 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::admission::AdmissionConfig;
+use crate::docker;
+use crate::error::ServiceError;
+use crate::queue::{QueueDepth, WorkerState, WorkerStatus};
+use crate::state::ServiceState;
+use crate::warmup::WarmupReport;
+
+/// Everything that decides when a queued job will start
+#[derive(Debug, Serialize)]
+pub struct QueueReport {
+    pub generated_at: DateTime<Utc>,
+    pub depths: Vec<QueueDepth>,
+    pub inflight: Vec<InflightExecution>,
+    /// Mean run time of recent jobs
+    pub average_duration_ms: Option<u64>,
+    pub limits: AdmissionConfig,
+    pub containers: ContainerPool,
+}
+
+/// A job a worker is running right now
+#[derive(Debug, Serialize)]
+pub struct InflightExecution {
+    pub job_id: Uuid,
+    pub worker_id: Option<String>,
+    pub language: Option<String>,
+    pub attempts: u32,
+    pub started_at: Option<DateTime<Utc>>,
+    pub running_ms: Option<u64>,
+    /// Time since the worker last confirmed the job is still running
+    pub heartbeat_age_ms: u64,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+}
+
+/// Containers on this host, both one-off execution containers and
+/// interactive session containers
+#[derive(Debug, Serialize)]
+pub struct ContainerPool {
+    pub executions: Vec<docker::ContainerSummary>,
+    pub sessions: usize,
+    /// Set when Docker could not be queried
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkersReport {
+    pub online: usize,
+    pub stale: usize,
+    pub workers: Vec<WorkerDetail>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkerDetail {
+    #[serde(flatten)]
+    pub status: WorkerStatus,
+    /// How long the worker has been on its current job
+    pub current_job_running_ms: Option<u64>,
+}
+
+pub async fn queue_report(
+    State(state): State<Arc<ServiceState>>,
+) -> Result<Json<QueueReport>, ServiceError> {
+    let depth = state.queue.depth().await?;
+    let average_duration_ms = state
+        .queue
+        .average_duration()
+        .await?
+        .map(|d| d.as_millis() as u64);
+    let inflight = inflight_executions(&state).await?;
+
+    let (executions, error) = match docker::list_containers("syla-exec-").await {
+        Ok(containers) => (containers, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+
+    Ok(Json(QueueReport {
+        generated_at: Utc::now(),
+        depths: vec![depth],
+        inflight,
+        average_duration_ms,
+        limits: state.admission.clone(),
+        containers: ContainerPool {
+            executions,
+            sessions: state.sessions.active_count().await,
+            error,
+        },
+    }))
+}
+
+pub async fn workers_report(
+    State(state): State<Arc<ServiceState>>,
+) -> Result<Json<WorkersReport>, ServiceError> {
+    let statuses = state.queue.workers().await?;
+    let now = Utc::now();
+
+    let mut workers = Vec::with_capacity(statuses.len());
+    for status in statuses {
+        let started_at = match status.info.current_job {
+            Some(job_id) => match state.get_execution(job_id).await {
+                Ok(job) => job.started_at,
+                Err(ServiceError::NotFound) => None,
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+        workers.push(WorkerDetail {
+            status,
+            current_job_running_ms: started_at.map(|t| elapsed_ms(t, now)),
+        });
+    }
+
+    let online = workers
+        .iter()
+        .filter(|w| matches!(w.status.state, WorkerState::Online))
+        .count();
+    Ok(Json(WorkersReport {
+        online,
+        stale: workers.len() - online,
+        workers,
+    }))
+}
+
+pub async fn warmup_report(State(state): State<Arc<ServiceState>>) -> Json<WarmupReport> {
+    Json(state.warmup.report())
+}
+
+/// Start pulling and warming runtime images; poll `GET` for progress
+pub async fn start_warmup(
+    State(state): State<Arc<ServiceState>>,
+) -> (StatusCode, Json<WarmupReport>) {
+    let status = if state.warmup.start() {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::CONFLICT
+    };
+    (status, Json(state.warmup.report()))
+}
+
+/// Running jobs, longest-running first
+async fn inflight_executions(state: &ServiceState) -> Result<Vec<InflightExecution>, ServiceError> {
+    let now = Utc::now();
+    let mut inflight = Vec::new();
+
+    for (job_id, last_beat_ms) in state.queue.inflight().await? {
+        let job = match state.get_execution(job_id).await {
+            Ok(job) => Some(job),
+            Err(ServiceError::NotFound) => None,
+            Err(e) => return Err(e),
+        };
+        let heartbeat_age_ms = (now.timestamp_millis() - last_beat_ms).max(0) as u64;
+        inflight.push(match job {
+            Some(job) => InflightExecution {
+                job_id,
+                worker_id: job.worker_id,
+                language: Some(job.request.language),
+                attempts: job.attempts,
+                started_at: job.started_at,
+                running_ms: job.started_at.map(|t| elapsed_ms(t, now)),
+                heartbeat_age_ms,
+                labels: job.request.labels,
+            },
+            None => InflightExecution {
+                job_id,
+                worker_id: None,
+                language: None,
+                attempts: 0,
+                started_at: None,
+                running_ms: None,
+                heartbeat_age_ms,
+                labels: HashMap::new(),
+            },
+        });
+    }
+
+    inflight.sort_by_key(|job| std::cmp::Reverse(job.running_ms));
+    Ok(inflight)
+}
+
+fn elapsed_ms(since: DateTime<Utc>, now: DateTime<Utc>) -> u64 {
+    (now - since).num_milliseconds().max(0) as u64
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use crate::error::ServiceError;
+use crate::queue::{RedisQueue, WorkerState};
+
+/// Assumed job duration before any job has finished to measure from
+const DEFAULT_JOB_DURATION: Duration = Duration::from_secs(5);
+
+/// Thresholds past which new submissions are turned away
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AdmissionConfig {
+    /// Jobs waiting to be picked up by a worker
+    pub max_queue_depth: usize,
+    /// Jobs being run by workers at once
+    pub max_running: usize,
+    /// Interactive sessions open at once
+    pub max_sessions: usize,
+    /// Interactive sessions one API key may hold open at once
+    pub max_sessions_per_key: usize,
+}
+
+impl AdmissionConfig {
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: usize| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            max_queue_depth: read("MAX_QUEUE_DEPTH", 1000),
+            max_running: read("MAX_RUNNING_EXECUTIONS", 200),
+            max_sessions: read("MAX_SESSIONS", 50),
+            max_sessions_per_key: read("MAX_SESSIONS_PER_KEY", 5),
+        }
+    }
+
+    /// Reject the submission if the service is past either threshold, with a
+    /// projected wait so clients know when to retry
+    pub async fn check(&self, queue: &RedisQueue) -> Result<(), ServiceError> {
+        let backlog = queue.backlog(self.max_queue_depth + 1).await?;
+        let running = queue.running_count().await?;
+
+        let reason = if backlog >= self.max_queue_depth {
+            format!("{} executions are already queued", backlog)
+        } else if running >= self.max_running {
+            format!("{} executions are already running", running)
+        } else {
+            return Ok(());
+        };
+
+        let retry_after = projected_wait(queue, backlog + running).await?;
+        tracing::warn!("Rejecting execution: {}", reason);
+
+        Err(ServiceError::Overloaded {
+            reason,
+            retry_after_seconds: retry_after.as_secs().max(1),
+        })
+    }
+
+    /// Reject a new session if the service or the caller's key already has
+    /// as many open as allowed. Room frees up as idle sessions are reaped.
+    pub fn check_sessions(&self, open: usize, open_for_key: usize, api_key: &str) -> Result<(), ServiceError> {
+        let reason = if open >= self.max_sessions {
+            format!("{} sessions are already open", open)
+        } else if open_for_key >= self.max_sessions_per_key {
+            format!("{} already has {} sessions open", api_key, open_for_key)
+        } else {
+            return Ok(());
+        };
+
+        tracing::warn!("Rejecting session: {}", reason);
+        Err(ServiceError::Overloaded {
+            reason,
+            retry_after_seconds: crate::session::REAP_INTERVAL.as_secs(),
+        })
+    }
+}
+
+/// Time until `ahead` jobs have drained across the online workers
+async fn projected_wait(queue: &RedisQueue, ahead: usize) -> Result<Duration, ServiceError> {
+    let per_job = queue.average_duration().await?.unwrap_or(DEFAULT_JOB_DURATION);
+    let workers = queue
+        .workers()
+        .await?
+        .iter()
+        .filter(|w| matches!(w.state, WorkerState::Online))
+        .count()
+        .max(1);
+
+    Ok(per_job * ahead as u32 / workers as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_are_capped_overall_and_per_key() {
+        let config = AdmissionConfig {
+            max_queue_depth: 10,
+            max_running: 10,
+            max_sessions: 3,
+            max_sessions_per_key: 2,
+        };
+        assert!(config.check_sessions(2, 1, "ci").is_ok());
+        assert!(matches!(
+            config.check_sessions(2, 2, "ci"),
+            Err(ServiceError::Overloaded { .. })
+        ));
+        assert!(matches!(
+            config.check_sessions(3, 0, "ci"),
+            Err(ServiceError::Overloaded { .. })
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+//! Standalone execution worker. Consumes jobs from the Redis stream and runs
+//! them, so execution capacity can be scaled separately from the API.
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use std::sync::Arc;
+use std::time::Duration;
+use syla_execution_service::state::ServiceState;
+use syla_execution_service::{
+    admission, docker, health, images, log_store, queue, retention, retry, sandbox, session, telemetry,
+    usage, warmup, wasm, worker,
+};
+use tokio::sync::Mutex;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let tracer_provider = telemetry::init(
+        "syla-execution-worker",
+        "syla_execution_service=debug,syla_execution_worker=debug",
+    )?;
+
+    let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6380/".to_string());
+    let redis_client = redis::Client::open(redis_url)?;
+    let redis_conn = ConnectionManager::new(redis_client.clone()).await?;
+
+    let redis_queue = Arc::new(queue::RedisQueue::new(redis_conn.clone()));
+    redis_queue.ensure_group().await?;
+
+    // Probe for bubblewrap or nsjail before deciding whether Docker is required
+    let sandbox_executor = Arc::new(sandbox::SandboxExecutor::new(
+        sandbox::SandboxConfig::from_env(),
+        docker::OutputLimits::from_env(),
+    ));
+
+    let state = Arc::new(ServiceState {
+        redis: Arc::new(Mutex::new(redis_conn)),
+        queue: redis_queue.clone(),
+        docker_executor: Arc::new(if sandbox_executor.replaces_docker() {
+            docker::DockerExecutor::unchecked(docker::OutputLimits::from_env())
+        } else {
+            docker::DockerExecutor::new(docker::OutputLimits::from_env())?
+        }),
+        wasm_executor: Arc::new(wasm::WasmExecutor::new(
+            wasm::WasmConfig::from_env(),
+            docker::OutputLimits::from_env(),
+        )?),
+        sandbox_executor: sandbox_executor.clone(),
+        // Sessions are served by the API process only
+        sessions: Arc::new(session::SessionManager::new(
+            Duration::ZERO,
+            Duration::ZERO,
+            docker::OutputLimits::from_env(),
+        )),
+        worker_heartbeat: Arc::new(health::Heartbeat::new()),
+        admission: admission::AdmissionConfig::from_env(),
+        retention: retention::RetentionManager::new(retention::RetentionPolicy::from_env()),
+        log_offload: log_store::LogOffload::from_env().await?,
+        warmup: Arc::new(warmup::ImageWarmer::new()),
+        images: images::ImagePolicy::from_env(),
+        api_keys: usage::ApiKeys::from_env(),
+        retry: retry::RetryPolicy::from_env(),
+        output_limits: docker::OutputLimits::from_env(),
+    });
+
+    // Only join the queue once runtime images are local, so no job waits
+    // on a pull
+    if warmup::enabled_on_start() {
+        state.warmup.run().await;
+    }
+
+    let consumer = worker::worker_id();
+    let worker_queue = Arc::new(queue::RedisQueue::new(ConnectionManager::new(redis_client).await?));
+
+    tokio::select! {
+        _ = worker::run_worker(state, worker_queue, consumer.clone()) => {}
+        _ = shutdown_signal() => {
+            tracing::info!("Shutting down worker {}", consumer);
+        }
+    }
+
+    // A job interrupted here stays pending on this consumer, so another
+    // worker claims it once it has been idle long enough
+    redis_queue.remove_worker(&consumer).await?;
+
+    telemetry::shutdown(tracer_provider);
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = tokio::signal::ctrl_c();
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        .expect("Failed to install SIGTERM handler");
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate.recv() => {}
+    }
+}
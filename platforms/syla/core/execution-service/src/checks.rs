@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+
+use crate::docker::{DockerExecutor, ExecutionInput, ExecutionResult};
+use crate::models::{CreateExecutionRequest, Diagnostic, DiagnosticSeverity, ExecutionMode};
+
+/// Command that compiles or lints the submitted `main.<ext>` without running it
+pub fn check_command(language: &str, mode: ExecutionMode) -> Option<&'static str> {
+    match (mode, language) {
+        (ExecutionMode::Compile, "python") => Some("python -m py_compile main.py"),
+        (ExecutionMode::Compile, "javascript") => Some("node --check main.js"),
+        (ExecutionMode::Compile, "go") => Some("go build -o /dev/null main.go"),
+        (ExecutionMode::Compile, "rust") => Some(
+            "rustc --edition 2021 --error-format short --emit metadata -o /tmp/main.rmeta main.rs",
+        ),
+        (ExecutionMode::Lint, "python") => Some("python -m pyflakes main.py"),
+        (ExecutionMode::Lint, "javascript") => Some("npx --no-install eslint --format unix main.js"),
+        (ExecutionMode::Lint, "go") => Some("go vet main.go"),
+        (ExecutionMode::Lint, "rust") => Some(
+            "clippy-driver --edition 2021 --error-format short --emit metadata -o /tmp/main.rmeta main.rs",
+        ),
+        _ => None,
+    }
+}
+
+/// Compile or lint a submission, returning the raw run alongside parsed diagnostics
+pub async fn run_check(
+    executor: &DockerExecutor,
+    request: &CreateExecutionRequest,
+) -> Result<(ExecutionResult, Vec<Diagnostic>)> {
+    let command = check_command(&request.language, request.mode).with_context(|| {
+        format!(
+            "{:?} mode is not supported for language '{}'",
+            request.mode, request.language
+        )
+    })?;
+
+    let result = executor
+        .execute_with_input(
+            &request.code,
+            &request.language,
+            request.timeout_seconds.unwrap_or(30),
+            request.max_output_bytes,
+            ExecutionInput {
+                stdin: None,
+                command: Some(command.to_string()),
+                collect_artifacts: false,
+                image: request.docker_image(),
+            },
+        )
+        .await?;
+
+    let default_severity = match request.mode {
+        ExecutionMode::Lint => DiagnosticSeverity::Warning,
+        _ => DiagnosticSeverity::Error,
+    };
+    let output = format!("{}\n{}", result.stdout, result.stderr);
+    let diagnostics = parse_diagnostics(&output, default_severity);
+
+    Ok((result, diagnostics))
+}
+
+/// Extract diagnostics from compiler/linter output. Understands the
+/// `file:line[:col]: message` shape most tools emit, plus the multi-line
+/// tracebacks of `py_compile` and `node --check`.
+fn parse_diagnostics(output: &str, default_severity: DiagnosticSeverity) -> Vec<Diagnostic> {
+    let lines: Vec<&str> = output.lines().collect();
+    let mut diagnostics = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        // Python: `  File "main.py", line 3` ... `SyntaxError: message`
+        if let Some((file, line_no)) = python_location(line) {
+            if let Some((code, message)) = find_exception(&lines[i + 1..]) {
+                diagnostics.push(Diagnostic {
+                    file,
+                    line: line_no,
+                    column: None,
+                    severity: DiagnosticSeverity::Error,
+                    code: Some(code),
+                    message,
+                });
+            }
+            continue;
+        }
+
+        let Some(location) = parse_location(line) else {
+            continue;
+        };
+
+        // Node: `/workspace/main.js:3` ... `SyntaxError: message`
+        if location.rest.is_empty() {
+            if let Some((code, message)) = find_exception(&lines[i + 1..]) {
+                diagnostics.push(Diagnostic {
+                    file: location.file,
+                    line: location.line,
+                    column: None,
+                    severity: DiagnosticSeverity::Error,
+                    code: Some(code),
+                    message,
+                });
+            }
+            continue;
+        }
+
+        let (severity, code, message) = classify(location.rest, default_severity);
+        diagnostics.push(Diagnostic {
+            file: location.file,
+            line: location.line,
+            column: location.column,
+            severity,
+            code,
+            message,
+        });
+    }
+
+    diagnostics
+}
+
+struct Location<'a> {
+    file: String,
+    line: u32,
+    column: Option<u32>,
+    rest: &'a str,
+}
+
+fn parse_location(line: &str) -> Option<Location<'_>> {
+    let mut parts = line.splitn(3, ':');
+    let file = parts.next()?.trim();
+    if file.is_empty() || file.contains(char::is_whitespace) {
+        return None;
+    }
+    let line_no = parts.next()?.trim().parse().ok()?;
+    let rest = parts.next().unwrap_or("");
+
+    let (column, rest) = match rest.split_once(':') {
+        Some((column, tail)) => match column.trim().parse() {
+            Ok(column) => (Some(column), tail),
+            Err(_) => (None, rest),
+        },
+        None => (None, rest),
+    };
+
+    Some(Location {
+        file: normalize_path(file),
+        line: line_no,
+        column,
+        rest: rest.trim(),
+    })
+}
+
+fn python_location(line: &str) -> Option<(String, u32)> {
+    let rest = line.trim().strip_prefix("File \"")?;
+    let (file, rest) = rest.split_once('"')?;
+    let line_no = rest.trim_start_matches(',').trim().strip_prefix("line ")?;
+    let line_no = line_no.split(',').next()?.trim().parse().ok()?;
+    Some((normalize_path(file), line_no))
+}
+
+/// Find an unindented `SomeError: message` line, as ends a traceback
+fn find_exception(lines: &[&str]) -> Option<(String, String)> {
+    lines.iter().find_map(|line| {
+        if line.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let (name, message) = line.split_once(": ")?;
+        let is_exception = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+            && (name.ends_with("Error") || name.ends_with("Exception"));
+        is_exception.then(|| (name.to_string(), message.trim().to_string()))
+    })
+}
+
+/// Split a tool message into severity, rule code and text. Handles rustc's
+/// `error[E0425]: ...` prefix and eslint's `... [Error/no-unused-vars]` suffix.
+fn classify(message: &str, default_severity: DiagnosticSeverity) -> (DiagnosticSeverity, Option<String>, String) {
+    let prefixes = [
+        ("error", DiagnosticSeverity::Error),
+        ("warning", DiagnosticSeverity::Warning),
+        ("note", DiagnosticSeverity::Note),
+    ];
+    for (prefix, severity) in prefixes {
+        let Some(rest) = message.strip_prefix(prefix) else {
+            continue;
+        };
+        if let Some(rest) = rest.strip_prefix(':') {
+            return (severity, None, rest.trim().to_string());
+        }
+        if let Some((code, rest)) = rest.strip_prefix('[').and_then(|r| r.split_once("]:")) {
+            return (severity, Some(code.to_string()), rest.trim().to_string());
+        }
+    }
+
+    if let Some((text, tag)) = message
+        .strip_suffix(']')
+        .and_then(|m| m.rsplit_once(" ["))
+    {
+        if let Some((level, rule)) = tag.split_once('/') {
+            let severity = match level {
+                "Error" => DiagnosticSeverity::Error,
+                _ => DiagnosticSeverity::Warning,
+            };
+            return (severity, Some(rule.to_string()), text.trim().to_string());
+        }
+    }
+
+    (default_severity, None, message.to_string())
+}
+
+fn normalize_path(path: &str) -> String {
+    path.trim_start_matches("/workspace/")
+        .trim_start_matches("./")
+        .to_string()
+}
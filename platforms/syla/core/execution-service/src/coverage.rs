@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+
+use crate::docker::{DockerExecutor, ExecutionInput, ExecutionResult, ARTIFACTS_MOUNT};
+use crate::models::{CoverageCounts, CoverageOptions, CoverageReport, CreateExecutionRequest, FileCoverage};
+
+/// Name of the lcov report each coverage command leaves in the artifacts mount
+const LCOV_FILE: &str = "lcov.info";
+
+/// Command that runs `main.<ext>` under the language's coverage tool and
+/// writes an lcov report, exiting with the program's own status
+pub fn coverage_command(language: &str) -> Option<String> {
+    let lcov = format!("{}/{}", ARTIFACTS_MOUNT, LCOV_FILE);
+    match language {
+        "python" => Some(format!(
+            "export COVERAGE_FILE=/tmp/.coverage; python -m coverage run main.py; status=$?; \
+             python -m coverage lcov -q -o {}; exit $status",
+            lcov
+        )),
+        "javascript" => Some(format!(
+            "npx --no-install c8 --reporter=lcovonly --report-dir={} --temp-directory=/tmp/c8 node main.js",
+            ARTIFACTS_MOUNT
+        )),
+        "rust" => Some(format!(
+            "rustc --edition 2021 -C instrument-coverage -o /tmp/main main.rs \
+             && LLVM_PROFILE_FILE=/tmp/main.profraw /tmp/main; status=$?; \
+             llvm-profdata merge -sparse /tmp/main.profraw -o /tmp/main.profdata \
+             && llvm-cov export -format=lcov -instr-profile=/tmp/main.profdata /tmp/main > {}; \
+             exit $status",
+            lcov
+        )),
+        _ => None,
+    }
+}
+
+/// Run a submission under coverage. The report is `None` when the tool
+/// produced nothing, e.g. because the program failed to compile.
+pub async fn run_with_coverage(
+    executor: &DockerExecutor,
+    request: &CreateExecutionRequest,
+    options: &CoverageOptions,
+) -> Result<(ExecutionResult, Option<CoverageReport>)> {
+    let command = coverage_command(&request.language).with_context(|| {
+        format!("Coverage is not supported for language '{}'", request.language)
+    })?;
+
+    let mut result = executor
+        .execute_with_input(
+            &request.code,
+            &request.language,
+            request.timeout_seconds.unwrap_or(30),
+            request.max_output_bytes,
+            ExecutionInput {
+                stdin: None,
+                command: Some(command),
+                collect_artifacts: true,
+                image: request.docker_image(),
+            },
+        )
+        .await?;
+
+    let report = result.artifacts.remove(LCOV_FILE).map(|lcov| {
+        let mut report = parse_lcov(&lcov);
+        if options.include_lcov {
+            report.lcov = Some(lcov);
+        }
+        report
+    });
+
+    Ok((result, report))
+}
+
+/// Summarize an lcov tracefile per source file and overall
+fn parse_lcov(lcov: &str) -> CoverageReport {
+    let mut report = CoverageReport::default();
+    let mut file = FileCoverage::default();
+    let mut line_hits: Vec<(u32, u64)> = Vec::new();
+
+    for line in lcov.lines() {
+        let (tag, value) = line.split_once(':').unwrap_or((line, ""));
+        let number = || value.trim().parse::<u64>().unwrap_or(0);
+        match tag {
+            "SF" => file.file = value.trim_start_matches("/workspace/").to_string(),
+            "DA" => {
+                let mut fields = value.split(',');
+                let line_no = fields.next().and_then(|v| v.parse().ok());
+                let hits = fields.next().and_then(|v| v.parse().ok());
+                if let (Some(line_no), Some(hits)) = (line_no, hits) {
+                    line_hits.push((line_no, hits));
+                }
+            }
+            "LF" => file.lines.total = number(),
+            "LH" => file.lines.covered = number(),
+            "FNF" => file.functions.total = number(),
+            "FNH" => file.functions.covered = number(),
+            "BRF" => file.branches.total = number(),
+            "BRH" => file.branches.covered = number(),
+            "end_of_record" => {
+                // Not every tool writes LF/LH; fall back to the DA records
+                if file.lines.total == 0 {
+                    file.lines.total = line_hits.len() as u64;
+                    file.lines.covered = line_hits.iter().filter(|(_, hits)| *hits > 0).count() as u64;
+                }
+                file.uncovered_lines = line_hits
+                    .drain(..)
+                    .filter(|(_, hits)| *hits == 0)
+                    .map(|(line_no, _)| line_no)
+                    .collect();
+                file.uncovered_lines.sort_unstable();
+                file.uncovered_lines.dedup();
+
+                for counts in [&mut file.lines, &mut file.functions, &mut file.branches] {
+                    counts.percent = percent(counts.covered, counts.total);
+                }
+                report.lines = add(report.lines, file.lines);
+                report.functions = add(report.functions, file.functions);
+                report.branches = add(report.branches, file.branches);
+                report.files.push(std::mem::take(&mut file));
+            }
+            _ => {}
+        }
+    }
+
+    report
+}
+
+fn add(a: CoverageCounts, b: CoverageCounts) -> CoverageCounts {
+    let covered = a.covered + b.covered;
+    let total = a.total + b.total;
+    CoverageCounts {
+        covered,
+        total,
+        percent: percent(covered, total),
+    }
+}
+
+fn percent(covered: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (covered as f64 / total as f64 * 1000.0).round() / 10.0
+    }
+}
@@ -1,11 +1,20 @@
 use anyhow::{Context, Result};
 use std::collections::HashMap;
-use std::path::Path;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::process::Stdio;
 use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::process::Command as TokioCommand;
 use uuid::Uuid;
 
+/// How often a running container's cgroup is sampled for resource usage
+const USAGE_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where programs write files to hand back, when artifacts are collected
+pub const ARTIFACTS_MOUNT: &str = "/.syla-artifacts";
+
 pub struct DockerClient {
     // Future: connection pool, etc
 }
@@ -18,71 +27,261 @@ pub struct ContainerConfig {
     pub memory_limit: Option<u64>,
     pub cpu_limit: Option<f64>,
     pub timeout_seconds: Option<u64>,
+    pub output_limit: Option<usize>,
+    pub stdin: Option<String>,
+    /// Host directory mounted writable at `ARTIFACTS_MOUNT`
+    pub artifacts_dir: Option<PathBuf>,
+}
+
+/// Extra inputs for a run beyond the submitted code
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionInput {
+    /// Data piped to the program's stdin
+    pub stdin: Option<String>,
+    /// Shell command to run instead of the language's default entrypoint
+    pub command: Option<String>,
+    /// Mount a writable directory at `ARTIFACTS_MOUNT` and return the files
+    /// the program leaves in it
+    pub collect_artifacts: bool,
+    /// Image to run in instead of the language's runtime image; must
+    /// already be pulled
+    pub image: Option<String>,
+}
+
+/// Per-stream caps on captured stdout/stderr
+#[derive(Debug, Clone, Copy)]
+pub struct OutputLimits {
+    /// Applied when a request doesn't ask for a specific limit
+    pub default_bytes: usize,
+    /// Upper bound on what a request may ask for
+    pub max_bytes: usize,
+}
+
+impl OutputLimits {
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: usize| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        let max_bytes = read("MAX_OUTPUT_BYTES", 10 * 1024 * 1024);
+        Self {
+            default_bytes: read("DEFAULT_OUTPUT_BYTES", 1024 * 1024).min(max_bytes),
+            max_bytes,
+        }
+    }
+
+    /// Effective limit for a request, clamped to the server maximum
+    pub fn resolve(&self, requested: Option<u64>) -> usize {
+        requested
+            .map(|r| usize::try_from(r).unwrap_or(usize::MAX))
+            .unwrap_or(self.default_bytes)
+            .min(self.max_bytes)
+    }
 }
 
 // Legacy DockerExecutor for backward compatibility
 pub struct DockerExecutor {
     client: DockerClient,
+    output_limits: OutputLimits,
 }
 
 impl DockerExecutor {
-    pub fn new() -> Result<Self> {
+    pub fn new(output_limits: OutputLimits) -> Result<Self> {
         // Verify Docker is available
         Command::new("docker")
             .arg("--version")
             .output()
             .context("Docker not found. Please install Docker.")?;
         
-        Ok(Self {
+        Ok(Self::unchecked(output_limits))
+    }
+
+    /// For workers whose runs all go to another backend, where Docker may
+    /// not be installed; runs that still ask for Docker fail individually
+    pub fn unchecked(output_limits: OutputLimits) -> Self {
+        Self {
             client: DockerClient {},
-        })
+            output_limits,
+        }
     }
-    
+
     pub async fn execute(
         &self,
         code: &str,
         language: &str,
         timeout_seconds: u64,
+        max_output_bytes: Option<u64>,
     ) -> Result<ExecutionResult> {
-        let temp_dir = tempfile::tempdir()?;
+        self.execute_with_input(code, language, timeout_seconds, max_output_bytes, ExecutionInput::default())
+            .await
+    }
+
+    pub async fn execute_with_input(
+        &self,
+        code: &str,
+        language: &str,
+        timeout_seconds: u64,
+        max_output_bytes: Option<u64>,
+        input: ExecutionInput,
+    ) -> Result<ExecutionResult> {
+        let temp_dir = scratch_dir()?;
         let file_extension = match language {
             "python" => "py",
             "javascript" => "js",
             "go" => "go",
+            "rust" => "rs",
             _ => "txt",
         };
         
         let file_path = temp_dir.path().join(format!("main.{}", file_extension));
         std::fs::write(&file_path, code)?;
+
+        let artifacts_dir = if input.collect_artifacts {
+            let dir = scratch_dir()?;
+            std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o777))?;
+            Some(dir)
+        } else {
+            None
+        };
         
         let config = ContainerConfig {
-            image: match language {
-                "python" => "python:3.11-slim",
-                "javascript" => "node:20-slim",
-                "go" => "golang:1.21-alpine",
-                _ => "ubuntu:22.04",
-            }.to_string(),
-            command: match language {
-                "python" => vec!["python".to_string(), "main.py".to_string()],
-                "javascript" => vec!["node".to_string(), "main.js".to_string()],
-                "go" => vec!["go".to_string(), "run".to_string(), "main.go".to_string()],
-                _ => vec![],
+            image: input.image.unwrap_or_else(|| runtime_image(language).to_string()),
+            command: match (input.command, language) {
+                (Some(command), _) => vec!["sh".to_string(), "-c".to_string(), command],
+                (None, "python") => vec!["python".to_string(), "main.py".to_string()],
+                (None, "javascript") => vec!["node".to_string(), "main.js".to_string()],
+                (None, "go") => vec!["go".to_string(), "run".to_string(), "main.go".to_string()],
+                (None, "rust") => vec![
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    "rustc -o /tmp/main main.rs && exec /tmp/main".to_string(),
+                ],
+                (None, _) => vec![],
             },
             environment: HashMap::new(),
             working_dir: "/workspace".to_string(),
             memory_limit: Some(512 * 1024 * 1024),
             cpu_limit: Some(1.0),
             timeout_seconds: Some(timeout_seconds),
+            output_limit: Some(self.output_limits.resolve(max_output_bytes)),
+            stdin: input.stdin,
+            artifacts_dir: artifacts_dir.as_ref().map(|d| d.path().to_path_buf()),
         };
         
-        self.client.run_container(
+        let mut result = self.client.run_container(
             &format!("syla-exec-{}", Uuid::new_v4()),
             config,
             Some(temp_dir.path()),
-        ).await
+        ).await?;
+
+        if let Some(dir) = &artifacts_dir {
+            result.artifacts = read_artifacts(dir.path(), ARTIFACT_MAX_FILE_BYTES, ARTIFACT_MAX_TOTAL_BYTES);
+        }
+        Ok(result)
     }
 }
 
+/// Prefix of per-run scratch directories, so leftovers from a crash can be
+/// told apart from other temp files and cleaned up
+pub const SCRATCH_PREFIX: &str = "syla-exec-";
+
+/// Temporary directory for a single run, removed when dropped
+pub fn scratch_dir() -> std::io::Result<tempfile::TempDir> {
+    tempfile::Builder::new().prefix(SCRATCH_PREFIX).tempdir()
+}
+
+/// Languages with a dedicated runtime image
+pub const SUPPORTED_LANGUAGES: &[&str] = &["python", "javascript", "go", "rust"];
+
+/// Runtime images by language and version; each language's first entry is
+/// what runs when a request doesn't pin a version
+const RUNTIME_IMAGES: &[(&str, &str, &str)] = &[
+    ("python", "3.11", "python:3.11-slim"),
+    ("python", "3.10", "python:3.10-slim"),
+    ("python", "3.12", "python:3.12-slim"),
+    ("python", "3.13", "python:3.13-slim"),
+    ("javascript", "20", "node:20-slim"),
+    ("javascript", "18", "node:18-slim"),
+    ("javascript", "22", "node:22-slim"),
+    ("go", "1.21", "golang:1.21-alpine"),
+    ("go", "1.22", "golang:1.22-alpine"),
+    ("go", "1.23", "golang:1.23-alpine"),
+    ("rust", "1.75", "rust:1.75-slim"),
+    ("rust", "1.80", "rust:1.80-slim"),
+    ("rust", "1.83", "rust:1.83-slim"),
+];
+
+/// Container image used to run code for a given language
+pub fn runtime_image(language: &str) -> &'static str {
+    RUNTIME_IMAGES
+        .iter()
+        .find(|(l, _, _)| *l == language)
+        .map(|(_, _, image)| *image)
+        .unwrap_or("ubuntu:22.04")
+}
+
+/// Image for a pinned runtime version, if the service supports it
+pub fn versioned_image(language: &str, version: &str) -> Option<&'static str> {
+    RUNTIME_IMAGES
+        .iter()
+        .find(|(l, v, _)| *l == language && *v == version)
+        .map(|(_, _, image)| *image)
+}
+
+/// Runtime version used when a request doesn't pin one
+pub fn default_runtime_version(language: &str) -> Option<&'static str> {
+    RUNTIME_IMAGES.iter().find(|(l, _, _)| *l == language).map(|(_, version, _)| *version)
+}
+
+/// Versions a language can be pinned to, oldest first
+pub fn runtime_versions(language: &str) -> Vec<&'static str> {
+    let mut versions: Vec<&str> = RUNTIME_IMAGES
+        .iter()
+        .filter(|(l, _, _)| *l == language)
+        .map(|(_, version, _)| *version)
+        .collect();
+    versions.sort_by_key(|version| version.split('.').map(|part| part.parse::<u32>().unwrap_or(0)).collect::<Vec<_>>());
+    versions
+}
+
+/// A container started by this service, as listed by `docker ps`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ContainerSummary {
+    pub name: String,
+    pub image: String,
+    pub status: String,
+    pub running_for: String,
+}
+
+/// Running containers whose names start with `prefix`
+pub async fn list_containers(prefix: &str) -> Result<Vec<ContainerSummary>> {
+    let output = TokioCommand::new("docker")
+        .args(["ps", "--filter"])
+        .arg(format!("name=^{}", prefix))
+        .args(["--format", "{{.Names}}\t{{.Image}}\t{{.Status}}\t{{.RunningFor}}"])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .context("Failed to run docker ps")?;
+    if !output.status.success() {
+        anyhow::bail!("docker ps failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            Some(ContainerSummary {
+                name: fields.next()?.to_string(),
+                image: fields.next()?.to_string(),
+                status: fields.next()?.to_string(),
+                running_for: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
 
 #[derive(Debug)]
 pub struct ExecutionResult {
@@ -91,6 +290,64 @@ pub struct ExecutionResult {
     pub stderr: String,
     pub duration_ms: u64,
     pub timed_out: bool,
+    pub stdout_bytes: u64,
+    pub stderr_bytes: u64,
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+    /// CPU time reported by the container's cgroup, if it could be read
+    pub cpu_time_ms: Option<u64>,
+    /// Peak memory reported by the container's cgroup, if it could be read
+    pub peak_memory_bytes: Option<u64>,
+    /// Files left in the artifacts mount, by file name
+    pub artifacts: HashMap<String, String>,
+}
+
+/// Output read from a stream, keeping at most the configured number of bytes
+#[derive(Debug, Default)]
+pub struct CapturedOutput {
+    pub data: Vec<u8>,
+    pub total_bytes: u64,
+}
+
+impl CapturedOutput {
+    pub fn truncated(&self) -> bool {
+        self.total_bytes > self.data.len() as u64
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.data).to_string()
+    }
+
+    /// Apply a limit to output that was already fully buffered
+    pub fn from_string(output: String, limit: usize) -> Self {
+        let total_bytes = output.len() as u64;
+        let mut data = output.into_bytes();
+        data.truncate(limit);
+        Self { data, total_bytes }
+    }
+
+    /// Take the size of the output from whoever cut it short before us
+    pub fn with_total(mut self, total_bytes: u64) -> Self {
+        self.total_bytes = self.total_bytes.max(total_bytes);
+        self
+    }
+}
+
+/// Drain a stream to completion, buffering at most `limit` bytes. Anything
+/// past the limit is counted and discarded so the writer never blocks.
+pub(crate) async fn capture<R: AsyncRead + Unpin>(mut reader: R, limit: usize) -> std::io::Result<CapturedOutput> {
+    let mut output = CapturedOutput::default();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        output.total_bytes += n as u64;
+        let room = limit.saturating_sub(output.data.len());
+        output.data.extend_from_slice(&buf[..n.min(room)]);
+    }
+    Ok(output)
 }
 
 impl DockerClient {
@@ -104,6 +361,7 @@ impl DockerClient {
         Ok(Self {})
     }
     
+    #[tracing::instrument(skip(self, config, mount_path), fields(image = %config.image))]
     pub async fn run_container(
         &self,
         name: &str,
@@ -114,11 +372,24 @@ impl DockerClient {
         cmd.arg("run")
             .arg("--rm")
             .arg("--name").arg(name);
+
+        // Keep stdin attached when there is input to feed the program
+        if config.stdin.is_some() {
+            cmd.arg("-i");
+        }
             
         // Add volume mount if provided
         if let Some(path) = mount_path {
             cmd.arg("-v").arg(format!("{}:{}:ro", path.display(), config.working_dir));
         }
+
+        // Docker writes the container ID here, which locates its cgroup
+        let cid_dir = scratch_dir()?;
+        let cidfile = cid_dir.path().join("cid");
+        cmd.arg("--cidfile").arg(&cidfile);
+        if let Some(dir) = &config.artifacts_dir {
+            cmd.arg("-v").arg(format!("{}:{}", dir.display(), ARTIFACTS_MOUNT));
+        }
         
         // Set working directory
         cmd.arg("-w").arg(&config.working_dir);
@@ -140,41 +411,286 @@ impl DockerClient {
         cmd.arg(&config.image);
         cmd.args(&config.command);
         
-        // Execute with timeout
+        // Execute with timeout, streaming output through capped buffers
         let start = std::time::Instant::now();
         let timeout = config.timeout_seconds.unwrap_or(30);
-        let output = tokio::time::timeout(
-            Duration::from_secs(timeout),
-            cmd.output()
-        ).await;
+        let limit = config.output_limit.unwrap_or(usize::MAX);
+
+        let mut child = cmd
+            .stdin(if config.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        if let (Some(input), Some(mut stdin)) = (config.stdin, child.stdin.take()) {
+            // Dropping the handle afterwards closes the pipe and signals EOF
+            tokio::spawn(async move {
+                let _ = stdin.write_all(input.as_bytes()).await;
+            });
+        }
+        let stdout = tokio::spawn(capture(child.stdout.take().context("No stdout pipe")?, limit));
+        let stderr = tokio::spawn(capture(child.stderr.take().context("No stderr pipe")?, limit));
+        let (stop_sampling, stopped) = tokio::sync::oneshot::channel();
+        let usage = tokio::spawn(sample_usage(cidfile, stopped));
+
+        let status = tokio::time::timeout(Duration::from_secs(timeout), child.wait()).await;
+        let _ = stop_sampling.send(());
         
+        let timed_out = status.is_err();
+        if timed_out {
+            // Timeout - try to kill container
+            let _ = Command::new("docker")
+                .args(["kill", name])
+                .output();
+            let _ = child.kill().await;
+        }
+
+        let stdout = stdout.await??;
+        let mut stderr = stderr.await??;
         let duration_ms = start.elapsed().as_millis() as u64;
-        
-        match output {
-            Ok(Ok(output)) => {
-                Ok(ExecutionResult {
-                    exit_code: output.status.code().unwrap_or(-1),
-                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                    stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                    duration_ms,
-                    timed_out: false,
-                })
-            }
-            Ok(Err(e)) => Err(e.into()),
+
+        let stderr_truncated = stderr.truncated();
+
+        let exit_code = match status {
+            Ok(status) => status?.code().unwrap_or(-1),
             Err(_) => {
-                // Timeout - try to kill container
-                let _ = Command::new("docker")
-                    .args(&["kill", name])
-                    .output();
-                    
-                Ok(ExecutionResult {
-                    exit_code: -1,
-                    stdout: String::new(),
-                    stderr: "Execution timed out".to_string(),
-                    duration_ms,
-                    timed_out: true,
-                })
+                stderr.data.extend_from_slice(b"Execution timed out");
+                -1
             }
+        };
+
+        let usage = usage.await?;
+
+        Ok(ExecutionResult {
+            exit_code,
+            stdout: stdout.text(),
+            stderr: stderr.text(),
+            duration_ms,
+            timed_out,
+            stdout_bytes: stdout.total_bytes,
+            stderr_bytes: stderr.total_bytes,
+            stdout_truncated: stdout.truncated(),
+            stderr_truncated,
+            cpu_time_ms: usage.cpu_usec.map(|us| us / 1000),
+            peak_memory_bytes: usage.peak_memory_bytes,
+            artifacts: HashMap::new(),
+        })
+    }
+}
+
+/// Largest artifact returned; bigger files are dropped
+const ARTIFACT_MAX_FILE_BYTES: u64 = 8 * 1024 * 1024;
+/// Cap on all artifacts of one run together
+const ARTIFACT_MAX_TOTAL_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Read the top-level files a run left in its artifacts directory, skipping
+/// links (which could point anywhere on the host) and files over the caps
+fn read_artifacts(dir: &Path, max_file: u64, max_total: u64) -> HashMap<String, String> {
+    let mut artifacts = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return artifacts;
+    };
+    let mut total = 0;
+    for entry in entries.flatten() {
+        if !entry.file_type().is_ok_and(|t| t.is_file()) {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Read one byte past the cap so growth after a size check still shows
+        let mut contents = Vec::new();
+        let read = std::fs::File::open(entry.path())
+            .and_then(|file| std::io::Read::read_to_end(&mut std::io::Read::take(file, max_file + 1), &mut contents));
+        if read.is_err() {
+            continue;
+        }
+        let size = contents.len() as u64;
+        if size > max_file || total + size > max_total {
+            tracing::warn!("Dropping artifact {} over the size limit", name);
+            continue;
+        }
+        total += size;
+        artifacts.insert(name, String::from_utf8_lossy(&contents).to_string());
+    }
+    artifacts
+}
+
+/// Resources a container has used so far, as read from its cgroup
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Usage {
+    cpu_usec: Option<u64>,
+    peak_memory_bytes: Option<u64>,
+}
+
+/// A container's cgroup on the host, in either the v2 or v1 layout
+#[derive(Debug)]
+enum Cgroup {
+    V2(PathBuf),
+    V1 { cpuacct: PathBuf, memory: PathBuf },
+}
+
+impl Cgroup {
+    /// Look the container up under the systemd and cgroupfs drivers' paths
+    fn find(root: &Path, id: &str) -> Option<Self> {
+        let scopes = [format!("system.slice/docker-{}.scope", id), format!("docker/{}", id)];
+        if let Some(dir) = scopes.iter().map(|s| root.join(s)).find(|d| d.join("cpu.stat").is_file()) {
+            return Some(Self::V2(dir));
+        }
+        let v1 = |controller: &str, file: &str| {
+            scopes
+                .iter()
+                .map(|s| root.join(controller).join(s))
+                .find(|d| d.join(file).is_file())
+        };
+        Some(Self::V1 {
+            cpuacct: v1("cpuacct", "cpuacct.usage")?,
+            memory: v1("memory", "memory.max_usage_in_bytes")?,
+        })
+    }
+
+    /// Fold the current counters into `usage`. Reads fail once the container
+    /// is gone, leaving the last sample in place.
+    fn sample(&self, usage: &mut Usage) {
+        let read = |path: PathBuf| std::fs::read_to_string(path).ok();
+        let number = |path: PathBuf| read(path).and_then(|v| v.trim().parse::<u64>().ok());
+        let (cpu_usec, memory) = match self {
+            Self::V2(dir) => (
+                read(dir.join("cpu.stat")).and_then(|stat| {
+                    stat.lines()
+                        .find_map(|line| line.strip_prefix("usage_usec "))
+                        .and_then(|v| v.trim().parse().ok())
+                }),
+                // memory.peak needs Linux 5.19; fall back to sampled usage
+                number(dir.join("memory.peak")).or_else(|| number(dir.join("memory.current"))),
+            ),
+            Self::V1 { cpuacct, memory } => (
+                number(cpuacct.join("cpuacct.usage")).map(|ns| ns / 1000),
+                number(memory.join("memory.max_usage_in_bytes")),
+            ),
+        };
+        if cpu_usec.is_some() {
+            usage.cpu_usec = cpu_usec;
+        }
+        if let Some(memory) = memory {
+            usage.peak_memory_bytes = Some(usage.peak_memory_bytes.unwrap_or(0).max(memory));
         }
     }
-}
\ No newline at end of file
+}
+
+/// Sample a container's cgroup from the host until `stop` fires, so nothing
+/// inside the container can touch the figures. They are unset when the
+/// cgroup isn't visible, e.g. with a remote or rootless Docker daemon, and
+/// miss whatever the container used after the last sample.
+async fn sample_usage(cidfile: PathBuf, mut stop: tokio::sync::oneshot::Receiver<()>) -> Usage {
+    let root = Path::new("/sys/fs/cgroup");
+    let mut usage = Usage::default();
+    let mut cgroup = None;
+    loop {
+        if cgroup.is_none() {
+            cgroup = std::fs::read_to_string(&cidfile)
+                .ok()
+                .filter(|id| !id.trim().is_empty())
+                .and_then(|id| Cgroup::find(root, id.trim()));
+        }
+        if let Some(cgroup) = &cgroup {
+            cgroup.sample(&mut usage);
+        }
+        tokio::select! {
+            _ = &mut stop => return usage,
+            _ = tokio::time::sleep(USAGE_SAMPLE_INTERVAL) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: PathBuf, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn v2_cgroups_report_cpu_and_the_highest_memory_seen() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("system.slice/docker-abc.scope");
+        write(dir.join("cpu.stat"), "usage_usec 2500\nuser_usec 2000\n");
+        write(dir.join("memory.current"), "4096\n");
+
+        let cgroup = Cgroup::find(root.path(), "abc").unwrap();
+        let mut usage = Usage::default();
+        cgroup.sample(&mut usage);
+        write(dir.join("memory.current"), "1024\n");
+        cgroup.sample(&mut usage);
+        assert_eq!(usage, Usage { cpu_usec: Some(2500), peak_memory_bytes: Some(4096) });
+
+        // Gone with the container; the last sample stands
+        std::fs::remove_dir_all(&dir).unwrap();
+        cgroup.sample(&mut usage);
+        assert_eq!(usage, Usage { cpu_usec: Some(2500), peak_memory_bytes: Some(4096) });
+    }
+
+    #[test]
+    fn v1_cgroups_are_found_per_controller() {
+        let root = tempfile::tempdir().unwrap();
+        write(root.path().join("cpuacct/docker/abc/cpuacct.usage"), "3000000\n");
+        write(root.path().join("memory/docker/abc/memory.max_usage_in_bytes"), "8192\n");
+
+        let mut usage = Usage::default();
+        Cgroup::find(root.path(), "abc").unwrap().sample(&mut usage);
+        assert_eq!(usage, Usage { cpu_usec: Some(3000), peak_memory_bytes: Some(8192) });
+        assert!(Cgroup::find(root.path(), "other").is_none());
+    }
+
+    #[test]
+    fn artifacts_over_the_caps_and_links_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("small"), "ok").unwrap();
+        std::fs::write(dir.path().join("large"), "x".repeat(20)).unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::os::unix::fs::symlink("/etc/hostname", dir.path().join("link")).unwrap();
+
+        let artifacts = read_artifacts(dir.path(), 10, 100);
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts["small"], "ok");
+
+        std::fs::write(dir.path().join("other"), "12345678").unwrap();
+        let artifacts = read_artifacts(dir.path(), 10, 9);
+        assert_eq!(artifacts.len(), 1);
+    }
+
+    #[test]
+    fn request_limits_are_clamped_to_the_maximum() {
+        let limits = OutputLimits { default_bytes: 10, max_bytes: 100 };
+        assert_eq!(limits.resolve(None), 10);
+        assert_eq!(limits.resolve(Some(50)), 50);
+        assert_eq!(limits.resolve(Some(u64::MAX)), 100);
+    }
+
+    async fn run(code: &str, language: &str) -> ExecutionResult {
+        let limits = OutputLimits { default_bytes: 1024, max_bytes: 1024 };
+        DockerExecutor::new(limits).unwrap().execute(code, language, 120, None).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Docker"]
+    async fn python_runs_with_the_default_command() {
+        let result = run("print('hello')", "python").await;
+        assert_eq!((result.exit_code, result.stdout.as_str()), (0, "hello\n"), "{}", result.stderr);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Docker"]
+    async fn go_runs_with_the_default_command() {
+        let result = run("package main\nimport \"fmt\"\nfunc main() { fmt.Println(\"hello\") }\n", "go").await;
+        assert_eq!((result.exit_code, result.stdout.as_str()), (0, "hello\n"), "{}", result.stderr);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Docker"]
+    async fn rust_is_compiled_and_run_with_the_default_command() {
+        let result = run("fn main() { println!(\"hello\"); }\n", "rust").await;
+        assert_eq!((result.exit_code, result.stdout.as_str()), (0, "hello\n"), "{}", result.stderr);
+    }
+}
@@ -1,16 +1,40 @@
+//! Errors the service returns, and how clients see them.
+//!
+//! Every error has an [`ErrorCode`] from a fixed catalog. REST responses
+//! carry it as an RFC 7807 `application/problem+json` body; gRPC responses
+//! use the matching status code, with the same problem document as the
+//! status details. Clients can branch on `code` and `retryable` without
+//! parsing messages. Storage and internal failures are logged here and
+//! reach the client only as their code.
+
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// Media type of a problem document
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
 #[derive(Error, Debug)]
 pub enum ServiceError {
     #[error("Not found")]
     NotFound,
 
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Service overloaded: {reason}")]
+    Overloaded {
+        reason: String,
+        retry_after_seconds: u64,
+    },
+
     #[error("Redis error: {0}")]
     Redis(#[from] redis::RedisError),
 
@@ -21,19 +45,162 @@ pub enum ServiceError {
     Internal(#[from] anyhow::Error),
 }
 
-impl IntoResponse for ServiceError {
-    fn into_response(self) -> Response {
-        let (status, message) = match self {
-            ServiceError::NotFound => (StatusCode::NOT_FOUND, "Not found"),
-            ServiceError::Redis(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
-            ServiceError::Serialization(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Serialization error"),
-            ServiceError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal error"),
+/// The catalog of errors clients can tell apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    InvalidRequest,
+    Forbidden,
+    Overloaded,
+    StorageUnavailable,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::InvalidRequest => "invalid_request",
+            ErrorCode::Forbidden => "forbidden",
+            ErrorCode::Overloaded => "overloaded",
+            ErrorCode::StorageUnavailable => "storage_unavailable",
+            ErrorCode::Internal => "internal",
+        }
+    }
+
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::InvalidRequest => StatusCode::BAD_REQUEST,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::Overloaded => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::StorageUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn grpc_code(self) -> tonic::Code {
+        match self {
+            ErrorCode::NotFound => tonic::Code::NotFound,
+            ErrorCode::InvalidRequest => tonic::Code::InvalidArgument,
+            ErrorCode::Forbidden => tonic::Code::PermissionDenied,
+            ErrorCode::Overloaded => tonic::Code::ResourceExhausted,
+            ErrorCode::StorageUnavailable => tonic::Code::Unavailable,
+            ErrorCode::Internal => tonic::Code::Internal,
+        }
+    }
+
+    /// Short summary, the same for every occurrence
+    pub fn title(self) -> &'static str {
+        match self {
+            ErrorCode::NotFound => "Not found",
+            ErrorCode::InvalidRequest => "Invalid request",
+            ErrorCode::Forbidden => "Forbidden",
+            ErrorCode::Overloaded => "Service overloaded",
+            ErrorCode::StorageUnavailable => "Storage unavailable",
+            ErrorCode::Internal => "Internal error",
+        }
+    }
+
+    /// Whether sending the same request again may succeed
+    pub fn retryable(self) -> bool {
+        matches!(self, ErrorCode::Overloaded | ErrorCode::StorageUnavailable)
+    }
+}
+
+/// An RFC 7807 problem document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    /// What went wrong this time, fit to show a user
+    pub detail: String,
+    pub code: ErrorCode,
+    pub retryable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_seconds: Option<u64>,
+}
+
+impl ServiceError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ServiceError::NotFound => ErrorCode::NotFound,
+            ServiceError::BadRequest(_) => ErrorCode::InvalidRequest,
+            ServiceError::Forbidden(_) => ErrorCode::Forbidden,
+            ServiceError::Overloaded { .. } => ErrorCode::Overloaded,
+            ServiceError::Redis(_) => ErrorCode::StorageUnavailable,
+            ServiceError::Serialization(_) | ServiceError::Internal(_) => ErrorCode::Internal,
+        }
+    }
+
+    pub fn problem(&self) -> Problem {
+        let code = self.code();
+        let detail = match self {
+            ServiceError::NotFound => "The requested resource does not exist".to_string(),
+            ServiceError::BadRequest(msg) | ServiceError::Forbidden(msg) => msg.clone(),
+            ServiceError::Overloaded { reason, .. } => reason.clone(),
+            ServiceError::Redis(_) => "The execution store is unavailable; try again shortly".to_string(),
+            ServiceError::Serialization(_) | ServiceError::Internal(_) => {
+                "The service failed to handle the request".to_string()
+            }
         };
+        let retry_after_seconds = match self {
+            ServiceError::Overloaded { retry_after_seconds, .. } => Some(*retry_after_seconds),
+            _ => None,
+        };
+        Problem {
+            problem_type: format!("urn:syla:error:{}", code.as_str()),
+            title: code.title().to_string(),
+            status: code.status().as_u16(),
+            detail,
+            code,
+            retryable: code.retryable(),
+            retry_after_seconds,
+        }
+    }
 
-        let body = Json(json!({
-            "error": message,
-        }));
+    /// Failures whose cause stays out of the response
+    fn log_cause(&self) {
+        if matches!(
+            self,
+            ServiceError::Redis(_) | ServiceError::Serialization(_) | ServiceError::Internal(_)
+        ) {
+            tracing::error!(code = self.code().as_str(), "{}", self);
+        }
+    }
+}
+
+impl IntoResponse for ServiceError {
+    fn into_response(self) -> Response {
+        self.log_cause();
+        let problem = self.problem();
+        let status = self.code().status();
+        let mut response = (status, Json(&problem)).into_response();
+        let headers = response.headers_mut();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        // Tell clients when capacity is expected to free up
+        if let Some(seconds) = problem.retry_after_seconds {
+            headers.insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
+    }
+}
 
-        (status, body).into_response()
+impl From<ServiceError> for tonic::Status {
+    fn from(error: ServiceError) -> Self {
+        error.log_cause();
+        let problem = error.problem();
+        let details = serde_json::to_vec(&problem).unwrap_or_default();
+        let mut status = tonic::Status::with_details(error.code().grpc_code(), problem.detail, details.into());
+        if let Ok(code) = problem.code.as_str().parse() {
+            status.metadata_mut().insert("syla-error-code", code);
+        }
+        if let Some(seconds) = problem.retry_after_seconds {
+            status.metadata_mut().insert("retry-after", seconds.into());
+        }
+        status
     }
-}
\ No newline at end of file
+}
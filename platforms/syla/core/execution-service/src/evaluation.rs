@@ -0,0 +1,185 @@
+use crate::docker::{DockerExecutor, ExecutionInput};
+use crate::models::{
+    CreateExecutionRequest, OutputComparison, TestReport, TestSpec, TestStatus, TestVerdict,
+};
+
+/// Run a submission against its test spec, one container per case.
+/// Stdin/expected-output cases run first, followed by the test command.
+#[tracing::instrument(skip_all, fields(cases = spec.cases.len()))]
+pub async fn run_tests(
+    executor: &DockerExecutor,
+    request: &CreateExecutionRequest,
+    spec: &TestSpec,
+) -> TestReport {
+    let timeout_seconds = request.timeout_seconds.unwrap_or(30);
+    let mut verdicts = Vec::with_capacity(spec.cases.len() + 1);
+
+    for (index, case) in spec.cases.iter().enumerate() {
+        let name = case
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("case-{}", index + 1));
+        let input = ExecutionInput {
+            stdin: Some(case.stdin.clone()),
+            command: None,
+            collect_artifacts: false,
+            image: request.docker_image(),
+        };
+
+        let verdict = match executor
+            .execute_with_input(
+                &request.code,
+                &request.language,
+                timeout_seconds,
+                request.max_output_bytes,
+                input,
+            )
+            .await
+        {
+            Ok(result) => {
+                let status = if result.timed_out {
+                    TestStatus::Timeout
+                } else if result.exit_code != 0 {
+                    TestStatus::Error
+                } else if outputs_match(&case.expected_output, &result.stdout, spec.comparison) {
+                    TestStatus::Passed
+                } else {
+                    TestStatus::Failed
+                };
+                let diff = matches!(status, TestStatus::Failed)
+                    .then(|| line_diff(&case.expected_output, &result.stdout));
+
+                TestVerdict {
+                    name,
+                    status,
+                    duration_ms: result.duration_ms,
+                    exit_code: Some(result.exit_code),
+                    expected_output: Some(case.expected_output.clone()),
+                    actual_output: result.stdout,
+                    stderr: result.stderr,
+                    diff,
+                }
+            }
+            Err(e) => TestVerdict::errored(name, e),
+        };
+        verdicts.push(verdict);
+    }
+
+    if let Some(command) = &spec.command {
+        let input = ExecutionInput {
+            stdin: None,
+            command: Some(command.clone()),
+            collect_artifacts: false,
+            image: request.docker_image(),
+        };
+
+        let verdict = match executor
+            .execute_with_input(
+                &request.code,
+                &request.language,
+                timeout_seconds,
+                request.max_output_bytes,
+                input,
+            )
+            .await
+        {
+            Ok(result) => TestVerdict {
+                name: "command".to_string(),
+                status: if result.timed_out {
+                    TestStatus::Timeout
+                } else if result.exit_code == 0 {
+                    TestStatus::Passed
+                } else {
+                    TestStatus::Failed
+                },
+                duration_ms: result.duration_ms,
+                exit_code: Some(result.exit_code),
+                expected_output: None,
+                actual_output: result.stdout,
+                stderr: result.stderr,
+                diff: None,
+            },
+            Err(e) => TestVerdict::errored("command".to_string(), e),
+        };
+        verdicts.push(verdict);
+    }
+
+    TestReport::new(verdicts)
+}
+
+fn outputs_match(expected: &str, actual: &str, comparison: OutputComparison) -> bool {
+    match comparison {
+        OutputComparison::Exact => expected == actual,
+        OutputComparison::TrimWhitespace => {
+            let normalize = |s: &str| {
+                s.trim_end()
+                    .lines()
+                    .map(str::trim_end)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            normalize(expected) == normalize(actual)
+        }
+    }
+}
+
+/// Line-by-line diff of expected against actual output, in unified style
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut diff = String::new();
+
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => {
+                diff.push_str(&format!(" {}\n", e));
+            }
+            (e, a) => {
+                if let Some(e) = e {
+                    diff.push_str(&format!("-{}\n", e));
+                }
+                if let Some(a) = a {
+                    diff.push_str(&format!("+{}\n", a));
+                }
+            }
+        }
+    }
+
+    diff
+}
+
+impl TestVerdict {
+    fn errored(name: String, error: anyhow::Error) -> Self {
+        Self {
+            name,
+            status: TestStatus::Error,
+            duration_ms: 0,
+            exit_code: None,
+            expected_output: None,
+            actual_output: String::new(),
+            stderr: format!("Execution error: {}", error),
+            diff: None,
+        }
+    }
+}
+
+impl TestReport {
+    fn new(verdicts: Vec<TestVerdict>) -> Self {
+        let passed = verdicts
+            .iter()
+            .filter(|v| matches!(v.status, TestStatus::Passed))
+            .count();
+
+        Self {
+            total: verdicts.len(),
+            passed,
+            failed: verdicts.len() - passed,
+            duration_ms: verdicts.iter().map(|v| v.duration_ms).sum(),
+            verdicts,
+        }
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.failed == 0
+    }
+}
@@ -0,0 +1,203 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+use crate::queue::WorkerState;
+use crate::state::ServiceState;
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Worker loops are considered stalled once they haven't beaten for this long.
+/// Jobs may run for their full timeout between beats, so this is generous.
+const HEARTBEAT_STALE_AFTER: Duration = Duration::from_secs(120);
+
+/// Last time a background loop reported progress
+#[derive(Default)]
+pub struct Heartbeat {
+    last_beat_ms: AtomicI64,
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        Self {
+            last_beat_ms: AtomicI64::new(0),
+        }
+    }
+
+    pub fn beat(&self) {
+        self.last_beat_ms
+            .store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    pub fn last_beat(&self) -> Option<DateTime<Utc>> {
+        match self.last_beat_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => DateTime::from_timestamp_millis(ms),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentState {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComponentStatus {
+    pub status: ComponentState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub status: ComponentState,
+    pub version: &'static str,
+    pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub components: BTreeMap<&'static str, ComponentStatus>,
+}
+
+impl HealthReport {
+    fn new(components: BTreeMap<&'static str, ComponentStatus>) -> Self {
+        let all_up = components
+            .values()
+            .all(|c| matches!(c.status, ComponentState::Up));
+
+        Self {
+            status: if all_up { ComponentState::Up } else { ComponentState::Down },
+            version: env!("CARGO_PKG_VERSION"),
+            timestamp: Utc::now(),
+            components,
+        }
+    }
+}
+
+/// Liveness: the process is running and able to serve requests
+pub async fn healthz() -> Json<HealthReport> {
+    Json(HealthReport::new(BTreeMap::new()))
+}
+
+/// Readiness: every dependency needed to run executions is reachable
+pub async fn readyz(State(state): State<Arc<ServiceState>>) -> (StatusCode, Json<HealthReport>) {
+    let (redis, worker) = tokio::join!(check_redis(&state), check_workers(&state));
+
+    let mut components = BTreeMap::new();
+    components.insert("redis", redis);
+    // Docker isn't needed when every run goes to the sandbox
+    if state.sandbox_executor.replaces_docker() {
+        components.insert("sandbox", check_sandbox(&state).await);
+    } else {
+        components.insert("docker", check_docker().await);
+    }
+    components.insert("worker", worker);
+
+    let report = HealthReport::new(components);
+    let status = match report.status {
+        ComponentState::Up => StatusCode::OK,
+        ComponentState::Down => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (status, Json(report))
+}
+
+async fn check_redis(state: &ServiceState) -> ComponentStatus {
+    let start = Instant::now();
+    let ping = async {
+        let mut redis = state.redis.lock().await;
+        redis::cmd("PING").query_async::<_, String>(&mut *redis).await
+    };
+
+    match tokio::time::timeout(CHECK_TIMEOUT, ping).await {
+        Ok(Ok(_)) => up(start),
+        Ok(Err(e)) => down(start, e.to_string()),
+        Err(_) => down(start, "timed out".to_string()),
+    }
+}
+
+async fn check_docker() -> ComponentStatus {
+    let start = Instant::now();
+    let version = Command::new("docker")
+        .args(["version", "--format", "{{.Server.Version}}"])
+        .kill_on_drop(true)
+        .output();
+
+    match tokio::time::timeout(CHECK_TIMEOUT, version).await {
+        Ok(Ok(output)) if output.status.success() => up(start),
+        Ok(Ok(output)) => down(
+            start,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ),
+        Ok(Err(e)) => down(start, e.to_string()),
+        Err(_) => down(start, "timed out".to_string()),
+    }
+}
+
+/// Whether the tool chosen at startup can still create a sandbox
+async fn check_sandbox(state: &ServiceState) -> ComponentStatus {
+    let start = Instant::now();
+    let sandbox = state.sandbox_executor.clone();
+    let probe = tokio::task::spawn_blocking(move || sandbox.probe());
+
+    match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok(Ok(Ok(()))) => up(start),
+        Ok(Ok(Err(e))) => down(start, format!("{:#}", e)),
+        Ok(Err(e)) => down(start, e.to_string()),
+        Err(_) => down(start, "timed out".to_string()),
+    }
+}
+
+/// Up if the embedded worker is beating, or any standalone worker is online
+async fn check_workers(state: &ServiceState) -> ComponentStatus {
+    let local = check_heartbeat(&state.worker_heartbeat);
+    if matches!(local.status, ComponentState::Up) {
+        return local;
+    }
+
+    let start = Instant::now();
+    match tokio::time::timeout(CHECK_TIMEOUT, state.queue.workers()).await {
+        Ok(Ok(workers)) if workers.iter().any(|w| matches!(w.state, WorkerState::Online)) => up(start),
+        Ok(Ok(_)) => down(start, "no online workers".to_string()),
+        Ok(Err(e)) => down(start, e.to_string()),
+        Err(_) => down(start, "timed out".to_string()),
+    }
+}
+
+fn check_heartbeat(heartbeat: &Heartbeat) -> ComponentStatus {
+    let start = Instant::now();
+    match heartbeat.last_beat() {
+        None => down(start, "no heartbeat yet".to_string()),
+        Some(last) => {
+            let age = (Utc::now() - last).to_std().unwrap_or_default();
+            if age > HEARTBEAT_STALE_AFTER {
+                down(start, format!("last heartbeat {}s ago", age.as_secs()))
+            } else {
+                up(start)
+            }
+        }
+    }
+}
+
+fn up(start: Instant) -> ComponentStatus {
+    ComponentStatus {
+        status: ComponentState::Up,
+        message: None,
+        latency_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+fn down(start: Instant, message: String) -> ComponentStatus {
+    ComponentStatus {
+        status: ComponentState::Down,
+        message: Some(message),
+        latency_ms: start.elapsed().as_millis() as u64,
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{bail, Context, Result};
+use std::time::Duration;
+use tokio::process::Command;
+
+use crate::error::ServiceError;
+
+/// Who may run submissions in their own image, and which images
+#[derive(Debug, Clone)]
+pub struct ImagePolicy {
+    /// Registries or repositories custom images must come from, e.g.
+    /// `registry.internal/toolchains/`; none disables custom images
+    pub allowed_prefixes: Vec<String>,
+    /// Bearer tokens of callers trusted to pick an image
+    pub trusted_tokens: Vec<String>,
+    /// How long a worker waits for an image it doesn't have yet
+    pub pull_timeout: Duration,
+}
+
+impl ImagePolicy {
+    pub fn from_env() -> Self {
+        let list = |key: &str| -> Vec<String> {
+            std::env::var(key)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect()
+        };
+        let pull_timeout = std::env::var("IMAGE_PULL_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+        Self {
+            allowed_prefixes: list("CUSTOM_IMAGE_PREFIXES"),
+            trusted_tokens: list("CUSTOM_IMAGE_TOKENS"),
+            pull_timeout: Duration::from_secs(pull_timeout),
+        }
+    }
+
+    /// Whether a submission carrying `token` may run in `image`
+    pub fn authorize(&self, image: &str, token: Option<&str>) -> Result<(), ServiceError> {
+        if self.allowed_prefixes.is_empty() {
+            return Err(ServiceError::BadRequest("Custom images are not enabled on this service".to_string()));
+        }
+        if !token.is_some_and(|token| self.trusted_tokens.iter().any(|trusted| trusted == token)) {
+            return Err(ServiceError::Forbidden("Only trusted callers may choose an image".to_string()));
+        }
+        self.check_image(image).map_err(|e| ServiceError::BadRequest(e.to_string()))
+    }
+
+    /// Checks the reference is well formed and under an allowed prefix
+    pub fn check_image(&self, image: &str) -> Result<()> {
+        let valid = !image.is_empty()
+            && !image.starts_with('-')
+            && image.chars().all(|c| c.is_ascii_alphanumeric() || "./:_@-".contains(c));
+        if !valid {
+            bail!("'{}' is not a valid image reference", image);
+        }
+        if !self.allowed_prefixes.iter().any(|prefix| has_prefix(image, prefix)) {
+            bail!(
+                "Image '{}' is not from an allowed registry ({})",
+                image,
+                self.allowed_prefixes.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Pulls `image` unless it is already present, within the pull timeout
+    pub async fn ensure_pulled(&self, image: &str) -> Result<()> {
+        let present = Command::new("docker")
+            .args(["image", "inspect", "--format", "{{.Id}}", image])
+            .output()
+            .await
+            .context("Failed to run docker")?
+            .status
+            .success();
+        if present {
+            return Ok(());
+        }
+
+        tracing::info!("Pulling custom image {}", image);
+        let pull = Command::new("docker").args(["pull", "--quiet", image]).kill_on_drop(true).output();
+        let output = tokio::time::timeout(self.pull_timeout, pull)
+            .await
+            .map_err(|_| anyhow::anyhow!("Pulling {} took longer than {:?}", image, self.pull_timeout))?
+            .context("Failed to run docker pull")?;
+        if !output.status.success() {
+            bail!("Failed to pull {}: {}", image, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(())
+    }
+}
+
+/// Prefix match that doesn't let `ghcr.io/acme` admit `ghcr.io/acme-evil`
+fn has_prefix(image: &str, prefix: &str) -> bool {
+    let Some(rest) = image.strip_prefix(prefix) else {
+        return false;
+    };
+    prefix.ends_with(['/', ':']) || rest.is_empty() || rest.starts_with(['/', ':', '@'])
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use crate::error::ServiceError;
+use crate::models::{ExecutionJob, JobStatus};
+
+/// Sorted set of every stored job ID, scored by creation time (unix ms)
+pub const INDEX_KEY: &str = "syla:execution:index";
+
+const MAX_LABELS: usize = 32;
+const MAX_LABEL_KEY_LEN: usize = 63;
+const MAX_LABEL_VALUE_LEN: usize = 256;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+/// Set of job IDs carrying a given label
+fn label_key(key: &str, value: &str) -> String {
+    format!("syla:execution:label:{}={}", key, value)
+}
+
+/// Queue index writes for a newly stored job
+pub fn add(pipe: &mut redis::Pipeline, job: &ExecutionJob) {
+    let id = job.id.to_string();
+    pipe.zadd(INDEX_KEY, &id, job.created_at.timestamp_millis()).ignore();
+    for (key, value) in &job.request.labels {
+        pipe.sadd(label_key(key, value), &id).ignore();
+    }
+}
+
+/// Queue index removals for a job being deleted
+pub fn remove(pipe: &mut redis::Pipeline, job: &ExecutionJob) {
+    let id = job.id.to_string();
+    pipe.zrem(INDEX_KEY, &id).ignore();
+    for (key, value) in &job.request.labels {
+        pipe.srem(label_key(key, value), &id).ignore();
+    }
+}
+
+pub fn validate_labels(labels: &HashMap<String, String>) -> Result<(), ServiceError> {
+    if labels.len() > MAX_LABELS {
+        return Err(ServiceError::BadRequest(format!(
+            "At most {} labels are allowed",
+            MAX_LABELS
+        )));
+    }
+    for (key, value) in labels {
+        let valid_key = !key.is_empty()
+            && key.len() <= MAX_LABEL_KEY_LEN
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if !valid_key {
+            return Err(ServiceError::BadRequest(format!(
+                "Invalid label key '{}': use up to {} letters, digits, '_', '-' or '.'",
+                key, MAX_LABEL_KEY_LEN
+            )));
+        }
+        if value.len() > MAX_LABEL_VALUE_LEN {
+            return Err(ServiceError::BadRequest(format!(
+                "Label '{}' exceeds {} bytes",
+                key, MAX_LABEL_VALUE_LEN
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Filters for listing executions, from query parameters like
+/// `?label.candidate_id=42&status=completed&limit=20`
+#[derive(Debug, Default)]
+pub struct ExecutionQuery {
+    pub labels: HashMap<String, String>,
+    pub status: Option<JobStatus>,
+    pub limit: usize,
+}
+
+impl ExecutionQuery {
+    pub fn from_params(params: HashMap<String, String>) -> Result<Self, ServiceError> {
+        let mut query = Self {
+            limit: DEFAULT_LIMIT,
+            ..Default::default()
+        };
+
+        for (name, value) in params {
+            if let Some(label) = name.strip_prefix("label.") {
+                query.labels.insert(label.to_string(), value);
+                continue;
+            }
+            match name.as_str() {
+                "status" => {
+                    let status = serde_json::from_value(serde_json::Value::String(value.clone()))
+                        .map_err(|_| ServiceError::BadRequest(format!("Unknown status '{}'", value)))?;
+                    query.status = Some(status);
+                }
+                "limit" => {
+                    let limit: usize = value
+                        .parse()
+                        .map_err(|_| ServiceError::BadRequest(format!("Invalid limit '{}'", value)))?;
+                    query.limit = limit.clamp(1, MAX_LIMIT);
+                }
+                other => {
+                    return Err(ServiceError::BadRequest(format!(
+                        "Unknown query parameter '{}'",
+                        other
+                    )))
+                }
+            }
+        }
+
+        Ok(query)
+    }
+
+    pub fn matches(&self, job: &ExecutionJob) -> bool {
+        let status_matches = self
+            .status
+            .as_ref()
+            .is_none_or(|s| std::mem::discriminant(s) == std::mem::discriminant(&job.status));
+        status_matches
+            && self
+                .labels
+                .iter()
+                .all(|(k, v)| job.request.labels.get(k) == Some(v))
+    }
+
+    /// Redis keys whose intersection holds the candidate job IDs
+    pub fn label_keys(&self) -> Vec<String> {
+        self.labels.iter().map(|(k, v)| label_key(k, v)).collect()
+    }
+}
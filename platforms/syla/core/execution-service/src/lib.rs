@@ -0,0 +1,24 @@
+pub mod admin;
+pub mod admission;
+pub mod checks;
+pub mod coverage;
+pub mod docker;
+pub mod error;
+pub mod evaluation;
+pub mod health;
+pub mod images;
+pub mod index;
+pub mod log_store;
+pub mod models;
+pub mod queue;
+pub mod recovery;
+pub mod retention;
+pub mod retry;
+pub mod sandbox;
+pub mod session;
+pub mod state;
+pub mod telemetry;
+pub mod usage;
+pub mod warmup;
+pub mod wasm;
+pub mod worker;
@@ -0,0 +1,333 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::primitives::ByteStream;
+use chrono::Utc;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::models::{CoverageReport, ExecutionJob, ExecutionResult, LogObject};
+
+/// Blob storage for execution output too large to keep in the job record
+#[async_trait]
+pub trait LogStore: Send + Sync {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()>;
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    async fn delete(&self, key: &str) -> Result<()>;
+    /// Link the object can be downloaded from until `ttl` has passed
+    async fn url(&self, key: &str, ttl: Duration) -> Result<String>;
+}
+
+/// Files under a local directory, served back by this service at `/logs`.
+/// Meant for development; links are not signed.
+pub struct LocalLogStore {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalLogStore {
+    pub fn new(root: PathBuf, base_url: String) -> Self {
+        Self {
+            root,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            anyhow::bail!("Invalid log key '{}'", key);
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl LogStore for LocalLogStore {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, data)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn url(&self, key: &str, _ttl: Duration) -> Result<String> {
+        Ok(format!("{}/{}", self.base_url, key))
+    }
+}
+
+/// An S3-compatible bucket, e.g. AWS S3 or MinIO. Credentials, region and
+/// endpoint come from the standard `AWS_*` environment variables.
+pub struct S3LogStore {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    prefix: String,
+}
+
+impl S3LogStore {
+    pub async fn from_env(bucket: String, prefix: String, path_style: bool) -> Self {
+        let shared = aws_config::load_from_env().await;
+        let config = aws_sdk_s3::config::Builder::from(&shared)
+            .force_path_style(path_style)
+            .build();
+        Self {
+            client: aws_sdk_s3::Client::from_conf(config),
+            bucket,
+            prefix,
+        }
+    }
+
+    fn object_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+#[async_trait]
+impl LogStore for S3LogStore {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .content_type("text/plain; charset=utf-8")
+            .body(ByteStream::from(data))
+            .send()
+            .await
+            .with_context(|| format!("Failed to upload {} to bucket {}", key, self.bucket))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .send()
+            .await;
+        match response {
+            Ok(object) => Ok(Some(object.body.collect().await?.into_bytes().to_vec())),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn url(&self, key: &str, ttl: Duration) -> Result<String> {
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .presigned(PresigningConfig::expires_in(ttl)?)
+            .await?;
+        Ok(request.uri().to_string())
+    }
+}
+
+/// Moves stdout/stderr and lcov reports above a size threshold out of
+/// execution records
+pub struct LogOffload {
+    pub store: Arc<dyn LogStore>,
+    /// Streams larger than this are stored externally, keeping only this
+    /// many bytes inline
+    pub threshold_bytes: usize,
+    pub url_ttl: Duration,
+}
+
+impl LogOffload {
+    /// Configured from `LOG_STORE` (`local` or `s3`); `None` when unset
+    pub async fn from_env() -> Result<Option<Self>> {
+        let read = |key: &str, default: u64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+
+        let store: Arc<dyn LogStore> = match std::env::var("LOG_STORE").as_deref() {
+            Err(_) | Ok("") | Ok("none") => return Ok(None),
+            Ok("local") => {
+                let root = std::env::var("LOG_STORE_DIR")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| std::env::temp_dir().join("syla-logs"));
+                let base_url = std::env::var("LOG_STORE_PUBLIC_URL")
+                    .unwrap_or_else(|_| "http://localhost:8083/logs".to_string());
+                Arc::new(LocalLogStore::new(root, base_url))
+            }
+            Ok("s3") => {
+                let bucket = std::env::var("LOG_STORE_BUCKET")
+                    .context("LOG_STORE_BUCKET must be set when LOG_STORE=s3")?;
+                let prefix = std::env::var("LOG_STORE_PREFIX").unwrap_or_default();
+                let path_style = std::env::var("LOG_STORE_PATH_STYLE").is_ok_and(|v| v == "true");
+                Arc::new(S3LogStore::from_env(bucket, prefix, path_style).await)
+            }
+            Ok(other) => anyhow::bail!("Unknown LOG_STORE '{}', expected 'local' or 's3'", other),
+        };
+
+        Ok(Some(Self {
+            store,
+            threshold_bytes: read("LOG_OFFLOAD_THRESHOLD_BYTES", 256 * 1024) as usize,
+            url_ttl: Duration::from_secs(read("LOG_URL_TTL_SECONDS", 3600)),
+        }))
+    }
+
+    /// Upload oversized streams and trim them in the record. Upload failures
+    /// leave the output inline rather than losing it.
+    pub async fn offload(&self, job_id: Uuid, result: &mut ExecutionResult) {
+        let streams = [
+            ("stdout", &mut result.stdout, &mut result.stdout_object),
+            ("stderr", &mut result.stderr, &mut result.stderr_object),
+        ];
+        for (name, text, object) in streams {
+            if text.len() <= self.threshold_bytes {
+                continue;
+            }
+            let key = log_key(job_id, name);
+            let bytes = text.len() as u64;
+            match self.store.put(&key, text.clone().into_bytes()).await {
+                Ok(()) => {
+                    text.truncate(floor_char_boundary(text, self.threshold_bytes));
+                    *object = Some(LogObject {
+                        key,
+                        bytes,
+                        url: None,
+                        url_expires_at: None,
+                    });
+                }
+                Err(e) => error!("Failed to offload {} of job {}: {:#}", name, job_id, e),
+            }
+        }
+    }
+
+    /// Upload an oversized lcov report in place of keeping it inline. A cut
+    /// report is no use, so unlike logs none of it stays in the record.
+    pub async fn offload_lcov(&self, job_id: Uuid, report: &mut CoverageReport) {
+        let Some(lcov) = report.lcov.take_if(|lcov| lcov.len() > self.threshold_bytes) else {
+            return;
+        };
+        let key = format!("executions/{}/lcov.info", job_id);
+        let bytes = lcov.len() as u64;
+        match self.store.put(&key, lcov.clone().into_bytes()).await {
+            Ok(()) => {
+                report.lcov_object = Some(LogObject {
+                    key,
+                    bytes,
+                    url: None,
+                    url_expires_at: None,
+                });
+            }
+            Err(e) => {
+                error!("Failed to offload lcov report of job {}: {:#}", job_id, e);
+                report.lcov = Some(lcov);
+            }
+        }
+    }
+
+    /// Fill in download links for any offloaded output
+    pub async fn sign_urls(&self, job: &mut ExecutionJob) {
+        let expires_at = Utc::now() + chrono::Duration::from_std(self.url_ttl).unwrap_or_default();
+        for object in objects_mut(job) {
+            match self.store.url(&object.key, self.url_ttl).await {
+                Ok(url) => {
+                    object.url = Some(url);
+                    object.url_expires_at = Some(expires_at);
+                }
+                Err(e) => error!("Failed to sign URL for {}: {:#}", object.key, e),
+            }
+        }
+    }
+
+    /// Remove a job's offloaded output, e.g. when its record expires
+    pub async fn delete(&self, job: &ExecutionJob) -> Result<()> {
+        let result = job.result.as_ref();
+        let objects = [
+            result.and_then(|r| r.stdout_object.as_ref()),
+            result.and_then(|r| r.stderr_object.as_ref()),
+            job.coverage.as_ref().and_then(|c| c.lcov_object.as_ref()),
+        ];
+        for object in objects.into_iter().flatten() {
+            self.store.delete(&object.key).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything of a job that was moved to the store
+fn objects_mut(job: &mut ExecutionJob) -> impl Iterator<Item = &mut LogObject> {
+    let (stdout, stderr) = match job.result.as_mut() {
+        Some(result) => (result.stdout_object.as_mut(), result.stderr_object.as_mut()),
+        None => (None, None),
+    };
+    let lcov = job.coverage.as_mut().and_then(|c| c.lcov_object.as_mut());
+    [stdout, stderr, lcov].into_iter().flatten()
+}
+
+fn log_key(job_id: Uuid, stream: &str) -> String {
+    format!("executions/{}/{}.log", job_id, stream)
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len()))
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn large_lcov_reports_are_moved_to_the_store() {
+        let root = tempfile::tempdir().unwrap();
+        let offload = LogOffload {
+            store: Arc::new(LocalLogStore::new(root.path().to_path_buf(), "http://logs".to_string())),
+            threshold_bytes: 16,
+            url_ttl: Duration::from_secs(60),
+        };
+        let job_id = Uuid::new_v4();
+
+        let mut small = CoverageReport { lcov: Some("SF:a\n".to_string()), ..Default::default() };
+        offload.offload_lcov(job_id, &mut small).await;
+        assert!(small.lcov.is_some() && small.lcov_object.is_none());
+
+        let lcov = "SF:main.py\nDA:1,1\nend_of_record\n".to_string();
+        let mut large = CoverageReport { lcov: Some(lcov.clone()), ..Default::default() };
+        offload.offload_lcov(job_id, &mut large).await;
+        assert!(large.lcov.is_none());
+        let object = large.lcov_object.unwrap();
+        assert_eq!(object.bytes, lcov.len() as u64);
+        assert_eq!(offload.store.get(&object.key).await.unwrap(), Some(lcov.into_bytes()));
+    }
+}
@@ -1,83 +1,139 @@
 use anyhow::Result;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     routing::{get, post},
     Json, Router,
 };
 use redis::aio::ConnectionManager;
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tower_http::trace::TraceLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use uuid::Uuid;
 
-mod docker;
-mod error;
-mod executor;
-mod grpc;
-mod models;
-mod queue;
-mod state;
-mod worker;
-
-use error::ServiceError;
-use state::ServiceState;
+use syla_execution_service::error::ServiceError;
+use syla_execution_service::state::ServiceState;
+use syla_execution_service::{
+    admin, admission, docker, health, images, index, log_store, models, queue, recovery,
+    retention, retry, sandbox, session, telemetry, usage, warmup, wasm, worker,
+};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "syla_execution_service=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize tracing, exporting spans over OTLP when configured
+    let tracer_provider = telemetry::init("syla-execution-service", "syla_execution_service=debug")?;
 
     // Connect to Redis
     let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6380/".to_string());
     let redis_client = redis::Client::open(redis_url)?;
-    let redis_conn = ConnectionManager::new(redis_client).await?;
+    let redis_conn = ConnectionManager::new(redis_client.clone()).await?;
 
     // Initialize components
     let redis_queue = Arc::new(queue::RedisQueue::new(redis_conn.clone()));
-    let docker_executor = Arc::new(executor::DockerExecutor::new().await?);
+    redis_queue.ensure_group().await?;
+
+    // Interactive sessions
+    let session_ttl = env_secs("SESSION_TTL_SECONDS", 600);
+    let session_max_ttl = env_secs("SESSION_MAX_TTL_SECONDS", 3600);
+    let sessions = Arc::new(session::SessionManager::new(
+        session_ttl,
+        session_max_ttl,
+        docker::OutputLimits::from_env(),
+    ));
+
+    // Probe for bubblewrap or nsjail before deciding whether Docker is required
+    let sandbox_executor = Arc::new(sandbox::SandboxExecutor::new(
+        sandbox::SandboxConfig::from_env(),
+        docker::OutputLimits::from_env(),
+    ));
 
     // Initialize state for REST API
     let state = Arc::new(ServiceState {
         redis: Arc::new(Mutex::new(redis_conn)),
-        docker_executor: Arc::new(docker::DockerExecutor::new()?),
+        queue: redis_queue.clone(),
+        docker_executor: Arc::new(if sandbox_executor.replaces_docker() {
+            docker::DockerExecutor::unchecked(docker::OutputLimits::from_env())
+        } else {
+            docker::DockerExecutor::new(docker::OutputLimits::from_env())?
+        }),
+        wasm_executor: Arc::new(wasm::WasmExecutor::new(
+            wasm::WasmConfig::from_env(),
+            docker::OutputLimits::from_env(),
+        )?),
+        sandbox_executor: sandbox_executor.clone(),
+        sessions: sessions.clone(),
+        worker_heartbeat: Arc::new(health::Heartbeat::new()),
+        admission: admission::AdmissionConfig::from_env(),
+        retention: retention::RetentionManager::new(retention::RetentionPolicy::from_env()),
+        log_offload: log_store::LogOffload::from_env().await?,
+        warmup: Arc::new(warmup::ImageWarmer::new()),
+        images: images::ImagePolicy::from_env(),
+        api_keys: usage::ApiKeys::from_env(),
+        retry: retry::RetryPolicy::from_env(),
+        output_limits: docker::OutputLimits::from_env(),
     });
 
-    // Start worker task
-    let worker_state = state.clone();
+    // Pull runtime images ahead of the first execution
+    if warmup::enabled_on_start() {
+        state.warmup.start();
+    }
+
+    // Start the embedded worker unless execution runs on standalone
+    // `syla-execution-worker` processes. Blocking stream reads get their own
+    // connection so they don't stall other commands on the shared one.
+    let embedded_worker = std::env::var("EMBEDDED_WORKER")
+        .map(|v| v != "false" && v != "0")
+        .unwrap_or(true);
+    if embedded_worker {
+        let worker_state = state.clone();
+        let worker_queue = Arc::new(queue::RedisQueue::new(ConnectionManager::new(redis_client).await?));
+        tokio::spawn(async move {
+            worker::run_worker(worker_state, worker_queue, worker::worker_id()).await;
+        });
+    } else {
+        tracing::info!("Embedded worker disabled; relying on standalone workers");
+    }
+
+    // Recover jobs whose worker died mid-execution
+    let recovery_state = state.clone();
     tokio::spawn(async move {
-        worker::run_worker(worker_state).await;
+        recovery::run_orphan_reaper(recovery_state, recovery::RecoveryConfig::from_env()).await;
     });
 
-    // Start gRPC server
-    let grpc_queue = redis_queue.clone();
-    let grpc_executor = docker_executor.clone();
+    // Expire old execution records and scratch files
+    let retention_state = state.clone();
+    let retention_interval = env_secs("RETENTION_SWEEP_INTERVAL_SECONDS", 300);
     tokio::spawn(async move {
-        let addr = "0.0.0.0:8081".parse().unwrap();
-        tracing::info!("Starting gRPC server on {}", addr);
-        
-        let service = grpc::server::ExecutionServiceImpl::new(grpc_queue, grpc_executor);
-        
-        tonic::transport::Server::builder()
-            .add_service(grpc::proto::syla::execution::v1::execution_service_server::ExecutionServiceServer::new(service))
-            .serve(addr)
-            .await
-            .expect("gRPC server failed");
+        retention::run_cleanup(retention_state, retention_interval).await;
+    });
+
+    // Start session cleanup task
+    tokio::spawn(async move {
+        session::run_reaper(sessions, session::REAP_INTERVAL).await;
     });
 
     // Build REST router
     let app = Router::new()
-        .route("/health", get(health_handler))
-        .route("/executions", post(create_execution))
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/executions", get(list_executions).post(create_execution))
         .route("/executions/:id", get(get_execution))
-        .layer(TraceLayer::new_for_http())
+        .route("/executions/:id/replay", post(replay_execution))
+        .route("/logs/*key", get(get_log))
+        .route("/queue", get(queue_info))
+        .route("/workers", get(list_workers))
+        .route("/retention", get(retention_report))
+        .route("/admin/queue", get(admin::queue_report))
+        .route("/admin/workers", get(admin::workers_report))
+        .route("/admin/warmup", get(admin::warmup_report).post(admin::start_warmup))
+        .route("/admin/usage", get(usage::usage_report))
+        .route("/sessions", post(create_session))
+        .route("/sessions/:id", get(get_session).delete(delete_session))
+        .route("/sessions/:id/exec", post(exec_in_session))
+        .layer(TraceLayer::new_for_http().make_span_with(telemetry::http_span))
         .with_state(state);
 
     // Start REST server
@@ -91,25 +147,132 @@ async fn main() -> Result<()> {
     let listener = tokio::net::TcpListener::bind(addr).await?;
     axum::serve(listener, app).await?;
 
+    telemetry::shutdown(tracer_provider);
     Ok(())
 }
 
-async fn health_handler() -> &'static str {
-    "OK"
-}
-
 async fn create_execution(
     State(state): State<Arc<ServiceState>>,
+    headers: HeaderMap,
     Json(request): Json<models::CreateExecutionRequest>,
 ) -> Result<Json<models::ExecutionJob>, ServiceError> {
-    let job = state.create_execution(request).await?;
+    let job = state.create_execution(request, bearer_token(&headers)).await?;
     Ok(Json(job))
 }
 
+async fn list_executions(
+    State(state): State<Arc<ServiceState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Vec<models::ExecutionJob>>, ServiceError> {
+    let query = index::ExecutionQuery::from_params(params)?;
+    let mut jobs = Vec::new();
+    for job in state.list_executions(&query).await? {
+        jobs.push(state.with_log_urls(job).await);
+    }
+    Ok(Json(jobs))
+}
+
 async fn get_execution(
     State(state): State<Arc<ServiceState>>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<models::ExecutionJob>, ServiceError> {
     let job = state.get_execution(id).await?;
+    Ok(Json(state.with_log_urls(job).await))
+}
+
+/// Re-run a stored execution, optionally in debug mode
+async fn replay_execution(
+    State(state): State<Arc<ServiceState>>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    request: Option<Json<models::ReplayRequest>>,
+) -> Result<Json<models::ExecutionJob>, ServiceError> {
+    let Json(request) = request.unwrap_or_default();
+    let job = state.replay_execution(id, request.debug, bearer_token(&headers)).await?;
     Ok(Json(job))
-}
\ No newline at end of file
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Offloaded output, for stores whose links point back at this service
+async fn get_log(
+    State(state): State<Arc<ServiceState>>,
+    Path(key): Path<String>,
+) -> Result<([(header::HeaderName, &'static str); 1], Vec<u8>), ServiceError> {
+    let offload = state.log_offload.as_ref().ok_or(ServiceError::NotFound)?;
+    let data = offload.store.get(&key).await?.ok_or(ServiceError::NotFound)?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], data))
+}
+
+async fn queue_info(
+    State(state): State<Arc<ServiceState>>,
+) -> Result<Json<queue::QueueInfo>, ServiceError> {
+    let info = state.queue.info().await?;
+    Ok(Json(info))
+}
+
+async fn list_workers(
+    State(state): State<Arc<ServiceState>>,
+) -> Result<Json<Vec<queue::WorkerStatus>>, ServiceError> {
+    let workers = state.queue.workers().await?;
+    Ok(Json(workers))
+}
+
+async fn retention_report(
+    State(state): State<Arc<ServiceState>>,
+) -> Json<retention::RetentionReport> {
+    Json(state.retention.report().await)
+}
+
+async fn create_session(
+    State(state): State<Arc<ServiceState>>,
+    headers: HeaderMap,
+    Json(request): Json<models::CreateSessionRequest>,
+) -> Result<(StatusCode, Json<models::SessionInfo>), ServiceError> {
+    let api_key = state.api_keys.identify(bearer_token(&headers));
+    let session = state
+        .sessions
+        .create_session(request, api_key, &state.admission)
+        .await?;
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+async fn get_session(
+    State(state): State<Arc<ServiceState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<models::SessionInfo>, ServiceError> {
+    let session = state.sessions.get_session(id).await?;
+    Ok(Json(session))
+}
+
+async fn exec_in_session(
+    State(state): State<Arc<ServiceState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<models::SessionExecRequest>,
+) -> Result<Json<models::ExecutionResult>, ServiceError> {
+    let result = state.sessions.execute(id, request).await?;
+    Ok(Json(result))
+}
+
+async fn delete_session(
+    State(state): State<Arc<ServiceState>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ServiceError> {
+    state.sessions.delete_session(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn env_secs(key: &str, default: u64) -> Duration {
+    let secs = std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default);
+    Duration::from_secs(secs)
+}
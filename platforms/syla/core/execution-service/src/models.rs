@@ -3,12 +3,95 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::retry::{FailureCategory, RetryOptions};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateExecutionRequest {
     pub code: String,
     pub language: String,
     pub timeout_seconds: Option<u64>,
     pub args: Option<Vec<String>>,
+    /// Per-stream output cap, clamped to the server maximum
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<u64>,
+    /// Sandbox to run in; defaults per language when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<ExecutorBackend>,
+    /// Run in test-evaluation mode against these cases
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tests: Option<TestSpec>,
+    /// Run the program, or only compile or lint it
+    #[serde(default)]
+    pub mode: ExecutionMode,
+    /// Run under the language's coverage tool and report what was exercised
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<CoverageOptions>,
+    /// Caller-defined tags (e.g. `candidate_id`, `model`) that executions
+    /// can be listed by
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+    /// Relaxed limits and extra diagnostics; set by debug replays
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub debug: bool,
+    /// Image to run in instead of the language's runtime image; trusted
+    /// callers only, and only from allowlisted registries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Language runtime to run under, e.g. `3.12` for python or `20` for
+    /// javascript; the service's default for the language when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime_version: Option<String>,
+    /// How failed runs are retried; the server's policy when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryOptions>,
+}
+
+impl CreateExecutionRequest {
+    /// Image a Docker run uses instead of the language default: the custom
+    /// image, or the one for the pinned runtime version
+    pub fn docker_image(&self) -> Option<String> {
+        if let Some(image) = &self.image {
+            return Some(image.clone());
+        }
+        let version = self.runtime_version.as_deref()?;
+        crate::docker::versioned_image(&self.language, version).map(String::from)
+    }
+}
+
+/// Body of `POST /executions/:id/replay`; may be omitted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayRequest {
+    /// Relax the time limit and output cap and record where and how the
+    /// run happened
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CoverageOptions {
+    /// Return the raw lcov report alongside the summary
+    #[serde(default)]
+    pub include_lcov: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionMode {
+    #[default]
+    Run,
+    /// Compile or syntax-check without executing
+    Compile,
+    /// Run the language's linter without executing
+    Lint,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutorBackend {
+    Docker,
+    Wasm,
+    /// bubblewrap or nsjail on the worker's host, without a container
+    Sandbox,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +103,67 @@ pub struct ExecutionJob {
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub result: Option<ExecutionResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_report: Option<TestReport>,
+    /// Compiler or linter findings in compile and lint modes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Vec<Diagnostic>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<CoverageReport>,
+    /// Number of times a worker has started this job
+    #[serde(default)]
+    pub attempts: u32,
+    /// Consumer name of the worker that last started the job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_id: Option<String>,
+    /// W3C trace context of the submitting request, so the worker's spans
+    /// join the caller's trace
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub trace_context: HashMap<String, String>,
+    /// The execution this one re-runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay_of: Option<Uuid>,
+    /// How a debug run was carried out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_info: Option<DebugInfo>,
+    /// Name of the API key that submitted the job, for usage accounting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Every run of the job, retries included, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempt_history: Vec<AttemptRecord>,
+}
+
+/// One run of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttemptRecord {
+    pub attempt: u32,
+    pub worker_id: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Why the run failed; unset when it succeeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<FailureCategory>,
+    /// The infrastructure error, for runs that never got to the program
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Wait before the next attempt, when the run was retried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retried_after_ms: Option<u64>,
+}
+
+/// Recorded for debug runs, to tell environment problems from bad code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebugInfo {
+    pub worker_id: String,
+    pub hostname: String,
+    /// Runtime image for the language
+    pub image: String,
+    /// The time limit actually applied
+    pub timeout_seconds: u64,
+    /// Time spent queued before a worker picked the job up
+    pub queue_wait_ms: i64,
+    pub attempt: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +182,183 @@ pub struct ExecutionResult {
     pub stdout: String,
     pub stderr: String,
     pub duration_ms: u64,
+    /// Original size of each stream before any truncation
+    #[serde(default)]
+    pub stdout_bytes: u64,
+    #[serde(default)]
+    pub stderr_bytes: u64,
+    #[serde(default)]
+    pub stdout_truncated: bool,
+    #[serde(default)]
+    pub stderr_truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_usage: Option<ResourceUsage>,
+    /// Full stdout in object storage, when it was too large to keep inline;
+    /// `stdout` then holds only its beginning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdout_object: Option<LogObject>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr_object: Option<LogObject>,
+    /// Language runtime the code ran under; unset for custom images and
+    /// backends without versioned runtimes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime_version: Option<String>,
+}
+
+/// Execution output kept in object storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogObject {
+    pub key: String,
+    pub bytes: u64,
+    /// Time-limited download link, issued when the execution is fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_expires_at: Option<DateTime<Utc>>,
+}
+
+/// Resources a run consumed, for spotting pathological submissions and tuning limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub wall_time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_time_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestSpec {
+    /// Stdin/expected-output pairs, each run in a fresh container
+    #[serde(default)]
+    pub cases: Vec<TestCase>,
+    /// Shell command run next to the submission; passes on exit code 0
+    pub command: Option<String>,
+    #[serde(default)]
+    pub comparison: OutputComparison,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub stdin: String,
+    pub expected_output: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputComparison {
+    Exact,
+    /// Ignore trailing whitespace on each line and at the end of output
+    #[default]
+    TrimWhitespace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestReport {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub duration_ms: u64,
+    pub verdicts: Vec<TestVerdict>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestVerdict {
+    pub name: String,
+    pub status: TestStatus,
+    pub duration_ms: u64,
+    pub exit_code: Option<i32>,
+    pub expected_output: Option<String>,
+    pub actual_output: String,
+    pub stderr: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Error,
+    Timeout,
+}
+
+/// Coverage normalized from the tool's lcov output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub lines: CoverageCounts,
+    pub functions: CoverageCounts,
+    pub branches: CoverageCounts,
+    pub files: Vec<FileCoverage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lcov: Option<String>,
+    /// Where a report too large to keep inline was stored instead of `lcov`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lcov_object: Option<LogObject>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CoverageCounts {
+    pub covered: u64,
+    pub total: u64,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileCoverage {
+    pub file: String,
+    pub lines: CoverageCounts,
+    pub functions: CoverageCounts,
+    pub branches: CoverageCounts,
+    pub uncovered_lines: Vec<u32>,
+}
+
+/// A compiler or linter finding, normalized across languages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    pub severity: DiagnosticSeverity,
+    /// Tool-specific rule or error code, e.g. `E0425` or `no-unused-vars`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Note,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSessionRequest {
+    pub language: String,
+    pub ttl_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionExecRequest {
+    pub code: String,
+    pub timeout_seconds: Option<u64>,
+    #[serde(default)]
+    pub max_output_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub id: Uuid,
+    pub language: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
 }
 
 // Database models for persistence
@@ -79,6 +400,16 @@ impl ExecutionJob {
             started_at: None,
             completed_at: None,
             result: None,
+            test_report: None,
+            diagnostics: None,
+            coverage: None,
+            attempts: 0,
+            worker_id: None,
+            trace_context: HashMap::new(),
+            replay_of: None,
+            debug_info: None,
+            api_key: None,
+            attempt_history: Vec::new(),
         }
     }
 }
\ No newline at end of file
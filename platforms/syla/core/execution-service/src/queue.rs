@@ -1,37 +1,636 @@
 use anyhow::Result;
 use redis::aio::ConnectionManager;
+use chrono::{DateTime, Utc};
+use redis::streams::{
+    StreamClaimReply, StreamId, StreamInfoConsumersReply, StreamInfoGroupsReply,
+    StreamPendingCountReply, StreamRangeReply, StreamReadOptions, StreamReadReply,
+};
 use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+const STREAM_KEY: &str = "syla:execution:stream";
+const GROUP_NAME: &str = "syla-workers";
+
+/// Hash of worker ID to its latest self-reported status
+const WORKERS_KEY: &str = "syla:execution:workers";
+
+/// Workers that haven't reported for this long are listed as stale
+const WORKER_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Capped list of recent job durations (ms), used to project wait times
+const DURATIONS_KEY: &str = "syla:execution:durations";
+const DURATION_SAMPLES: isize = 100;
+
+/// Sorted set of running job IDs scored by their last heartbeat (unix ms)
+const INFLIGHT_KEY: &str = "syla:execution:inflight";
+/// Hash of running job ID to the stream entry it was delivered as
+const INFLIGHT_ENTRIES_KEY: &str = "syla:execution:inflight:entries";
+
+/// Most waiting entries `depth` reads when counting the backlog
+const DEPTH_SCAN_LIMIT: usize = 10_000;
+
+/// A job read from the stream that must be acknowledged once processed
+#[derive(Debug, Clone)]
+pub struct QueuedJob {
+    pub entry_id: String,
+    pub job_id: Uuid,
+    pub delivery_count: usize,
+}
+
+/// A running job whose worker stopped sending heartbeats
+#[derive(Debug, Clone)]
+pub struct OrphanedJob {
+    pub job_id: Uuid,
+    pub entry_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueInfo {
+    pub length: usize,
+    pub pending: usize,
+    pub consumers: usize,
+    pub last_delivered_id: Option<String>,
+}
+
+/// Jobs at one priority level. The queue has a single level today, reported
+/// as `default`, so clients keep working when more are added.
+#[derive(Debug, Serialize)]
+pub struct QueueDepth {
+    pub priority: String,
+    /// Jobs not yet delivered to any worker
+    pub waiting: usize,
+    /// Jobs delivered to a worker and not yet acknowledged
+    pub pending: usize,
+    /// Age of the oldest job still waiting for a worker
+    pub oldest_waiting_ms: Option<u64>,
+}
+
+/// Status a worker periodically publishes about itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerInfo {
+    pub id: String,
+    pub hostname: String,
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub current_job: Option<Uuid>,
+    pub jobs_processed: u64,
+    /// Jobs whose processing hit an internal error
+    pub jobs_errored: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkerState {
+    Online,
+    Stale,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkerStatus {
+    #[serde(flatten)]
+    pub info: WorkerInfo,
+    pub state: WorkerState,
+    /// Jobs delivered to this worker and not yet acknowledged
+    pub pending: usize,
+}
+
+/// Job queue on Redis Streams. Workers read through a shared consumer group,
+/// so each job is delivered to one worker and stays pending until acked.
 pub struct RedisQueue {
     conn: Mutex<ConnectionManager>,
-    queue_key: String,
+    stream_key: String,
+    group: String,
 }
 
 impl RedisQueue {
     pub fn new(conn: ConnectionManager) -> Self {
         Self {
             conn: Mutex::new(conn),
-            queue_key: "syla:execution:queue".to_string(),
+            stream_key: STREAM_KEY.to_string(),
+            group: GROUP_NAME.to_string(),
         }
     }
-    
+
+    /// Create the consumer group (and stream) if they don't exist yet
+    pub async fn ensure_group(&self) -> Result<()> {
+        let mut conn = self.conn.lock().await;
+        let result: redis::RedisResult<()> = conn
+            .xgroup_create_mkstream(&self.stream_key, &self.group, "0")
+            .await;
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) if e.code() == Some("BUSYGROUP") => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
     pub async fn push_job(&self, job_id: Uuid) -> Result<()> {
         let mut conn = self.conn.lock().await;
-        let _: () = conn.lpush(&self.queue_key, job_id.to_string()).await?;
+        let _: String = conn
+            .xadd(&self.stream_key, "*", &[("job_id", job_id.to_string())])
+            .await?;
         Ok(())
     }
-    
-    pub async fn pop_job(&self) -> Result<Option<Uuid>> {
+
+    /// Read the next new job for `consumer`, waiting up to `block` for one
+    pub async fn pop_job(&self, consumer: &str, block: Duration) -> Result<Option<QueuedJob>> {
+        let options = StreamReadOptions::default()
+            .group(&self.group, consumer)
+            .count(1)
+            .block(block.as_millis() as usize);
+
+        let mut conn = self.conn.lock().await;
+        let reply: Option<StreamReadReply> = conn
+            .xread_options(&[&self.stream_key], &[">"], &options)
+            .await?;
+
+        let mut job = None;
+        for entry in reply.into_iter().flat_map(|r| r.keys).flat_map(|k| k.ids) {
+            match parse_entry(&entry, 1) {
+                Some(queued) => job = Some(queued),
+                // Malformed entries can never be processed; drop them
+                None => self.remove_entry(&mut conn, &entry.id).await?,
+            }
+        }
+        Ok(job)
+    }
+
+    /// Acknowledge a job so it leaves the pending entries list
+    pub async fn ack(&self, entry_id: &str) -> Result<()> {
         let mut conn = self.conn.lock().await;
-        let result: Option<String> = conn.rpop(&self.queue_key, None).await?;
-        result.map(|s| Uuid::parse_str(&s)).transpose().map_err(Into::into)
+        self.remove_entry(&mut conn, entry_id).await
     }
-    
+
+    /// Ack an entry and delete it from the stream. The stream is never
+    /// trimmed by length, which could drop jobs that are still waiting or
+    /// pending, so this is what keeps it from growing.
+    async fn remove_entry(&self, conn: &mut ConnectionManager, entry_id: &str) -> Result<()> {
+        redis::pipe()
+            .atomic()
+            .xack(&self.stream_key, &self.group, &[entry_id])
+            .ignore()
+            .xdel(&self.stream_key, &[entry_id])
+            .ignore()
+            .query_async::<_, ()>(conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Take over jobs left pending by consumers that have been silent for
+    /// longer than `min_idle`, e.g. because their worker crashed
+    pub async fn claim_stale(&self, consumer: &str, min_idle: Duration, count: usize) -> Result<Vec<QueuedJob>> {
+        let mut conn = self.conn.lock().await;
+        let pending: StreamPendingCountReply = conn
+            .xpending_count(&self.stream_key, &self.group, "-", "+", count)
+            .await?;
+
+        let min_idle_ms = min_idle.as_millis() as usize;
+        let stale: Vec<_> = pending
+            .ids
+            .iter()
+            .filter(|p| p.last_delivered_ms >= min_idle_ms)
+            .collect();
+        if stale.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<&str> = stale.iter().map(|p| p.id.as_str()).collect();
+        let claimed: StreamClaimReply = conn
+            .xclaim(&self.stream_key, &self.group, consumer, min_idle_ms, &ids)
+            .await?;
+
+        // Consumers kept around by `remove_worker` can go once drained
+        let mut owners: Vec<&str> = stale.iter().map(|p| p.consumer.as_str()).collect();
+        owners.sort_unstable();
+        owners.dedup();
+        for owner in owners.into_iter().filter(|o| *o != consumer) {
+            let registered: bool = conn.hexists(WORKERS_KEY, owner).await?;
+            let left: StreamPendingCountReply = conn
+                .xpending_consumer_count(&self.stream_key, &self.group, "-", "+", 1, owner)
+                .await?;
+            if !registered && left.ids.is_empty() {
+                let _: usize = redis::cmd("XGROUP")
+                    .arg("DELCONSUMER")
+                    .arg(&self.stream_key)
+                    .arg(&self.group)
+                    .arg(owner)
+                    .query_async(&mut *conn)
+                    .await?;
+            }
+        }
+
+        Ok(claimed
+            .ids
+            .iter()
+            .filter_map(|entry| {
+                let deliveries = stale
+                    .iter()
+                    .find(|p| p.id == entry.id)
+                    .map_or(1, |p| p.times_delivered + 1);
+                parse_entry(entry, deliveries)
+            })
+            .collect())
+    }
+
+    /// Record that a worker has started `job_id`, delivered as `entry_id`
+    pub async fn track_inflight(&self, job_id: Uuid, entry_id: &str) -> Result<()> {
+        let mut conn = self.conn.lock().await;
+        redis::pipe()
+            .atomic()
+            .zadd(INFLIGHT_KEY, job_id.to_string(), now_ms())
+            .ignore()
+            .hset(INFLIGHT_ENTRIES_KEY, job_id.to_string(), entry_id)
+            .ignore()
+            .query_async::<_, ()>(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Refresh the heartbeat of a running job. Returns false if the job is no
+    /// longer tracked, i.e. it has already been recovered as orphaned.
+    ///
+    /// Re-claiming the entry for `consumer` resets its idle time, so however
+    /// long the job runs `claim_stale` only takes it once the beats stop.
+    pub async fn heartbeat(&self, job_id: Uuid, consumer: &str, entry_id: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().await;
+        let changed: usize = redis::cmd("ZADD")
+            .arg(INFLIGHT_KEY)
+            .arg("XX")
+            .arg("CH")
+            .arg(now_ms())
+            .arg(job_id.to_string())
+            .query_async(&mut *conn)
+            .await?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        let _: Vec<String> = redis::cmd("XCLAIM")
+            .arg(&self.stream_key)
+            .arg(&self.group)
+            .arg(consumer)
+            .arg(0)
+            .arg(entry_id)
+            .arg("JUSTID")
+            .query_async(&mut *conn)
+            .await?;
+        Ok(true)
+    }
+
+    /// When a running job last sent a heartbeat (unix ms), if it is tracked
+    pub async fn last_heartbeat(&self, job_id: Uuid) -> Result<Option<i64>> {
+        let mut conn = self.conn.lock().await;
+        let beat: Option<i64> = conn.zscore(INFLIGHT_KEY, job_id.to_string()).await?;
+        Ok(beat)
+    }
+
+    pub async fn untrack_inflight(&self, job_id: Uuid) -> Result<()> {
+        let mut conn = self.conn.lock().await;
+        redis::pipe()
+            .atomic()
+            .zrem(INFLIGHT_KEY, job_id.to_string())
+            .ignore()
+            .hdel(INFLIGHT_ENTRIES_KEY, job_id.to_string())
+            .ignore()
+            .query_async::<_, ()>(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Remove and return running jobs with no heartbeat for `stale_after`.
+    /// Removal is per job, so concurrent reapers never recover the same job twice.
+    pub async fn take_orphans(&self, stale_after: Duration, count: isize) -> Result<Vec<OrphanedJob>> {
+        let cutoff = now_ms() - stale_after.as_millis() as i64;
+        let mut conn = self.conn.lock().await;
+        let stale: Vec<String> = conn
+            .zrangebyscore_limit(INFLIGHT_KEY, "-inf", cutoff, 0, count)
+            .await?;
+
+        let mut orphans = Vec::new();
+        for job_id in stale {
+            let removed: usize = conn.zrem(INFLIGHT_KEY, &job_id).await?;
+            if removed == 0 {
+                continue;
+            }
+            let entry_id: Option<String> = conn.hget(INFLIGHT_ENTRIES_KEY, &job_id).await?;
+            let _: usize = conn.hdel(INFLIGHT_ENTRIES_KEY, &job_id).await?;
+
+            match Uuid::parse_str(&job_id) {
+                Ok(job_id) => orphans.push(OrphanedJob { job_id, entry_id }),
+                Err(e) => tracing::error!("Invalid in-flight job ID {}: {}", job_id, e),
+            }
+        }
+        Ok(orphans)
+    }
+
+    /// Number of jobs not yet delivered to any worker, counting at most `limit`
+    pub async fn backlog(&self, limit: usize) -> Result<usize> {
+        let mut conn = self.conn.lock().await;
+        let groups: StreamInfoGroupsReply = conn.xinfo_groups(&self.stream_key).await?;
+        let last_delivered = groups
+            .groups
+            .into_iter()
+            .find(|g| g.name == self.group)
+            .map_or_else(|| "0-0".to_string(), |g| g.last_delivered_id);
+
+        let waiting: StreamRangeReply = conn
+            .xrange_count(&self.stream_key, format!("({}", last_delivered), "+", limit)
+            .await?;
+        Ok(waiting.ids.len())
+    }
+
+    pub async fn depth(&self) -> Result<QueueDepth> {
+        let mut conn = self.conn.lock().await;
+        let groups: StreamInfoGroupsReply = conn.xinfo_groups(&self.stream_key).await?;
+        let group = groups.groups.into_iter().find(|g| g.name == self.group);
+        let last_delivered = group
+            .as_ref()
+            .map_or_else(|| "0-0".to_string(), |g| g.last_delivered_id.clone());
+
+        let waiting: StreamRangeReply = conn
+            .xrange_count(&self.stream_key, format!("({}", last_delivered), "+", DEPTH_SCAN_LIMIT)
+            .await?;
+        let oldest_waiting_ms = waiting
+            .ids
+            .first()
+            .and_then(|entry| entry_time_ms(&entry.id))
+            .map(|added| (now_ms() - added).max(0) as u64);
+
+        Ok(QueueDepth {
+            priority: "default".to_string(),
+            waiting: waiting.ids.len(),
+            pending: group.map_or(0, |g| g.pending),
+            oldest_waiting_ms,
+        })
+    }
+
+    /// Running job IDs with the time of their last heartbeat (unix ms)
+    pub async fn inflight(&self) -> Result<Vec<(Uuid, i64)>> {
+        let mut conn = self.conn.lock().await;
+        let entries: Vec<(String, i64)> = conn.zrange_withscores(INFLIGHT_KEY, 0, -1).await?;
+        Ok(entries
+            .into_iter()
+            .filter_map(|(job_id, beat)| Uuid::parse_str(&job_id).ok().map(|id| (id, beat)))
+            .collect())
+    }
+
+    /// Number of jobs currently being run by a worker
+    pub async fn running_count(&self) -> Result<usize> {
+        let mut conn = self.conn.lock().await;
+        let count: usize = conn.zcard(INFLIGHT_KEY).await?;
+        Ok(count)
+    }
+
+    pub async fn record_duration(&self, duration: Duration) -> Result<()> {
+        let mut conn = self.conn.lock().await;
+        redis::pipe()
+            .lpush(DURATIONS_KEY, duration.as_millis() as u64)
+            .ignore()
+            .ltrim(DURATIONS_KEY, 0, DURATION_SAMPLES - 1)
+            .ignore()
+            .query_async::<_, ()>(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Mean duration of recent jobs, if any have finished
+    pub async fn average_duration(&self) -> Result<Option<Duration>> {
+        let mut conn = self.conn.lock().await;
+        let samples: Vec<u64> = conn.lrange(DURATIONS_KEY, 0, -1).await?;
+        if samples.is_empty() {
+            return Ok(None);
+        }
+        let mean = samples.iter().sum::<u64>() / samples.len() as u64;
+        Ok(Some(Duration::from_millis(mean)))
+    }
+
+    pub async fn report_worker(&self, info: &WorkerInfo) -> Result<()> {
+        let mut conn = self.conn.lock().await;
+        let _: usize = conn
+            .hset(WORKERS_KEY, &info.id, serde_json::to_string(info)?)
+            .await?;
+        Ok(())
+    }
+
+    /// Drop a worker from the registry, e.g. on graceful shutdown. Its
+    /// consumer is only deleted once nothing is pending on it, since that
+    /// would discard jobs delivered to it but not yet acknowledged; left in
+    /// place, those are taken over by `claim_stale`.
+    pub async fn remove_worker(&self, worker_id: &str) -> Result<()> {
+        let mut conn = self.conn.lock().await;
+        let _: usize = conn.hdel(WORKERS_KEY, worker_id).await?;
+        let pending: StreamPendingCountReply = conn
+            .xpending_consumer_count(&self.stream_key, &self.group, "-", "+", 1, worker_id)
+            .await?;
+        if !pending.ids.is_empty() {
+            tracing::info!("Keeping consumer {} until its pending jobs are claimed", worker_id);
+            return Ok(());
+        }
+        let _: usize = redis::cmd("XGROUP")
+            .arg("DELCONSUMER")
+            .arg(&self.stream_key)
+            .arg(&self.group)
+            .arg(worker_id)
+            .query_async(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    /// All registered workers with their load, most recently seen first
+    pub async fn workers(&self) -> Result<Vec<WorkerStatus>> {
+        let mut conn = self.conn.lock().await;
+        let registered: HashMap<String, String> = conn.hgetall(WORKERS_KEY).await?;
+        let consumers: StreamInfoConsumersReply = conn
+            .xinfo_consumers(&self.stream_key, &self.group)
+            .await?;
+
+        let now = Utc::now();
+        let mut workers: Vec<WorkerStatus> = registered
+            .into_values()
+            .filter_map(|json| serde_json::from_str::<WorkerInfo>(&json).ok())
+            .map(|info| {
+                let pending = consumers
+                    .consumers
+                    .iter()
+                    .find(|c| c.name == info.id)
+                    .map_or(0, |c| c.pending);
+                let age = (now - info.last_seen).to_std().unwrap_or_default();
+                let state = if age > WORKER_STALE_AFTER {
+                    WorkerState::Stale
+                } else {
+                    WorkerState::Online
+                };
+                WorkerStatus { info, state, pending }
+            })
+            .collect();
+
+        workers.sort_by_key(|w| std::cmp::Reverse(w.info.last_seen));
+        Ok(workers)
+    }
+
     pub async fn get_queue_length(&self) -> Result<usize> {
         let mut conn = self.conn.lock().await;
-        let len: usize = conn.llen(&self.queue_key).await?;
+        let len: usize = conn.xlen(&self.stream_key).await?;
         Ok(len)
     }
-}
\ No newline at end of file
+
+    pub async fn info(&self) -> Result<QueueInfo> {
+        let mut conn = self.conn.lock().await;
+        let length: usize = conn.xlen(&self.stream_key).await?;
+        let groups: StreamInfoGroupsReply = conn.xinfo_groups(&self.stream_key).await?;
+        let group = groups.groups.into_iter().find(|g| g.name == self.group);
+
+        Ok(QueueInfo {
+            length,
+            pending: group.as_ref().map_or(0, |g| g.pending),
+            consumers: group.as_ref().map_or(0, |g| g.consumers),
+            last_delivered_id: group.map(|g| g.last_delivered_id),
+        })
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Unix ms a stream entry was added, from the first half of its ID
+fn entry_time_ms(entry_id: &str) -> Option<i64> {
+    entry_id.split('-').next()?.parse().ok()
+}
+
+fn parse_entry(entry: &StreamId, delivery_count: usize) -> Option<QueuedJob> {
+    let job_id: String = entry.get("job_id")?;
+    match Uuid::parse_str(&job_id) {
+        Ok(job_id) => Some(QueuedJob {
+            entry_id: entry.id.clone(),
+            job_id,
+            delivery_count,
+        }),
+        Err(e) => {
+            tracing::error!("Invalid job ID {} in stream entry {}: {}", job_id, entry.id, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Queue on its own stream so tests don't touch real jobs
+    async fn test_queue() -> RedisQueue {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6380/".to_string());
+        let conn = ConnectionManager::new(redis::Client::open(url).unwrap()).await.unwrap();
+        let queue = RedisQueue {
+            conn: Mutex::new(conn),
+            stream_key: format!("syla:test:stream:{}", Uuid::new_v4()),
+            group: GROUP_NAME.to_string(),
+        };
+        queue.ensure_group().await.unwrap();
+        queue
+    }
+
+    async fn consumers(queue: &RedisQueue) -> Vec<String> {
+        let mut conn = queue.conn.lock().await;
+        let reply: StreamInfoConsumersReply =
+            conn.xinfo_consumers(&queue.stream_key, &queue.group).await.unwrap();
+        reply.consumers.into_iter().map(|c| c.name).collect()
+    }
+
+    #[test]
+    fn entry_time_is_the_first_half_of_the_id() {
+        assert_eq!(entry_time_ms("1700000000000-3"), Some(1_700_000_000_000));
+        assert_eq!(entry_time_ms("not-an-id"), None);
+    }
+
+    #[test]
+    fn entries_without_a_valid_job_id_are_skipped() {
+        let job_id = Uuid::new_v4();
+        let mut entry = StreamId {
+            id: "1-0".to_string(),
+            map: HashMap::new(),
+        };
+        assert!(parse_entry(&entry, 1).is_none());
+
+        entry.map.insert("job_id".to_string(), redis::Value::Data(b"nope".to_vec()));
+        assert!(parse_entry(&entry, 1).is_none());
+
+        entry.map.insert("job_id".to_string(), redis::Value::Data(job_id.to_string().into_bytes()));
+        let queued = parse_entry(&entry, 2).unwrap();
+        assert_eq!(queued.job_id, job_id);
+        assert_eq!(queued.entry_id, "1-0");
+        assert_eq!(queued.delivery_count, 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn only_acknowledged_jobs_leave_the_stream() {
+        let queue = test_queue().await;
+        for _ in 0..3 {
+            queue.push_job(Uuid::new_v4()).await.unwrap();
+        }
+        let first = queue.pop_job("worker", Duration::from_millis(10)).await.unwrap().unwrap();
+        queue.pop_job("worker", Duration::from_millis(10)).await.unwrap().unwrap();
+        assert_eq!(queue.get_queue_length().await.unwrap(), 3);
+
+        queue.ack(&first.entry_id).await.unwrap();
+        let info = queue.info().await.unwrap();
+        assert_eq!(info.length, 2);
+        assert_eq!(info.pending, 1);
+        assert_eq!(queue.depth().await.unwrap().waiting, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn heartbeats_keep_long_jobs_from_being_claimed() {
+        let queue = test_queue().await;
+        let job_id = Uuid::new_v4();
+        queue.push_job(job_id).await.unwrap();
+        let queued = queue.pop_job("running", Duration::from_millis(10)).await.unwrap().unwrap();
+        queue.track_inflight(job_id, &queued.entry_id).await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(queue.heartbeat(job_id, "running", &queued.entry_id).await.unwrap());
+        let claimed = queue.claim_stale("other", Duration::from_millis(50), 10).await.unwrap();
+        assert!(claimed.is_empty());
+
+        queue.untrack_inflight(job_id).await.unwrap();
+        assert!(!queue.heartbeat(job_id, "running", &queued.entry_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn removing_a_worker_keeps_its_pending_jobs() {
+        let queue = test_queue().await;
+        let job_id = Uuid::new_v4();
+        queue.push_job(job_id).await.unwrap();
+        queue.pop_job("leaving", Duration::from_millis(10)).await.unwrap().unwrap();
+
+        queue.remove_worker("leaving").await.unwrap();
+        assert_eq!(consumers(&queue).await, vec!["leaving".to_string()]);
+
+        let claimed = queue.claim_stale("other", Duration::ZERO, 10).await.unwrap();
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].job_id, job_id);
+        assert_eq!(claimed[0].delivery_count, 2);
+        // Drained and unregistered, so the old consumer is gone
+        assert_eq!(consumers(&queue).await, vec!["other".to_string()]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn removing_an_idle_worker_deletes_its_consumer() {
+        let queue = test_queue().await;
+        queue.pop_job("idle", Duration::from_millis(10)).await.unwrap();
+        assert_eq!(consumers(&queue).await, vec!["idle".to_string()]);
+
+        queue.remove_worker("idle").await.unwrap();
+        assert!(consumers(&queue).await.is_empty());
+    }
+}
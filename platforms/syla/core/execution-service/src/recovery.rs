@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::models::{ExecutionResult, JobStatus};
+use crate::queue::OrphanedJob;
+use crate::state::ServiceState;
+
+/// What to do with a job whose worker died mid-execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrphanPolicy {
+    /// Put the job back on the queue until it runs out of attempts
+    Requeue,
+    /// Mark the job failed straight away
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecoveryConfig {
+    pub policy: OrphanPolicy,
+    pub max_attempts: u32,
+    /// Running jobs are orphaned after this long without a heartbeat
+    pub stale_after: Duration,
+    pub scan_interval: Duration,
+}
+
+impl RecoveryConfig {
+    pub fn from_env() -> Self {
+        let policy = match std::env::var("ORPHANED_JOB_POLICY").as_deref() {
+            Ok("fail") => OrphanPolicy::Fail,
+            _ => OrphanPolicy::Requeue,
+        };
+
+        Self {
+            policy,
+            max_attempts: std::env::var("MAX_JOB_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+            stale_after: Duration::from_secs(
+                std::env::var("JOB_HEARTBEAT_TIMEOUT_SECONDS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(30),
+            ),
+            scan_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+pub async fn run_orphan_reaper(state: Arc<ServiceState>, config: RecoveryConfig) {
+    let mut ticker = tokio::time::interval(config.scan_interval);
+    loop {
+        ticker.tick().await;
+        let orphans = match state.queue.take_orphans(config.stale_after, 100).await {
+            Ok(orphans) => orphans,
+            Err(e) => {
+                error!("Failed to scan for orphaned jobs: {}", e);
+                continue;
+            }
+        };
+
+        for orphan in orphans {
+            if let Err(e) = recover_job(&state, &config, &orphan).await {
+                error!("Failed to recover orphaned job {}: {}", orphan.job_id, e);
+            }
+        }
+    }
+}
+
+async fn recover_job(
+    state: &ServiceState,
+    config: &RecoveryConfig,
+    orphan: &OrphanedJob,
+) -> anyhow::Result<()> {
+    let mut job = state.get_execution(orphan.job_id).await?;
+    if !matches!(job.status, JobStatus::Running) {
+        // Finished between its last heartbeat and this scan
+        return Ok(());
+    }
+
+    // Settle the original delivery so stream-level claiming doesn't also
+    // redeliver it
+    if let Some(entry_id) = &orphan.entry_id {
+        state.queue.ack(entry_id).await?;
+    }
+
+    let worker = job.worker_id.clone().unwrap_or_else(|| "unknown".to_string());
+
+    if config.policy == OrphanPolicy::Requeue && job.attempts < config.max_attempts {
+        warn!(
+            "Requeueing job {} after worker {} stopped responding (attempt {} of {})",
+            job.id, worker, job.attempts, config.max_attempts
+        );
+        job.status = JobStatus::Queued;
+        job.started_at = None;
+        state.update_execution(&job).await?;
+        state.queue.push_job(job.id).await?;
+    } else {
+        info!(
+            "Failing job {} after worker {} stopped responding",
+            job.id, worker
+        );
+        let stderr = format!(
+            "Execution abandoned: worker {} stopped responding after {} attempt(s)",
+            worker, job.attempts
+        );
+        job.status = JobStatus::Failed;
+        job.result = Some(ExecutionResult {
+            exit_code: -1,
+            stdout: String::new(),
+            stderr_bytes: stderr.len() as u64,
+            stderr,
+            duration_ms: 0,
+            stdout_bytes: 0,
+            stdout_truncated: false,
+            stderr_truncated: false,
+            resource_usage: None,
+            stdout_object: None,
+            stderr_object: None,
+            runtime_version: None,
+        });
+        job.completed_at = Some(chrono::Utc::now());
+        state.update_execution(&job).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+use crate::docker::SCRATCH_PREFIX;
+use crate::index;
+use crate::models::{ExecutionJob, JobStatus};
+use crate::state::ServiceState;
+
+/// Job keys examined per SCAN round trip
+const SCAN_BATCH: usize = 500;
+
+/// How long execution records are kept, by final status
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionPolicy {
+    pub completed_seconds: u64,
+    pub failed_seconds: u64,
+    pub timeout_seconds: u64,
+    /// Queued jobs that never ran, e.g. because the queue entry was lost
+    pub queued_seconds: u64,
+    /// Scratch directories left on disk by a crashed run
+    pub scratch_seconds: u64,
+}
+
+impl RetentionPolicy {
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: u64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            completed_seconds: read("RETENTION_COMPLETED_SECONDS", 24 * 3600),
+            failed_seconds: read("RETENTION_FAILED_SECONDS", 7 * 24 * 3600),
+            timeout_seconds: read("RETENTION_TIMEOUT_SECONDS", 7 * 24 * 3600),
+            queued_seconds: read("RETENTION_QUEUED_SECONDS", 7 * 24 * 3600),
+            scratch_seconds: read("RETENTION_SCRATCH_SECONDS", 3600),
+        }
+    }
+
+    /// Whether a record has outlived its retention. Running jobs are never
+    /// expired here; orphan recovery settles them first.
+    fn is_expired(&self, job: &ExecutionJob, now: DateTime<Utc>) -> bool {
+        let (retention, since) = match job.status {
+            JobStatus::Running => return false,
+            JobStatus::Queued => (self.queued_seconds, job.created_at),
+            JobStatus::Completed => (self.completed_seconds, finished_at(job)),
+            JobStatus::Failed => (self.failed_seconds, finished_at(job)),
+            JobStatus::Timeout => (self.timeout_seconds, finished_at(job)),
+        };
+        (now - since).num_seconds() >= retention as i64
+    }
+}
+
+fn finished_at(job: &ExecutionJob) -> DateTime<Utc> {
+    job.completed_at.unwrap_or(job.created_at)
+}
+
+/// Totals since the service started, plus the outcome of the latest sweep
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetentionStats {
+    pub sweeps: u64,
+    pub records_deleted: u64,
+    pub record_bytes_reclaimed: u64,
+    pub scratch_dirs_removed: u64,
+    pub scratch_bytes_reclaimed: u64,
+    pub last_sweep_at: Option<DateTime<Utc>>,
+    pub last_sweep_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionReport {
+    pub policy: RetentionPolicy,
+    pub stats: RetentionStats,
+}
+
+pub struct RetentionManager {
+    policy: RetentionPolicy,
+    stats: Mutex<RetentionStats>,
+}
+
+impl RetentionManager {
+    pub fn new(policy: RetentionPolicy) -> Self {
+        Self {
+            policy,
+            stats: Mutex::new(RetentionStats::default()),
+        }
+    }
+
+    pub async fn report(&self) -> RetentionReport {
+        RetentionReport {
+            policy: self.policy.clone(),
+            stats: self.stats.lock().await.clone(),
+        }
+    }
+
+    /// Delete expired execution records and stale scratch directories
+    pub async fn sweep(&self, state: &ServiceState) -> Result<()> {
+        let start = std::time::Instant::now();
+        let (records, record_bytes) = self.sweep_records(state).await?;
+        let (dirs, dir_bytes) = self.sweep_scratch(&std::env::temp_dir());
+
+        if records > 0 || dirs > 0 {
+            info!(
+                "Retention sweep removed {} records ({} bytes) and {} scratch dirs ({} bytes)",
+                records, record_bytes, dirs, dir_bytes
+            );
+        }
+
+        let mut stats = self.stats.lock().await;
+        stats.sweeps += 1;
+        stats.records_deleted += records;
+        stats.record_bytes_reclaimed += record_bytes;
+        stats.scratch_dirs_removed += dirs;
+        stats.scratch_bytes_reclaimed += dir_bytes;
+        stats.last_sweep_at = Some(Utc::now());
+        stats.last_sweep_ms = Some(start.elapsed().as_millis() as u64);
+        Ok(())
+    }
+
+    async fn sweep_records(&self, state: &ServiceState) -> Result<(u64, u64)> {
+        let now = Utc::now();
+        let mut cursor: u64 = 0;
+        let mut deleted = 0;
+        let mut reclaimed = 0;
+
+        loop {
+            // Hold the shared connection per batch, not for the whole scan
+            let mut redis = state.redis.lock().await;
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg("job:*")
+                .arg("COUNT")
+                .arg(SCAN_BATCH)
+                .query_async(&mut *redis)
+                .await?;
+
+            let mut expired = Vec::new();
+            for key in keys {
+                let json: Option<String> = redis::cmd("GET").arg(&key).query_async(&mut *redis).await?;
+                let Some(json) = json else { continue };
+
+                let job = match serde_json::from_str::<ExecutionJob>(&json) {
+                    Ok(job) => job,
+                    Err(e) => {
+                        error!("Skipping unreadable execution record {}: {}", key, e);
+                        continue;
+                    }
+                };
+                if self.policy.is_expired(&job, now) {
+                    let mut pipe = redis::pipe();
+                    pipe.cmd("DEL").arg(&key).ignore();
+                    index::remove(&mut pipe, &job);
+                    pipe.query_async::<_, ()>(&mut *redis).await?;
+                    deleted += 1;
+                    reclaimed += json.len() as u64;
+                    expired.push(job);
+                }
+            }
+            drop(redis);
+
+            // Object storage calls are slow; make them off the shared connection
+            if let Some(offload) = &state.log_offload {
+                for job in &expired {
+                    if let Err(e) = offload.delete(job).await {
+                        error!("Failed to delete offloaded logs of job {}: {:#}", job.id, e);
+                    }
+                }
+            }
+
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        Ok((deleted, reclaimed))
+    }
+
+    fn sweep_scratch(&self, temp_dir: &Path) -> (u64, u64) {
+        let max_age = Duration::from_secs(self.policy.scratch_seconds);
+        let Ok(entries) = std::fs::read_dir(temp_dir) else {
+            return (0, 0);
+        };
+
+        let mut removed = 0;
+        let mut reclaimed = 0;
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().starts_with(SCRATCH_PREFIX) {
+                continue;
+            }
+            let age = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+            if age.is_none_or(|age| age < max_age) {
+                continue;
+            }
+
+            let path = entry.path();
+            let size = dir_size(&path);
+            match std::fs::remove_dir_all(&path) {
+                Ok(()) => {
+                    removed += 1;
+                    reclaimed += size;
+                }
+                Err(e) => error!("Failed to remove scratch dir {}: {}", path.display(), e),
+            }
+        }
+        (removed, reclaimed)
+    }
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(m) if m.is_dir() => dir_size(&entry.path()),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+pub async fn run_cleanup(state: Arc<ServiceState>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        if let Err(e) = state.retention.sweep(&state).await {
+            error!("Retention sweep failed: {}", e);
+        }
+    }
+}
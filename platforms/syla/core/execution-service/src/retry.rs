@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::docker::ExecutionResult;
+
+/// Why a run failed, for deciding whether running it again could help
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureCategory {
+    /// The sandbox couldn't run the code: Docker errors, pulls, crashes
+    Infrastructure,
+    /// The program ran out of time
+    Timeout,
+    /// The program ran and exited non-zero
+    ProgramError,
+}
+
+impl FailureCategory {
+    /// How a run that got to the program failed; `None` when it succeeded.
+    /// Runs that never got there are `Infrastructure` failures.
+    pub fn of(result: &ExecutionResult) -> Option<Self> {
+        if result.timed_out {
+            Some(FailureCategory::Timeout)
+        } else if result.exit_code != 0 {
+            Some(FailureCategory::ProgramError)
+        } else {
+            None
+        }
+    }
+}
+
+/// Per-request overrides of the server's retry policy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetryOptions {
+    /// Runs in total, the first included; clamped to the server limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+    /// Wait before the first retry, doubling for each one after
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backoff_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_on: Option<Vec<FailureCategory>>,
+}
+
+/// How the worker retries failed runs
+#[derive(Debug, Clone, Serialize)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    pub retry_on: Vec<FailureCategory>,
+    /// Most attempts a request may ask for
+    pub attempts_limit: u32,
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: u64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        let retry_on = match std::env::var("RETRY_ON") {
+            Ok(value) => value
+                .split(',')
+                .filter_map(|name| serde_json::from_value(serde_json::Value::String(name.trim().to_string())).ok())
+                .collect(),
+            Err(_) => vec![FailureCategory::Infrastructure],
+        };
+        Self {
+            max_attempts: read("RETRY_MAX_ATTEMPTS", 3) as u32,
+            backoff: Duration::from_millis(read("RETRY_BACKOFF_MS", 500)),
+            max_backoff: Duration::from_millis(read("RETRY_MAX_BACKOFF_MS", 10_000)),
+            retry_on,
+            attempts_limit: read("RETRY_ATTEMPTS_LIMIT", 5) as u32,
+        }
+    }
+
+    /// The policy for one request: its overrides on top of the server's
+    pub fn for_request(&self, options: Option<&RetryOptions>) -> Self {
+        let mut policy = self.clone();
+        if let Some(options) = options {
+            if let Some(max_attempts) = options.max_attempts {
+                policy.max_attempts = max_attempts.clamp(1, self.attempts_limit.max(1));
+            }
+            if let Some(backoff_ms) = options.backoff_ms {
+                policy.backoff = Duration::from_millis(backoff_ms).min(self.max_backoff);
+            }
+            if let Some(retry_on) = &options.retry_on {
+                policy.retry_on = retry_on.clone();
+            }
+        }
+        policy
+    }
+
+    /// How long to wait before running again after `attempt` failed with
+    /// `failure`; `None` when it shouldn't be retried
+    pub fn retry_after(&self, attempt: u32, failure: FailureCategory) -> Option<Duration> {
+        if attempt >= self.max_attempts || !self.retry_on.contains(&failure) {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        Some(self.backoff.saturating_mul(factor).min(self.max_backoff))
+    }
+}
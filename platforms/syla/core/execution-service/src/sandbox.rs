@@ -0,0 +1,393 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tokio::process::Command as TokioCommand;
+
+use crate::docker::{capture, scratch_dir, ExecutionResult, OutputLimits};
+
+/// Applies the rlimits, runs the program, then records the CPU time its
+/// processes used to the stats mount. Arguments: data segment limit in KiB,
+/// CPU seconds, process count, then the program.
+const LIMITS_WRAPPER: &str = r#"ulimit -d "$1" && ulimit -t "$2" && ulimit -u "$3" || exit 126
+shift 3
+"$@"
+status=$?
+times > /.syla-stats/usage 2>/dev/null
+exit $status"#;
+
+const STATS_MOUNT: &str = "/.syla-stats";
+
+/// Host directories the sandbox sees read-only when `SANDBOX_READONLY_PATHS`
+/// isn't set; whichever exist are mounted
+const DEFAULT_READONLY_PATHS: &[&str] = &[
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib64",
+    "/etc/alternatives",
+    "/etc/ssl",
+    "/opt",
+];
+
+const SANDBOX_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// Syscalls nsjail refuses (with EPERM) on top of its namespaces, in its
+/// Kafel policy language
+const NSJAIL_SECCOMP_POLICY: &str = "POLICY syla { ERRNO(1) { \
+    ptrace, process_vm_readv, process_vm_writev, mount, umount2, pivot_root, chroot, \
+    unshare, setns, kexec_load, kexec_file_load, reboot, swapon, swapoff, \
+    init_module, finit_module, delete_module, bpf, perf_event_open, \
+    keyctl, add_key, request_key, userfaultfd, acct, settimeofday, clock_settime \
+} } USE syla DEFAULT ALLOW";
+
+/// Program that isolates a run without a container daemon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandboxTool {
+    Bubblewrap,
+    Nsjail,
+}
+
+impl SandboxTool {
+    fn binary(self) -> &'static str {
+        match self {
+            SandboxTool::Bubblewrap => "bwrap",
+            SandboxTool::Nsjail => "nsjail",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    /// Tools to try, in order; empty disables the backend
+    pub tools: Vec<SandboxTool>,
+    /// Languages that run in the sandbox unless a request picks a backend;
+    /// `*` sends every language it can run here
+    pub default_languages: Vec<String>,
+    /// Host directories mounted read-only, providing the interpreters
+    pub readonly_paths: Vec<PathBuf>,
+    pub memory_limit: u64,
+    /// Counted across every process of the worker's user, as rlimits are
+    pub max_processes: u64,
+    /// Compiled seccomp filter for bubblewrap, which can't build its own
+    pub seccomp_filter: Option<PathBuf>,
+}
+
+impl SandboxConfig {
+    pub fn from_env() -> Self {
+        let list = |key: &str| -> Option<Vec<String>> {
+            std::env::var(key)
+                .ok()
+                .map(|v| v.split(',').map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect())
+        };
+        let tools = match std::env::var("SANDBOX_TOOL").unwrap_or_default().trim() {
+            "" | "auto" => vec![SandboxTool::Bubblewrap, SandboxTool::Nsjail],
+            "bwrap" | "bubblewrap" => vec![SandboxTool::Bubblewrap],
+            "nsjail" => vec![SandboxTool::Nsjail],
+            "none" => Vec::new(),
+            other => {
+                tracing::warn!("Unknown SANDBOX_TOOL '{}'; the sandbox backend is disabled", other);
+                Vec::new()
+            }
+        };
+        let read = |key: &str, default: u64| std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default);
+
+        Self {
+            tools,
+            default_languages: list("SANDBOX_DEFAULT_LANGUAGES").unwrap_or_default(),
+            readonly_paths: list("SANDBOX_READONLY_PATHS")
+                .unwrap_or_else(|| DEFAULT_READONLY_PATHS.iter().map(|p| p.to_string()).collect())
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+            memory_limit: read("SANDBOX_MEMORY_MB", 512) * 1024 * 1024,
+            max_processes: read("SANDBOX_MAX_PROCESSES", 64),
+            seccomp_filter: std::env::var("SANDBOX_SECCOMP_BPF").ok().map(PathBuf::from),
+        }
+    }
+}
+
+/// Runs code directly on the host's interpreters inside bubblewrap or
+/// nsjail: fresh namespaces with no network, a read-only view of the
+/// toolchains, rlimits for memory, CPU and processes, and a seccomp filter.
+/// Needs no daemon or root, for CI runners and laptops without Docker.
+pub struct SandboxExecutor {
+    /// The first configured tool that passed the startup probe
+    tool: Option<SandboxTool>,
+    config: SandboxConfig,
+    output_limits: OutputLimits,
+}
+
+impl SandboxExecutor {
+    /// Probes the configured tools, keeping the first that can actually
+    /// create a sandbox here; unprivileged user namespaces are often off
+    pub fn new(config: SandboxConfig, output_limits: OutputLimits) -> Self {
+        let mut tool = None;
+        for candidate in &config.tools {
+            match probe(*candidate) {
+                Ok(()) => {
+                    tool = Some(*candidate);
+                    break;
+                }
+                Err(e) => tracing::debug!("Sandbox tool {} unusable: {:#}", candidate.binary(), e),
+            }
+        }
+        match tool {
+            Some(tool) => tracing::info!("Sandbox backend available using {}", tool.binary()),
+            None if !config.default_languages.is_empty() => tracing::warn!(
+                "SANDBOX_DEFAULT_LANGUAGES is set but neither bubblewrap nor nsjail works here; those languages run under Docker"
+            ),
+            None => {}
+        }
+
+        Self {
+            tool,
+            config,
+            output_limits,
+        }
+    }
+
+    pub fn tool(&self) -> Option<SandboxTool> {
+        self.tool
+    }
+
+    /// Creates an empty sandbox with the chosen tool, as at startup
+    pub fn probe(&self) -> Result<()> {
+        match self.tool {
+            Some(tool) => probe(tool),
+            None => bail!("Neither bubblewrap nor nsjail is usable"),
+        }
+    }
+
+    pub fn supports(&self, language: &str) -> bool {
+        self.tool.is_some()
+            && language_command(language).is_some_and(|(program, _)| self.find_program(program).is_some())
+    }
+
+    /// Whether a language runs in the sandbox when the request doesn't say
+    pub fn is_default_for(&self, language: &str) -> bool {
+        self.supports(language) && self.config.default_languages.iter().any(|l| l == language || l == "*")
+    }
+
+    /// Whether every run defaults to the sandbox, so Docker isn't needed
+    pub fn replaces_docker(&self) -> bool {
+        self.tool.is_some() && self.config.default_languages.iter().any(|l| l == "*")
+    }
+
+    #[tracing::instrument(name = "sandbox_execute", skip(self, code, max_output_bytes))]
+    pub async fn execute(
+        &self,
+        code: &str,
+        language: &str,
+        timeout_seconds: u64,
+        max_output_bytes: Option<u64>,
+    ) -> Result<ExecutionResult> {
+        let Some(tool) = self.tool else {
+            bail!("The sandbox backend is not available on this worker");
+        };
+        let Some((program, args)) = language_command(language) else {
+            bail!("Language '{}' can't run in the sandbox", language);
+        };
+        if self.find_program(program).is_none() {
+            bail!("No {} found for language '{}' in the sandbox's read-only paths", program, language);
+        }
+
+        let temp_dir = scratch_dir()?;
+        let file_name = format!("main.{}", source_extension(language));
+        std::fs::write(temp_dir.path().join(&file_name), code)?;
+        let stats_dir = scratch_dir()?;
+        std::fs::set_permissions(stats_dir.path(), std::fs::Permissions::from_mode(0o777))?;
+
+        let mut command = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            LIMITS_WRAPPER.to_string(),
+            "sh".to_string(),
+            (self.config.memory_limit / 1024).to_string(),
+            // The wall-clock timeout ends runs first; this catches CPU spent
+            // in several processes at once
+            (timeout_seconds + 1).to_string(),
+            self.config.max_processes.to_string(),
+            program.to_string(),
+        ];
+        command.extend(args.iter().map(|arg| arg.to_string()));
+
+        let mut cmd = match tool {
+            SandboxTool::Bubblewrap => self.bubblewrap(temp_dir.path(), stats_dir.path(), &command),
+            SandboxTool::Nsjail => self.nsjail(temp_dir.path(), stats_dir.path(), &command, timeout_seconds),
+        };
+
+        let start = Instant::now();
+        let limit = self.output_limits.resolve(max_output_bytes);
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", tool.binary()))?;
+        let stdout = tokio::spawn(capture(child.stdout.take().context("No stdout pipe")?, limit));
+        let stderr = tokio::spawn(capture(child.stderr.take().context("No stderr pipe")?, limit));
+
+        let status = tokio::time::timeout(Duration::from_secs(timeout_seconds), child.wait()).await;
+        let timed_out = status.is_err();
+        if timed_out {
+            // Both tools take the whole sandbox down with them
+            let _ = child.kill().await;
+        }
+
+        let stdout = stdout.await??;
+        let mut stderr = stderr.await??;
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let stderr_truncated = stderr.truncated();
+
+        let exit_code = match status {
+            Ok(status) => status?.code().unwrap_or(-1),
+            Err(_) => {
+                stderr.data.extend_from_slice(b"Execution timed out");
+                -1
+            }
+        };
+
+        Ok(ExecutionResult {
+            exit_code,
+            stdout: stdout.text(),
+            stderr: stderr.text(),
+            duration_ms,
+            timed_out,
+            stdout_bytes: stdout.total_bytes,
+            stderr_bytes: stderr.total_bytes,
+            stdout_truncated: stdout.truncated(),
+            stderr_truncated,
+            cpu_time_ms: read_cpu_time(stats_dir.path()),
+            // rlimits cap memory but, without a cgroup, nothing records the peak
+            peak_memory_bytes: None,
+            artifacts: HashMap::new(),
+        })
+    }
+
+    fn readonly_paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.config.readonly_paths.iter().filter(|path| path.exists())
+    }
+
+    /// The host binary for `program` the sandbox would run
+    fn find_program(&self, program: &str) -> Option<PathBuf> {
+        SANDBOX_PATH
+            .split(':')
+            .map(|dir| Path::new(dir).join(program))
+            .filter(|path| self.readonly_paths().any(|mounted| path.starts_with(mounted)))
+            .find(|path| path.is_file())
+    }
+
+    fn bubblewrap(&self, workspace: &Path, stats: &Path, command: &[String]) -> TokioCommand {
+        // bwrap reads a compiled filter from a file descriptor, so a shell
+        // opens it on fd 10 before exec'ing bwrap
+        let mut cmd = match &self.config.seccomp_filter {
+            Some(filter) => {
+                let mut cmd = TokioCommand::new("sh");
+                cmd.args(["-c", "filter=$1; shift; exec \"$@\" 10<\"$filter\"", "sh"])
+                    .arg(filter)
+                    .args(["bwrap", "--seccomp", "10"]);
+                cmd
+            }
+            None => TokioCommand::new("bwrap"),
+        };
+        cmd.args(["--unshare-all", "--die-with-parent", "--new-session", "--clearenv"])
+            .args(["--hostname", "syla-sandbox"])
+            .args(["--setenv", "PATH", SANDBOX_PATH])
+            .args(["--setenv", "HOME", "/tmp"])
+            .args(["--setenv", "GOCACHE", "/tmp/go-cache"]);
+        for path in self.readonly_paths() {
+            cmd.arg("--ro-bind").arg(path).arg(path);
+        }
+        cmd.args(["--proc", "/proc", "--dev", "/dev", "--tmpfs", "/tmp"])
+            .arg("--ro-bind")
+            .arg(workspace)
+            .arg("/workspace")
+            .arg("--bind")
+            .arg(stats)
+            .arg(STATS_MOUNT)
+            .args(["--chdir", "/workspace", "--"])
+            .args(command);
+        cmd
+    }
+
+    fn nsjail(&self, workspace: &Path, stats: &Path, command: &[String], timeout_seconds: u64) -> TokioCommand {
+        let mut cmd = TokioCommand::new("nsjail");
+        cmd.args(["--mode", "o", "--really_quiet", "--hostname", "syla-sandbox"])
+            .args(["--time_limit", &(timeout_seconds + 1).to_string()])
+            // Memory and processes are limited by the wrapper, like under
+            // bubblewrap; nsjail's own defaults are too tight for compilers
+            .args(["--rlimit_as", "max", "--rlimit_nproc", "max", "--rlimit_fsize", "64", "--rlimit_nofile", "256"])
+            .args(["--seccomp_string", NSJAIL_SECCOMP_POLICY])
+            .args(["--env", &format!("PATH={}", SANDBOX_PATH)])
+            .args(["--env", "HOME=/tmp", "--env", "GOCACHE=/tmp/go-cache"]);
+        for path in self.readonly_paths() {
+            cmd.arg("--bindmount_ro").arg(path);
+        }
+        cmd.args(["--tmpfsmount", "/tmp"])
+            .arg("--bindmount_ro")
+            .arg(format!("{}:/workspace", workspace.display()))
+            .arg("--bindmount")
+            .arg(format!("{}:{}", stats.display(), STATS_MOUNT))
+            .args(["--cwd", "/workspace", "--"])
+            .args(command);
+        cmd
+    }
+}
+
+/// Creates and tears down an empty sandbox
+fn probe(tool: SandboxTool) -> Result<()> {
+    let mut cmd = Command::new(tool.binary());
+    match tool {
+        SandboxTool::Bubblewrap => cmd.args(["--unshare-all", "--ro-bind", "/", "/", "--", "true"]),
+        SandboxTool::Nsjail => cmd.args(["--mode", "o", "--really_quiet", "--chroot", "/", "--", "/bin/true"]),
+    };
+    let output = cmd
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("{} not found", tool.binary()))?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Interpreter or compiler on the host, and its arguments
+fn language_command(language: &str) -> Option<(&'static str, &'static [&'static str])> {
+    match language {
+        "python" => Some(("python3", &["main.py"])),
+        "javascript" => Some(("node", &["main.js"])),
+        "go" => Some(("go", &["run", "main.go"])),
+        "rust" => Some(("sh", &["-c", "rustc -o /tmp/main main.rs && exec /tmp/main"])),
+        _ => None,
+    }
+}
+
+fn source_extension(language: &str) -> &'static str {
+    match language {
+        "python" => "py",
+        "javascript" => "js",
+        "go" => "go",
+        "rust" => "rs",
+        _ => "txt",
+    }
+}
+
+/// Children's user and system time from the `times` line the wrapper wrote,
+/// e.g. `0m0.012000s 0m0.004000s`
+fn read_cpu_time(stats_dir: &Path) -> Option<u64> {
+    let contents = std::fs::read_to_string(stats_dir.join("usage")).ok()?;
+    let children = contents.lines().nth(1)?;
+    children
+        .split_whitespace()
+        .map(|field| {
+            let (minutes, seconds) = field.strip_suffix('s')?.split_once('m')?;
+            Some(minutes.parse::<f64>().ok()? * 60.0 + seconds.parse::<f64>().ok()?)
+        })
+        .sum::<Option<f64>>()
+        .map(|seconds| (seconds * 1000.0).round() as u64)
+}
@@ -0,0 +1,434 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::admission::AdmissionConfig;
+use crate::docker::{runtime_image, CapturedOutput, OutputLimits};
+use crate::error::ServiceError;
+use crate::models::{CreateSessionRequest, ExecutionResult, SessionExecRequest, SessionInfo};
+use crate::worker::{DEFAULT_TIMEOUT_SECONDS, MAX_TIMEOUT_SECONDS};
+
+/// How often sessions past their TTL are terminated
+pub const REAP_INTERVAL: Duration = Duration::from_secs(30);
+
+// Driver loops run inside the session container. Each reads one JSON request
+// per line, evaluates it against persistent interpreter state and writes one
+// JSON result line back, with output cut to the request's `limit` bytes and
+// the full sizes reported alongside. The protocol runs over the container's
+// stdin/stdout, which the drivers keep from user code: Python moves it to
+// duplicated descriptors and points 0 and 1 at /dev/null, while JavaScript
+// code sees a `process` whose streams are captured or empty.
+const PYTHON_DRIVER: &str = r#"
+import os, sys, io, json, contextlib, traceback
+control_in, control_out = os.fdopen(os.dup(0), "r"), os.fdopen(os.dup(1), "w")
+null = os.open(os.devnull, os.O_RDWR)
+os.dup2(null, 0)
+os.dup2(null, 1)
+sys.stdin = open(os.devnull)
+scope = {"__name__": "__main__"}
+def cap(text, limit):
+    data = text.encode()
+    return data[:limit].decode(errors="ignore"), len(data)
+for line in control_in:
+    request = json.loads(line)
+    out, err, code = io.StringIO(), io.StringIO(), 0
+    with contextlib.redirect_stdout(out), contextlib.redirect_stderr(err):
+        try:
+            exec(compile(request["code"], "<session>", "exec"), scope)
+        except SystemExit as e:
+            if e.code is None or isinstance(e.code, int):
+                code = e.code or 0
+            else:
+                print(e.code, file=sys.stderr)
+                code = 1
+        except BaseException:
+            traceback.print_exc()
+            code = 1
+    stdout, stdout_bytes = cap(out.getvalue(), request["limit"])
+    stderr, stderr_bytes = cap(err.getvalue(), request["limit"])
+    control_out.write(json.dumps({"exit_code": code, "stdout": stdout, "stderr": stderr,
+                                  "stdout_bytes": stdout_bytes, "stderr_bytes": stderr_bytes}) + "\n")
+    control_out.flush()
+"#;
+
+const JAVASCRIPT_DRIVER: &str = r#"
+const vm = require('vm'), util = require('util'), { Readable } = require('stream');
+const fmt = (args) => args.map((a) => typeof a === 'string' ? a : util.inspect(a)).join(' ') + '\n';
+const cap = (text, limit) => { const data = Buffer.from(text); return [data.subarray(0, limit).toString(), data.length]; };
+class Exit { constructor(code) { this.code = code; } }
+let stdout = '', stderr = '';
+const sink = (append) => ({ write: (chunk) => { append(String(chunk)); return true; } });
+const userProcess = Object.create(process, {
+  stdin: { value: Readable.from([]) },
+  stdout: { value: sink((s) => { stdout += s; }) },
+  stderr: { value: sink((s) => { stderr += s; }) },
+  exit: { value: (code = 0) => { throw new Exit(code); } },
+});
+const context = vm.createContext({ require, process: userProcess, Buffer, setTimeout, clearTimeout });
+context.console = {
+  log: (...a) => { stdout += fmt(a); }, info: (...a) => { stdout += fmt(a); },
+  warn: (...a) => { stderr += fmt(a); }, error: (...a) => { stderr += fmt(a); },
+};
+require('readline').createInterface({ input: process.stdin }).on('line', (line) => {
+  const request = JSON.parse(line);
+  let exit_code = 0;
+  stdout = ''; stderr = '';
+  try { vm.runInContext(request.code, context, { filename: '<session>' }); }
+  catch (e) {
+    if (e instanceof Exit) { exit_code = e.code; }
+    else { stderr += ((e && e.stack) || String(e)) + '\n'; exit_code = 1; }
+  }
+  const [out, stdout_bytes] = cap(stdout, request.limit), [err, stderr_bytes] = cap(stderr, request.limit);
+  process.stdout.write(JSON.stringify({ exit_code, stdout: out, stderr: err, stdout_bytes, stderr_bytes }) + '\n');
+});
+"#;
+
+#[derive(serde::Deserialize)]
+struct DriverResponse {
+    exit_code: i32,
+    stdout: String,
+    stderr: String,
+    stdout_bytes: u64,
+    stderr_bytes: u64,
+}
+
+/// Worst-case growth of text once escaped into a JSON string (`\u0000`)
+const JSON_ESCAPE_FACTOR: usize = 6;
+
+/// Read one line of at most `max` bytes. A longer line is drained without
+/// being buffered and reported as an error, since the driver broke protocol.
+async fn read_line_capped<R: AsyncBufRead + Unpin>(reader: &mut R, max: usize) -> Result<String> {
+    let mut line = Vec::new();
+    let mut overflowed = false;
+    loop {
+        let available = reader.fill_buf().await?;
+        anyhow::ensure!(!available.is_empty(), "Session container exited");
+        let (chunk, done) = match available.iter().position(|b| *b == b'\n') {
+            Some(end) => (&available[..=end], true),
+            None => (available, false),
+        };
+        let used = chunk.len();
+        if line.len() + used > max {
+            overflowed = true;
+        } else {
+            line.extend_from_slice(chunk);
+        }
+        reader.consume(used);
+        if done {
+            break;
+        }
+    }
+    anyhow::ensure!(!overflowed, "Session response exceeded {} bytes", max);
+    Ok(String::from_utf8(line)?)
+}
+
+/// A long-lived language container that keeps interpreter state between calls
+pub struct Session {
+    pub id: Uuid,
+    pub language: String,
+    pub container_name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub ttl: Duration,
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Session {
+    fn info(&self) -> SessionInfo {
+        SessionInfo {
+            id: self.id,
+            language: self.language.clone(),
+            created_at: self.created_at,
+            last_used_at: self.last_used_at,
+            expires_at: self.expires_at(),
+        }
+    }
+
+    fn expires_at(&self) -> DateTime<Utc> {
+        self.last_used_at + chrono::Duration::from_std(self.ttl).unwrap_or_default()
+    }
+
+    async fn terminate(&mut self) {
+        let _ = self.process.start_kill();
+        let _ = Command::new("docker")
+            .args(["rm", "-f", &self.container_name])
+            .output()
+            .await;
+    }
+}
+
+/// An open session with the key that opened it, which is kept outside the
+/// session's lock so it can be counted while the session is busy
+struct Slot {
+    api_key: String,
+    session: Arc<Mutex<Session>>,
+}
+
+pub struct SessionManager {
+    sessions: Mutex<HashMap<Uuid, Slot>>,
+    default_ttl: Duration,
+    max_ttl: Duration,
+    output_limits: OutputLimits,
+}
+
+impl SessionManager {
+    pub fn new(default_ttl: Duration, max_ttl: Duration, output_limits: OutputLimits) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            default_ttl,
+            max_ttl,
+            output_limits,
+        }
+    }
+
+    /// Start a session for `api_key`, within the limits in `admission`
+    pub async fn create_session(
+        &self,
+        request: CreateSessionRequest,
+        api_key: String,
+        admission: &AdmissionConfig,
+    ) -> Result<SessionInfo, ServiceError> {
+        let driver = match request.language.as_str() {
+            "python" => vec!["python", "-u", "-c", PYTHON_DRIVER],
+            "javascript" => vec!["node", "-e", JAVASCRIPT_DRIVER],
+            other => {
+                return Err(ServiceError::BadRequest(format!(
+                    "Sessions are not supported for language '{}'",
+                    other
+                )))
+            }
+        };
+
+        let ttl = request
+            .ttl_seconds
+            .map(Duration::from_secs)
+            .unwrap_or(self.default_ttl)
+            .min(self.max_ttl);
+
+        // Held until the session is in the map, so concurrent requests can't
+        // both take the last slot
+        let mut sessions = self.sessions.lock().await;
+        let for_key = sessions.values().filter(|slot| slot.api_key == api_key).count();
+        admission.check_sessions(sessions.len(), for_key, &api_key)?;
+
+        let id = Uuid::new_v4();
+        let container_name = format!("syla-session-{}", id);
+
+        let mut process = Command::new("docker")
+            .args(["run", "-i", "--rm", "--name", &container_name])
+            .args(["--network", "none", "--memory", "536870912", "--cpus", "1"])
+            .arg(runtime_image(&request.language))
+            .args(&driver)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start session container")?;
+
+        let stdin = process.stdin.take().context("Session container has no stdin")?;
+        let stdout = process.stdout.take().context("Session container has no stdout")?;
+
+        let now = Utc::now();
+        let session = Session {
+            id,
+            language: request.language,
+            container_name,
+            created_at: now,
+            last_used_at: now,
+            ttl,
+            process,
+            stdin,
+            stdout: BufReader::new(stdout),
+        };
+        let info = session.info();
+
+        sessions.insert(
+            id,
+            Slot {
+                api_key,
+                session: Arc::new(Mutex::new(session)),
+            },
+        );
+        tracing::info!(session_id = %id, language = %info.language, "Session created");
+
+        Ok(info)
+    }
+
+    pub async fn active_count(&self) -> usize {
+        self.sessions.lock().await.len()
+    }
+
+    pub async fn get_session(&self, id: Uuid) -> Result<SessionInfo, ServiceError> {
+        let session = self.lookup(id).await?;
+        let session = session.lock().await;
+        Ok(session.info())
+    }
+
+    pub async fn execute(&self, id: Uuid, request: SessionExecRequest) -> Result<ExecutionResult, ServiceError> {
+        let session = self.lookup(id).await?;
+        let mut session = session.lock().await;
+        session.last_used_at = Utc::now();
+
+        let limit = self.output_limits.resolve(request.max_output_bytes);
+        let mut line = serde_json::to_string(&serde_json::json!({ "code": request.code, "limit": limit }))?;
+        line.push('\n');
+        // Both streams at their limit, fully escaped, plus the envelope
+        let max_response = 2 * limit * JSON_ESCAPE_FACTOR + 1024;
+
+        let start = std::time::Instant::now();
+        let timeout = Duration::from_secs(
+            request
+                .timeout_seconds
+                .unwrap_or(DEFAULT_TIMEOUT_SECONDS)
+                .min(MAX_TIMEOUT_SECONDS),
+        );
+
+        let exchange = async {
+            session.stdin.write_all(line.as_bytes()).await?;
+            session.stdin.flush().await?;
+            read_line_capped(&mut session.stdout, max_response).await
+        };
+
+        let response = match tokio::time::timeout(timeout, exchange).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => {
+                // The interpreter is gone, so the session can't be resumed
+                session.terminate().await;
+                drop(session);
+                self.sessions.lock().await.remove(&id);
+                return Err(ServiceError::Internal(e));
+            }
+            Err(_) => {
+                // A timed-out interpreter may still be busy; discard it
+                session.terminate().await;
+                drop(session);
+                self.sessions.lock().await.remove(&id);
+                let stderr = "Execution timed out; session terminated".to_string();
+                return Ok(ExecutionResult {
+                    exit_code: -1,
+                    stdout: String::new(),
+                    stderr_bytes: stderr.len() as u64,
+                    stderr,
+                    duration_ms: start.elapsed().as_millis() as u64,
+                    stdout_bytes: 0,
+                    stdout_truncated: false,
+                    stderr_truncated: false,
+                    resource_usage: None,
+                    stdout_object: None,
+                    stderr_object: None,
+                    runtime_version: None,
+                });
+            }
+        };
+
+        let response: DriverResponse = serde_json::from_str(response.trim())?;
+        session.last_used_at = Utc::now();
+
+        let stdout = CapturedOutput::from_string(response.stdout, limit).with_total(response.stdout_bytes);
+        let stderr = CapturedOutput::from_string(response.stderr, limit).with_total(response.stderr_bytes);
+
+        Ok(ExecutionResult {
+            exit_code: response.exit_code,
+            stdout: stdout.text(),
+            stderr: stderr.text(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            stdout_bytes: stdout.total_bytes,
+            stderr_bytes: stderr.total_bytes,
+            stdout_truncated: stdout.truncated(),
+            stderr_truncated: stderr.truncated(),
+            resource_usage: None,
+            stdout_object: None,
+            stderr_object: None,
+            runtime_version: None,
+        })
+    }
+
+    pub async fn delete_session(&self, id: Uuid) -> Result<(), ServiceError> {
+        let session = self
+            .sessions
+            .lock()
+            .await
+            .remove(&id)
+            .ok_or(ServiceError::NotFound)?
+            .session;
+        session.lock().await.terminate().await;
+        tracing::info!(session_id = %id, "Session deleted");
+        Ok(())
+    }
+
+    /// Terminate sessions that have been idle longer than their TTL
+    pub async fn reap_expired(&self) -> usize {
+        let now = Utc::now();
+        let candidates: Vec<(Uuid, Arc<Mutex<Session>>)> = {
+            let sessions = self.sessions.lock().await;
+            sessions.iter().map(|(id, slot)| (*id, slot.session.clone())).collect()
+        };
+
+        let mut reaped = 0;
+        for (id, session) in candidates {
+            // Sessions busy executing are in use and therefore not expired
+            let Ok(mut session) = session.try_lock() else {
+                continue;
+            };
+            if session.expires_at() <= now {
+                session.terminate().await;
+                self.sessions.lock().await.remove(&id);
+                tracing::info!(session_id = %id, "Session expired");
+                reaped += 1;
+            }
+        }
+        reaped
+    }
+
+    async fn lookup(&self, id: Uuid) -> Result<Arc<Mutex<Session>>, ServiceError> {
+        self.sessions
+            .lock()
+            .await
+            .get(&id)
+            .map(|slot| slot.session.clone())
+            .ok_or(ServiceError::NotFound)
+    }
+}
+
+pub async fn run_reaper(sessions: Arc<SessionManager>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let reaped = sessions.reap_expired().await;
+        if reaped > 0 {
+            tracing::debug!("Reaped {} expired sessions", reaped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lines_within_the_cap_are_read_one_at_a_time() {
+        let mut reader = BufReader::new(&b"{\"a\":1}\n{\"b\":2}\n"[..]);
+        assert_eq!(read_line_capped(&mut reader, 16).await.unwrap(), "{\"a\":1}\n");
+        assert_eq!(read_line_capped(&mut reader, 16).await.unwrap(), "{\"b\":2}\n");
+        assert!(read_line_capped(&mut reader, 16).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn oversized_lines_are_drained_and_rejected() {
+        let input = format!("{}\nnext\n", "x".repeat(100));
+        // A tiny buffer makes the line arrive over many reads
+        let mut reader = BufReader::with_capacity(8, input.as_bytes());
+        assert!(read_line_capped(&mut reader, 50).await.is_err());
+        assert_eq!(read_line_capped(&mut reader, 50).await.unwrap(), "next\n");
+    }
+}
@@ -1,5 +1,7 @@
 use crate::error::ServiceError;
-use crate::models::{CreateExecutionRequest, ExecutionJob};
+use crate::index::{self, ExecutionQuery};
+use crate::models::{CreateExecutionRequest, ExecutionJob, ExecutionMode, ExecutorBackend};
+use crate::worker::{DEFAULT_TIMEOUT_SECONDS, MAX_TIMEOUT_SECONDS};
 use anyhow::Result;
 use redis::aio::ConnectionManager;
 use std::sync::Arc;
@@ -8,37 +10,159 @@ use uuid::Uuid;
 
 pub struct ServiceState {
     pub redis: Arc<Mutex<ConnectionManager>>,
+    pub queue: Arc<crate::queue::RedisQueue>,
     pub docker_executor: Arc<crate::docker::DockerExecutor>,
+    pub wasm_executor: Arc<crate::wasm::WasmExecutor>,
+    pub sandbox_executor: Arc<crate::sandbox::SandboxExecutor>,
+    pub sessions: Arc<crate::session::SessionManager>,
+    pub worker_heartbeat: Arc<crate::health::Heartbeat>,
+    pub admission: crate::admission::AdmissionConfig,
+    pub retention: crate::retention::RetentionManager,
+    /// Where oversized output goes; kept inline when unset
+    pub log_offload: Option<crate::log_store::LogOffload>,
+    pub warmup: Arc<crate::warmup::ImageWarmer>,
+    /// Who may pick a custom image, and from where
+    pub images: crate::images::ImagePolicy,
+    /// Names usage is attributed to
+    pub api_keys: crate::usage::ApiKeys,
+    /// How workers retry failed runs, unless a request says otherwise
+    pub retry: crate::retry::RetryPolicy,
+    /// Caps on captured output; debug replays get the maximum
+    pub output_limits: crate::docker::OutputLimits,
 }
 
 impl ServiceState {
+    #[tracing::instrument(skip_all, fields(language = %request.language, job_id))]
     pub async fn create_execution(
         &self,
-        request: CreateExecutionRequest,
+        mut request: CreateExecutionRequest,
+        caller_token: Option<&str>,
     ) -> Result<ExecutionJob, ServiceError> {
-        let job = ExecutionJob::new(request);
+        if request.mode != ExecutionMode::Run
+            && crate::checks::check_command(&request.language, request.mode).is_none()
+        {
+            return Err(ServiceError::BadRequest(format!(
+                "{:?} mode is not supported for language '{}'",
+                request.mode, request.language
+            )));
+        }
+
+        if request.coverage.is_some() {
+            if request.mode != ExecutionMode::Run || request.tests.is_some() {
+                return Err(ServiceError::BadRequest(
+                    "Coverage can only be collected for plain runs".to_string(),
+                ));
+            }
+            if crate::coverage::coverage_command(&request.language).is_none() {
+                return Err(ServiceError::BadRequest(format!(
+                    "Coverage is not supported for language '{}'",
+                    request.language
+                )));
+            }
+        }
+
+        index::validate_labels(&request.labels)?;
+        self.authorize_image(&request, caller_token)?;
+        check_runtime_version(&request)?;
+        request.timeout_seconds = request.timeout_seconds.map(|t| t.min(MAX_TIMEOUT_SECONDS));
+
+        let mut job = ExecutionJob::new(request);
+        job.api_key = Some(self.api_keys.identify(caller_token));
+        self.enqueue(job).await
+    }
+
+    /// Re-runs a stored execution with the same code, inputs and limits;
+    /// `debug` relaxes the limits and records how the run happened
+    #[tracing::instrument(skip(self, debug, caller_token), fields(job_id))]
+    pub async fn replay_execution(
+        &self,
+        id: Uuid,
+        debug: bool,
+        caller_token: Option<&str>,
+    ) -> Result<ExecutionJob, ServiceError> {
+        let original = self.get_execution(id).await?;
+        let mut request = original.request;
+        // Replaying someone else's custom-image run still takes a trusted caller
+        self.authorize_image(&request, caller_token)?;
+        if debug {
+            let timeout = request.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS);
+            request.timeout_seconds = Some((timeout * DEBUG_TIMEOUT_FACTOR).min(MAX_TIMEOUT_SECONDS).max(timeout));
+            request.max_output_bytes = Some(self.output_limits.max_bytes as u64);
+        }
+        request.debug = debug;
+
+        let mut job = ExecutionJob::new(request);
+        job.replay_of = Some(id);
+        job.api_key = Some(self.api_keys.identify(caller_token));
+        self.enqueue(job).await
+    }
+
+    fn authorize_image(&self, request: &CreateExecutionRequest, caller_token: Option<&str>) -> Result<(), ServiceError> {
+        let Some(image) = &request.image else {
+            return Ok(());
+        };
+        if matches!(request.backend, Some(ExecutorBackend::Wasm | ExecutorBackend::Sandbox)) {
+            return Err(ServiceError::BadRequest(
+                "Custom images can only be used with the docker backend".to_string(),
+            ));
+        }
+        self.images.authorize(image, caller_token)
+    }
+
+    /// Stores a new job and queues it, if there is capacity
+    async fn enqueue(&self, mut job: ExecutionJob) -> Result<ExecutionJob, ServiceError> {
+        self.admission.check(&self.queue).await?;
+
+        job.trace_context = crate::telemetry::current_context();
+        tracing::Span::current().record("job_id", tracing::field::display(job.id));
         
         // Store job in Redis
         let mut redis = self.redis.lock().await;
         let job_key = format!("job:{}", job.id);
         let job_json = serde_json::to_string(&job)?;
         
-        redis::cmd("SET")
-            .arg(&job_key)
-            .arg(&job_json)
-            .query_async::<_, ()>(&mut *redis)
-            .await?;
+        let mut pipe = redis::pipe();
+        pipe.cmd("SET").arg(&job_key).arg(&job_json).ignore();
+        index::add(&mut pipe, &job);
+        pipe.query_async::<_, ()>(&mut *redis).await?;
         
+        drop(redis);
+
         // Add to queue
-        redis::cmd("RPUSH")
-            .arg("execution_queue")
-            .arg(job.id.to_string())
-            .query_async::<_, ()>(&mut *redis)
-            .await?;
+        self.queue.push_job(job.id).await?;
         
         Ok(job)
     }
     
+    pub async fn update_execution(&self, job: &ExecutionJob) -> Result<(), ServiceError> {
+        let mut redis = self.redis.lock().await;
+        let job_key = format!("job:{}", job.id);
+        let job_json = serde_json::to_string(job)?;
+
+        redis::cmd("SET")
+            .arg(&job_key)
+            .arg(&job_json)
+            .query_async::<_, ()>(&mut *redis)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Counts a finished job towards its key's and tenant's usage
+    pub async fn record_usage(&self, job: &ExecutionJob) -> Result<(), ServiceError> {
+        let mut redis = self.redis.lock().await;
+        crate::usage::record(&mut redis, job).await?;
+        Ok(())
+    }
+
+    /// Add download links for offloaded output, for returning to clients
+    pub async fn with_log_urls(&self, mut job: ExecutionJob) -> ExecutionJob {
+        if let Some(offload) = &self.log_offload {
+            offload.sign_urls(&mut job).await;
+        }
+        job
+    }
+
     pub async fn get_execution(&self, id: Uuid) -> Result<ExecutionJob, ServiceError> {
         let mut redis = self.redis.lock().await;
         let job_key = format!("job:{}", id);
@@ -53,4 +177,111 @@ impl ServiceState {
             None => Err(ServiceError::NotFound),
         }
     }
-}
\ No newline at end of file
+
+    /// Newest executions first, narrowed by labels and status
+    pub async fn list_executions(
+        &self,
+        query: &ExecutionQuery,
+    ) -> Result<Vec<ExecutionJob>, ServiceError> {
+        let mut redis = self.redis.lock().await;
+        let mut jobs = Vec::new();
+
+        if query.labels.is_empty() {
+            // Walk the creation-time index in pages until enough jobs match
+            let mut offset = 0;
+            while jobs.len() < query.limit {
+                let ids: Vec<String> = redis::cmd("ZREVRANGE")
+                    .arg(index::INDEX_KEY)
+                    .arg(offset)
+                    .arg(offset + LIST_PAGE - 1)
+                    .query_async(&mut *redis)
+                    .await?;
+                if ids.is_empty() {
+                    break;
+                }
+                offset += ids.len();
+
+                for job in fetch_jobs(&mut redis, &ids).await? {
+                    if query.matches(&job) && jobs.len() < query.limit {
+                        jobs.push(job);
+                    }
+                }
+            }
+        } else {
+            let ids: Vec<String> = redis::cmd("SINTER")
+                .arg(query.label_keys())
+                .query_async(&mut *redis)
+                .await?;
+            for chunk in ids.chunks(LIST_PAGE) {
+                jobs.extend(
+                    fetch_jobs(&mut redis, chunk)
+                        .await?
+                        .into_iter()
+                        .filter(|job| query.matches(job)),
+                );
+            }
+            jobs.sort_by_key(|job| std::cmp::Reverse(job.created_at));
+            jobs.truncate(query.limit);
+        }
+
+        Ok(jobs)
+    }
+}
+
+/// How much longer than the original's time limit a debug replay may run
+const DEBUG_TIMEOUT_FACTOR: u64 = 4;
+
+/// Job IDs fetched per MGET while listing
+const LIST_PAGE: usize = 200;
+
+/// Load jobs by ID, skipping any whose records have since been deleted
+async fn fetch_jobs(
+    redis: &mut ConnectionManager,
+    ids: &[String],
+) -> Result<Vec<ExecutionJob>, ServiceError> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let keys: Vec<String> = ids.iter().map(|id| format!("job:{}", id)).collect();
+    let records: Vec<Option<String>> = redis::cmd("MGET")
+        .arg(keys)
+        .query_async(redis)
+        .await?;
+    Ok(records
+        .into_iter()
+        .flatten()
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect())
+}
+
+/// Pinned versions must be ones the service has an image for, and only
+/// apply to the language's own runtime image
+fn check_runtime_version(request: &CreateExecutionRequest) -> Result<(), ServiceError> {
+    let Some(version) = &request.runtime_version else {
+        return Ok(());
+    };
+    if request.image.is_some() {
+        return Err(ServiceError::BadRequest(
+            "runtime_version can't be combined with a custom image".to_string(),
+        ));
+    }
+    if matches!(request.backend, Some(ExecutorBackend::Wasm | ExecutorBackend::Sandbox)) {
+        return Err(ServiceError::BadRequest(
+            "runtime_version can only be used with the docker backend".to_string(),
+        ));
+    }
+    if crate::docker::versioned_image(&request.language, version).is_none() {
+        let versions = crate::docker::runtime_versions(&request.language);
+        return Err(ServiceError::BadRequest(if versions.is_empty() {
+            format!("Language '{}' has no pinnable runtime versions", request.language)
+        } else {
+            format!(
+                "Unsupported {} version '{}'; supported: {}",
+                request.language,
+                version,
+                versions.join(", ")
+            )
+        }));
+    }
+    Ok(())
+}
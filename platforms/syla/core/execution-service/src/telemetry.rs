@@ -0,0 +1,117 @@
+use anyhow::Result;
+use axum::http::{HeaderMap, Request};
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{global, Context, KeyValue};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+use std::collections::HashMap;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Install the tracing subscriber. Spans are exported over OTLP when
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is set; otherwise only logs are written.
+/// The returned provider must be shut down on exit to flush pending spans.
+pub fn init(service_name: &'static str, default_filter: &str) -> Result<Option<TracerProvider>> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let provider = match std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        Ok(_) => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .build()?;
+            let provider = TracerProvider::builder()
+                .with_batch_exporter(exporter, runtime::Tokio)
+                .with_resource(Resource::new_with_defaults([KeyValue::new(
+                    "service.name",
+                    service_name,
+                )]))
+                .build();
+            global::set_tracer_provider(provider.clone());
+            Some(provider)
+        }
+        Err(_) => None,
+    };
+
+    let otel_layer = provider
+        .as_ref()
+        .map(|p| tracing_opentelemetry::layer().with_tracer(p.tracer(service_name)));
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| default_filter.into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel_layer)
+        .init();
+
+    Ok(provider)
+}
+
+pub fn shutdown(provider: Option<TracerProvider>) {
+    if let Some(provider) = provider {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("Failed to flush traces: {}", e);
+        }
+    }
+}
+
+/// Request span for `TraceLayer`, continuing any W3C trace context the
+/// caller sent in `traceparent`/`tracestate`
+pub fn http_span<B>(request: &Request<B>) -> Span {
+    let span = tracing::info_span!(
+        "http_request",
+        method = %request.method(),
+        uri = %request.uri(),
+    );
+    let parent = global::get_text_map_propagator(|p| p.extract(&HeaderExtractor(request.headers())));
+    span.set_parent(parent);
+    span
+}
+
+/// Trace context of the current span, serialized for storing with a job
+pub fn current_context() -> HashMap<String, String> {
+    let mut carrier = HashMap::new();
+    let cx = Span::current().context();
+    global::get_text_map_propagator(|p| p.inject_context(&cx, &mut MapInjector(&mut carrier)));
+    carrier
+}
+
+/// Rebuild a trace context stored by `current_context`
+pub fn context_from(carrier: &HashMap<String, String>) -> Context {
+    global::get_text_map_propagator(|p| p.extract(&MapExtractor(carrier)))
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+struct MapInjector<'a>(&'a mut HashMap<String, String>);
+
+impl Injector for MapInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_string(), value);
+    }
+}
+
+struct MapExtractor<'a>(&'a HashMap<String, String>);
+
+impl Extractor for MapExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(String::as_str).collect()
+    }
+}
@@ -0,0 +1,313 @@
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use redis::aio::ConnectionManager;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::error::ServiceError;
+use crate::models::{ExecutionJob, JobStatus};
+use crate::state::ServiceState;
+
+/// Label that attributes an execution to a tenant
+pub const TENANT_LABEL: &str = "tenant";
+
+/// Recorded for executions submitted without a bearer token
+pub const ANONYMOUS: &str = "anonymous";
+/// Recorded for bearer tokens missing from `API_KEYS`
+pub const UNREGISTERED: &str = "unregistered";
+/// Recorded for executions without a tenant label
+pub const NO_TENANT: &str = "none";
+
+const DEFAULT_WINDOW_DAYS: u64 = 30;
+const MAX_WINDOW_DAYS: u64 = 366;
+
+/// Per-day counters: one hash per UTC day, fields `<metric>:<key>:<tenant>`
+fn day_key(day: NaiveDate) -> String {
+    format!("syla:usage:{}", day.format("%Y-%m-%d"))
+}
+
+/// Names for caller API keys, so usage is attributed without storing the
+/// keys themselves
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys {
+    names: HashMap<String, String>,
+}
+
+impl ApiKeys {
+    /// Reads `API_KEYS`, a comma-separated list of `name:token` pairs
+    pub fn from_env() -> Self {
+        let names = std::env::var("API_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|pair| {
+                let (name, token) = pair.trim().split_once(':')?;
+                let (name, token) = (name.trim(), token.trim());
+                (!name.is_empty() && !token.is_empty()).then(|| (token.to_string(), name.to_string()))
+            })
+            .collect();
+        Self { names }
+    }
+
+    /// The name usage is recorded under for a caller's token
+    pub fn identify(&self, token: Option<&str>) -> String {
+        match token {
+            None => ANONYMOUS.to_string(),
+            Some(token) => self.names.get(token).cloned().unwrap_or_else(|| UNREGISTERED.to_string()),
+        }
+    }
+}
+
+/// Metrics tracked per key and tenant; order is the CSV column order
+const METRICS: &[&str] = &[
+    "executions",
+    "failed",
+    "timeouts",
+    "wall_time_ms",
+    "cpu_time_ms",
+    "input_bytes",
+    "output_bytes",
+];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Usage {
+    pub executions: u64,
+    pub failed: u64,
+    pub timeouts: u64,
+    /// From a worker starting the job to it finishing
+    pub wall_time_ms: u64,
+    /// Only counted for runs whose CPU time could be measured
+    pub cpu_time_ms: u64,
+    /// Submitted code and test input
+    pub input_bytes: u64,
+    /// Stdout and stderr before truncation
+    pub output_bytes: u64,
+}
+
+impl Usage {
+    fn of(job: &ExecutionJob) -> Self {
+        let wall_time_ms = match (job.started_at, job.completed_at) {
+            (Some(started), Some(completed)) => (completed - started).num_milliseconds().max(0) as u64,
+            _ => 0,
+        };
+        let mut cpu_time_ms = 0;
+        let mut output_bytes = 0;
+        if let Some(result) = &job.result {
+            cpu_time_ms = result.resource_usage.as_ref().and_then(|r| r.cpu_time_ms).unwrap_or(0);
+            output_bytes = result.stdout_bytes + result.stderr_bytes;
+        }
+        if let Some(report) = &job.test_report {
+            output_bytes += report
+                .verdicts
+                .iter()
+                .map(|v| (v.actual_output.len() + v.stderr.len()) as u64)
+                .sum::<u64>();
+        }
+        let test_input: usize = job
+            .request
+            .tests
+            .as_ref()
+            .map(|spec| spec.cases.iter().map(|case| case.stdin.len()).sum())
+            .unwrap_or(0);
+
+        Self {
+            executions: 1,
+            failed: matches!(job.status, JobStatus::Failed) as u64,
+            timeouts: matches!(job.status, JobStatus::Timeout) as u64,
+            wall_time_ms,
+            cpu_time_ms,
+            input_bytes: (job.request.code.len() + test_input) as u64,
+            output_bytes,
+        }
+    }
+
+    fn values(&self) -> [u64; 7] {
+        [
+            self.executions,
+            self.failed,
+            self.timeouts,
+            self.wall_time_ms,
+            self.cpu_time_ms,
+            self.input_bytes,
+            self.output_bytes,
+        ]
+    }
+
+    fn add(&mut self, metric: &str, value: u64) {
+        let counter = match metric {
+            "executions" => &mut self.executions,
+            "failed" => &mut self.failed,
+            "timeouts" => &mut self.timeouts,
+            "wall_time_ms" => &mut self.wall_time_ms,
+            "cpu_time_ms" => &mut self.cpu_time_ms,
+            "input_bytes" => &mut self.input_bytes,
+            "output_bytes" => &mut self.output_bytes,
+            _ => return,
+        };
+        *counter += value;
+    }
+}
+
+/// Adds a finished job to its day's counters
+pub async fn record(redis: &mut ConnectionManager, job: &ExecutionJob) -> anyhow::Result<()> {
+    let day = job.completed_at.unwrap_or_else(Utc::now).date_naive();
+    let key = day_key(day);
+    let api_key = job.api_key.as_deref().unwrap_or(ANONYMOUS);
+    let tenant = job.request.labels.get(TENANT_LABEL).map(String::as_str).unwrap_or(NO_TENANT);
+    let retention_days = std::env::var("USAGE_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(400);
+
+    let mut pipe = redis::pipe();
+    for (metric, value) in METRICS.iter().zip(Usage::of(job).values()) {
+        if value > 0 {
+            pipe.hincr(&key, format!("{}:{}:{}", metric, api_key, tenant), value).ignore();
+        }
+    }
+    pipe.cmd("EXPIRE").arg(&key).arg(retention_days * 24 * 3600).ignore();
+    pipe.query_async::<_, ()>(redis).await?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountUsage {
+    pub api_key: String,
+    pub tenant: String,
+    #[serde(flatten)]
+    pub usage: Usage,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageReport {
+    pub generated_at: DateTime<Utc>,
+    /// First and last UTC day covered, inclusive
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub accounts: Vec<AccountUsage>,
+    pub total: Usage,
+}
+
+impl UsageReport {
+    fn to_csv(&self) -> String {
+        let mut csv = format!("api_key,tenant,{}\n", METRICS.join(","));
+        for account in &self.accounts {
+            let values: Vec<String> = account.usage.values().iter().map(u64::to_string).collect();
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                csv_field(&account.api_key),
+                csv_field(&account.tenant),
+                values.join(",")
+            ));
+        }
+        csv
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Sums the daily counters between two UTC days, inclusive
+pub async fn report(redis: &mut ConnectionManager, from: NaiveDate, to: NaiveDate) -> anyhow::Result<UsageReport> {
+    let mut pipe = redis::pipe();
+    let mut day = from;
+    while day <= to {
+        pipe.cmd("HGETALL").arg(day_key(day));
+        day = day + Days::new(1);
+    }
+    let days: Vec<HashMap<String, u64>> = pipe.query_async(redis).await?;
+
+    let mut accounts: BTreeMap<(String, String), Usage> = BTreeMap::new();
+    for (field, value) in days.into_iter().flatten() {
+        // Tenants come last since label values may contain ':'
+        let mut parts = field.splitn(3, ':');
+        let (Some(metric), Some(api_key), Some(tenant)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        accounts
+            .entry((api_key.to_string(), tenant.to_string()))
+            .or_default()
+            .add(metric, value);
+    }
+
+    let mut total = Usage::default();
+    for usage in accounts.values() {
+        for (metric, value) in METRICS.iter().zip(usage.values()) {
+            total.add(metric, value);
+        }
+    }
+    Ok(UsageReport {
+        generated_at: Utc::now(),
+        from,
+        to,
+        accounts: accounts
+            .into_iter()
+            .map(|((api_key, tenant), usage)| AccountUsage { api_key, tenant, usage })
+            .collect(),
+        total,
+    })
+}
+
+/// `GET /admin/usage?from=2024-05-01&to=2024-05-31`, as JSON or, with
+/// `format=csv`, as a CSV download. Defaults to the last 30 days.
+pub async fn usage_report(
+    State(state): State<Arc<ServiceState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, ServiceError> {
+    let today = Utc::now().date_naive();
+    let to = params.get("to").map(String::as_str).map(parse_day).transpose()?.unwrap_or(today);
+    let from = match params.get("from") {
+        Some(v) => parse_day(v)?,
+        None => to - Days::new(DEFAULT_WINDOW_DAYS - 1),
+    };
+    if from > to {
+        return Err(ServiceError::BadRequest("'from' must not be after 'to'".to_string()));
+    }
+    if (to - from).num_days() as u64 >= MAX_WINDOW_DAYS {
+        return Err(ServiceError::BadRequest(format!(
+            "Usage can be reported for at most {} days at a time",
+            MAX_WINDOW_DAYS
+        )));
+    }
+
+    let report = {
+        let mut redis = state.redis.lock().await;
+        report(&mut redis, from, to).await?
+    };
+
+    match params.get("format").map(String::as_str) {
+        None | Some("json") => Ok(Json(report).into_response()),
+        Some("csv") => {
+            let disposition = format!("attachment; filename=\"usage-{}-{}.csv\"", from, to);
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                report.to_csv(),
+            )
+                .into_response())
+        }
+        Some(other) => Err(ServiceError::BadRequest(format!(
+            "Unknown format '{}'; use json or csv",
+            other
+        ))),
+    }
+}
+
+/// A UTC day, given as a date or an RFC 3339 timestamp
+fn parse_day(value: &str) -> Result<NaiveDate, ServiceError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| DateTime::parse_from_rfc3339(value).map(|t| t.with_timezone(&Utc).date_naive()))
+        .map_err(|_| ServiceError::BadRequest(format!("Invalid date '{}'; use YYYY-MM-DD", value)))
+}
@@ -0,0 +1,193 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::process::Command;
+use tracing::{error, info, warn};
+
+use crate::docker::{runtime_image, versioned_image, SUPPORTED_LANGUAGES};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase", tag = "state")]
+pub enum ImageState {
+    Pending,
+    Pulling,
+    /// Pulled; starting a throwaway container to load it from disk
+    Warming,
+    Ready { duration_ms: u64 },
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageProgress {
+    pub image: String,
+    pub languages: Vec<String>,
+    #[serde(flatten)]
+    pub state: ImageState,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WarmupReport {
+    pub running: bool,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub images: Vec<ImageProgress>,
+}
+
+/// Whether to warm images at startup, from `WARMUP_IMAGES`
+pub fn enabled_on_start() -> bool {
+    std::env::var("WARMUP_IMAGES").is_ok_and(|v| v == "true" || v == "1")
+}
+
+/// Pulls and warms runtime images so the first execution on a fresh node
+/// doesn't wait on an image pull
+#[derive(Default)]
+pub struct ImageWarmer {
+    report: Mutex<WarmupReport>,
+}
+
+impl ImageWarmer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn report(&self) -> WarmupReport {
+        self.report.lock().unwrap().clone()
+    }
+
+    /// Begin warming in the background. Returns false if a run is already
+    /// in progress.
+    pub fn start(self: &Arc<Self>) -> bool {
+        if !self.begin() {
+            return false;
+        }
+        let warmer = self.clone();
+        tokio::spawn(async move { warmer.warm_all().await });
+        true
+    }
+
+    /// Warm every image and wait for the result
+    pub async fn run(self: &Arc<Self>) {
+        if self.begin() {
+            self.warm_all().await;
+        }
+    }
+
+    fn begin(&self) -> bool {
+        let mut report = self.report.lock().unwrap();
+        if report.running {
+            return false;
+        }
+        *report = WarmupReport {
+            running: true,
+            started_at: Some(Utc::now()),
+            finished_at: None,
+            images: images()
+                .into_iter()
+                .map(|(image, languages)| ImageProgress {
+                    image,
+                    languages,
+                    state: ImageState::Pending,
+                })
+                .collect(),
+        };
+        true
+    }
+
+    async fn warm_all(self: &Arc<Self>) {
+        let images: Vec<String> = self
+            .report()
+            .images
+            .into_iter()
+            .map(|progress| progress.image)
+            .collect();
+        info!("Warming {} runtime images", images.len());
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for image in images {
+            tasks.spawn(self.clone().warm_image(image));
+        }
+        while tasks.join_next().await.is_some() {}
+
+        let mut report = self.report.lock().unwrap();
+        report.running = false;
+        report.finished_at = Some(Utc::now());
+    }
+
+    async fn warm_image(self: Arc<Self>, image: String) {
+        let start = Instant::now();
+        self.set_state(&image, ImageState::Pulling);
+        let outcome = match docker(&["pull", "--quiet", &image]).await {
+            Ok(()) => {
+                self.set_state(&image, ImageState::Warming);
+                docker(&["run", "--rm", "--network", "none", "--entrypoint", "true", &image]).await
+            }
+            Err(e) => Err(e),
+        };
+
+        let state = match outcome {
+            Ok(()) => {
+                info!("Runtime image {} ready in {:?}", image, start.elapsed());
+                ImageState::Ready {
+                    duration_ms: start.elapsed().as_millis() as u64,
+                }
+            }
+            Err(e) => {
+                error!("Failed to warm runtime image {}: {}", image, e);
+                ImageState::Failed { error: e }
+            }
+        };
+        self.set_state(&image, state);
+    }
+
+    fn set_state(&self, image: &str, state: ImageState) {
+        let mut report = self.report.lock().unwrap();
+        if let Some(progress) = report.images.iter_mut().find(|p| p.image == image) {
+            progress.state = state;
+        }
+    }
+}
+
+/// Images to warm, from `WARMUP_LANGUAGES` or every supported language.
+/// Entries like `python@3.12` warm a pinned runtime version.
+fn images() -> Vec<(String, Vec<String>)> {
+    let languages: Vec<String> = match std::env::var("WARMUP_LANGUAGES") {
+        Ok(list) => list
+            .split(',')
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect(),
+        Err(_) => SUPPORTED_LANGUAGES.iter().map(|l| l.to_string()).collect(),
+    };
+
+    let mut images: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for language in languages {
+        let image = match language.split_once('@') {
+            Some((name, version)) => match versioned_image(name, version) {
+                Some(image) => image,
+                None => {
+                    warn!("Not warming {}: no image for that runtime version", language);
+                    continue;
+                }
+            },
+            None => runtime_image(&language),
+        };
+        images.entry(image.to_string()).or_default().push(language);
+    }
+    images.into_iter().collect()
+}
+
+async fn docker(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("docker")
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
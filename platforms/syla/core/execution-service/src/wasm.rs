@@ -0,0 +1,313 @@
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use wasmtime::{
+    Config, Engine, Linker, Module, ResourceLimiter, Store, StoreLimits, StoreLimitsBuilder, Trap,
+};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{
+    DirPerms, FilePerms, HostOutputStream, I32Exit, StdoutStream, StreamError, Subscribe,
+    WasiCtxBuilder,
+};
+
+use crate::docker::{scratch_dir, CapturedOutput, ExecutionResult, OutputLimits};
+
+/// How often the engine epoch advances; execution timeouts are measured in ticks
+const EPOCH_TICK: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub struct WasmConfig {
+    /// Directory holding WASI builds of language runtimes, e.g. `python.wasm`
+    pub runtime_dir: PathBuf,
+    /// Languages that run under wasmtime unless a request picks a backend
+    pub default_languages: Vec<String>,
+    pub fuel: u64,
+    pub memory_limit: usize,
+}
+
+impl WasmConfig {
+    pub fn from_env() -> Self {
+        Self {
+            runtime_dir: std::env::var("WASM_RUNTIME_DIR")
+                .unwrap_or_else(|_| "/opt/syla/wasm".to_string())
+                .into(),
+            default_languages: std::env::var("WASM_DEFAULT_LANGUAGES")
+                .map(|v| v.split(',').map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+                .unwrap_or_default(),
+            fuel: std::env::var("WASM_FUEL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20_000_000_000),
+            memory_limit: 512 * 1024 * 1024,
+        }
+    }
+}
+
+struct WasmState {
+    wasi: WasiP1Ctx,
+    limits: PeakTrackingLimits,
+}
+
+/// Store limits that also remember the largest memory the guest grew to
+struct PeakTrackingLimits {
+    limits: StoreLimits,
+    peak: usize,
+}
+
+impl ResourceLimiter for PeakTrackingLimits {
+    fn memory_growing(&mut self, current: usize, desired: usize, maximum: Option<usize>) -> Result<bool> {
+        let allowed = self.limits.memory_growing(current, desired, maximum)?;
+        if allowed {
+            self.peak = self.peak.max(desired);
+        }
+        Ok(allowed)
+    }
+
+    fn table_growing(&mut self, current: usize, desired: usize, maximum: Option<usize>) -> Result<bool> {
+        self.limits.table_growing(current, desired, maximum)
+    }
+}
+
+/// Runs code inside WASI builds of language interpreters under wasmtime.
+/// Much cheaper than a container, with fuel and memory caps standing in for
+/// cgroup limits.
+pub struct WasmExecutor {
+    engine: Engine,
+    runtimes: HashMap<String, Module>,
+    config: WasmConfig,
+    output_limits: OutputLimits,
+}
+
+impl WasmExecutor {
+    pub fn new(config: WasmConfig, output_limits: OutputLimits) -> Result<Self> {
+        let mut engine_config = Config::new();
+        engine_config.consume_fuel(true).epoch_interruption(true);
+        let engine = Engine::new(&engine_config)?;
+
+        let runtimes = load_runtimes(&engine, &config.runtime_dir)?;
+        if !runtimes.is_empty() {
+            tracing::info!(
+                "Loaded WASM runtimes: {}",
+                runtimes.keys().cloned().collect::<Vec<_>>().join(", ")
+            );
+        }
+
+        let ticker = engine.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(EPOCH_TICK);
+            ticker.increment_epoch();
+        });
+
+        Ok(Self {
+            engine,
+            runtimes,
+            config,
+            output_limits,
+        })
+    }
+
+    pub fn supports(&self, language: &str) -> bool {
+        self.runtimes.contains_key(language)
+    }
+
+    /// Whether a language runs under wasmtime when the request doesn't say
+    pub fn is_default_for(&self, language: &str) -> bool {
+        self.supports(language) && self.config.default_languages.iter().any(|l| l == language)
+    }
+
+    #[tracing::instrument(name = "wasm_execute", skip(self, code, max_output_bytes))]
+    pub async fn execute(
+        &self,
+        code: &str,
+        language: &str,
+        timeout_seconds: u64,
+        max_output_bytes: Option<u64>,
+    ) -> Result<ExecutionResult> {
+        let module = self
+            .runtimes
+            .get(language)
+            .cloned()
+            .with_context(|| format!("No WASM runtime available for language '{}'", language))?;
+
+        let temp_dir = scratch_dir()?;
+        let file_name = format!("main.{}", source_extension(language));
+        std::fs::write(temp_dir.path().join(&file_name), code)?;
+
+        let engine = self.engine.clone();
+        let fuel = self.config.fuel;
+        let memory_limit = self.config.memory_limit;
+        let output_limit = self.output_limits.resolve(max_output_bytes);
+        let deadline_ticks = (Duration::from_secs(timeout_seconds).as_millis()
+            / EPOCH_TICK.as_millis())
+        .max(1) as u64;
+        let language = language.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let stdout = CappedPipe::new(output_limit);
+            let stderr = CappedPipe::new(output_limit);
+
+            let wasi = WasiCtxBuilder::new()
+                .args(&[language.as_str(), &format!("/workspace/{}", file_name)])
+                .stdout(stdout.clone())
+                .stderr(stderr.clone())
+                .preopened_dir(temp_dir.path(), "/workspace", DirPerms::READ, FilePerms::READ)?
+                .build_p1();
+
+            let mut store = Store::new(
+                &engine,
+                WasmState {
+                    wasi,
+                    limits: PeakTrackingLimits {
+                        limits: StoreLimitsBuilder::new().memory_size(memory_limit).build(),
+                        peak: 0,
+                    },
+                },
+            );
+            store.limiter(|state| &mut state.limits);
+            store.set_fuel(fuel)?;
+            store.set_epoch_deadline(deadline_ticks);
+            store.epoch_deadline_trap();
+
+            let mut linker: Linker<WasmState> = Linker::new(&engine);
+            preview1::add_to_linker_sync(&mut linker, |state| &mut state.wasi)?;
+
+            let start = Instant::now();
+            let outcome = linker
+                .instantiate(&mut store, &module)
+                .and_then(|instance| instance.get_typed_func::<(), ()>(&mut store, "_start"))
+                .and_then(|entry| entry.call(&mut store, ()));
+            let duration_ms = start.elapsed().as_millis() as u64;
+            let peak_memory_bytes = store.data().limits.peak as u64;
+
+            let mut timed_out = false;
+            let (exit_code, diagnostic) = match outcome {
+                Ok(()) => (0, None),
+                Err(e) => match e.downcast_ref::<I32Exit>() {
+                    Some(exit) => (exit.0, None),
+                    None => {
+                        let message = match e.downcast_ref::<Trap>() {
+                            Some(Trap::Interrupt) => {
+                                timed_out = true;
+                                "Execution timed out".to_string()
+                            }
+                            Some(Trap::OutOfFuel) => "Execution exceeded its fuel budget".to_string(),
+                            _ => format!("{:#}", e),
+                        };
+                        (-1, Some(message))
+                    }
+                },
+            };
+
+            let stdout = stdout.take();
+            let stderr = stderr.take();
+            let mut stderr_text = stderr.text();
+            if let Some(message) = diagnostic {
+                stderr_text.push_str(&message);
+            }
+
+            Ok(ExecutionResult {
+                exit_code,
+                stdout: stdout.text(),
+                stderr: stderr_text,
+                duration_ms,
+                timed_out,
+                stdout_bytes: stdout.total_bytes,
+                stderr_bytes: stderr.total_bytes,
+                stdout_truncated: stdout.truncated(),
+                stderr_truncated: stderr.truncated(),
+                cpu_time_ms: None,
+                peak_memory_bytes: Some(peak_memory_bytes),
+                artifacts: HashMap::new(),
+            })
+        })
+        .await?
+    }
+}
+
+fn load_runtimes(engine: &Engine, dir: &Path) -> Result<HashMap<String, Module>> {
+    let mut runtimes = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(runtimes);
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("wasm") {
+            continue;
+        }
+        if let Some(language) = path.file_stem().and_then(|s| s.to_str()) {
+            let module = Module::from_file(engine, &path)
+                .with_context(|| format!("Failed to compile WASM runtime {}", path.display()))?;
+            runtimes.insert(language.to_string(), module);
+        }
+    }
+
+    Ok(runtimes)
+}
+
+fn source_extension(language: &str) -> &'static str {
+    match language {
+        "python" => "py",
+        "javascript" => "js",
+        "ruby" => "rb",
+        _ => "txt",
+    }
+}
+
+/// WASI output stream that keeps the first `limit` bytes and counts the rest,
+/// mirroring how container output is captured.
+#[derive(Clone)]
+struct CappedPipe {
+    limit: usize,
+    output: Arc<Mutex<CapturedOutput>>,
+}
+
+impl CappedPipe {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            output: Arc::new(Mutex::new(CapturedOutput::default())),
+        }
+    }
+
+    fn take(&self) -> CapturedOutput {
+        std::mem::take(&mut *self.output.lock().unwrap())
+    }
+}
+
+impl HostOutputStream for CappedPipe {
+    fn write(&mut self, bytes: Bytes) -> Result<(), StreamError> {
+        let mut output = self.output.lock().unwrap();
+        output.total_bytes += bytes.len() as u64;
+        let room = self.limit.saturating_sub(output.data.len());
+        output.data.extend_from_slice(&bytes[..bytes.len().min(room)]);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), StreamError> {
+        Ok(())
+    }
+
+    fn check_write(&mut self) -> Result<usize, StreamError> {
+        // Overflow is discarded, so the guest can always write
+        Ok(64 * 1024)
+    }
+}
+
+#[wasmtime_wasi::async_trait]
+impl Subscribe for CappedPipe {
+    async fn ready(&mut self) {}
+}
+
+impl StdoutStream for CappedPipe {
+    fn stream(&self) -> Box<dyn HostOutputStream> {
+        Box::new(self.clone())
+    }
+
+    fn isatty(&self) -> bool {
+        false
+    }
+}
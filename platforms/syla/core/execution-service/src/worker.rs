@@ -1,117 +1,563 @@
-use crate::models::{ExecutionJob, ExecutionResult, JobStatus};
+use crate::checks;
+use crate::coverage;
+use crate::docker;
+use crate::evaluation;
+use crate::models::{
+    AttemptRecord, CreateExecutionRequest, DebugInfo, ExecutionJob, ExecutionMode, ExecutionResult, ExecutorBackend,
+    JobStatus, ResourceUsage,
+};
+use crate::queue::{QueuedJob, RedisQueue, WorkerInfo};
+use crate::retry::FailureCategory;
 use crate::state::ServiceState;
+use crate::telemetry;
+use std::future::Future;
 use std::sync::Arc;
-use tracing::{error, info};
+use std::time::{Duration, Instant};
+use tracing::{error, info, info_span, warn, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+
+/// Jobs pending longer than this without an ack or heartbeat are assumed to
+/// belong to a dead worker and are claimed by a live one
+const STALE_JOB_IDLE: Duration = Duration::from_secs(300);
+const CLAIM_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the worker publishes its status to the registry
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often a running job's heartbeat is refreshed
+const JOB_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time limit for jobs that don't set one
+pub const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+/// Longest time limit a job may ask for
+pub const MAX_TIMEOUT_SECONDS: u64 = 300;
+
+/// Consumer name for this worker, from `WORKER_ID` or generated
+pub fn worker_id() -> String {
+    std::env::var("WORKER_ID")
+        .unwrap_or_else(|_| format!("worker-{}", &Uuid::new_v4().simple().to_string()[..8]))
+}
+
+pub async fn run_worker(state: Arc<ServiceState>, queue: Arc<RedisQueue>, consumer: String) {
+    info!("Starting execution worker {}", consumer);
+
+    if let Err(e) = queue.ensure_group().await {
+        error!("Failed to create consumer group: {}", e);
+    }
+
+    let now = chrono::Utc::now();
+    let status = Arc::new(std::sync::Mutex::new(WorkerInfo {
+        id: consumer.clone(),
+        hostname: hostname(),
+        pid: std::process::id(),
+        started_at: now,
+        last_seen: now,
+        current_job: None,
+        jobs_processed: 0,
+        jobs_errored: 0,
+    }));
+    tokio::spawn(report_status(queue.clone(), status.clone()));
+
+    let mut last_claim: Option<Instant> = None;
 
-pub async fn run_worker(state: Arc<ServiceState>) {
-    info!("Starting execution worker");
-    
     loop {
-        // Get job from queue
-        let job_id = {
-            let mut redis = state.redis.lock().await;
-            let result: Result<Option<String>, _> = redis::cmd("LPOP")
-                .arg("execution_queue")
-                .query_async(&mut *redis)
-                .await;
-                
-            match result {
-                Ok(Some(id)) => id,
-                Ok(None) => {
-                    // No jobs, wait a bit
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                    continue;
-                }
-                Err(e) => {
-                    error!("Redis error: {}", e);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                    continue;
+        state.worker_heartbeat.beat();
+
+        // Periodically recover jobs abandoned by crashed workers
+        if last_claim.is_none_or(|t| t.elapsed() >= CLAIM_INTERVAL) {
+            last_claim = Some(Instant::now());
+            match queue.claim_stale(&consumer, STALE_JOB_IDLE, 10).await {
+                Ok(claimed) => {
+                    for queued in claimed {
+                        info!(
+                            "Claimed stale job {} (delivery {})",
+                            queued.job_id, queued.delivery_count
+                        );
+                        handle_job(&state, &queue, &consumer, &status, &queued).await;
+                    }
                 }
+                Err(e) => error!("Failed to claim stale jobs: {}", e),
             }
-        };
-        
-        // Parse job ID
-        let job_id = match job_id.parse::<uuid::Uuid>() {
-            Ok(id) => id,
+        }
+
+        // Get job from queue
+        let queued = match queue.pop_job(&consumer, Duration::from_secs(1)).await {
+            Ok(Some(queued)) => queued,
+            Ok(None) => continue,
             Err(e) => {
-                error!("Invalid job ID: {}", e);
+                error!("Redis error: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
                 continue;
             }
         };
-        
-        // Process job
-        if let Err(e) = process_job(&state, job_id).await {
-            error!("Error processing job {}: {}", job_id, e);
+
+        handle_job(&state, &queue, &consumer, &status, &queued).await;
+    }
+}
+
+/// Process a job and acknowledge it. Failed jobs are acked too, since
+/// processing records the failure on the job itself.
+async fn handle_job(
+    state: &ServiceState,
+    queue: &Arc<RedisQueue>,
+    consumer: &str,
+    status: &std::sync::Mutex<WorkerInfo>,
+    queued: &QueuedJob,
+) {
+    status.lock().unwrap().current_job = Some(queued.job_id);
+
+    let outcome = process_job(state, queue, consumer, queued).await;
+
+    {
+        let mut status = status.lock().unwrap();
+        status.current_job = None;
+        status.jobs_processed += 1;
+        if outcome.is_err() {
+            status.jobs_errored += 1;
+        }
+    }
+    if let Err(e) = outcome {
+        error!("Error processing job {}: {}", queued.job_id, e);
+    }
+
+    if let Err(e) = queue.ack(&queued.entry_id).await {
+        error!("Failed to ack job {}: {}", queued.job_id, e);
+    }
+}
+
+/// Publish this worker's status to the registry until the task is dropped
+async fn report_status(queue: Arc<RedisQueue>, status: Arc<std::sync::Mutex<WorkerInfo>>) {
+    let mut ticker = tokio::time::interval(REPORT_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let snapshot = {
+            let mut status = status.lock().unwrap();
+            status.last_seen = chrono::Utc::now();
+            status.clone()
+        };
+        if let Err(e) = queue.report_worker(&snapshot).await {
+            error!("Failed to report worker status: {}", e);
         }
     }
 }
 
-async fn process_job(state: &ServiceState, job_id: uuid::Uuid) -> anyhow::Result<()> {
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname").map(|h| h.trim().to_string()))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+async fn process_job(
+    state: &ServiceState,
+    queue: &Arc<RedisQueue>,
+    consumer: &str,
+    queued: &QueuedJob,
+) -> anyhow::Result<()> {
+    let job_id = queued.job_id;
     info!("Processing job {}", job_id);
     
     // Get job details
-    let mut job = state.get_execution(job_id).await?;
-    
+    let job = state.get_execution(job_id).await?;
+    if matches!(
+        job.status,
+        JobStatus::Completed | JobStatus::Failed | JobStatus::Timeout
+    ) {
+        // Redelivered after it finished, e.g. when the ack was lost
+        info!("Skipping job {} already finished as {:?}", job_id, job.status);
+        return Ok(());
+    }
+    if matches!(job.status, JobStatus::Running) && heartbeat_is_live(queue, job_id).await? {
+        // Claimed while its worker is still running it
+        info!("Skipping job {} still running on {:?}", job_id, job.worker_id);
+        return Ok(());
+    }
+
+    // Continue the trace of the request that submitted the job
+    let queue_wait_ms = (chrono::Utc::now() - job.created_at).num_milliseconds();
+    let span = info_span!(
+        "execute_job",
+        job_id = %job_id,
+        language = %job.request.language,
+        worker = %consumer,
+        attempt = job.attempts + 1,
+        queue_wait_ms,
+    );
+    span.set_parent(telemetry::context_from(&job.trace_context));
+
+    run_tracked(state, queue, consumer, queued, job)
+        .instrument(span)
+        .await
+}
+
+/// Run a job while keeping its in-flight heartbeat fresh
+async fn run_tracked(
+    state: &ServiceState,
+    queue: &Arc<RedisQueue>,
+    consumer: &str,
+    queued: &QueuedJob,
+    mut job: ExecutionJob,
+) -> anyhow::Result<()> {
+    let job_id = job.id;
+
     // Update status to running
     job.status = JobStatus::Running;
     job.started_at = Some(chrono::Utc::now());
-    update_job(state, &job).await?;
-    
-    // Execute
-    let result = state.docker_executor
-        .execute(
-            &job.request.code,
-            &job.request.language,
-            job.request.timeout_seconds.unwrap_or(30),
+    job.attempts += 1;
+    job.worker_id = Some(consumer.to_string());
+    if job.request.debug {
+        job.debug_info = Some(DebugInfo {
+            worker_id: consumer.to_string(),
+            hostname: hostname(),
+            image: job
+                .request
+                .docker_image()
+                .unwrap_or_else(|| docker::runtime_image(&job.request.language).to_string()),
+            timeout_seconds: job.request.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS),
+            queue_wait_ms: (chrono::Utc::now() - job.created_at).num_milliseconds(),
+            attempt: job.attempts,
+        });
+    }
+    state.update_execution(&job).await?;
+
+    // Heartbeat while running so the orphan reaper can tell a slow job from
+    // one whose worker died
+    queue.track_inflight(job_id, &queued.entry_id).await?;
+    let heartbeat_queue = queue.clone();
+    let heartbeat_consumer = consumer.to_string();
+    let entry_id = queued.entry_id.clone();
+    let heartbeat = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(JOB_HEARTBEAT_INTERVAL);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match heartbeat_queue.heartbeat(job_id, &heartbeat_consumer, &entry_id).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    warn!("Job {} was recovered as orphaned while still running", job_id);
+                    break;
+                }
+                Err(e) => error!("Failed to send heartbeat for job {}: {}", job_id, e),
+            }
+        }
+    });
+
+    let started = Instant::now();
+    let outcome = run_job(state, &mut job).await;
+    heartbeat.abort();
+    if let Err(e) = queue.record_duration(started.elapsed()).await {
+        error!("Failed to record duration of job {}: {}", job_id, e);
+    }
+    if let Err(e) = queue.untrack_inflight(job_id).await {
+        error!("Failed to clear in-flight state for job {}: {}", job_id, e);
+    }
+    outcome
+}
+
+/// Whether a running job has sent a heartbeat within `STALE_JOB_IDLE`
+async fn heartbeat_is_live(queue: &RedisQueue, job_id: Uuid) -> anyhow::Result<bool> {
+    let now = chrono::Utc::now().timestamp_millis();
+    Ok(queue
+        .last_heartbeat(job_id)
+        .await?
+        .is_some_and(|beat| now - beat < STALE_JOB_IDLE.as_millis() as i64))
+}
+
+async fn run_job(state: &ServiceState, job: &mut ExecutionJob) -> anyhow::Result<()> {
+    let job_id = job.id;
+
+    // Custom images are pulled on first use; the allowlist is checked again
+    // in case it changed since the job was queued
+    if let Some(image) = job.request.image.clone() {
+        let ready = match state.images.check_image(&image) {
+            Ok(()) => state.images.ensure_pulled(&image).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = ready {
+            job.status = JobStatus::Failed;
+            job.result = Some(error_result(&e));
+            complete(state, job).await?;
+
+            warn!("Job {} could not use image {}: {}", job_id, image, e);
+            return Ok(());
+        }
+    }
+
+    // Checked again in case the version was retired since the job was queued
+    if let Some(version) = &job.request.runtime_version {
+        if docker::versioned_image(&job.request.language, version).is_none() {
+            let e = anyhow::anyhow!("{} {} is no longer supported", job.request.language, version);
+            job.status = JobStatus::Failed;
+            job.result = Some(error_result(&e));
+            complete(state, job).await?;
+
+            warn!("Job {} could not run: {}", job_id, e);
+            return Ok(());
+        }
+    }
+
+    let request = job.request.clone();
+    if request.mode != ExecutionMode::Run {
+        let outcome = with_retries(
+            state,
+            job,
+            || checks::run_check(&state.docker_executor, &request),
+            |(result, _)| result,
+        )
+        .await;
+        match outcome {
+            Ok((exec_result, diagnostics)) => {
+                job.status = final_status(&exec_result);
+                job.result = Some(execution_result(exec_result, docker_runtime_version(&job.request)));
+                job.diagnostics = Some(diagnostics);
+            }
+            Err(e) => {
+                job.status = JobStatus::Failed;
+                job.result = Some(error_result(&e));
+            }
+        }
+        complete(state, job).await?;
+
+        info!("Job {} checked with status {:?}", job_id, job.status);
+        return Ok(());
+    }
+
+    if let Some(options) = &request.coverage {
+        let outcome = with_retries(
+            state,
+            job,
+            || coverage::run_with_coverage(&state.docker_executor, &request, options),
+            |(result, _)| result,
         )
         .await;
+        match outcome {
+            Ok((exec_result, report)) => {
+                job.status = final_status(&exec_result);
+                job.result = Some(execution_result(exec_result, docker_runtime_version(&job.request)));
+                job.coverage = report;
+            }
+            Err(e) => {
+                job.status = JobStatus::Failed;
+                job.result = Some(error_result(&e));
+            }
+        }
+        complete(state, job).await?;
+
+        info!("Job {} ran under coverage with status {:?}", job_id, job.status);
+        return Ok(());
+    }
+
+    if let Some(spec) = job.request.tests.clone() {
+        let report = evaluation::run_tests(&state.docker_executor, &job.request, &spec).await;
+        job.status = if report.all_passed() {
+            JobStatus::Completed
+        } else {
+            JobStatus::Failed
+        };
+        job.test_report = Some(report);
+        complete(state, job).await?;
+
+        info!("Job {} evaluated with status {:?}", job_id, job.status);
+        return Ok(());
+    }
+    
+    // Execute on the requested backend, falling back to the language default;
+    // custom images and pinned versions always run under Docker
+    let backend = job.request.backend.unwrap_or_else(|| {
+        if job.request.image.is_some() || job.request.runtime_version.is_some() {
+            ExecutorBackend::Docker
+        } else if state.wasm_executor.is_default_for(&job.request.language) {
+            ExecutorBackend::Wasm
+        } else if state.sandbox_executor.is_default_for(&job.request.language) {
+            ExecutorBackend::Sandbox
+        } else {
+            ExecutorBackend::Docker
+        }
+    });
+    let result = with_retries(state, job, || execute(state, &request, backend), |result| result).await;
     
     // Update job with result
+    let runtime_version = match backend {
+        ExecutorBackend::Docker => docker_runtime_version(&job.request),
+        ExecutorBackend::Wasm | ExecutorBackend::Sandbox => None,
+    };
     match result {
         Ok(exec_result) => {
-            job.status = if exec_result.timed_out {
-                JobStatus::Timeout
-            } else if exec_result.exit_code == 0 {
-                JobStatus::Completed
-            } else {
-                JobStatus::Failed
-            };
-            
-            job.result = Some(ExecutionResult {
-                exit_code: exec_result.exit_code,
-                stdout: exec_result.stdout,
-                stderr: exec_result.stderr,
-                duration_ms: exec_result.duration_ms,
-            });
+            job.status = final_status(&exec_result);
+            job.result = Some(execution_result(exec_result, runtime_version));
         }
         Err(e) => {
             job.status = JobStatus::Failed;
-            job.result = Some(ExecutionResult {
-                exit_code: -1,
-                stdout: String::new(),
-                stderr: format!("Execution error: {}", e),
-                duration_ms: 0,
-            });
+            job.result = Some(error_result(&e));
         }
     }
     
-    job.completed_at = Some(chrono::Utc::now());
-    update_job(state, &job).await?;
+    complete(state, job).await?;
     
     info!("Job {} completed with status {:?}", job_id, job.status);
     Ok(())
 }
 
-async fn update_job(state: &ServiceState, job: &ExecutionJob) -> anyhow::Result<()> {
-    let mut redis = state.redis.lock().await;
-    let job_key = format!("job:{}", job.id);
-    let job_json = serde_json::to_string(job)?;
-    
-    redis::cmd("SET")
-        .arg(&job_key)
-        .arg(&job_json)
-        .query_async::<_, ()>(&mut *redis)
-        .await?;
-        
+async fn execute(
+    state: &ServiceState,
+    request: &CreateExecutionRequest,
+    backend: ExecutorBackend,
+) -> anyhow::Result<docker::ExecutionResult> {
+    let timeout_seconds = request.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS);
+    match backend {
+        ExecutorBackend::Docker => {
+            state.docker_executor
+                .execute_with_input(
+                    &request.code,
+                    &request.language,
+                    timeout_seconds,
+                    request.max_output_bytes,
+                    docker::ExecutionInput {
+                        image: request.docker_image(),
+                        ..Default::default()
+                    },
+                )
+                .await
+        }
+        ExecutorBackend::Wasm => {
+            state.wasm_executor
+                .execute(&request.code, &request.language, timeout_seconds, request.max_output_bytes)
+                .await
+        }
+        ExecutorBackend::Sandbox => {
+            state.sandbox_executor
+                .execute(&request.code, &request.language, timeout_seconds, request.max_output_bytes)
+                .await
+        }
+    }
+}
+
+/// Runs `run` again while it fails in a way the job's retry policy allows,
+/// recording each attempt on the job. `result` picks the program's outcome
+/// out of what `run` returns.
+async fn with_retries<T, Fut>(
+    state: &ServiceState,
+    job: &mut ExecutionJob,
+    mut run: impl FnMut() -> Fut,
+    result: impl Fn(&T) -> &docker::ExecutionResult,
+) -> anyhow::Result<T>
+where
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let policy = state.retry.for_request(job.request.retry.as_ref());
+    loop {
+        let started_at = chrono::Utc::now();
+        let started = Instant::now();
+        let outcome = run().await;
+        let failure = match &outcome {
+            Ok(value) => FailureCategory::of(result(value)),
+            Err(_) => Some(FailureCategory::Infrastructure),
+        };
+        let retry_after = failure.and_then(|failure| policy.retry_after(job.attempts, failure));
+        job.attempt_history.push(AttemptRecord {
+            attempt: job.attempts,
+            worker_id: job.worker_id.clone().unwrap_or_default(),
+            started_at,
+            duration_ms: started.elapsed().as_millis() as u64,
+            failure,
+            error: outcome.as_ref().err().map(|e| e.to_string()),
+            retried_after_ms: retry_after.map(|delay| delay.as_millis() as u64),
+        });
+        let (Some(failure), Some(delay)) = (failure, retry_after) else {
+            return outcome;
+        };
+
+        warn!(
+            "Job {} attempt {} of {} failed ({:?}); retrying in {}ms",
+            job.id,
+            job.attempts,
+            policy.max_attempts,
+            failure,
+            delay.as_millis()
+        );
+        // Keep the history visible while waiting
+        job.attempts += 1;
+        state.update_execution(job).await?;
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Record a finished job, moving oversized output and reports to object
+/// storage first
+async fn complete(state: &ServiceState, job: &mut ExecutionJob) -> anyhow::Result<()> {
+    let job_id = job.id;
+    if let Some(offload) = &state.log_offload {
+        if let Some(result) = job.result.as_mut() {
+            offload.offload(job_id, result).await;
+        }
+        if let Some(report) = job.coverage.as_mut() {
+            offload.offload_lcov(job_id, report).await;
+        }
+    }
+    job.completed_at = Some(chrono::Utc::now());
+    state.update_execution(job).await?;
+    if let Err(e) = state.record_usage(job).await {
+        error!("Failed to record usage of job {}: {}", job_id, e);
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn final_status(result: &docker::ExecutionResult) -> JobStatus {
+    if result.timed_out {
+        JobStatus::Timeout
+    } else if result.exit_code == 0 {
+        JobStatus::Completed
+    } else {
+        JobStatus::Failed
+    }
+}
+
+/// Runtime version a Docker run used; unknown for custom images
+fn docker_runtime_version(request: &CreateExecutionRequest) -> Option<String> {
+    if request.image.is_some() {
+        return None;
+    }
+    request
+        .runtime_version
+        .clone()
+        .or_else(|| docker::default_runtime_version(&request.language).map(String::from))
+}
+
+fn execution_result(result: docker::ExecutionResult, runtime_version: Option<String>) -> ExecutionResult {
+    ExecutionResult {
+        exit_code: result.exit_code,
+        stdout: result.stdout,
+        stderr: result.stderr,
+        duration_ms: result.duration_ms,
+        stdout_bytes: result.stdout_bytes,
+        stderr_bytes: result.stderr_bytes,
+        stdout_truncated: result.stdout_truncated,
+        stderr_truncated: result.stderr_truncated,
+        resource_usage: Some(ResourceUsage {
+            wall_time_ms: result.duration_ms,
+            cpu_time_ms: result.cpu_time_ms,
+            peak_memory_bytes: result.peak_memory_bytes,
+        }),
+        stdout_object: None,
+        stderr_object: None,
+        runtime_version,
+    }
+}
+
+fn error_result(error: &anyhow::Error) -> ExecutionResult {
+    let stderr = format!("Execution error: {}", error);
+    ExecutionResult {
+        exit_code: -1,
+        stdout: String::new(),
+        stderr_bytes: stderr.len() as u64,
+        stderr,
+        duration_ms: 0,
+        stdout_bytes: 0,
+        stdout_truncated: false,
+        stderr_truncated: false,
+        resource_usage: None,
+        stdout_object: None,
+        stderr_object: None,
+        runtime_version: None,
+    }
+}
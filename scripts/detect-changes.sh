@@ -58,13 +58,14 @@ has_changed() {
         
         # Check if any source files are newer than the build
         newest_src=$(find "$service/src" -type f -name "*.rs" -printf '%T@\n' 2>/dev/null | sort -rn | head -1 || echo "0")
-        if [ -f "$service/target/release/$(basename $service)" ]; then
-            build_time=$(stat -c %Y "$service/target/release/$(basename $service)" 2>/dev/null || echo "0")
+        local binary="$service/target/${PROFILE:-release}/$(basename $service)"
+        if [ -f "$binary" ]; then
+            build_time=$(stat -c %Y "$binary" 2>/dev/null || echo "0")
             if [ "${newest_src%.*}" -gt "$build_time" ]; then
                 return 0
             fi
         else
-            return 0  # No release build
+            return 0  # No build for this profile
         fi
     fi
     